@@ -0,0 +1,25 @@
+use crate::render::Renderer;
+use crate::Message;
+
+use iced::theme;
+use iced::widget::{button, column, container, text};
+use iced::{Element, Length};
+
+pub fn view<'a>(renderer: &Renderer) -> Element<'a, Message> {
+    container(
+        column![
+            text("Bonkitor").size(16),
+            text(format!("Version {}", env!("CARGO_PKG_VERSION"))).size(14),
+            text(format!("Renderer: {}", renderer.describe())).size(14),
+            text("Start with --software-render if the window stays blank.").size(13),
+            button(text("Close").size(14))
+                .on_press(Message::ClosePanel)
+                .style(theme::Button::Secondary),
+        ]
+        .spacing(10),
+    )
+    .width(Length::Fill)
+    .padding(10)
+    .style(theme::Container::Box)
+    .into()
+}
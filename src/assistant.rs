@@ -0,0 +1,304 @@
+use crate::{Error, Message};
+
+use iced::futures::{SinkExt, StreamExt};
+use iced::theme;
+use iced::widget::{button, column, container, row, scrollable, text, text_input};
+use iced::{subscription, Element, Length, Subscription};
+use serde::Deserialize;
+
+const KEYRING_SERVICE: &str = "bonkitor-assistant";
+pub const DEFAULT_MODEL: &str = "gpt-4o-mini";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Prompt {
+    Explain,
+    Refactor,
+    FixGrammar,
+}
+
+impl Prompt {
+    pub const ALL: [Prompt; 3] = [Prompt::Explain, Prompt::Refactor, Prompt::FixGrammar];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Prompt::Explain => "Explain",
+            Prompt::Refactor => "Refactor",
+            Prompt::FixGrammar => "Fix grammar",
+        }
+    }
+
+    fn instruction(self) -> &'static str {
+        match self {
+            Prompt::Explain => "Explain what the following text or code does.",
+            Prompt::Refactor => {
+                "Refactor the following code for clarity. Reply with only the rewritten code."
+            }
+            Prompt::FixGrammar => {
+                "Fix spelling and grammar in the following text. Reply with only the corrected text."
+            }
+        }
+    }
+
+    // Only rewrites make sense to put back into the buffer
+    fn is_edit(self) -> bool {
+        !matches!(self, Prompt::Explain)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub id: usize,
+    pub prompt: Prompt,
+    pub input: String,
+    pub from_selection: bool,
+    /// The tab the input came from, the only one a rewrite may go back into
+    pub tab: usize,
+    pub endpoint: String,
+    pub model: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    Delta(String),
+    Finished,
+    Failed(String),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Assistant {
+    pub request: Option<Request>,
+    pub response: String,
+    pub is_streaming: bool,
+    pub key: String,
+    next: usize,
+}
+
+impl Assistant {
+    pub fn ask(&mut self, mut request: Request) {
+        self.next += 1;
+        request.id = self.next;
+
+        self.request = Some(request);
+        self.response.clear();
+        self.is_streaming = true;
+    }
+
+    pub fn subscription(&self) -> Subscription<Event> {
+        match self.request.as_ref().filter(|_| self.is_streaming) {
+            Some(request) => stream(request.clone()),
+            None => Subscription::none(),
+        }
+    }
+
+    /// The response as it should replace the input, without a surrounding code fence.
+    pub fn edit(&self) -> Option<String> {
+        let request = self.request.as_ref()?;
+
+        if !request.prompt.is_edit() || self.is_streaming || self.response.is_empty() {
+            return None;
+        }
+
+        let response = self.response.trim();
+
+        let unfenced = response
+            .strip_prefix("```")
+            .and_then(|rest| rest.strip_suffix("```"))
+            .and_then(|body| body.split_once('\n'))
+            .map(|(_language, body)| body.trim_end_matches('\n'));
+
+        Some(unfenced.unwrap_or(response).to_string())
+    }
+
+    /// With `reduce_motion`, the response shows once it is complete rather than as it streams.
+    pub fn view(
+        &self,
+        has_endpoint: bool,
+        can_apply: bool,
+        reduce_motion: bool,
+    ) -> Element<'_, Message> {
+        let prompts = Prompt::ALL
+            .into_iter()
+            .fold(row![].spacing(5), |row, prompt| {
+                row.push(
+                    button(text(prompt.label()).size(14))
+                        .on_press_maybe(has_endpoint.then_some(Message::AskAssistant(prompt))),
+                )
+            });
+
+        let status = if !has_endpoint {
+            "Set assistant_endpoint in the config to an OpenAI-compatible API"
+        } else if self.is_streaming {
+            "Waiting for the response..."
+        } else {
+            ""
+        };
+
+        let response = if self.is_streaming && reduce_motion {
+            ""
+        } else {
+            &self.response
+        };
+
+        let mut actions = row![].spacing(10);
+
+        if self.edit().is_some() {
+            actions = actions.push(
+                button(text("Apply as edit").size(14))
+                    .on_press_maybe(can_apply.then_some(Message::ApplyAssistant)),
+            );
+        }
+
+        actions = actions.push(
+            button(text("Close").size(14))
+                .on_press(Message::ClosePanel)
+                .style(theme::Button::Secondary),
+        );
+
+        container(
+            column![
+                text("Assistant").size(16),
+                prompts,
+                text(status).size(13),
+                scrollable(text(response).size(14)).height(Length::Fill),
+                row![
+                    text_input("API key", &self.key)
+                        .on_input(Message::AssistantKeyChanged)
+                        .on_submit(Message::SaveAssistantKey)
+                        .password()
+                        .size(14)
+                        .padding(5),
+                    button(text("Save key").size(14)).on_press_maybe(
+                        (!self.key.is_empty()).then_some(Message::SaveAssistantKey)
+                    ),
+                ]
+                .spacing(5),
+                actions,
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fill)
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
+
+/// Stores the key for an endpoint in the OS keychain.
+pub async fn save_key(endpoint: String, key: String) -> Result<(), Error> {
+    tokio::task::spawn_blocking(move || {
+        keyring::Entry::new(KEYRING_SERVICE, &endpoint)
+            .and_then(|entry| entry.set_password(&key))
+            .map_err(|error| Error::RemoteFailed(error.to_string()))
+    })
+    .await
+    .map_err(|error| Error::RemoteFailed(error.to_string()))?
+}
+
+/// Reads the key saved for an endpoint, if any.
+pub async fn load_key(endpoint: String) -> Option<String> {
+    tokio::task::spawn_blocking(move || {
+        keyring::Entry::new(KEYRING_SERVICE, &endpoint)
+            .and_then(|entry| entry.get_password())
+            .ok()
+    })
+    .await
+    .ok()
+    .flatten()
+}
+
+#[derive(Deserialize)]
+struct Chunk {
+    choices: Vec<Choice>,
+}
+
+#[derive(Deserialize)]
+struct Choice {
+    delta: Delta,
+}
+
+#[derive(Deserialize)]
+struct Delta {
+    content: Option<String>,
+}
+
+fn stream(request: Request) -> Subscription<Event> {
+    subscription::channel(
+        ("assistant", request.id),
+        100,
+        move |mut output| async move {
+            let event = match send(&request, &mut output).await {
+                Ok(()) => Event::Finished,
+                Err(error) => Event::Failed(error),
+            };
+
+            let _ = output.send(event).await;
+
+            loop {
+                std::future::pending::<()>().await;
+            }
+        },
+    )
+}
+
+async fn send(
+    request: &Request,
+    output: &mut iced::futures::channel::mpsc::Sender<Event>,
+) -> Result<(), String> {
+    let body = serde_json::json!({
+        "model": request.model,
+        "stream": true,
+        "messages": [
+            { "role": "system", "content": request.prompt.instruction() },
+            { "role": "user", "content": request.input },
+        ],
+    });
+
+    let url = format!(
+        "{}/chat/completions",
+        request.endpoint.trim_end_matches('/')
+    );
+    let mut builder = reqwest::Client::new().post(url).json(&body);
+
+    // Local servers usually need no key
+    if let Some(key) = load_key(request.endpoint.clone()).await {
+        builder = builder.bearer_auth(key);
+    }
+
+    let response = builder
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|error| error.to_string())?;
+
+    let mut bytes = response.bytes_stream();
+    let mut pending = String::new();
+
+    while let Some(chunk) = bytes.next().await {
+        let chunk = chunk.map_err(|error| error.to_string())?;
+        pending.push_str(&String::from_utf8_lossy(&chunk));
+
+        // Server-sent events arrive one `data:` line at a time
+        while let Some(end) = pending.find('\n') {
+            let line: String = pending.drain(..=end).collect();
+
+            let Some(data) = line.trim().strip_prefix("data:") else {
+                continue;
+            };
+
+            if data.trim() == "[DONE]" {
+                return Ok(());
+            }
+
+            let delta = serde_json::from_str::<Chunk>(data.trim())
+                .ok()
+                .and_then(|chunk| chunk.choices.into_iter().next())
+                .and_then(|choice| choice.delta.content);
+
+            if let Some(delta) = delta {
+                let _ = output.send(Event::Delta(delta)).await;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,196 @@
+use crate::Message;
+
+use iced::advanced::layout::{self, Layout};
+use iced::advanced::renderer::{self, Quad};
+use iced::advanced::widget::{Operation, Tree, Widget};
+use iced::advanced::{self, overlay, Clipboard, Shell};
+use iced::application;
+use iced::event::{self, Event};
+use iced::mouse;
+use iced::widget::image;
+use iced::{Color, Element, Length, Rectangle, Size, Theme};
+
+/// Makes the window's own background see-through, leaving panels and bars as they are.
+pub struct Translucent(pub f32);
+
+impl application::StyleSheet for Translucent {
+    type Style = Theme;
+
+    fn appearance(&self, style: &Self::Style) -> application::Appearance {
+        let palette = style.palette();
+
+        application::Appearance {
+            background_color: Color {
+                a: self.0,
+                ..palette.background
+            },
+            text_color: palette.text,
+        }
+    }
+}
+
+/// Draws `content` over an image that covers its bounds, faded toward the theme's background.
+pub fn view<'a>(
+    content: impl Into<Element<'a, Message>>,
+    image: image::Handle,
+    dim: f32,
+) -> Element<'a, Message> {
+    Element::new(Backdrop {
+        content: content.into(),
+        image,
+        dim,
+    })
+}
+
+struct Backdrop<'a> {
+    content: Element<'a, Message>,
+    image: image::Handle,
+    dim: f32,
+}
+
+impl<'a> Widget<Message, iced::Renderer> for Backdrop<'a> {
+    fn width(&self) -> Length {
+        self.content.as_widget().width()
+    }
+
+    fn height(&self) -> Length {
+        self.content.as_widget().height()
+    }
+
+    fn children(&self) -> Vec<Tree> {
+        vec![Tree::new(&self.content)]
+    }
+
+    fn diff(&self, tree: &mut Tree) {
+        tree.diff_children(std::slice::from_ref(&self.content));
+    }
+
+    fn layout(
+        &self,
+        tree: &mut Tree,
+        renderer: &iced::Renderer,
+        limits: &layout::Limits,
+    ) -> layout::Node {
+        self.content
+            .as_widget()
+            .layout(&mut tree.children[0], renderer, limits)
+    }
+
+    fn operate(
+        &self,
+        tree: &mut Tree,
+        layout: Layout<'_>,
+        renderer: &iced::Renderer,
+        operation: &mut dyn Operation<Message>,
+    ) {
+        self.content
+            .as_widget()
+            .operate(&mut tree.children[0], layout, renderer, operation);
+    }
+
+    fn on_event(
+        &mut self,
+        tree: &mut Tree,
+        event: Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        renderer: &iced::Renderer,
+        clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+        viewport: &Rectangle,
+    ) -> event::Status {
+        self.content.as_widget_mut().on_event(
+            &mut tree.children[0],
+            event,
+            layout,
+            cursor,
+            renderer,
+            clipboard,
+            shell,
+            viewport,
+        )
+    }
+
+    fn mouse_interaction(
+        &self,
+        tree: &Tree,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+        renderer: &iced::Renderer,
+    ) -> mouse::Interaction {
+        self.content.as_widget().mouse_interaction(
+            &tree.children[0],
+            layout,
+            cursor,
+            viewport,
+            renderer,
+        )
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut iced::Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+    ) {
+        let bounds = layout.bounds();
+        let size = advanced::image::Renderer::dimensions(renderer, &self.image);
+
+        // Scaled to cover the bounds, cropping whichever side overflows
+        if size.width > 0 && size.height > 0 {
+            let scale = (bounds.width / size.width as f32).max(bounds.height / size.height as f32);
+            let covered = Size::new(size.width as f32 * scale, size.height as f32 * scale);
+
+            let image = Rectangle {
+                x: bounds.x + (bounds.width - covered.width) / 2.0,
+                y: bounds.y + (bounds.height - covered.height) / 2.0,
+                width: covered.width,
+                height: covered.height,
+            };
+
+            advanced::Renderer::with_layer(renderer, bounds, |renderer| {
+                advanced::image::Renderer::draw(renderer, self.image.clone(), image);
+            });
+        }
+
+        advanced::Renderer::fill_quad(
+            renderer,
+            Quad {
+                bounds,
+                border_radius: 0.0.into(),
+                border_width: 0.0,
+                border_color: Color::TRANSPARENT,
+            },
+            Color {
+                a: self.dim,
+                ..theme.palette().background
+            },
+        );
+
+        self.content.as_widget().draw(
+            &tree.children[0],
+            renderer,
+            theme,
+            style,
+            layout,
+            cursor,
+            viewport,
+        );
+    }
+
+    fn overlay<'b>(
+        &'b mut self,
+        tree: &'b mut Tree,
+        layout: Layout<'_>,
+        renderer: &iced::Renderer,
+    ) -> Option<overlay::Element<'b, Message, iced::Renderer>> {
+        self.content
+            .as_widget_mut()
+            .overlay(&mut tree.children[0], layout, renderer)
+    }
+}
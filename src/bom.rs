@@ -0,0 +1,90 @@
+use std::io;
+
+const UTF8: &[u8] = &[0xEF, 0xBB, 0xBF];
+const UTF16_LE: &[u8] = &[0xFF, 0xFE];
+const UTF16_BE: &[u8] = &[0xFE, 0xFF];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bom {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl Bom {
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(UTF8) {
+            Some(Bom::Utf8)
+        } else if bytes.starts_with(UTF16_LE) {
+            Some(Bom::Utf16Le)
+        } else if bytes.starts_with(UTF16_BE) {
+            Some(Bom::Utf16Be)
+        } else {
+            None
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Bom::Utf8 => "UTF-8 BOM",
+            Bom::Utf16Le => "UTF-16 LE BOM",
+            Bom::Utf16Be => "UTF-16 BE BOM",
+        }
+    }
+
+    fn bytes(self) -> &'static [u8] {
+        match self {
+            Bom::Utf8 => UTF8,
+            Bom::Utf16Le => UTF16_LE,
+            Bom::Utf16Be => UTF16_BE,
+        }
+    }
+}
+
+pub fn decode(bytes: Vec<u8>) -> io::Result<(String, Option<Bom>)> {
+    let invalid = || io::Error::from(io::ErrorKind::InvalidData);
+
+    let Some(bom) = Bom::detect(&bytes) else {
+        return String::from_utf8(bytes)
+            .map(|text| (text, None))
+            .map_err(|_| invalid());
+    };
+
+    let body = &bytes[bom.bytes().len()..];
+
+    let text = match bom {
+        Bom::Utf8 => std::str::from_utf8(body).map_err(|_| invalid())?.to_owned(),
+        Bom::Utf16Le | Bom::Utf16Be => {
+            if body.len() % 2 != 0 {
+                return Err(invalid());
+            }
+
+            let units = body.chunks_exact(2).map(|pair| match bom {
+                Bom::Utf16Le => u16::from_le_bytes([pair[0], pair[1]]),
+                _ => u16::from_be_bytes([pair[0], pair[1]]),
+            });
+
+            char::decode_utf16(units)
+                .collect::<Result<String, _>>()
+                .map_err(|_| invalid())?
+        }
+    };
+
+    Ok((text, Some(bom)))
+}
+
+pub fn encode(text: String, bom: Option<Bom>) -> Vec<u8> {
+    let Some(bom) = bom else {
+        return text.into_bytes();
+    };
+
+    let mut bytes = bom.bytes().to_vec();
+
+    match bom {
+        Bom::Utf8 => bytes.extend_from_slice(text.as_bytes()),
+        Bom::Utf16Le => bytes.extend(text.encode_utf16().flat_map(u16::to_le_bytes)),
+        Bom::Utf16Be => bytes.extend(text.encode_utf16().flat_map(u16::to_be_bytes)),
+    }
+
+    bytes
+}
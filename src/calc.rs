@@ -0,0 +1,698 @@
+use crate::{widgets, Message};
+
+use iced::theme;
+use iced::widget::{button, column, container, row, scrollable, text, text_editor};
+use iced::{Color, Element, Length};
+use std::collections::BTreeMap;
+
+const LINE_HEIGHT: f32 = 18.0;
+const RESULT_WIDTH: f32 = 220.0;
+const FADED: Color = Color::from_rgb(0.55, 0.55, 0.55);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dimension {
+    Length,
+    Mass,
+    Time,
+    Data,
+}
+
+#[derive(Debug, PartialEq)]
+struct Unit {
+    /// The first name is the one results are written with
+    names: &'static [&'static str],
+    dimension: Dimension,
+    /// How many of the dimension's base unit one of these is
+    factor: f64,
+}
+
+const UNITS: &[Unit] = &[
+    Unit {
+        names: &["mm", "millimeters"],
+        dimension: Dimension::Length,
+        factor: 0.001,
+    },
+    Unit {
+        names: &["cm", "centimeters"],
+        dimension: Dimension::Length,
+        factor: 0.01,
+    },
+    Unit {
+        names: &["m", "meters"],
+        dimension: Dimension::Length,
+        factor: 1.0,
+    },
+    Unit {
+        names: &["km", "kilometers"],
+        dimension: Dimension::Length,
+        factor: 1000.0,
+    },
+    Unit {
+        names: &["in", "inches"],
+        dimension: Dimension::Length,
+        factor: 0.0254,
+    },
+    Unit {
+        names: &["ft", "feet"],
+        dimension: Dimension::Length,
+        factor: 0.3048,
+    },
+    Unit {
+        names: &["yd", "yards"],
+        dimension: Dimension::Length,
+        factor: 0.9144,
+    },
+    Unit {
+        names: &["mi", "miles"],
+        dimension: Dimension::Length,
+        factor: 1609.344,
+    },
+    Unit {
+        names: &["mg"],
+        dimension: Dimension::Mass,
+        factor: 0.001,
+    },
+    Unit {
+        names: &["g", "grams"],
+        dimension: Dimension::Mass,
+        factor: 1.0,
+    },
+    Unit {
+        names: &["kg", "kilograms"],
+        dimension: Dimension::Mass,
+        factor: 1000.0,
+    },
+    Unit {
+        names: &["oz", "ounces"],
+        dimension: Dimension::Mass,
+        factor: 28.349_523_125,
+    },
+    Unit {
+        names: &["lb", "lbs", "pounds"],
+        dimension: Dimension::Mass,
+        factor: 453.592_37,
+    },
+    Unit {
+        names: &["ms"],
+        dimension: Dimension::Time,
+        factor: 0.001,
+    },
+    Unit {
+        names: &["s", "sec", "seconds"],
+        dimension: Dimension::Time,
+        factor: 1.0,
+    },
+    Unit {
+        names: &["min", "minutes"],
+        dimension: Dimension::Time,
+        factor: 60.0,
+    },
+    Unit {
+        names: &["h", "hours"],
+        dimension: Dimension::Time,
+        factor: 3600.0,
+    },
+    Unit {
+        names: &["days", "day"],
+        dimension: Dimension::Time,
+        factor: 86_400.0,
+    },
+    Unit {
+        names: &["B", "bytes"],
+        dimension: Dimension::Data,
+        factor: 1.0,
+    },
+    Unit {
+        names: &["KB"],
+        dimension: Dimension::Data,
+        factor: 1e3,
+    },
+    Unit {
+        names: &["MB"],
+        dimension: Dimension::Data,
+        factor: 1e6,
+    },
+    Unit {
+        names: &["GB"],
+        dimension: Dimension::Data,
+        factor: 1e9,
+    },
+    Unit {
+        names: &["TB"],
+        dimension: Dimension::Data,
+        factor: 1e12,
+    },
+    Unit {
+        names: &["KiB"],
+        dimension: Dimension::Data,
+        factor: 1024.0,
+    },
+    Unit {
+        names: &["MiB"],
+        dimension: Dimension::Data,
+        factor: 1_048_576.0,
+    },
+    Unit {
+        names: &["GiB"],
+        dimension: Dimension::Data,
+        factor: 1_073_741_824.0,
+    },
+    Unit {
+        names: &["TiB"],
+        dimension: Dimension::Data,
+        factor: 1_099_511_627_776.0,
+    },
+];
+
+fn unit(name: &str) -> Option<&'static Unit> {
+    UNITS.iter().find(|unit| unit.names.contains(&name))
+}
+
+/// An amount in its dimension's base unit, and the unit to write it in.
+#[derive(Debug, Clone, Copy)]
+struct Value {
+    amount: f64,
+    unit: Option<&'static Unit>,
+}
+
+impl Value {
+    fn number(amount: f64) -> Self {
+        Self { amount, unit: None }
+    }
+
+    fn shown(&self) -> f64 {
+        self.amount / self.unit.map_or(1.0, |unit| unit.factor)
+    }
+
+    fn written(self) -> Result<String, String> {
+        let shown = self.shown();
+
+        if !shown.is_finite() {
+            return Err(String::from("the result is not a number"));
+        }
+
+        Ok(match self.unit {
+            Some(unit) => format!("{} {}", format_number(shown), unit.names[0]),
+            None => format_number(shown),
+        })
+    }
+}
+
+pub fn format_number(number: f64) -> String {
+    if number != 0.0 && !(1e-6..1e15).contains(&number.abs()) {
+        return format!("{number:e}");
+    }
+
+    let fixed = format!("{number:.10}");
+    let trimmed = fixed.trim_end_matches('0').trim_end_matches('.');
+
+    match trimmed {
+        "-0" => String::from("0"),
+        trimmed => trimmed.to_string(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Name(String),
+    Symbol(char),
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.chars().peekable();
+
+    while let Some(&character) = chars.peek() {
+        if character.is_whitespace() {
+            chars.next();
+        } else if character.is_ascii_digit() || character == '.' {
+            let mut number = String::new();
+
+            while let Some(&character) = chars.peek() {
+                if character.is_ascii_digit() || character == '.' {
+                    number.push(character);
+                } else if character != '_' && character != ',' {
+                    break;
+                }
+
+                chars.next();
+            }
+
+            // `e` starts an exponent only when digits follow it
+            let mut lookahead = chars.clone();
+
+            if matches!(lookahead.next(), Some('e' | 'E')) {
+                let sign = lookahead.next_if(|next| matches!(next, '+' | '-'));
+
+                if lookahead.peek().is_some_and(char::is_ascii_digit) {
+                    number.push('e');
+                    number.extend(sign);
+                    chars = lookahead;
+
+                    while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                        number.push(digit);
+                    }
+                }
+            }
+
+            let number = number
+                .parse()
+                .map_err(|_| format!("{number} is not a number"))?;
+
+            tokens.push(Token::Number(number));
+        } else if character.is_alphabetic() || character == '_' {
+            let mut name = String::new();
+
+            while let Some(character) = chars.next_if(|next| next.is_alphanumeric() || *next == '_')
+            {
+                name.push(character);
+            }
+
+            tokens.push(Token::Name(name));
+        } else if "+-*/%^()=×÷".contains(character) {
+            let symbol = match character {
+                '×' => '*',
+                '÷' => '/',
+                symbol => symbol,
+            };
+
+            tokens.push(Token::Symbol(symbol));
+            chars.next();
+        } else {
+            return Err(format!("unexpected {character:?}"));
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    position: usize,
+    variables: &'a BTreeMap<String, Value>,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, symbol: char) -> bool {
+        if self.peek() == Some(&Token::Symbol(symbol)) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// A unit name coming up, unless it is `in` being used to convert.
+    fn unit_ahead(&self) -> Option<&'static Unit> {
+        let Some(Token::Name(name)) = self.peek() else {
+            return None;
+        };
+
+        // In `2 in in cm` the first `in` is the unit
+        let is_conversion = name == "in"
+            && matches!(
+                self.tokens.get(self.position + 1),
+                Some(Token::Name(next)) if next != "in" && unit(next).is_some()
+            );
+
+        unit(name).filter(|_| !is_conversion)
+    }
+
+    fn conversion(&mut self) -> Result<Value, String> {
+        let value = self.sum()?;
+
+        match self.peek() {
+            Some(Token::Name(name)) if name == "to" || name == "in" => {
+                self.position += 1;
+
+                let Some(Token::Name(name)) = self.next() else {
+                    return Err(String::from("expected a unit to convert to"));
+                };
+
+                let target = unit(&name).ok_or_else(|| format!("{name} is not a unit"))?;
+
+                match value.unit {
+                    Some(current) if current.dimension == target.dimension => Ok(Value {
+                        amount: value.amount,
+                        unit: Some(target),
+                    }),
+                    Some(current) => Err(format!(
+                        "cannot convert {} to {}",
+                        current.names[0], target.names[0]
+                    )),
+                    None => Ok(Value {
+                        amount: value.amount * target.factor,
+                        unit: Some(target),
+                    }),
+                }
+            }
+            _ => Ok(value),
+        }
+    }
+
+    fn sum(&mut self) -> Result<Value, String> {
+        let mut value = self.product()?;
+
+        loop {
+            let sign = if self.eat('+') {
+                1.0
+            } else if self.eat('-') {
+                -1.0
+            } else {
+                return Ok(value);
+            };
+
+            let other = self.product()?;
+            let (left, right) = align(value, other, "add")?;
+
+            value = Value {
+                amount: left.amount + sign * right.amount,
+                unit: left.unit,
+            };
+        }
+    }
+
+    fn product(&mut self) -> Result<Value, String> {
+        let mut value = self.unary()?;
+
+        loop {
+            if self.eat('*') {
+                let other = self.unary()?;
+
+                if value.unit.is_some() && other.unit.is_some() {
+                    return Err(String::from("cannot multiply two amounts with units"));
+                }
+
+                value = Value {
+                    amount: value.amount * other.amount,
+                    unit: value.unit.or(other.unit),
+                };
+            } else if self.eat('/') {
+                let other = self.unary()?;
+
+                value = match (value.unit, other.unit) {
+                    (_, None) => Value {
+                        amount: value.amount / other.amount,
+                        unit: value.unit,
+                    },
+                    (Some(left), Some(right)) if left.dimension == right.dimension => {
+                        Value::number(value.amount / other.amount)
+                    }
+                    _ => return Err(String::from("cannot divide by an amount with a unit")),
+                };
+            } else if self.eat('%') {
+                let other = self.unary()?;
+                let (left, right) = align(value, other, "take the remainder of")?;
+
+                value = Value {
+                    amount: left.amount % right.amount,
+                    unit: left.unit,
+                };
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<Value, String> {
+        if self.eat('-') {
+            let value = self.unary()?;
+
+            Ok(Value {
+                amount: -value.amount,
+                ..value
+            })
+        } else if self.eat('+') {
+            self.unary()
+        } else {
+            self.power()
+        }
+    }
+
+    fn power(&mut self) -> Result<Value, String> {
+        let base = self.postfix()?;
+
+        if !self.eat('^') {
+            return Ok(base);
+        }
+
+        let exponent = self.unary()?;
+
+        if base.unit.is_some() || exponent.unit.is_some() {
+            return Err(String::from("cannot raise amounts with units"));
+        }
+
+        Ok(Value::number(base.amount.powf(exponent.amount)))
+    }
+
+    fn postfix(&mut self) -> Result<Value, String> {
+        let value = self.primary()?;
+
+        match self.unit_ahead() {
+            Some(unit) if value.unit.is_none() => {
+                self.position += 1;
+
+                Ok(Value {
+                    amount: value.amount * unit.factor,
+                    unit: Some(unit),
+                })
+            }
+            _ => Ok(value),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Value, String> {
+        match self.next() {
+            Some(Token::Number(number)) => Ok(Value::number(number)),
+            Some(Token::Symbol('(')) => {
+                let value = self.sum()?;
+
+                if !self.eat(')') {
+                    return Err(String::from("missing )"));
+                }
+
+                Ok(value)
+            }
+            Some(Token::Name(name)) if self.peek() == Some(&Token::Symbol('(')) => {
+                self.position += 1;
+
+                let argument = self.sum()?;
+
+                if !self.eat(')') {
+                    return Err(String::from("missing )"));
+                }
+
+                call(&name, argument)
+            }
+            Some(Token::Name(name)) => match name.as_str() {
+                "pi" | "π" => Ok(Value::number(std::f64::consts::PI)),
+                "e" => Ok(Value::number(std::f64::consts::E)),
+                _ => self
+                    .variables
+                    .get(&name)
+                    .copied()
+                    .ok_or_else(|| format!("{name} is not defined")),
+            },
+            Some(Token::Symbol(symbol)) => Err(format!("unexpected {symbol}")),
+            None => Err(String::from("the expression ends early")),
+        }
+    }
+}
+
+/// Gives both sides one unit, a bare number taking the other side's.
+fn align(left: Value, right: Value, verb: &str) -> Result<(Value, Value), String> {
+    match (left.unit, right.unit) {
+        (Some(a), Some(b)) if a.dimension != b.dimension => {
+            Err(format!("cannot {verb} {} and {}", a.names[0], b.names[0]))
+        }
+        (Some(unit), None) => Ok((
+            left,
+            Value {
+                amount: right.amount * unit.factor,
+                unit: Some(unit),
+            },
+        )),
+        (None, Some(unit)) => Ok((
+            Value {
+                amount: left.amount * unit.factor,
+                unit: Some(unit),
+            },
+            right,
+        )),
+        _ => Ok((left, right)),
+    }
+}
+
+fn call(name: &str, argument: Value) -> Result<Value, String> {
+    // Rounding happens in the unit the amount is shown in
+    let rounded = |round: fn(f64) -> f64| Value {
+        amount: round(argument.shown()) * argument.unit.map_or(1.0, |unit| unit.factor),
+        unit: argument.unit,
+    };
+
+    let plain = |function: fn(f64) -> f64| match argument.unit {
+        None => Ok(Value::number(function(argument.amount))),
+        Some(_) => Err(format!("{name} takes a plain number")),
+    };
+
+    match name {
+        "abs" => Ok(Value {
+            amount: argument.amount.abs(),
+            ..argument
+        }),
+        "round" => Ok(rounded(f64::round)),
+        "floor" => Ok(rounded(f64::floor)),
+        "ceil" => Ok(rounded(f64::ceil)),
+        "sqrt" => plain(f64::sqrt),
+        "exp" => plain(f64::exp),
+        "ln" => plain(f64::ln),
+        "log" => plain(f64::log10),
+        "sin" => plain(f64::sin),
+        "cos" => plain(f64::cos),
+        "tan" => plain(f64::tan),
+        _ => Err(format!("{name} is not a function")),
+    }
+}
+
+/// Parses a whole line, which may assign its value with `name = ...`.
+fn line(
+    text: &str,
+    variables: &BTreeMap<String, Value>,
+) -> Result<(Option<String>, Value), String> {
+    let mut tokens = tokenize(text)?;
+    let mut name = None;
+
+    if let [Token::Name(assigned), Token::Symbol('='), ..] = tokens.as_slice() {
+        name = Some(assigned.clone());
+        tokens.drain(..2);
+    }
+
+    let mut parser = Parser {
+        tokens,
+        position: 0,
+        variables,
+    };
+
+    let value = parser.conversion()?;
+
+    match parser.peek() {
+        None => Ok((name, value)),
+        Some(Token::Number(number)) => Err(format!("unexpected {number}")),
+        Some(Token::Name(name)) => Err(format!("unexpected {name}")),
+        Some(Token::Symbol(symbol)) => Err(format!("unexpected {symbol}")),
+    }
+}
+
+/// Evaluates an expression such as `2 * (3 + 4)` or `5 km + 300 m in mi`.
+pub fn evaluate(expression: &str) -> Result<String, String> {
+    let (_, value) = line(expression.trim(), &BTreeMap::new())?;
+
+    value.written()
+}
+
+/// Each line's result, skipping blank lines and comments, with earlier assignments and `ans` in scope.
+pub fn evaluate_lines(text: &str) -> Vec<Option<Result<String, String>>> {
+    let mut variables = BTreeMap::new();
+
+    text.lines()
+        .map(|source| {
+            let source = source.trim();
+
+            if source.is_empty() || source.starts_with('#') || source.starts_with("//") {
+                return None;
+            }
+
+            let result = line(source, &variables).and_then(|(name, value)| {
+                let shown = value.written()?;
+
+                if let Some(name) = name {
+                    variables.insert(name, value);
+                }
+
+                variables.insert(String::from("ans"), value);
+
+                Ok(shown)
+            });
+
+            Some(result)
+        })
+        .collect()
+}
+
+/// The calculation panel's results and scroll position.
+#[derive(Debug, Clone, Default)]
+pub struct Sheet {
+    offset: f32,
+    results: Vec<Option<Result<String, String>>>,
+}
+
+impl Sheet {
+    pub fn refresh(&mut self, text: &str) {
+        self.results = evaluate_lines(text);
+    }
+
+    pub fn scrolled(&mut self, viewport: scrollable::Viewport) {
+        self.offset = viewport.absolute_offset().y;
+    }
+
+    pub fn view<'a>(
+        &'a self,
+        content: &'a text_editor::Content,
+        height: f32,
+    ) -> Element<'a, Message> {
+        let line = move |index: usize| -> Element<'a, Message> {
+            let source = content
+                .line(index)
+                .map(|line| line.to_string())
+                .unwrap_or_default();
+
+            let result: Element<'a, Message> = match self.results.get(index) {
+                Some(Some(Ok(result))) => text(format!("= {result}")).size(13).into(),
+                Some(Some(Err(error))) => text(error).size(13).style(FADED).into(),
+                _ => text("").into(),
+            };
+
+            row![
+                text(format!("{:>5} {source}", index + 1))
+                    .size(13)
+                    .width(Length::Fill),
+                container(result).width(RESULT_WIDTH),
+            ]
+            .spacing(10)
+            .into()
+        };
+
+        container(
+            column![
+                text("Calculation").size(16),
+                widgets::virtual_list(
+                    content.line_count(),
+                    LINE_HEIGHT,
+                    self.offset,
+                    height,
+                    line,
+                    Message::CalculationScrolled,
+                ),
+                button(text("Close").size(14))
+                    .on_press(Message::ClosePanel)
+                    .style(theme::Button::Secondary),
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fill)
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
@@ -0,0 +1,395 @@
+use crate::Message;
+
+use iced::futures::channel::mpsc;
+use iced::futures::{SinkExt, StreamExt};
+use iced::theme;
+use iced::widget::{button, column, container, row, scrollable, text, Column};
+use iced::{subscription, Color, Element, Length, Subscription};
+use std::collections::{BTreeMap, VecDeque};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::Command;
+
+pub const MARKER_COLOR: Color = Color::from_rgb(0.45, 0.6, 0.85);
+
+/// Printed by the driver once a cell is done, on a line of its own.
+const DONE: &str = "\u{1e}";
+const MAX_OUTPUT: usize = 64 * 1024;
+
+/// Runs framed cells in one namespace, so later cells see what earlier ones defined.
+const DRIVER: &str = r#"
+import sys, traceback
+sys.stderr = sys.stdout
+namespace = {"__name__": "__main__"}
+while True:
+    header = sys.stdin.readline()
+    if not header:
+        break
+    code = sys.stdin.read(int(header))
+    try:
+        exec(compile(code, "<cell>", "exec"), namespace)
+    except BaseException:
+        traceback.print_exc()
+    print("\x1e", flush=True)
+"#;
+
+pub fn supports(extension: &str) -> bool {
+    matches!(extension, "py" | "rs")
+}
+
+/// Python cells share a long-running interpreter; others go through a configured command.
+pub fn has_kernel(extension: &str) -> bool {
+    extension == "py"
+}
+
+pub fn is_marker(line: &str) -> bool {
+    let line = line.trim_start();
+
+    line.starts_with("# %%") || line.starts_with("// %%")
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cell {
+    pub title: String,
+    /// The marker's line, or the first line for code above every marker
+    pub start: usize,
+    /// The lines of code, after the marker
+    pub lines: Range<usize>,
+}
+
+impl Cell {
+    pub fn code(&self, text: &str) -> String {
+        text.lines()
+            .skip(self.lines.start)
+            .take(self.lines.len())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+pub fn split(text: &str) -> Vec<Cell> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut cells = Vec::new();
+    let mut current: Option<(String, usize)> = None;
+    let mut start = 0;
+
+    let mut close = |title: Option<(String, usize)>, lines: Range<usize>| {
+        let has_code = text
+            .lines()
+            .skip(lines.start)
+            .take(lines.len())
+            .any(|line| !line.trim().is_empty());
+
+        match title {
+            Some((title, marker)) => cells.push(Cell {
+                title,
+                start: marker,
+                lines,
+            }),
+            None if has_code => cells.push(Cell {
+                title: String::from("Preamble"),
+                start: 0,
+                lines,
+            }),
+            None => {}
+        }
+    };
+
+    for (index, line) in lines.iter().enumerate() {
+        if is_marker(line) {
+            close(current.take(), start..index);
+
+            let title = line
+                .trim_start()
+                .trim_start_matches(['#', '/'])
+                .trim_start()
+                .trim_start_matches("%%")
+                .trim();
+
+            current = Some((title.to_string(), index));
+            start = index + 1;
+        }
+    }
+
+    close(current, start..lines.len());
+
+    cells
+}
+
+/// The cell holding `line`, marker included.
+pub fn at(cells: &[Cell], line: usize) -> Option<usize> {
+    cells
+        .iter()
+        .position(|cell| (cell.start..cell.lines.end.max(cell.start + 1)).contains(&line))
+}
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    Ready(mpsc::Sender<String>),
+    Line(String),
+    Done,
+    Exited(String),
+}
+
+/// A file's cells and what running them printed.
+pub struct Notebook {
+    pub path: PathBuf,
+    pub cells: Vec<Cell>,
+    has_kernel: bool,
+    id: u64,
+    is_alive: bool,
+    sender: Option<mpsc::Sender<String>>,
+    queued: Vec<String>,
+    /// Cells sent to the kernel that have not finished, in order
+    pending: VecDeque<usize>,
+    outputs: BTreeMap<usize, String>,
+    running: Vec<usize>,
+}
+
+impl Notebook {
+    pub fn new(path: PathBuf, has_kernel: bool) -> Self {
+        Self {
+            path,
+            cells: Vec::new(),
+            has_kernel,
+            id: 0,
+            is_alive: false,
+            sender: None,
+            queued: Vec::new(),
+            pending: VecDeque::new(),
+            outputs: BTreeMap::new(),
+            running: Vec::new(),
+        }
+    }
+
+    pub fn has_kernel(&self) -> bool {
+        self.has_kernel
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.is_alive
+    }
+
+    /// Starts a fresh interpreter under a new subscription, forgetting the old one's state.
+    pub fn restart(&mut self, id: u64) {
+        self.id = id;
+        self.is_alive = true;
+        self.sender = None;
+        self.queued.clear();
+
+        for index in self.pending.drain(..) {
+            self.running.retain(|running| *running != index);
+        }
+    }
+
+    pub fn connect(&mut self, sender: mpsc::Sender<String>) {
+        self.sender = Some(sender);
+
+        for frame in std::mem::take(&mut self.queued) {
+            self.send(frame);
+        }
+    }
+
+    /// Sends a cell to the kernel.
+    pub fn run(&mut self, index: usize, code: String) {
+        self.start(index);
+        self.pending.push_back(index);
+
+        self.send(format!("{}\n{code}", code.chars().count()));
+    }
+
+    /// Marks a cell as running, clearing what it printed before.
+    pub fn start(&mut self, index: usize) {
+        self.outputs.insert(index, String::new());
+
+        if !self.running.contains(&index) {
+            self.running.push(index);
+        }
+    }
+
+    pub fn finish(&mut self, index: usize, output: String) {
+        self.running.retain(|running| *running != index);
+        self.outputs.insert(index, output);
+    }
+
+    pub fn handle(&mut self, event: Event) {
+        match event {
+            Event::Ready(sender) => self.connect(sender),
+            Event::Line(line) => {
+                if let Some(output) = self
+                    .pending
+                    .front()
+                    .and_then(|index| self.outputs.get_mut(index))
+                {
+                    if output.len() < MAX_OUTPUT {
+                        output.push_str(&line);
+                        output.push('\n');
+                    }
+                }
+            }
+            Event::Done => {
+                if let Some(index) = self.pending.pop_front() {
+                    self.running.retain(|running| *running != index);
+                }
+            }
+            Event::Exited(reason) => {
+                self.is_alive = false;
+                self.sender = None;
+
+                for index in self.pending.drain(..) {
+                    self.running.retain(|running| *running != index);
+
+                    if let Some(output) = self.outputs.get_mut(&index) {
+                        output.push_str(&reason);
+                    }
+                }
+            }
+        }
+    }
+
+    fn send(&mut self, frame: String) {
+        match &mut self.sender {
+            Some(sender) => {
+                let _ = sender.try_send(frame);
+            }
+            None => self.queued.push(frame),
+        }
+    }
+
+    /// Keeps the interpreter running while the subscription is kept, killing it once dropped.
+    pub fn subscription(&self) -> Subscription<Event> {
+        if !self.has_kernel || !self.is_alive {
+            return Subscription::none();
+        }
+
+        let directory = self
+            .path
+            .parent()
+            .map_or_else(|| PathBuf::from("."), PathBuf::from);
+
+        subscription::channel(("cells", self.id), 100, move |mut output| async move {
+            let python = if cfg!(windows) { "python" } else { "python3" };
+
+            let spawned = Command::new(python)
+                .args(["-u", "-c", DRIVER])
+                .current_dir(&directory)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .kill_on_drop(true)
+                .spawn();
+
+            let reason = match spawned {
+                Ok(mut child) => match (child.stdin.take(), child.stdout.take()) {
+                    (Some(mut stdin), Some(stdout)) => {
+                        let (sender, mut frames) = mpsc::channel::<String>(100);
+
+                        tokio::spawn(async move {
+                            while let Some(frame) = frames.next().await {
+                                if stdin.write_all(frame.as_bytes()).await.is_err() {
+                                    break;
+                                }
+
+                                let _ = stdin.flush().await;
+                            }
+                        });
+
+                        let _ = output.send(Event::Ready(sender)).await;
+
+                        let mut lines = BufReader::new(stdout).lines();
+
+                        while let Ok(Some(line)) = lines.next_line().await {
+                            let event = if line == DONE {
+                                Event::Done
+                            } else {
+                                Event::Line(line)
+                            };
+
+                            let _ = output.send(event).await;
+                        }
+
+                        String::from("The interpreter exited")
+                    }
+                    _ => String::from("The interpreter could not be reached"),
+                },
+                Err(error) => format!("{python} could not start: {error}"),
+            };
+
+            let _ = output.send(Event::Exited(reason)).await;
+
+            loop {
+                std::future::pending::<()>().await;
+            }
+        })
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let cells =
+            self.cells
+                .iter()
+                .enumerate()
+                .fold(Column::new().spacing(8), |cells, (index, cell)| {
+                    let title = if cell.title.is_empty() {
+                        format!("Cell {}", index + 1)
+                    } else {
+                        cell.title.clone()
+                    };
+
+                    let output = match (self.outputs.get(&index), self.running.contains(&index)) {
+                        (output, true) => {
+                            format!("{}Running...", output.map_or("", String::as_str))
+                        }
+                        (Some(output), false) if output.trim().is_empty() => {
+                            String::from("(no output)")
+                        }
+                        (Some(output), false) => output.trim_end().to_string(),
+                        (None, false) => String::new(),
+                    };
+
+                    cells.push(
+                        column![
+                            row![
+                                button(text(title).size(14))
+                                    .on_press(Message::CellSelected(index))
+                                    .style(theme::Button::Text)
+                                    .width(Length::Fill),
+                                button(text("Run").size(13)).on_press(Message::RunCellAt(index)),
+                            ]
+                            .spacing(10),
+                            text(output).size(13),
+                        ]
+                        .spacing(4),
+                    )
+                });
+
+        let mut actions =
+            row![button(text("Run all").size(14)).on_press(Message::RunAllCells)].spacing(10);
+
+        if self.has_kernel {
+            actions = actions.push(
+                button(text("Restart kernel").size(14))
+                    .on_press(Message::RestartKernel)
+                    .style(theme::Button::Secondary),
+            );
+        }
+
+        container(
+            column![
+                text("Cells").size(16),
+                scrollable(cells).height(Length::Fill),
+                actions.push(
+                    button(text("Close").size(14))
+                        .on_press(Message::ClosePanel)
+                        .style(theme::Button::Secondary),
+                ),
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fill)
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
@@ -0,0 +1,85 @@
+use crate::Error;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io;
+use std::path::{Path, PathBuf};
+
+const CHECKSUMS_FILE: &str = "checksums.toml";
+const MAX_CHECKSUMS: usize = 1_000;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Checksums {
+    files: Vec<Checksum>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Checksum {
+    path: PathBuf,
+    sha256: String,
+}
+
+impl Checksums {
+    pub fn get(&self, path: &Path) -> Option<&str> {
+        self.files
+            .iter()
+            .find(|checksum| checksum.path == path)
+            .map(|checksum| checksum.sha256.as_str())
+    }
+
+    pub fn set(&mut self, path: PathBuf, sha256: String) {
+        self.files.retain(|checksum| checksum.path != path);
+        self.files.insert(0, Checksum { path, sha256 });
+        self.files.truncate(MAX_CHECKSUMS);
+    }
+
+    pub async fn load() -> Result<Self, Error> {
+        let Some(path) = checksums_file() else {
+            return Ok(Self::default());
+        };
+
+        match tokio::fs::read_to_string(&path).await {
+            Ok(text) => {
+                toml::from_str(&text).map_err(|error| Error::InvalidConfig(error.to_string()))
+            }
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(Error::IOFailed(error.kind())),
+        }
+    }
+
+    pub async fn save(self) -> Result<(), Error> {
+        let path = checksums_file().ok_or(Error::IOFailed(io::ErrorKind::NotFound))?;
+        let text = toml::to_string_pretty(&self)
+            .map_err(|error| Error::InvalidConfig(error.to_string()))?;
+
+        if let Some(directory) = path.parent() {
+            tokio::fs::create_dir_all(directory)
+                .await
+                .map_err(|error| Error::IOFailed(error.kind()))?;
+        }
+
+        tokio::fs::write(&path, text)
+            .await
+            .map_err(|error| Error::IOFailed(error.kind()))
+    }
+}
+
+/// The SHA-256 of the file as it is on disk, in lowercase hex.
+pub async fn sha256(path: PathBuf) -> Result<(PathBuf, String), Error> {
+    let bytes = tokio::fs::read(&path)
+        .await
+        .map_err(|error| Error::IOFailed(error.kind()))?;
+
+    let digest = tokio::task::spawn_blocking(move || Sha256::digest(bytes))
+        .await
+        .map_err(|_| Error::IOFailed(io::ErrorKind::Other))?;
+
+    let hex = digest.iter().map(|byte| format!("{byte:02x}")).collect();
+
+    Ok((path, hex))
+}
+
+fn checksums_file() -> Option<PathBuf> {
+    dirs::data_dir().map(|directory| directory.join("bonkitor").join(CHECKSUMS_FILE))
+}
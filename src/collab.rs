@@ -0,0 +1,602 @@
+use crate::Message;
+
+use iced::futures::channel::mpsc;
+use iced::futures::{SinkExt, StreamExt};
+use iced::theme;
+use iced::widget::{button, column, container, row, text, text_input};
+use iced::{subscription, Color, Element, Length, Subscription};
+use std::collections::BTreeMap;
+use std::io;
+use std::ops::Range;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+use yrs::updates::decoder::Decode;
+use yrs::updates::encoder::Encode;
+use yrs::{Doc, GetString, ReadTxn, StateVector, Text, TextRef, Transact, Update};
+
+const DEFAULT_PORT: u16 = 7878;
+const MAX_FRAME: usize = 64 * 1024 * 1024;
+const MAX_KEY: usize = 256;
+/// How long a new connection has to present the session key
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+const COLORS: [Color; 5] = [
+    Color::from_rgb(0.9, 0.4, 0.4),
+    Color::from_rgb(0.4, 0.8, 0.4),
+    Color::from_rgb(0.4, 0.6, 1.0),
+    Color::from_rgb(0.9, 0.7, 0.3),
+    Color::from_rgb(0.8, 0.5, 0.9),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Role {
+    /// A port to listen on and the key peers must present
+    Host(u16, String),
+    /// An address to connect to and the host's key
+    Join(String, String),
+}
+
+#[derive(Debug, Clone)]
+pub enum Frame {
+    Update(Vec<u8>),
+    Cursor { name: String, caret: Caret },
+}
+
+/// Where a peer's cursor is, and where its selection started; both as line and
+/// character column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Caret {
+    pub head: (usize, usize),
+    pub anchor: (usize, usize),
+}
+
+/// A remote edit, as the byte range of the old text it replaces.
+#[derive(Debug, Clone)]
+pub struct Change {
+    pub range: Range<usize>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    Ready(mpsc::UnboundedSender<Frame>),
+    PeerJoined,
+    /// A peer fell too far behind to be sent the updates it missed
+    Lagged,
+    Received(Frame),
+    PeerLeft,
+    Disconnected(String),
+}
+
+pub struct Session {
+    pub role: Role,
+    pub name: String,
+    pub peers: BTreeMap<String, Caret>,
+    doc: Doc,
+    text: TextRef,
+    last_text: String,
+    sender: Option<mpsc::UnboundedSender<Frame>>,
+}
+
+impl Session {
+    pub fn new(role: Role, name: String, text: String) -> Self {
+        let doc = Doc::new();
+        let shared = doc.get_or_insert_text("buffer");
+
+        if let Role::Host(..) = role {
+            shared.insert(&mut doc.transact_mut(), 0, &text);
+        }
+
+        Self {
+            role,
+            name,
+            peers: BTreeMap::new(),
+            last_text: shared.get_string(&doc.transact()),
+            text: shared,
+            doc,
+            sender: None,
+        }
+    }
+
+    pub fn subscription(&self) -> Subscription<Event> {
+        match &self.role {
+            Role::Host(port, key) => host(*port, key.clone()),
+            Role::Join(address, key) => join(address.clone(), key.clone()),
+        }
+    }
+
+    pub fn connect(&mut self, sender: mpsc::UnboundedSender<Frame>) {
+        self.sender = Some(sender);
+    }
+
+    pub fn send_state(&mut self) {
+        let update = self
+            .doc
+            .transact()
+            .encode_state_as_update_v1(&StateVector::default());
+
+        self.send(Frame::Update(update));
+    }
+
+    pub fn send_cursor(&mut self, caret: Caret) {
+        self.send(Frame::Cursor {
+            name: self.name.clone(),
+            caret,
+        });
+    }
+
+    pub fn local_edit(&mut self, text: String) {
+        if text == self.last_text {
+            return;
+        }
+
+        let (start, removed, inserted) = diff(&self.last_text, &text);
+        let before = self.doc.transact().state_vector();
+
+        {
+            let mut transaction = self.doc.transact_mut();
+
+            if removed > 0 {
+                self.text
+                    .remove_range(&mut transaction, start as u32, removed as u32);
+            }
+
+            if !inserted.is_empty() {
+                self.text.insert(&mut transaction, start as u32, inserted);
+            }
+        }
+
+        let update = self.doc.transact().encode_state_as_update_v1(&before);
+
+        self.last_text = text;
+        self.send(Frame::Update(update));
+    }
+
+    pub fn receive(&mut self, frame: Frame) -> Option<Change> {
+        match frame {
+            Frame::Update(update) => {
+                let update = Update::decode_v1(&update).ok()?;
+                let _ = self.doc.transact_mut().apply_update(update);
+
+                let text = self.text.get_string(&self.doc.transact());
+
+                if text == self.last_text {
+                    return None;
+                }
+
+                let (start, removed, inserted) = diff(&self.last_text, &text);
+                let change = Change {
+                    range: start..start + removed,
+                    text: inserted.to_string(),
+                };
+
+                self.last_text = text;
+
+                Some(change)
+            }
+            Frame::Cursor { name, caret } => {
+                if name != self.name {
+                    self.peers.insert(name, caret);
+                }
+
+                None
+            }
+        }
+    }
+
+    /// The line and byte range of every span a peer has selected, or the character at
+    /// its cursor, in that peer's color.
+    pub fn highlights(&self) -> Vec<(usize, Range<usize>, Color)> {
+        let lines: Vec<&str> = self.last_text.split('\n').collect();
+        let mut highlights = Vec::new();
+
+        for (_, caret, color) in self.colored() {
+            let start = caret.head.min(caret.anchor);
+            let end = caret.head.max(caret.anchor);
+
+            if start == end {
+                let Some(line) = lines.get(start.0) else {
+                    continue;
+                };
+
+                if let Some(range) = marker(line, start.1) {
+                    highlights.push((start.0, range, color));
+                }
+
+                continue;
+            }
+
+            for (number, line) in lines.iter().enumerate().take(end.0 + 1).skip(start.0) {
+                let from = if number == start.0 {
+                    byte(line, start.1)
+                } else {
+                    0
+                };
+                let to = if number == end.0 {
+                    byte(line, end.1)
+                } else {
+                    line.len()
+                };
+
+                if from < to {
+                    highlights.push((number, from..to, color));
+                }
+            }
+        }
+
+        highlights
+    }
+
+    pub fn status(&self) -> Element<'_, Message> {
+        let role = match &self.role {
+            Role::Host(port, key) => format!("Hosting on :{port} with key {key}"),
+            Role::Join(address, _) => format!("Joined {address}"),
+        };
+
+        self.colored()
+            .fold(row![text(role).size(14)], |status, (name, caret, color)| {
+                let (line, column) = caret.head;
+
+                status.push(
+                    text(format!("{name} {}:{}", line + 1, column + 1))
+                        .size(14)
+                        .style(color),
+                )
+            })
+            .spacing(10)
+            .into()
+    }
+
+    /// Peers in a stable order, each with a color of its own until there are more
+    /// peers than colors.
+    fn colored(&self) -> impl Iterator<Item = (&String, &Caret, Color)> {
+        self.peers
+            .iter()
+            .zip(COLORS.iter().cycle())
+            .map(|((name, caret), color)| (name, caret, *color))
+    }
+
+    fn send(&mut self, frame: Frame) {
+        // Unbounded, since a dropped update would leave the peers' documents apart for good
+        if let Some(sender) = &self.sender {
+            let _ = sender.unbounded_send(frame);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Dialog {
+    pub name: String,
+    pub port: String,
+    pub address: String,
+    pub key: String,
+}
+
+impl Dialog {
+    pub fn new() -> Self {
+        Self {
+            name: std::env::var("USER").unwrap_or_else(|_| String::from("guest")),
+            port: DEFAULT_PORT.to_string(),
+            address: format!("127.0.0.1:{DEFAULT_PORT}"),
+            key: uuid::Uuid::new_v4().simple().to_string()[..16].to_string(),
+        }
+    }
+
+    pub fn host(&self) -> Option<Role> {
+        let port = self.port.trim().parse().ok()?;
+
+        self.key().map(|key| Role::Host(port, key.to_string()))
+    }
+
+    pub fn join(&self) -> Option<Role> {
+        let address = self.address.trim();
+
+        if !address.contains(':') {
+            return None;
+        }
+
+        self.key()
+            .map(|key| Role::Join(address.to_string(), key.to_string()))
+    }
+
+    fn key(&self) -> Option<&str> {
+        let key = self.key.trim();
+
+        (!key.is_empty() && key.len() <= MAX_KEY).then_some(key)
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        container(
+            column![
+                text("Collaborate").size(16),
+                field("Your name", &self.name, Message::CollabNameChanged),
+                field("Session key", &self.key, Message::CollabKeyChanged),
+                row![
+                    field("Port", &self.port, Message::CollabPortChanged),
+                    button(text("Host").size(14))
+                        .on_press_maybe(self.host().map(Message::StartCollab)),
+                ]
+                .spacing(10),
+                row![
+                    field("host:port", &self.address, Message::CollabAddressChanged),
+                    button(text("Join").size(14))
+                        .on_press_maybe(self.join().map(Message::StartCollab)),
+                ]
+                .spacing(10),
+                button(text("Close").size(14))
+                    .on_press(Message::ClosePanel)
+                    .style(theme::Button::Secondary),
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fill)
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
+
+fn field<'a>(
+    placeholder: &str,
+    value: &str,
+    on_input: fn(String) -> Message,
+) -> Element<'a, Message> {
+    text_input(placeholder, value)
+        .on_input(on_input)
+        .size(14)
+        .padding(5)
+        .into()
+}
+
+fn byte(line: &str, column: usize) -> usize {
+    line.char_indices()
+        .nth(column)
+        .map_or(line.len(), |(index, _)| index)
+}
+
+/// The character at a cursor, or the one before it at the end of a line.
+fn marker(line: &str, column: usize) -> Option<Range<usize>> {
+    let start = byte(line, column);
+
+    match line[start..].chars().next() {
+        Some(character) => Some(start..start + character.len_utf8()),
+        None => line
+            .char_indices()
+            .last()
+            .map(|(index, character)| index..index + character.len_utf8()),
+    }
+}
+
+fn diff<'a>(old: &str, new: &'a str) -> (usize, usize, &'a str) {
+    let prefix = old
+        .char_indices()
+        .zip(new.chars())
+        .find(|((_, a), b)| a != b)
+        .map_or(old.len().min(new.len()), |((index, _), _)| index);
+
+    let suffix = old[prefix..]
+        .chars()
+        .rev()
+        .zip(new[prefix..].chars().rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(a, _)| a.len_utf8())
+        .sum::<usize>();
+
+    let removed = old.len() - prefix - suffix;
+    let inserted = &new[prefix..new.len() - suffix];
+
+    (prefix, removed, inserted)
+}
+
+fn host(port: u16, key: String) -> Subscription<Event> {
+    subscription::channel(
+        ("collab-host", port, key.clone()),
+        100,
+        move |mut output| async move {
+            match TcpListener::bind(("0.0.0.0", port)).await {
+                Ok(listener) => {
+                    let (relay, _) = broadcast::channel(256);
+                    let _ = output.send(Event::Ready(forward(relay.clone()))).await;
+
+                    let mut next = 1;
+
+                    while let Ok((mut stream, _)) = listener.accept().await {
+                        let id = next;
+                        let key = key.clone();
+                        let relay = relay.clone();
+                        let mut output = output.clone();
+
+                        next += 1;
+
+                        // Anyone who can reach the port may connect, so nothing is shared
+                        // until they prove they were given the key
+                        tokio::spawn(async move {
+                            let presented =
+                                tokio::time::timeout(HANDSHAKE_TIMEOUT, read_key(&mut stream))
+                                    .await;
+
+                            if let Ok(Ok(presented)) = presented {
+                                if presented == key {
+                                    serve(stream, id, relay, output.clone());
+
+                                    let _ = output.send(Event::PeerJoined).await;
+                                }
+                            }
+                        });
+                    }
+                }
+                Err(error) => {
+                    let _ = output.send(Event::Disconnected(error.to_string())).await;
+                }
+            }
+
+            loop {
+                std::future::pending::<()>().await;
+            }
+        },
+    )
+}
+
+fn join(address: String, key: String) -> Subscription<Event> {
+    subscription::channel(
+        ("collab-join", address.clone(), key.clone()),
+        100,
+        move |mut output| async move {
+            let connection = async {
+                let mut stream = TcpStream::connect(&address).await?;
+                write_key(&mut stream, &key).await?;
+
+                Ok::<_, io::Error>(stream)
+            };
+
+            match connection.await {
+                Ok(stream) => {
+                    let (relay, _) = broadcast::channel(256);
+
+                    serve(stream, 1, relay.clone(), output.clone());
+
+                    let _ = output.send(Event::Ready(forward(relay))).await;
+                }
+                Err(error) => {
+                    let _ = output.send(Event::Disconnected(error.to_string())).await;
+                }
+            }
+
+            loop {
+                std::future::pending::<()>().await;
+            }
+        },
+    )
+}
+
+fn forward(relay: broadcast::Sender<(usize, Frame)>) -> mpsc::UnboundedSender<Frame> {
+    let (sender, mut frames) = mpsc::unbounded();
+
+    tokio::spawn(async move {
+        while let Some(frame) = frames.next().await {
+            let _ = relay.send((0, frame));
+        }
+    });
+
+    sender
+}
+
+fn serve(
+    stream: TcpStream,
+    id: usize,
+    relay: broadcast::Sender<(usize, Frame)>,
+    mut output: mpsc::Sender<Event>,
+) {
+    let (mut reader, mut writer) = stream.into_split();
+    let mut outgoing = relay.subscribe();
+    let mut lagged = output.clone();
+
+    tokio::spawn(async move {
+        loop {
+            match outgoing.recv().await {
+                Ok((origin, frame)) => {
+                    if origin != id && write_frame(&mut writer, &frame).await.is_err() {
+                        break;
+                    }
+                }
+                // The updates it skipped are gone, so the whole document is sent again
+                Err(broadcast::error::RecvError::Lagged(_)) => {
+                    let _ = lagged.send(Event::Lagged).await;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+
+    tokio::spawn(async move {
+        loop {
+            match read_frame(&mut reader).await {
+                Ok(frame) => {
+                    let _ = relay.send((id, frame.clone()));
+                    let _ = output.send(Event::Received(frame)).await;
+                }
+                Err(_) => {
+                    let _ = output.send(Event::PeerLeft).await;
+                    break;
+                }
+            }
+        }
+    });
+}
+
+async fn read_key(stream: &mut TcpStream) -> io::Result<String> {
+    let length = stream.read_u32().await? as usize;
+
+    if length > MAX_KEY {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "key too long"));
+    }
+
+    let mut key = vec![0; length];
+    stream.read_exact(&mut key).await?;
+
+    Ok(String::from_utf8_lossy(&key).into_owned())
+}
+
+async fn write_key(stream: &mut TcpStream, key: &str) -> io::Result<()> {
+    stream.write_u32(key.len() as u32).await?;
+    stream.write_all(key.as_bytes()).await
+}
+
+async fn read_frame(reader: &mut OwnedReadHalf) -> io::Result<Frame> {
+    let tag = reader.read_u8().await?;
+    let length = reader.read_u32().await? as usize;
+
+    if length > MAX_FRAME {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "frame too large",
+        ));
+    }
+
+    let mut payload = vec![0; length];
+    reader.read_exact(&mut payload).await?;
+
+    match tag {
+        0 => Ok(Frame::Update(payload)),
+        1 => {
+            let payload = String::from_utf8_lossy(&payload);
+            let mut parts = payload.rsplitn(5, '\t');
+            let mut number = || -> Option<usize> { parts.next()?.parse().ok() };
+
+            let anchor = number().zip(number()).map(|(column, line)| (line, column));
+            let head = number().zip(number()).map(|(column, line)| (line, column));
+
+            match (parts.next(), head, anchor) {
+                (Some(name), Some(head), Some(anchor)) => Ok(Frame::Cursor {
+                    name: name.to_string(),
+                    caret: Caret { head, anchor },
+                }),
+                _ => Err(io::Error::new(io::ErrorKind::InvalidData, "invalid cursor")),
+            }
+        }
+        _ => Err(io::Error::new(io::ErrorKind::InvalidData, "unknown frame")),
+    }
+}
+
+async fn write_frame(writer: &mut OwnedWriteHalf, frame: &Frame) -> io::Result<()> {
+    let (tag, payload) = match frame {
+        Frame::Update(update) => (0, update.clone()),
+        Frame::Cursor { name, caret } => {
+            let Caret {
+                head: (line, column),
+                anchor: (anchor_line, anchor_column),
+            } = caret;
+
+            (
+                1,
+                format!("{name}\t{line}\t{column}\t{anchor_line}\t{anchor_column}").into_bytes(),
+            )
+        }
+    };
+
+    writer.write_u8(tag).await?;
+    writer.write_u32(payload.len() as u32).await?;
+    writer.write_all(&payload).await
+}
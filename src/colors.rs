@@ -0,0 +1,379 @@
+use crate::{compression, Message};
+
+use iced::theme;
+use iced::widget::{button, column, container, row, scrollable, slider, text, Column, Space};
+use iced::{Background, Color, Element, Length, Theme};
+use std::ops::Range;
+use std::path::Path;
+
+/// Files where colors are worth showing; elsewhere words like "red" are just words.
+const EXTENSIONS: [&str; 8] = [
+    "css", "scss", "sass", "less", "html", "htm", "svg", "tmtheme",
+];
+
+const NAMED: &[(&str, [u8; 3])] = &[
+    ("black", [0, 0, 0]),
+    ("silver", [192, 192, 192]),
+    ("gray", [128, 128, 128]),
+    ("white", [255, 255, 255]),
+    ("maroon", [128, 0, 0]),
+    ("red", [255, 0, 0]),
+    ("purple", [128, 0, 128]),
+    ("fuchsia", [255, 0, 255]),
+    ("green", [0, 128, 0]),
+    ("lime", [0, 255, 0]),
+    ("olive", [128, 128, 0]),
+    ("yellow", [255, 255, 0]),
+    ("navy", [0, 0, 128]),
+    ("blue", [0, 0, 255]),
+    ("teal", [0, 128, 128]),
+    ("aqua", [0, 255, 255]),
+    ("orange", [255, 165, 0]),
+    ("pink", [255, 192, 203]),
+    ("brown", [165, 42, 42]),
+    ("gold", [255, 215, 0]),
+    ("indigo", [75, 0, 130]),
+    ("violet", [238, 130, 238]),
+    ("crimson", [220, 20, 60]),
+    ("coral", [255, 127, 80]),
+    ("salmon", [250, 128, 114]),
+    ("tomato", [255, 99, 71]),
+    ("turquoise", [64, 224, 208]),
+    ("skyblue", [135, 206, 235]),
+    ("steelblue", [70, 130, 180]),
+    ("slategray", [112, 128, 144]),
+    ("rebeccapurple", [102, 51, 153]),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Notation {
+    Hex,
+    Rgb,
+    Named,
+}
+
+#[derive(Debug, Clone)]
+pub struct Swatch {
+    pub range: Range<usize>,
+    pub color: Color,
+    pub notation: Notation,
+}
+
+#[derive(Debug, Clone)]
+pub struct Picker {
+    pub line: usize,
+    pub original: Swatch,
+    pub color: Color,
+}
+
+impl Picker {
+    pub fn value(&self) -> String {
+        format(self.color, self.original.notation)
+    }
+}
+
+/// Style sheets, markup, and theme files, going by the name.
+pub fn supports(path: &Path) -> bool {
+    let extension = compression::extension(path)
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    let is_theme = matches!(extension.as_str(), "json" | "toml")
+        && path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.to_ascii_lowercase().contains("theme"));
+
+    EXTENSIONS.contains(&extension.as_str()) || is_theme
+}
+
+/// Every color in the text, with the line it is on.
+pub fn list(text: &str) -> Vec<(usize, Swatch)> {
+    text.split('\n')
+        .enumerate()
+        .flat_map(|(line, text)| find(text).into_iter().map(move |swatch| (line, swatch)))
+        .collect()
+}
+
+/// The first character of a color, which is painted in it to mark where it is.
+pub fn marker(line: &str, swatch: &Swatch) -> Range<usize> {
+    let length = line[swatch.range.start..]
+        .chars()
+        .next()
+        .map_or(0, char::len_utf8);
+
+    swatch.range.start..swatch.range.start + length
+}
+
+pub fn find(line: &str) -> Vec<Swatch> {
+    let mut swatches = Vec::new();
+    let bytes = line.as_bytes();
+    let mut index = 0;
+
+    while index < bytes.len() {
+        let rest = &line[index..];
+
+        let swatch = if bytes[index] == b'#' {
+            hex(rest)
+        } else if rest.starts_with("rgb") && !is_word(line, index) {
+            rgb(rest)
+        } else if bytes[index].is_ascii_alphabetic() && !is_word(line, index) {
+            named(rest)
+        } else {
+            None
+        };
+
+        match swatch {
+            Some((length, color, notation)) => {
+                swatches.push(Swatch {
+                    range: index..index + length,
+                    color,
+                    notation,
+                });
+
+                index += length;
+            }
+            None => {
+                index += rest.chars().next().map_or(1, char::len_utf8);
+            }
+        }
+    }
+
+    swatches
+}
+
+/// The color under a cursor, with `column` counted in characters like the editor's cursor.
+pub fn at(line: &str, column: usize) -> Option<Swatch> {
+    let offset = line
+        .char_indices()
+        .nth(column)
+        .map_or(line.len(), |(index, _)| index);
+
+    find(line)
+        .into_iter()
+        .find(|swatch| swatch.range.start <= offset && offset <= swatch.range.end)
+}
+
+pub fn format(color: Color, notation: Notation) -> String {
+    let [r, g, b, a] = color.into_rgba8();
+
+    match notation {
+        Notation::Rgb if a < 255 => format!("rgba({r}, {g}, {b}, {:.2})", color.a),
+        Notation::Rgb => format!("rgb({r}, {g}, {b})"),
+        Notation::Hex | Notation::Named if a < 255 => format!("#{r:02x}{g:02x}{b:02x}{a:02x}"),
+        Notation::Hex | Notation::Named => format!("#{r:02x}{g:02x}{b:02x}"),
+    }
+}
+
+fn is_word(line: &str, index: usize) -> bool {
+    line[..index]
+        .chars()
+        .next_back()
+        .is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '-')
+}
+
+fn hex(text: &str) -> Option<(usize, Color, Notation)> {
+    let digits: String = text[1..]
+        .chars()
+        .take_while(char::is_ascii_hexdigit)
+        .collect();
+
+    if text[1 + digits.len()..]
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_alphanumeric() || c == '_')
+    {
+        return None;
+    }
+
+    let channel = |range: Range<usize>| u8::from_str_radix(&digits[range], 16).ok();
+    let short = |index: usize| channel(index..index + 1).map(|value| value * 17);
+
+    let [r, g, b, a] = match digits.len() {
+        3 => [short(0)?, short(1)?, short(2)?, 255],
+        4 => [short(0)?, short(1)?, short(2)?, short(3)?],
+        6 => [channel(0..2)?, channel(2..4)?, channel(4..6)?, 255],
+        8 => [
+            channel(0..2)?,
+            channel(2..4)?,
+            channel(4..6)?,
+            channel(6..8)?,
+        ],
+        _ => return None,
+    };
+
+    Some((
+        digits.len() + 1,
+        Color::from_rgba8(r, g, b, f32::from(a) / 255.0),
+        Notation::Hex,
+    ))
+}
+
+fn rgb(text: &str) -> Option<(usize, Color, Notation)> {
+    let open = text.find('(')?;
+    let close = text.find(')')?;
+
+    if !matches!(&text[..open], "rgb" | "rgba") || close < open {
+        return None;
+    }
+
+    let values: Vec<f32> = text[open + 1..close]
+        .split([',', ' ', '/'])
+        .filter(|value| !value.is_empty())
+        .map(|value| value.trim().parse().ok())
+        .collect::<Option<_>>()?;
+
+    let channel = |value: f32| value.clamp(0.0, 255.0) as u8;
+
+    let color = match values.as_slice() {
+        [r, g, b] => Color::from_rgb8(channel(*r), channel(*g), channel(*b)),
+        [r, g, b, a] => Color::from_rgba8(channel(*r), channel(*g), channel(*b), a.clamp(0.0, 1.0)),
+        _ => return None,
+    };
+
+    Some((close + 1, color, Notation::Rgb))
+}
+
+fn named(text: &str) -> Option<(usize, Color, Notation)> {
+    let word: &str = text
+        .split(|c: char| !c.is_ascii_alphabetic())
+        .next()
+        .unwrap_or_default();
+
+    let (_, [r, g, b]) = NAMED
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(word))?;
+
+    Some((word.len(), Color::from_rgb8(*r, *g, *b), Notation::Named))
+}
+
+pub fn swatch<'a>(color: Color, on_press: Message) -> Element<'a, Message> {
+    button(Space::new(14, 14))
+        .on_press(on_press)
+        .padding(0)
+        .style(theme::Button::Custom(Box::new(Fill(color))))
+        .into()
+}
+
+/// The colors used in the file, each next to a sample of it.
+pub fn overview(swatches: &[(usize, Swatch)]) -> Element<'_, Message> {
+    let entries = swatches.iter().map(|(line, swatch)| {
+        row![
+            swatch_preview(swatch.color),
+            button(
+                text(format!(
+                    "{}: {}",
+                    line + 1,
+                    format(swatch.color, swatch.notation)
+                ))
+                .size(13)
+            )
+            .on_press(Message::ColorSelected(*line, swatch.range.start))
+            .width(Length::Fill)
+            .padding([2, 4])
+            .style(theme::Button::Text),
+        ]
+        .spacing(5)
+        .into()
+    });
+
+    let list: Element<_> = if swatches.is_empty() {
+        text("No colors in this file").size(14).into()
+    } else {
+        scrollable(Column::with_children(entries.collect()))
+            .height(Length::Fill)
+            .into()
+    };
+
+    container(
+        column![
+            text("Colors").size(16),
+            list,
+            button(text("Close").size(14))
+                .on_press(Message::ClosePanel)
+                .style(theme::Button::Secondary),
+        ]
+        .spacing(10),
+    )
+    .width(Length::Fill)
+    .padding(10)
+    .style(theme::Container::Box)
+    .into()
+}
+
+fn swatch_preview<'a>(color: Color) -> Element<'a, Message> {
+    container(Space::new(14, 14))
+        .style(theme::Container::Custom(Box::new(Fill(color))))
+        .into()
+}
+
+pub fn view(picker: &Picker) -> Element<'_, Message> {
+    let color = picker.color;
+
+    let channel = |label: &'static str, value: f32, update: fn(Color, f32) -> Color| {
+        row![
+            text(label).size(14).width(20),
+            slider(0.0..=1.0, value, move |value| Message::SwatchChanged(
+                update(color, value)
+            ))
+            .step(1.0 / 255.0),
+        ]
+        .spacing(10)
+    };
+
+    container(
+        column![
+            row![
+                text(picker.value()).size(14),
+                Space::with_width(Length::Fill),
+                swatch(color, Message::ApplySwatch),
+            ],
+            channel("R", color.r, |color, r| Color { r, ..color }),
+            channel("G", color.g, |color, g| Color { g, ..color }),
+            channel("B", color.b, |color, b| Color { b, ..color }),
+            channel("A", color.a, |color, a| Color { a, ..color }),
+            row![
+                button(text("Apply").size(14)).on_press(Message::ApplySwatch),
+                button(text("Cancel").size(14))
+                    .on_press(Message::ClosePanel)
+                    .style(theme::Button::Secondary),
+            ]
+            .spacing(10),
+        ]
+        .spacing(10),
+    )
+    .width(Length::Fill)
+    .padding(10)
+    .style(theme::Container::Box)
+    .into()
+}
+
+struct Fill(Color);
+
+impl container::StyleSheet for Fill {
+    type Style = Theme;
+
+    fn appearance(&self, _style: &Self::Style) -> container::Appearance {
+        container::Appearance {
+            background: Some(Background::Color(self.0)),
+            border_radius: 3.0.into(),
+            border_width: 1.0,
+            border_color: Color::from_rgb(0.5, 0.5, 0.5),
+            ..container::Appearance::default()
+        }
+    }
+}
+
+impl button::StyleSheet for Fill {
+    type Style = Theme;
+
+    fn active(&self, _style: &Self::Style) -> button::Appearance {
+        button::Appearance {
+            background: Some(Background::Color(self.0)),
+            border_radius: 3.0.into(),
+            border_width: 1.0,
+            border_color: Color::from_rgb(0.5, 0.5, 0.5),
+            ..button::Appearance::default()
+        }
+    }
+}
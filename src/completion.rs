@@ -0,0 +1,107 @@
+use crate::{assistant, Error};
+
+use iced::Color;
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+
+/// How long typing has to pause before a continuation is requested.
+pub const DELAY: Duration = Duration::from_millis(600);
+
+pub const GHOST_COLOR: Color = Color::from_rgba(0.5, 0.5, 0.5, 0.8);
+
+// Characters before the cursor sent as the prompt
+const CONTEXT: usize = 4000;
+const MAX_TOKENS: u32 = 48;
+
+#[derive(Debug, Default)]
+pub struct Completion {
+    pub typed_at: Option<Instant>,
+    pub request: usize,
+    pub ghost: Option<Ghost>,
+}
+
+/// A suggestion for the cursor's position, kept out of the buffer until it is accepted.
+///
+/// The editor widget cannot draw text that is not in its content, so it is previewed in
+/// the status bar instead of inline.
+#[derive(Debug, Clone)]
+pub struct Ghost {
+    pub line: usize,
+    pub column: usize,
+    pub text: String,
+}
+
+impl Completion {
+    pub fn is_due(&self) -> bool {
+        self.typed_at
+            .is_some_and(|typed_at| typed_at.elapsed() >= DELAY)
+    }
+}
+
+/// The text before the cursor, trimmed to the context the model is given.
+pub fn prompt(text: &str, offset: usize) -> String {
+    let before = &text[..offset.min(text.len())];
+    let skip = before.chars().count().saturating_sub(CONTEXT);
+
+    before.chars().skip(skip).collect()
+}
+
+#[derive(Deserialize)]
+struct Response {
+    choices: Vec<Choice>,
+}
+
+#[derive(Deserialize)]
+struct Choice {
+    text: String,
+}
+
+/// Asks an OpenAI-compatible completions endpoint to continue the prompt.
+///
+/// Only the rest of the current line is kept, so the suggestion fits inline.
+pub async fn complete(
+    endpoint: String,
+    model: Option<String>,
+    prompt: String,
+) -> Result<String, Error> {
+    let mut body = serde_json::json!({
+        "prompt": prompt,
+        "max_tokens": MAX_TOKENS,
+        "temperature": 0.2,
+        "stop": ["\n"],
+    });
+
+    if let Some(model) = model {
+        body["model"] = serde_json::Value::String(model);
+    }
+
+    let url = format!("{}/completions", endpoint.trim_end_matches('/'));
+    let mut builder = reqwest::Client::new().post(url).json(&body);
+
+    if let Some(key) = assistant::load_key(endpoint).await {
+        builder = builder.bearer_auth(key);
+    }
+
+    let response: Response = builder
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|error| Error::RemoteFailed(error.to_string()))?
+        .json()
+        .await
+        .map_err(|error| Error::RemoteFailed(error.to_string()))?;
+
+    let text = response
+        .choices
+        .into_iter()
+        .next()
+        .map(|choice| choice.text)
+        .unwrap_or_default();
+
+    Ok(text
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .trim_end()
+        .to_string())
+}
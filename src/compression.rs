@@ -0,0 +1,77 @@
+use crate::encryption;
+
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Gzip,
+    Zstd,
+    Xz,
+}
+
+impl Format {
+    pub fn detect(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "gz" => Some(Format::Gzip),
+            "zst" => Some(Format::Zstd),
+            "xz" => Some(Format::Xz),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Format::Gzip => "gzip",
+            Format::Zstd => "zstd",
+            Format::Xz => "xz",
+        }
+    }
+
+    pub fn decompress(self, bytes: &[u8]) -> io::Result<Vec<u8>> {
+        let mut output = Vec::new();
+
+        match self {
+            Format::Gzip => {
+                flate2::read::MultiGzDecoder::new(bytes).read_to_end(&mut output)?;
+            }
+            Format::Zstd => {
+                output = zstd::decode_all(bytes)?;
+            }
+            Format::Xz => {
+                xz2::read::XzDecoder::new_multi_decoder(bytes).read_to_end(&mut output)?;
+            }
+        }
+
+        Ok(output)
+    }
+
+    pub fn compress(self, bytes: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            Format::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(bytes)?;
+                encoder.finish()
+            }
+            Format::Zstd => zstd::encode_all(bytes, 0),
+            Format::Xz => {
+                let mut encoder = xz2::write::XzEncoder::new(Vec::new(), 6);
+                encoder.write_all(bytes)?;
+                encoder.finish()
+            }
+        }
+    }
+}
+
+pub fn extension(path: &Path) -> Option<&str> {
+    let is_wrapped = Format::detect(path).is_some() || encryption::Format::detect(path).is_some();
+
+    let path = if is_wrapped {
+        Path::new(path.file_stem()?)
+    } else {
+        path
+    };
+
+    path.extension()?.to_str()
+}
@@ -0,0 +1,210 @@
+use crate::{assistant, memory, preview, status, toolbar, Error};
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::PathBuf;
+
+const MAX_RECENT_WORKSPACES: usize = 10;
+const FOCUS_WIDTH: u16 = 720;
+const QUICK_NOTE_HOTKEY: &str = "ctrl+alt+KeyN";
+const MATH_RENDERER: &str = "tex2svg";
+const DIAGRAM_RENDERER: &str = "mmdc --input - --output - --outputFormat svg --quiet";
+const MIN_WINDOW_OPACITY: f32 = 0.2;
+const BACKGROUND_DIM: f32 = 0.8;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub sync_endpoint: Option<String>,
+    pub recent_workspaces: Vec<PathBuf>,
+    pub date_format: Option<String>,
+    pub subword_deletion: bool,
+    /// Typing an opening bracket or quote adds its closing one after the cursor.
+    pub auto_pairs: bool,
+    pub subword_navigation: bool,
+    pub focus_width: Option<u16>,
+    pub dim_paragraphs: bool,
+    pub typewriter_scrolling: bool,
+    /// Unset follows the desktop's reduced-motion setting.
+    pub reduce_motion: Option<bool>,
+    pub wrap_column: Option<u16>,
+    pub tray_icon: bool,
+    pub minimize_to_tray: bool,
+    /// Set to an empty string to turn the quick-note hotkey off.
+    pub quick_note_hotkey: Option<String>,
+    pub lock_files: bool,
+    /// Show who last changed the cursor's line in the status bar.
+    pub inline_blame: bool,
+    /// Remember the SHA-256 of saved files and warn when they change elsewhere.
+    pub record_checksums: bool,
+    pub prose_mode: bool,
+    /// A multiple of the platform's normal speaking rate.
+    pub speech_rate: Option<f32>,
+    pub speech_voice: Option<String>,
+    /// Gets a 16 kHz mono WAV file as its last argument and prints the transcript.
+    pub dictation_command: Option<String>,
+    /// Preview converter commands keyed by format, e.g. `rst` or `asciidoc`.
+    pub preview_converters: BTreeMap<String, String>,
+    /// Set to an empty string to show display math as plain TeX.
+    pub math_renderer: Option<String>,
+    /// Set to an empty string to show Mermaid diagrams as plain source.
+    pub diagram_renderer: Option<String>,
+    /// Base URL of an OpenAI-compatible API, e.g. `https://api.openai.com/v1`.
+    pub assistant_endpoint: Option<String>,
+    pub assistant_model: Option<String>,
+    /// Completions endpoint for inline suggestions; they stay off while this is unset.
+    pub completion_endpoint: Option<String>,
+    pub completion_model: Option<String>,
+    /// Files whose possible secrets were marked as expected.
+    pub secrets_ignored: Vec<PathBuf>,
+    /// Status bar segments in the order they show; any left out follow in their usual place.
+    pub status_order: Vec<status::Slot>,
+    pub status_hidden: Vec<status::Slot>,
+    /// Toolbar buttons in order; unset keeps the usual ones.
+    pub toolbar: Option<Vec<toolbar::Button>>,
+    pub toolbar_style: toolbar::Style,
+    /// Bytes of buffers to keep before unloading background tabs; 0 never unloads them.
+    pub memory_budget: Option<u64>,
+    /// Database connections by name, as `sqlite://`, `postgres://` or `mysql://` URLs.
+    pub databases: BTreeMap<String, String>,
+    /// Commands that run a code cell given on stdin, keyed by extension, e.g. `rs = "evcxr"`.
+    pub cell_runners: BTreeMap<String, String>,
+    /// Below 1.0 the desktop shows through the window; turning it on takes a restart.
+    pub window_opacity: Option<f32>,
+    /// An image drawn behind the editor text.
+    pub background_image: Option<PathBuf>,
+    /// How far the background image is faded toward the theme's background, from 0.0 to 1.0.
+    pub background_dim: Option<f32>,
+}
+
+pub fn directory() -> Option<PathBuf> {
+    dirs::config_dir().map(|directory| directory.join("bonkitor"))
+}
+
+fn file() -> Option<PathBuf> {
+    directory().map(|directory| directory.join("config.toml"))
+}
+
+impl Config {
+    pub fn memory_budget(&self) -> Option<u64> {
+        match self.memory_budget {
+            None => Some(memory::DEFAULT_BUDGET),
+            Some(0) => None,
+            Some(budget) => Some(budget),
+        }
+    }
+
+    pub fn focus_width(&self) -> u16 {
+        self.focus_width.unwrap_or(FOCUS_WIDTH)
+    }
+
+    pub fn quick_note_hotkey(&self) -> Option<&str> {
+        match self.quick_note_hotkey.as_deref() {
+            None => Some(QUICK_NOTE_HOTKEY),
+            Some("") => None,
+            Some(accelerator) => Some(accelerator),
+        }
+    }
+
+    pub fn toolbar(&self) -> &[toolbar::Button] {
+        self.toolbar.as_deref().unwrap_or(&toolbar::Button::DEFAULT)
+    }
+
+    pub fn window_opacity(&self) -> f32 {
+        self.window_opacity
+            .unwrap_or(1.0)
+            .clamp(MIN_WINDOW_OPACITY, 1.0)
+    }
+
+    pub fn background_dim(&self) -> f32 {
+        self.background_dim
+            .unwrap_or(BACKGROUND_DIM)
+            .clamp(0.0, 1.0)
+    }
+
+    /// Whether the editor should leave its own background undrawn.
+    pub fn is_see_through(&self) -> bool {
+        self.window_opacity() < 1.0 || self.background_image.is_some()
+    }
+
+    pub fn speech_rate(&self) -> f32 {
+        self.speech_rate.unwrap_or(1.0)
+    }
+
+    pub fn assistant_model(&self) -> &str {
+        self.assistant_model
+            .as_deref()
+            .unwrap_or(assistant::DEFAULT_MODEL)
+    }
+
+    pub fn math_renderer(&self) -> Option<&str> {
+        match self.math_renderer.as_deref() {
+            None => Some(MATH_RENDERER),
+            Some("") => None,
+            Some(command) => Some(command),
+        }
+    }
+
+    pub fn diagram_renderer(&self) -> Option<&str> {
+        match self.diagram_renderer.as_deref() {
+            None => Some(DIAGRAM_RENDERER),
+            Some("") => None,
+            Some(command) => Some(command),
+        }
+    }
+
+    pub fn preview_converter(&self, format: preview::Format) -> Option<String> {
+        self.preview_converters
+            .get(format.key())
+            .map(String::as_str)
+            .or(format.default_converter())
+            .map(str::to_string)
+    }
+
+    pub fn add_recent_workspace(&mut self, root: PathBuf) {
+        self.recent_workspaces.retain(|recent| recent != &root);
+        self.recent_workspaces.insert(0, root);
+        self.recent_workspaces.truncate(MAX_RECENT_WORKSPACES);
+    }
+
+    /// Reads the file right away, for settings needed before the window opens.
+    ///
+    /// Errors are left for the usual load to report.
+    pub fn read() -> Self {
+        file()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|text| toml::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    pub async fn load() -> Result<Self, Error> {
+        let Some(path) = file() else {
+            return Ok(Self::default());
+        };
+
+        match tokio::fs::read_to_string(&path).await {
+            Ok(text) => {
+                toml::from_str(&text).map_err(|error| Error::InvalidConfig(error.to_string()))
+            }
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(Error::IOFailed(error.kind())),
+        }
+    }
+
+    pub async fn save(self) -> Result<(), Error> {
+        let path = file().ok_or(Error::IOFailed(io::ErrorKind::NotFound))?;
+        let text = toml::to_string_pretty(&self)
+            .map_err(|error| Error::InvalidConfig(error.to_string()))?;
+
+        if let Some(directory) = path.parent() {
+            tokio::fs::create_dir_all(directory)
+                .await
+                .map_err(|error| Error::IOFailed(error.kind()))?;
+        }
+
+        tokio::fs::write(&path, text)
+            .await
+            .map_err(|error| Error::IOFailed(error.kind()))
+    }
+}
@@ -0,0 +1,74 @@
+use crate::{Error, Message};
+
+use iced::theme;
+use iced::widget::{button, column, container, row, text};
+use iced::{Element, Length};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Overwrite,
+    Reload,
+    /// Compare the buffer with the file on disk, then save again to keep the buffer
+    Merge,
+}
+
+/// When the file was last written, as the editor will compare it before saving.
+pub async fn modified(path: PathBuf) -> (PathBuf, Option<SystemTime>) {
+    let time = tokio::fs::metadata(&path)
+        .await
+        .and_then(|metadata| metadata.modified())
+        .ok();
+
+    (path, time)
+}
+
+/// Fails if the file was written since `expected`, by something other than the editor.
+///
+/// A file that has gone or can't be read passes, since saving recreates it.
+pub async fn check(path: &Path, expected: Option<SystemTime>) -> Result<(), Error> {
+    let Some(expected) = expected else {
+        return Ok(());
+    };
+
+    match modified(path.to_path_buf()).await {
+        (_, Some(current)) if current != expected => Err(Error::Conflict(path.to_path_buf())),
+        _ => Ok(()),
+    }
+}
+
+pub fn view(path: &Path) -> Element<'_, Message> {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string());
+
+    let choice = |label, resolution| {
+        button(text(label).size(14)).on_press(Message::ResolveConflict(resolution))
+    };
+
+    container(
+        column![
+            text("Changed on disk").size(16),
+            text(format!(
+                "{name} was changed by another program after it was opened. Saving now would replace those changes."
+            ))
+            .size(14),
+            row![
+                choice("Overwrite", Resolution::Overwrite).style(theme::Button::Destructive),
+                choice("Reload", Resolution::Reload),
+                choice("Compare", Resolution::Merge),
+                button(text("Cancel").size(14))
+                    .on_press(Message::ClosePanel)
+                    .style(theme::Button::Secondary),
+            ]
+            .spacing(10),
+        ]
+        .spacing(10),
+    )
+    .width(Length::Fill)
+    .padding(10)
+    .style(theme::Container::Box)
+    .into()
+}
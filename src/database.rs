@@ -0,0 +1,276 @@
+use crate::{Error, Message};
+
+use iced::futures::TryStreamExt;
+use iced::theme;
+use iced::widget::scrollable::{Direction, Properties};
+use iced::widget::{button, column, container, row, scrollable, text, Column, Row};
+use iced::{Element, Length};
+use sqlx::any::{Any, AnyConnection, AnyRow};
+use sqlx::{Column as _, Connection, Either, Executor, Row as _};
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+const PAGE_SIZE: usize = 100;
+/// Rows past this are dropped, so a careless `SELECT *` can't fill memory.
+const MAX_ROWS: usize = 10_000;
+const COLUMN_WIDTH: f32 = 160.0;
+
+pub fn is_sql(extension: &str) -> bool {
+    extension.eq_ignore_ascii_case("sql")
+}
+
+/// The statement around `offset`, split at semicolons outside quotes and `--` comments.
+pub fn statement_at(text: &str, offset: usize) -> Option<Range<usize>> {
+    let mut statements = Vec::new();
+    let mut start = 0;
+    let mut quote = None;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((index, character)) = chars.next() {
+        match (quote, character) {
+            (Some(open), _) => {
+                if character == open {
+                    quote = None;
+                }
+            }
+            (None, '\'' | '"' | '`') => quote = Some(character),
+            (None, '-') if chars.peek().is_some_and(|(_, next)| *next == '-') => {
+                for (_, character) in chars.by_ref() {
+                    if character == '\n' {
+                        break;
+                    }
+                }
+            }
+            (None, ';') => {
+                statements.push(start..index);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+
+    statements.push(start..text.len());
+
+    let trim = |range: &Range<usize>| {
+        let statement = &text[range.clone()];
+        let start = range.start + (statement.len() - statement.trim_start().len());
+
+        (start < range.start + statement.trim_end().len())
+            .then(|| start..range.start + statement.trim_end().len())
+    };
+
+    // Right after a semicolon, the statement it ends is the one meant
+    let index = statements
+        .iter()
+        .position(|range| offset <= range.end + 1)?;
+
+    trim(&statements[index]).or_else(|| trim(statements.get(index.checked_sub(1)?)?))
+}
+
+#[derive(Debug, Clone)]
+pub struct Results {
+    pub connection: String,
+    statement: String,
+    columns: Vec<String>,
+    rows: Vec<Vec<String>>,
+    affected: u64,
+    is_truncated: bool,
+    elapsed: Duration,
+    page: usize,
+}
+
+pub async fn run(connection: String, url: String, statement: String) -> Result<Results, Error> {
+    let failed = |error: sqlx::Error| Error::RemoteFailed(error.to_string());
+
+    sqlx::any::install_default_drivers();
+
+    let started = Instant::now();
+    let mut database = AnyConnection::connect(&url).await.map_err(failed)?;
+
+    let mut results = Results {
+        connection,
+        statement,
+        columns: Vec::new(),
+        rows: Vec::new(),
+        affected: 0,
+        is_truncated: false,
+        elapsed: Duration::ZERO,
+        page: 0,
+    };
+
+    {
+        let mut stream = database.fetch_many(results.statement.as_str());
+
+        while let Some(item) = stream.try_next().await.map_err(failed)? {
+            match item {
+                Either::Left(done) => results.affected += done.rows_affected(),
+                Either::Right(row) => {
+                    if results.rows.len() == MAX_ROWS {
+                        results.is_truncated = true;
+                        break;
+                    }
+
+                    if results.columns.is_empty() {
+                        results.columns = row
+                            .columns()
+                            .iter()
+                            .map(|column| column.name().to_string())
+                            .collect();
+                    }
+
+                    results
+                        .rows
+                        .push((0..row.len()).map(|index| cell(&row, index)).collect());
+                }
+            }
+        }
+    }
+
+    results.elapsed = started.elapsed();
+    let _ = database.close().await;
+
+    Ok(results)
+}
+
+/// A value as text, trying each type the `Any` driver can decode.
+fn cell(row: &AnyRow, index: usize) -> String {
+    fn get<T>(row: &AnyRow, index: usize) -> Option<Option<String>>
+    where
+        T: for<'r> sqlx::Decode<'r, Any> + sqlx::Type<Any> + ToString,
+    {
+        row.try_get::<Option<T>, _>(index)
+            .ok()
+            .map(|value| value.as_ref().map(T::to_string))
+    }
+
+    get::<String>(row, index)
+        .or_else(|| get::<i64>(row, index))
+        .or_else(|| get::<i32>(row, index))
+        .or_else(|| get::<i16>(row, index))
+        .or_else(|| get::<f64>(row, index))
+        .or_else(|| get::<f32>(row, index))
+        .or_else(|| get::<bool>(row, index))
+        .map(|value| value.unwrap_or_else(|| String::from("NULL")))
+        .or_else(|| {
+            row.try_get::<Vec<u8>, _>(index)
+                .ok()
+                .map(|bytes| format!("<{} bytes>", bytes.len()))
+        })
+        .unwrap_or_else(|| String::from("?"))
+}
+
+impl Results {
+    fn pages(&self) -> usize {
+        self.rows.len().div_ceil(PAGE_SIZE).max(1)
+    }
+
+    pub fn show_page(&mut self, page: usize) {
+        self.page = page.min(self.pages() - 1);
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut writer = csv::WriterBuilder::new()
+            .flexible(true)
+            .from_writer(Vec::new());
+
+        let _ = writer.write_record(&self.columns);
+
+        for row in &self.rows {
+            let _ = writer.write_record(row);
+        }
+
+        writer
+            .into_inner()
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let summary = if self.columns.is_empty() {
+            format!(
+                "{} rows affected in {} ms",
+                self.affected,
+                self.elapsed.as_millis()
+            )
+        } else {
+            format!(
+                "{}{} rows in {} ms",
+                self.rows.len(),
+                if self.is_truncated { "+" } else { "" },
+                self.elapsed.as_millis()
+            )
+        };
+
+        let cells = |cells: &'_ [String], size| {
+            Row::with_children(
+                cells
+                    .iter()
+                    .map(|cell| text(cell).size(size).width(COLUMN_WIDTH).into())
+                    .collect(),
+            )
+            .spacing(4)
+        };
+
+        let rows = self
+            .rows
+            .iter()
+            .skip(self.page * PAGE_SIZE)
+            .take(PAGE_SIZE)
+            .fold(Column::new().spacing(2), |rows, row| {
+                rows.push(cells(row, 13))
+            });
+
+        let grid = scrollable(column![cells(&self.columns, 14), rows].spacing(4))
+            .direction(Direction::Both {
+                vertical: Properties::default(),
+                horizontal: Properties::default(),
+            })
+            .width(Length::Fill)
+            .height(Length::Fill);
+
+        container(
+            column![
+                text(format!("{}: {summary}", self.connection)).size(16),
+                text(self.statement.lines().next().unwrap_or_default()).size(13),
+                grid,
+                row![
+                    button(text("Previous").size(14)).on_press_maybe(
+                        (self.page > 0).then(|| Message::ResultsPage(self.page - 1))
+                    ),
+                    text(format!("Page {} of {}", self.page + 1, self.pages())).size(14),
+                    button(text("Next").size(14)).on_press_maybe(
+                        (self.page + 1 < self.pages()).then(|| Message::ResultsPage(self.page + 1))
+                    ),
+                    button(text("Export CSV...").size(14))
+                        .on_press_maybe((!self.columns.is_empty()).then_some(Message::ExportQuery)),
+                    button(text("Close").size(14))
+                        .on_press(Message::ClosePanel)
+                        .style(theme::Button::Secondary),
+                ]
+                .spacing(10),
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fill)
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
+
+pub fn running<'a>(connection: &str) -> Element<'a, Message> {
+    container(
+        column![
+            text(format!("{connection}: running...")).size(16),
+            button(text("Close").size(14))
+                .on_press(Message::ClosePanel)
+                .style(theme::Button::Secondary),
+        ]
+        .spacing(10),
+    )
+    .width(Length::Fill)
+    .padding(10)
+    .style(theme::Container::Box)
+    .into()
+}
@@ -0,0 +1,78 @@
+use crate::Message;
+
+use iced::{time, Subscription};
+use std::time::{Duration, Instant};
+
+/// How long typing has to pause before derived state catches up.
+const DELAY: Duration = Duration::from_millis(250);
+/// The longest derived state lags behind while typing never pauses.
+const MAX_WAIT: Duration = Duration::from_secs(2);
+
+/// Derived state that is too costly to rebuild on every keystroke.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Job {
+    Words,
+    Outline,
+    Colors,
+    Preview,
+    Statistics,
+    Changes,
+    Calculation,
+}
+
+/// Jobs waiting for typing to pause, each run once however many edits asked for it.
+#[derive(Debug, Default)]
+pub struct Pending {
+    jobs: Vec<Job>,
+    first: Option<Instant>,
+    last: Option<Instant>,
+}
+
+impl Pending {
+    pub fn schedule(&mut self, job: Job) {
+        let now = Instant::now();
+
+        if !self.jobs.contains(&job) {
+            self.jobs.push(job);
+        }
+
+        self.first.get_or_insert(now);
+        self.last = Some(now);
+    }
+
+    pub fn is_waiting(&self) -> bool {
+        !self.jobs.is_empty()
+    }
+
+    /// The jobs to run now, once typing has paused or has gone on for too long.
+    pub fn take_due(&mut self) -> Vec<Job> {
+        let is_due = self.last.is_some_and(|last| last.elapsed() >= DELAY)
+            || self.first.is_some_and(|first| first.elapsed() >= MAX_WAIT);
+
+        if is_due {
+            self.first = None;
+            self.last = None;
+
+            std::mem::take(&mut self.jobs)
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Removes the job so it can be run straight away, returning whether it was waiting.
+    pub fn take(&mut self, job: Job) -> bool {
+        let was_waiting = self.jobs.contains(&job);
+        self.jobs.retain(|other| *other != job);
+
+        if self.jobs.is_empty() {
+            self.first = None;
+            self.last = None;
+        }
+
+        was_waiting
+    }
+}
+
+pub fn subscription() -> Subscription<Message> {
+    time::every(Duration::from_millis(50)).map(|_| Message::RecomputeTick)
+}
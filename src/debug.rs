@@ -0,0 +1,667 @@
+use crate::{workspace, Message};
+
+use iced::futures::channel::mpsc;
+use iced::futures::{SinkExt, StreamExt};
+use iced::theme;
+use iced::widget::{button, column, container, row, scrollable, text, text_input, Column};
+use iced::{subscription, Color, Element, Length, Subscription};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::process::ChildStdin;
+
+pub const BREAKPOINT_COLOR: Color = Color::from_rgb(0.9, 0.3, 0.3);
+pub const STOPPED_COLOR: Color = Color::from_rgb(0.95, 0.8, 0.2);
+
+const MAX_MESSAGE: usize = 64 * 1024 * 1024;
+const MAX_CONSOLE: usize = 500;
+const CONSOLE_LINES: usize = 8;
+
+/// Lines with a breakpoint, counted from zero, by file.
+pub type Breakpoints = BTreeMap<PathBuf, BTreeSet<usize>>;
+
+/// How to start one debug adapter, from a `[debug.<name>]` table in the workspace settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Configuration {
+    /// A command speaking the Debug Adapter Protocol on stdio, like `python -m debugpy.adapter`
+    pub adapter: String,
+    /// Either `launch` or `attach`
+    #[serde(default = "launch")]
+    pub request: String,
+    /// Passed as they are to the adapter, which documents what it takes
+    #[serde(default)]
+    pub arguments: Map<String, Value>,
+}
+
+fn launch() -> String {
+    String::from("launch")
+}
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    Ready(mpsc::Sender<Value>),
+    Received(Value),
+    Exited(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Control {
+    Continue,
+    Pause,
+    StepOver,
+    StepIn,
+    StepOut,
+    Stop,
+}
+
+/// What a message from the adapter means for the editor.
+pub enum Outcome {
+    Nothing,
+    /// Stopped at a file and a line counted from zero
+    Stopped(PathBuf, usize),
+    Ended(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Starting,
+    Running,
+    Stopped(i64),
+    Ending,
+}
+
+struct Scope {
+    name: String,
+    variables: Vec<(String, String)>,
+}
+
+struct Watch {
+    expression: String,
+    value: Option<String>,
+}
+
+pub struct Session {
+    pub name: String,
+    configuration: Configuration,
+    root: PathBuf,
+    id: u64,
+    sender: Option<mpsc::Sender<Value>>,
+    /// Messages written before the adapter was running
+    queued: Vec<Value>,
+    seq: i64,
+    state: State,
+    reason: String,
+    thread: Option<i64>,
+    frame: Option<i64>,
+    /// Where the program is stopped, with the line counted from zero
+    pub location: Option<(PathBuf, usize)>,
+    scopes: Vec<Scope>,
+    watches: Vec<Watch>,
+    /// Outstanding `variables` and `evaluate` requests, by sequence number
+    scope_requests: BTreeMap<i64, usize>,
+    watch_requests: BTreeMap<i64, usize>,
+    pub new_watch: String,
+    console: Vec<String>,
+}
+
+impl Session {
+    pub fn new(id: u64, name: String, configuration: Configuration, root: PathBuf) -> Self {
+        let mut session = Self {
+            name,
+            configuration,
+            root,
+            id,
+            sender: None,
+            queued: Vec::new(),
+            seq: 0,
+            state: State::Starting,
+            reason: String::new(),
+            thread: None,
+            frame: None,
+            location: None,
+            scopes: Vec::new(),
+            watches: Vec::new(),
+            scope_requests: BTreeMap::new(),
+            watch_requests: BTreeMap::new(),
+            new_watch: String::new(),
+            console: Vec::new(),
+        };
+
+        let adapter = session.name.clone();
+
+        session.request(
+            "initialize",
+            json!({
+                "clientID": "bonkitor",
+                "clientName": "Bonkitor",
+                "adapterID": adapter,
+                "linesStartAt1": true,
+                "columnsStartAt1": true,
+                "pathFormat": "path",
+            }),
+        );
+
+        session
+    }
+
+    pub fn is_ending(&self) -> bool {
+        self.state == State::Ending
+    }
+
+    pub fn connect(&mut self, sender: mpsc::Sender<Value>) {
+        self.sender = Some(sender);
+
+        for message in std::mem::take(&mut self.queued) {
+            self.send(message);
+        }
+    }
+
+    /// Sends a file's breakpoints, replacing the ones the adapter had for it.
+    pub fn set_breakpoints(&mut self, path: &Path, lines: &BTreeSet<usize>) {
+        // Everything is sent at once when the adapter says it is initialized
+        if self.state == State::Starting {
+            return;
+        }
+
+        let breakpoints: Vec<Value> = lines
+            .iter()
+            .map(|line| json!({ "line": line + 1 }))
+            .collect();
+
+        self.request(
+            "setBreakpoints",
+            json!({
+                "source": {
+                    "name": path.file_name().map(|name| name.to_string_lossy()),
+                    "path": path.to_string_lossy(),
+                },
+                "breakpoints": breakpoints,
+            }),
+        );
+    }
+
+    pub fn control(&mut self, control: Control) {
+        match (control, self.state) {
+            (Control::Stop, _) => {
+                self.state = State::Ending;
+                self.request("disconnect", json!({ "terminateDebuggee": true }));
+            }
+            (Control::Pause, State::Running) => {
+                if let Some(thread) = self.thread {
+                    self.request("pause", json!({ "threadId": thread }));
+                }
+            }
+            (Control::Continue, State::Stopped(thread)) => self.resume("continue", thread),
+            (Control::StepOver, State::Stopped(thread)) => self.resume("next", thread),
+            (Control::StepIn, State::Stopped(thread)) => self.resume("stepIn", thread),
+            (Control::StepOut, State::Stopped(thread)) => self.resume("stepOut", thread),
+            _ => {}
+        }
+    }
+
+    pub fn add_watch(&mut self) {
+        let expression = self.new_watch.trim().to_string();
+
+        if expression.is_empty() {
+            return;
+        }
+
+        self.new_watch.clear();
+        self.watches.push(Watch {
+            expression,
+            value: None,
+        });
+
+        self.evaluate(self.watches.len() - 1);
+    }
+
+    pub fn remove_watch(&mut self, index: usize) {
+        if index < self.watches.len() {
+            self.watches.remove(index);
+            self.watch_requests.clear();
+        }
+    }
+
+    pub fn handle(&mut self, message: Value, breakpoints: &Breakpoints) -> Outcome {
+        match message["type"].as_str() {
+            Some("response") => self.response(&message),
+            Some("event") => self.event(&message, breakpoints),
+            Some("request") => {
+                // Reverse requests like `runInTerminal` are not supported, so say so
+                self.send(json!({
+                    "seq": 0,
+                    "type": "response",
+                    "request_seq": message["seq"],
+                    "command": message["command"],
+                    "success": false,
+                    "message": "not supported",
+                }));
+
+                Outcome::Nothing
+            }
+            _ => Outcome::Nothing,
+        }
+    }
+
+    fn response(&mut self, message: &Value) -> Outcome {
+        let command = message["command"].as_str().unwrap_or_default();
+        let request = message["request_seq"].as_i64().unwrap_or_default();
+        let body = &message["body"];
+
+        if !message["success"].as_bool().unwrap_or(false) {
+            let error = message["message"]
+                .as_str()
+                .or_else(|| body["error"]["format"].as_str())
+                .unwrap_or("failed")
+                .to_string();
+
+            if let Some(index) = self.watch_requests.remove(&request) {
+                if let Some(watch) = self.watches.get_mut(index) {
+                    watch.value = Some(error);
+                }
+
+                return Outcome::Nothing;
+            }
+
+            return match command {
+                "initialize" | "launch" | "attach" => {
+                    Outcome::Ended(format!("The debugger could not start: {error}"))
+                }
+                "disconnect" => Outcome::Ended(String::from("Debugging ended")),
+                _ => {
+                    self.print(format!("{command}: {error}"));
+                    Outcome::Nothing
+                }
+            };
+        }
+
+        match command {
+            "initialize" => {
+                let request = self.configuration.request.clone();
+                let arguments = Value::Object(self.configuration.arguments.clone());
+
+                self.request(&request, arguments);
+            }
+            "disconnect" => return Outcome::Ended(String::from("Debugging ended")),
+            "stackTrace" => {
+                let Some(frame) = body["stackFrames"].get(0) else {
+                    return Outcome::Nothing;
+                };
+
+                self.frame = frame["id"].as_i64();
+                self.scopes.clear();
+                self.scope_requests.clear();
+
+                if let Some(frame) = self.frame {
+                    self.request("scopes", json!({ "frameId": frame }));
+                }
+
+                for index in 0..self.watches.len() {
+                    self.evaluate(index);
+                }
+
+                let path = frame["source"]["path"].as_str().map(PathBuf::from);
+                let line = frame["line"].as_u64().unwrap_or(1) as usize;
+
+                if let Some(path) = path {
+                    self.location = Some((path.clone(), line.saturating_sub(1)));
+
+                    return Outcome::Stopped(path, line.saturating_sub(1));
+                }
+            }
+            "scopes" => {
+                for scope in body["scopes"].as_array().into_iter().flatten() {
+                    let reference = scope["variablesReference"].as_i64().unwrap_or_default();
+
+                    self.scopes.push(Scope {
+                        name: scope["name"].as_str().unwrap_or("Scope").to_string(),
+                        variables: Vec::new(),
+                    });
+
+                    if reference > 0 && !scope["expensive"].as_bool().unwrap_or(false) {
+                        let seq =
+                            self.request("variables", json!({ "variablesReference": reference }));
+
+                        self.scope_requests.insert(seq, self.scopes.len() - 1);
+                    }
+                }
+            }
+            "variables" => {
+                if let Some(scope) = self
+                    .scope_requests
+                    .remove(&request)
+                    .and_then(|index| self.scopes.get_mut(index))
+                {
+                    scope.variables = body["variables"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .map(|variable| {
+                            (
+                                variable["name"].as_str().unwrap_or_default().to_string(),
+                                variable["value"].as_str().unwrap_or_default().to_string(),
+                            )
+                        })
+                        .collect();
+                }
+            }
+            "evaluate" => {
+                if let Some(watch) = self
+                    .watch_requests
+                    .remove(&request)
+                    .and_then(|index| self.watches.get_mut(index))
+                {
+                    watch.value = body["result"].as_str().map(str::to_string);
+                }
+            }
+            _ => {}
+        }
+
+        Outcome::Nothing
+    }
+
+    fn event(&mut self, message: &Value, breakpoints: &Breakpoints) -> Outcome {
+        let body = &message["body"];
+
+        match message["event"].as_str().unwrap_or_default() {
+            "initialized" => {
+                self.state = State::Running;
+
+                for (path, lines) in breakpoints {
+                    self.set_breakpoints(path, lines);
+                }
+
+                self.request("configurationDone", json!({}));
+            }
+            "stopped" => {
+                let thread = body["threadId"].as_i64().or(self.thread).unwrap_or(1);
+
+                self.thread = Some(thread);
+                self.state = State::Stopped(thread);
+                self.reason = body["reason"].as_str().unwrap_or("paused").to_string();
+
+                self.request(
+                    "stackTrace",
+                    json!({ "threadId": thread, "startFrame": 0, "levels": 1 }),
+                );
+            }
+            "continued" => {
+                if self.state != State::Ending {
+                    self.state = State::Running;
+                    self.location = None;
+                }
+            }
+            "thread" => {
+                if self.thread.is_none() && body["reason"] == "started" {
+                    self.thread = body["threadId"].as_i64();
+                }
+            }
+            "output" => {
+                if let Some(output) = body["output"].as_str() {
+                    for line in output.lines() {
+                        self.print(line.to_string());
+                    }
+                }
+            }
+            "exited" => {
+                if let Some(code) = body["exitCode"].as_i64() {
+                    self.print(format!("The program exited with code {code}"));
+                }
+            }
+            "terminated" => return Outcome::Ended(String::from("The program ended")),
+            _ => {}
+        }
+
+        Outcome::Nothing
+    }
+
+    fn resume(&mut self, command: &str, thread: i64) {
+        self.state = State::Running;
+        self.location = None;
+        self.frame = None;
+
+        self.request(command, json!({ "threadId": thread }));
+    }
+
+    fn evaluate(&mut self, index: usize) {
+        let (Some(frame), Some(watch)) = (self.frame, self.watches.get(index)) else {
+            return;
+        };
+
+        let expression = watch.expression.clone();
+
+        let seq = self.request(
+            "evaluate",
+            json!({ "expression": expression, "frameId": frame, "context": "watch" }),
+        );
+
+        self.watch_requests.insert(seq, index);
+    }
+
+    fn request(&mut self, command: &str, arguments: Value) -> i64 {
+        self.seq += 1;
+
+        self.send(json!({
+            "seq": self.seq,
+            "type": "request",
+            "command": command,
+            "arguments": arguments,
+        }));
+
+        self.seq
+    }
+
+    fn send(&mut self, message: Value) {
+        match &mut self.sender {
+            Some(sender) => {
+                let _ = sender.try_send(message);
+            }
+            None => self.queued.push(message),
+        }
+    }
+
+    fn print(&mut self, line: String) {
+        self.console.push(line);
+
+        if self.console.len() > MAX_CONSOLE {
+            self.console.remove(0);
+        }
+    }
+
+    /// Runs the adapter while the subscription is kept, killing it once dropped.
+    pub fn subscription(&self) -> Subscription<Event> {
+        let (id, adapter, root) = (
+            self.id,
+            self.configuration.adapter.clone(),
+            self.root.clone(),
+        );
+
+        subscription::channel(("debug", id), 100, move |mut output| async move {
+            let spawned = workspace::shell(&adapter)
+                .current_dir(&root)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .kill_on_drop(true)
+                .spawn();
+
+            let reason = match spawned {
+                Ok(mut child) => match (child.stdin.take(), child.stdout.take()) {
+                    (Some(stdin), Some(stdout)) => {
+                        let (sender, messages) = mpsc::channel(100);
+
+                        tokio::spawn(write(stdin, messages));
+                        let _ = output.send(Event::Ready(sender)).await;
+
+                        let mut reader = BufReader::new(stdout);
+
+                        loop {
+                            match read(&mut reader).await {
+                                Ok(message) => {
+                                    let _ = output.send(Event::Received(message)).await;
+                                }
+                                Err(error) => break format!("The debug adapter stopped: {error}"),
+                            }
+                        }
+                    }
+                    _ => String::from("The debug adapter could not be reached"),
+                },
+                Err(error) => format!("The debug adapter could not start: {error}"),
+            };
+
+            let _ = output.send(Event::Exited(reason)).await;
+
+            loop {
+                std::future::pending::<()>().await;
+            }
+        })
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let status = match self.state {
+            State::Starting => String::from("starting"),
+            State::Running => String::from("running"),
+            State::Stopped(_) => format!("stopped ({})", self.reason),
+            State::Ending => String::from("stopping"),
+        };
+
+        let is_stopped = matches!(self.state, State::Stopped(_));
+
+        let control = |label, control, enabled: bool| {
+            button(text(label).size(14))
+                .on_press_maybe(enabled.then_some(Message::DebugControl(control)))
+        };
+
+        let location = match &self.location {
+            Some((path, line)) => {
+                let path = path.strip_prefix(&self.root).unwrap_or(path);
+
+                format!("{}:{}", path.display(), line + 1)
+            }
+            None => String::new(),
+        };
+
+        let mut details = Column::new().spacing(4);
+
+        for scope in &self.scopes {
+            details = details.push(text(&scope.name).size(14));
+
+            for (name, value) in &scope.variables {
+                details = details.push(text(format!("  {name} = {value}")).size(13));
+            }
+        }
+
+        details = details.push(text("Watch").size(14));
+
+        for (index, watch) in self.watches.iter().enumerate() {
+            details = details.push(
+                row![
+                    text(format!(
+                        "{} = {}",
+                        watch.expression,
+                        watch.value.as_deref().unwrap_or("?")
+                    ))
+                    .size(13)
+                    .width(Length::Fill),
+                    button(text("Remove").size(13))
+                        .on_press(Message::RemoveWatch(index))
+                        .style(theme::Button::Text),
+                ]
+                .spacing(10),
+            );
+        }
+
+        let console = self.console[self.console.len().saturating_sub(CONSOLE_LINES)..]
+            .iter()
+            .fold(Column::new(), |console, line| {
+                console.push(text(line).size(13))
+            });
+
+        container(
+            column![
+                text(format!("Debugging {}: {status}", self.name)).size(16),
+                row![
+                    control("Continue", Control::Continue, is_stopped),
+                    control("Pause", Control::Pause, self.state == State::Running),
+                    control("Step over", Control::StepOver, is_stopped),
+                    control("Step in", Control::StepIn, is_stopped),
+                    control("Step out", Control::StepOut, is_stopped),
+                    button(text("Stop").size(14))
+                        .on_press(Message::DebugControl(Control::Stop))
+                        .style(theme::Button::Destructive),
+                ]
+                .spacing(10),
+                text(location).size(13),
+                scrollable(details).height(Length::Fill),
+                row![
+                    text_input("Watch an expression", &self.new_watch)
+                        .on_input(Message::WatchChanged)
+                        .on_submit(Message::AddWatch),
+                    button(text("Add").size(14)).on_press(Message::AddWatch),
+                ]
+                .spacing(10),
+                console,
+                button(text("Close").size(14))
+                    .on_press(Message::ClosePanel)
+                    .style(theme::Button::Secondary),
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fill)
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
+
+/// Reads one message, framed by a `Content-Length` header.
+async fn read(reader: &mut (impl AsyncBufRead + Unpin)) -> io::Result<Value> {
+    let mut length = None;
+
+    loop {
+        let mut header = String::new();
+
+        if reader.read_line(&mut header).await? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "it closed its output",
+            ));
+        }
+
+        let header = header.trim();
+
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let length = length
+        .filter(|length| *length <= MAX_MESSAGE)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "a message had no length"))?;
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).await?;
+
+    serde_json::from_slice(&body).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+async fn write(mut stdin: ChildStdin, mut messages: mpsc::Receiver<Value>) {
+    while let Some(message) = messages.next().await {
+        let body = message.to_string();
+        let frame = format!("Content-Length: {}\r\n\r\n{body}", body.len());
+
+        if stdin.write_all(frame.as_bytes()).await.is_err() || stdin.flush().await.is_err() {
+            break;
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use crate::Error;
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SampleFormat, SizedSample};
+use std::io;
+use std::sync::{Arc, Mutex};
+
+// The rate speech models such as whisper expect
+const SAMPLE_RATE: u32 = 16_000;
+
+pub struct Recording {
+    _stream: cpal::Stream,
+    samples: Arc<Mutex<Vec<f32>>>,
+    rate: u32,
+}
+
+impl Recording {
+    pub fn start() -> Result<Self, String> {
+        let device = cpal::default_host()
+            .default_input_device()
+            .ok_or_else(|| String::from("no microphone found"))?;
+
+        let config = device
+            .default_input_config()
+            .map_err(|error| error.to_string())?;
+
+        let channels = usize::from(config.channels());
+        let rate = config.sample_rate().0;
+        let samples = Arc::new(Mutex::new(Vec::new()));
+
+        let stream = match config.sample_format() {
+            SampleFormat::F32 => build::<f32>(&device, &config.into(), channels, &samples),
+            SampleFormat::I16 => build::<i16>(&device, &config.into(), channels, &samples),
+            SampleFormat::U16 => build::<u16>(&device, &config.into(), channels, &samples),
+            format => return Err(format!("unsupported sample format {format:?}")),
+        }
+        .map_err(|error| error.to_string())?;
+
+        stream.play().map_err(|error| error.to_string())?;
+
+        Ok(Self {
+            _stream: stream,
+            samples,
+            rate,
+        })
+    }
+
+    /// Stops recording and returns the audio as a 16 kHz mono WAV file.
+    pub fn finish(self) -> Vec<u8> {
+        let samples = self
+            .samples
+            .lock()
+            .map(|samples| samples.clone())
+            .unwrap_or_default();
+
+        wav(&resample(&samples, self.rate))
+    }
+}
+
+/// Runs the command with a WAV file as its last argument and returns what it prints.
+pub async fn transcribe(command: String, audio: Vec<u8>) -> Result<String, Error> {
+    let io = |error: io::Error| Error::IOFailed(error.kind());
+
+    let path =
+        std::env::temp_dir().join(format!("bonkitor-dictation-{}.wav", uuid::Uuid::new_v4()));
+
+    tokio::fs::write(&path, audio).await.map_err(io)?;
+
+    let mut parts = command.split_whitespace();
+    let program = parts
+        .next()
+        .ok_or(Error::IOFailed(io::ErrorKind::NotFound))?;
+
+    let output = tokio::process::Command::new(program)
+        .args(parts)
+        .arg(&path)
+        .output()
+        .await;
+
+    let _ = tokio::fs::remove_file(&path).await;
+
+    let output = output.map_err(io)?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(Error::RemoteFailed(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+fn build<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    channels: usize,
+    samples: &Arc<Mutex<Vec<f32>>>,
+) -> Result<cpal::Stream, cpal::BuildStreamError>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let samples = samples.clone();
+
+    device.build_input_stream(
+        config,
+        move |data: &[T], _: &cpal::InputCallbackInfo| {
+            if let Ok(mut samples) = samples.lock() {
+                // Mix every frame down to mono
+                samples.extend(data.chunks(channels).map(|frame| {
+                    frame
+                        .iter()
+                        .map(|sample| f32::from_sample(*sample))
+                        .sum::<f32>()
+                        / channels as f32
+                }));
+            }
+        },
+        |_| {},
+        None,
+    )
+}
+
+fn resample(samples: &[f32], rate: u32) -> Vec<f32> {
+    if rate == SAMPLE_RATE || samples.is_empty() {
+        return samples.to_vec();
+    }
+
+    let step = rate as f64 / SAMPLE_RATE as f64;
+    let length = (samples.len() as f64 / step) as usize;
+
+    (0..length)
+        .map(|index| {
+            let position = index as f64 * step;
+            let before = position as usize;
+            let after = (before + 1).min(samples.len() - 1);
+            let weight = (position - before as f64) as f32;
+
+            samples[before] * (1.0 - weight) + samples[after] * weight
+        })
+        .collect()
+}
+
+fn wav(samples: &[f32]) -> Vec<u8> {
+    let data = (samples.len() * 2) as u32;
+    let mut bytes = Vec::with_capacity(44 + data as usize);
+
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    bytes.extend_from_slice(&1u16.to_le_bytes()); // mono
+    bytes.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    bytes.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    bytes.extend_from_slice(&2u16.to_le_bytes());
+    bytes.extend_from_slice(&16u16.to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data.to_le_bytes());
+
+    for sample in samples {
+        let sample = (sample.clamp(-1.0, 1.0) * f32::from(i16::MAX)) as i16;
+
+        bytes.extend_from_slice(&sample.to_le_bytes());
+    }
+
+    bytes
+}
@@ -0,0 +1,119 @@
+use crate::{widgets, Message};
+
+use iced::theme;
+use iced::widget::{button, column, container, scrollable, text};
+use iced::{Color, Element, Length};
+use similar::{ChangeTag, TextDiff};
+
+#[derive(Debug, Clone)]
+pub struct Diff {
+    lines: Vec<(ChangeTag, String)>,
+    inserted: usize,
+    deleted: usize,
+    sides: Sides,
+    /// How far the lines are scrolled, in pixels
+    offset: f32,
+}
+
+const LINE_HEIGHT: f32 = 18.0;
+
+/// What the two texts being compared are called.
+#[derive(Debug, Clone, Copy)]
+pub struct Sides {
+    pub title: &'static str,
+    pub old: &'static str,
+    pub new: &'static str,
+}
+
+impl Diff {
+    pub fn compare(selection: &str, clipboard: &str) -> Self {
+        Self::between(
+            selection,
+            clipboard,
+            Sides {
+                title: "Selection vs. clipboard",
+                old: "selection",
+                new: "clipboard",
+            },
+        )
+    }
+
+    pub fn between(old: &str, new: &str, sides: Sides) -> Self {
+        let lines: Vec<_> = TextDiff::from_lines(old, new)
+            .iter_all_changes()
+            .map(|change| {
+                (
+                    change.tag(),
+                    change.value().trim_end_matches(['\r', '\n']).to_string(),
+                )
+            })
+            .collect();
+
+        let count = |tag| lines.iter().filter(|(kind, _)| *kind == tag).count();
+
+        Self {
+            inserted: count(ChangeTag::Insert),
+            deleted: count(ChangeTag::Delete),
+            lines,
+            sides,
+            offset: 0.0,
+        }
+    }
+
+    pub fn scrolled(&mut self, viewport: scrollable::Viewport) {
+        self.offset = viewport.absolute_offset().y;
+    }
+
+    pub fn view(&self, height: f32) -> Element<'_, Message> {
+        let Sides { title, old, new } = self.sides;
+
+        let summary = if self.inserted == 0 && self.deleted == 0 {
+            format!("The {old} and {new} are identical")
+        } else {
+            format!(
+                "{} lines only in the {new}, {} only in the {old}",
+                self.inserted, self.deleted
+            )
+        };
+
+        let line = |index: usize| -> Element<'_, Message> {
+            let (tag, line) = &self.lines[index];
+
+            let (sign, color) = match tag {
+                ChangeTag::Equal => (' ', None),
+                ChangeTag::Delete => ('-', Some(Color::from_rgb(0.9, 0.3, 0.3))),
+                ChangeTag::Insert => ('+', Some(Color::from_rgb(0.4, 0.75, 0.45))),
+            };
+
+            let line = text(format!("{sign} {line}")).size(13);
+
+            match color {
+                Some(color) => line.style(color).into(),
+                None => line.into(),
+            }
+        };
+
+        container(
+            column![
+                text(title).size(16),
+                text(summary).size(14),
+                widgets::virtual_list(
+                    self.lines.len(),
+                    LINE_HEIGHT,
+                    self.offset,
+                    height,
+                    line,
+                    Message::DiffScrolled,
+                ),
+                button(text("Close").size(14))
+                    .on_press(Message::ClosePanel)
+                    .style(theme::Button::Secondary),
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fill)
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
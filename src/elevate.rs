@@ -0,0 +1,80 @@
+use crate::Error;
+
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+pub async fn write(path: PathBuf, bytes: Vec<u8>) -> Result<PathBuf, Error> {
+    // The copy runs as root and follows whatever sits at the path it is given, so the
+    // bytes are staged in a fresh file only this user can reach, never at a guessable name
+    let (staged, directory) = tokio::task::spawn_blocking(move || {
+        let directory = tempfile::Builder::new().prefix("bonkitor-").tempdir()?;
+        let mut staged = tempfile::NamedTempFile::new_in(directory.path())?;
+
+        staged.write_all(&bytes)?;
+        staged.flush()?;
+
+        Ok::<_, io::Error>((staged, directory))
+    })
+    .await
+    .map_err(|_| Error::IOFailed(io::ErrorKind::Other))?
+    .map_err(|error| Error::IOFailed(error.kind()))?;
+
+    let result = copy(staged.path(), &path).await;
+
+    drop(staged);
+    drop(directory);
+
+    result.map(|()| path)
+}
+
+#[cfg(target_os = "linux")]
+async fn copy(from: &Path, to: &Path) -> Result<(), Error> {
+    run(Command::new("pkexec").arg("cp").arg(from).arg(to)).await
+}
+
+#[cfg(target_os = "macos")]
+async fn copy(from: &Path, to: &Path) -> Result<(), Error> {
+    let script = format!(
+        "do shell script \"cp \" & quoted form of {:?} & \" \" & quoted form of {:?} with administrator privileges",
+        from.display().to_string(),
+        to.display().to_string(),
+    );
+
+    run(Command::new("osascript").arg("-e").arg(script)).await
+}
+
+#[cfg(windows)]
+async fn copy(from: &Path, to: &Path) -> Result<(), Error> {
+    let quote = |path: &Path| path.display().to_string().replace('\'', "''");
+
+    let script = format!(
+        "$p = Start-Process -FilePath cmd -ArgumentList '/c copy /y \"{}\" \"{}\"' -Verb RunAs -Wait -PassThru; exit $p.ExitCode",
+        quote(from),
+        quote(to),
+    );
+
+    run(Command::new("powershell")
+        .args(["-NoProfile", "-Command"])
+        .arg(script))
+    .await
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+async fn copy(_from: &Path, _to: &Path) -> Result<(), Error> {
+    Err(Error::IOFailed(io::ErrorKind::Unsupported))
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos", windows))]
+async fn run(command: &mut Command) -> Result<(), Error> {
+    let status = command
+        .status()
+        .await
+        .map_err(|error| Error::IOFailed(error.kind()))?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(Error::IOFailed(io::ErrorKind::PermissionDenied))
+    }
+}
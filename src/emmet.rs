@@ -0,0 +1,583 @@
+use std::ops::Range;
+use std::path::Path;
+
+const INDENT: &str = "    ";
+const VOID_ELEMENTS: &[&str] = &["area", "br", "col", "hr", "img", "input", "link", "meta"];
+const DEFAULT_ATTRIBUTES: &[(&str, &[&str])] = &[
+    ("a", &["href"]),
+    ("img", &["src", "alt"]),
+    ("link", &["rel", "href"]),
+    ("input", &["type"]),
+    ("label", &["for"]),
+    ("form", &["action"]),
+];
+const CSS_PROPERTIES: &[(&str, &str)] = &[
+    ("bg", "background"),
+    ("bgc", "background-color"),
+    ("bd", "border"),
+    ("bdrs", "border-radius"),
+    ("c", "color"),
+    ("d", "display"),
+    ("fz", "font-size"),
+    ("fw", "font-weight"),
+    ("h", "height"),
+    ("lh", "line-height"),
+    ("m", "margin"),
+    ("mt", "margin-top"),
+    ("mr", "margin-right"),
+    ("mb", "margin-bottom"),
+    ("ml", "margin-left"),
+    ("maw", "max-width"),
+    ("mah", "max-height"),
+    ("miw", "min-width"),
+    ("mih", "min-height"),
+    ("op", "opacity"),
+    ("p", "padding"),
+    ("pt", "padding-top"),
+    ("pr", "padding-right"),
+    ("pb", "padding-bottom"),
+    ("pl", "padding-left"),
+    ("pos", "position"),
+    ("t", "top"),
+    ("r", "right"),
+    ("b", "bottom"),
+    ("l", "left"),
+    ("ta", "text-align"),
+    ("w", "width"),
+    ("z", "z-index"),
+];
+const CSS_KEYWORDS: &[(&str, &str)] = &[
+    ("db", "display: block;"),
+    ("dib", "display: inline-block;"),
+    ("di", "display: inline;"),
+    ("df", "display: flex;"),
+    ("dg", "display: grid;"),
+    ("dn", "display: none;"),
+    ("posa", "position: absolute;"),
+    ("posf", "position: fixed;"),
+    ("posr", "position: relative;"),
+    ("poss", "position: sticky;"),
+    ("tac", "text-align: center;"),
+    ("tal", "text-align: left;"),
+    ("tar", "text-align: right;"),
+    ("fwb", "font-weight: bold;"),
+    ("fwn", "font-weight: normal;"),
+    ("ma", "margin: auto;"),
+];
+const UNITLESS: &[&str] = &["font-weight", "line-height", "opacity", "z-index"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Syntax {
+    Html,
+    Css,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expansion {
+    pub text: String,
+    pub cursor: (usize, usize),
+}
+
+pub fn syntax(path: &Path) -> Option<Syntax> {
+    match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+        "html" | "htm" | "xhtml" | "vue" | "svelte" => Some(Syntax::Html),
+        "css" | "scss" | "less" => Some(Syntax::Css),
+        _ => None,
+    }
+}
+
+pub fn abbreviation(line: &str, column: usize) -> Option<Range<usize>> {
+    let end = line
+        .char_indices()
+        .nth(column)
+        .map_or(line.len(), |(index, _)| index);
+
+    let before = &line[..end];
+    let mut depth = 0;
+
+    let start = before
+        .char_indices()
+        .rev()
+        .find(|(_, c)| {
+            match c {
+                '}' | ']' => depth += 1,
+                '{' | '[' => depth -= 1,
+                _ => {}
+            }
+
+            depth == 0 && (c.is_whitespace() || matches!(c, ';' | '"' | '\'' | '<'))
+        })
+        .map_or(0, |(index, c)| index + c.len_utf8());
+
+    (start < end).then_some(start..end)
+}
+
+pub fn expand(abbreviation: &str, syntax: Syntax, indent: &str) -> Option<Expansion> {
+    match syntax {
+        Syntax::Html => {
+            let mut parser = Parser {
+                input: abbreviation.chars().collect(),
+                position: 0,
+            };
+
+            let nodes = parser.siblings()?;
+
+            if parser.position < parser.input.len() {
+                return None;
+            }
+
+            let mut output = Output {
+                text: String::new(),
+                indent: indent.to_string(),
+                cursor: None,
+            };
+
+            for (index, node) in nodes.iter().enumerate() {
+                if index > 0 {
+                    output.newline(0);
+                }
+
+                node.render(&mut output, 0, None);
+            }
+
+            let cursor = output.cursor.unwrap_or_else(|| output.position());
+
+            Some(Expansion {
+                text: output.text,
+                cursor,
+            })
+        }
+        Syntax::Css => css(abbreviation),
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+    tag: String,
+    id: Option<String>,
+    classes: Vec<String>,
+    attributes: Vec<(String, String)>,
+    text: Option<String>,
+    children: Vec<Node>,
+    index: Option<usize>,
+}
+
+impl Node {
+    fn render(&self, output: &mut Output, depth: usize, index: Option<usize>) {
+        let index = self.index.or(index);
+        let number = |value: &str| numbered(value, index);
+
+        let mut attributes = Vec::new();
+
+        if let Some(id) = &self.id {
+            attributes.push(format!("id=\"{}\"", number(id)));
+        }
+
+        if !self.classes.is_empty() {
+            let classes: Vec<_> = self.classes.iter().map(|class| number(class)).collect();
+
+            attributes.push(format!("class=\"{}\"", classes.join(" ")));
+        }
+
+        for (name, value) in &self.attributes {
+            attributes.push(format!("{}=\"{}\"", number(name), number(value)));
+        }
+
+        let defaults = DEFAULT_ATTRIBUTES
+            .iter()
+            .find(|(tag, _)| *tag == self.tag)
+            .map(|(_, names)| *names)
+            .unwrap_or_default();
+
+        let mut open = format!("<{}", self.tag);
+
+        for attribute in &attributes {
+            open.push(' ');
+            open.push_str(attribute);
+        }
+
+        for name in defaults.iter().filter(|name| {
+            !self
+                .attributes
+                .iter()
+                .any(|(existing, _)| existing == *name)
+        }) {
+            open.push_str(&format!(" {name}=\""));
+            output.text.push_str(&open);
+            output.mark();
+            open = String::from("\"");
+        }
+
+        open.push('>');
+        output.text.push_str(&open);
+
+        if VOID_ELEMENTS.contains(&self.tag.as_str()) {
+            return;
+        }
+
+        if let Some(text) = &self.text {
+            output.text.push_str(&number(text));
+        }
+
+        if self.children.is_empty() {
+            if self.text.is_none() {
+                output.mark();
+            }
+        } else {
+            for child in &self.children {
+                output.newline(depth + 1);
+                child.render(output, depth + 1, index);
+            }
+
+            output.newline(depth);
+        }
+
+        output.text.push_str(&format!("</{}>", self.tag));
+    }
+}
+
+struct Output {
+    text: String,
+    indent: String,
+    cursor: Option<(usize, usize)>,
+}
+
+impl Output {
+    fn newline(&mut self, depth: usize) {
+        self.text.push('\n');
+        self.text.push_str(&self.indent);
+        self.text.push_str(&INDENT.repeat(depth));
+    }
+
+    fn position(&self) -> (usize, usize) {
+        let line = self.text.matches('\n').count();
+        let column = self.text.len() - self.text.rfind('\n').map_or(0, |index| index + 1);
+
+        (line, column)
+    }
+
+    fn mark(&mut self) {
+        if self.cursor.is_none() {
+            self.cursor = Some(self.position());
+        }
+    }
+}
+
+struct Parser {
+    input: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.input.get(self.position).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += 1;
+        Some(c)
+    }
+
+    fn eat(&mut self, wanted: char) -> bool {
+        if self.peek() == Some(wanted) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn siblings(&mut self) -> Option<Vec<Node>> {
+        let mut nodes = Vec::new();
+
+        loop {
+            let (sequence, _) = self.sequence()?;
+            nodes.extend(sequence);
+
+            if !matches!(self.peek(), Some(c) if c != ')') {
+                return Some(nodes);
+            }
+        }
+    }
+
+    fn sequence(&mut self) -> Option<(Vec<Node>, usize)> {
+        let mut nodes = Vec::new();
+
+        loop {
+            let mut items = self.item()?;
+
+            match self.peek() {
+                Some('>') => {
+                    self.bump();
+
+                    let (children, climb) = self.sequence()?;
+
+                    for item in &mut items {
+                        item.children.extend(children.clone());
+                    }
+
+                    nodes.extend(items);
+
+                    match climb {
+                        0 => return Some((nodes, 0)),
+                        1 => {}
+                        climb => return Some((nodes, climb - 1)),
+                    }
+                }
+                Some('+') => {
+                    self.bump();
+                    nodes.extend(items);
+                }
+                Some('^') => {
+                    let mut climb = 0;
+
+                    while self.eat('^') {
+                        climb += 1;
+                    }
+
+                    nodes.extend(items);
+
+                    return Some((nodes, climb));
+                }
+                _ => {
+                    nodes.extend(items);
+
+                    return Some((nodes, 0));
+                }
+            }
+        }
+    }
+
+    fn item(&mut self) -> Option<Vec<Node>> {
+        if self.eat('(') {
+            let group = self.siblings()?;
+
+            if !self.eat(')') {
+                return None;
+            }
+
+            let count = self.multiplier()?;
+
+            return Some(
+                (1..=count)
+                    .flat_map(|index| {
+                        group.iter().cloned().map(move |mut node| {
+                            if count > 1 {
+                                node.index = Some(index);
+                            }
+
+                            node
+                        })
+                    })
+                    .collect(),
+            );
+        }
+
+        let mut node = Node {
+            tag: self.take_while(|c| c.is_ascii_alphanumeric() || matches!(c, ':' | '-' | '!')),
+            ..Node::default()
+        };
+
+        loop {
+            match self.peek() {
+                Some('#') => {
+                    self.bump();
+                    node.id = Some(self.name());
+                }
+                Some('.') => {
+                    self.bump();
+                    node.classes.push(self.name());
+                }
+                Some('[') => {
+                    self.bump();
+                    node.attributes.extend(self.attributes()?);
+                }
+                Some('{') => {
+                    self.bump();
+                    let text = self.take_while(|c| c != '}');
+
+                    if !self.eat('}') {
+                        return None;
+                    }
+
+                    node.text = Some(text);
+                }
+                _ => break,
+            }
+        }
+
+        if node.tag.is_empty() {
+            if node.id.is_none()
+                && node.classes.is_empty()
+                && node.attributes.is_empty()
+                && node.text.is_none()
+            {
+                return None;
+            }
+
+            node.tag = String::from("div");
+        }
+
+        let count = self.multiplier()?;
+
+        Some(
+            (1..=count)
+                .map(|index| Node {
+                    index: (count > 1).then_some(index),
+                    ..node.clone()
+                })
+                .collect(),
+        )
+    }
+
+    fn multiplier(&mut self) -> Option<usize> {
+        if !self.eat('*') {
+            return Some(1);
+        }
+
+        self.take_while(|c| c.is_ascii_digit())
+            .parse()
+            .ok()
+            .filter(|count| *count > 0)
+    }
+
+    fn name(&mut self) -> String {
+        self.take_while(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '$'))
+    }
+
+    fn attributes(&mut self) -> Option<Vec<(String, String)>> {
+        let mut attributes = Vec::new();
+
+        loop {
+            self.take_while(char::is_whitespace);
+
+            if self.eat(']') {
+                return Some(attributes);
+            }
+
+            let name = self.take_while(|c| !matches!(c, '=' | ']') && !c.is_whitespace());
+
+            if name.is_empty() {
+                return None;
+            }
+
+            let value = if self.eat('=') {
+                match self.peek() {
+                    Some(quote @ ('"' | '\'')) => {
+                        self.bump();
+                        let value = self.take_while(|c| c != quote);
+                        self.bump()?;
+                        value
+                    }
+                    _ => self.take_while(|c| c != ']' && !c.is_whitespace()),
+                }
+            } else {
+                String::new()
+            };
+
+            attributes.push((name, value));
+        }
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> String {
+        let start = self.position;
+
+        while self.peek().is_some_and(&predicate) {
+            self.position += 1;
+        }
+
+        self.input[start..self.position].iter().collect()
+    }
+}
+
+fn numbered(value: &str, index: Option<usize>) -> String {
+    let Some(index) = index else {
+        return value.to_string();
+    };
+
+    let mut result = String::new();
+    let mut characters = value.chars().peekable();
+
+    while let Some(c) = characters.next() {
+        if c == '$' {
+            let mut width = 1;
+
+            while characters.next_if_eq(&'$').is_some() {
+                width += 1;
+            }
+
+            result.push_str(&format!("{index:0width$}"));
+        } else {
+            result.push(c);
+        }
+    }
+
+    result
+}
+
+fn css(abbreviation: &str) -> Option<Expansion> {
+    if let Some((_, keyword)) = CSS_KEYWORDS.iter().find(|(name, _)| *name == abbreviation) {
+        return Some(Expansion {
+            text: keyword.to_string(),
+            cursor: (0, keyword.len()),
+        });
+    }
+
+    let split = abbreviation
+        .find(|c: char| c.is_ascii_digit() || matches!(c, '#' | '-' | '.'))
+        .unwrap_or(abbreviation.len());
+
+    let (name, value) = abbreviation.split_at(split);
+    let (_, property) = CSS_PROPERTIES.iter().find(|(short, _)| *short == name)?;
+
+    if value.is_empty() {
+        let text = format!("{property}: ;");
+
+        return Some(Expansion {
+            cursor: (0, text.len() - 1),
+            text,
+        });
+    }
+
+    let value = if value.starts_with('#') {
+        value.to_string()
+    } else {
+        value
+            .split('-')
+            .filter(|part| !part.is_empty())
+            .map(|part| css_value(part, property))
+            .collect::<Option<Vec<_>>>()?
+            .join(" ")
+    };
+
+    let text = format!("{property}: {value};");
+
+    Some(Expansion {
+        cursor: (0, text.len()),
+        text,
+    })
+}
+
+fn css_value(part: &str, property: &str) -> Option<String> {
+    let digits = part
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(part.len());
+    let (number, unit) = part.split_at(digits);
+
+    if number.is_empty() {
+        return None;
+    }
+
+    let unit = match unit {
+        "" if UNITLESS.contains(&property) || number == "0" => "",
+        "" => "px",
+        "p" => "%",
+        "e" => "em",
+        "r" => "rem",
+        "x" => "ex",
+        unit => unit,
+    };
+
+    Some(format!("{number}{unit}"))
+}
@@ -0,0 +1,148 @@
+use crate::{config, Error};
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+
+/// The age private key, kept in the config directory but never synced with it.
+pub const AGE_IDENTITY: &str = "age.txt";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Gpg,
+    Age,
+}
+
+impl Format {
+    pub fn detect(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            // Not .asc, which is as often a public key or a signature as a message
+            "gpg" | "pgp" => Some(Format::Gpg),
+            "age" => Some(Format::Age),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Format::Gpg => "GPG encrypted",
+            Format::Age => "age encrypted",
+        }
+    }
+
+    /// Decrypts through the format's command line tool, so the plaintext never touches disk.
+    ///
+    /// GPG asks its agent for the key, which prompts for a passphrase through pinentry.
+    pub async fn decrypt(self, bytes: Vec<u8>) -> Result<Vec<u8>, Error> {
+        match self {
+            Format::Gpg => run("gpg", &["--quiet", "--yes", "--decrypt"], bytes).await,
+            Format::Age => {
+                let identity = age_identity()?;
+
+                run("age", &["--decrypt", "--identity", &identity], bytes).await
+            }
+        }
+    }
+
+    /// Encrypts for the same recipients the file at `path` was encrypted for.
+    ///
+    /// New GPG files and ones without recipients are encrypted with a passphrase.
+    pub async fn encrypt(self, path: &Path, bytes: Vec<u8>) -> Result<Vec<u8>, Error> {
+        match self {
+            Format::Gpg => {
+                let recipients = recipients(path).await;
+                let mut args = vec!["--quiet", "--yes"];
+
+                if recipients.is_empty() {
+                    args.push("--symmetric");
+                } else {
+                    args.push("--encrypt");
+
+                    for recipient in &recipients {
+                        args.extend(["--recipient", recipient]);
+                    }
+                }
+
+                run("gpg", &args, bytes).await
+            }
+            Format::Age => {
+                let identity = age_identity()?;
+
+                run("age", &["--encrypt", "--identity", &identity], bytes).await
+            }
+        }
+    }
+}
+
+// The key IDs of the public keys a file is encrypted to
+async fn recipients(path: &Path) -> Vec<String> {
+    let Ok(output) = tokio::process::Command::new("gpg")
+        .args(["--batch", "--list-packets", "--list-only"])
+        .arg(path)
+        .stdin(Stdio::null())
+        .output()
+        .await
+    else {
+        return Vec::new();
+    };
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| line.starts_with(":pubkey enc packet:"))
+        .filter_map(|line| line.split("keyid ").nth(1))
+        .map(|id| id.trim().to_string())
+        .collect()
+}
+
+fn age_identity() -> Result<String, Error> {
+    let path: PathBuf = config::directory()
+        .map(|directory| directory.join(AGE_IDENTITY))
+        .ok_or(Error::IOFailed(io::ErrorKind::NotFound))?;
+
+    if !path.exists() {
+        return Err(Error::RemoteFailed(format!(
+            "age needs an identity file at {}",
+            path.display()
+        )));
+    }
+
+    Ok(path.display().to_string())
+}
+
+async fn run(program: &str, args: &[&str], input: Vec<u8>) -> Result<Vec<u8>, Error> {
+    let io = |error: io::Error| Error::IOFailed(error.kind());
+
+    let mut child = tokio::process::Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(io)?;
+
+    // Feed the input while the output is read, so neither pipe fills up
+    let writer = child.stdin.take().map(|mut stdin| {
+        tokio::spawn(async move {
+            stdin.write_all(&input).await?;
+            stdin.shutdown().await
+        })
+    });
+
+    let output = child.wait_with_output().await.map_err(io)?;
+
+    if !output.status.success() {
+        return Err(Error::RemoteFailed(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+
+    if let Some(writer) = writer {
+        writer
+            .await
+            .map_err(|_| Error::IOFailed(io::ErrorKind::Other))?
+            .map_err(io)?;
+    }
+
+    Ok(output.stdout)
+}
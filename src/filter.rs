@@ -0,0 +1,134 @@
+use crate::{workspace, Error, Message};
+
+use iced::theme;
+use iced::widget::{button, column, container, row, text, text_input};
+use iced::{Element, Length};
+use std::io;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+/// Commands still running after this are stopped, and the buffer is left as it was.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Asks for the command to run the selection, or the whole buffer, through.
+#[derive(Debug, Clone, Default)]
+pub struct Prompt {
+    pub command: String,
+    pub has_selection: bool,
+}
+
+/// What a command printed for one run, with the text it was given.
+#[derive(Debug, Clone)]
+pub struct Output {
+    /// The bytes of the buffer that were piped in, to be replaced
+    pub range: Range<usize>,
+    /// The buffer as it was, so a result that arrives after typing can be refused
+    pub buffer: String,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl Prompt {
+    pub fn new(command: String, has_selection: bool) -> Self {
+        Self {
+            command,
+            has_selection,
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let target = if self.has_selection {
+            "the selection"
+        } else {
+            "the whole buffer"
+        };
+
+        let run = (!self.command.trim().is_empty()).then_some(Message::RunFilter);
+
+        container(
+            column![
+                text("Filter through command").size(16),
+                text(format!(
+                    "Pipes {target} into the command and replaces it with what the command prints."
+                ))
+                .size(14),
+                text_input("sort | uniq", &self.command)
+                    .on_input(Message::FilterCommandChanged)
+                    .on_submit(Message::RunFilter)
+                    .size(14),
+                row![
+                    button(text("Run").size(14)).on_press_maybe(run),
+                    button(text("Cancel").size(14))
+                        .on_press(Message::ClosePanel)
+                        .style(theme::Button::Secondary),
+                ]
+                .spacing(10),
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fill)
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
+
+pub async fn run(
+    command: String,
+    directory: PathBuf,
+    buffer: String,
+    range: Range<usize>,
+) -> Result<Output, Error> {
+    let io = |error: io::Error| Error::IOFailed(error.kind());
+
+    let mut child = workspace::shell(&command)
+        .current_dir(directory)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(io)?;
+
+    // Written alongside the reads, as a command may print before taking all of its input
+    if let Some(mut stdin) = child.stdin.take() {
+        let input = buffer[range.clone()].to_string();
+
+        tokio::spawn(async move {
+            let _ = stdin.write_all(input.as_bytes()).await;
+        });
+    }
+
+    let output = tokio::time::timeout(TIMEOUT, child.wait_with_output())
+        .await
+        .map_err(|_| {
+            Error::RemoteFailed(format!(
+                "{command} was stopped after {} seconds",
+                TIMEOUT.as_secs()
+            ))
+        })?
+        .map_err(io)?;
+
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+
+    if !output.status.success() {
+        return Err(Error::RemoteFailed(if stderr.is_empty() {
+            format!("{command} failed with {}", output.status)
+        } else {
+            stderr
+        }));
+    }
+
+    let stdout = String::from_utf8(output.stdout)
+        .map_err(|_| Error::IOFailed(io::ErrorKind::InvalidData))?;
+
+    Ok(Output {
+        range,
+        buffer,
+        stdout,
+        stderr,
+    })
+}
@@ -0,0 +1,68 @@
+use crate::Message;
+
+use iced::widget::{container, text_input};
+use iced::{theme, Color, Command, Element, Theme};
+
+/// Id of a text input that never exists, focused to take focus away from every real one.
+const NOWHERE: &str = "focus-nowhere";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Region {
+    Tree,
+    #[default]
+    Editor,
+    Panel,
+}
+
+impl Region {
+    /// The region `step` places away among the ones on screen, wrapping around.
+    pub fn cycle(self, available: &[Region], step: i32) -> Region {
+        let Some(index) = available.iter().position(|region| *region == self) else {
+            return Region::Editor;
+        };
+
+        let count = available.len() as i32;
+        let next = (index as i32 + step).rem_euclid(count);
+
+        available[next as usize]
+    }
+}
+
+/// Moves keyboard focus into the region's first text input, or out of all of them.
+pub fn enter(region: Region) -> Command<Message> {
+    match region {
+        Region::Editor => text_input::focus(text_input::Id::new(NOWHERE)),
+        Region::Tree => text_input::focus(crate::tree::filter_id()),
+        // Nothing is focused after the first, so this lands on the panel's last input
+        Region::Panel => Command::batch([
+            text_input::focus(text_input::Id::new(NOWHERE)),
+            iced::widget::focus_previous(),
+        ]),
+    }
+}
+
+pub fn ring<'a>(content: Element<'a, Message>, is_focused: bool) -> Element<'a, Message> {
+    container(content)
+        .padding(2)
+        .style(theme::Container::Custom(Box::new(Ring(is_focused))))
+        .into()
+}
+
+struct Ring(bool);
+
+impl container::StyleSheet for Ring {
+    type Style = Theme;
+
+    fn appearance(&self, style: &Self::Style) -> container::Appearance {
+        container::Appearance {
+            border_radius: 4.0.into(),
+            border_width: 2.0,
+            border_color: if self.0 {
+                style.extended_palette().primary.strong.color
+            } else {
+                Color::TRANSPARENT
+            },
+            ..container::Appearance::default()
+        }
+    }
+}
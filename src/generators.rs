@@ -0,0 +1,43 @@
+use chrono::{Local, SecondsFormat};
+use std::fmt::Write;
+
+pub const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Generator {
+    DateTime,
+    Timestamp,
+    Uuid,
+}
+
+impl Generator {
+    pub const ALL: [Generator; 3] = [Generator::DateTime, Generator::Timestamp, Generator::Uuid];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Generator::DateTime => "Insert date/time",
+            Generator::Timestamp => "Insert ISO-8601 timestamp",
+            Generator::Uuid => "Insert UUID",
+        }
+    }
+
+    pub fn generate(self, date_format: Option<&str>) -> String {
+        match self {
+            Generator::DateTime => {
+                let now = Local::now();
+                let mut text = String::new();
+
+                match write!(
+                    text,
+                    "{}",
+                    now.format(date_format.unwrap_or(DEFAULT_DATE_FORMAT))
+                ) {
+                    Ok(()) => text,
+                    Err(_) => now.format(DEFAULT_DATE_FORMAT).to_string(),
+                }
+            }
+            Generator::Timestamp => Local::now().to_rfc3339_opts(SecondsFormat::Secs, false),
+            Generator::Uuid => uuid::Uuid::new_v4().to_string(),
+        }
+    }
+}
@@ -0,0 +1,798 @@
+use crate::{widgets, Error, Message};
+
+use iced::theme;
+use iced::widget::{button, column, container, row, scrollable, text, text_editor, Column};
+use iced::{Color, Element, Length};
+use similar::TextDiff;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{Duration, UNIX_EPOCH};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+const LINE_HEIGHT: f32 = 18.0;
+const FADED: Color = Color::from_rgb(0.55, 0.55, 0.55);
+const ADDED: Color = Color::from_rgb(0.4, 0.75, 0.45);
+const REMOVED: Color = Color::from_rgb(0.9, 0.3, 0.3);
+const RECORD: char = '\u{1e}';
+const FIELD: char = '\u{1f}';
+
+#[derive(Debug, Clone)]
+pub struct Commit {
+    pub id: String,
+    pub author: String,
+    /// Seconds since the Unix epoch
+    pub time: u64,
+    pub summary: String,
+}
+
+impl Commit {
+    /// Lines changed in the buffer or working tree blame to a commit of zeros.
+    pub fn is_committed(&self) -> bool {
+        self.id.bytes().any(|byte| byte != b'0')
+    }
+
+    pub fn short_id(&self) -> &str {
+        &self.id[..self.id.len().min(8)]
+    }
+
+    pub fn date(&self) -> String {
+        chrono::DateTime::<chrono::Local>::from(UNIX_EPOCH + Duration::from_secs(self.time))
+            .format("%Y-%m-%d")
+            .to_string()
+    }
+
+    /// The subtle one-line form shown for the cursor's line.
+    pub fn annotation(&self) -> String {
+        if self.is_committed() {
+            format!("{}, {} · {}", self.author, self.date(), self.summary)
+        } else {
+            String::from("Not committed yet")
+        }
+    }
+}
+
+/// Who last changed each line of a file, as of the text that was blamed.
+#[derive(Debug, Clone)]
+pub struct Blame {
+    pub path: PathBuf,
+    commits: Vec<Commit>,
+    /// Index into `commits` for each line
+    lines: Vec<Option<usize>>,
+}
+
+impl Blame {
+    pub fn line(&self, line: usize) -> Option<&Commit> {
+        self.lines
+            .get(line)
+            .copied()
+            .flatten()
+            .map(|index| &self.commits[index])
+    }
+}
+
+/// The blame panel's scroll position and the commit message opened from it.
+///
+/// It stands in for a blame gutter: the editor widget can't draw beside its lines, so blame is
+/// listed in a panel next to the buffer that scrolls on its own.
+#[derive(Debug, Clone, Default)]
+pub struct Gutter {
+    offset: f32,
+    pub commit: Option<(String, String)>,
+}
+
+impl Gutter {
+    pub fn scrolled(&mut self, viewport: scrollable::Viewport) {
+        self.offset = viewport.absolute_offset().y;
+    }
+
+    pub fn view<'a>(
+        &'a self,
+        blame: Option<&'a Blame>,
+        content: &'a text_editor::Content,
+        height: f32,
+    ) -> Element<'a, Message> {
+        let line = move |index: usize| -> Element<'a, Message> {
+            let source = content
+                .line(index)
+                .map(|line| line.to_string())
+                .unwrap_or_default();
+
+            let annotation: Element<_> = match blame.and_then(|blame| blame.line(index)) {
+                Some(commit) if commit.is_committed() => row![
+                    button(text(commit.short_id()).size(13))
+                        .on_press(Message::ShowCommit(commit.id.clone()))
+                        .padding(0)
+                        .style(theme::Button::Text),
+                    text(&commit.author).size(13).width(120),
+                    text(commit.date()).size(13).style(FADED),
+                ]
+                .spacing(8)
+                .width(300)
+                .into(),
+                Some(_) => text("Not committed yet")
+                    .size(13)
+                    .style(FADED)
+                    .width(300)
+                    .into(),
+                None => text("").width(300).into(),
+            };
+
+            row![
+                annotation,
+                text(format!("{:>5} {source}", index + 1)).size(13)
+            ]
+            .spacing(10)
+            .into()
+        };
+
+        let commit: Element<_> = match &self.commit {
+            Some((id, message)) => container(
+                column![
+                    text(format!("commit {id}")).size(13).style(FADED),
+                    text(message).size(14),
+                ]
+                .spacing(5),
+            )
+            .padding(5)
+            .style(theme::Container::Box)
+            .into(),
+            None => text("Click a commit to read its message").size(13).into(),
+        };
+
+        let title = match blame {
+            Some(blame) => format!("Blame for {}", blame.path.display()),
+            None => String::from("Reading blame..."),
+        };
+
+        container(
+            column![
+                text(title).size(16),
+                commit,
+                widgets::virtual_list(
+                    content.line_count(),
+                    LINE_HEIGHT,
+                    self.offset,
+                    height,
+                    line,
+                    Message::BlameScrolled,
+                ),
+                button(text("Close").size(14))
+                    .on_press(Message::ClosePanel)
+                    .style(theme::Button::Secondary),
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fill)
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
+
+/// One commit that touched a file.
+#[derive(Debug, Clone)]
+pub struct Revision {
+    pub commit: Commit,
+    /// The file's path at that commit, from the root of the repository
+    pub name: String,
+}
+
+impl Revision {
+    /// Names the revision in git's own `commit:path` form, as a read-only buffer's title.
+    pub fn label(&self) -> String {
+        format!("{}:{}", self.commit.short_id(), self.name)
+    }
+}
+
+/// The commits that touched a file, newest first, and the change each one made.
+#[derive(Debug, Clone)]
+pub struct History {
+    pub path: PathBuf,
+    revisions: Vec<Revision>,
+    selected: Option<usize>,
+    patch: Vec<String>,
+    offset: f32,
+}
+
+impl History {
+    pub fn select(&mut self, index: usize) -> Option<Revision> {
+        let revision = self.revisions.get(index)?.clone();
+
+        self.selected = Some(index);
+        self.patch.clear();
+        self.offset = 0.0;
+
+        Some(revision)
+    }
+
+    pub fn selected(&self) -> Option<&Revision> {
+        self.selected.and_then(|index| self.revisions.get(index))
+    }
+
+    /// Shows the change a revision made, if it is still the selected one.
+    pub fn patched(&mut self, id: &str, patch: &str) {
+        if self
+            .selected()
+            .is_some_and(|revision| revision.commit.id == id)
+        {
+            self.patch = patch.lines().map(str::to_string).collect();
+        }
+    }
+
+    pub fn scrolled(&mut self, viewport: scrollable::Viewport) {
+        self.offset = viewport.absolute_offset().y;
+    }
+
+    pub fn view(&self, height: f32) -> Element<'_, Message> {
+        let revisions = self.revisions.iter().enumerate().map(|(index, revision)| {
+            let commit = &revision.commit;
+
+            button(
+                column![
+                    text(&commit.summary).size(14),
+                    text(format!(
+                        "{} · {} · {}",
+                        commit.short_id(),
+                        commit.author,
+                        commit.date()
+                    ))
+                    .size(12)
+                    .style(FADED),
+                ]
+                .spacing(2),
+            )
+            .on_press(Message::RevisionSelected(index))
+            .width(Length::Fill)
+            .style(if self.selected == Some(index) {
+                theme::Button::Primary
+            } else {
+                theme::Button::Text
+            })
+            .into()
+        });
+
+        let line = |index: usize| -> Element<'_, Message> {
+            let line = &self.patch[index];
+
+            let color = if line.starts_with("+++") || line.starts_with("---") {
+                Some(FADED)
+            } else if line.starts_with('+') {
+                Some(ADDED)
+            } else if line.starts_with('-') {
+                Some(REMOVED)
+            } else if line.starts_with("@@") {
+                Some(FADED)
+            } else {
+                None
+            };
+
+            let line = text(line).size(13);
+
+            match color {
+                Some(color) => line.style(color).into(),
+                None => line.into(),
+            }
+        };
+
+        let preview: Element<_> = match self.selected() {
+            Some(_) if self.patch.is_empty() => text("Reading the change...").size(14).into(),
+            Some(_) => widgets::virtual_list(
+                self.patch.len(),
+                LINE_HEIGHT,
+                self.offset,
+                height,
+                line,
+                Message::HistoryScrolled,
+            ),
+            None => text("Choose a commit to see what it changed")
+                .size(14)
+                .into(),
+        };
+
+        let summary = match self.revisions.len() {
+            0 => String::from("No commits touch this file"),
+            1 => String::from("1 commit"),
+            count => format!("{count} commits"),
+        };
+
+        container(
+            column![
+                text(format!("History of {}", self.path.display())).size(16),
+                text(summary).size(14),
+                row![
+                    scrollable(Column::with_children(revisions.collect()).spacing(2))
+                        .width(Length::FillPortion(2))
+                        .height(Length::Fill),
+                    container(preview)
+                        .width(Length::FillPortion(3))
+                        .height(Length::Fill),
+                ]
+                .spacing(10)
+                .height(Length::Fill),
+                row![
+                    button(text("Open revision").size(14))
+                        .on_press_maybe(self.selected.map(|_| Message::OpenRevision)),
+                    button(text("Close").size(14))
+                        .on_press(Message::ClosePanel)
+                        .style(theme::Button::Secondary),
+                ]
+                .spacing(10),
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fill)
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
+
+/// A run of lines where the buffer differs from the staged file.
+#[derive(Debug, Clone)]
+struct Hunk {
+    staged: Range<usize>,
+    buffer: Range<usize>,
+}
+
+/// How the buffer differs from what is staged for its file, hunk by hunk.
+#[derive(Debug, Clone)]
+pub struct Changes {
+    pub path: PathBuf,
+    /// The buffer text the hunks were found in
+    pub buffer: String,
+    mode: String,
+    staged: String,
+    hunks: Vec<Hunk>,
+}
+
+impl Changes {
+    /// The byte range of a hunk in the buffer, and the staged text that goes back in its place.
+    pub fn revert(&self, index: usize) -> Option<(Range<usize>, String)> {
+        let hunk = self.hunks.get(index)?;
+
+        let range = span(&self.buffer, hunk.buffer.clone());
+        let staged = span(&self.staged, hunk.staged.clone());
+
+        Some((range, self.staged[staged].to_string()))
+    }
+
+    /// The staged text with just one hunk of the buffer applied.
+    fn staged_with(&self, index: usize) -> Option<String> {
+        let hunk = self.hunks.get(index)?;
+
+        let staged = span(&self.staged, hunk.staged.clone());
+        let buffer = span(&self.buffer, hunk.buffer.clone());
+
+        Some(format!(
+            "{}{}{}",
+            &self.staged[..staged.start],
+            &self.buffer[buffer],
+            &self.staged[staged.end..]
+        ))
+    }
+
+    /// The first line of a hunk in the buffer, to move the cursor to.
+    pub fn line(&self, index: usize) -> Option<usize> {
+        self.hunks.get(index).map(|hunk| hunk.buffer.start)
+    }
+
+    pub fn view(&self, cursor: usize) -> Element<'_, Message> {
+        let staged: Vec<&str> = self.staged.split_inclusive('\n').collect();
+        let buffer: Vec<&str> = self.buffer.split_inclusive('\n').collect();
+
+        let hunks = self.hunks.iter().enumerate().map(|(index, hunk)| {
+            let is_current = hunk.buffer.contains(&cursor)
+                || (hunk.buffer.is_empty() && hunk.buffer.start == cursor);
+
+            let title = if hunk.buffer.is_empty() {
+                format!("Removed before line {}", hunk.buffer.start + 1)
+            } else {
+                format!("Lines {}–{}", hunk.buffer.start + 1, hunk.buffer.end)
+            };
+
+            let removed = staged[hunk.staged.clone()].iter().map(|line| {
+                text(format!("-{}", line.trim_end_matches('\n')))
+                    .size(13)
+                    .style(REMOVED)
+                    .into()
+            });
+            let added = buffer[hunk.buffer.clone()].iter().map(|line| {
+                text(format!("+{}", line.trim_end_matches('\n')))
+                    .size(13)
+                    .style(ADDED)
+                    .into()
+            });
+
+            container(
+                column![
+                    row![
+                        text(if is_current {
+                            format!("▶ {title}")
+                        } else {
+                            title
+                        })
+                        .size(14)
+                        .width(Length::Fill),
+                        button(text("Go to").size(13))
+                            .on_press(Message::HunkSelected(index))
+                            .style(theme::Button::Secondary),
+                        button(text("Stage hunk").size(13)).on_press(Message::StageHunk(index)),
+                        button(text("Revert hunk").size(13))
+                            .on_press(Message::RevertHunk(index))
+                            .style(theme::Button::Destructive),
+                    ]
+                    .spacing(5),
+                    Column::with_children(removed.chain(added).collect()),
+                ]
+                .spacing(5),
+            )
+            .padding(5)
+            .style(theme::Container::Box)
+            .into()
+        });
+
+        let summary = match self.hunks.len() {
+            0 => String::from("The buffer matches what is staged"),
+            1 => String::from("1 unstaged hunk"),
+            count => format!("{count} unstaged hunks"),
+        };
+
+        container(
+            column![
+                text("Changes").size(16),
+                text(summary).size(14),
+                scrollable(Column::with_children(hunks.collect()).spacing(8)).height(Length::Fill),
+                button(text("Close").size(14))
+                    .on_press(Message::ClosePanel)
+                    .style(theme::Button::Secondary),
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fill)
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
+
+/// Byte offsets of a range of lines, each with its line ending.
+fn span(text: &str, lines: Range<usize>) -> Range<usize> {
+    let mut offsets = std::iter::once(0)
+        .chain(text.match_indices('\n').map(|(index, _)| index + 1))
+        .chain(std::iter::once(text.len()));
+
+    let start = offsets
+        .nth(lines.start)
+        .unwrap_or(text.len())
+        .min(text.len());
+    let end = match lines.len() {
+        0 => start,
+        len => offsets.nth(len - 1).unwrap_or(text.len()).min(text.len()),
+    };
+
+    start..end
+}
+
+/// Compares the buffer with the file as staged, or with nothing for a file git doesn't track.
+pub async fn changes(path: PathBuf, buffer: String) -> Result<Changes, Error> {
+    let directory = directory(&path);
+    let name = file_name(&path);
+
+    let entry = run(&directory, &["ls-files", "-s", "--", &name]).await?;
+
+    let (mode, staged) = match entry.split_whitespace().next() {
+        Some(mode) => (
+            mode.to_string(),
+            run(&directory, &["show", &format!(":./{name}")]).await?,
+        ),
+        None => (String::from("100644"), String::new()),
+    };
+
+    let hunks = TextDiff::from_lines(&staged, &buffer)
+        .grouped_ops(0)
+        .into_iter()
+        .filter_map(|group| {
+            let first = group.first()?;
+            let last = group.last()?;
+
+            Some(Hunk {
+                staged: first.old_range().start..last.old_range().end,
+                buffer: first.new_range().start..last.new_range().end,
+            })
+        })
+        .collect();
+
+    Ok(Changes {
+        path,
+        buffer,
+        mode,
+        staged,
+        hunks,
+    })
+}
+
+/// Stages one hunk of the buffer, leaving the rest of the index and the file on disk alone.
+pub async fn stage(changes: Changes, index: usize) -> Result<PathBuf, Error> {
+    let Some(staged) = changes.staged_with(index) else {
+        return Ok(changes.path);
+    };
+
+    let directory = directory(&changes.path);
+    let name = file_name(&changes.path);
+
+    let blob = pipe(&directory, &["hash-object", "-w", "--stdin"], Some(staged)).await?;
+    let entry = format!("{},{},{name}", changes.mode, blob.trim());
+
+    run(
+        &directory,
+        &["update-index", "--add", "--cacheinfo", &entry],
+    )
+    .await?;
+
+    Ok(changes.path)
+}
+
+/// Blames `contents` as the text of `path`, so unsaved edits count as not committed.
+pub async fn blame(path: PathBuf, contents: String) -> Result<Blame, Error> {
+    let directory = directory(&path);
+    let name = path.to_string_lossy().into_owned();
+
+    let output = pipe(
+        &directory,
+        &["blame", "--porcelain", "--contents", "-", "--", &name],
+        Some(contents),
+    )
+    .await?;
+
+    let mut commits: Vec<Commit> = Vec::new();
+    let mut known: HashMap<String, usize> = HashMap::new();
+    let mut lines: Vec<Option<usize>> = Vec::new();
+    let mut current: Option<usize> = None;
+
+    for line in output.lines() {
+        if line.starts_with('\t') {
+            continue;
+        }
+
+        let mut words = line.splitn(2, ' ');
+        let key = words.next().unwrap_or_default();
+        let value = words.next().unwrap_or_default();
+
+        // A header names the commit and the line it gave, counted from one
+        if key.len() >= 40 && key.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            let index = *known.entry(key.to_string()).or_insert_with(|| {
+                commits.push(Commit {
+                    id: key.to_string(),
+                    author: String::new(),
+                    time: 0,
+                    summary: String::new(),
+                });
+                commits.len() - 1
+            });
+
+            let Some(final_line) = value
+                .split(' ')
+                .nth(1)
+                .and_then(|line| line.parse::<usize>().ok())
+            else {
+                continue;
+            };
+
+            if lines.len() < final_line {
+                lines.resize(final_line, None);
+            }
+
+            lines[final_line - 1] = Some(index);
+            current = Some(index);
+            continue;
+        }
+
+        let Some(index) = current else {
+            continue;
+        };
+
+        let commit = &mut commits[index];
+
+        match key {
+            "author" => commit.author = value.to_string(),
+            "author-time" => commit.time = value.parse().unwrap_or_default(),
+            "summary" => commit.summary = value.to_string(),
+            _ => {}
+        }
+    }
+
+    Ok(Blame {
+        path,
+        commits,
+        lines,
+    })
+}
+
+/// The full message of commit `id` in the repository holding `path`.
+pub async fn message(path: PathBuf, id: String) -> Result<(String, String), Error> {
+    let output = run(
+        &directory(&path),
+        &["show", "-s", "--format=%an <%ae>%n%ad%n%n%B", &id],
+    )
+    .await?;
+
+    Ok((id, output.trim_end().to_string()))
+}
+
+/// The commits that touched `path`, following it back through renames.
+pub async fn history(path: PathBuf) -> Result<History, Error> {
+    let name = path.to_string_lossy().into_owned();
+    let format = format!("--format={RECORD}%H{FIELD}%an{FIELD}%at{FIELD}%s");
+
+    let output = run(
+        &directory(&path),
+        &["log", "--follow", "--name-only", &format, "--", &name],
+    )
+    .await?;
+
+    let revisions = output
+        .split(RECORD)
+        .filter_map(|record| {
+            let mut lines = record.lines();
+            let mut fields = lines.next()?.split(FIELD);
+
+            let commit = Commit {
+                id: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                time: fields.next()?.parse().unwrap_or_default(),
+                summary: fields.next().unwrap_or_default().to_string(),
+            };
+
+            let name = lines.rev().find(|line| !line.is_empty())?.to_string();
+
+            Some(Revision { commit, name })
+        })
+        .collect();
+
+    Ok(History {
+        path,
+        revisions,
+        selected: None,
+        patch: Vec::new(),
+        offset: 0.0,
+    })
+}
+
+/// The change `revision` made to the file, as a patch.
+pub async fn patch(path: PathBuf, revision: Revision) -> Result<(String, String), Error> {
+    let pathspec = format!(":(top){}", revision.name);
+
+    let output = run(
+        &directory(&path),
+        &["show", "--format=", &revision.commit.id, "--", &pathspec],
+    )
+    .await?;
+
+    Ok((revision.commit.id, output))
+}
+
+/// The file's text as it was at `revision`, with the revision's label.
+pub async fn show(path: PathBuf, revision: Revision) -> Result<(String, String), Error> {
+    let object = format!("{}:{}", revision.commit.id, revision.name);
+    let output = run(&directory(&path), &["show", &object]).await?;
+
+    Ok((revision.label(), output))
+}
+
+/// A link to the lines on the forge hosting the `origin` remote, pinned to the checked out commit.
+///
+/// Hosts with "gitlab" in their name get GitLab's form; any other is taken to work like GitHub.
+pub async fn permalink(path: PathBuf, lines: Range<usize>) -> Result<String, Error> {
+    let directory = directory(&path);
+
+    let remote = run(&directory, &["remote", "get-url", "origin"]).await?;
+    let commit = run(&directory, &["rev-parse", "HEAD"]).await?;
+    let prefix = run(&directory, &["rev-parse", "--show-prefix"]).await?;
+
+    let base = web_url(remote.trim()).ok_or_else(|| {
+        Error::RemoteFailed(format!("no web address for the remote {}", remote.trim()))
+    })?;
+
+    let file = format!("{}{}", prefix.trim(), file_name(&path));
+    let file: String = file
+        .split('/')
+        .map(|part| urlencoding::encode(part).into_owned())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    let (first, last) = (lines.start + 1, lines.end.max(lines.start + 1));
+    let is_gitlab = base.contains("gitlab");
+
+    let anchor = match (first == last, is_gitlab) {
+        (true, _) => format!("L{first}"),
+        (false, true) => format!("L{first}-{last}"),
+        (false, false) => format!("L{first}-L{last}"),
+    };
+
+    let blob = if is_gitlab { "-/blob" } else { "blob" };
+
+    Ok(format!("{base}/{blob}/{}/{file}#{anchor}", commit.trim()))
+}
+
+/// The web address of a repository from its clone URL, over SSH or HTTPS.
+fn web_url(remote: &str) -> Option<String> {
+    let remote = remote.trim_end_matches('/').trim_end_matches(".git");
+
+    let (host, repository) = if let Some(rest) = remote
+        .strip_prefix("https://")
+        .or_else(|| remote.strip_prefix("http://"))
+        .or_else(|| remote.strip_prefix("ssh://"))
+        .or_else(|| remote.strip_prefix("git://"))
+    {
+        let rest = rest.rsplit_once('@').map_or(rest, |(_, rest)| rest);
+        let (host, repository) = rest.split_once('/')?;
+
+        // A port only matters for SSH, the web address never uses it
+        (host.split(':').next()?, repository)
+    } else {
+        // The scp-like `git@host:owner/repository` form
+        let rest = remote.rsplit_once('@').map_or(remote, |(_, rest)| rest);
+        rest.split_once(':')?
+    };
+
+    Some(format!("https://{host}/{repository}"))
+}
+
+pub async fn run(directory: &Path, arguments: &[&str]) -> Result<String, Error> {
+    pipe(directory, arguments, None).await
+}
+
+async fn pipe(
+    directory: &Path,
+    arguments: &[&str],
+    input: Option<String>,
+) -> Result<String, Error> {
+    let mut child = Command::new("git")
+        .args(arguments)
+        .current_dir(directory)
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|error| Error::IOFailed(error.kind()))?;
+
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        stdin
+            .write_all(input.as_bytes())
+            .await
+            .map_err(|error| Error::IOFailed(error.kind()))?;
+    }
+
+    let output = child
+        .wait_with_output()
+        .await
+        .map_err(|error| Error::IOFailed(error.kind()))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(Error::RemoteFailed(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn directory(path: &Path) -> PathBuf {
+    path.parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."))
+        .to_path_buf()
+}
@@ -0,0 +1,193 @@
+use crate::{Error, Message};
+
+use iced::theme;
+use iced::widget::{button, column, container, row, scrollable, text, text_input, Column};
+use iced::{Element, Length};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const GOALS_FILE: &str = "goals.toml";
+const HISTORY_DAYS: usize = 14;
+const BAR_WIDTH: usize = 20;
+const PROSE_EXTENSIONS: &[&str] = &["md", "markdown", "txt", "rst", "adoc", "org", "tex"];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Goals {
+    documents: Vec<Goal>,
+    /// Words written per day, keyed by `YYYY-MM-DD`
+    days: BTreeMap<String, usize>,
+    // The last word count seen for each document, so saves only add what is new
+    #[serde(skip)]
+    counts: BTreeMap<PathBuf, usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Goal {
+    path: PathBuf,
+    words: usize,
+}
+
+pub fn is_prose(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| PROSE_EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+}
+
+pub fn count(text: &str) -> usize {
+    text.split_whitespace().count()
+}
+
+fn today() -> String {
+    chrono::Local::now().format("%Y-%m-%d").to_string()
+}
+
+impl Goals {
+    pub fn goal(&self, path: &Path) -> Option<usize> {
+        self.documents
+            .iter()
+            .find(|goal| goal.path == path)
+            .map(|goal| goal.words)
+    }
+
+    pub fn set_goal(&mut self, path: PathBuf, words: Option<usize>) {
+        self.documents.retain(|goal| goal.path != path);
+
+        if let Some(words) = words {
+            self.documents.push(Goal { path, words });
+        }
+    }
+
+    pub fn opened(&mut self, path: PathBuf, words: usize) {
+        self.counts.entry(path).or_insert(words);
+    }
+
+    /// Adds the words written since the document was last opened or saved to today.
+    pub fn saved(&mut self, path: PathBuf, words: usize) {
+        let previous = self.counts.insert(path, words).unwrap_or(words);
+
+        if words > previous {
+            *self.days.entry(today()).or_default() += words - previous;
+        }
+    }
+
+    pub fn written_today(&self) -> usize {
+        self.days.get(&today()).copied().unwrap_or_default()
+    }
+
+    pub async fn load() -> Result<Self, Error> {
+        let Some(path) = goals_file() else {
+            return Ok(Self::default());
+        };
+
+        match tokio::fs::read_to_string(&path).await {
+            Ok(text) => {
+                toml::from_str(&text).map_err(|error| Error::InvalidConfig(error.to_string()))
+            }
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(Error::IOFailed(error.kind())),
+        }
+    }
+
+    pub async fn save(self) -> Result<(), Error> {
+        let path = goals_file().ok_or(Error::IOFailed(io::ErrorKind::NotFound))?;
+        let text = toml::to_string_pretty(&self)
+            .map_err(|error| Error::InvalidConfig(error.to_string()))?;
+
+        if let Some(directory) = path.parent() {
+            tokio::fs::create_dir_all(directory)
+                .await
+                .map_err(|error| Error::IOFailed(error.kind()))?;
+        }
+
+        tokio::fs::write(&path, text)
+            .await
+            .map_err(|error| Error::IOFailed(error.kind()))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Dialog {
+    pub input: String,
+}
+
+impl Dialog {
+    pub fn view<'a>(
+        &'a self,
+        goals: &'a Goals,
+        path: Option<&Path>,
+        words: usize,
+    ) -> Element<'a, Message> {
+        let goal = path.and_then(|path| goals.goal(path));
+        let target = self
+            .input
+            .trim()
+            .parse::<usize>()
+            .ok()
+            .filter(|words| *words > 0);
+
+        let progress = match goal {
+            Some(goal) => format!("{words} of {goal} words"),
+            None => format!("{words} words, no goal set"),
+        };
+
+        let recent: Vec<_> = goals.days.iter().rev().take(HISTORY_DAYS).collect();
+        let most = recent.iter().map(|(_, words)| **words).max().unwrap_or(1);
+
+        let history = recent.into_iter().rev().map(|(day, words)| {
+            let bar = "█".repeat((words * BAR_WIDTH).div_ceil(most.max(1)));
+
+            row![
+                text(day.get(5..).unwrap_or(day)).size(13).width(48),
+                text(bar).size(13).width(Length::Fill),
+                text(words).size(13),
+            ]
+            .spacing(5)
+            .into()
+        });
+
+        let mut actions = row![button(text("Set goal").size(14)).on_press_maybe(
+            target
+                .filter(|_| path.is_some())
+                .map(|words| Message::SetWordGoal(Some(words)))
+        )]
+        .spacing(10);
+
+        if goal.is_some() {
+            actions = actions.push(
+                button(text("Clear").size(14))
+                    .on_press(Message::SetWordGoal(None))
+                    .style(theme::Button::Secondary),
+            );
+        }
+
+        container(
+            column![
+                text("Writing goals").size(16),
+                text(progress).size(14),
+                text_input("Word goal for this document", &self.input)
+                    .on_input(Message::WordGoalChanged)
+                    .size(14)
+                    .padding(5),
+                actions,
+                text(format!("Written today: {}", goals.written_today())).size(14),
+                text("Recent writing days").size(14),
+                scrollable(Column::with_children(history.collect())).height(Length::Fill),
+                button(text("Close").size(14))
+                    .on_press(Message::ClosePanel)
+                    .style(theme::Button::Secondary),
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fill)
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
+
+fn goals_file() -> Option<PathBuf> {
+    dirs::data_dir().map(|directory| directory.join("bonkitor").join(GOALS_FILE))
+}
@@ -0,0 +1,355 @@
+use crate::cells;
+use crate::colors;
+use crate::debug;
+use crate::links;
+use crate::patch;
+use crate::problems;
+use crate::suspicious;
+use crate::tail;
+
+use iced::advanced::text::highlighter::{self, Format};
+use iced::{highlighter as syntax, Color, Font};
+use std::ops::Range;
+use std::sync::OnceLock;
+use syntect::{highlighting, parsing};
+
+const LINK_COLOR: Color = Color::from_rgb(0.35, 0.6, 1.0);
+const DIMMED_COLOR: Color = Color::from_rgba(0.5, 0.5, 0.5, 0.6);
+const LINES_PER_SNAPSHOT: usize = 50;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub syntax: syntax::Settings,
+    /// Colors by scope from a user theme, used in place of the built-in theme
+    pub scopes: Option<Vec<(String, Color)>>,
+    pub focus: Option<Range<usize>>,
+    /// Lines with a breakpoint, and the one the debugger is stopped at
+    pub breakpoints: Vec<usize>,
+    pub stopped: Option<usize>,
+    /// The line, column and color of each problem the last task run found in this file
+    pub problems: Vec<(usize, usize, Color)>,
+    /// Whether colors written in the text get a marker in that color
+    pub swatches: bool,
+    /// The line, byte range and color of each collaborator's cursor or selection
+    pub peers: Vec<(usize, Range<usize>, Color)>,
+}
+
+pub struct Highlighter {
+    syntax: Syntax,
+    is_log: bool,
+    is_patch: bool,
+    has_cells: bool,
+    focus: Option<Range<usize>>,
+    breakpoints: Vec<usize>,
+    stopped: Option<usize>,
+    problems: Vec<(usize, usize, Color)>,
+    swatches: bool,
+    peers: Vec<(usize, Range<usize>, Color)>,
+}
+
+pub enum Highlight {
+    Syntax(syntax::Highlight),
+    Link,
+    Color(Color),
+}
+
+impl Highlight {
+    pub fn to_format(&self) -> Format<Font> {
+        match self {
+            Highlight::Syntax(highlight) => highlight.to_format(),
+            Highlight::Link => Format {
+                color: Some(LINK_COLOR),
+                font: None,
+            },
+            Highlight::Color(color) => Format {
+                color: Some(*color),
+                font: None,
+            },
+        }
+    }
+}
+
+impl highlighter::Highlighter for Highlighter {
+    type Settings = Settings;
+    type Highlight = Highlight;
+
+    type Iterator<'a> = std::vec::IntoIter<(Range<usize>, Highlight)>;
+
+    fn new(settings: &Self::Settings) -> Self {
+        Self {
+            syntax: Syntax::new(settings),
+            is_log: settings.syntax.extension == "log",
+            is_patch: patch::is_patch(&settings.syntax.extension),
+            has_cells: cells::supports(&settings.syntax.extension),
+            focus: settings.focus.clone(),
+            breakpoints: settings.breakpoints.clone(),
+            stopped: settings.stopped,
+            problems: settings.problems.clone(),
+            swatches: settings.swatches,
+            peers: settings.peers.clone(),
+        }
+    }
+
+    fn update(&mut self, new_settings: &Self::Settings) {
+        self.syntax.update(new_settings);
+        self.is_log = new_settings.syntax.extension == "log";
+        self.is_patch = patch::is_patch(&new_settings.syntax.extension);
+        self.has_cells = cells::supports(&new_settings.syntax.extension);
+        self.focus = new_settings.focus.clone();
+        self.breakpoints = new_settings.breakpoints.clone();
+        self.stopped = new_settings.stopped;
+        self.problems = new_settings.problems.clone();
+        self.swatches = new_settings.swatches;
+        self.peers = new_settings.peers.clone();
+    }
+
+    fn change_line(&mut self, line: usize) {
+        self.syntax.change_line(line);
+    }
+
+    fn highlight_line(&mut self, line: &str) -> Self::Iterator<'_> {
+        let number = self.syntax.current_line();
+
+        let mut spans = self.syntax.highlight_line(line);
+
+        if let Some(color) = self.is_log.then(|| tail::level_color(line)).flatten() {
+            spans.push((0..line.len(), Highlight::Color(color)));
+        }
+
+        if let Some(color) = self.is_patch.then(|| patch::line_color(line)).flatten() {
+            spans.push((0..line.len(), Highlight::Color(color)));
+        }
+
+        if self.has_cells && cells::is_marker(line) {
+            spans.push((0..line.len(), Highlight::Color(cells::MARKER_COLOR)));
+        }
+
+        spans.extend(
+            links::find(line)
+                .into_iter()
+                .map(|(range, _link)| (range, Highlight::Link)),
+        );
+
+        if self.swatches {
+            spans.extend(colors::find(line).into_iter().map(|swatch| {
+                (
+                    colors::marker(line, &swatch),
+                    Highlight::Color(swatch.color),
+                )
+            }));
+        }
+
+        spans.extend(
+            suspicious::find(line)
+                .into_iter()
+                .map(|range| (range, Highlight::Color(suspicious::WARNING_COLOR))),
+        );
+
+        if self.breakpoints.contains(&number) {
+            spans.push((0..line.len(), Highlight::Color(debug::BREAKPOINT_COLOR)));
+        }
+
+        if self.stopped == Some(number) {
+            spans.push((0..line.len(), Highlight::Color(debug::STOPPED_COLOR)));
+        }
+
+        if self
+            .focus
+            .as_ref()
+            .is_some_and(|focus| !focus.contains(&number))
+        {
+            spans.push((0..line.len(), Highlight::Color(DIMMED_COLOR)));
+        }
+
+        spans.extend(
+            self.problems
+                .iter()
+                .filter(|(line, ..)| *line == number)
+                .map(|(_, column, color)| {
+                    (problems::marker(line, *column), Highlight::Color(*color))
+                }),
+        );
+
+        spans.extend(
+            self.peers
+                .iter()
+                .filter(|(line, ..)| *line == number)
+                .map(|(_, range, color)| (range.clone(), Highlight::Color(*color))),
+        );
+
+        spans.into_iter()
+    }
+
+    fn current_line(&self) -> usize {
+        self.syntax.current_line()
+    }
+}
+
+enum Syntax {
+    Builtin(syntax::Highlighter),
+    Scoped(Scoped),
+}
+
+impl Syntax {
+    fn new(settings: &Settings) -> Self {
+        match &settings.scopes {
+            Some(scopes) => Syntax::Scoped(Scoped::new(scopes, &settings.syntax.extension)),
+            None => Syntax::Builtin(syntax::Highlighter::new(&settings.syntax)),
+        }
+    }
+
+    fn update(&mut self, settings: &Settings) {
+        match (&mut *self, &settings.scopes) {
+            (Syntax::Builtin(highlighter), None) => highlighter.update(&settings.syntax),
+            (syntax, _) => *syntax = Syntax::new(settings),
+        }
+    }
+
+    fn change_line(&mut self, line: usize) {
+        match self {
+            Syntax::Builtin(highlighter) => highlighter.change_line(line),
+            Syntax::Scoped(highlighter) => highlighter.change_line(line),
+        }
+    }
+
+    fn highlight_line(&mut self, line: &str) -> Vec<(Range<usize>, Highlight)> {
+        match self {
+            Syntax::Builtin(highlighter) => highlighter
+                .highlight_line(line)
+                .map(|(range, highlight)| (range, Highlight::Syntax(highlight)))
+                .collect(),
+            Syntax::Scoped(highlighter) => highlighter
+                .highlight_line(line)
+                .into_iter()
+                .map(|(range, color)| (range, Highlight::Color(color)))
+                .collect(),
+        }
+    }
+
+    fn current_line(&self) -> usize {
+        match self {
+            Syntax::Builtin(highlighter) => highlighter.current_line(),
+            Syntax::Scoped(highlighter) => highlighter.current_line,
+        }
+    }
+}
+
+fn syntaxes() -> &'static parsing::SyntaxSet {
+    static SYNTAXES: OnceLock<parsing::SyntaxSet> = OnceLock::new();
+
+    SYNTAXES.get_or_init(parsing::SyntaxSet::load_defaults_nonewlines)
+}
+
+/// Parses like the built-in highlighter, coloring by a user theme's scope selectors.
+///
+/// The parser's state is kept every few lines, so an edit only parses again from the
+/// snapshot before it.
+struct Scoped {
+    syntax: &'static parsing::SyntaxReference,
+    theme: highlighting::Theme,
+    caches: Vec<(parsing::ParseState, parsing::ScopeStack)>,
+    current_line: usize,
+}
+
+impl Scoped {
+    fn new(scopes: &[(String, Color)], extension: &str) -> Self {
+        let syntax = syntaxes()
+            .find_syntax_by_token(extension)
+            .unwrap_or_else(|| syntaxes().find_syntax_plain_text());
+
+        let theme = highlighting::Theme {
+            scopes: scopes
+                .iter()
+                .filter_map(|(scope, color)| {
+                    let [r, g, b, a] = color.into_rgba8();
+
+                    Some(highlighting::ThemeItem {
+                        scope: scope.parse().ok()?,
+                        style: highlighting::StyleModifier {
+                            foreground: Some(highlighting::Color { r, g, b, a }),
+                            background: None,
+                            font_style: None,
+                        },
+                    })
+                })
+                .collect(),
+            ..highlighting::Theme::default()
+        };
+
+        Self {
+            syntax,
+            theme,
+            caches: vec![(parsing::ParseState::new(syntax), parsing::ScopeStack::new())],
+            current_line: 0,
+        }
+    }
+
+    fn change_line(&mut self, line: usize) {
+        let snapshot = line / LINES_PER_SNAPSHOT;
+
+        if snapshot <= self.caches.len() {
+            self.caches.truncate(snapshot);
+            self.current_line = snapshot * LINES_PER_SNAPSHOT;
+        } else {
+            self.caches.truncate(1);
+            self.current_line = 0;
+        }
+
+        let cache = self.caches.last().cloned().unwrap_or_else(|| {
+            (
+                parsing::ParseState::new(self.syntax),
+                parsing::ScopeStack::new(),
+            )
+        });
+
+        self.caches.push(cache);
+    }
+
+    fn highlight_line(&mut self, line: &str) -> Vec<(Range<usize>, Color)> {
+        if self.current_line / LINES_PER_SNAPSHOT >= self.caches.len() {
+            if let Some(cache) = self.caches.last().cloned() {
+                self.caches.push(cache);
+            }
+        }
+
+        self.current_line += 1;
+
+        let highlighter = highlighting::Highlighter::new(&self.theme);
+
+        let Some((parser, stack)) = self.caches.last_mut() else {
+            return Vec::new();
+        };
+
+        let color = |stack: &parsing::ScopeStack| {
+            highlighter
+                .style_mod_for_stack(stack.as_slice())
+                .foreground
+                .map(|color| {
+                    Color::from_rgba8(color.r, color.g, color.b, f32::from(color.a) / 255.0)
+                })
+        };
+
+        let mut spans = Vec::new();
+        let mut start = 0;
+
+        for (index, operation) in parser.parse_line(line, syntaxes()).unwrap_or_default() {
+            if index > start {
+                if let Some(color) = color(stack) {
+                    spans.push((start..index, color));
+                }
+
+                start = index;
+            }
+
+            let _ = stack.apply(&operation);
+        }
+
+        if start < line.len() {
+            if let Some(color) = color(stack) {
+                spans.push((start..line.len(), color));
+            }
+        }
+
+        spans
+    }
+}
@@ -0,0 +1,37 @@
+use crate::Error;
+
+use global_hotkey::hotkey::HotKey;
+use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
+
+pub struct QuickNote {
+    _manager: GlobalHotKeyManager,
+    id: u32,
+}
+
+impl QuickNote {
+    pub fn register(accelerator: &str) -> Result<Self, Error> {
+        let hotkey = accelerator
+            .parse::<HotKey>()
+            .map_err(|error| Error::InvalidConfig(error.to_string()))?;
+
+        let manager =
+            GlobalHotKeyManager::new().map_err(|error| Error::InvalidConfig(error.to_string()))?;
+
+        manager
+            .register(hotkey)
+            .map_err(|error| Error::InvalidConfig(error.to_string()))?;
+
+        Ok(Self {
+            _manager: manager,
+            id: hotkey.id(),
+        })
+    }
+
+    pub fn is_pressed(&self) -> bool {
+        GlobalHotKeyEvent::receiver()
+            .try_iter()
+            .filter(|event| event.id == self.id && event.state == HotKeyState::Pressed)
+            .count()
+            > 0
+    }
+}
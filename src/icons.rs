@@ -0,0 +1,258 @@
+use crate::{compression, encryption, viewer, Message};
+
+use iced::widget::svg;
+use iced::{theme, Color, Element, Theme};
+use std::path::Path;
+
+const SIZE: u16 = 14;
+
+/// The kinds of file the bundled SVG icons tell apart.
+///
+/// Common languages have their own badge; other source files share the generic code icon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    File,
+    Text,
+    Code,
+    Rust,
+    Python,
+    JavaScript,
+    TypeScript,
+    Go,
+    C,
+    Cpp,
+    Java,
+    Ruby,
+    Lua,
+    Kotlin,
+    Swift,
+    Shell,
+    Css,
+    Html,
+    Sql,
+    Markup,
+    Data,
+    Image,
+    Archive,
+    Encrypted,
+    Folder,
+    OpenFolder,
+}
+
+impl Kind {
+    pub fn of(path: &Path) -> Self {
+        if encryption::Format::detect(path).is_some() {
+            return Kind::Encrypted;
+        }
+
+        if viewer::is_image(path) {
+            return Kind::Image;
+        }
+
+        let Some(extension) = compression::extension(path) else {
+            return match compression::Format::detect(path) {
+                Some(_) => Kind::Archive,
+                None => Kind::File,
+            };
+        };
+
+        match extension.to_ascii_lowercase().as_str() {
+            "rs" => Kind::Rust,
+            "py" | "pyw" | "pyi" | "ipynb" => Kind::Python,
+            "js" | "mjs" | "cjs" | "jsx" => Kind::JavaScript,
+            "ts" | "mts" | "cts" | "tsx" => Kind::TypeScript,
+            "go" => Kind::Go,
+            "c" | "h" => Kind::C,
+            "cpp" | "cc" | "cxx" | "hpp" | "hh" | "hxx" => Kind::Cpp,
+            "java" => Kind::Java,
+            "rb" | "erb" | "gemspec" => Kind::Ruby,
+            "lua" => Kind::Lua,
+            "kt" | "kts" => Kind::Kotlin,
+            "swift" => Kind::Swift,
+            "sh" | "bash" | "zsh" | "fish" | "ps1" | "bat" | "cmd" => Kind::Shell,
+            "css" | "scss" | "sass" | "less" => Kind::Css,
+            "html" | "htm" | "xhtml" | "vue" | "svelte" => Kind::Html,
+            "sql" => Kind::Sql,
+            "cs" | "php" | "scala" | "zig" | "hs" | "ex" | "exs" | "erl" | "dart" | "r" | "pl"
+            | "m" | "jl" | "nim" | "ml" | "clj" | "el" | "vim" => Kind::Code,
+            "md" | "markdown" | "rst" | "adoc" | "asciidoc" | "tex" | "org" => Kind::Markup,
+            "json" | "toml" | "yaml" | "yml" | "xml" | "csv" | "tsv" | "ini" | "lock" => Kind::Data,
+            "txt" | "log" => Kind::Text,
+            "zip" | "tar" | "gz" | "bz2" | "xz" | "zst" | "7z" => Kind::Archive,
+            _ => Kind::File,
+        }
+    }
+
+    fn handle(self) -> svg::Handle {
+        let bytes: &'static [u8] = match self {
+            Kind::File => include_bytes!("../icons/file.svg"),
+            Kind::Text => include_bytes!("../icons/text.svg"),
+            Kind::Code => include_bytes!("../icons/code.svg"),
+            Kind::Rust => include_bytes!("../icons/rust.svg"),
+            Kind::Python => include_bytes!("../icons/python.svg"),
+            Kind::JavaScript => include_bytes!("../icons/javascript.svg"),
+            Kind::TypeScript => include_bytes!("../icons/typescript.svg"),
+            Kind::Go => include_bytes!("../icons/go.svg"),
+            Kind::C => include_bytes!("../icons/c.svg"),
+            Kind::Cpp => include_bytes!("../icons/cpp.svg"),
+            Kind::Java => include_bytes!("../icons/java.svg"),
+            Kind::Ruby => include_bytes!("../icons/ruby.svg"),
+            Kind::Lua => include_bytes!("../icons/lua.svg"),
+            Kind::Kotlin => include_bytes!("../icons/kotlin.svg"),
+            Kind::Swift => include_bytes!("../icons/swift.svg"),
+            Kind::Shell => include_bytes!("../icons/shell.svg"),
+            Kind::Css => include_bytes!("../icons/css.svg"),
+            Kind::Html => include_bytes!("../icons/html.svg"),
+            Kind::Sql => include_bytes!("../icons/sql.svg"),
+            Kind::Markup => include_bytes!("../icons/markup.svg"),
+            Kind::Data => include_bytes!("../icons/data.svg"),
+            Kind::Image => include_bytes!("../icons/image.svg"),
+            Kind::Archive => include_bytes!("../icons/archive.svg"),
+            Kind::Encrypted => include_bytes!("../icons/locked.svg"),
+            Kind::Folder => include_bytes!("../icons/folder.svg"),
+            Kind::OpenFolder => include_bytes!("../icons/folder-open.svg"),
+        };
+
+        svg::Handle::from_memory(bytes)
+    }
+
+    /// A hue per kind, lighter on dark themes so it keeps its contrast.
+    fn color(self, is_dark: bool) -> Option<Color> {
+        let (light, dark) = match self {
+            Kind::File | Kind::Text => return None,
+            Kind::Code => (
+                Color::from_rgb8(0x1f, 0x6f, 0xc5),
+                Color::from_rgb8(0x6c, 0xb6, 0xff),
+            ),
+            Kind::Rust => (
+                Color::from_rgb8(0xb7, 0x41, 0x0e),
+                Color::from_rgb8(0xe8, 0x87, 0x5a),
+            ),
+            Kind::Python => (
+                Color::from_rgb8(0x35, 0x72, 0xa5),
+                Color::from_rgb8(0x7e, 0xb0, 0xe0),
+            ),
+            Kind::JavaScript => (
+                Color::from_rgb8(0x9a, 0x82, 0x00),
+                Color::from_rgb8(0xf1, 0xe0, 0x5a),
+            ),
+            Kind::TypeScript => (
+                Color::from_rgb8(0x31, 0x78, 0xc6),
+                Color::from_rgb8(0x70, 0xa7, 0xe8),
+            ),
+            Kind::Go => (
+                Color::from_rgb8(0x00, 0x80, 0x9d),
+                Color::from_rgb8(0x5c, 0xcf, 0xe6),
+            ),
+            Kind::C => (
+                Color::from_rgb8(0x55, 0x5d, 0x6b),
+                Color::from_rgb8(0xa8, 0xb0, 0xbd),
+            ),
+            Kind::Cpp => (
+                Color::from_rgb8(0xa8, 0x32, 0x6d),
+                Color::from_rgb8(0xf0, 0x7a, 0xae),
+            ),
+            Kind::Java => (
+                Color::from_rgb8(0xb0, 0x72, 0x19),
+                Color::from_rgb8(0xe8, 0xa0, 0x50),
+            ),
+            Kind::Ruby => (
+                Color::from_rgb8(0xa8, 0x1c, 0x3a),
+                Color::from_rgb8(0xf2, 0x6d, 0x8a),
+            ),
+            Kind::Lua => (
+                Color::from_rgb8(0x2c, 0x2c, 0x9c),
+                Color::from_rgb8(0x8f, 0x8f, 0xff),
+            ),
+            Kind::Kotlin => (
+                Color::from_rgb8(0x7f, 0x52, 0xff),
+                Color::from_rgb8(0xb7, 0x9c, 0xff),
+            ),
+            Kind::Swift => (
+                Color::from_rgb8(0xd9, 0x48, 0x2b),
+                Color::from_rgb8(0xff, 0x8a, 0x70),
+            ),
+            Kind::Shell => (
+                Color::from_rgb8(0x3f, 0x7f, 0x25),
+                Color::from_rgb8(0x89, 0xe0, 0x51),
+            ),
+            Kind::Css => (
+                Color::from_rgb8(0x56, 0x3d, 0x7c),
+                Color::from_rgb8(0xa5, 0x8b, 0xd1),
+            ),
+            Kind::Html => (
+                Color::from_rgb8(0xc0, 0x45, 0x1b),
+                Color::from_rgb8(0xf0, 0x80, 0x5a),
+            ),
+            Kind::Sql => (
+                Color::from_rgb8(0x2f, 0x6f, 0x8f),
+                Color::from_rgb8(0x7f, 0xb8, 0xd6),
+            ),
+            Kind::Markup => (
+                Color::from_rgb8(0x2d, 0x8a, 0x4e),
+                Color::from_rgb8(0x7e, 0xd3, 0x97),
+            ),
+            Kind::Data => (
+                Color::from_rgb8(0xa3, 0x6a, 0x00),
+                Color::from_rgb8(0xf0, 0xb4, 0x4c),
+            ),
+            Kind::Image => (
+                Color::from_rgb8(0x8a, 0x3f, 0xb8),
+                Color::from_rgb8(0xc9, 0x93, 0xf0),
+            ),
+            Kind::Archive => (
+                Color::from_rgb8(0x7a, 0x5c, 0x3e),
+                Color::from_rgb8(0xc8, 0xa8, 0x86),
+            ),
+            Kind::Encrypted => (
+                Color::from_rgb8(0xb3, 0x26, 0x1e),
+                Color::from_rgb8(0xff, 0x80, 0x75),
+            ),
+            Kind::Folder | Kind::OpenFolder => (
+                Color::from_rgb8(0x5a, 0x7d, 0x9a),
+                Color::from_rgb8(0x9c, 0xb8, 0xd0),
+            ),
+        };
+
+        Some(if is_dark { dark } else { light })
+    }
+}
+
+pub fn file<'a>(path: &Path) -> Element<'a, Message> {
+    view(Kind::of(path))
+}
+
+pub fn folder<'a>(is_open: bool) -> Element<'a, Message> {
+    view(if is_open {
+        Kind::OpenFolder
+    } else {
+        Kind::Folder
+    })
+}
+
+pub fn view<'a>(kind: Kind) -> Element<'a, Message> {
+    svg(kind.handle())
+        .width(SIZE)
+        .height(SIZE)
+        .style(theme::Svg::Custom(Box::new(Tint(kind))))
+        .into()
+}
+
+struct Tint(Kind);
+
+impl svg::StyleSheet for Tint {
+    type Style = Theme;
+
+    fn appearance(&self, style: &Self::Style) -> svg::Appearance {
+        let palette = style.extended_palette();
+
+        svg::Appearance {
+            // Plain files take the text color, so they work on any background
+            color: Some(
+                self.0
+                    .color(palette.is_dark)
+                    .unwrap_or(palette.background.base.text),
+            ),
+        }
+    }
+}
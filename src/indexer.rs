@@ -0,0 +1,383 @@
+use crate::tree;
+
+use iced::futures::SinkExt;
+use iced::{subscription, Subscription};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::overrides::{Override, OverrideBuilder};
+use notify::{RecursiveMode, Watcher};
+use regex::Regex;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+const MAX_SYMBOLS: usize = 500;
+/// How many files are read between progress reports.
+const BATCH: usize = 250;
+/// How long file events are gathered before the files they name are read again.
+const SETTLE: Duration = Duration::from_millis(300);
+
+/// What is known about every file in the project folder.
+#[derive(Debug, Clone, Default)]
+pub struct Index {
+    files: BTreeMap<PathBuf, File>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct File {
+    symbols: Vec<Symbol>,
+    /// Lowercased three-byte runs within words, for narrowing searches
+    trigrams: HashSet<[u8; 3]>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: String,
+    pub line: usize,
+}
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    Progress(usize, usize),
+    Updated(Arc<Index>),
+    Failed(String),
+}
+
+/// The latest index the background task has sent, and how far along it is.
+#[derive(Debug, Default)]
+pub struct Indexer {
+    pub index: Option<Arc<Index>>,
+    progress: Option<(usize, usize)>,
+    error: Option<String>,
+}
+
+impl Indexer {
+    pub fn handle(&mut self, event: Event) {
+        match event {
+            Event::Progress(done, total) => self.progress = Some((done, total)),
+            Event::Updated(index) => {
+                self.index = Some(index);
+                self.progress = None;
+            }
+            Event::Failed(error) => self.error = Some(error),
+        }
+    }
+
+    pub fn status(&self) -> Option<String> {
+        if let Some((done, total)) = self.progress {
+            return Some(format!("Indexing {done}/{total}"));
+        }
+
+        match (&self.index, &self.error) {
+            (Some(index), Some(_)) => Some(format!(
+                "{} files indexed, not watching",
+                index.file_count()
+            )),
+            (Some(index), None) => Some(format!("{} files indexed", index.file_count())),
+            (None, Some(error)) => Some(format!("Indexing failed: {error}")),
+            (None, None) => None,
+        }
+    }
+}
+
+impl Index {
+    pub fn file_count(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = &Path> {
+        self.files.keys().map(PathBuf::as_path)
+    }
+
+    pub fn symbols(&self) -> impl Iterator<Item = (&Path, &Symbol)> {
+        self.files.iter().flat_map(|(path, file)| {
+            file.symbols
+                .iter()
+                .map(move |symbol| (path.as_path(), symbol))
+        })
+    }
+
+    /// The files that could contain `literal`, or `None` when it is too short to narrow them.
+    pub fn candidates(&self, literal: &str) -> Option<Vec<PathBuf>> {
+        let wanted = trigrams(literal);
+
+        if wanted.is_empty() {
+            return None;
+        }
+
+        Some(
+            self.files
+                .iter()
+                .filter(|(_, file)| wanted.is_subset(&file.trigrams))
+                .map(|(path, _)| path.clone())
+                .collect(),
+        )
+    }
+
+    fn insert(&mut self, path: PathBuf, file: Option<File>) {
+        // A path that is gone may have been a folder, taking its files with it
+        self.files.retain(|other, _| !other.starts_with(&path));
+
+        if let Some(file) = file {
+            self.files.insert(path, file);
+        }
+    }
+}
+
+/// Indexes the folder, then keeps the index current as files change on disk.
+pub fn subscription(root: PathBuf, filter: tree::Filter) -> Subscription<Event> {
+    let id = (
+        "indexer",
+        root.clone(),
+        filter.show_ignored,
+        filter.exclude.clone(),
+    );
+
+    subscription::channel(id, 100, move |mut output| async move {
+        let (sender, mut changes) = mpsc::unbounded_channel();
+
+        let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            if let Ok(event) = event {
+                let _ = sender.send(event.paths);
+            }
+        })
+        .and_then(|mut watcher| {
+            watcher.watch(&root, RecursiveMode::Recursive)?;
+            Ok(watcher)
+        });
+
+        if let Err(error) = &watcher {
+            let _ = output.send(Event::Failed(error.to_string())).await;
+        }
+
+        let rules = Rules::new(&root, &filter);
+        let mut index = Arc::new(Index::default());
+
+        let paths = {
+            let (root, filter) = (root.clone(), filter.clone());
+            tokio::task::spawn_blocking(move || files(&root, &filter))
+                .await
+                .unwrap_or_default()
+        };
+
+        let total = paths.len();
+        let mut done = 0;
+
+        for batch in paths.chunks(BATCH) {
+            let batch = batch.to_vec();
+            done += batch.len();
+
+            let read = tokio::task::spawn_blocking(move || {
+                batch
+                    .into_iter()
+                    .map(|path| {
+                        let file = File::read(&path);
+                        (path, file)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .await
+            .unwrap_or_default();
+
+            Arc::make_mut(&mut index).files.extend(read);
+
+            let _ = output.send(Event::Progress(done, total)).await;
+        }
+
+        let _ = output.send(Event::Updated(Arc::clone(&index))).await;
+
+        while let Some(mut paths) = changes.recv().await {
+            // Saves and checkouts come as bursts of events, so they are read once
+            tokio::time::sleep(SETTLE).await;
+
+            while let Ok(more) = changes.try_recv() {
+                paths.extend(more);
+            }
+
+            paths.sort();
+            paths.dedup();
+
+            // Folders already in the index only report their own metadata changing
+            paths
+                .retain(|path| !path.is_dir() || !index.files().any(|file| file.starts_with(path)));
+
+            let (rules, filter) = (rules.clone(), filter.clone());
+
+            let read = tokio::task::spawn_blocking(move || {
+                paths
+                    .into_iter()
+                    .filter(|path| rules.includes(path))
+                    .flat_map(|path| {
+                        if path.is_dir() {
+                            let mut read = vec![(path.clone(), None)];
+                            read.extend(files(&path, &filter).into_iter().map(|path| {
+                                let file = File::read(&path);
+                                (path, Some(file))
+                            }));
+                            read
+                        } else {
+                            let file = path.is_file().then(|| File::read(&path));
+                            vec![(path, file)]
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .await
+            .unwrap_or_default();
+
+            if read.is_empty() {
+                continue;
+            }
+
+            let changed = Arc::make_mut(&mut index);
+
+            for (path, file) in read {
+                changed.insert(path, file);
+            }
+
+            let _ = output.send(Event::Updated(Arc::clone(&index))).await;
+        }
+
+        // Only reached when the watcher could not start
+        loop {
+            std::future::pending::<()>().await;
+        }
+    })
+}
+
+impl File {
+    fn read(path: &Path) -> Self {
+        let text = std::fs::metadata(path)
+            .ok()
+            .filter(|metadata| metadata.len() <= MAX_FILE_SIZE)
+            .and_then(|_| std::fs::read_to_string(path).ok());
+
+        let Some(text) = text else {
+            return Self::default();
+        };
+
+        let is_markdown = path
+            .extension()
+            .is_some_and(|extension| extension == "md" || extension == "markdown");
+
+        Self {
+            symbols: symbols(&text, is_markdown),
+            trigrams: trigrams(&text),
+        }
+    }
+}
+
+fn files(root: &Path, filter: &tree::Filter) -> Vec<PathBuf> {
+    tree::walk(root, filter)
+        .into_iter()
+        .filter(|entry| !entry.is_dir)
+        .map(|entry| entry.path)
+        .collect()
+}
+
+fn symbols(text: &str, is_markdown: bool) -> Vec<Symbol> {
+    static DEFINITION: OnceLock<Regex> = OnceLock::new();
+    static HEADING: OnceLock<Regex> = OnceLock::new();
+
+    let pattern = if is_markdown {
+        HEADING.get_or_init(|| Regex::new(r"^(#{1,6})\s+(.+?)\s*#*$").unwrap())
+    } else {
+        DEFINITION.get_or_init(|| {
+            Regex::new(
+                r"^\s*(?:(?:pub(?:\([^)]*\))?|export|default|async|unsafe|static|abstract)\s+)*(fn|struct|enum|trait|mod|type|impl|def|class|function|interface|func)\s+([A-Za-z_][A-Za-z0-9_]*)",
+            )
+            .unwrap()
+        })
+    };
+
+    text.lines()
+        .enumerate()
+        .filter_map(|(line, text)| {
+            let captures = pattern.captures(text)?;
+
+            let kind = if is_markdown {
+                String::from("heading")
+            } else {
+                captures[1].to_string()
+            };
+
+            Some(Symbol {
+                name: captures[2].to_string(),
+                kind,
+                line,
+            })
+        })
+        .take(MAX_SYMBOLS)
+        .collect()
+}
+
+fn trigrams(text: &str) -> HashSet<[u8; 3]> {
+    text.to_lowercase()
+        .split(|character: char| !character.is_alphanumeric() && character != '_')
+        .flat_map(|word| {
+            word.as_bytes()
+                .windows(3)
+                .map(|window| [window[0], window[1], window[2]])
+        })
+        .collect()
+}
+
+/// Decides whether a path named by a file event belongs in the index, as the walk would.
+///
+/// Only the `.gitignore` at the root of the folder is read here.
+#[derive(Clone)]
+struct Rules {
+    root: PathBuf,
+    overrides: Override,
+    gitignore: Gitignore,
+    show_ignored: bool,
+}
+
+impl Rules {
+    fn new(root: &Path, filter: &tree::Filter) -> Self {
+        let mut overrides = OverrideBuilder::new(root);
+
+        for pattern in &filter.exclude {
+            let _ = overrides.add(&format!("!{pattern}"));
+        }
+
+        let mut gitignore = GitignoreBuilder::new(root);
+        let _ = gitignore.add(root.join(".gitignore"));
+
+        Self {
+            root: root.to_path_buf(),
+            overrides: overrides.build().unwrap_or_else(|_| Override::empty()),
+            gitignore: gitignore.build().unwrap_or_else(|_| Gitignore::empty()),
+            show_ignored: filter.show_ignored,
+        }
+    }
+
+    fn includes(&self, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+
+        let is_dir = path.is_dir();
+
+        if self.overrides.matched(path, is_dir).is_ignore() {
+            return false;
+        }
+
+        if self.show_ignored {
+            return true;
+        }
+
+        let is_hidden = relative.components().any(|component| {
+            matches!(component, Component::Normal(name) if name.to_string_lossy().starts_with('.'))
+        });
+
+        !is_hidden
+            && !self
+                .gitignore
+                .matched_path_or_any_parents(path, is_dir)
+                .is_ignore()
+    }
+}
@@ -0,0 +1,188 @@
+use crate::Message;
+
+use iced::widget::{container, mouse_area, row, vertical_rule};
+use iced::{mouse, subscription, Element, Event, Length, Subscription};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+const MIN_WIDTH: f32 = 150.0;
+const MAX_WIDTH: f32 = 800.0;
+const HANDLE_WIDTH: u16 = 10;
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+/// A draggable edge between the editor and one of the regions beside it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Divider {
+    /// Between the file tree and the editor
+    Tree,
+    /// Between the editor and the side panel (preview, search and the rest)
+    Panel,
+}
+
+/// How wide the regions beside the editor are, saved with the workspace.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Sizes {
+    pub tree: f32,
+    pub panel: f32,
+    pub tree_collapsed: bool,
+    pub panel_collapsed: bool,
+}
+
+impl Default for Sizes {
+    fn default() -> Self {
+        Self {
+            tree: 250.0,
+            panel: 300.0,
+            tree_collapsed: false,
+            panel_collapsed: false,
+        }
+    }
+}
+
+impl Sizes {
+    pub fn width(&self, divider: Divider) -> f32 {
+        let width = match divider {
+            Divider::Tree => self.tree,
+            Divider::Panel => self.panel,
+        };
+
+        width.clamp(MIN_WIDTH, MAX_WIDTH)
+    }
+
+    pub fn is_collapsed(&self, divider: Divider) -> bool {
+        match divider {
+            Divider::Tree => self.tree_collapsed,
+            Divider::Panel => self.panel_collapsed,
+        }
+    }
+
+    pub fn resize(&mut self, divider: Divider, width: f32) {
+        let width = width.clamp(MIN_WIDTH, MAX_WIDTH);
+
+        match divider {
+            Divider::Tree => self.tree = width,
+            Divider::Panel => self.panel = width,
+        }
+    }
+
+    pub fn toggle(&mut self, divider: Divider) {
+        match divider {
+            Divider::Tree => self.tree_collapsed = !self.tree_collapsed,
+            Divider::Panel => self.panel_collapsed = !self.panel_collapsed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Drag {
+    divider: Divider,
+    /// Where the cursor was when it first moved, since presses don't carry a position
+    origin: Option<f32>,
+    start_width: f32,
+    has_moved: bool,
+}
+
+/// Tracks presses on the dividers, telling drags apart from double clicks.
+#[derive(Debug, Default)]
+pub struct Dividers {
+    drag: Option<Drag>,
+    last_press: Option<(Divider, Instant)>,
+}
+
+impl Dividers {
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    /// Starts a drag, or returns `true` when the press completes a double click.
+    pub fn press(&mut self, divider: Divider, sizes: &Sizes) -> bool {
+        let now = Instant::now();
+        let is_double = self
+            .last_press
+            .is_some_and(|(last, at)| last == divider && now.duration_since(at) < DOUBLE_CLICK);
+
+        if is_double {
+            self.last_press = None;
+            self.drag = None;
+
+            return true;
+        }
+
+        self.last_press = Some((divider, now));
+        self.drag = (!sizes.is_collapsed(divider)).then_some(Drag {
+            divider,
+            origin: None,
+            start_width: sizes.width(divider),
+            has_moved: false,
+        });
+
+        false
+    }
+
+    /// The new width of the dragged region with the cursor at `x`.
+    pub fn drag(&mut self, x: f32) -> Option<(Divider, f32)> {
+        let drag = self.drag.as_mut()?;
+        let origin = *drag.origin.get_or_insert(x);
+        let offset = x - origin;
+
+        drag.has_moved |= offset != 0.0;
+
+        // The tree sits left of its divider and the panel right of it
+        let width = match drag.divider {
+            Divider::Tree => drag.start_width + offset,
+            Divider::Panel => drag.start_width - offset,
+        };
+
+        Some((drag.divider, width))
+    }
+
+    /// Ends the drag, returning whether anything was resized.
+    pub fn release(&mut self) -> bool {
+        self.drag.take().is_some_and(|drag| drag.has_moved)
+    }
+}
+
+pub fn subscription() -> Subscription<Message> {
+    subscription::events_with(|event, _status| match event {
+        Event::Mouse(mouse::Event::CursorMoved { position }) => {
+            Some(Message::DividerDragged(position.x))
+        }
+        Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+            Some(Message::DividerReleased)
+        }
+        _ => None,
+    })
+}
+
+/// The strip between the editor and a region, dragged to resize and double clicked to collapse.
+pub fn handle<'a>(divider: Divider) -> Element<'a, Message> {
+    mouse_area(
+        container(vertical_rule(2))
+            .width(HANDLE_WIDTH)
+            .height(Length::Fill)
+            .center_x(),
+    )
+    .on_press(Message::DividerPressed(divider))
+    .into()
+}
+
+/// Lays a region out at its saved width, or leaves just its handle when collapsed.
+pub fn region<'a>(
+    content: Element<'a, Message>,
+    divider: Divider,
+    sizes: &Sizes,
+) -> Element<'a, Message> {
+    if sizes.is_collapsed(divider) {
+        return handle(divider);
+    }
+
+    let content = container(content)
+        .width(sizes.width(divider))
+        .height(Length::Fill);
+
+    match divider {
+        Divider::Tree => row![content, handle(divider)].into(),
+        Divider::Panel => row![handle(divider), content].into(),
+    }
+}
@@ -0,0 +1,225 @@
+use unicode_width::UnicodeWidthStr;
+
+/// How a file ends its lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Ending {
+    #[default]
+    Lf,
+    Crlf,
+}
+
+impl Ending {
+    /// The ending most of the text's lines use, or LF when it has no line breaks.
+    pub fn detect(text: &str) -> Self {
+        let crlf = text.matches("\r\n").count();
+        let lf = text.matches('\n').count() - crlf;
+
+        if crlf > lf {
+            Ending::Crlf
+        } else {
+            Ending::Lf
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Ending::Lf => "LF",
+            Ending::Crlf => "CRLF",
+        }
+    }
+
+    pub fn toggled(self) -> Self {
+        match self {
+            Ending::Lf => Ending::Crlf,
+            Ending::Crlf => Ending::Lf,
+        }
+    }
+
+    /// The text with every line ended this way, whatever it used before.
+    pub fn apply(self, text: String) -> String {
+        let text = if text.contains('\r') {
+            text.replace("\r\n", "\n")
+        } else {
+            text
+        };
+
+        match self {
+            Ending::Lf => text,
+            Ending::Crlf => text.replace('\n', "\r\n"),
+        }
+    }
+}
+
+const COMMENT_MARKERS: &[&str] = &["///", "//!", "//", "#", "--", ";", "*"];
+const BULLETS: &[&str] = &["- ", "* ", "+ "];
+
+pub fn join<'a>(lines: impl IntoIterator<Item = &'a str>) -> String {
+    let mut lines = lines.into_iter();
+    let mut joined = lines.next().unwrap_or_default().trim_end().to_string();
+
+    let marker = comment_marker(&joined);
+
+    for line in lines {
+        let mut line = line.trim();
+
+        if let Some(rest) = comment_marker(line)
+            .filter(|next| marker == Some(*next) || *next == "*")
+            .and_then(|marker| line.strip_prefix(marker))
+        {
+            line = rest.trim_start();
+        }
+
+        if line.is_empty() {
+            continue;
+        }
+
+        if !joined.is_empty() && !joined.ends_with(char::is_whitespace) {
+            joined.push(' ');
+        }
+
+        joined.push_str(line);
+    }
+
+    joined
+}
+
+pub fn reflow<'a>(lines: impl IntoIterator<Item = &'a str>, width: usize) -> String {
+    let lines: Vec<_> = lines.into_iter().collect();
+    let mut output = Vec::new();
+    let mut paragraph = Vec::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        if body(line, comment_marker(line)).trim().is_empty() {
+            if !paragraph.is_empty() {
+                output.push(wrap(&paragraph, width));
+                paragraph.clear();
+            }
+
+            output.push(line.trim_end().to_string());
+        } else if paragraph.is_empty() || !starts_item(line, lines.get(index + 1).copied()) {
+            paragraph.push(*line);
+        } else {
+            output.push(wrap(&paragraph, width));
+            paragraph = vec![*line];
+        }
+    }
+
+    if !paragraph.is_empty() {
+        output.push(wrap(&paragraph, width));
+    }
+
+    output.join("\n")
+}
+
+/// Roughly how many rows a line takes when wrapped at word boundaries to `columns` cells.
+pub fn rows(line: &str, columns: usize) -> usize {
+    let columns = columns.max(1);
+    let mut rows = 1;
+    let mut used = 0;
+
+    for word in line.split_inclusive(' ') {
+        let trimmed = word.trim_end_matches(' ');
+        let width = trimmed.width();
+
+        if used > 0 && used + width > columns {
+            rows += 1;
+            used = 0;
+        }
+
+        // A word longer than a row is broken wherever it runs out of room
+        if used == 0 && width > columns {
+            rows += (width - 1) / columns;
+            used = width - (width - 1) / columns * columns;
+        } else {
+            used += width;
+        }
+
+        // Spaces hang past the edge instead of starting a row
+        used += word.len() - trimmed.len();
+    }
+
+    rows
+}
+
+fn wrap(lines: &[&str], width: usize) -> String {
+    let first = lines[0];
+    let marker = comment_marker(first).filter(|marker| {
+        // A lone `*` line is a list bullet rather than a block comment
+        *marker != "*" || (lines.len() > 1 && comment_marker(lines[1]) == Some("*"))
+    });
+
+    let indent = &first[..first.len() - first.trim_start().len()];
+    let rest = body(first, marker);
+    let spacing = &rest[..rest.len() - rest.trim_start().len()];
+    let prefix = match marker {
+        Some(marker) => format!(
+            "{indent}{marker}{}",
+            if spacing.is_empty() { " " } else { spacing }
+        ),
+        None => indent.to_string(),
+    };
+
+    let rest = rest.trim_start();
+    let bullet = bullet(rest).unwrap_or("");
+    let continuation = format!("{prefix}{}", " ".repeat(bullet.chars().count()));
+
+    let words = std::iter::once(&rest[bullet.len()..])
+        .chain(lines[1..].iter().map(|line| body(line, marker)))
+        .flat_map(str::split_whitespace);
+
+    let mut wrapped = Vec::new();
+    let mut line = format!("{prefix}{bullet}");
+    let mut is_empty = true;
+
+    for word in words {
+        if !is_empty && line.chars().count() + 1 + word.chars().count() > width {
+            wrapped.push(std::mem::replace(&mut line, continuation.clone()));
+            is_empty = true;
+        }
+
+        if !is_empty {
+            line.push(' ');
+        }
+
+        line.push_str(word);
+        is_empty = false;
+    }
+
+    wrapped.push(line);
+    wrapped.join("\n")
+}
+
+fn body<'a>(line: &'a str, marker: Option<&str>) -> &'a str {
+    let line = line.trim_start();
+
+    marker
+        .and_then(|marker| line.strip_prefix(marker))
+        .unwrap_or(line)
+}
+
+fn starts_item(line: &str, next: Option<&str>) -> bool {
+    let marker = comment_marker(line)
+        .filter(|marker| *marker != "*" || next.and_then(comment_marker) == Some("*"));
+
+    bullet(body(line, marker).trim_start()).is_some()
+}
+
+fn bullet(text: &str) -> Option<&str> {
+    if let Some(bullet) = BULLETS.iter().find(|bullet| text.starts_with(*bullet)) {
+        return Some(bullet);
+    }
+
+    let digits = text.chars().take_while(char::is_ascii_digit).count();
+
+    (digits > 0 && (text[digits..].starts_with(". ") || text[digits..].starts_with(") ")))
+        .then(|| &text[..digits + 2])
+}
+
+fn comment_marker(line: &str) -> Option<&'static str> {
+    let line = line.trim_start();
+
+    COMMENT_MARKERS
+        .iter()
+        .find(|marker| line.starts_with(*marker))
+        .copied()
+}
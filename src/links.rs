@@ -0,0 +1,114 @@
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Link {
+    Url(String),
+    Path(String),
+}
+
+impl Link {
+    pub fn resolve(&self, base: Option<&Path>) -> Option<PathBuf> {
+        match self {
+            Link::Url(_) => None,
+            Link::Path(path) => {
+                let path = match path.strip_prefix("~/") {
+                    Some(rest) => std::env::var_os("HOME").map(PathBuf::from)?.join(rest),
+                    None => PathBuf::from(path),
+                };
+
+                if path.is_absolute() {
+                    Some(path)
+                } else {
+                    let directory = base.and_then(Path::parent).unwrap_or(Path::new("."));
+
+                    Some(directory.join(path))
+                }
+            }
+        }
+    }
+}
+
+pub fn find(line: &str) -> Vec<(Range<usize>, Link)> {
+    tokens(line)
+        .filter_map(|(start, token)| {
+            let token = token.trim_end_matches(['.', ',', ':', ';', '!', '?']);
+
+            let link = if token.starts_with("https://") || token.starts_with("http://") {
+                Link::Url(token.to_string())
+            } else if is_path(token) {
+                Link::Path(strip_location(token).to_string())
+            } else {
+                return None;
+            };
+
+            Some((start..start + token.len(), link))
+        })
+        .collect()
+}
+
+/// The link under a cursor, with `column` counted in characters like the editor's cursor.
+pub fn at(line: &str, column: usize) -> Option<Link> {
+    let offset = line
+        .char_indices()
+        .nth(column)
+        .map_or(line.len(), |(index, _)| index);
+
+    find(line)
+        .into_iter()
+        .find(|(range, _)| range.start <= offset && offset < range.end)
+        .map(|(_, link)| link)
+}
+
+/// Splits a line at delimiters, keeping the byte offset each token starts at.
+fn tokens(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut start = 0;
+
+    line.char_indices()
+        .filter(|(_, c)| c.is_whitespace() || "\"'`()<>[]{},;".contains(*c))
+        .map(|(index, c)| (index, index + c.len_utf8()))
+        .chain(std::iter::once((line.len(), line.len())))
+        .map(move |(end, next)| {
+            let token = (start, &line[start..end]);
+            start = next;
+
+            token
+        })
+        .filter(|(_, token)| !token.is_empty())
+}
+
+fn is_path(token: &str) -> bool {
+    if token.contains("://") || !token.contains('/') {
+        return false;
+    }
+
+    if token.starts_with("./") || token.starts_with("../") || token.starts_with("~/") {
+        return true;
+    }
+
+    let file = strip_location(token).rsplit('/').next().unwrap_or_default();
+
+    match file.rsplit_once('.') {
+        Some((name, extension)) => {
+            !name.is_empty()
+                && !extension.is_empty()
+                && extension.chars().all(|c| c.is_ascii_alphanumeric())
+        }
+        None => false,
+    }
+}
+
+fn strip_location(token: &str) -> &str {
+    let mut path = token;
+
+    for _ in 0..2 {
+        match path.rsplit_once(':') {
+            Some((rest, number)) if number.chars().all(|c| c.is_ascii_digit()) => {
+                path = rest;
+            }
+            _ => break,
+        }
+    }
+
+    path
+}
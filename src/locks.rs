@@ -0,0 +1,48 @@
+use fs2::FileExt;
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Default)]
+pub struct Locks {
+    files: BTreeMap<PathBuf, File>,
+}
+
+impl Locks {
+    pub fn acquire(&mut self, path: &Path) -> bool {
+        if self.files.contains_key(path) {
+            return true;
+        }
+
+        let Ok(file) = OpenOptions::new().read(true).open(path) else {
+            return true;
+        };
+
+        if file.try_lock_exclusive().is_err() {
+            return false;
+        }
+
+        self.files.insert(path.to_path_buf(), file);
+
+        true
+    }
+
+    pub fn release(&mut self, path: &Path) {
+        if let Some(file) = self.files.remove(path) {
+            let _ = file.unlock();
+        }
+    }
+
+    pub fn retain(&mut self, paths: &[PathBuf]) {
+        let released: Vec<_> = self
+            .files
+            .keys()
+            .filter(|path| !paths.contains(path))
+            .cloned()
+            .collect();
+
+        for path in released {
+            self.release(&path);
+        }
+    }
+}
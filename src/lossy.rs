@@ -0,0 +1,105 @@
+use similar::{ChangeTag, TextDiff};
+
+pub const MARKER: char = char::REPLACEMENT_CHARACTER;
+
+#[derive(Debug, Clone)]
+pub struct Lossy {
+    decoded: String,
+    // The original bytes behind every marker in `decoded`, in order
+    spans: Vec<Vec<u8>>,
+}
+
+impl Lossy {
+    pub fn invalid(&self) -> usize {
+        self.spans
+            .iter()
+            .filter(|span| std::str::from_utf8(span).is_err())
+            .count()
+    }
+
+    /// Puts each marker that survived the edit back to the bytes it stood for.
+    ///
+    /// The lines are diffed first, then the characters of only the changed lines that held
+    /// markers. Neither has a deadline, as a coarser diff would mistake kept markers for new ones.
+    pub fn encode(&self, text: &str) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(text.len());
+        let mut spans = self.spans.iter().peekable();
+
+        let lines = TextDiff::from_lines(self.decoded.as_str(), text);
+
+        for op in lines.ops() {
+            let old = lines.old_slices()[op.old_range()].concat();
+            let new = lines.new_slices()[op.new_range()].concat();
+
+            if !old.contains(MARKER) {
+                bytes.extend_from_slice(new.as_bytes());
+                continue;
+            }
+
+            for change in TextDiff::from_chars(old.as_str(), new.as_str()).iter_all_changes() {
+                let value = change.value();
+
+                match change.tag() {
+                    ChangeTag::Equal => {
+                        for c in value.chars() {
+                            match spans.next_if(|_| c == MARKER) {
+                                Some(span) => bytes.extend_from_slice(span),
+                                None => {
+                                    bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes())
+                                }
+                            }
+                        }
+                    }
+                    ChangeTag::Delete => {
+                        spans
+                            .by_ref()
+                            .take(value.matches(MARKER).count())
+                            .for_each(drop);
+                    }
+                    ChangeTag::Insert => bytes.extend_from_slice(value.as_bytes()),
+                }
+            }
+        }
+
+        bytes
+    }
+}
+
+pub fn decode(mut bytes: &[u8]) -> (String, Lossy) {
+    let mut decoded = String::with_capacity(bytes.len());
+    let mut spans = Vec::new();
+
+    loop {
+        match std::str::from_utf8(bytes) {
+            Ok(valid) => {
+                push(&mut decoded, &mut spans, valid);
+                break;
+            }
+            Err(error) => {
+                let (valid, rest) = bytes.split_at(error.valid_up_to());
+                let invalid = error.error_len().unwrap_or(rest.len());
+
+                push(
+                    &mut decoded,
+                    &mut spans,
+                    std::str::from_utf8(valid).unwrap_or_default(),
+                );
+                decoded.push(MARKER);
+                spans.push(rest[..invalid].to_vec());
+
+                bytes = &rest[invalid..];
+            }
+        }
+    }
+
+    (decoded.clone(), Lossy { decoded, spans })
+}
+
+fn push(decoded: &mut String, spans: &mut Vec<Vec<u8>>, valid: &str) {
+    // Genuine replacement characters need a span too, so markers stay in step
+    for _ in valid.matches(MARKER) {
+        spans.push(MARKER.to_string().into_bytes());
+    }
+
+    decoded.push_str(valid);
+}
@@ -1,20 +1,177 @@
-use iced::highlighter::{self, Highlighter};
+mod about;
+mod assistant;
+mod backdrop;
+mod bom;
+mod calc;
+mod cells;
+mod checksums;
+mod collab;
+mod colors;
+mod completion;
+mod compression;
+mod config;
+mod conflict;
+mod database;
+mod debounce;
+mod debug;
+mod dictation;
+mod diff;
+mod elevate;
+mod emmet;
+mod encryption;
+mod filter;
+mod focus;
+mod generators;
+mod git;
+mod goals;
+mod highlight;
+mod hotkey;
+mod icons;
+mod indexer;
+mod layout;
+mod lines;
+mod links;
+mod locks;
+mod lossy;
+mod memory;
+mod menu;
+mod merge;
+mod motion;
+mod offline;
+mod palette;
+mod patch;
+mod pattern;
+mod perf;
+mod preview;
+mod primary;
+mod problems;
+mod properties;
+mod prose;
+mod remote;
+mod render;
+mod repl;
+mod rest;
+mod runner;
+mod scope;
+mod scratch;
+mod search;
+mod secrets;
+mod session;
+mod speech;
+mod stats;
+mod status;
+mod suspicious;
+mod sync;
+mod table;
+mod tabs;
+mod tail;
+mod tasks;
+mod themes;
+mod toast;
+mod toc;
+mod todos;
+mod toolbar;
+mod transforms;
+mod tray;
+mod tree;
+mod unicode;
+mod vault;
+mod viewer;
+mod web;
+mod widgets;
+mod words;
+mod workspace;
+
+use iced::event;
+use iced::highlighter;
 use iced::keyboard;
+use iced::mouse;
+use iced::subscription;
 use iced::theme;
+use iced::time;
+use iced::widget::image;
 use iced::widget::{
-    button, column, container, horizontal_space, pick_list, row, text, text_editor, tooltip,
+    button, checkbox, column, container, horizontal_space, mouse_area, pick_list, progress_bar,
+    row, scrollable, text, text_editor, tooltip, Row,
+};
+use iced::window;
+use iced::{
+    executor, Application, Command, Element, Event, Font, Length, Point, Settings, Subscription,
+    Theme, Vector,
 };
-use iced::{executor, Application, Command, Element, Font, Length, Settings, Subscription, Theme};
+use links::Link;
 use std::io;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+const LINE_HEIGHT: f32 = 16.0 * 1.3;
+const CHARACTER_WIDTH: f32 = 16.0 * 0.6;
+const EDITOR_PADDING: f32 = 10.0;
+const WRAP_COLUMNS: [u16; 3] = [80, 100, 120];
+const REFLOW_WIDTH: u16 = 80;
+const SEARCH_URL: &str = "https://duckduckgo.com/?q=";
+const CHROME_HEIGHT: f32 = 170.0;
+const FOCUS_CHROME_HEIGHT: f32 = 20.0;
 
 fn main() -> iced::Result {
-    Editor::run(Settings {
+    // Spans around update and view, for profiling typing latency
+    if std::env::var_os("RUST_LOG").is_some() {
+        tracing_subscriber::fmt()
+            .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+            .init();
+    }
+
+    let flags = Flags::parse();
+    let renderer = flags.renderer.clone();
+    renderer.select();
+
+    let result = Editor::run(Settings {
         default_font: Font::MONOSPACE,
         fonts: vec![include_bytes!("../fonts/editor.ttf").as_slice().into()],
+        flags,
+        exit_on_close_request: false,
+        window: window::Settings {
+            transparent: config::Config::read().window_opacity() < 1.0,
+            ..window::Settings::default()
+        },
         ..Settings::default()
-    })
+    });
+
+    match result {
+        Err(iced::Error::GraphicsCreationFailed(error)) if renderer == render::Renderer::Gpu => {
+            eprintln!("The GPU renderer failed to start ({error}), retrying in software");
+
+            render::restart_in_software()
+        }
+        result => result,
+    }
+}
+
+#[derive(Debug, Default)]
+struct Flags {
+    target: Option<String>,
+    session: Option<String>,
+    renderer: render::Renderer,
+}
+
+impl Flags {
+    fn parse() -> Self {
+        let mut flags = Self::default();
+        let mut args = std::env::args().skip(1);
+        let mut software_render = false;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--session" => flags.session = args.next(),
+                "--software-render" => software_render = true,
+                _ => flags.target = Some(arg),
+            }
+        }
+
+        flags.renderer = render::Renderer::choose(software_render);
+        flags
+    }
 }
 
 struct Editor {
@@ -22,233 +179,8388 @@ struct Editor {
     content: text_editor::Content,
     error: Option<Error>,
     theme: highlighter::Theme,
+    /// A user theme in use, drawn over the built-in one
+    custom_theme: Option<themes::Theme>,
+    themes: Vec<themes::Theme>,
     is_dirty: bool,
+    modifiers: keyboard::Modifiers,
+    panel: Option<Panel>,
+    scan_folder: bool,
+    pending_jump: Option<usize>,
+    image: Option<viewer::Image>,
+    table: Option<table::Table>,
+    tail: Option<tail::Tail>,
+    remote: Option<remote::Session>,
+    url: Option<String>,
+    config: config::Config,
+    collab: Option<collab::Session>,
+    palette: Option<palette::Palette>,
+    workspace: Option<workspace::Workspace>,
+    tree: Option<tree::Tree>,
+    show_tree: bool,
+    tabs: Vec<tabs::Tab>,
+    active: usize,
+    tab_menu: Option<usize>,
+    dragging: Option<usize>,
+    history: Vec<usize>,
+    switcher: Option<usize>,
+    expansions: Vec<std::ops::Range<usize>>,
+    /// The tab and cursor position between the pair typed last, if nothing has happened since
+    auto_pair: Option<(usize, (usize, usize))>,
+    inspect: bool,
+    focus_mode: bool,
+    window_height: f32,
+    editor_menu: bool,
+    primary: primary::Selection,
+    tray: Option<tray::Tray>,
+    quick_note: Option<hotkey::QuickNote>,
+    session: Option<String>,
+    positions: session::Positions,
+    goals: goals::Goals,
+    words: usize,
+    speech: Option<speech::Reader>,
+    dictation: Option<dictation::Recording>,
+    is_transcribing: bool,
+    save_denied: bool,
+    link: Option<PathBuf>,
+    replace_link: bool,
+    locks: locks::Locks,
+    is_read_only: bool,
+    bom: Option<bom::Bom>,
+    line_ending: lines::Ending,
+    lossy: Option<lossy::Lossy>,
+    offer_lossy: Option<PathBuf>,
+    completion: completion::Completion,
+    secrets_confirmed: bool,
+    vault: Option<vault::Key>,
+    checksums: checksums::Checksums,
+    changed_outside: bool,
+    focus: focus::Region,
+    /// Whether the focus ring shows, which it only does after moving focus by keyboard
+    focus_visible: bool,
+    /// Panel sizes while no workspace is open to keep them
+    layout: layout::Sizes,
+    dividers: layout::Dividers,
+    branch: Option<String>,
+    menu: Option<menu::Menu>,
+    system_reduces_motion: bool,
+    renderer: render::Renderer,
+    hud: Option<perf::Hud>,
+    pending: debounce::Pending,
+    indexer: indexer::Indexer,
+    tasks: tasks::Tasks,
+    /// When the file was last read or written here, to catch changes made elsewhere
+    modified: Option<SystemTime>,
+    merge: Option<merge::Merge>,
+    blame: Option<git::Blame>,
+    toast: Option<toast::Toast>,
+    /// The last command run by "Filter through command", offered again next time
+    last_filter: String,
+    run: Option<runner::Run>,
+    /// Counts task and debug runs, so output from an earlier run is never taken for the current one
+    runs: u64,
+    problems: Vec<problems::Problem>,
+    debug: Option<debug::Session>,
+    breakpoints: debug::Breakpoints,
+    /// The connection SQL statements run against, by its name in the config
+    database: Option<String>,
+    notebook: Option<cells::Notebook>,
+    repls: Vec<repl::Repl>,
+    active_repl: usize,
+    repl_history: repl::History,
+    offline: offline::Queue,
+}
+
+enum Panel {
+    Todos(todos::List),
+    Swatch(colors::Picker),
+    Remote(remote::Dialog),
+    Sync(sync::Panel),
+    Collab(collab::Dialog),
+    Diff(diff::Diff),
+    Unicode(unicode::Picker),
+    Scratch(scratch::Menu),
+    Statistics(Option<stats::Statistics>),
+    Memory,
+    Blame(git::Gutter),
+    History(git::History),
+    Changes(Option<git::Changes>),
+    Patch(patch::Report),
+    Filter(filter::Prompt),
+    Output,
+    Problems(problems::List),
+    Debug,
+    Response(Option<rest::Response>),
+    Query(Option<database::Results>),
+    Cells,
+    Repl,
+    Pattern(pattern::Tester),
+    Calculation(calc::Sheet),
+    LineTool(transforms::Prompt),
+    Columns(table::Prompt),
+    TabLabel(tabs::Prompt),
+    ThemeEditor(themes::Draft),
+    Sessions(session::Manager),
+    Search(search::Search),
+    Properties(Option<properties::Properties>),
+    Preview(Option<preview::Preview>),
+    Outline(Vec<toc::Heading>),
+    Colors(Vec<(usize, colors::Swatch)>),
+    Goals(goals::Dialog),
+    Assistant(assistant::Assistant),
+    Secrets(secrets::Warning, Option<Box<Panel>>),
+    Passphrase(vault::Prompt),
+    Conflict(PathBuf),
+    StatusBar,
+    Toolbar,
+    About,
 }
+
 #[derive(Debug, Clone)]
 enum Message {
     Edit(text_editor::Action),
+    CycleFocus(i32),
+    ActivateFocused,
+    DividerPressed(layout::Divider),
+    DividerDragged(f32),
+    DividerReleased,
+    ToggleStatusSettings,
+    ToggleToolbarSettings,
+    ToolbarButtonToggled(toolbar::Button, bool),
+    ToolbarButtonMoved(toolbar::Button, i32),
+    ToolbarStyleSelected(toolbar::Style),
+    StatusSegmentToggled(status::Slot, bool),
+    StatusSegmentMoved(status::Slot, i32),
+    BranchLoaded(Option<String>),
+    MenuOpened(Option<menu::Menu>),
+    MenuRun(Box<Message>),
+    MotionPreferenceLoaded(bool),
+    ShowAbout,
+    TogglePerformanceHud,
+    ShowMemory,
+    TaskFinished(tasks::Ticket, Option<Box<Message>>),
+    ModifiedRead((PathBuf, Option<SystemTime>)),
+    ResolveConflict(conflict::Resolution),
+    ConflictDiskRead(Result<(PathBuf, Arc<String>, Option<bom::Bom>), Error>),
+    StartMerge,
+    MergeFiles,
+    MergeLoaded(Result<merge::Merge, Error>),
+    MergeChose(usize, merge::Choice),
+    MergeNavigated(i32),
+    WriteMerge,
+    MergeWritten(Result<PathBuf, Error>),
+    CancelMerge,
+    ToggleInlineBlame,
+    ToggleBlameGutter,
+    BlameLoaded(Result<git::Blame, Error>),
+    BlameScrolled(scrollable::Viewport),
+    ShowCommit(String),
+    CommitLoaded(Result<(String, String), Error>),
+    ToggleHistory,
+    HistoryLoaded(Result<git::History, Error>),
+    RevisionSelected(usize),
+    PatchLoaded(Result<(String, String), Error>),
+    HistoryScrolled(scrollable::Viewport),
+    OpenRevision,
+    RevisionOpened(Result<(String, String), Error>),
+    ToggleChanges,
+    ChangesRead(Result<git::Changes, Error>),
+    HunkSelected(usize),
+    StageHunk(usize),
+    HunkStaged(Result<PathBuf, Error>),
+    RevertHunk(usize),
+    CopyPermalink,
+    PermalinkBuilt(Result<String, Error>),
+    CheckPatch,
+    PatchChecked(Result<patch::Report, Error>),
+    ApplyPatch,
+    PatchApplied(Result<usize, Error>),
+    ToggleFilter,
+    FilterCommandChanged(String),
+    RunFilter,
+    FilterFinished(Result<filter::Output, Error>),
+    ToastTick,
+    DismissToast,
+    RunWorkspaceTask(String),
+    TaskOutput(runner::Event),
+    StopTaskRun,
+    OutputScrolled(scrollable::Viewport),
+    ShowOutput,
+    ShowProblems,
+    ProblemSelected(usize),
+    StartDebugging(String),
+    Debug(debug::Event),
+    ToggleBreakpoint,
+    DebugControl(debug::Control),
+    WatchChanged(String),
+    AddWatch,
+    RemoveWatch(usize),
+    ToggleDebugPanel,
+    SendRequest,
+    ResponseReceived(Result<rest::Response, Error>),
+    OpenResponseBody,
+    UseDatabase(String),
+    RunQuery,
+    QueryFinished(Result<database::Results, Error>),
+    ResultsPage(usize),
+    ExportQuery,
+    QueryExported(Result<PathBuf, Error>),
+    ToggleCells,
+    RunCell,
+    RunCellAt(usize),
+    RunAllCells,
+    RestartKernel,
+    Cells(cells::Event),
+    CellSelected(usize),
+    CellRan(usize, Result<filter::Output, Error>),
+    StartRepl(String),
+    ReplEvent(repl::Event),
+    ReplInputChanged(String),
+    SubmitRepl,
+    RecallRepl(isize),
+    SelectRepl(usize),
+    CloseRepl(usize),
+    SendToRepl,
+    ToggleRepls,
+    ReplScrolled(scrollable::Viewport),
+    ReplHistoryLoaded(Result<repl::History, Error>),
+    ReplHistorySaved(Result<(), Error>),
+    ToggleRegexTester,
+    PatternChanged(String),
+    PatternFlagToggled(pattern::Flag),
+    SampleEdited(text_editor::Action),
+    SearchWithPattern,
+    EvictBackgroundTabs,
+    FrameRendered(Instant),
+    SetReducedMotion(Option<bool>),
     New,
-    FileOpened(Result<(PathBuf, Arc<String>), Error>),
+    FileOpened(Result<(PathBuf, Arc<String>, Option<bom::Bom>), Error>),
+    ImageOpened(Result<(PathBuf, viewer::Image), Error>),
     Open,
+    FilePicked(Result<PathBuf, Error>),
     Save,
     FileSaved(Result<PathBuf, Error>),
     ThemeSelected(highlighter::Theme),
+    ThemesLoaded(Result<Vec<themes::Theme>, Error>),
+    ThemesChanged,
+    CustomThemeSelected(String),
+    ToggleThemeEditor,
+    ThemeNameChanged(String),
+    ThemeTargetSelected(themes::Target),
+    ThemeColorChanged(iced::Color),
+    ThemeHexChanged(String),
+    ExportTheme,
+    ThemeExported(Result<PathBuf, Error>),
+    ImportTheme,
+    ThemeImported(Result<themes::Theme, Error>),
+    ModifiersChanged(keyboard::Modifiers),
+    ToggleTodos,
+    ScanFolderToggled(bool),
+    TodosScanned(Vec<todos::Group>),
+    TodoSelected(Option<PathBuf>, usize),
+    PickColor,
+    SwatchChanged(iced::Color),
+    ApplySwatch,
+    ClosePanel,
+    ZoomImage(viewer::Zoom),
+    ToggleTable,
+    TableChanged(table::Action),
+    ToggleTail,
+    TailStarted(Result<u64, Error>),
+    TailTick,
+    TailRead(Result<tail::Chunk, Error>),
+    TailFilterChanged(String),
+    TailRegexToggled(bool),
+    OpenRemote,
+    RemoteUrlChanged(String),
+    RemotePasswordChanged(String),
+    RemoteAgentToggled(bool),
+    ConnectRemote,
+    RemoteOpened(Result<(remote::Session, String), Error>),
+    RemoteSaved(Result<(), Error>),
+    UrlOpened(Result<(String, String), Error>),
+    ConfigLoaded(Result<config::Config, Error>),
+    ToggleSync,
+    SyncEndpointChanged(String),
+    SyncNow,
+    Synced(Result<sync::Report, Error>),
+    ToggleCollab,
+    CollabNameChanged(String),
+    CollabPortChanged(String),
+    CollabAddressChanged(String),
+    CollabKeyChanged(String),
+    StartCollab(collab::Role),
+    Collab(collab::Event),
+    Indexed(indexer::Event),
+    TogglePalette,
+    ClosePalette,
+    PaletteChanged(String),
+    PaletteMoved(isize),
+    PaletteSubmitted,
+    PaletteRun(Box<Message>),
+    ConfigSaved(Result<(), Error>),
+    OpenFolder,
+    FolderPicked(Result<PathBuf, Error>),
+    SwitchWorkspace(PathBuf),
+    WorkspaceOpened(Result<workspace::Workspace, Error>),
+    WorkspaceSaved(Result<(), Error>),
+    FormatDocument,
+    Formatted(Result<String, Error>),
+    ToggleTree,
+    TreeIndexed(PathBuf, Vec<tree::Entry>),
+    TreeToggled(PathBuf),
+    TreeSelected(PathBuf),
+    ShowIgnoredToggled(bool),
+    TreeFilterChanged(String),
+    TabSelected(usize),
+    TabMenu(Option<usize>),
+    TabAction(usize, tabs::Action),
+    TabNameChanged(String),
+    TabTagSelected(Option<tabs::Tag>),
+    ApplyTabLabel,
+    TabPressed(usize),
+    TabDropped(usize),
+    TabDragCancelled,
+    CycleTabs(isize),
+    CompareClipboard,
+    DiffScrolled(scrollable::Viewport),
+    SearchScrolled(scrollable::Viewport),
+    ProblemsScrolled(scrollable::Viewport),
+    TodosScrolled(scrollable::Viewport),
+    TableScrolled(scrollable::Viewport),
+    ClipboardCompared(String, Option<String>),
+    Generate(generators::Generator),
+    Transform(transforms::Transform, transforms::Output),
+    EvaluateSelection(transforms::Output),
+    ToggleCalculation,
+    CalculationScrolled(scrollable::Viewport),
+    ShowLineTool(transforms::Tool),
+    LineToolInputChanged(String),
+    SortNumericToggled(bool),
+    SortDescendingToggled(bool),
+    ApplyLineTool,
+    ShowColumnTool(table::Operation),
+    ColumnDelimiterChanged(String),
+    ColumnChanged(String),
+    OtherColumnChanged(String),
+    ApplyColumnTool,
+    ExpandAbbreviation,
+    ExpandSelection,
+    ShrinkSelection,
+    PasteAndIndent,
+    PasteAsColumn,
+    ColumnPasted(Option<String>),
+    PasteIndented(Option<String>),
+    JoinLines,
+    ReflowParagraph,
+    ToggleInspector,
+    OpenUnicodePicker,
+    UnicodeQueryChanged(String),
+    InsertCharacter(char),
+    FindSuspicious,
+    StripSuspicious,
+    ToggleScratch,
+    ScratchesListed(Result<Vec<String>, Error>),
+    ScratchNameChanged(String),
+    CreateScratch,
+    OpenScratch(String),
+    ScratchCreated(Result<PathBuf, Error>),
+    AutosaveScratch,
+    ScratchSaved(Result<PathBuf, Error>),
+    ShowStatistics,
+    StatisticsComputed(stats::Statistics),
+    ToggleFocusMode,
+    ToggleDimParagraphs,
+    ToggleTypewriter,
+    WindowResized(u32),
+    WrapAt(Option<u16>),
+    EditorMenu(bool),
+    Cut,
+    Copy,
+    Paste,
+    Pasted(Option<String>),
+    SelectAll,
+    SearchWeb,
+    PastePrimary(Point),
+    ToggleTrayIcon,
+    ToggleMinimizeToTray,
+    TrayPolled,
+    CloseRequested,
+    QuickNotePolled,
+    ToggleSessions,
+    SessionsListed(Result<Vec<String>, Error>),
+    SessionNameChanged(String),
+    SaveSession,
+    SessionSaved(Result<String, Error>),
+    SwitchSession(String),
+    SessionLoaded(Result<(String, session::Session), Error>),
+    SessionWorkspaceOpened(bool, Result<workspace::Workspace, Error>),
+    RenameSession(String),
+    DeleteSession(String),
+    SessionDeleted(Result<(), Error>),
+    ToggleSearch,
+    SearchQueryChanged(String),
+    ReplacementChanged(String),
+    SearchRegexToggled(bool),
+    RunSearch,
+    SearchFinished(Vec<search::Group>),
+    SearchFileToggled(usize),
+    SearchMatchToggled(usize, usize),
+    SearchResultSelected(PathBuf, usize),
+    ApplyReplacements,
+    ReplacementsApplied(Result<search::Summary, Error>),
+    ExportResults(search::Export),
+    ResultsExported(Result<PathBuf, Error>),
+    PositionsLoaded(Result<session::Positions, Error>),
+    PositionsSaved(Result<(), Error>),
+    ToggleChecksums,
+    ChecksumsLoaded(Result<checksums::Checksums, Error>),
+    ChecksumsSaved(Result<(), Error>),
+    ChecksumVerified(Result<(PathBuf, String), Error>),
+    ChecksumRecorded(Result<(PathBuf, String), Error>),
+    CopyChecksum,
+    ChecksumCopied(Result<(PathBuf, String), Error>),
+    ToggleWritingGoals,
+    EncryptFile,
+    DecryptFile,
+    PassphraseChanged(String),
+    PassphraseConfirmed(String),
+    SubmitPassphrase,
+    VaultUnlocked(Result<(PathBuf, Arc<String>, vault::Key), Error>),
+    VaultKeyCreated(Result<vault::Key, Error>),
+    ConfirmSecrets,
+    SuppressSecrets,
+    CompletionTick,
+    RecomputeTick,
+    CompletionReceived(usize, Result<String, Error>),
+    ToggleAssistant,
+    AskAssistant(assistant::Prompt),
+    Assistant(assistant::Event),
+    ApplyAssistant,
+    AssistantKeyChanged(String),
+    SaveAssistantKey,
+    AssistantKeySaved(Result<(), Error>),
+    ReadAloud,
+    ToggleDictation,
+    Transcribed(Result<String, Error>),
+    SpeechPolled,
+    PauseSpeech,
+    ResumeSpeech,
+    StopSpeech,
+    SpeechRate(f32),
+    SpeechVoice(String),
+    WordGoalChanged(String),
+    SetWordGoal(Option<usize>),
+    GoalsLoaded(Result<goals::Goals, Error>),
+    OfflineQueueLoaded(Result<offline::Queue, Error>),
+    OfflineQueueSaved(Result<(), Error>),
+    StorageTick,
+    FlushOffline,
+    OfflineFlushed(Vec<offline::Entry>),
+    GoalsSaved(Result<(), Error>),
+    Exit,
+    ReplaceLinkToggled(bool),
+    EditAnyway,
+    ToggleProseMode,
+    TogglePreview,
+    ToggleOutline,
+    OutlineSelected(usize),
+    ToggleColors,
+    ColorSelected(usize, usize),
+    UpdateToc,
+    PreviewRendered(Result<preview::Preview, Error>),
+    SetBom(Option<bom::Bom>),
+    ToggleLineEnding,
+    OpenLossy,
+    LossyOpened(Result<(PathBuf, Arc<String>, lossy::Lossy), Error>),
+    ToggleFileLocking,
+    SaveElevated,
+    SaveCopy,
+    ShowProperties,
+    PropertiesLoaded(Result<properties::Properties, Error>),
+    CopyPath(String),
 }
 
 impl Application for Editor {
     type Message = Message;
     type Theme = Theme;
     type Executor = executor::Default;
-    type Flags = ();
+    type Flags = Flags;
+
+    fn new(flags: Self::Flags) -> (Self, Command<Message>) {
+        let mut link = None;
+        let tab = tabs::Tab::active();
+        let mut tasks = tasks::Tasks::default();
+
+        let command = match (flags.target, flags.session) {
+            (Some(url), _) if web::is_url(&url) => {
+                Command::perform(web::fetch(url), Message::UrlOpened)
+            }
+            (Some(path), _) => {
+                let (path, resolved) = resolve(PathBuf::from(path));
+                link = resolved;
+
+                open(&mut tasks, tab.id, path)
+            }
+            (None, Some(name)) => Command::perform(session::load(name), Message::SessionLoaded),
+            (None, None) => tasks.perform(
+                tasks::Kind::Open,
+                Some(tab.id),
+                load_file(default_file()),
+                Message::FileOpened,
+            ),
+        };
 
-    fn new(_flags: Self::Flags) -> (Self, Command<Message>) {
         (
             Self {
                 content: text_editor::Content::new(),
                 error: None,
                 path: None,
                 theme: highlighter::Theme::Base16Eighties,
+                custom_theme: None,
+                themes: Vec::new(),
                 is_dirty: true,
+                modifiers: keyboard::Modifiers::default(),
+                panel: None,
+                scan_folder: false,
+                pending_jump: None,
+                image: None,
+                table: None,
+                tail: None,
+                remote: None,
+                url: None,
+                config: config::Config::default(),
+                collab: None,
+                palette: None,
+                workspace: None,
+                tree: None,
+                show_tree: false,
+                tabs: vec![tab],
+                active: 0,
+                tab_menu: None,
+                dragging: None,
+                history: Vec::new(),
+                switcher: None,
+                expansions: Vec::new(),
+                auto_pair: None,
+                inspect: false,
+                focus_mode: false,
+                window_height: 768.0,
+                editor_menu: false,
+                primary: primary::Selection::new(),
+                tray: None,
+                quick_note: None,
+                session: None,
+                positions: session::Positions::default(),
+                goals: goals::Goals::default(),
+                words: 0,
+                speech: None,
+                dictation: None,
+                is_transcribing: false,
+                save_denied: false,
+                link,
+                replace_link: false,
+                locks: locks::Locks::default(),
+                is_read_only: false,
+                bom: None,
+                line_ending: lines::Ending::Lf,
+                lossy: None,
+                offer_lossy: None,
+                completion: completion::Completion::default(),
+                secrets_confirmed: false,
+                vault: None,
+                checksums: checksums::Checksums::default(),
+                changed_outside: false,
+                focus: focus::Region::Editor,
+                focus_visible: false,
+                layout: layout::Sizes::default(),
+                dividers: layout::Dividers::default(),
+                branch: None,
+                menu: None,
+                system_reduces_motion: false,
+                renderer: flags.renderer,
+                hud: None,
+                pending: debounce::Pending::default(),
+                indexer: indexer::Indexer::default(),
+                tasks,
+                modified: None,
+                merge: None,
+                blame: None,
+                toast: None,
+                last_filter: String::new(),
+                run: None,
+                runs: 0,
+                problems: Vec::new(),
+                debug: None,
+                breakpoints: debug::Breakpoints::new(),
+                database: None,
+                notebook: None,
+                repls: Vec::new(),
+                active_repl: 0,
+                repl_history: repl::History::new(),
+                offline: offline::Queue::default(),
             },
-            Command::perform(load_file(default_file()), Message::FileOpened),
+            Command::batch([
+                Command::perform(config::Config::load(), Message::ConfigLoaded),
+                Command::perform(themes::load(), Message::ThemesLoaded),
+                Command::perform(session::Positions::load(), Message::PositionsLoaded),
+                Command::perform(checksums::Checksums::load(), Message::ChecksumsLoaded),
+                Command::perform(goals::Goals::load(), Message::GoalsLoaded),
+                Command::perform(offline::Queue::load(), Message::OfflineQueueLoaded),
+                Command::perform(
+                    motion::system_prefers_reduced(),
+                    Message::MotionPreferenceLoaded,
+                ),
+                command,
+            ]),
         )
     }
 
     fn title(&self) -> String {
-        String::from("Bonkitor")
+        match &self.workspace {
+            Some(workspace) => format!("Bonkitor - {}", workspace.name()),
+            None => String::from("Bonkitor"),
+        }
     }
 
     fn update(&mut self, message: Self::Message) -> Command<Message> {
+        let _span = tracing::trace_span!("update").entered();
+        let _timer = self
+            .hud
+            .as_ref()
+            .filter(|_| !matches!(message, Message::FrameRendered(_)))
+            .map(perf::Hud::time_update);
+
+        // A suggestion is only kept while nothing else touches the buffer
+        if self.completion.ghost.is_some()
+            && !matches!(
+                message,
+                Message::ExpandAbbreviation
+                    | Message::CompletionTick
+                    | Message::CompletionReceived(..)
+                    | Message::ModifiersChanged(_)
+                    | Message::WindowResized(_)
+                    | Message::TrayPolled
+                    | Message::QuickNotePolled
+                    | Message::SpeechPolled
+                    | Message::FrameRendered(_)
+                    | Message::RecomputeTick
+                    | Message::Indexed(_)
+                    | Message::ModifiedRead(_)
+                    | Message::StorageTick
+                    | Message::ThemesChanged
+                    | Message::ThemesLoaded(_)
+                    | Message::ToastTick
+                    | Message::TaskOutput(_)
+                    | Message::Debug(_)
+                    | Message::Cells(_)
+                    | Message::ReplEvent(_)
+            )
+        {
+            self.dismiss_completion();
+        }
+
         match message {
+            // The editor keeps its own focus, so keys meant for another region arrive here
+            Message::Edit(action)
+                if self.focused() != focus::Region::Editor
+                    && !matches!(
+                        action,
+                        text_editor::Action::Click(_)
+                            | text_editor::Action::Drag(_)
+                            | text_editor::Action::Scroll { .. }
+                    ) =>
+            {
+                match action {
+                    text_editor::Action::Move(text_editor::Motion::Up) => {
+                        self.update(Message::PaletteMoved(-1))
+                    }
+                    text_editor::Action::Move(text_editor::Motion::Down) => {
+                        self.update(Message::PaletteMoved(1))
+                    }
+                    text_editor::Action::Edit(text_editor::Edit::Enter) => {
+                        self.update(Message::ActivateFocused)
+                    }
+                    _ => Command::none(),
+                }
+            }
+            Message::Edit(action) if self.is_read_only() && action.is_edit() => Command::none(),
             Message::Edit(action) => {
-                self.is_dirty = self.is_dirty || action.is_edit();
+                if self.modifiers.alt() && self.modifiers.shift() {
+                    match action {
+                        text_editor::Action::Select(
+                            text_editor::Motion::Right | text_editor::Motion::WordRight,
+                        ) => return self.update(Message::ExpandSelection),
+                        text_editor::Action::Select(
+                            text_editor::Motion::Left | text_editor::Motion::WordLeft,
+                        ) => return self.update(Message::ShrinkSelection),
+                        _ => {}
+                    }
+                }
+
+                if self.modifiers.alt()
+                    && matches!(
+                        action,
+                        text_editor::Action::Edit(text_editor::Edit::Insert('q' | 'Q'))
+                    )
+                {
+                    return self.update(Message::ReflowParagraph);
+                }
+
+                if matches!(
+                    action,
+                    text_editor::Action::Click(_) | text_editor::Action::Drag(_)
+                ) {
+                    self.focus = focus::Region::Editor;
+                    self.focus_visible = false;
+                }
+
+                self.expansions.clear();
+                self.editor_menu = false;
+
+                let action = match action {
+                    text_editor::Action::Edit(text_editor::Edit::Paste(text))
+                        if self.modifiers.command() && self.modifiers.shift() =>
+                    {
+                        text_editor::Action::Edit(text_editor::Edit::Paste(Arc::new(
+                            self.reindent(&text),
+                        )))
+                    }
+                    action => action,
+                };
+
+                let (action, renumber) = if self.config.prose_mode {
+                    self.prose(action)
+                } else {
+                    (action, None)
+                };
+
+                if self.config.subword_navigation && self.move_by_subword(&action) {
+                    return Command::none();
+                }
+
+                let (line, column) = self.content.cursor_position();
+                let current = self.content.line(line).map(|text| text.to_string());
+
+                let is_auto_pair = self.auto_pair.take()
+                    == Some((self.tabs[self.active].id, (line, column)))
+                    && current
+                        .as_deref()
+                        .is_some_and(|text| words::is_in_pair(text, column));
+
+                // Typing the closing character of a pair that was just added steps over it
+                let action = match action {
+                    text_editor::Action::Edit(text_editor::Edit::Insert(c))
+                        if is_auto_pair
+                            && current.as_deref().and_then(|text| text.chars().nth(column))
+                                == Some(c) =>
+                    {
+                        text_editor::Action::Move(text_editor::Motion::Right)
+                    }
+                    action => action,
+                };
+
+                let closing = match &action {
+                    text_editor::Action::Edit(text_editor::Edit::Insert(open))
+                        if self.config.auto_pairs && self.content.selection().is_none() =>
+                    {
+                        current
+                            .as_deref()
+                            .and_then(|text| words::closing(text, column, *open))
+                    }
+                    _ => None,
+                };
+
+                let follow_link =
+                    matches!(action, text_editor::Action::Click(_)) && self.modifiers.command();
+
+                let is_edit = action.is_edit();
+                let is_selection = matches!(
+                    action,
+                    text_editor::Action::Select(_)
+                        | text_editor::Action::SelectWord
+                        | text_editor::Action::SelectLine
+                        | text_editor::Action::Drag(_)
+                );
+                let recenter = self.config.typewriter_scrolling
+                    && !self.reduces_motion()
+                    && !matches!(
+                        action,
+                        text_editor::Action::Click(_)
+                            | text_editor::Action::Drag(_)
+                            | text_editor::Action::Scroll { .. }
+                    );
+
+                self.is_dirty = self.is_dirty || is_edit;
                 self.error = None;
+
+                match &action {
+                    text_editor::Action::Edit(text_editor::Edit::Backspace) => {
+                        self.extend_deletion(false, is_auto_pair);
+                    }
+                    text_editor::Action::Edit(text_editor::Edit::Delete) => {
+                        self.extend_deletion(true, false);
+                    }
+                    _ => {}
+                }
+
                 self.content.edit(action);
-                Command::none()
+
+                if let Some(close) = closing {
+                    self.content
+                        .edit(text_editor::Action::Edit(text_editor::Edit::Insert(close)));
+                    self.content
+                        .edit(text_editor::Action::Move(text_editor::Motion::Left));
+
+                    self.auto_pair =
+                        Some((self.tabs[self.active].id, self.content.cursor_position()));
+                }
+
+                if let Some(line) = renumber {
+                    self.renumber_list(line);
+                }
+
+                if recenter {
+                    self.center_cursor();
+                }
+
+                if self.config.completion_endpoint.is_some() {
+                    self.completion.typed_at = is_edit.then(Instant::now);
+                }
+
+                if is_edit {
+                    self.pending.schedule(debounce::Job::Words);
+
+                    match &self.panel {
+                        Some(Panel::Outline(_)) => self.pending.schedule(debounce::Job::Outline),
+                        Some(Panel::Colors(_)) => self.pending.schedule(debounce::Job::Colors),
+                        Some(Panel::Calculation(_)) => {
+                            self.pending.schedule(debounce::Job::Calculation)
+                        }
+                        Some(Panel::Preview(Some(_))) => {
+                            self.pending.schedule(debounce::Job::Preview)
+                        }
+                        Some(Panel::Statistics(Some(_))) => {
+                            self.pending.schedule(debounce::Job::Statistics)
+                        }
+                        Some(Panel::Changes(Some(_))) => {
+                            self.pending.schedule(debounce::Job::Changes)
+                        }
+                        _ => {}
+                    }
+                }
+
+                if is_selection {
+                    if let Some(selection) = self.content.selection() {
+                        self.primary.set(selection);
+                    }
+                }
+
+                if let Some(session) = &mut self.collab {
+                    if is_edit {
+                        session.local_edit(self.content.text());
+                    }
+
+                    session.send_cursor(caret(&self.content));
+                }
+
+                if follow_link {
+                    self.follow_link()
+                } else {
+                    Command::none()
+                }
             }
             Message::New => {
-                self.path = None;
-                self.content = text_editor::Content::new();
-                self.is_dirty = true;
+                self.new_tab();
                 Command::none()
             }
-            Message::FileOpened(Ok((path, content))) => {
+            Message::FileOpened(Ok((path, content, bom))) => {
+                if self.path.as_ref() != Some(&path) {
+                    if let Some(index) = self.tab_index(&path) {
+                        self.switch_tab(index);
+                    }
+                }
+
                 self.path = Some(path);
+                self.image = None;
+                self.table = None;
+                self.tail = None;
+                self.remote = None;
+                self.url = None;
                 self.content = text_editor::Content::with(&content);
                 self.is_dirty = false;
+                self.bom = bom;
+                self.line_ending = lines::Ending::detect(&content);
+                self.lossy = None;
+                self.offer_lossy = None;
+                self.vault = None;
+                self.changed_outside = false;
+                self.modified = None;
+                self.is_read_only = self.config.lock_files
+                    && self
+                        .path
+                        .as_deref()
+                        .is_some_and(|path| !self.locks.acquire(path));
+
+                self.count_words();
+
+                if let Some(path) = self.path.clone().filter(|path| goals::is_prose(path)) {
+                    self.goals.opened(path, self.words);
+                }
+
+                if let Some(line) = self.pending_jump.take() {
+                    jump_to(&mut self.content, line);
+                } else if let Some((line, column)) = self
+                    .path
+                    .as_deref()
+                    .and_then(|path| self.positions.get(path))
+                {
+                    move_to(&mut self.content, line, column);
+                }
+
+                Command::batch([
+                    self.save_workspace(),
+                    self.verify_checksum(),
+                    self.read_modified(),
+                    self.read_blame(),
+                ])
+            }
+            Message::FileOpened(Err(Error::Sealed(path))) => {
+                self.unbind_unread();
+                self.panel = Some(Panel::Passphrase(vault::Prompt::unlock(path)));
 
                 Command::none()
             }
             Message::FileOpened(Err(error)) => {
+                self.offer_lossy = self.unbind_unread().filter(|path| {
+                    matches!(error, Error::IOFailed(io::ErrorKind::InvalidData))
+                        && compression::Format::detect(path).is_none()
+                });
+                self.error = Some(error);
+                self.pending_jump = None;
+                Command::none()
+            }
+            Message::ImageOpened(Ok((path, image))) => {
+                self.path = Some(path);
+                self.image = Some(image);
+                self.table = None;
+                self.tail = None;
+                self.remote = None;
+                self.url = None;
+                self.content = text_editor::Content::new();
+                self.is_dirty = false;
+
+                Command::none()
+            }
+            Message::ImageOpened(Err(error)) => {
                 self.error = Some(error);
                 Command::none()
             }
             Message::FileSaved(Ok(path)) => {
+                if self.link.as_ref() == Some(&path) {
+                    self.link = None;
+                    self.replace_link = false;
+                }
+
+                if self.config.lock_files {
+                    self.locks.acquire(&path);
+                }
+
+                let checksum = if self.config.record_checksums {
+                    Command::perform(checksums::sha256(path.clone()), Message::ChecksumRecorded)
+                } else {
+                    Command::none()
+                };
+
+                // The goal is recorded against what was saved, not the last pause
+                if self.pending.take(debounce::Job::Words) {
+                    self.count_words();
+                }
+
+                self.pending.take(debounce::Job::Preview);
+
+                let goals = if goals::is_prose(&path) {
+                    self.goals.saved(path.clone(), self.words);
+
+                    Command::perform(self.goals.clone().save(), Message::GoalsSaved)
+                } else {
+                    Command::none()
+                };
+
                 self.path = Some(path);
+                self.url = None;
+                self.is_dirty = false;
+                self.save_denied = false;
+
+                let preview = match &self.panel {
+                    Some(Panel::Preview(Some(preview)))
+                        if self.path.as_ref() == Some(&preview.path) =>
+                    {
+                        self.render_preview()
+                    }
+                    _ => Command::none(),
+                };
+
+                let blame = self.read_blame();
+                let unqueued = self
+                    .path
+                    .clone()
+                    .map_or_else(Command::none, |path| self.unqueue_save(&path));
+
+                Command::batch([
+                    goals,
+                    preview,
+                    checksum,
+                    self.read_modified(),
+                    blame,
+                    unqueued,
+                ])
+            }
+            Message::FileSaved(Err(Error::Queued(entry))) => {
+                if self.config.lock_files {
+                    self.locks.acquire(&entry.path);
+                }
+
+                let name = entry.path.file_name().map_or_else(
+                    || entry.path.display().to_string(),
+                    |name| name.to_string_lossy().into_owned(),
+                );
+
+                self.toast = Some(toast::Toast::new(format!(
+                    "{name} is not answering, so it was saved locally and will be written once it is back"
+                )));
+
+                self.path = Some(entry.path.clone());
+                self.url = None;
                 self.is_dirty = false;
+                self.save_denied = false;
+
+                self.queue_save(entry)
+            }
+            Message::FileSaved(Err(Error::Conflict(path))) => {
+                if self.config.lock_files {
+                    self.locks.acquire(&path);
+                }
+
+                self.panel = Some(Panel::Conflict(path));
+
                 Command::none()
             }
             Message::FileSaved(Err(error)) => {
+                if let Some(path) = self.path.as_deref().filter(|_| self.config.lock_files) {
+                    self.locks.acquire(path);
+                }
+
+                self.save_denied =
+                    matches!(error, Error::IOFailed(io::ErrorKind::PermissionDenied))
+                        && self.path.is_some()
+                        && self.remote.is_none();
                 self.error = Some(error);
                 Command::none()
             }
-            Message::Open => Command::perform(pick_file(), Message::FileOpened),
-            Message::Save => {
-                let text = self.content.text();
-                Command::perform(save_file(self.path.clone(), text), Message::FileSaved)
+            // An image tab has no text of its own, so saving would empty the file
+            Message::Save | Message::SaveCopy | Message::SaveElevated if self.image.is_some() => {
+                Command::none()
             }
-            Message::ThemeSelected(theme) => {
-                self.theme = theme;
+            Message::SaveElevated => {
+                self.save_denied = false;
+
+                let text = self.saved_text();
+
+                match self.path.clone() {
+                    Some(path) => self.perform_for_tab(
+                        tasks::Kind::Save,
+                        save_elevated(path, text, self.bom, self.vault.clone()),
+                        Message::FileSaved,
+                    ),
+                    None => Command::none(),
+                }
+            }
+            Message::SaveCopy => {
+                self.save_denied = false;
+
+                let text = self.saved_text();
 
+                match self.vault.clone() {
+                    Some(key) => self.perform_for_tab(
+                        tasks::Kind::Save,
+                        save_sealed_copy(key, text, self.bom),
+                        Message::FileSaved,
+                    ),
+                    None => self.perform_for_tab(
+                        tasks::Kind::Save,
+                        save_file(None, text, self.bom),
+                        Message::FileSaved,
+                    ),
+                }
+            }
+            Message::Open => Command::perform(pick_file(), Message::FilePicked),
+            Message::FilePicked(Ok(path)) => self.open_in_tab(path),
+            Message::FilePicked(Err(error)) => {
+                self.error = Some(error);
                 Command::none()
             }
-        }
-    }
+            Message::Save if self.path.as_deref().is_some_and(scratch::is_scratch) => {
+                self.update(Message::AutosaveScratch)
+            }
+            Message::Save => {
+                let is_encrypted = self
+                    .path
+                    .as_deref()
+                    .and_then(encryption::Format::detect)
+                    .is_some()
+                    || self.vault.is_some();
 
-    fn subscription(&self) -> Subscription<Message> {
-        keyboard::on_key_press(|key_code, modifiers| match key_code {
-            keyboard::KeyCode::S if modifiers.command() => Some(Message::Save),
-            _ => None,
-        })
-    }
+                // Secrets are what encrypted files are for
+                if !is_encrypted
+                    && self.warn_of_secrets(&self.content.text(), Message::Save, "Save")
+                {
+                    return Command::none();
+                }
 
-    fn view(&self) -> Element<'_, Self::Message> {
-        let controls = row![
-            action(new_icon(), Some(Message::New), "New File"),
-            action(open_icon(), Some(Message::Open), "Open File"),
-            action(save_icon(), self.is_dirty.then_some(Message::Save), "Save"),
-            horizontal_space(Length::Fill),
-            pick_list(
-                highlighter::Theme::ALL,
-                Some(self.theme),
-                Message::ThemeSelected
-            )
-        ]
-        .spacing(15);
-        let input = text_editor(&self.content)
-            .on_edit(Message::Edit)
-            .highlight::<Highlighter>(
-                highlighter::Settings {
-                    theme: self.theme,
-                    extension: self
-                        .path
-                        .as_ref()
-                        .and_then(|path| path.extension()?.to_str())
-                        .unwrap_or("rs")
-                        .to_string(),
-                },
-                |highlight, _theme| highlight.to_format(),
-            );
-        let status_bar = {
-            let status = if let Some(Error::IOFailed(error)) = self.error.as_ref() {
-                text(error.to_string())
-            } else {
-                match self.path.as_deref().and_then(Path::to_str) {
-                    Some(path) => text(path).size(14),
-                    None => text("New file"),
+                let text = self.saved_text();
+
+                if self.url.is_some() {
+                    return self.perform_for_tab(
+                        tasks::Kind::Save,
+                        save_file(None, text, self.bom),
+                        Message::FileSaved,
+                    );
                 }
-            };
-            let position = {
-                let (line, column) = self.content.cursor_position();
-                text(format!("{}:{}", line + 1, column + 1))
-            };
 
-            row![status, horizontal_space(Length::Fill), position]
-        };
-        container(column![controls, input, status_bar].spacing(10))
-            .padding(10)
-            .into()
-    }
+                if let Some(session) = &self.remote {
+                    return Command::perform(
+                        remote::upload(session.clone(), text),
+                        Message::RemoteSaved,
+                    );
+                }
+                // Windows locks are mandatory, so let go of ours while writing
+                if let Some(path) = &self.path {
+                    self.locks.release(path);
+                }
 
-    fn theme(&self) -> Theme {
-        if self.theme.is_dark() {
-            Theme::Dark
-        } else {
-            Theme::Light
-        }
-    }
-}
+                if let (Some(path), Some(key)) = (&self.path, &self.vault) {
+                    let bytes = vault::seal(key, &bom::encode(text, self.bom));
+                    let path = path.clone();
 
-fn icon<'a>(codepoint: char) -> Element<'a, Message> {
-    const ICON_FONT: Font = Font::with_name("editor");
+                    return self.perform_for_tab(
+                        tasks::Kind::Save,
+                        async move {
+                            tokio::fs::write(&path, bytes)
+                                .await
+                                .map(|()| path)
+                                .map_err(|error| Error::IOFailed(error.kind()))
+                        },
+                        Message::FileSaved,
+                    );
+                }
 
-    text(codepoint).font(ICON_FONT).into()
-}
+                if let (Some(path), Some(lossy)) = (&self.path, &self.lossy) {
+                    let bytes = lossy.encode(&text);
+                    let path = path.clone();
 
-fn action<'a>(
-    content: Element<'a, Message>,
-    on_press: Option<Message>,
-    label: &str,
-) -> Element<'a, Message> {
-    let is_disabled = on_press.is_none();
-    tooltip(
-        button(container(content).width(30).center_x())
-            .on_press_maybe(on_press)
-            .padding([5, 10])
-            .style(if is_disabled {
-                theme::Button::Secondary
-            } else {
-                theme::Button::Primary
-            }),
-        label,
-        tooltip::Position::FollowCursor,
-    )
-    .style(theme::Container::Box)
-    .into()
-}
+                    self.lossy = Some(lossy::decode(&bytes).1);
 
-fn new_icon<'a>() -> Element<'a, Message> {
-    icon('\u{E801}')
-}
+                    return self.perform_for_tab(
+                        tasks::Kind::Save,
+                        async move {
+                            tokio::fs::write(&path, bytes)
+                                .await
+                                .map(|()| path)
+                                .map_err(|error| Error::IOFailed(error.kind()))
+                        },
+                        Message::FileSaved,
+                    );
+                }
 
-fn save_icon<'a>() -> Element<'a, Message> {
-    icon('\u{E800}')
-}
+                if let Some(link) = self.link.clone().filter(|_| self.replace_link) {
+                    return self.perform_for_tab(
+                        tasks::Kind::Save,
+                        replace_link(link, text, self.bom),
+                        Message::FileSaved,
+                    );
+                }
 
-fn open_icon<'a>() -> Element<'a, Message> {
-    icon('\u{E802}')
-}
+                let (path, modified, bom) = (self.path.clone(), self.modified, self.bom);
 
-fn default_file() -> PathBuf {
-    PathBuf::from(format!("{}/src/main.rs", env!("CARGO_MANIFEST_DIR")))
-}
+                self.perform_for_tab(
+                    tasks::Kind::Save,
+                    async move {
+                        if let Some(path) = &path {
+                            conflict::check(path, modified).await?;
+                        }
 
-async fn pick_file() -> Result<(PathBuf, Arc<String>), Error> {
-    let handle = rfd::AsyncFileDialog::new()
-        .set_title("Choose a file to open...")
-        .pick_file()
-        .await
-        .ok_or(Error::DialogClosed)?;
-    load_file(handle.path().to_owned()).await
-}
+                        save_file(path, text, bom).await
+                    },
+                    Message::FileSaved,
+                )
+            }
+            Message::OpenLossy => match self.offer_lossy.take() {
+                Some(path) => {
+                    if !self.is_blank() {
+                        self.new_tab();
+                    }
 
-async fn save_file(path: Option<PathBuf>, text: String) -> Result<PathBuf, Error> {
-    let path = if let Some(path) = path {
-        path
-    } else {
-        rfd::AsyncFileDialog::new()
-            .set_title("Choose a file name...")
-            .save_file()
-            .await
-            .ok_or(Error::DialogClosed)
-            .map(|handle| handle.path().to_owned())?
-    };
-    tokio::fs::write(&path, text)
-        .await
-        .map_err(|error| Error::IOFailed(error.kind()))?;
-    Ok(path)
-}
+                    self.perform_for_tab(tasks::Kind::Open, load_lossy(path), Message::LossyOpened)
+                }
+                None => Command::none(),
+            },
+            Message::LossyOpened(Ok((path, text, lossy))) => {
+                let command = self.update(Message::FileOpened(Ok((path, text, None))));
+                self.lossy = Some(lossy);
 
-async fn load_file(path: PathBuf) -> Result<(PathBuf, Arc<String>), Error> {
-    let contents = tokio::fs::read_to_string(&path)
-        .await
-        .map(Arc::new)
-        .map_err(|error| error.kind())
-        .map_err(Error::IOFailed)?;
-    Ok((path, contents))
-}
-#[derive(Debug, Clone)]
-enum Error {
-    DialogClosed,
-    IOFailed(io::ErrorKind),
+                command
+            }
+            Message::LossyOpened(Err(error)) => {
+                self.error = Some(error);
+                Command::none()
+            }
+            Message::SetBom(bom) => {
+                self.bom = bom;
+                self.is_dirty = true;
+
+                Command::none()
+            }
+            Message::ToggleLineEnding => {
+                self.line_ending = self.line_ending.toggled();
+                self.is_dirty = true;
+
+                Command::none()
+            }
+            Message::ToggleDictation => {
+                if let Some(recording) = self.dictation.take() {
+                    let Some(command) = self.config.dictation_command.clone() else {
+                        return Command::none();
+                    };
+
+                    self.is_transcribing = true;
+
+                    return Command::perform(
+                        dictation::transcribe(command, recording.finish()),
+                        Message::Transcribed,
+                    );
+                }
+
+                match dictation::Recording::start() {
+                    Ok(recording) => self.dictation = Some(recording),
+                    Err(error) => self.error = Some(Error::RemoteFailed(error)),
+                }
+
+                Command::none()
+            }
+            Message::Transcribed(Ok(text)) => {
+                self.is_transcribing = false;
+
+                if text.is_empty() {
+                    return Command::none();
+                }
+
+                self.update(Message::Edit(text_editor::Action::Edit(
+                    text_editor::Edit::Paste(Arc::new(text)),
+                )))
+            }
+            Message::Transcribed(Err(error)) => {
+                self.is_transcribing = false;
+                self.error = Some(error);
+                Command::none()
+            }
+            Message::EncryptFile => {
+                self.panel = Some(Panel::Passphrase(vault::Prompt::encrypt()));
+
+                Command::none()
+            }
+            Message::DecryptFile => {
+                self.vault = None;
+                self.is_dirty = true;
+
+                Command::none()
+            }
+            Message::PassphraseChanged(passphrase) => {
+                if let Some(Panel::Passphrase(prompt)) = &mut self.panel {
+                    prompt.passphrase = passphrase;
+                }
+
+                Command::none()
+            }
+            Message::PassphraseConfirmed(confirmation) => {
+                if let Some(Panel::Passphrase(prompt)) = &mut self.panel {
+                    prompt.confirmation = confirmation;
+                }
+
+                Command::none()
+            }
+            Message::SubmitPassphrase => {
+                let Some(Panel::Passphrase(prompt)) = &mut self.panel else {
+                    return Command::none();
+                };
+
+                let is_confirmed =
+                    prompt.path.is_some() || prompt.passphrase == prompt.confirmation;
+
+                if prompt.passphrase.is_empty() || !is_confirmed || prompt.is_working {
+                    return Command::none();
+                }
+
+                prompt.is_working = true;
+                prompt.confirmation.clear();
+
+                let passphrase = std::mem::take(&mut prompt.passphrase);
+
+                match prompt.path.clone() {
+                    Some(path) => {
+                        Command::perform(vault::unlock(path, passphrase), Message::VaultUnlocked)
+                    }
+                    None => Command::perform(vault::create(passphrase), Message::VaultKeyCreated),
+                }
+            }
+            Message::VaultUnlocked(Ok((path, text, key))) => {
+                self.panel = None;
+
+                let command = self.update(Message::FileOpened(Ok((path, text, None))));
+                self.vault = Some(key);
+
+                command
+            }
+            Message::VaultKeyCreated(Ok(key)) => {
+                self.panel = None;
+                self.vault = Some(key);
+
+                self.update(Message::Save)
+            }
+            Message::VaultUnlocked(Err(error)) | Message::VaultKeyCreated(Err(error)) => {
+                if let Some(Panel::Passphrase(prompt)) = &mut self.panel {
+                    prompt.is_working = false;
+                }
+
+                self.error = Some(error);
+                Command::none()
+            }
+            Message::ConfirmSecrets => {
+                let Some(Panel::Secrets(warning, previous)) = self.panel.take() else {
+                    return Command::none();
+                };
+
+                self.panel = previous.map(|panel| *panel);
+                self.secrets_confirmed = true;
+
+                let command = self.update(warning.then);
+                self.secrets_confirmed = false;
+
+                command
+            }
+            Message::SuppressSecrets => {
+                let Some(path) = self.path.clone() else {
+                    return Command::none();
+                };
+
+                if !self.config.secrets_ignored.contains(&path) {
+                    self.config.secrets_ignored.push(path);
+                }
+
+                Command::batch([
+                    Command::perform(self.config.clone().save(), Message::ConfigSaved),
+                    self.update(Message::ConfirmSecrets),
+                ])
+            }
+            Message::RecomputeTick => {
+                let jobs = self.pending.take_due();
+
+                Command::batch(jobs.into_iter().map(|job| self.recompute(job)))
+            }
+            Message::CompletionTick => {
+                if !self.completion.is_due() {
+                    return Command::none();
+                }
+
+                self.completion.typed_at = None;
+
+                let Some(endpoint) = self.config.completion_endpoint.clone() else {
+                    return Command::none();
+                };
+
+                if self.is_read_only()
+                    || self.content.selection().is_some()
+                    || self.palette.is_some()
+                    || self.table.is_some()
+                    || self.tail.is_some()
+                {
+                    return Command::none();
+                }
+
+                let text = self.content.text();
+
+                // Completions are never worth asking about, so they just stop
+                if !self.ignores_secrets() && !secrets::scan(&text).is_empty() {
+                    return Command::none();
+                }
+
+                let prompt = completion::prompt(&text, cursor_offset(&self.content, &text));
+
+                self.completion.request += 1;
+                let request = self.completion.request;
+
+                Command::perform(
+                    completion::complete(endpoint, self.config.completion_model.clone(), prompt),
+                    move |result| Message::CompletionReceived(request, result),
+                )
+            }
+            Message::CompletionReceived(request, Ok(suggestion)) => {
+                // Anything typed since the request makes the suggestion stale
+                if request != self.completion.request
+                    || self.completion.typed_at.is_some()
+                    || suggestion.is_empty()
+                    || self.content.selection().is_some()
+                {
+                    return Command::none();
+                }
+
+                let (line, column) = self.content.cursor_position();
+
+                self.completion.ghost = Some(completion::Ghost {
+                    line,
+                    column,
+                    text: suggestion,
+                });
+
+                Command::none()
+            }
+            Message::CompletionReceived(_, Err(error)) => {
+                self.error = Some(error);
+                Command::none()
+            }
+            Message::ToggleAssistant => {
+                if let Some(Panel::Assistant(_)) = self.panel {
+                    self.panel = None;
+                } else {
+                    self.panel = Some(Panel::Assistant(assistant::Assistant::default()));
+                }
+
+                Command::none()
+            }
+            Message::AskAssistant(prompt) => {
+                let Some(endpoint) = self.config.assistant_endpoint.clone() else {
+                    return Command::none();
+                };
+
+                let selection = self.content.selection().filter(|text| !text.is_empty());
+
+                let request = assistant::Request {
+                    id: 0,
+                    prompt,
+                    from_selection: selection.is_some(),
+                    input: selection.unwrap_or_else(|| self.content.text()),
+                    tab: self.tabs[self.active].id,
+                    endpoint,
+                    model: self.config.assistant_model().to_string(),
+                };
+
+                if self.warn_of_secrets(&request.input, Message::AskAssistant(prompt), "Send") {
+                    return Command::none();
+                }
+
+                if !matches!(self.panel, Some(Panel::Assistant(_))) {
+                    self.panel = Some(Panel::Assistant(assistant::Assistant::default()));
+                }
+
+                if let Some(Panel::Assistant(assistant)) = &mut self.panel {
+                    assistant.ask(request);
+                }
+
+                Command::none()
+            }
+            Message::Assistant(event) => {
+                let Some(Panel::Assistant(assistant)) = &mut self.panel else {
+                    return Command::none();
+                };
+
+                match event {
+                    assistant::Event::Delta(delta) => assistant.response.push_str(&delta),
+                    assistant::Event::Finished => assistant.is_streaming = false,
+                    assistant::Event::Failed(error) => {
+                        assistant.is_streaming = false;
+                        self.error = Some(Error::RemoteFailed(error));
+                    }
+                }
+
+                Command::none()
+            }
+            Message::ApplyAssistant => {
+                let Some(Panel::Assistant(assistant)) = &self.panel else {
+                    return Command::none();
+                };
+
+                let (Some(request), Some(edit)) = (assistant.request.clone(), assistant.edit())
+                else {
+                    return Command::none();
+                };
+
+                if self.is_read_only() {
+                    return Command::none();
+                }
+
+                if request.tab != self.tabs[self.active].id {
+                    self.toast = Some(toast::Toast::new(
+                        "The rewrite belongs to another tab; switch back to apply it",
+                    ));
+
+                    return Command::none();
+                }
+
+                // Either way the rewrite only goes over the very text that was sent
+                if request.from_selection {
+                    if self.content.selection().as_deref() != Some(request.input.as_str()) {
+                        self.toast = Some(toast::Toast::new(
+                            "The selection changed since it was sent; select it again to apply",
+                        ));
+
+                        return Command::none();
+                    }
+
+                    return self.update(Message::Edit(text_editor::Action::Edit(
+                        text_editor::Edit::Paste(Arc::new(edit)),
+                    )));
+                }
+
+                if self.content.text() != request.input {
+                    self.toast = Some(toast::Toast::new(
+                        "The buffer changed since it was sent; ask again to apply",
+                    ));
+
+                    return Command::none();
+                }
+
+                let (line, column) = self.content.cursor_position();
+
+                self.content = text_editor::Content::with(&edit);
+                self.is_dirty = true;
+                move_to(&mut self.content, line, column);
+
+                Command::none()
+            }
+            Message::AssistantKeyChanged(key) => {
+                if let Some(Panel::Assistant(assistant)) = &mut self.panel {
+                    assistant.key = key;
+                }
+
+                Command::none()
+            }
+            Message::SaveAssistantKey => {
+                let Some(Panel::Assistant(assistant)) = &mut self.panel else {
+                    return Command::none();
+                };
+
+                let Some(endpoint) = self.config.assistant_endpoint.clone() else {
+                    return Command::none();
+                };
+
+                let key = std::mem::take(&mut assistant.key);
+
+                Command::perform(
+                    assistant::save_key(endpoint, key),
+                    Message::AssistantKeySaved,
+                )
+            }
+            Message::AssistantKeySaved(Ok(())) => Command::none(),
+            Message::AssistantKeySaved(Err(error)) => {
+                self.error = Some(error);
+                Command::none()
+            }
+            Message::ReadAloud => {
+                if self.speech.is_none() {
+                    match speech::Reader::new(
+                        self.config.speech_rate(),
+                        self.config.speech_voice.as_deref(),
+                    ) {
+                        Ok(reader) => self.speech = Some(reader),
+                        Err(error) => {
+                            self.error = Some(Error::RemoteFailed(error));
+
+                            return Command::none();
+                        }
+                    }
+                }
+
+                let text = self
+                    .content
+                    .selection()
+                    .unwrap_or_else(|| self.content.text());
+
+                if let Some(reader) = &mut self.speech {
+                    reader.read(&text);
+                    reader.poll();
+                }
+
+                Command::none()
+            }
+            Message::SpeechPolled => {
+                if let Some(reader) = &mut self.speech {
+                    reader.poll();
+                }
+
+                Command::none()
+            }
+            Message::PauseSpeech => {
+                if let Some(reader) = &mut self.speech {
+                    reader.pause();
+                }
+
+                Command::none()
+            }
+            Message::ResumeSpeech => {
+                if let Some(reader) = &mut self.speech {
+                    reader.resume();
+                    reader.poll();
+                }
+
+                Command::none()
+            }
+            Message::StopSpeech => {
+                if let Some(reader) = &mut self.speech {
+                    reader.stop();
+                }
+
+                Command::none()
+            }
+            Message::SpeechRate(rate) => {
+                self.config.speech_rate = Some(rate);
+
+                if let Some(reader) = &mut self.speech {
+                    reader.set_rate(rate);
+                }
+
+                Command::perform(self.config.clone().save(), Message::ConfigSaved)
+            }
+            Message::SpeechVoice(voice) => {
+                if let Some(reader) = &mut self.speech {
+                    reader.set_voice(&voice);
+                }
+
+                self.config.speech_voice = Some(voice);
+
+                Command::perform(self.config.clone().save(), Message::ConfigSaved)
+            }
+            Message::ToggleWritingGoals => {
+                if let Some(Panel::Goals(_)) = self.panel {
+                    self.panel = None;
+                } else {
+                    let input = self
+                        .path
+                        .as_deref()
+                        .and_then(|path| self.goals.goal(path))
+                        .map(|words| words.to_string())
+                        .unwrap_or_default();
+
+                    self.panel = Some(Panel::Goals(goals::Dialog { input }));
+                }
+
+                Command::none()
+            }
+            Message::WordGoalChanged(input) => {
+                if let Some(Panel::Goals(dialog)) = &mut self.panel {
+                    dialog.input = input;
+                }
+
+                Command::none()
+            }
+            Message::SetWordGoal(words) => {
+                let Some(path) = self.path.clone() else {
+                    return Command::none();
+                };
+
+                self.goals.set_goal(path, words);
+
+                if words.is_none() {
+                    if let Some(Panel::Goals(dialog)) = &mut self.panel {
+                        dialog.input.clear();
+                    }
+                }
+
+                Command::perform(self.goals.clone().save(), Message::GoalsSaved)
+            }
+            Message::GoalsLoaded(Ok(goals)) => {
+                self.goals = goals;
+
+                if let Some(path) = self.path.clone().filter(|path| goals::is_prose(path)) {
+                    self.goals.opened(path, self.words);
+                }
+
+                Command::none()
+            }
+            Message::GoalsLoaded(Err(error)) => {
+                self.error = Some(error);
+                Command::none()
+            }
+            Message::ToggleOutline => {
+                if let Some(Panel::Outline(_)) = self.panel {
+                    self.panel = None;
+                } else {
+                    self.panel = Some(Panel::Outline(toc::headings(&self.content.text())));
+                }
+
+                Command::none()
+            }
+            Message::OutlineSelected(line) => {
+                jump_to(&mut self.content, line);
+
+                Command::none()
+            }
+            Message::ToggleColors => {
+                if let Some(Panel::Colors(_)) = self.panel {
+                    self.panel = None;
+                } else {
+                    self.panel = Some(Panel::Colors(colors::list(&self.content.text())));
+                }
+
+                Command::none()
+            }
+            Message::ColorSelected(line, column) => {
+                move_to_byte(&mut self.content, line, column);
+
+                Command::none()
+            }
+            Message::UpdateToc => {
+                if self.is_read_only() {
+                    return Command::none();
+                }
+
+                let (line, column) = self.content.cursor_position();
+                let text = toc::update(&self.content.text(), line);
+
+                self.content = text_editor::Content::with(&text);
+                self.is_dirty = true;
+
+                if let Some(Panel::Outline(headings)) = &mut self.panel {
+                    *headings = toc::headings(&text);
+                }
+
+                move_to(&mut self.content, line, column);
+
+                Command::none()
+            }
+            Message::TogglePreview => {
+                if let Some(Panel::Preview(_)) = self.panel {
+                    self.panel = None;
+
+                    return Command::none();
+                }
+
+                self.panel = Some(Panel::Preview(None));
+                self.render_preview()
+            }
+            Message::PreviewRendered(Ok(rendered)) => {
+                if let Some(Panel::Preview(current)) = &mut self.panel {
+                    *current = Some(rendered);
+                }
+
+                Command::none()
+            }
+            Message::PreviewRendered(Err(error)) => {
+                if let Some(Panel::Preview(None)) = self.panel {
+                    self.panel = None;
+                }
+
+                self.error = Some(error);
+                Command::none()
+            }
+            Message::ToggleProseMode => {
+                self.config.prose_mode = !self.config.prose_mode;
+
+                Command::perform(self.config.clone().save(), Message::ConfigSaved)
+            }
+            Message::EditAnyway => {
+                self.is_read_only = false;
+
+                Command::none()
+            }
+            Message::ToggleFileLocking => {
+                self.config.lock_files = !self.config.lock_files;
+
+                if !self.config.lock_files {
+                    self.locks.retain(&[]);
+                } else if let Some(path) = &self.path {
+                    self.is_read_only = !self.locks.acquire(path);
+                }
+
+                Command::perform(self.config.clone().save(), Message::ConfigSaved)
+            }
+            Message::ReplaceLinkToggled(replace_link) => {
+                self.replace_link = replace_link;
+
+                Command::none()
+            }
+            Message::ThemeSelected(theme) => {
+                self.theme = theme;
+                self.custom_theme = None;
+
+                Command::none()
+            }
+            Message::ThemesLoaded(Ok(themes)) => {
+                // Edits to the theme in use show at once
+                if let Some(current) = &mut self.custom_theme {
+                    if let Some(theme) = themes.iter().find(|theme| theme.name == current.name) {
+                        *current = theme.clone();
+                    }
+                }
+
+                self.themes = themes;
+
+                Command::none()
+            }
+            Message::ThemesChanged => Command::perform(themes::load(), Message::ThemesLoaded),
+            Message::CustomThemeSelected(name) => {
+                self.custom_theme = self.themes.iter().find(|theme| theme.name == name).cloned();
+
+                Command::none()
+            }
+            Message::ToggleThemeEditor => {
+                if let Some(Panel::ThemeEditor(_)) = self.panel {
+                    self.panel = None;
+                } else {
+                    let theme = self.custom_theme.clone().unwrap_or_else(|| {
+                        themes::Theme::from_palette(
+                            format!("{} custom", self.theme),
+                            Application::theme(self).palette(),
+                        )
+                    });
+
+                    self.panel = Some(Panel::ThemeEditor(themes::Draft::new(theme)));
+                }
+
+                Command::none()
+            }
+            Message::ThemeNameChanged(name) => {
+                if let Some(Panel::ThemeEditor(draft)) = &mut self.panel {
+                    draft.theme.name = name;
+                }
+
+                Command::none()
+            }
+            Message::ThemeTargetSelected(target) => {
+                if let Some(Panel::ThemeEditor(draft)) = &mut self.panel {
+                    draft.select(target);
+                }
+
+                Command::none()
+            }
+            Message::ThemeColorChanged(color) => {
+                if let Some(Panel::ThemeEditor(draft)) = &mut self.panel {
+                    draft.set(color);
+                }
+
+                Command::none()
+            }
+            Message::ThemeHexChanged(hex) => {
+                if let Some(Panel::ThemeEditor(draft)) = &mut self.panel {
+                    draft.set_hex(hex);
+                }
+
+                Command::none()
+            }
+            Message::ExportTheme => match &self.panel {
+                Some(Panel::ThemeEditor(draft)) => {
+                    let theme = themes::Theme {
+                        name: draft.theme.name.trim().to_string(),
+                        ..draft.theme.clone()
+                    };
+
+                    self.custom_theme = Some(theme.clone());
+
+                    Command::perform(themes::save(theme), Message::ThemeExported)
+                }
+                _ => Command::none(),
+            },
+            Message::ImportTheme => Command::perform(themes::import(), Message::ThemeImported),
+            Message::ThemeImported(Ok(theme)) => {
+                self.toast = Some(toast::Toast::new(format!("Imported theme {}", theme.name)));
+                self.custom_theme = Some(theme);
+
+                Command::none()
+            }
+            Message::ThemeExported(Ok(path)) => {
+                self.toast = Some(toast::Toast::new(format!(
+                    "Theme saved to {}",
+                    path.display()
+                )));
+
+                Command::none()
+            }
+            Message::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers;
+
+                if !modifiers.control() {
+                    if let Some(position) = self.switcher.take() {
+                        if let Some(index) = self.recent_tabs().get(position) {
+                            return self.select_tab(*index);
+                        }
+                    }
+                }
+
+                Command::none()
+            }
+            Message::ToggleTodos => {
+                if let Some(Panel::Todos(_)) = self.panel {
+                    self.panel = None;
+
+                    Command::none()
+                } else {
+                    self.panel = Some(Panel::Todos(todos::List::default()));
+
+                    self.scan_todos()
+                }
+            }
+            Message::ScanFolderToggled(scan_folder) => {
+                self.scan_folder = scan_folder;
+
+                self.scan_todos()
+            }
+            Message::TodosScanned(groups) => {
+                if let Some(Panel::Todos(list)) = &mut self.panel {
+                    list.groups = groups;
+                }
+
+                Command::none()
+            }
+            Message::TodoSelected(path, line) => {
+                if path == self.path {
+                    jump_to(&mut self.content, line);
+
+                    Command::none()
+                } else if let Some(path) = path {
+                    self.pending_jump = Some(line);
+
+                    self.open_in_tab(path)
+                } else {
+                    Command::none()
+                }
+            }
+            Message::PickColor => {
+                let (line, column) = self.content.cursor_position();
+
+                if let Some(swatch) = self
+                    .content
+                    .line(line)
+                    .and_then(|text| colors::at(&text, column))
+                {
+                    self.panel = Some(Panel::Swatch(colors::Picker {
+                        line,
+                        color: swatch.color,
+                        original: swatch,
+                    }));
+                }
+
+                Command::none()
+            }
+            Message::SwatchChanged(color) => {
+                if let Some(Panel::Swatch(picker)) = &mut self.panel {
+                    picker.color = color;
+                }
+
+                Command::none()
+            }
+            Message::ApplySwatch => {
+                if let Some(Panel::Swatch(picker)) = self.panel.take() {
+                    select_range(
+                        &mut self.content,
+                        picker.line,
+                        picker.original.range.clone(),
+                    );
+
+                    self.content
+                        .edit(text_editor::Action::Edit(text_editor::Edit::Paste(
+                            Arc::new(picker.value()),
+                        )));
+                    self.is_dirty = true;
+                }
+
+                Command::none()
+            }
+            Message::ClosePanel => {
+                self.panel = None;
+
+                Command::none()
+            }
+            Message::ZoomImage(zoom) => {
+                if let Some(image) = &mut self.image {
+                    image.zoom(zoom);
+                }
+
+                Command::none()
+            }
+            Message::ToggleTable => {
+                if let Some(table) = self.table.take() {
+                    if table.is_modified {
+                        self.content = text_editor::Content::with(&table.to_text());
+                    }
+                } else if let Some(delimiter) = self.path.as_deref().and_then(table::delimiter) {
+                    self.table = table::Table::parse(&self.content.text(), delimiter).ok();
+                }
+
+                Command::none()
+            }
+            Message::TableChanged(action) => {
+                if let Some(table) = &mut self.table {
+                    self.is_dirty = self.is_dirty || matches!(action, table::Action::Edit { .. });
+
+                    table.perform(action);
+                }
+
+                Command::none()
+            }
+            Message::ToggleTail => {
+                if self.tail.take().is_some() {
+                    Command::none()
+                } else if let Some(path) = self.path.clone() {
+                    Command::perform(tail::start(path), Message::TailStarted)
+                } else {
+                    Command::none()
+                }
+            }
+            Message::TailStarted(Ok(offset)) => {
+                self.tail = Some(tail::Tail::new(offset));
+
+                Command::none()
+            }
+            Message::TailStarted(Err(error)) => {
+                self.error = Some(error);
+                Command::none()
+            }
+            Message::TailTick => match (&mut self.tail, &self.path) {
+                (Some(tail), Some(path)) if !tail.is_reading => {
+                    tail.is_reading = true;
+
+                    Command::perform(tail::read(path.clone(), tail.offset), Message::TailRead)
+                }
+                _ => Command::none(),
+            },
+            Message::TailRead(Ok(chunk)) => {
+                if let Some(tail) = &mut self.tail {
+                    if chunk.is_truncated {
+                        self.content = text_editor::Content::with(&chunk.text);
+                        self.content
+                            .edit(text_editor::Action::Move(text_editor::Motion::DocumentEnd));
+                    } else if !chunk.text.is_empty() {
+                        append(&mut self.content, chunk.text);
+                    }
+
+                    tail.offset = chunk.offset;
+                    tail.is_reading = false;
+                    tail.refilter(&self.content);
+                }
+
+                Command::none()
+            }
+            Message::TailRead(Err(error)) => {
+                self.error = Some(error);
+                self.tail = None;
+                self.remote = None;
+                self.url = None;
+                Command::none()
+            }
+            Message::TailFilterChanged(filter) => {
+                if let Some(tail) = &mut self.tail {
+                    tail.filter = filter;
+                    tail.refilter(&self.content);
+                }
+
+                Command::none()
+            }
+            Message::TailRegexToggled(is_regex) => {
+                if let Some(tail) = &mut self.tail {
+                    tail.is_regex = is_regex;
+                    tail.refilter(&self.content);
+                }
+
+                Command::none()
+            }
+            Message::OpenRemote => {
+                self.panel = Some(Panel::Remote(remote::Dialog::new()));
+
+                Command::none()
+            }
+            Message::RemoteUrlChanged(url) => {
+                if let Some(Panel::Remote(dialog)) = &mut self.panel {
+                    dialog.url = url;
+                }
+
+                Command::none()
+            }
+            Message::RemotePasswordChanged(password) => {
+                if let Some(Panel::Remote(dialog)) = &mut self.panel {
+                    dialog.password = password;
+                }
+
+                Command::none()
+            }
+            Message::RemoteAgentToggled(use_agent) => {
+                if let Some(Panel::Remote(dialog)) = &mut self.panel {
+                    dialog.use_agent = use_agent;
+                }
+
+                Command::none()
+            }
+            Message::ConnectRemote => {
+                let Some(Panel::Remote(dialog)) = &self.panel else {
+                    return Command::none();
+                };
+
+                if web::is_url(&dialog.url) {
+                    let url = dialog.url.clone();
+                    self.panel = None;
+
+                    return Command::perform(web::fetch(url), Message::UrlOpened);
+                }
+
+                match dialog.session() {
+                    Some(session) => {
+                        self.panel = None;
+
+                        Command::perform(remote::download(session), Message::RemoteOpened)
+                    }
+                    None => Command::none(),
+                }
+            }
+            Message::RemoteOpened(Ok((session, content))) => {
+                self.path = None;
+                self.image = None;
+                self.table = None;
+                self.tail = None;
+                self.remote = Some(session);
+                self.url = None;
+                self.content = text_editor::Content::with(&content);
+                self.is_dirty = false;
+
+                Command::none()
+            }
+            Message::RemoteOpened(Err(error)) => {
+                self.error = Some(error);
+                Command::none()
+            }
+            Message::UrlOpened(Ok((url, content))) => {
+                self.path = None;
+                self.image = None;
+                self.table = None;
+                self.tail = None;
+                self.remote = None;
+                self.url = Some(url);
+                self.content = text_editor::Content::with(&content);
+                self.is_dirty = false;
+
+                Command::none()
+            }
+            Message::UrlOpened(Err(error)) => {
+                self.error = Some(error);
+                Command::none()
+            }
+            Message::ConfigLoaded(Ok(config)) => {
+                self.config = config;
+
+                if self.config.tray_icon && self.tray.is_none() {
+                    self.tray = Some(tray::Tray::spawn(self.config.recent_workspaces.clone()));
+                }
+
+                if let Some(accelerator) = self.config.quick_note_hotkey() {
+                    match hotkey::QuickNote::register(accelerator) {
+                        Ok(quick_note) => self.quick_note = Some(quick_note),
+                        Err(error) => self.error = Some(error),
+                    }
+                }
+
+                Command::none()
+            }
+            Message::ConfigLoaded(Err(error)) => {
+                self.error = Some(error);
+                Command::none()
+            }
+            Message::ToggleSync => {
+                if let Some(Panel::Sync(_)) = self.panel {
+                    self.panel = None;
+                } else {
+                    self.panel = Some(Panel::Sync(sync::Panel {
+                        endpoint: self.config.sync_endpoint.clone().unwrap_or_default(),
+                        ..sync::Panel::default()
+                    }));
+                }
+
+                Command::none()
+            }
+            Message::SyncEndpointChanged(endpoint) => {
+                if let Some(Panel::Sync(panel)) = &mut self.panel {
+                    panel.endpoint = endpoint;
+                }
+
+                Command::none()
+            }
+            Message::SyncNow => {
+                let Some(Panel::Sync(panel)) = &mut self.panel else {
+                    return Command::none();
+                };
+
+                let endpoint = panel.endpoint.trim().to_string();
+
+                if endpoint.is_empty() || panel.is_syncing {
+                    return Command::none();
+                }
+
+                panel.is_syncing = true;
+                self.config.sync_endpoint = Some(endpoint);
+
+                Command::perform(sync::run(self.config.clone()), Message::Synced)
+            }
+            Message::Synced(result) => {
+                let report = match result {
+                    Ok(report) => Some(report),
+                    Err(error) => {
+                        self.error = Some(error);
+                        None
+                    }
+                };
+
+                if let Some(Panel::Sync(panel)) = &mut self.panel {
+                    panel.is_syncing = false;
+                    panel.report = report;
+                }
+
+                Command::none()
+            }
+            Message::ToggleCollab => {
+                if self.collab.take().is_some() {
+                    return Command::none();
+                }
+
+                if let Some(Panel::Collab(_)) = self.panel {
+                    self.panel = None;
+                } else {
+                    self.panel = Some(Panel::Collab(collab::Dialog::new()));
+                }
+
+                Command::none()
+            }
+            Message::CollabNameChanged(name) => {
+                if let Some(Panel::Collab(dialog)) = &mut self.panel {
+                    dialog.name = name;
+                }
+
+                Command::none()
+            }
+            Message::CollabPortChanged(port) => {
+                if let Some(Panel::Collab(dialog)) = &mut self.panel {
+                    dialog.port = port;
+                }
+
+                Command::none()
+            }
+            Message::CollabAddressChanged(address) => {
+                if let Some(Panel::Collab(dialog)) = &mut self.panel {
+                    dialog.address = address;
+                }
+
+                Command::none()
+            }
+            Message::CollabKeyChanged(key) => {
+                if let Some(Panel::Collab(dialog)) = &mut self.panel {
+                    dialog.key = key;
+                }
+
+                Command::none()
+            }
+            Message::StartCollab(role) => {
+                let text = self.content.text();
+
+                if self.warn_of_secrets(&text, Message::StartCollab(role.clone()), "Share") {
+                    return Command::none();
+                }
+
+                if let Some(Panel::Collab(dialog)) = self.panel.take() {
+                    self.collab =
+                        Some(collab::Session::new(role, dialog.name, self.content.text()));
+                }
+
+                Command::none()
+            }
+            Message::Indexed(event) => {
+                self.indexer.handle(event);
+                Command::none()
+            }
+            Message::Collab(event) => {
+                let Some(session) = &mut self.collab else {
+                    return Command::none();
+                };
+
+                match event {
+                    collab::Event::Ready(sender) => {
+                        session.connect(sender);
+                    }
+                    collab::Event::PeerJoined | collab::Event::Lagged => {
+                        session.send_state();
+                    }
+                    collab::Event::Received(frame) => {
+                        if let Some(change) = session.receive(frame) {
+                            splice(&mut self.content, change.range, &change.text);
+                            self.is_dirty = true;
+                        }
+                    }
+                    collab::Event::PeerLeft => {
+                        if let collab::Role::Join(..) = session.role {
+                            self.collab = None;
+                            self.error =
+                                Some(Error::RemoteFailed(String::from("disconnected from host")));
+                        }
+                    }
+                    collab::Event::Disconnected(error) => {
+                        self.collab = None;
+                        self.error = Some(Error::RemoteFailed(error));
+                    }
+                }
+
+                Command::none()
+            }
+            Message::TogglePalette => {
+                if self.palette.take().is_some() {
+                    Command::none()
+                } else {
+                    self.palette = Some(palette::Palette::default());
+
+                    palette::Palette::focus()
+                }
+            }
+            Message::ClosePalette => {
+                self.palette = None;
+                self.menu = None;
+
+                // Escape also brings focus back to the editor
+                if self.focused() != focus::Region::Editor {
+                    self.focus = focus::Region::Editor;
+
+                    return focus::enter(self.focus);
+                }
+
+                Command::none()
+            }
+            Message::CycleFocus(step) => {
+                self.focus = self.focused().cycle(&self.focus_regions(), step);
+                self.focus_visible = true;
+
+                if let (focus::Region::Tree, Some(tree)) = (self.focus, &mut self.tree) {
+                    if tree.cursor.is_none() {
+                        tree.move_cursor(0);
+                    }
+                }
+
+                focus::enter(self.focus)
+            }
+            Message::ActivateFocused => match (self.focused(), &self.tree) {
+                (focus::Region::Tree, Some(tree)) => match tree.activate() {
+                    Some(message) => self.update(message),
+                    None => Command::none(),
+                },
+                _ => Command::none(),
+            },
+            Message::DividerPressed(divider) => {
+                let sizes = self.sizes().clone();
+
+                if self.dividers.press(divider, &sizes) {
+                    self.sizes_mut().toggle(divider);
+
+                    return self.save_sizes();
+                }
+
+                Command::none()
+            }
+            Message::DividerDragged(x) => {
+                if let Some((divider, width)) = self.dividers.drag(x) {
+                    self.sizes_mut().resize(divider, width);
+                }
+
+                Command::none()
+            }
+            Message::DividerReleased => {
+                if self.dividers.release() {
+                    self.save_sizes()
+                } else {
+                    Command::none()
+                }
+            }
+            Message::ToggleStatusSettings => {
+                self.panel = match self.panel {
+                    Some(Panel::StatusBar) => None,
+                    _ => Some(Panel::StatusBar),
+                };
+
+                Command::none()
+            }
+            Message::ToggleToolbarSettings => {
+                self.panel = match self.panel {
+                    Some(Panel::Toolbar) => None,
+                    _ => Some(Panel::Toolbar),
+                };
+
+                Command::none()
+            }
+            Message::ToolbarButtonToggled(button, is_shown) => {
+                toolbar::set_shown(&mut self.config, button, is_shown);
+
+                Command::perform(self.config.clone().save(), Message::ConfigSaved)
+            }
+            Message::ToolbarButtonMoved(button, step) => {
+                toolbar::move_button(&mut self.config, button, step);
+
+                Command::perform(self.config.clone().save(), Message::ConfigSaved)
+            }
+            Message::ToolbarStyleSelected(style) => {
+                self.config.toolbar_style = style;
+
+                Command::perform(self.config.clone().save(), Message::ConfigSaved)
+            }
+            Message::StatusSegmentToggled(slot, is_shown) => {
+                self.config.status_hidden.retain(|hidden| *hidden != slot);
+
+                if !is_shown {
+                    self.config.status_hidden.push(slot);
+                }
+
+                Command::perform(self.config.clone().save(), Message::ConfigSaved)
+            }
+            Message::StatusSegmentMoved(slot, step) => {
+                status::move_slot(&mut self.config, slot, step);
+
+                Command::perform(self.config.clone().save(), Message::ConfigSaved)
+            }
+            Message::MenuOpened(menu) => {
+                self.menu = menu;
+
+                Command::none()
+            }
+            Message::MenuRun(message) => {
+                self.menu = None;
+
+                self.update(*message)
+            }
+            Message::TaskFinished(ticket, result) => {
+                let is_latest = self.tasks.finish(ticket);
+                let index = ticket
+                    .tab
+                    .and_then(|id| self.tabs.iter().position(|tab| tab.id == id));
+
+                // The tab was closed while the task ran
+                if ticket.tab.is_some() && index.is_none() {
+                    return Command::none();
+                }
+
+                let is_shown = match index {
+                    Some(index) => index == self.active,
+                    None => true,
+                };
+
+                if is_latest && is_shown {
+                    return match result {
+                        Some(result) => self.update(*result),
+                        None => Command::none(),
+                    };
+                }
+
+                // Overtaken, or meant for a tab that is now in the background
+                if let Some(buffer) = index.and_then(|index| self.tabs[index].buffer.as_mut()) {
+                    match (ticket.kind, result.as_deref()) {
+                        (tasks::Kind::Open, _) => buffer.is_loaded = false,
+                        (
+                            tasks::Kind::Save,
+                            Some(Message::FileSaved(Ok(_) | Err(Error::Queued(_)))),
+                        ) if is_latest => {
+                            buffer.is_dirty = false;
+                        }
+                        _ => {}
+                    }
+                }
+
+                // A save kept locally still has to reach its file, whichever tab it came from
+                match result.map(|message| *message) {
+                    Some(Message::FileSaved(Err(Error::Queued(entry)))) => self.queue_save(entry),
+                    _ => Command::none(),
+                }
+            }
+            Message::ModifiedRead((path, modified)) => {
+                if self.path.as_ref() == Some(&path) {
+                    self.modified = modified;
+                }
+
+                Command::none()
+            }
+            Message::ResolveConflict(resolution) => {
+                let Some(Panel::Conflict(path)) = self.panel.take() else {
+                    return Command::none();
+                };
+
+                match resolution {
+                    conflict::Resolution::Overwrite => {
+                        self.modified = None;
+                        self.update(Message::Save)
+                    }
+                    conflict::Resolution::Reload => self.load(path),
+                    conflict::Resolution::Merge => {
+                        Command::perform(load_file(path), Message::ConflictDiskRead)
+                    }
+                }
+            }
+            Message::ConflictDiskRead(Ok((path, text, _))) => {
+                if self.path.as_ref() != Some(&path) {
+                    return Command::none();
+                }
+
+                self.panel = Some(Panel::Diff(diff::Diff::between(
+                    &self.content.text(),
+                    &text,
+                    diff::Sides {
+                        title: "Buffer vs. file on disk, save again to keep the buffer",
+                        old: "buffer",
+                        new: "file on disk",
+                    },
+                )));
+
+                // Having seen the other version, the next save replaces it
+                Command::perform(conflict::modified(path), Message::ModifiedRead)
+            }
+            Message::ConflictDiskRead(Err(error)) => {
+                self.error = Some(error);
+                Command::none()
+            }
+            Message::StartMerge => {
+                let Some(path) = self.path.clone() else {
+                    return Command::none();
+                };
+
+                self.merge = Some(merge::Merge::from_markers(path, &self.content.text()));
+                Command::none()
+            }
+            Message::MergeFiles => Command::perform(merge::pick(), Message::MergeLoaded),
+            Message::MergeLoaded(Ok(merge)) => {
+                self.merge = Some(merge);
+                Command::none()
+            }
+            Message::MergeLoaded(Err(error)) => {
+                if !matches!(error, Error::DialogClosed) {
+                    self.error = Some(error);
+                }
+
+                Command::none()
+            }
+            Message::MergeChose(index, choice) => {
+                if let Some(merge) = &mut self.merge {
+                    merge.choose(index, choice);
+                }
+
+                Command::none()
+            }
+            Message::MergeNavigated(step) => match &mut self.merge {
+                Some(merge) => merge.navigate(step),
+                None => Command::none(),
+            },
+            Message::WriteMerge => match &self.merge {
+                Some(merge) => Command::perform(
+                    merge::write(merge.target.clone(), merge.result()),
+                    Message::MergeWritten,
+                ),
+                None => Command::none(),
+            },
+            Message::MergeWritten(Ok(path)) => {
+                self.merge = None;
+
+                if self.path.as_ref() == Some(&path) {
+                    self.load(path)
+                } else {
+                    self.open_in_tab(path)
+                }
+            }
+            Message::MergeWritten(Err(error)) => {
+                self.error = Some(error);
+                Command::none()
+            }
+            Message::CancelMerge => {
+                self.merge = None;
+                Command::none()
+            }
+            Message::ToggleInlineBlame => {
+                self.config.inline_blame = !self.config.inline_blame;
+
+                Command::batch([
+                    Command::perform(self.config.clone().save(), Message::ConfigSaved),
+                    self.read_blame(),
+                ])
+            }
+            Message::ToggleBlameGutter => {
+                if let Some(Panel::Blame(_)) = self.panel {
+                    self.panel = None;
+                    return Command::none();
+                }
+
+                self.panel = Some(Panel::Blame(git::Gutter::default()));
+                self.read_blame()
+            }
+            Message::BlameLoaded(Ok(blame)) => {
+                if self.path.as_ref() == Some(&blame.path) {
+                    self.blame = Some(blame);
+                }
+
+                Command::none()
+            }
+            Message::BlameLoaded(Err(error)) => {
+                self.blame = None;
+
+                // Files outside a repository only fail quietly in the status bar
+                if let Some(Panel::Blame(_)) = self.panel {
+                    self.panel = None;
+                    self.error = Some(error);
+                }
+
+                Command::none()
+            }
+            Message::BlameScrolled(viewport) => {
+                if let Some(Panel::Blame(gutter)) = &mut self.panel {
+                    gutter.scrolled(viewport);
+                }
+
+                Command::none()
+            }
+            Message::ShowCommit(id) => {
+                let Some(path) = self.path.clone() else {
+                    return Command::none();
+                };
+
+                let read = match self.panel {
+                    Some(Panel::Blame(_)) => Command::none(),
+                    _ => {
+                        self.panel = Some(Panel::Blame(git::Gutter::default()));
+                        self.read_blame()
+                    }
+                };
+
+                Command::batch([
+                    read,
+                    Command::perform(git::message(path, id), Message::CommitLoaded),
+                ])
+            }
+            Message::CommitLoaded(Ok(commit)) => {
+                if let Some(Panel::Blame(gutter)) = &mut self.panel {
+                    gutter.commit = Some(commit);
+                }
+
+                Command::none()
+            }
+            Message::CommitLoaded(Err(error)) => {
+                self.error = Some(error);
+                Command::none()
+            }
+            Message::ToggleHistory => {
+                if let Some(Panel::History(_)) = self.panel {
+                    self.panel = None;
+                    return Command::none();
+                }
+
+                match self.path.clone() {
+                    Some(path) if self.remote.is_none() && self.url.is_none() => {
+                        Command::perform(git::history(path), Message::HistoryLoaded)
+                    }
+                    _ => Command::none(),
+                }
+            }
+            Message::HistoryLoaded(Ok(history)) => {
+                self.panel = Some(Panel::History(history));
+                Command::none()
+            }
+            Message::RevisionSelected(index) => {
+                let Some(Panel::History(history)) = &mut self.panel else {
+                    return Command::none();
+                };
+
+                match history.select(index) {
+                    Some(revision) => Command::perform(
+                        git::patch(history.path.clone(), revision),
+                        Message::PatchLoaded,
+                    ),
+                    None => Command::none(),
+                }
+            }
+            Message::PatchLoaded(Ok((id, patch))) => {
+                if let Some(Panel::History(history)) = &mut self.panel {
+                    history.patched(&id, &patch);
+                }
+
+                Command::none()
+            }
+            Message::HistoryScrolled(viewport) => {
+                if let Some(Panel::History(history)) = &mut self.panel {
+                    history.scrolled(viewport);
+                }
+
+                Command::none()
+            }
+            Message::OpenRevision => match &self.panel {
+                Some(Panel::History(history)) => match history.selected() {
+                    Some(revision) => Command::perform(
+                        git::show(history.path.clone(), revision.clone()),
+                        Message::RevisionOpened,
+                    ),
+                    None => Command::none(),
+                },
+                _ => Command::none(),
+            },
+            Message::RevisionOpened(Ok(revision)) => {
+                // Shown like a page from the web, read-only and saved only under a new name
+                self.new_tab();
+                self.update(Message::UrlOpened(Ok(revision)))
+            }
+            Message::ToggleChanges => {
+                if let Some(Panel::Changes(_)) = self.panel {
+                    self.panel = None;
+                    return Command::none();
+                }
+
+                self.panel = Some(Panel::Changes(None));
+                self.read_changes()
+            }
+            Message::ChangesRead(Ok(changes)) => {
+                let is_current = self.path.as_ref() == Some(&changes.path)
+                    && changes.buffer == self.content.text();
+
+                if let Some(Panel::Changes(current)) = &mut self.panel {
+                    if is_current {
+                        *current = Some(changes);
+                    }
+                }
+
+                Command::none()
+            }
+            Message::HunkSelected(index) => {
+                if let Some(Panel::Changes(Some(changes))) = &self.panel {
+                    if let Some(line) = changes.line(index) {
+                        jump_to(&mut self.content, line);
+                    }
+                }
+
+                Command::none()
+            }
+            Message::StageHunk(index) => match &self.panel {
+                Some(Panel::Changes(Some(changes))) => {
+                    Command::perform(git::stage(changes.clone(), index), Message::HunkStaged)
+                }
+                _ => Command::none(),
+            },
+            Message::HunkStaged(Ok(_)) => self.read_changes(),
+            Message::RevertHunk(index) => {
+                if self.is_read_only() {
+                    return Command::none();
+                }
+
+                let Some(Panel::Changes(Some(changes))) = &self.panel else {
+                    return Command::none();
+                };
+
+                let text = self.content.text();
+
+                // The hunks are out of date until the pending comparison comes back
+                if changes.buffer != text {
+                    return self.read_changes();
+                }
+
+                let Some((range, staged)) = changes.revert(index) else {
+                    return Command::none();
+                };
+
+                select_offsets(&mut self.content, &text, range);
+
+                self.update(Message::Edit(text_editor::Action::Edit(
+                    text_editor::Edit::Paste(Arc::new(staged)),
+                )))
+            }
+            Message::CopyPermalink => {
+                let lines = selected_lines(&self.content).unwrap_or_else(|| {
+                    let line = self.content.cursor_position().0;
+                    line..line + 1
+                });
+
+                match self.path.clone() {
+                    Some(path) if self.remote.is_none() && self.url.is_none() => {
+                        Command::perform(git::permalink(path, lines), Message::PermalinkBuilt)
+                    }
+                    _ => Command::none(),
+                }
+            }
+            Message::PermalinkBuilt(Ok(link)) => iced::clipboard::write(link),
+            Message::CheckPatch => match self.project_folder() {
+                Some((root, _)) => Command::perform(
+                    patch::check(root, self.content.text()),
+                    Message::PatchChecked,
+                ),
+                None => Command::none(),
+            },
+            Message::PatchChecked(Ok(report)) => {
+                self.panel = Some(Panel::Patch(report));
+                Command::none()
+            }
+            Message::ApplyPatch => match &self.panel {
+                Some(Panel::Patch(report)) if report.applies() => {
+                    Command::perform(patch::write(report.clone()), Message::PatchApplied)
+                }
+                _ => Command::none(),
+            },
+            Message::PatchApplied(Ok(files)) => {
+                let Some(Panel::Patch(report)) = &mut self.panel else {
+                    return Command::none();
+                };
+
+                report.applied(files);
+
+                let changed: Vec<PathBuf> = report.paths().map(Path::to_path_buf).collect();
+
+                // Background tabs read the patched files once shown, as unloaded tabs do
+                for buffer in self.tabs.iter_mut().filter_map(|tab| tab.buffer.as_mut()) {
+                    if !buffer.is_dirty
+                        && buffer
+                            .path
+                            .as_ref()
+                            .is_some_and(|path| changed.contains(path))
+                    {
+                        buffer.is_loaded = false;
+                    }
+                }
+
+                match self.path.clone() {
+                    Some(path) if !self.is_dirty && changed.contains(&path) => self.load(path),
+                    _ => Command::none(),
+                }
+            }
+            Message::ToggleFilter => {
+                if let Some(Panel::Filter(_)) = self.panel {
+                    self.panel = None;
+                } else if !self.is_read_only() {
+                    self.panel = Some(Panel::Filter(filter::Prompt::new(
+                        self.last_filter.clone(),
+                        self.content.selection().is_some(),
+                    )));
+                }
+
+                Command::none()
+            }
+            Message::FilterCommandChanged(command) => {
+                if let Some(Panel::Filter(prompt)) = &mut self.panel {
+                    prompt.command = command;
+                }
+
+                Command::none()
+            }
+            Message::RunFilter => {
+                let Some(Panel::Filter(prompt)) = &self.panel else {
+                    return Command::none();
+                };
+
+                let command = prompt.command.trim().to_string();
+
+                if command.is_empty() || self.is_read_only() {
+                    return Command::none();
+                }
+
+                self.panel = None;
+                self.last_filter = command.clone();
+
+                let buffer = self.content.text();
+                let range = selection_offsets(&self.content).unwrap_or(0..buffer.len());
+                let directory = self
+                    .project_folder()
+                    .map(|(folder, _)| folder)
+                    .or_else(dirs::home_dir)
+                    .unwrap_or_else(|| PathBuf::from("."));
+
+                self.perform_for_tab(
+                    tasks::Kind::Filter,
+                    filter::run(command, directory, buffer, range),
+                    Message::FilterFinished,
+                )
+            }
+            Message::FilterFinished(Ok(output)) => {
+                if output.buffer != self.content.text() {
+                    self.toast = Some(toast::Toast::new(
+                        "The buffer changed while the command ran, so its output was dropped",
+                    ));
+                    return Command::none();
+                }
+
+                if !output.stderr.is_empty() {
+                    self.toast = Some(toast::Toast::new(output.stderr.clone()));
+                }
+
+                // Commands end their output with a line break the selection may not have had
+                let input = &output.buffer[output.range.clone()];
+                let replacement = match output.stdout.strip_suffix('\n') {
+                    Some(stdout) if !input.ends_with('\n') => stdout.to_string(),
+                    _ => output.stdout.clone(),
+                };
+
+                if output.range == (0..output.buffer.len()) {
+                    self.content.edit(text_editor::Action::Move(
+                        text_editor::Motion::DocumentStart,
+                    ));
+                    self.content.edit(text_editor::Action::Select(
+                        text_editor::Motion::DocumentEnd,
+                    ));
+                } else {
+                    select_offsets(&mut self.content, &output.buffer, output.range);
+                }
+
+                self.update(Message::Edit(text_editor::Action::Edit(
+                    text_editor::Edit::Paste(Arc::new(replacement)),
+                )))
+            }
+            Message::FilterFinished(Err(error)) => {
+                self.toast =
+                    Some(toast::Toast::new(error.message().unwrap_or_else(|| {
+                        String::from("The command could not run")
+                    })));
+
+                Command::none()
+            }
+            Message::OfflineQueueLoaded(Ok(queue)) => {
+                self.offline.restore(queue);
+                Command::none()
+            }
+            Message::StorageTick => Command::none(),
+            Message::FlushOffline => {
+                if self.offline.is_empty() || self.offline.is_flushing {
+                    return Command::none();
+                }
+
+                self.offline.is_flushing = true;
+
+                Command::perform(
+                    offline::flush(self.offline.entries()),
+                    Message::OfflineFlushed,
+                )
+            }
+            Message::OfflineFlushed(written) => {
+                self.offline.is_flushing = false;
+
+                if written.is_empty() {
+                    return Command::none();
+                }
+
+                self.offline.written(&written);
+
+                self.toast = Some(toast::Toast::new(match written.len() {
+                    1 => String::from("Wrote a save that was waiting for its file"),
+                    count => format!("Wrote {count} saves that were waiting for their files"),
+                }));
+
+                let modified = written.into_iter().map(|entry| {
+                    Command::perform(conflict::modified(entry.path), Message::ModifiedRead)
+                });
+
+                Command::batch(modified.chain([Command::perform(
+                    self.offline.clone().save(Vec::new()),
+                    Message::OfflineQueueSaved,
+                )]))
+            }
+            Message::ToastTick => {
+                if self
+                    .toast
+                    .as_ref()
+                    .is_some_and(|toast| toast.is_expired(Instant::now()))
+                {
+                    self.toast = None;
+                }
+
+                Command::none()
+            }
+            Message::DismissToast => {
+                self.toast = None;
+                Command::none()
+            }
+            Message::RunWorkspaceTask(task) => {
+                let Some(workspace) = &self.workspace else {
+                    return Command::none();
+                };
+
+                let Some(command) = workspace.settings.tasks.get(&task) else {
+                    return Command::none();
+                };
+
+                self.runs += 1;
+                self.run = Some(runner::Run::new(
+                    self.runs,
+                    task,
+                    command.clone(),
+                    workspace.root.clone(),
+                ));
+                self.panel = Some(Panel::Output);
+
+                Command::none()
+            }
+            Message::TaskOutput(event) => {
+                if self.run.as_mut().is_some_and(|run| run.handle(event)) {
+                    self.match_problems();
+                }
+
+                Command::none()
+            }
+            Message::StopTaskRun => {
+                if let Some(run) = self.run.as_mut().filter(|run| run.is_running()) {
+                    run.stop();
+                    self.match_problems();
+                }
+
+                Command::none()
+            }
+            Message::OutputScrolled(viewport) => {
+                if let Some(run) = &mut self.run {
+                    run.scrolled(viewport);
+                }
+
+                Command::none()
+            }
+            Message::ShowOutput => {
+                if let Some(Panel::Output) = self.panel {
+                    self.panel = None;
+                } else if self.run.is_some() {
+                    self.panel = Some(Panel::Output);
+                }
+
+                Command::none()
+            }
+            Message::ShowProblems => {
+                if let Some(Panel::Problems(_)) = self.panel {
+                    self.panel = None;
+                } else {
+                    self.panel = Some(Panel::Problems(problems::List::default()));
+                }
+
+                Command::none()
+            }
+            Message::ProblemSelected(index) => match self.problems.get(index) {
+                Some(problem) => {
+                    let (path, line) = (problem.path.clone(), problem.line);
+
+                    self.update(Message::TodoSelected(Some(path), line))
+                }
+                None => Command::none(),
+            },
+            Message::StartDebugging(name) => {
+                let Some(workspace) = &self.workspace else {
+                    return Command::none();
+                };
+
+                let Some(configuration) = workspace.settings.debug.get(&name) else {
+                    return Command::none();
+                };
+
+                self.runs += 1;
+                self.debug = Some(debug::Session::new(
+                    self.runs,
+                    name,
+                    configuration.clone(),
+                    workspace.root.clone(),
+                ));
+                self.panel = Some(Panel::Debug);
+
+                Command::none()
+            }
+            Message::Debug(event) => {
+                let Some(session) = &mut self.debug else {
+                    return Command::none();
+                };
+
+                let outcome = match event {
+                    debug::Event::Ready(sender) => {
+                        session.connect(sender);
+                        debug::Outcome::Nothing
+                    }
+                    debug::Event::Received(message) => session.handle(message, &self.breakpoints),
+                    debug::Event::Exited(reason) => debug::Outcome::Ended(reason),
+                };
+
+                match outcome {
+                    debug::Outcome::Nothing => Command::none(),
+                    debug::Outcome::Stopped(path, line) => {
+                        self.update(Message::TodoSelected(Some(path), line))
+                    }
+                    debug::Outcome::Ended(reason) => {
+                        self.end_debugging();
+                        self.toast = Some(toast::Toast::new(reason));
+
+                        Command::none()
+                    }
+                }
+            }
+            Message::ToggleBreakpoint => {
+                let Some(path) = self.path.clone() else {
+                    return Command::none();
+                };
+
+                let line = self.content.cursor_position().0;
+                let lines = self.breakpoints.entry(path.clone()).or_default();
+
+                if !lines.remove(&line) {
+                    lines.insert(line);
+                }
+
+                if let Some(session) = &mut self.debug {
+                    session.set_breakpoints(&path, lines);
+                }
+
+                if lines.is_empty() {
+                    self.breakpoints.remove(&path);
+                }
+
+                Command::none()
+            }
+            Message::DebugControl(control) => {
+                // A second Stop gives up on an adapter that never answered the first
+                if control == debug::Control::Stop
+                    && self.debug.as_ref().is_some_and(debug::Session::is_ending)
+                {
+                    self.end_debugging();
+                } else if let Some(session) = &mut self.debug {
+                    session.control(control);
+                }
+
+                Command::none()
+            }
+            Message::WatchChanged(expression) => {
+                if let Some(session) = &mut self.debug {
+                    session.new_watch = expression;
+                }
+
+                Command::none()
+            }
+            Message::AddWatch => {
+                if let Some(session) = &mut self.debug {
+                    session.add_watch();
+                }
+
+                Command::none()
+            }
+            Message::RemoveWatch(index) => {
+                if let Some(session) = &mut self.debug {
+                    session.remove_watch(index);
+                }
+
+                Command::none()
+            }
+            Message::ToggleDebugPanel => {
+                if let Some(Panel::Debug) = self.panel {
+                    self.panel = None;
+                } else if self.debug.is_some() {
+                    self.panel = Some(Panel::Debug);
+                }
+
+                Command::none()
+            }
+            Message::SendRequest => {
+                let line = self.content.cursor_position().0;
+
+                let Some(request) = rest::request_at(&self.content.text(), line) else {
+                    self.toast = Some(toast::Toast::new("There is no request under the cursor"));
+                    return Command::none();
+                };
+
+                self.panel = Some(Panel::Response(None));
+
+                Command::perform(rest::send(request), Message::ResponseReceived)
+            }
+            Message::ResponseReceived(Ok(response)) => {
+                self.panel = Some(Panel::Response(Some(response)));
+                Command::none()
+            }
+            Message::ResponseReceived(Err(error)) => {
+                if let Some(Panel::Response(None)) = self.panel {
+                    self.panel = None;
+                }
+
+                self.error = Some(error);
+                Command::none()
+            }
+            Message::OpenResponseBody => {
+                let Some(Panel::Response(Some(response))) = &self.panel else {
+                    return Command::none();
+                };
+
+                let opened = (response.label(), response.body.clone());
+
+                self.new_tab();
+                self.update(Message::UrlOpened(Ok(opened)))
+            }
+            Message::UseDatabase(name) => {
+                self.database = Some(name);
+                Command::none()
+            }
+            Message::RunQuery => {
+                let name = match &self.database {
+                    Some(name) => name.clone(),
+                    None if self.config.databases.len() == 1 => self
+                        .config
+                        .databases
+                        .keys()
+                        .next()
+                        .cloned()
+                        .unwrap_or_default(),
+                    None => {
+                        self.toast = Some(toast::Toast::new(
+                            "Choose a connection with \"Use database\" first",
+                        ));
+                        return Command::none();
+                    }
+                };
+
+                let Some(url) = self.config.databases.get(&name).cloned() else {
+                    return Command::none();
+                };
+
+                let statement = match self.content.selection() {
+                    Some(selection) => selection,
+                    None => {
+                        let text = self.content.text();
+                        let offset = cursor_offset(&self.content, &text);
+
+                        match database::statement_at(&text, offset) {
+                            Some(range) => text[range].to_string(),
+                            None => return Command::none(),
+                        }
+                    }
+                };
+
+                self.database = Some(name.clone());
+                self.panel = Some(Panel::Query(None));
+
+                Command::perform(database::run(name, url, statement), Message::QueryFinished)
+            }
+            Message::QueryFinished(Ok(results)) => {
+                self.panel = Some(Panel::Query(Some(results)));
+                Command::none()
+            }
+            Message::QueryFinished(Err(error)) => {
+                if let Some(Panel::Query(None)) = self.panel {
+                    self.panel = None;
+                }
+
+                self.error = Some(error);
+                Command::none()
+            }
+            Message::ResultsPage(page) => {
+                if let Some(Panel::Query(Some(results))) = &mut self.panel {
+                    results.show_page(page);
+                }
+
+                Command::none()
+            }
+            Message::ExportQuery => match &self.panel {
+                Some(Panel::Query(Some(results))) => Command::perform(
+                    save_file(None, results.to_csv(), None),
+                    Message::QueryExported,
+                ),
+                _ => Command::none(),
+            },
+            Message::QueryExported(Ok(path)) => {
+                self.toast = Some(toast::Toast::new(format!(
+                    "Exported the results to {}",
+                    path.display()
+                )));
+
+                Command::none()
+            }
+            Message::ToggleCells => {
+                if let Some(Panel::Cells) = self.panel {
+                    self.panel = None;
+                } else if self.has_cells() {
+                    self.refresh_notebook();
+                    self.panel = Some(Panel::Cells);
+                }
+
+                Command::none()
+            }
+            Message::RunCell => {
+                let line = self.content.cursor_position().0;
+
+                match cells::at(&cells::split(&self.content.text()), line) {
+                    Some(index) => self.update(Message::RunCellAt(index)),
+                    None => Command::none(),
+                }
+            }
+            Message::RunCellAt(index) => {
+                let text = self.content.text();
+
+                let code = self
+                    .refresh_notebook()
+                    .and_then(|notebook| notebook.cells.get(index))
+                    .map(|cell| cell.code(&text));
+
+                let (Some(code), Some(notebook)) = (code, &mut self.notebook) else {
+                    return Command::none();
+                };
+
+                self.panel = Some(Panel::Cells);
+
+                if notebook.has_kernel() {
+                    if !notebook.is_alive() {
+                        self.runs += 1;
+                        notebook.restart(self.runs);
+                    }
+
+                    notebook.run(index, code);
+
+                    return Command::none();
+                }
+
+                let runner = compression::extension(&notebook.path)
+                    .and_then(|extension| self.config.cell_runners.get(extension))
+                    .cloned();
+
+                let Some(command) = runner else {
+                    self.toast = Some(toast::Toast::new(
+                        "Set a command for this kind of file under cell_runners to run its cells",
+                    ));
+                    return Command::none();
+                };
+
+                let directory = notebook
+                    .path
+                    .parent()
+                    .map_or_else(|| PathBuf::from("."), Path::to_path_buf);
+
+                notebook.start(index);
+
+                Command::perform(
+                    filter::run(command, directory, code.clone(), 0..code.len()),
+                    move |result| Message::CellRan(index, result),
+                )
+            }
+            Message::RunAllCells => {
+                let count = cells::split(&self.content.text()).len();
+                let mut commands = Vec::with_capacity(count);
+
+                for index in 0..count {
+                    commands.push(self.update(Message::RunCellAt(index)));
+                }
+
+                Command::batch(commands)
+            }
+            Message::RestartKernel => {
+                if let Some(notebook) = self
+                    .notebook
+                    .as_mut()
+                    .filter(|notebook| notebook.has_kernel())
+                {
+                    self.runs += 1;
+                    notebook.restart(self.runs);
+                }
+
+                Command::none()
+            }
+            Message::Cells(event) => {
+                if let Some(notebook) = &mut self.notebook {
+                    notebook.handle(event);
+                }
+
+                Command::none()
+            }
+            Message::CellSelected(index) => {
+                let start = self
+                    .notebook
+                    .as_ref()
+                    .and_then(|notebook| notebook.cells.get(index))
+                    .map(|cell| cell.start);
+
+                if let Some(start) = start {
+                    jump_to(&mut self.content, start);
+                }
+
+                Command::none()
+            }
+            Message::CellRan(index, result) => {
+                if let Some(notebook) = &mut self.notebook {
+                    let output = match result {
+                        Ok(output) if output.stderr.is_empty() => output.stdout,
+                        Ok(output) => format!("{}{}", output.stdout, output.stderr),
+                        Err(error) => error.message().unwrap_or_default(),
+                    };
+
+                    notebook.finish(index, output);
+                }
+
+                Command::none()
+            }
+            Message::StartRepl(name) => {
+                let Some(root) = self
+                    .workspace
+                    .as_ref()
+                    .map(|workspace| workspace.root.clone())
+                else {
+                    return Command::none();
+                };
+
+                let Some(command) = self.repl_command(&name) else {
+                    return Command::none();
+                };
+
+                self.runs += 1;
+                self.repls
+                    .push(repl::Repl::new(self.runs, name, command, root));
+                self.active_repl = self.repls.len() - 1;
+                self.panel = Some(Panel::Repl);
+
+                Command::none()
+            }
+            Message::ReplEvent(event) => {
+                let id = match &event {
+                    repl::Event::Ready(id, _)
+                    | repl::Event::Line(id, _)
+                    | repl::Event::Exited(id, _) => *id,
+                };
+
+                if let Some(repl) = self.repls.iter_mut().find(|repl| repl.id == id) {
+                    repl.handle(event);
+                }
+
+                Command::none()
+            }
+            Message::ReplInputChanged(input) => {
+                if let Some(repl) = self.repls.get_mut(self.active_repl) {
+                    repl.input = input;
+                }
+
+                Command::none()
+            }
+            Message::SubmitRepl => {
+                let Some(repl) = self.repls.get_mut(self.active_repl) else {
+                    return Command::none();
+                };
+
+                let input = std::mem::take(&mut repl.input);
+                repl.send(&input);
+
+                if input.trim().is_empty() {
+                    return Command::none();
+                }
+
+                repl::remember(&mut self.repl_history, &repl.name, &input);
+                self.save_repl_history()
+            }
+            Message::RecallRepl(step) => {
+                if let Some(repl) = self.repls.get_mut(self.active_repl) {
+                    let history = self
+                        .repl_history
+                        .get(&repl.name)
+                        .map(Vec::as_slice)
+                        .unwrap_or_default();
+
+                    repl.recall(step, history);
+                }
+
+                Command::none()
+            }
+            Message::SelectRepl(index) => {
+                self.active_repl = index.min(self.repls.len().saturating_sub(1));
+                Command::none()
+            }
+            Message::CloseRepl(index) => {
+                if index < self.repls.len() {
+                    self.repls.remove(index);
+
+                    if self.active_repl > index {
+                        self.active_repl -= 1;
+                    }
+
+                    self.active_repl = self.active_repl.min(self.repls.len().saturating_sub(1));
+                }
+
+                Command::none()
+            }
+            Message::SendToRepl => {
+                let code = match self.content.selection() {
+                    Some(selection) => selection,
+                    None => {
+                        let line = self.content.cursor_position().0;
+
+                        self.content
+                            .line(line)
+                            .map(|line| line.to_string())
+                            .unwrap_or_default()
+                    }
+                };
+
+                let is_running = self
+                    .repls
+                    .get(self.active_repl)
+                    .is_some_and(repl::Repl::is_running);
+
+                if !is_running {
+                    let name = self
+                        .path
+                        .as_deref()
+                        .and_then(compression::extension)
+                        .and_then(repl::for_extension)
+                        .map(str::to_string)
+                        .or_else(|| self.repl_names().into_iter().next());
+
+                    if let Some(name) = name {
+                        let _ = self.update(Message::StartRepl(name));
+                    }
+                }
+
+                let Some(repl) = self
+                    .repls
+                    .get_mut(self.active_repl)
+                    .filter(|repl| repl.is_running())
+                else {
+                    self.toast = Some(toast::Toast::new("Open a workspace to start a REPL"));
+                    return Command::none();
+                };
+
+                repl.send(&code);
+                repl::remember(&mut self.repl_history, &repl.name, code.trim_end());
+                self.panel = Some(Panel::Repl);
+
+                self.save_repl_history()
+            }
+            Message::ToggleRepls => {
+                if let Some(Panel::Repl) = self.panel {
+                    self.panel = None;
+                } else {
+                    self.panel = Some(Panel::Repl);
+                }
+
+                Command::none()
+            }
+            Message::ReplScrolled(viewport) => {
+                if let Some(repl) = self.repls.get_mut(self.active_repl) {
+                    repl.scrolled(viewport);
+                }
+
+                Command::none()
+            }
+            Message::ReplHistoryLoaded(Ok(history)) => {
+                self.repl_history = history;
+                Command::none()
+            }
+            Message::ReplHistorySaved(Ok(())) => Command::none(),
+            Message::ToggleRegexTester => {
+                if let Some(Panel::Pattern(_)) = self.panel {
+                    self.panel = None;
+                } else {
+                    let sample = self.content.selection().unwrap_or_default();
+
+                    self.panel = Some(Panel::Pattern(pattern::Tester::new(String::new(), &sample)));
+                }
+
+                Command::none()
+            }
+            Message::PatternChanged(value) => {
+                if let Some(Panel::Pattern(tester)) = &mut self.panel {
+                    tester.pattern = value;
+                    tester.run();
+                }
+
+                Command::none()
+            }
+            Message::PatternFlagToggled(flag) => {
+                if let Some(Panel::Pattern(tester)) = &mut self.panel {
+                    tester.toggle(flag);
+                }
+
+                Command::none()
+            }
+            Message::SampleEdited(action) => {
+                if let Some(Panel::Pattern(tester)) = &mut self.panel {
+                    let is_edit = action.is_edit();
+
+                    tester.sample.perform(action);
+
+                    if is_edit {
+                        tester.run();
+                    }
+                }
+
+                Command::none()
+            }
+            Message::SearchWithPattern => {
+                if let Some(Panel::Pattern(tester)) = &self.panel {
+                    self.panel = Some(Panel::Search(search::Search {
+                        query: tester.inline(),
+                        is_regex: true,
+                        ..search::Search::default()
+                    }));
+                }
+
+                Command::none()
+            }
+            Message::HistoryLoaded(Err(error))
+            | Message::PatchChecked(Err(error))
+            | Message::PatchApplied(Err(error))
+            | Message::PermalinkBuilt(Err(error))
+            | Message::ChangesRead(Err(error))
+            | Message::HunkStaged(Err(error))
+            | Message::PatchLoaded(Err(error))
+            | Message::RevisionOpened(Err(error))
+            | Message::QueryExported(Err(error))
+            | Message::ReplHistoryLoaded(Err(error))
+            | Message::ReplHistorySaved(Err(error)) => {
+                self.error = Some(error);
+                Command::none()
+            }
+            Message::ShowMemory => {
+                self.panel = Some(Panel::Memory);
+                Command::none()
+            }
+            Message::EvictBackgroundTabs => {
+                self.evict_background_tabs(0);
+                Command::none()
+            }
+            Message::TogglePerformanceHud => {
+                self.hud = match self.hud {
+                    Some(_) => None,
+                    None => Some(perf::Hud::default()),
+                };
+
+                Command::none()
+            }
+            Message::FrameRendered(at) => {
+                if let Some(hud) = &mut self.hud {
+                    hud.frame(at);
+                }
+
+                Command::none()
+            }
+            Message::ShowAbout => {
+                self.panel = Some(Panel::About);
+
+                Command::none()
+            }
+            Message::MotionPreferenceLoaded(is_reduced) => {
+                self.system_reduces_motion = is_reduced;
+
+                Command::none()
+            }
+            Message::SetReducedMotion(is_reduced) => {
+                self.config.reduce_motion = is_reduced;
+
+                Command::perform(self.config.clone().save(), Message::ConfigSaved)
+            }
+            Message::BranchLoaded(branch) => {
+                self.branch = branch;
+
+                Command::none()
+            }
+            Message::PaletteChanged(query) => {
+                if let Some(palette) = &mut self.palette {
+                    palette.query = query;
+                    palette.selected = 0;
+                }
+
+                Command::none()
+            }
+            Message::PaletteMoved(offset) => {
+                if self.palette.is_none() && self.focused() == focus::Region::Tree {
+                    if let Some(tree) = &mut self.tree {
+                        tree.move_cursor(offset);
+                    }
+
+                    return Command::none();
+                }
+
+                let count = match &self.palette {
+                    Some(palette) => palette.matches(self.commands()).len(),
+                    None => return Command::none(),
+                };
+
+                if let Some(palette) = &mut self.palette {
+                    palette.select(offset, count);
+                }
+
+                Command::none()
+            }
+            Message::PaletteSubmitted => {
+                let Some(palette) = self.palette.take() else {
+                    return Command::none();
+                };
+
+                match palette
+                    .matches(self.commands())
+                    .into_iter()
+                    .nth(palette.selected)
+                {
+                    Some((_label, message)) => self.update(message),
+                    None => Command::none(),
+                }
+            }
+            Message::PaletteRun(message) => {
+                self.palette = None;
+
+                self.update(*message)
+            }
+            Message::ConfigSaved(Ok(()))
+            | Message::WorkspaceSaved(Ok(()))
+            | Message::PositionsSaved(Ok(()))
+            | Message::ChecksumsSaved(Ok(()))
+            | Message::GoalsSaved(Ok(()))
+            | Message::OfflineQueueSaved(Ok(())) => Command::none(),
+            Message::ConfigSaved(Err(error))
+            | Message::WorkspaceSaved(Err(error))
+            | Message::PositionsSaved(Err(error))
+            | Message::ChecksumsSaved(Err(error))
+            | Message::GoalsSaved(Err(error))
+            | Message::OfflineQueueLoaded(Err(error))
+            | Message::OfflineQueueSaved(Err(error))
+            | Message::ThemesLoaded(Err(error))
+            | Message::ThemeExported(Err(error))
+            | Message::ThemeImported(Err(error)) => {
+                self.error = Some(error);
+                Command::none()
+            }
+            Message::OpenFolder => Command::perform(workspace::pick(), Message::FolderPicked),
+            Message::FolderPicked(Ok(root)) | Message::SwitchWorkspace(root) => {
+                let save = match self.workspace.take() {
+                    Some(mut workspace) => {
+                        workspace.set_open_files(self.path.clone());
+
+                        Command::perform(workspace.save(), Message::WorkspaceSaved)
+                    }
+                    None => Command::none(),
+                };
+
+                Command::batch([
+                    save,
+                    Command::perform(workspace::Workspace::open(root), Message::WorkspaceOpened),
+                ])
+            }
+            Message::FolderPicked(Err(error)) => {
+                self.error = Some(error);
+                Command::none()
+            }
+            Message::WorkspaceOpened(Ok(workspace)) => {
+                let files = workspace.open_files().collect();
+                let command = self.set_workspace(workspace);
+
+                Command::batch([command, self.open_files(files)])
+            }
+            Message::WorkspaceOpened(Err(error)) => {
+                self.error = Some(error);
+                Command::none()
+            }
+            Message::ToggleTree => {
+                if self.workspace.is_none() {
+                    return self.update(Message::OpenFolder);
+                }
+
+                self.show_tree = !self.show_tree;
+
+                Command::none()
+            }
+            Message::TreeIndexed(root, entries) => {
+                if let Some(tree) = self.tree.as_mut().filter(|tree| tree.root == root) {
+                    tree.entries = entries;
+                }
+
+                Command::none()
+            }
+            Message::TreeToggled(path) => {
+                if let Some(tree) = &mut self.tree {
+                    tree.toggle(path);
+                }
+
+                Command::none()
+            }
+            Message::TreeSelected(path) => self.open_in_tab(path),
+            Message::TreeFilterChanged(query) => {
+                if let Some(tree) = &mut self.tree {
+                    tree.query = query;
+                }
+
+                Command::none()
+            }
+            Message::ShowIgnoredToggled(show_ignored) => {
+                if let Some(tree) = &mut self.tree {
+                    tree.show_ignored = show_ignored;
+                }
+
+                self.index_tree()
+            }
+            Message::TabSelected(index) => {
+                self.tab_menu = None;
+
+                self.select_tab(index)
+            }
+            Message::TabMenu(index) => {
+                self.tab_menu = index;
+
+                Command::none()
+            }
+            Message::TabAction(index, action) => {
+                self.tab_menu = None;
+
+                match action {
+                    tabs::Action::Pin => {
+                        if let Some(tab) = self.tabs.get_mut(index) {
+                            tab.is_pinned = !tab.is_pinned;
+
+                            let pinned = self.tabs.iter().filter(|tab| tab.is_pinned).count();
+                            let target = if self.tabs[index].is_pinned {
+                                pinned - 1
+                            } else {
+                                pinned
+                            };
+
+                            self.move_tab(index, target);
+                        }
+
+                        Command::none()
+                    }
+                    tabs::Action::Close => self.close_tabs(vec![index]),
+                    tabs::Action::CloseOthers => {
+                        self.close_tabs((0..self.tabs.len()).filter(|i| *i != index).collect())
+                    }
+                    tabs::Action::CloseToRight => {
+                        self.close_tabs((index + 1..self.tabs.len()).collect())
+                    }
+                    tabs::Action::CopyPath => match self.tab_path(index) {
+                        Some(path) => iced::clipboard::write(path.display().to_string()),
+                        None => Command::none(),
+                    },
+                    tabs::Action::Reveal => {
+                        let folder = self
+                            .tab_path(index)
+                            .and_then(Path::parent)
+                            .map(Path::to_path_buf);
+
+                        if let Some(folder) = folder {
+                            if let Err(error) = open::that_detached(folder) {
+                                self.error = Some(Error::IOFailed(error.kind()));
+                            }
+                        }
+
+                        Command::none()
+                    }
+                    tabs::Action::Label => {
+                        if let Some(tab) = self.tabs.get(index) {
+                            let title = self.tab_labels().swap_remove(index).title;
+
+                            self.panel = Some(Panel::TabLabel(tabs::Prompt::new(tab, title)));
+                        }
+
+                        Command::none()
+                    }
+                }
+            }
+            Message::TabNameChanged(name) => {
+                if let Some(Panel::TabLabel(prompt)) = &mut self.panel {
+                    prompt.name = name;
+                }
+
+                Command::none()
+            }
+            Message::TabTagSelected(tag) => {
+                if let Some(Panel::TabLabel(prompt)) = &mut self.panel {
+                    prompt.tag = tag;
+                }
+
+                Command::none()
+            }
+            Message::ApplyTabLabel => {
+                let Some(Panel::TabLabel(prompt)) = self.panel.take() else {
+                    return Command::none();
+                };
+
+                if let Some(tab) = self.tabs.iter_mut().find(|tab| tab.id == prompt.id) {
+                    tab.name = prompt.name();
+                    tab.tag = prompt.tag;
+                }
+
+                Command::none()
+            }
+            Message::TabPressed(index) => {
+                self.tab_menu = None;
+                self.dragging = Some(index);
+
+                self.select_tab(index)
+            }
+            Message::TabDropped(index) => {
+                let tabs = self.tabs.len();
+
+                if let Some(from) = self.dragging.take().filter(|from| *from < tabs) {
+                    let pinned = self.tabs.iter().filter(|tab| tab.is_pinned).count();
+
+                    let to = if self.tabs[from].is_pinned {
+                        index.min(pinned - 1)
+                    } else {
+                        index.max(pinned)
+                    };
+
+                    self.move_tab(from, to);
+                }
+
+                Command::none()
+            }
+            Message::TabDragCancelled => {
+                self.dragging = None;
+
+                Command::none()
+            }
+            Message::CycleTabs(offset) => {
+                let count = self.tabs.len() as isize;
+
+                if count > 1 {
+                    let position = self.switcher.unwrap_or(0) as isize;
+
+                    self.switcher = Some((position + offset).rem_euclid(count) as usize);
+                }
+
+                Command::none()
+            }
+            Message::CompareClipboard => {
+                let selection = self
+                    .content
+                    .selection()
+                    .unwrap_or_else(|| self.content.text());
+
+                iced::clipboard::read(move |clipboard| {
+                    Message::ClipboardCompared(selection.clone(), clipboard)
+                })
+            }
+            Message::ClipboardCompared(selection, clipboard) => {
+                self.panel = Some(Panel::Diff(diff::Diff::compare(
+                    &selection,
+                    &clipboard.unwrap_or_default(),
+                )));
+
+                Command::none()
+            }
+            Message::DiffScrolled(viewport) => {
+                if let Some(Panel::Diff(diff)) = &mut self.panel {
+                    diff.scrolled(viewport);
+                }
+
+                Command::none()
+            }
+            Message::SearchScrolled(viewport) => {
+                if let Some(Panel::Search(search)) = &mut self.panel {
+                    search.scrolled(viewport);
+                }
+
+                Command::none()
+            }
+            Message::ProblemsScrolled(viewport) => {
+                if let Some(Panel::Problems(list)) = &mut self.panel {
+                    list.scrolled(viewport);
+                }
+
+                Command::none()
+            }
+            Message::TodosScrolled(viewport) => {
+                if let Some(Panel::Todos(list)) = &mut self.panel {
+                    list.scrolled(viewport);
+                }
+
+                Command::none()
+            }
+            Message::TableScrolled(viewport) => {
+                if let Some(table) = &mut self.table {
+                    table.scrolled(viewport);
+                }
+
+                Command::none()
+            }
+            Message::Generate(generator) => {
+                let text = generator.generate(self.config.date_format.as_deref());
+
+                self.update(Message::Edit(text_editor::Action::Edit(
+                    text_editor::Edit::Paste(Arc::new(text)),
+                )))
+            }
+            Message::Transform(transform, output) => {
+                let Some(selection) = self.content.selection() else {
+                    return Command::none();
+                };
+
+                let Some(result) = transform.apply(&selection) else {
+                    self.error = Some(Error::IOFailed(io::ErrorKind::InvalidData));
+                    return Command::none();
+                };
+
+                match output {
+                    transforms::Output::Replace => self.update(Message::Edit(
+                        text_editor::Action::Edit(text_editor::Edit::Paste(Arc::new(result))),
+                    )),
+                    transforms::Output::Copy => iced::clipboard::write(result),
+                    transforms::Output::Show => {
+                        self.toast = Some(toast::Toast::new(result));
+                        Command::none()
+                    }
+                }
+            }
+            Message::EvaluateSelection(output) => {
+                let Some(selection) = self.content.selection() else {
+                    return Command::none();
+                };
+
+                let result = match calc::evaluate(&selection) {
+                    Ok(result) => result,
+                    Err(error) => {
+                        self.toast = Some(toast::Toast::new(format!("Cannot evaluate: {error}")));
+                        return Command::none();
+                    }
+                };
+
+                match output {
+                    transforms::Output::Replace => {
+                        let text = format!("{} = {result}", selection.trim_end());
+
+                        self.update(Message::Edit(text_editor::Action::Edit(
+                            text_editor::Edit::Paste(Arc::new(text)),
+                        )))
+                    }
+                    transforms::Output::Copy => iced::clipboard::write(result),
+                    transforms::Output::Show => {
+                        self.toast = Some(toast::Toast::new(format!("= {result}")));
+                        Command::none()
+                    }
+                }
+            }
+            Message::ShowLineTool(tool) => {
+                if !self.is_read_only() {
+                    self.panel = Some(Panel::LineTool(transforms::Prompt::new(tool)));
+                }
+
+                Command::none()
+            }
+            Message::LineToolInputChanged(input) => {
+                if let Some(Panel::LineTool(prompt)) = &mut self.panel {
+                    prompt.input = input;
+                }
+
+                Command::none()
+            }
+            Message::SortNumericToggled(is_numeric) => {
+                if let Some(Panel::LineTool(prompt)) = &mut self.panel {
+                    prompt.is_numeric = is_numeric;
+                }
+
+                Command::none()
+            }
+            Message::SortDescendingToggled(is_descending) => {
+                if let Some(Panel::LineTool(prompt)) = &mut self.panel {
+                    prompt.is_descending = is_descending;
+                }
+
+                Command::none()
+            }
+            Message::ApplyLineTool => {
+                let Some(Panel::LineTool(prompt)) = &self.panel else {
+                    return Command::none();
+                };
+
+                if self.is_read_only() {
+                    return Command::none();
+                }
+
+                let text = self.content.text();
+                let lines = selected_lines(&self.content)
+                    .unwrap_or_else(|| paragraph(&self.content, self.content.cursor_position().0));
+                let range = line_offsets(&text, lines);
+
+                let Some(result) = prompt.apply(&text[range.clone()]) else {
+                    return Command::none();
+                };
+
+                self.panel = None;
+
+                if result == text[range.clone()] {
+                    return Command::none();
+                }
+
+                select_offsets(&mut self.content, &text, range);
+
+                self.update(Message::Edit(text_editor::Action::Edit(
+                    text_editor::Edit::Paste(Arc::new(result)),
+                )))
+            }
+            Message::ShowColumnTool(operation) => {
+                let delimiter = self
+                    .path
+                    .as_deref()
+                    .and_then(table::delimiter)
+                    .unwrap_or(b',');
+
+                self.panel = Some(Panel::Columns(table::Prompt::new(operation, delimiter)));
+
+                Command::none()
+            }
+            Message::ColumnDelimiterChanged(delimiter) => {
+                if let Some(Panel::Columns(prompt)) = &mut self.panel {
+                    prompt.delimiter = delimiter;
+                }
+
+                Command::none()
+            }
+            Message::ColumnChanged(column) => {
+                if let Some(Panel::Columns(prompt)) = &mut self.panel {
+                    prompt.column = column;
+                }
+
+                Command::none()
+            }
+            Message::OtherColumnChanged(column) => {
+                if let Some(Panel::Columns(prompt)) = &mut self.panel {
+                    prompt.other = column;
+                }
+
+                Command::none()
+            }
+            Message::ApplyColumnTool => {
+                let Some(Panel::Columns(prompt)) = &self.panel else {
+                    return Command::none();
+                };
+
+                let text = self.content.text();
+                let range = selected_lines(&self.content)
+                    .map_or(0..text.len(), |lines| line_offsets(&text, lines));
+
+                let outcome = match prompt.apply(&text[range.clone()]) {
+                    Some(Ok(outcome)) => outcome,
+                    Some(Err(error)) => {
+                        self.toast = Some(toast::Toast::new(format!(
+                            "Cannot read the columns: {error}"
+                        )));
+                        return Command::none();
+                    }
+                    None => return Command::none(),
+                };
+
+                let column = prompt.column.trim().to_string();
+
+                self.panel = None;
+
+                match outcome {
+                    table::Outcome::Replace(written) => {
+                        if self.is_read_only() || written == text[range.clone()] {
+                            return Command::none();
+                        }
+
+                        select_offsets(&mut self.content, &text, range);
+
+                        self.update(Message::Edit(text_editor::Action::Edit(
+                            text_editor::Edit::Paste(Arc::new(written)),
+                        )))
+                    }
+                    table::Outcome::Open(values) => {
+                        self.new_tab();
+                        self.content = text_editor::Content::with(&values);
+
+                        Command::none()
+                    }
+                    table::Outcome::Total {
+                        sum,
+                        values,
+                        skipped,
+                    } => {
+                        let mut message = format!(
+                            "Column {column}: {} from {values} values",
+                            calc::format_number(sum)
+                        );
+
+                        if skipped > 0 {
+                            message.push_str(&format!(", {skipped} non-numeric cells skipped"));
+                        }
+
+                        self.toast = Some(toast::Toast::new(message));
+
+                        Command::none()
+                    }
+                }
+            }
+            Message::ToggleCalculation => {
+                if let Some(Panel::Calculation(_)) = self.panel {
+                    self.panel = None;
+                } else {
+                    let mut sheet = calc::Sheet::default();
+
+                    sheet.refresh(&self.content.text());
+                    self.panel = Some(Panel::Calculation(sheet));
+                }
+
+                Command::none()
+            }
+            Message::CalculationScrolled(viewport) => {
+                if let Some(Panel::Calculation(sheet)) = &mut self.panel {
+                    sheet.scrolled(viewport);
+                }
+
+                Command::none()
+            }
+            Message::ExpandAbbreviation if self.focused() == focus::Region::Panel => {
+                if self.modifiers.shift() {
+                    iced::widget::focus_previous()
+                } else {
+                    iced::widget::focus_next()
+                }
+            }
+            Message::ExpandAbbreviation if self.completion.ghost.is_some() => {
+                self.accept_completion();
+
+                Command::none()
+            }
+            Message::ExpandAbbreviation => {
+                let Some(syntax) = self.path.as_deref().and_then(emmet::syntax) else {
+                    return Command::none();
+                };
+
+                if self.palette.is_some() || self.table.is_some() || self.tail.is_some() {
+                    return Command::none();
+                }
+
+                let (line, column) = self.content.cursor_position();
+
+                let Some(text) = self.content.line(line).map(|text| text.to_string()) else {
+                    return Command::none();
+                };
+
+                let Some(range) = emmet::abbreviation(&text, column) else {
+                    return Command::none();
+                };
+
+                let indent: String = text.chars().take_while(|c| c.is_whitespace()).collect();
+
+                let Some(expansion) = emmet::expand(&text[range.clone()], syntax, &indent) else {
+                    return Command::none();
+                };
+
+                select_range(&mut self.content, line, range.clone());
+
+                let command = self.update(Message::Edit(text_editor::Action::Edit(
+                    text_editor::Edit::Paste(Arc::new(expansion.text)),
+                )));
+
+                let (row, column) = expansion.cursor;
+
+                move_to_byte(
+                    &mut self.content,
+                    line + row,
+                    if row == 0 {
+                        range.start + column
+                    } else {
+                        column
+                    },
+                );
+
+                command
+            }
+            Message::ExpandSelection => {
+                let text = self.content.text();
+
+                let current = match self.expansions.last() {
+                    Some(range) => range.clone(),
+                    None => {
+                        let offset = cursor_offset(&self.content, &text);
+                        self.expansions.push(offset..offset);
+                        offset..offset
+                    }
+                };
+
+                let extension = self.path.as_deref().and_then(compression::extension);
+
+                if let Some(range) = scope::expand(&text, current, extension) {
+                    select_offsets(&mut self.content, &text, range.clone());
+                    self.expansions.push(range);
+                }
+
+                Command::none()
+            }
+            Message::ShrinkSelection => {
+                if self.expansions.len() > 1 {
+                    let text = self.content.text();
+
+                    self.expansions.pop();
+
+                    if let Some(range) = self.expansions.last().cloned() {
+                        select_offsets(&mut self.content, &text, range);
+                    }
+                }
+
+                Command::none()
+            }
+            Message::EditorMenu(is_open) => {
+                self.editor_menu = is_open;
+
+                Command::none()
+            }
+            Message::Cut => {
+                self.editor_menu = false;
+
+                match self.content.selection() {
+                    Some(selection) => Command::batch([
+                        iced::clipboard::write(selection),
+                        self.update(Message::Edit(text_editor::Action::Edit(
+                            text_editor::Edit::Delete,
+                        ))),
+                    ]),
+                    None => Command::none(),
+                }
+            }
+            Message::Copy => {
+                self.editor_menu = false;
+
+                match self.content.selection() {
+                    Some(selection) => iced::clipboard::write(selection),
+                    None => Command::none(),
+                }
+            }
+            Message::Paste => {
+                self.editor_menu = false;
+
+                iced::clipboard::read(Message::Pasted)
+            }
+            Message::Pasted(Some(text)) => self.update(Message::Edit(text_editor::Action::Edit(
+                text_editor::Edit::Paste(Arc::new(text)),
+            ))),
+            Message::Pasted(None) => Command::none(),
+            Message::ToggleTrayIcon => {
+                self.config.tray_icon = !self.config.tray_icon;
+
+                if self.config.tray_icon && self.tray.is_none() {
+                    self.tray = Some(tray::Tray::spawn(self.config.recent_workspaces.clone()));
+                }
+
+                Command::perform(self.config.clone().save(), Message::ConfigSaved)
+            }
+            Message::ToggleMinimizeToTray => {
+                self.config.minimize_to_tray = !self.config.minimize_to_tray;
+
+                Command::perform(self.config.clone().save(), Message::ConfigSaved)
+            }
+            Message::TrayPolled => {
+                let actions = self.tray.as_ref().map(tray::Tray::poll).unwrap_or_default();
+
+                Command::batch(actions.into_iter().map(|action| match action {
+                    tray::Action::Show => window::change_mode(window::Mode::Windowed),
+                    tray::Action::NewWindow => {
+                        if let Err(error) = std::env::current_exe()
+                            .and_then(|program| std::process::Command::new(program).spawn())
+                        {
+                            self.error = Some(Error::IOFailed(error.kind()));
+                        }
+
+                        Command::none()
+                    }
+                    tray::Action::OpenRecent(root) => Command::batch([
+                        window::change_mode(window::Mode::Windowed),
+                        self.update(Message::SwitchWorkspace(root)),
+                    ]),
+                    tray::Action::Quit => {
+                        self.remember_position();
+
+                        Command::perform(self.positions.clone().save(), |_| Message::Exit)
+                    }
+                }))
+            }
+            Message::QuickNotePolled => {
+                if !self
+                    .quick_note
+                    .as_ref()
+                    .is_some_and(hotkey::QuickNote::is_pressed)
+                {
+                    return Command::none();
+                }
+
+                Command::batch([
+                    window::change_mode(window::Mode::Windowed),
+                    window::gain_focus(),
+                    self.update(Message::OpenScratch(String::from(scratch::INBOX))),
+                ])
+            }
+            Message::ToggleSessions => {
+                if let Some(Panel::Sessions(_)) = self.panel {
+                    self.panel = None;
+
+                    Command::none()
+                } else {
+                    self.panel = Some(Panel::Sessions(session::Manager {
+                        name: self.session.clone().unwrap_or_default(),
+                        sessions: Vec::new(),
+                    }));
+
+                    Command::perform(session::list(), Message::SessionsListed)
+                }
+            }
+            Message::SessionsListed(Ok(sessions)) => {
+                if let Some(Panel::Sessions(manager)) = &mut self.panel {
+                    manager.sessions = sessions;
+                }
+
+                Command::none()
+            }
+            Message::SessionNameChanged(name) => {
+                if let Some(Panel::Sessions(manager)) = &mut self.panel {
+                    manager.name = name;
+                }
+
+                Command::none()
+            }
+            Message::SaveSession => match &self.panel {
+                Some(Panel::Sessions(manager)) => Command::perform(
+                    session::save(manager.name.clone(), self.current_session()),
+                    Message::SessionSaved,
+                ),
+                _ => Command::none(),
+            },
+            Message::SessionSaved(Ok(name)) => {
+                self.session = Some(name);
+
+                Command::perform(session::list(), Message::SessionsListed)
+            }
+            Message::SwitchSession(name) => {
+                Command::perform(session::load(name), Message::SessionLoaded)
+            }
+            Message::SessionLoaded(Ok((name, session))) => {
+                if let Some(Panel::Sessions(_)) = self.panel {
+                    self.panel = None;
+                }
+
+                self.session = Some(name);
+                self.focus_mode = session.focus_mode;
+
+                let clean = (0..self.tabs.len())
+                    .filter(|index| !self.has_unsaved_work(*index))
+                    .collect();
+                let close = self.close_tabs(clean);
+
+                let blank = (self.tabs.len() == 1
+                    && self.path.is_none()
+                    && !self.has_unsaved_work(self.active))
+                .then_some(self.active);
+
+                for path in &session.open_files {
+                    if self.tab_index(path).is_none() {
+                        self.tabs.push(tabs::Tab::unloaded(path.clone()));
+                    }
+                }
+
+                for label in session.labels {
+                    if let Some(index) = self.tab_index(&label.path) {
+                        self.tabs[index].name = label.name;
+                        self.tabs[index].tag = label.tag;
+                    }
+                }
+
+                let select = session
+                    .open_files
+                    .get(session.active)
+                    .or(session.open_files.first())
+                    .and_then(|path| self.tab_index(path))
+                    .map(|index| self.select_tab(index))
+                    .unwrap_or_else(Command::none);
+
+                let cleanup = blank
+                    .filter(|index| *index != self.active)
+                    .map(|index| self.close_tabs(vec![index]))
+                    .unwrap_or_else(Command::none);
+
+                let workspace = match session.workspace {
+                    Some(root)
+                        if self.workspace.as_ref().map(|workspace| &workspace.root)
+                            != Some(&root) =>
+                    {
+                        let show_tree = session.show_tree;
+
+                        Command::perform(workspace::Workspace::open(root), move |result| {
+                            Message::SessionWorkspaceOpened(show_tree, result)
+                        })
+                    }
+                    _ => Command::none(),
+                };
+
+                self.show_tree = session.show_tree && self.workspace.is_some();
+
+                Command::batch([close, select, cleanup, workspace])
+            }
+            Message::SessionWorkspaceOpened(show_tree, Ok(workspace)) => {
+                let command = self.set_workspace(workspace);
+                self.show_tree = show_tree;
+
+                command
+            }
+            Message::RenameSession(from) => match &self.panel {
+                Some(Panel::Sessions(manager)) => Command::perform(
+                    session::rename(from, manager.name.clone()),
+                    Message::SessionSaved,
+                ),
+                _ => Command::none(),
+            },
+            Message::DeleteSession(name) => {
+                if self.session.as_deref() == Some(name.as_str()) {
+                    self.session = None;
+                }
+
+                Command::perform(session::delete(name), Message::SessionDeleted)
+            }
+            Message::SessionDeleted(Ok(())) => {
+                Command::perform(session::list(), Message::SessionsListed)
+            }
+            Message::SessionsListed(Err(error))
+            | Message::SessionSaved(Err(error))
+            | Message::SessionLoaded(Err(error))
+            | Message::SessionWorkspaceOpened(_, Err(error))
+            | Message::SessionDeleted(Err(error)) => {
+                self.error = Some(error);
+                Command::none()
+            }
+            Message::ToggleSearch => {
+                if let Some(Panel::Search(_)) = self.panel {
+                    self.panel = None;
+                } else {
+                    self.panel = Some(Panel::Search(search::Search {
+                        query: self.content.selection().unwrap_or_default(),
+                        ..search::Search::default()
+                    }));
+                }
+
+                Command::none()
+            }
+            Message::SearchQueryChanged(query) => {
+                if let Some(Panel::Search(search)) = &mut self.panel {
+                    search.query = query;
+                }
+
+                Command::none()
+            }
+            Message::ReplacementChanged(replacement) => {
+                if let Some(Panel::Search(search)) = &mut self.panel {
+                    search.replacement = replacement;
+                }
+
+                Command::none()
+            }
+            Message::SearchRegexToggled(is_regex) => {
+                if let Some(Panel::Search(search)) = &mut self.panel {
+                    search.is_regex = is_regex;
+                }
+
+                Command::none()
+            }
+            Message::RunSearch => {
+                let folder = self.project_folder();
+                let index = self.indexer.index.clone();
+
+                let Some(Panel::Search(search)) = &mut self.panel else {
+                    return Command::none();
+                };
+
+                match (folder, search.pattern()) {
+                    (Some((root, filter)), Some(pattern)) => {
+                        search.is_searching = true;
+                        search.summary = None;
+
+                        // Regular expressions can't be narrowed by trigrams, so they read every file
+                        let candidates = index
+                            .filter(|_| !search.is_regex)
+                            .and_then(|index| index.candidates(&search.query));
+
+                        self.tasks.perform(
+                            tasks::Kind::Search,
+                            None,
+                            search::find(root, filter, pattern, candidates),
+                            Message::SearchFinished,
+                        )
+                    }
+                    _ => Command::none(),
+                }
+            }
+            Message::SearchFinished(groups) => {
+                if let Some(Panel::Search(search)) = &mut self.panel {
+                    search.groups = groups;
+                    search.is_searching = false;
+                }
+
+                Command::none()
+            }
+            Message::SearchFileToggled(index) => {
+                if let Some(Panel::Search(search)) = &mut self.panel {
+                    search.toggle_file(index);
+                }
+
+                Command::none()
+            }
+            Message::SearchMatchToggled(group, index) => {
+                if let Some(Panel::Search(search)) = &mut self.panel {
+                    search.toggle_match(group, index);
+                }
+
+                Command::none()
+            }
+            Message::SearchResultSelected(path, line) => {
+                self.update(Message::TodoSelected(Some(path), line))
+            }
+            Message::ApplyReplacements => {
+                let unsaved: Vec<_> = (0..self.tabs.len())
+                    .filter(|index| self.has_unsaved_work(*index))
+                    .filter_map(|index| self.tab_path(index).map(Path::to_path_buf))
+                    .collect();
+
+                let Some((root, _)) = self.project_folder() else {
+                    return Command::none();
+                };
+
+                let Some(Panel::Search(search)) = &mut self.panel else {
+                    return Command::none();
+                };
+
+                let Some(pattern) = search.pattern() else {
+                    return Command::none();
+                };
+
+                let (files, skipped): (Vec<_>, Vec<_>) = search
+                    .selected()
+                    .into_iter()
+                    .partition(|(path, _)| !unsaved.contains(path));
+
+                search.is_searching = true;
+                search.summary = (!skipped.is_empty())
+                    .then(|| format!("Skipping {} files with unsaved changes", skipped.len()));
+
+                Command::perform(
+                    search::replace(root, files, pattern, search.replacement.clone()),
+                    Message::ReplacementsApplied,
+                )
+            }
+            Message::ReplacementsApplied(Ok(summary)) => {
+                let touched = summary.paths.len();
+
+                for tab in &mut self.tabs {
+                    if let Some(buffer) = &mut tab.buffer {
+                        if buffer
+                            .path
+                            .as_ref()
+                            .is_some_and(|path| summary.paths.contains(path))
+                        {
+                            buffer.is_loaded = false;
+                        }
+                    }
+                }
+
+                if let Some(Panel::Search(search)) = &mut self.panel {
+                    let skipped = search.summary.take();
+
+                    search.is_searching = false;
+                    search.groups.clear();
+                    search.summary = Some(format!(
+                        "Replaced {} occurrences in {touched} files{}{}",
+                        summary.replacements,
+                        summary
+                            .backups
+                            .map(|backups| format!(", backups in {}", backups.display()))
+                            .unwrap_or_default(),
+                        skipped
+                            .map(|skipped| format!(". {skipped}"))
+                            .unwrap_or_default(),
+                    ));
+                }
+
+                match self.path.clone() {
+                    Some(path) if summary.paths.contains(&path) => self.load(path),
+                    _ => Command::none(),
+                }
+            }
+            Message::ReplacementsApplied(Err(error)) => {
+                if let Some(Panel::Search(search)) = &mut self.panel {
+                    search.is_searching = false;
+                    search.summary = Some(String::from(
+                        "Replacement failed, files already written were restored",
+                    ));
+                }
+
+                self.error = Some(error);
+                Command::none()
+            }
+            Message::ExportResults(export) => {
+                let Some(Panel::Search(search)) = &self.panel else {
+                    return Command::none();
+                };
+
+                let report = search.report(
+                    self.workspace
+                        .as_ref()
+                        .map(|workspace| workspace.root.as_path()),
+                );
+
+                match export {
+                    search::Export::Buffer => {
+                        self.new_tab();
+                        self.content = text_editor::Content::with(&report);
+
+                        Command::none()
+                    }
+                    search::Export::File => {
+                        Command::perform(search::export(report), Message::ResultsExported)
+                    }
+                }
+            }
+            Message::ResultsExported(result) => {
+                match result {
+                    Ok(path) => {
+                        if let Some(Panel::Search(search)) = &mut self.panel {
+                            search.summary = Some(format!("Exported to {}", path.display()));
+                        }
+                    }
+                    Err(error) => self.error = Some(error),
+                }
+
+                Command::none()
+            }
+            Message::CloseRequested => {
+                self.remember_position();
+
+                let save = self.positions.clone().save();
+
+                if self.tray.is_some() && self.config.minimize_to_tray {
+                    Command::batch([
+                        Command::perform(save, Message::PositionsSaved),
+                        window::change_mode(window::Mode::Hidden),
+                    ])
+                } else {
+                    Command::perform(save, |_| Message::Exit)
+                }
+            }
+            Message::Exit => window::close(),
+            Message::ShowProperties => match &self.path {
+                Some(path) => {
+                    self.panel = Some(Panel::Properties(None));
+
+                    Command::perform(properties::inspect(path.clone()), Message::PropertiesLoaded)
+                }
+                None => Command::none(),
+            },
+            Message::PropertiesLoaded(Ok(properties)) => {
+                if let Some(Panel::Properties(current)) = &mut self.panel {
+                    *current = Some(properties);
+                }
+
+                Command::none()
+            }
+            Message::PropertiesLoaded(Err(error)) => {
+                if let Some(Panel::Properties(_)) = self.panel {
+                    self.panel = None;
+                }
+
+                self.error = Some(error);
+                Command::none()
+            }
+            Message::CopyPath(path) => iced::clipboard::write(path),
+            Message::PositionsLoaded(Ok(positions)) => {
+                self.positions = positions;
+
+                // The first file may have finished loading before its position did
+                if self.content.cursor_position() == (0, 0) && self.pending_jump.is_none() {
+                    if let Some((line, column)) = self
+                        .path
+                        .as_deref()
+                        .and_then(|path| self.positions.get(path))
+                    {
+                        move_to(&mut self.content, line, column);
+                    }
+                }
+
+                Command::none()
+            }
+            Message::ToggleChecksums => {
+                self.config.record_checksums = !self.config.record_checksums;
+
+                if !self.config.record_checksums {
+                    self.changed_outside = false;
+                }
+
+                Command::perform(self.config.clone().save(), Message::ConfigSaved)
+            }
+            Message::ChecksumsLoaded(Ok(checksums)) => {
+                self.checksums = checksums;
+
+                // The first file may have finished loading before its checksum did
+                self.verify_checksum()
+            }
+            Message::ChecksumVerified(Ok((path, sha256))) => {
+                if self.path.as_ref() == Some(&path) {
+                    self.changed_outside = self
+                        .checksums
+                        .get(&path)
+                        .is_some_and(|recorded| recorded != sha256);
+                }
+
+                Command::none()
+            }
+            Message::ChecksumRecorded(Ok((path, sha256))) => {
+                if self.path.as_ref() == Some(&path) {
+                    self.changed_outside = false;
+                }
+
+                self.checksums.set(path, sha256);
+
+                Command::perform(self.checksums.clone().save(), Message::ChecksumsSaved)
+            }
+            Message::CopyChecksum => match self.path.clone() {
+                Some(path) => Command::perform(checksums::sha256(path), Message::ChecksumCopied),
+                None => Command::none(),
+            },
+            Message::ChecksumCopied(Ok((_path, sha256))) => iced::clipboard::write(sha256),
+            Message::ChecksumsLoaded(Err(error))
+            | Message::ChecksumVerified(Err(error))
+            | Message::ChecksumRecorded(Err(error))
+            | Message::ChecksumCopied(Err(error)) => {
+                self.error = Some(error);
+                Command::none()
+            }
+            Message::PositionsLoaded(Err(error)) => {
+                self.error = Some(error);
+                Command::none()
+            }
+            Message::PastePrimary(position) => match self.primary.get() {
+                Some(text) => {
+                    // Pasted where the button went down, as other apps with a primary selection do
+                    self.content.edit(text_editor::Action::Click(
+                        position - Vector::new(EDITOR_PADDING, EDITOR_PADDING),
+                    ));
+
+                    self.update(Message::Pasted(Some(text)))
+                }
+                None => Command::none(),
+            },
+            Message::SelectAll => {
+                self.editor_menu = false;
+
+                self.content.edit(text_editor::Action::Move(
+                    text_editor::Motion::DocumentStart,
+                ));
+                self.content.edit(text_editor::Action::Select(
+                    text_editor::Motion::DocumentEnd,
+                ));
+
+                Command::none()
+            }
+            Message::SearchWeb => {
+                self.editor_menu = false;
+
+                if let Some(selection) = self.content.selection() {
+                    let url = format!("{SEARCH_URL}{}", urlencoding::encode(selection.trim()));
+
+                    if let Err(error) = open::that_detached(url) {
+                        self.error = Some(Error::IOFailed(error.kind()));
+                    }
+                }
+
+                Command::none()
+            }
+            Message::PasteAndIndent => iced::clipboard::read(Message::PasteIndented),
+            Message::PasteIndented(Some(text)) => {
+                let text = self.reindent(&text);
+
+                self.update(Message::Edit(text_editor::Action::Edit(
+                    text_editor::Edit::Paste(Arc::new(text)),
+                )))
+            }
+            Message::PasteIndented(None) => Command::none(),
+            Message::PasteAsColumn => iced::clipboard::read(Message::ColumnPasted),
+            Message::ColumnPasted(Some(clipboard)) => {
+                let (line, column) = self.content.cursor_position();
+                let text = self.content.text();
+
+                let rows: Vec<&str> = clipboard
+                    .trim_end_matches(['\r', '\n'])
+                    .split('\n')
+                    .map(|row| row.trim_end_matches('\r'))
+                    .collect();
+
+                // The lines the rows land on, short of any that have to be added at the end
+                let lines = line_offsets(
+                    &text,
+                    line..(line + rows.len()).min(self.content.line_count()),
+                );
+                let block = rectangle(&text[lines.clone()], column, &rows);
+
+                select_offsets(&mut self.content, &text, lines);
+
+                self.update(Message::Edit(text_editor::Action::Edit(
+                    text_editor::Edit::Paste(Arc::new(block)),
+                )))
+            }
+            Message::ColumnPasted(None) => Command::none(),
+            Message::JoinLines => {
+                if self.is_read_only() {
+                    return Command::none();
+                }
+
+                let text = self.content.text();
+                let offset = cursor_offset(&self.content, &text);
+
+                let (start, end) = match self.content.selection() {
+                    Some(selection) if text[..offset].ends_with(selection.as_str()) => {
+                        (offset - selection.len(), offset)
+                    }
+                    Some(selection) => (offset, offset + selection.len()),
+                    None => (offset, offset),
+                };
+
+                let first = text[..start].matches('\n').count();
+                let last = text[..end].matches('\n').count().max(first + 1);
+
+                let line_start = text[..start].rfind('\n').map_or(0, |index| index + 1);
+                let line_end = text
+                    .split('\n')
+                    .take(last + 1)
+                    .map(|line| line.len() + 1)
+                    .sum::<usize>()
+                    .saturating_sub(1)
+                    .min(text.len());
+
+                if last >= self.content.line_count() {
+                    return Command::none();
+                }
+
+                let joined = lines::join(text[line_start..line_end].split('\n'));
+
+                select_offsets(&mut self.content, &text, line_start..line_end);
+
+                self.update(Message::Edit(text_editor::Action::Edit(
+                    text_editor::Edit::Paste(Arc::new(joined)),
+                )))
+            }
+            Message::ReflowParagraph => {
+                if self.is_read_only() {
+                    return Command::none();
+                }
+
+                let text = self.content.text();
+
+                let lines = selected_lines(&self.content)
+                    .unwrap_or_else(|| paragraph(&self.content, self.content.cursor_position().0));
+
+                let range = line_offsets(&text, lines);
+                let width = usize::from(self.config.wrap_column.unwrap_or(REFLOW_WIDTH));
+                let reflowed = lines::reflow(text[range.clone()].split('\n'), width);
+
+                if reflowed == text[range.clone()] {
+                    return Command::none();
+                }
+
+                select_offsets(&mut self.content, &text, range);
+
+                self.update(Message::Edit(text_editor::Action::Edit(
+                    text_editor::Edit::Paste(Arc::new(reflowed)),
+                )))
+            }
+            Message::ToggleInspector => {
+                self.inspect = !self.inspect;
+
+                Command::none()
+            }
+            Message::OpenUnicodePicker => {
+                self.panel = Some(Panel::Unicode(unicode::Picker::default()));
+
+                Command::none()
+            }
+            Message::UnicodeQueryChanged(query) => {
+                if let Some(Panel::Unicode(picker)) = &mut self.panel {
+                    picker.search(query);
+                }
+
+                Command::none()
+            }
+            Message::InsertCharacter(c) => self.update(Message::Edit(text_editor::Action::Edit(
+                text_editor::Edit::Insert(c),
+            ))),
+            Message::FindSuspicious => {
+                let text = self.content.text();
+                let offset = cursor_offset(&self.content, &text);
+                let offsets = suspicious::offsets(&text);
+
+                if let Some(start) = offsets
+                    .iter()
+                    .find(|start| **start > offset)
+                    .or(offsets.first())
+                {
+                    let end = start + text[*start..].chars().next().map_or(0, char::len_utf8);
+
+                    select_offsets(&mut self.content, &text, *start..end);
+                }
+
+                Command::none()
+            }
+            Message::StripSuspicious => {
+                if self.is_read_only() {
+                    return Command::none();
+                }
+
+                let text = self.content.text();
+                let stripped = suspicious::strip(&text);
+
+                if stripped != text {
+                    let (line, column) = self.content.cursor_position();
+
+                    self.content = text_editor::Content::with(&stripped);
+                    self.is_dirty = true;
+
+                    move_to(&mut self.content, line, column);
+
+                    if let Some(session) = &mut self.collab {
+                        session.local_edit(stripped);
+                    }
+                }
+
+                Command::none()
+            }
+            Message::ToggleScratch => {
+                if let Some(Panel::Scratch(_)) = self.panel {
+                    self.panel = None;
+
+                    Command::none()
+                } else {
+                    self.panel = Some(Panel::Scratch(scratch::Menu::default()));
+
+                    Command::perform(scratch::list(), Message::ScratchesListed)
+                }
+            }
+            Message::ScratchesListed(Ok(scratches)) => {
+                if let Some(Panel::Scratch(menu)) = &mut self.panel {
+                    menu.scratches = scratches;
+                }
+
+                Command::none()
+            }
+            Message::ScratchNameChanged(name) => {
+                if let Some(Panel::Scratch(menu)) = &mut self.panel {
+                    menu.name = name;
+                }
+
+                Command::none()
+            }
+            Message::CreateScratch => match &self.panel {
+                Some(Panel::Scratch(menu)) => {
+                    Command::perform(scratch::create(menu.name.clone()), Message::ScratchCreated)
+                }
+                _ => Command::none(),
+            },
+            Message::OpenScratch(name) => {
+                Command::perform(scratch::create(name), Message::ScratchCreated)
+            }
+            Message::ScratchCreated(Ok(path)) => {
+                self.panel = None;
+
+                self.open_in_tab(path)
+            }
+            Message::AutosaveScratch => match &self.path {
+                Some(path) if self.is_dirty && scratch::is_scratch(path) => Command::perform(
+                    scratch::save(path.clone(), self.content.text()),
+                    Message::ScratchSaved,
+                ),
+                _ => Command::none(),
+            },
+            Message::ScratchSaved(Ok(path)) => {
+                if self.path.as_ref() == Some(&path) {
+                    self.is_dirty = false;
+                } else if let Some(buffer) = self
+                    .tab_index(&path)
+                    .and_then(|index| self.tabs[index].buffer.as_mut())
+                {
+                    buffer.is_dirty = false;
+                }
+
+                Command::none()
+            }
+            Message::ScratchesListed(Err(error))
+            | Message::ScratchCreated(Err(error))
+            | Message::ScratchSaved(Err(error)) => {
+                self.error = Some(error);
+                Command::none()
+            }
+            Message::ShowStatistics => {
+                self.panel = Some(Panel::Statistics(None));
+
+                Command::perform(
+                    stats::count(Arc::new(self.content.text())),
+                    Message::StatisticsComputed,
+                )
+            }
+            Message::StatisticsComputed(statistics) => {
+                if let Some(Panel::Statistics(current)) = &mut self.panel {
+                    *current = Some(statistics);
+                }
+
+                Command::none()
+            }
+            Message::ToggleFocusMode => {
+                self.focus_mode = !self.focus_mode;
+
+                Command::none()
+            }
+            Message::ToggleTypewriter => {
+                self.config.typewriter_scrolling = !self.config.typewriter_scrolling;
+
+                if self.config.typewriter_scrolling && !self.reduces_motion() {
+                    self.center_cursor();
+                }
+
+                Command::perform(self.config.clone().save(), Message::ConfigSaved)
+            }
+            Message::WrapAt(column) => {
+                self.config.wrap_column = column;
+
+                Command::perform(self.config.clone().save(), Message::ConfigSaved)
+            }
+            Message::WindowResized(height) => {
+                self.window_height = height as f32;
+
+                Command::none()
+            }
+            Message::ToggleDimParagraphs => {
+                self.config.dim_paragraphs = !self.config.dim_paragraphs;
+
+                Command::perform(self.config.clone().save(), Message::ConfigSaved)
+            }
+            Message::FormatDocument => {
+                let (Some(workspace), Some(path)) = (&self.workspace, &self.path) else {
+                    return Command::none();
+                };
+
+                let Some(command) = workspace.formatter(path).map(str::to_string) else {
+                    return Command::none();
+                };
+
+                let root = workspace.root.clone();
+                let text = self.content.text();
+
+                self.perform_for_tab(
+                    tasks::Kind::Format,
+                    workspace::format(command, root, text),
+                    Message::Formatted,
+                )
+            }
+            Message::Formatted(Ok(text)) => {
+                let (line, column) = self.content.cursor_position();
+
+                self.content = text_editor::Content::with(&text);
+                self.is_dirty = true;
+
+                move_to(&mut self.content, line, column);
+
+                Command::none()
+            }
+            Message::Formatted(Err(error)) => {
+                self.error = Some(error);
+                Command::none()
+            }
+            Message::RemoteSaved(Ok(())) => {
+                self.is_dirty = false;
+                Command::none()
+            }
+            Message::RemoteSaved(Err(error)) => {
+                self.error = Some(error);
+                Command::none()
+            }
+        }
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        let tail = if self.tail.is_some() {
+            time::every(Duration::from_millis(500)).map(|_| Message::TailTick)
+        } else {
+            Subscription::none()
+        };
+
+        let tray = if self.tray.is_some() {
+            time::every(Duration::from_millis(250)).map(|_| Message::TrayPolled)
+        } else {
+            Subscription::none()
+        };
+
+        let quick_note = if self.quick_note.is_some() {
+            time::every(Duration::from_millis(200)).map(|_| Message::QuickNotePolled)
+        } else {
+            Subscription::none()
+        };
+
+        let speech = if self.speech.as_ref().is_some_and(speech::Reader::is_active) {
+            time::every(Duration::from_millis(200)).map(|_| Message::SpeechPolled)
+        } else {
+            Subscription::none()
+        };
+
+        // Ctrl+Enter is only taken over in SQL files and buffers tied to a database
+        let query = if !self.config.databases.is_empty()
+            && (self.is_sql() || self.database.is_some())
+            && self.focused() != focus::Region::Tree
+        {
+            keyboard::on_key_press(|key_code, modifiers| match key_code {
+                keyboard::KeyCode::Enter if modifiers.command() => Some(Message::RunQuery),
+                _ => None,
+            })
+        } else {
+            Subscription::none()
+        };
+
+        let autosave = if self.is_dirty && self.path.as_deref().is_some_and(scratch::is_scratch) {
+            time::every(Duration::from_secs(1)).map(|_| Message::AutosaveScratch)
+        } else {
+            Subscription::none()
+        };
+
+        let collab = match &self.collab {
+            Some(session) => session.subscription().map(Message::Collab),
+            None => Subscription::none(),
+        };
+
+        let indexer = match self.project_folder() {
+            Some((root, filter)) => indexer::subscription(root, filter).map(Message::Indexed),
+            None => Subscription::none(),
+        };
+
+        let completion = if self.completion.typed_at.is_some() {
+            time::every(Duration::from_millis(100)).map(|_| Message::CompletionTick)
+        } else {
+            Subscription::none()
+        };
+
+        let assistant = match &self.panel {
+            Some(Panel::Assistant(assistant)) => assistant.subscription().map(Message::Assistant),
+            _ => Subscription::none(),
+        };
+
+        let debugger = match &self.debug {
+            Some(session) => session.subscription().map(Message::Debug),
+            None => Subscription::none(),
+        };
+
+        let repls = Subscription::batch(
+            self.repls
+                .iter()
+                .filter(|repl| repl.is_running())
+                .map(|repl| repl.subscription().map(Message::ReplEvent)),
+        );
+
+        let kernel = match &self.notebook {
+            Some(notebook) => notebook.subscription().map(Message::Cells),
+            None => Subscription::none(),
+        };
+
+        let runner = match self.run.as_ref().filter(|run| run.is_running()) {
+            Some(run) => run.subscription().map(Message::TaskOutput),
+            None => Subscription::none(),
+        };
+
+        let toast = if self.toast.is_some() {
+            toast::subscription()
+        } else {
+            Subscription::none()
+        };
+
+        // Redraws while a file is slow to answer, so the status bar can say so
+        let storage = if self
+            .tasks
+            .is_running(&[tasks::Kind::Open, tasks::Kind::Save])
+        {
+            time::every(Duration::from_millis(250)).map(|_| Message::StorageTick)
+        } else {
+            Subscription::none()
+        };
+
+        let offline = if !self.offline.is_empty() && !self.offline.is_flushing {
+            time::every(offline::FLUSH_INTERVAL).map(|_| Message::FlushOffline)
+        } else {
+            Subscription::none()
+        };
+
+        let frames = if self.hud.is_some() {
+            window::frames().map(Message::FrameRendered)
+        } else {
+            Subscription::none()
+        };
+
+        let recompute = if self.pending.is_waiting() {
+            debounce::subscription()
+        } else {
+            Subscription::none()
+        };
+
+        let divider = if self.dividers.is_dragging() {
+            layout::subscription()
+        } else {
+            Subscription::none()
+        };
+
+        Subscription::batch([
+            tail,
+            autosave,
+            tray,
+            quick_note,
+            speech,
+            collab,
+            indexer,
+            completion,
+            recompute,
+            assistant,
+            divider,
+            frames,
+            runner,
+            debugger,
+            kernel,
+            repls,
+            toast,
+            storage,
+            themes::subscription(),
+            offline,
+            query,
+            keyboard::on_key_press(|key_code, modifiers| match key_code {
+                keyboard::KeyCode::S if modifiers.command() => Some(Message::Save),
+                keyboard::KeyCode::J if modifiers.command() => Some(Message::JoinLines),
+                keyboard::KeyCode::F11 => Some(Message::ToggleFocusMode),
+                keyboard::KeyCode::F9 => Some(Message::ToggleBreakpoint),
+                keyboard::KeyCode::F10 => Some(Message::DebugControl(debug::Control::StepOver)),
+                keyboard::KeyCode::F6 => {
+                    Some(Message::CycleFocus(if modifiers.shift() { -1 } else { 1 }))
+                }
+                keyboard::KeyCode::Enter => Some(Message::ActivateFocused),
+                keyboard::KeyCode::F if modifiers.command() && modifiers.shift() => {
+                    Some(Message::ToggleSearch)
+                }
+                keyboard::KeyCode::P if modifiers.command() && modifiers.shift() => {
+                    Some(Message::TogglePalette)
+                }
+                keyboard::KeyCode::Up => Some(Message::PaletteMoved(-1)),
+                keyboard::KeyCode::Down => Some(Message::PaletteMoved(1)),
+                keyboard::KeyCode::Escape => Some(Message::ClosePalette),
+                keyboard::KeyCode::Tab if modifiers.control() => {
+                    Some(Message::CycleTabs(if modifiers.shift() { -1 } else { 1 }))
+                }
+                keyboard::KeyCode::Tab => Some(Message::ExpandAbbreviation),
+                _ => None,
+            }),
+            subscription::events_with(|event, status| match event {
+                Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) => {
+                    Some(Message::ModifiersChanged(modifiers))
+                }
+                Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left))
+                    if status == event::Status::Ignored =>
+                {
+                    Some(Message::TabDragCancelled)
+                }
+                Event::Window(window::Event::Resized { height, .. }) => {
+                    Some(Message::WindowResized(height))
+                }
+                Event::Window(window::Event::CloseRequested) => Some(Message::CloseRequested),
+                _ => None,
+            }),
+        ])
+    }
+
+    fn view(&self) -> Element<'_, Self::Message> {
+        let _span = tracing::trace_span!("view").entered();
+        let _timer = self.hud.as_ref().map(perf::Hud::time_view);
+
+        let controls = Row::with_children(
+            self.config
+                .toolbar()
+                .iter()
+                .filter_map(|button| self.tool(*button))
+                .collect(),
+        )
+        .spacing(15);
+
+        let input: Element<_> = match (&self.merge, &self.image, &self.table) {
+            (Some(merge), _, _) => merge.view(),
+            (None, Some(image), _) => viewer::view(image),
+            (None, None, Some(table)) => table.view(self.window_height),
+            (None, None, None) => {
+                let syntax = highlighter::Settings {
+                    theme: self.theme,
+                    extension: if self.tail.is_some() {
+                        "log"
+                    } else {
+                        self.path
+                            .as_deref()
+                            .or(self.remote.as_ref().map(|session| session.location.path()))
+                            .and_then(compression::extension)
+                            .or(self.url.as_deref().and_then(web::extension))
+                            .unwrap_or("rs")
+                    }
+                    .to_string(),
+                };
+
+                let settings = highlight::Settings {
+                    syntax,
+                    scopes: self.active_theme().map(|custom| custom.scopes.clone()),
+                    focus: (self.focus_mode && self.config.dim_paragraphs)
+                        .then(|| paragraph(&self.content, self.content.cursor_position().0)),
+                    breakpoints: self
+                        .path
+                        .as_ref()
+                        .and_then(|path| self.breakpoints.get(path))
+                        .map(|lines| lines.iter().copied().collect())
+                        .unwrap_or_default(),
+                    stopped: self
+                        .debug
+                        .as_ref()
+                        .and_then(|session| session.location.as_ref())
+                        .filter(|(path, _)| self.path.as_ref() == Some(path))
+                        .map(|(_, line)| *line),
+                    problems: self
+                        .problems
+                        .iter()
+                        .filter(|problem| self.path.as_ref() == Some(&problem.path))
+                        .map(|problem| (problem.line, problem.column, problem.severity.color()))
+                        .collect(),
+                    swatches: self.has_colors(),
+                    peers: self
+                        .collab
+                        .as_ref()
+                        .map(collab::Session::highlights)
+                        .unwrap_or_default(),
+                };
+
+                let editor = match self.tail.as_ref().and_then(|tail| tail.filtered.as_ref()) {
+                    Some(filtered) => text_editor(filtered),
+                    None => text_editor(&self.content).on_edit(Message::Edit),
+                }
+                .padding(EDITOR_PADDING)
+                .highlight::<highlight::Highlighter>(settings, |highlight, _theme| {
+                    highlight.to_format()
+                });
+
+                let editor = match (self.active_theme(), self.config.is_see_through()) {
+                    (None, false) => editor,
+                    (custom, is_clear) => {
+                        editor.style(theme::TextEditor::Custom(Box::new(themes::Style {
+                            selection: custom.map(|custom| custom.selection),
+                            is_clear,
+                        })))
+                    }
+                };
+
+                // The editor wraps at its own width, so a fixed column is a width cap
+                let editor: Element<_> = match self.config.wrap_column {
+                    Some(column) => container(editor)
+                        .max_width(f32::from(column) * CHARACTER_WIDTH + 2.0 * EDITOR_PADDING)
+                        .into(),
+                    None => editor.into(),
+                };
+
+                let editor = mouse_area(primary::paste_area(editor, Message::PastePrimary))
+                    .on_right_press(Message::EditorMenu(true));
+
+                let editor: Element<_> = match &self.config.background_image {
+                    Some(path) => backdrop::view(
+                        editor,
+                        image::Handle::from_path(path),
+                        self.config.background_dim(),
+                    ),
+                    None => editor.into(),
+                };
+
+                match &self.tail {
+                    Some(tail) => column![tail.controls(), editor].spacing(10).into(),
+                    None => editor.into(),
+                }
+            }
+        };
+        let palette = self.palette.as_ref().map(|palette| {
+            let matches = palette
+                .matches(self.commands())
+                .into_iter()
+                .map(|(label, message)| (label, Message::PaletteRun(Box::new(message))))
+                .collect();
+
+            palette.view(matches)
+        });
+
+        if self.focus_mode {
+            let text_column = container(input).max_width(f32::from(self.config.focus_width()));
+
+            let content = match palette {
+                Some(palette) => column![palette, text_column].spacing(10),
+                None => column![text_column],
+            };
+
+            return container(content)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .padding(10)
+                .center_x()
+                .into();
+        }
+
+        let status_bar = {
+            let file = if let Some(error) = self.error.as_ref().and_then(Error::message) {
+                text(error)
+            } else if let Some(url) = &self.url {
+                text(format!("{url} (read-only)")).size(14)
+            } else if let Some(session) = &self.remote {
+                text(session.location.to_string()).size(14)
+            } else {
+                match self.path.as_deref() {
+                    Some(path) if self.link.is_some() => text(format!(
+                        "{} → {}",
+                        self.link.as_deref().unwrap_or(path).display(),
+                        path.display()
+                    ))
+                    .size(14),
+                    Some(path) => match compression::Format::detect(path)
+                        .map(compression::Format::name)
+                        .or(encryption::Format::detect(path).map(encryption::Format::name))
+                    {
+                        Some(format) => text(format!("{} ({format})", path.display())),
+                        None => text(path.display()),
+                    }
+                    .size(14),
+                    None => text("New file"),
+                }
+            };
+            let (line, column) = self.content.cursor_position();
+            let position = match &self.image {
+                Some(image) => text(image.status()),
+                None => text(format!("{}:{}", line + 1, column + 1)),
+            };
+
+            let mut status_bar = status::Bar::default();
+            status_bar.register(status::Slot::File, file, None);
+
+            if let Some(branch) = &self.branch {
+                status_bar.register(
+                    status::Slot::Branch,
+                    text(format!("⎇ {branch}")).size(14),
+                    None,
+                );
+            }
+
+            if self.image.is_none() {
+                status_bar.register(
+                    status::Slot::Encoding,
+                    text(self.bom.map_or("UTF-8", bom::Bom::name)).size(14),
+                    self.path.is_some().then_some(Message::ShowProperties),
+                );
+            }
+
+            if self.image.is_none() && self.table.is_none() {
+                status_bar.register(
+                    status::Slot::LineEnding,
+                    text(self.line_ending.name()).size(14),
+                    Some(Message::ToggleLineEnding),
+                );
+            }
+
+            if self.link.is_some() && self.url.is_none() && self.remote.is_none() {
+                status_bar.register(
+                    status::Slot::Link,
+                    checkbox(
+                        "Save replaces the link",
+                        self.replace_link,
+                        Message::ReplaceLinkToggled,
+                    )
+                    .size(14)
+                    .text_size(14),
+                    None,
+                );
+            }
+
+            if self.offer_lossy.is_some() {
+                status_bar.register(
+                    status::Slot::Lossy,
+                    button(text("Open with replacement markers").size(14))
+                        .on_press(Message::OpenLossy)
+                        .padding([0, 5]),
+                    None,
+                );
+            }
+
+            if let Some(lossy) = &self.lossy {
+                status_bar.register(
+                    status::Slot::Lossy,
+                    text(format!(
+                        "{} invalid UTF-8 sequences kept as {}",
+                        lossy.invalid(),
+                        lossy::MARKER
+                    ))
+                    .size(14)
+                    .style(suspicious::WARNING_COLOR),
+                    None,
+                );
+            }
+
+            if self.vault.is_some() {
+                status_bar.register(status::Slot::Encryption, text("Encrypted").size(14), None);
+            }
+
+            if self.changed_outside {
+                status_bar.register(
+                    status::Slot::ChangedOutside,
+                    text("Changed outside Bonkitor since it was last saved")
+                        .size(14)
+                        .style(suspicious::WARNING_COLOR),
+                    None,
+                );
+            }
+
+            if self.is_read_only {
+                status_bar.register(
+                    status::Slot::ReadOnly,
+                    row![
+                        text("Open in another editor, read-only")
+                            .size(14)
+                            .style(suspicious::WARNING_COLOR),
+                        button(text("Edit anyway").size(14))
+                            .on_press(Message::EditAnyway)
+                            .padding([0, 5])
+                            .style(theme::Button::Secondary),
+                    ]
+                    .spacing(10),
+                    None,
+                );
+            }
+
+            let stalled = self
+                .tasks
+                .stalled(&[tasks::Kind::Open, tasks::Kind::Save], offline::STALL);
+
+            match stalled {
+                Some(kind) => status_bar.register(
+                    status::Slot::Storage,
+                    text(if kind == tasks::Kind::Save {
+                        "Still saving…"
+                    } else {
+                        "Still opening…"
+                    })
+                    .size(14),
+                    None,
+                ),
+                None if !self.offline.is_empty() => status_bar.register(
+                    status::Slot::Storage,
+                    text(match self.offline.len() {
+                        1 => String::from("1 save waiting for its file"),
+                        count => format!("{count} saves waiting for their files"),
+                    })
+                    .size(14),
+                    Some(Message::FlushOffline),
+                ),
+                None => {}
+            }
+
+            if self.save_denied {
+                status_bar.register(
+                    status::Slot::SaveDenied,
+                    row![
+                        button(text("Retry with elevated privileges").size(14))
+                            .on_press(Message::SaveElevated)
+                            .padding([0, 5]),
+                        button(text("Save a copy elsewhere").size(14))
+                            .on_press(Message::SaveCopy)
+                            .padding([0, 5])
+                            .style(theme::Button::Secondary),
+                    ]
+                    .spacing(10),
+                    None,
+                );
+            }
+
+            if self
+                .content
+                .line(line)
+                .is_some_and(|text| !suspicious::find(&text).is_empty())
+            {
+                status_bar.register(
+                    status::Slot::Suspicious,
+                    text("Suspicious characters on this line")
+                        .size(14)
+                        .style(suspicious::WARNING_COLOR),
+                    Some(Message::FindSuspicious),
+                );
+            }
+
+            if let Some(swatch) = self
+                .content
+                .line(line)
+                .filter(|_| self.has_colors())
+                .and_then(|text| colors::at(&text, column))
+            {
+                status_bar.register(
+                    status::Slot::Color,
+                    colors::swatch(swatch.color, Message::PickColor),
+                    None,
+                );
+            }
+
+            if let Some(session) = &self.collab {
+                status_bar.register(status::Slot::Collab, session.status(), None);
+            }
+
+            if self.inspect {
+                if let Some(c) = self
+                    .content
+                    .line(line)
+                    .and_then(|text| text.chars().nth(column))
+                {
+                    status_bar.register(
+                        status::Slot::Character,
+                        text(unicode::describe(c)).size(14),
+                        None,
+                    );
+                }
+            }
+
+            if let Some(reader) = self.speech.as_ref().filter(|reader| reader.is_active()) {
+                status_bar.register(
+                    status::Slot::Speech,
+                    reader.view(
+                        self.config.speech_rate(),
+                        self.config.speech_voice.as_deref(),
+                    ),
+                    None,
+                );
+            }
+
+            if let Some(goal) = self.path.as_deref().and_then(|path| self.goals.goal(path)) {
+                status_bar.register(
+                    status::Slot::Goal,
+                    row![
+                        progress_bar(0.0..=goal as f32, self.words as f32)
+                            .width(100)
+                            .height(8),
+                        text(format!("{}/{goal} words", self.words)).size(14),
+                    ]
+                    .spacing(5),
+                    Some(Message::ToggleWritingGoals),
+                );
+            }
+
+            if let Some(language) = self.path.as_deref().and_then(status::language) {
+                status_bar.register(
+                    status::Slot::Language,
+                    text(language).size(14),
+                    Some(Message::ShowProperties),
+                );
+            }
+
+            if self.is_rest() {
+                status_bar.register(
+                    status::Slot::Request,
+                    text("Send request").size(14),
+                    Some(Message::SendRequest),
+                );
+            }
+
+            if let Some(ghost) = &self.completion.ghost {
+                status_bar.register(
+                    status::Slot::Completion,
+                    text(format!("Tab to insert: {}", ghost.text))
+                        .size(14)
+                        .style(completion::GHOST_COLOR),
+                    Some(Message::ExpandAbbreviation),
+                );
+            }
+
+            let here: Vec<_> = self
+                .problems
+                .iter()
+                .filter(|problem| self.path.as_ref() == Some(&problem.path))
+                .collect();
+
+            if !here.is_empty() {
+                let count = |severity| {
+                    here.iter()
+                        .filter(|problem| problem.severity == severity)
+                        .count()
+                };
+
+                status_bar.register(
+                    status::Slot::Problems,
+                    text(format!(
+                        "{} errors, {} warnings",
+                        count(problems::Severity::Error),
+                        count(problems::Severity::Warning)
+                    ))
+                    .size(14),
+                    Some(Message::ShowProblems),
+                );
+            }
+
+            if let Some(commit) = self
+                .blame
+                .as_ref()
+                .filter(|blame| self.config.inline_blame && self.path.as_ref() == Some(&blame.path))
+                .and_then(|blame| blame.line(line))
+            {
+                status_bar.register(
+                    status::Slot::Blame,
+                    text(commit.annotation())
+                        .size(13)
+                        .style(iced::Color::from_rgb(0.55, 0.55, 0.55)),
+                    commit
+                        .is_committed()
+                        .then(|| Message::ShowCommit(commit.id.clone())),
+                );
+            }
+
+            if let Some(status) = self.indexer.status() {
+                status_bar.register(status::Slot::Index, text(status).size(14), None);
+            }
+
+            if let Some(hud) = &self.hud {
+                status_bar.register(
+                    status::Slot::Performance,
+                    hud.view(self.content.line_count()),
+                    Some(Message::TogglePerformanceHud),
+                );
+            }
+
+            // The editor can't mark its continuation rows, so the cursor's line is counted here
+            if let Some(rows) = self
+                .config
+                .wrap_column
+                .zip(self.content.line(line))
+                .map(|(column, text)| lines::rows(&text.to_string(), usize::from(column)))
+                .filter(|rows| *rows > 1)
+            {
+                status_bar.register(
+                    status::Slot::Wrap,
+                    text(format!("Wrapped, {rows} rows"))
+                        .size(13)
+                        .style(iced::Color::from_rgb(0.55, 0.55, 0.55)),
+                    None,
+                );
+            }
+
+            status_bar.register(
+                status::Slot::Position,
+                position,
+                self.image.is_none().then_some(Message::ToggleInspector),
+            );
+
+            status_bar.view(&self.config)
+        };
+        let panel: Option<Element<_>> = match &self.panel {
+            Some(Panel::Todos(list)) => Some(list.view(self.scan_folder, self.window_height)),
+            Some(Panel::Swatch(picker)) => Some(colors::view(picker)),
+            Some(Panel::Remote(dialog)) => Some(dialog.view()),
+            Some(Panel::Sync(panel)) => Some(panel.view()),
+            Some(Panel::Collab(dialog)) => Some(dialog.view()),
+            Some(Panel::Diff(diff)) => Some(diff.view(self.window_height)),
+            Some(Panel::Unicode(picker)) => Some(picker.view()),
+            Some(Panel::Scratch(menu)) => Some(menu.view()),
+            Some(Panel::Statistics(Some(statistics))) => Some(statistics.view()),
+            Some(Panel::Sessions(manager)) => Some(manager.view(self.session.as_deref())),
+            Some(Panel::Search(search)) => Some(
+                search.view(
+                    self.workspace
+                        .as_ref()
+                        .map(|workspace| workspace.root.as_path()),
+                    self.window_height,
+                ),
+            ),
+            Some(Panel::Properties(Some(properties))) => Some(properties.view()),
+            Some(Panel::Properties(None)) => Some(properties::loading()),
+            Some(Panel::Statistics(None)) => Some(stats::computing()),
+            Some(Panel::Conflict(path)) => Some(conflict::view(path)),
+            Some(Panel::History(history)) => Some(history.view(self.window_height)),
+            Some(Panel::Patch(report)) => Some(report.view()),
+            Some(Panel::Filter(prompt)) => Some(prompt.view()),
+            Some(Panel::LineTool(prompt)) => Some(prompt.view()),
+            Some(Panel::Columns(prompt)) => Some(prompt.view()),
+            Some(Panel::TabLabel(prompt)) => Some(prompt.view()),
+            Some(Panel::ThemeEditor(draft)) => Some(draft.view()),
+            Some(Panel::Output) => self.run.as_ref().map(|run| run.view(self.window_height)),
+            Some(Panel::Debug) => self.debug.as_ref().map(debug::Session::view),
+            Some(Panel::Response(Some(response))) => Some(response.view()),
+            Some(Panel::Response(None)) => Some(rest::sending()),
+            Some(Panel::Query(Some(results))) => Some(results.view()),
+            Some(Panel::Cells) => self.notebook.as_ref().map(cells::Notebook::view),
+            Some(Panel::Pattern(tester)) => Some(tester.view()),
+            Some(Panel::Repl) => Some(repl::view(
+                &self.repls,
+                self.active_repl,
+                self.window_height,
+            )),
+            Some(Panel::Query(None)) => Some(database::running(
+                self.database.as_deref().unwrap_or("Database"),
+            )),
+            Some(Panel::Problems(list)) => Some(
+                list.view(
+                    &self.problems,
+                    self.workspace
+                        .as_ref()
+                        .map(|workspace| workspace.root.as_path()),
+                    self.window_height,
+                ),
+            ),
+            Some(Panel::Changes(Some(changes))) => {
+                Some(changes.view(self.content.cursor_position().0))
+            }
+            Some(Panel::Calculation(sheet)) => Some(sheet.view(&self.content, self.window_height)),
+            Some(Panel::Blame(gutter)) => {
+                Some(gutter.view(self.blame.as_ref(), &self.content, self.window_height))
+            }
+            Some(Panel::Memory) => Some(memory::view(
+                self.memory_usage(),
+                self.config.memory_budget(),
+            )),
+            Some(Panel::Preview(Some(preview))) => Some(preview.view()),
+            Some(Panel::Preview(None)) => Some(preview::rendering()),
+            Some(Panel::Outline(headings)) => Some(toc::view(headings)),
+            Some(Panel::Colors(swatches)) => Some(colors::overview(swatches)),
+            Some(Panel::Goals(dialog)) => {
+                Some(dialog.view(&self.goals, self.path.as_deref(), self.words))
+            }
+            Some(Panel::Passphrase(prompt)) => Some(prompt.view()),
+            Some(Panel::StatusBar) => Some(status::settings(&self.config)),
+            Some(Panel::Toolbar) => Some(toolbar::settings(&self.config)),
+            Some(Panel::About) => Some(about::view(&self.renderer)),
+            Some(Panel::Secrets(warning, _)) => Some(warning.view(self.path.is_some())),
+            Some(Panel::Assistant(assistant)) => Some(assistant.view(
+                self.config.assistant_endpoint.is_some(),
+                !self.is_read_only(),
+                self.reduces_motion(),
+            )),
+            None => None,
+        };
+        let input = focus::ring(input, self.shows_focus(focus::Region::Editor));
+        let body: Element<_> = match panel {
+            Some(panel) => row![
+                input,
+                layout::region(
+                    focus::ring(panel, self.shows_focus(focus::Region::Panel)),
+                    layout::Divider::Panel,
+                    self.sizes()
+                )
+            ]
+            .into(),
+            None => input,
+        };
+        let body = match self.tree.as_ref().filter(|_| self.show_tree) {
+            Some(tree) => row![
+                layout::region(
+                    focus::ring(
+                        tree.view(self.path.as_deref()),
+                        self.shows_focus(focus::Region::Tree)
+                    ),
+                    layout::Divider::Tree,
+                    self.sizes()
+                ),
+                body
+            ]
+            .into(),
+            None => body,
+        };
+        let mut content = column![menu::bar(self.menu)].spacing(10);
+
+        if let Some(menu) = self.menu {
+            content = content.push(menu::dropdown(menu, self.menu_items(menu)));
+        }
+
+        let mut content = content.push(controls).push(tabs::view(
+            self.tab_labels(),
+            self.active,
+            self.tab_menu,
+            self.dragging,
+        ));
+
+        if self.editor_menu {
+            let has_selection = self.content.selection().is_some();
+            let is_editable = self.url.is_none();
+
+            content = content.push(widgets::context_menu(
+                vec![
+                    (
+                        "Cut",
+                        (has_selection && is_editable).then_some(Message::Cut),
+                    ),
+                    ("Copy", has_selection.then_some(Message::Copy)),
+                    ("Paste", is_editable.then_some(Message::Paste)),
+                    ("Select All", Some(Message::SelectAll)),
+                    ("Go to Definition", None),
+                    (
+                        "Search the Web for Selection",
+                        has_selection.then_some(Message::SearchWeb),
+                    ),
+                ],
+                Message::EditorMenu(false),
+            ));
+        }
+
+        if let Some(selected) = self.switcher {
+            let labels = self.tab_labels();
+            let recent = self
+                .recent_tabs()
+                .into_iter()
+                .map(|index| labels[index].clone())
+                .collect();
+
+            content = content.push(tabs::switcher(recent, selected));
+        }
+
+        if let Some(palette) = palette {
+            content = content.push(palette);
+        }
+
+        let mut content = content.push(body);
+
+        if let Some(toast) = &self.toast {
+            content = content.push(toast.view());
+        }
+
+        container(content.push(status_bar)).padding(10).into()
+    }
+
+    fn theme(&self) -> Theme {
+        if let Some(custom) = self.active_theme() {
+            custom.to_iced()
+        } else if self.theme.is_dark() {
+            Theme::Dark
+        } else {
+            Theme::Light
+        }
+    }
+
+    fn style(&self) -> theme::Application {
+        match self.config.window_opacity() {
+            opacity if opacity < 1.0 => {
+                theme::Application::Custom(Box::new(backdrop::Translucent(opacity)))
+            }
+            _ => theme::Application::Default,
+        }
+    }
+}
+
+impl Editor {
+    fn center_cursor(&mut self) {
+        // The editor does not expose its viewport, so we rewind to the top and
+        // scroll down by half a screen less than the cursor line
+        let chrome = if self.focus_mode {
+            FOCUS_CHROME_HEIGHT
+        } else {
+            CHROME_HEIGHT
+        };
+        let rows = ((self.window_height - chrome).max(0.0) / LINE_HEIGHT) as i32;
+        let (line, _) = self.content.cursor_position();
+
+        self.content.edit(text_editor::Action::Scroll {
+            lines: -(self.content.line_count() as i32),
+        });
+        self.content.edit(text_editor::Action::Scroll {
+            lines: (line as i32 - rows / 2).max(0),
+        });
+    }
+
+    fn move_by_subword(&mut self, action: &text_editor::Action) -> bool {
+        let (line, column) = self.content.cursor_position();
+
+        let Some(text) = self.content.line(line).map(|text| text.to_string()) else {
+            return false;
+        };
+
+        let (select, forward) = match action {
+            text_editor::Action::Move(text_editor::Motion::WordLeft) => (false, false),
+            text_editor::Action::Move(text_editor::Motion::WordRight) => (false, true),
+            text_editor::Action::Select(text_editor::Motion::WordLeft) => (true, false),
+            text_editor::Action::Select(text_editor::Motion::WordRight) => (true, true),
+            text_editor::Action::SelectWord => {
+                select_range(&mut self.content, line, words::around(&text, column));
+
+                return true;
+            }
+            _ => return false,
+        };
+
+        let (count, motion) = if forward {
+            (
+                words::next(&text, column, true) - column,
+                text_editor::Motion::Right,
+            )
+        } else {
+            (
+                column - words::previous(&text, column, true),
+                text_editor::Motion::Left,
+            )
+        };
+
+        if count == 0 {
+            return false;
+        }
+
+        for _ in 0..count {
+            self.content.edit(if select {
+                text_editor::Action::Select(motion)
+            } else {
+                text_editor::Action::Move(motion)
+            });
+        }
+
+        true
+    }
+
+    fn focus_regions(&self) -> Vec<focus::Region> {
+        let mut regions = Vec::new();
+
+        let sizes = self.sizes();
+
+        if self.show_tree && self.tree.is_some() && !sizes.is_collapsed(layout::Divider::Tree) {
+            regions.push(focus::Region::Tree);
+        }
+
+        regions.push(focus::Region::Editor);
+
+        if self.panel.is_some() && !sizes.is_collapsed(layout::Divider::Panel) {
+            regions.push(focus::Region::Panel);
+        }
+
+        regions
+    }
+
+    fn has_colors(&self) -> bool {
+        self.path.as_deref().is_some_and(colors::supports)
+    }
+
+    /// The text a save writes: the table when one is open, otherwise the buffer with the
+    /// file's own line endings.
+    fn saved_text(&self) -> String {
+        match &self.table {
+            Some(table) => table.to_text(),
+            None => self.line_ending.apply(self.content.text()),
+        }
+    }
+
+    fn is_sql(&self) -> bool {
+        self.path
+            .as_deref()
+            .and_then(compression::extension)
+            .is_some_and(database::is_sql)
+    }
+
+    /// The focused region, falling back to the editor once the focused one is gone.
+    fn focused(&self) -> focus::Region {
+        if self.focus_regions().contains(&self.focus) {
+            self.focus
+        } else {
+            focus::Region::Editor
+        }
+    }
+
+    fn shows_focus(&self, region: focus::Region) -> bool {
+        self.focus_visible && self.focused() == region
+    }
+
+    fn queue_save(&mut self, entry: offline::Entry) -> Command<Message> {
+        let replaced = self.offline.push(entry);
+
+        Command::perform(
+            self.offline.clone().save(replaced.into_iter().collect()),
+            Message::OfflineQueueSaved,
+        )
+    }
+
+    /// Forgets a queued save once a newer one has reached the file.
+    fn unqueue_save(&mut self, path: &Path) -> Command<Message> {
+        match self.offline.remove(path) {
+            Some(entry) => Command::perform(
+                self.offline.clone().save(vec![entry]),
+                Message::OfflineQueueSaved,
+            ),
+            None => Command::none(),
+        }
+    }
+
+    fn read_modified(&self) -> Command<Message> {
+        match self.path.clone() {
+            Some(path) if self.remote.is_none() && self.url.is_none() => {
+                Command::perform(conflict::modified(path), Message::ModifiedRead)
+            }
+            _ => Command::none(),
+        }
+    }
+
+    fn read_changes(&self) -> Command<Message> {
+        match self.path.clone() {
+            Some(path) if self.remote.is_none() && self.url.is_none() => Command::perform(
+                git::changes(path, self.content.text()),
+                Message::ChangesRead,
+            ),
+            _ => Command::none(),
+        }
+    }
+
+    /// Blames the buffer when the status bar or the gutter panel will show it.
+    fn read_blame(&mut self) -> Command<Message> {
+        let is_wanted = self.config.inline_blame || matches!(self.panel, Some(Panel::Blame(_)));
+
+        match self.path.clone() {
+            Some(path) if is_wanted && self.remote.is_none() && self.url.is_none() => self
+                .perform_for_tab(
+                    tasks::Kind::Blame,
+                    git::blame(path, self.content.text()),
+                    Message::BlameLoaded,
+                ),
+            _ => Command::none(),
+        }
+    }
+
+    fn verify_checksum(&self) -> Command<Message> {
+        match self.path.clone() {
+            Some(path) if self.config.record_checksums && self.checksums.get(&path).is_some() => {
+                Command::perform(checksums::sha256(path), Message::ChecksumVerified)
+            }
+            _ => Command::none(),
+        }
+    }
+
+    fn ignores_secrets(&self) -> bool {
+        self.path
+            .as_ref()
+            .is_some_and(|path| self.config.secrets_ignored.contains(path))
+    }
+
+    /// Shows a warning instead of carrying on when the text looks like it holds credentials.
+    fn warn_of_secrets(&mut self, text: &str, then: Message, action: &'static str) -> bool {
+        if self.secrets_confirmed || self.ignores_secrets() {
+            return false;
+        }
+
+        let findings = secrets::scan(text);
+
+        if findings.is_empty() {
+            return false;
+        }
+
+        let previous = self.panel.take().map(Box::new);
+
+        self.panel = Some(Panel::Secrets(
+            secrets::Warning {
+                findings,
+                then,
+                action,
+            },
+            previous,
+        ));
+
+        true
+    }
+
+    fn accept_completion(&mut self) {
+        let Some(ghost) = self.completion.ghost.take() else {
+            return;
+        };
+
+        if self.content.cursor_position() != (ghost.line, ghost.column) {
+            return;
+        }
+
+        self.content
+            .edit(text_editor::Action::Edit(text_editor::Edit::Paste(
+                Arc::new(ghost.text),
+            )));
+
+        if let Some(session) = &mut self.collab {
+            session.local_edit(self.content.text());
+        }
+
+        self.is_dirty = true;
+        self.count_words();
+    }
+
+    fn dismiss_completion(&mut self) {
+        self.completion.ghost = None;
+    }
+
+    fn prose(&mut self, action: text_editor::Action) -> (text_editor::Action, Option<usize>) {
+        let text_editor::Action::Edit(edit) = &action else {
+            return (action, None);
+        };
+
+        let (line, column) = self.content.cursor_position();
+        let current = self
+            .content
+            .line(line)
+            .map(|text| text.to_string())
+            .unwrap_or_default();
+        let before: String = current.chars().take(column).collect();
+
+        let edit = match edit {
+            text_editor::Edit::Insert(quote @ ('"' | '\'')) => {
+                text_editor::Edit::Insert(prose::curly(*quote, before.chars().last()))
+            }
+            text_editor::Edit::Insert('-')
+                if before.ends_with('-')
+                    && before.trim() != "-"
+                    && !current.contains('|')
+                    && self.content.selection().is_none() =>
+            {
+                self.content
+                    .edit(text_editor::Action::Edit(text_editor::Edit::Backspace));
+
+                text_editor::Edit::Insert('—')
+            }
+            text_editor::Edit::Enter if self.content.selection().is_none() => {
+                let Some(item) = prose::item(&current).filter(|item| before.len() >= item.prefix)
+                else {
+                    return (action, None);
+                };
+
+                // Enter on an empty item ends the list
+                if item.is_empty {
+                    select_range(&mut self.content, line, 0..current.len());
+
+                    return (text_editor::Action::Edit(text_editor::Edit::Delete), None);
+                }
+
+                return (
+                    text_editor::Action::Edit(text_editor::Edit::Paste(Arc::new(format!(
+                        "\n{}",
+                        item.next()
+                    )))),
+                    item.is_ordered().then_some(line + 1),
+                );
+            }
+            _ => return (action, None),
+        };
+
+        (text_editor::Action::Edit(edit), None)
+    }
+
+    fn renumber_list(&mut self, line: usize) {
+        let lines: Vec<String> = (line..)
+            .map_while(|index| self.content.line(index).map(|text| text.to_string()))
+            .take_while(|text| !text.trim().is_empty())
+            .collect();
+
+        let edits = prose::renumber(&lines.iter().map(String::as_str).collect::<Vec<_>>());
+
+        if edits.is_empty() {
+            return;
+        }
+
+        let (cursor_line, cursor_column) = self.content.cursor_position();
+
+        for (offset, range, number) in edits {
+            select_range(&mut self.content, line + offset, range);
+
+            self.content
+                .edit(text_editor::Action::Edit(text_editor::Edit::Paste(
+                    Arc::new(number),
+                )));
+        }
+
+        move_to(&mut self.content, cursor_line, cursor_column);
+    }
+
+    fn reindent(&self, text: &str) -> String {
+        let (line, column) = self.content.cursor_position();
+        let current = self
+            .content
+            .line(line)
+            .map(|text| text.to_string())
+            .unwrap_or_default();
+
+        let indent: String = current
+            .chars()
+            .take(column)
+            .take_while(|c| c.is_whitespace())
+            .collect();
+
+        let common = text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.len() - line.trim_start().len())
+            .min()
+            .unwrap_or_default();
+
+        text.split('\n')
+            .enumerate()
+            .map(|(index, line)| {
+                let line = line.get(common..).unwrap_or(line.trim_start());
+
+                if index == 0 || line.trim().is_empty() {
+                    line.to_string()
+                } else {
+                    format!("{indent}{line}")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Widens a Backspace or Delete to a whole word, or to both halves of a pair just typed.
+    fn extend_deletion(&mut self, forward: bool, is_auto_pair: bool) {
+        if self.content.selection().is_some() {
+            return;
+        }
+
+        let (line, column) = self.content.cursor_position();
+
+        let Some(text) = self.content.line(line).map(|text| text.to_string()) else {
+            return;
+        };
+
+        if self.modifiers.command() {
+            let subwords = self.config.subword_deletion;
+
+            let (count, motion) = if forward {
+                (
+                    words::next(&text, column, subwords) - column,
+                    text_editor::Motion::Right,
+                )
+            } else {
+                (
+                    column - words::previous(&text, column, subwords),
+                    text_editor::Motion::Left,
+                )
+            };
+
+            for _ in 0..count {
+                self.content.edit(text_editor::Action::Select(motion));
+            }
+        } else if !forward && is_auto_pair {
+            self.content
+                .edit(text_editor::Action::Edit(text_editor::Edit::Delete));
+        }
+    }
+
+    fn follow_link(&mut self) -> Command<Message> {
+        let (line, column) = self.content.cursor_position();
+
+        let Some(link) = self
+            .content
+            .line(line)
+            .and_then(|text| links::at(&text, column))
+        else {
+            return Command::none();
+        };
+
+        match link {
+            Link::Url(url) => {
+                if let Err(error) = open::that_detached(url) {
+                    self.error = Some(Error::IOFailed(error.kind()));
+                }
+
+                Command::none()
+            }
+            Link::Path(_) => match link.resolve(self.path.as_deref()) {
+                Some(path) => self.open_in_tab(path),
+                None => Command::none(),
+            },
+        }
+    }
+
+    fn commands(&self) -> Vec<(String, Message)> {
+        let mut commands = vec![
+            (String::from("New file"), Message::New),
+            (String::from("Open file..."), Message::Open),
+            (String::from("Open folder..."), Message::OpenFolder),
+            (String::from("Switch session..."), Message::ToggleSessions),
+            (String::from("Open remote..."), Message::OpenRemote),
+            (String::from("Save"), Message::Save),
+            (String::from("Toggle TODO panel"), Message::ToggleTodos),
+            (String::from("Find in files..."), Message::ToggleSearch),
+            (String::from("Regex tester"), Message::ToggleRegexTester),
+            (String::from("Theme editor"), Message::ToggleThemeEditor),
+            (String::from("Import theme..."), Message::ImportTheme),
+            (String::from("Calculation mode"), Message::ToggleCalculation),
+            (String::from("Sync settings"), Message::ToggleSync),
+            (String::from("Scratch buffers"), Message::ToggleScratch),
+            (String::from("Collaborate"), Message::ToggleCollab),
+            (String::from("Paste and indent"), Message::PasteAndIndent),
+            (String::from("Paste as column"), Message::PasteAsColumn),
+            (
+                String::from("Filter through command..."),
+                Message::ToggleFilter,
+            ),
+            (String::from("Join lines"), Message::JoinLines),
+            (String::from("Reflow paragraph"), Message::ReflowParagraph),
+            (
+                String::from("Align selection on character..."),
+                Message::ShowLineTool(transforms::Tool::Align),
+            ),
+            (
+                String::from("Sort lines by column..."),
+                Message::ShowLineTool(transforms::Tool::Sort),
+            ),
+            (String::from("Document statistics"), Message::ShowStatistics),
+            (String::from("Writing goals"), Message::ToggleWritingGoals),
+            (String::from("Assistant"), Message::ToggleAssistant),
+            (
+                String::from(if self.content.selection().is_some() {
+                    "Read selection aloud"
+                } else {
+                    "Read document aloud"
+                }),
+                Message::ReadAloud,
+            ),
+            (String::from("File info"), Message::ShowProperties),
+            (String::from("About Bonkitor"), Message::ShowAbout),
+            (
+                String::from("Toggle performance HUD"),
+                Message::TogglePerformanceHud,
+            ),
+            (String::from("Show memory usage"), Message::ShowMemory),
+            (
+                String::from(if self.config.inline_blame {
+                    "Hide blame for the current line"
+                } else {
+                    "Show blame for the current line"
+                }),
+                Message::ToggleInlineBlame,
+            ),
+            (
+                String::from("Toggle blame panel"),
+                Message::ToggleBlameGutter,
+            ),
+            (String::from("File history"), Message::ToggleHistory),
+            (String::from("Unstaged changes"), Message::ToggleChanges),
+            (
+                String::from("Unload background tabs"),
+                Message::EvictBackgroundTabs,
+            ),
+            (
+                String::from("Customize status bar"),
+                Message::ToggleStatusSettings,
+            ),
+            (
+                String::from("Customize toolbar"),
+                Message::ToggleToolbarSettings,
+            ),
+            (
+                String::from(if self.config.record_checksums {
+                    "Stop recording checksums of saved files"
+                } else {
+                    "Record checksums of saved files"
+                }),
+                Message::ToggleChecksums,
+            ),
+            (
+                String::from(match self.line_ending {
+                    lines::Ending::Lf => "Use CRLF line endings",
+                    lines::Ending::Crlf => "Use LF line endings",
+                }),
+                Message::ToggleLineEnding,
+            ),
+            match self.bom {
+                Some(_) => (
+                    String::from("Remove byte order mark (save as plain UTF-8)"),
+                    Message::SetBom(None),
+                ),
+                None => (
+                    String::from("Add UTF-8 byte order mark"),
+                    Message::SetBom(Some(bom::Bom::Utf8)),
+                ),
+            },
+            (
+                String::from(if self.config.lock_files {
+                    "Stop locking open files"
+                } else {
+                    "Lock open files against other editors"
+                }),
+                Message::ToggleFileLocking,
+            ),
+            (String::from("Toggle focus mode"), Message::ToggleFocusMode),
+            (
+                String::from(if self.config.prose_mode {
+                    "Turn off prose mode"
+                } else {
+                    "Turn on prose mode (smart quotes, dashes and lists)"
+                }),
+                Message::ToggleProseMode,
+            ),
+            (
+                String::from(if self.config.tray_icon {
+                    "Hide tray icon (after restart)"
+                } else {
+                    "Show tray icon"
+                }),
+                Message::ToggleTrayIcon,
+            ),
+            (
+                String::from(if self.config.minimize_to_tray {
+                    "Close the window instead of minimizing to tray"
+                } else {
+                    "Minimize to tray when closing the window"
+                }),
+                Message::ToggleMinimizeToTray,
+            ),
+            (
+                String::from(if self.config.typewriter_scrolling {
+                    "Disable typewriter scrolling"
+                } else {
+                    "Enable typewriter scrolling"
+                }),
+                Message::ToggleTypewriter,
+            ),
+            (
+                String::from(if self.config.dim_paragraphs {
+                    "Focus mode: stop dimming other paragraphs"
+                } else {
+                    "Focus mode: dim other paragraphs"
+                }),
+                Message::ToggleDimParagraphs,
+            ),
+            (String::from("Inspect character"), Message::ToggleInspector),
+            (
+                String::from(if self.reduces_motion() {
+                    "Allow motion (typewriter scrolling, streamed replies)"
+                } else {
+                    "Reduce motion"
+                }),
+                Message::SetReducedMotion(Some(!self.reduces_motion())),
+            ),
+            (
+                String::from("Find suspicious character"),
+                Message::FindSuspicious,
+            ),
+            (
+                String::from("Strip invisible and confusable characters"),
+                Message::StripSuspicious,
+            ),
+            (
+                String::from("Insert Unicode character..."),
+                Message::OpenUnicodePicker,
+            ),
+            (String::from("Expand selection"), Message::ExpandSelection),
+            (String::from("Shrink selection"), Message::ShrinkSelection),
+            (
+                String::from("Compare selection with clipboard"),
+                Message::CompareClipboard,
+            ),
+        ];
+
+        commands.extend(table::Operation::ALL.into_iter().map(|operation| {
+            (
+                format!("Columns: {}...", operation.label()),
+                Message::ShowColumnTool(operation),
+            )
+        }));
+
+        commands.extend(
+            WRAP_COLUMNS
+                .into_iter()
+                .filter(|column| self.config.wrap_column != Some(*column))
+                .map(|column| {
+                    (
+                        format!("Wrap at column {column}"),
+                        Message::WrapAt(Some(column)),
+                    )
+                }),
+        );
+
+        if self.config.wrap_column.is_some() {
+            commands.push((String::from("Wrap at window width"), Message::WrapAt(None)));
+        }
+
+        if self.config.reduce_motion.is_some() {
+            commands.push((
+                String::from("Follow the system's reduced-motion setting"),
+                Message::SetReducedMotion(None),
+            ));
+        }
+
+        commands.extend(
+            generators::Generator::ALL
+                .into_iter()
+                .map(|generator| (generator.label().to_string(), Message::Generate(generator))),
+        );
+
+        if self.path.is_some() && self.remote.is_none() && self.url.is_none() {
+            commands.push((String::from("Copy SHA-256 of file"), Message::CopyChecksum));
+            commands.push((
+                String::from(if self.content.selection().is_some() {
+                    "Copy permalink to selection"
+                } else {
+                    "Copy permalink to line"
+                }),
+                Message::CopyPermalink,
+            ));
+        }
+
+        commands.push((String::from("Merge three files..."), Message::MergeFiles));
+
+        if self
+            .path
+            .as_deref()
+            .and_then(compression::extension)
+            .is_some_and(patch::is_patch)
+        {
+            commands.push((String::from("Apply patch..."), Message::CheckPatch));
+        }
+
+        if self.is_rest() {
+            commands.push((String::from("Send request"), Message::SendRequest));
+        }
+
+        if self.has_cells() {
+            commands.push((String::from("Run cell"), Message::RunCell));
+            commands.push((String::from("Run all cells"), Message::RunAllCells));
+            commands.push((String::from("Show cells"), Message::ToggleCells));
+        }
+
+        if self.workspace.is_some() {
+            commands.extend(
+                self.repl_names()
+                    .into_iter()
+                    .map(|name| (format!("Start REPL: {name}"), Message::StartRepl(name))),
+            );
+
+            commands.push((
+                String::from(if self.content.selection().is_some() {
+                    "Send selection to REPL"
+                } else {
+                    "Send line to REPL"
+                }),
+                Message::SendToRepl,
+            ));
+        }
+
+        if !self.repls.is_empty() {
+            commands.push((String::from("Show REPLs"), Message::ToggleRepls));
+        }
+
+        commands.extend(self.config.databases.keys().map(|name| {
+            (
+                format!("Use database: {name}"),
+                Message::UseDatabase(name.clone()),
+            )
+        }));
+
+        if !self.config.databases.is_empty()
+            && (self.is_sql() || self.content.selection().is_some())
+        {
+            commands.push((String::from("Run SQL statement"), Message::RunQuery));
+        }
+
+        if let Some(workspace) = &self.workspace {
+            commands.extend(workspace.settings.tasks.keys().map(|task| {
+                (
+                    format!("Run task: {task}"),
+                    Message::RunWorkspaceTask(task.clone()),
+                )
+            }));
+        }
+
+        if self.run.is_some() {
+            commands.push((String::from("Show task output"), Message::ShowOutput));
+        }
+
+        commands.push((String::from("Show problems"), Message::ShowProblems));
+
+        if self.path.is_some() {
+            commands.push((String::from("Toggle breakpoint"), Message::ToggleBreakpoint));
+        }
+
+        if self.debug.is_some() {
+            commands.push((String::from("Show debugger"), Message::ToggleDebugPanel));
+            commands.push((
+                String::from("Stop debugging"),
+                Message::DebugControl(debug::Control::Stop),
+            ));
+        } else if let Some(workspace) = &self.workspace {
+            commands.extend(workspace.settings.debug.keys().map(|name| {
+                (
+                    format!("Debug: {name}"),
+                    Message::StartDebugging(name.clone()),
+                )
+            }));
+        }
+
+        if self.path.is_some() && self.remote.is_none() && merge::has_markers(&self.content.text())
+        {
+            commands.push((
+                String::from("Resolve conflict markers"),
+                Message::StartMerge,
+            ));
+        }
+
+        let can_encrypt = self.path.as_deref().is_some_and(|path| {
+            !scratch::is_scratch(path) && encryption::Format::detect(path).is_none()
+        }) && self.remote.is_none()
+            && self.url.is_none();
+
+        if self.vault.is_some() {
+            commands.push((
+                String::from("Remove encryption (save as plain text)"),
+                Message::DecryptFile,
+            ));
+        } else if can_encrypt {
+            commands.push((String::from("Encrypt this file..."), Message::EncryptFile));
+        }
+
+        if self.config.assistant_endpoint.is_some() {
+            commands.extend(assistant::Prompt::ALL.into_iter().map(|prompt| {
+                (
+                    format!("Assistant: {}", prompt.label()),
+                    Message::AskAssistant(prompt),
+                )
+            }));
+        }
+
+        if self.content.selection().is_some() {
+            commands.extend(
+                transforms::Transform::ALL
+                    .into_iter()
+                    .flat_map(|transform| {
+                        let show = transform.can_show().then(|| {
+                            (
+                                format!("Transform selection: {} (show)", transform.label()),
+                                Message::Transform(transform, transforms::Output::Show),
+                            )
+                        });
+
+                        [
+                            (
+                                format!("Transform selection: {}", transform.label()),
+                                Message::Transform(transform, transforms::Output::Replace),
+                            ),
+                            (
+                                format!("Transform selection: {} (copy)", transform.label()),
+                                Message::Transform(transform, transforms::Output::Copy),
+                            ),
+                        ]
+                        .into_iter()
+                        .chain(show)
+                    }),
+            );
+
+            commands.extend([
+                (
+                    String::from("Evaluate selection"),
+                    Message::EvaluateSelection(transforms::Output::Replace),
+                ),
+                (
+                    String::from("Evaluate selection (copy)"),
+                    Message::EvaluateSelection(transforms::Output::Copy),
+                ),
+                (
+                    String::from("Evaluate selection (show)"),
+                    Message::EvaluateSelection(transforms::Output::Show),
+                ),
+            ]);
+        }
+
+        if self
+            .path
+            .as_deref()
+            .and_then(preview::Format::detect)
+            .is_some()
+        {
+            commands.push((String::from("Toggle preview"), Message::TogglePreview));
+        }
+
+        if self.has_colors() {
+            commands.push((String::from("Show colors in file"), Message::ToggleColors));
+        }
+
+        if self.path.as_deref().and_then(preview::Format::detect) == Some(preview::Format::Markdown)
+        {
+            commands.extend([
+                (String::from("Toggle outline"), Message::ToggleOutline),
+                (
+                    String::from("Insert or update table of contents"),
+                    Message::UpdateToc,
+                ),
+            ]);
+        }
+
+        if let (Some(workspace), Some(path)) = (&self.workspace, &self.path) {
+            if workspace.formatter(path).is_some() {
+                commands.push((String::from("Format document"), Message::FormatDocument));
+            }
+        }
+
+        commands.extend(
+            self.config
+                .recent_workspaces
+                .iter()
+                .filter(|root| {
+                    Some(*root) != self.workspace.as_ref().map(|workspace| &workspace.root)
+                })
+                .map(|root| {
+                    (
+                        format!(
+                            "Switch workspace: {} ({})",
+                            workspace::name(root),
+                            root.display()
+                        ),
+                        Message::SwitchWorkspace(root.clone()),
+                    )
+                }),
+        );
+
+        if let Some(tree) = &self.tree {
+            // The index also has the files in folders the tree hasn't expanded
+            let files: Vec<&Path> = match &self.indexer.index {
+                Some(index) => index.files().collect(),
+                None => tree.files().collect(),
+            };
+
+            commands.extend(files.into_iter().map(|path| {
+                (
+                    format!("Open: {}", tree.relative(path).display()),
+                    Message::TreeSelected(path.to_path_buf()),
+                )
+            }));
+
+            if let Some(index) = &self.indexer.index {
+                commands.extend(index.symbols().map(|(path, symbol)| {
+                    (
+                        format!(
+                            "Go to symbol: {} ({}) in {}:{}",
+                            symbol.name,
+                            symbol.kind,
+                            tree.relative(path).display(),
+                            symbol.line + 1
+                        ),
+                        Message::TodoSelected(Some(path.to_path_buf()), symbol.line),
+                    )
+                }));
+            }
+        }
+
+        commands.extend(
+            highlighter::Theme::ALL
+                .iter()
+                .map(|theme| (format!("Theme: {theme}"), Message::ThemeSelected(*theme))),
+        );
+
+        commands.extend(self.themes.iter().map(|theme| {
+            (
+                format!("Theme: {}", theme.name),
+                Message::CustomThemeSelected(theme.name.clone()),
+            )
+        }));
+
+        if self.image.is_some() {
+            commands.retain(|(_, message)| {
+                !matches!(
+                    message,
+                    Message::Save | Message::SaveCopy | Message::SaveElevated
+                )
+            });
+        }
+
+        commands
+    }
+
+    fn tool(&self, button: toolbar::Button) -> Option<Element<'_, Message>> {
+        let style = self.config.toolbar_style;
+        let panel = |is_open: fn(&Panel) -> bool| self.panel.as_ref().is_some_and(is_open);
+        let markup = self.path.as_deref().and_then(preview::Format::detect);
+
+        let tool = match button {
+            toolbar::Button::New => action(new_icon(), Some(Message::New), "New File", style),
+            toolbar::Button::Open => action(open_icon(), Some(Message::Open), "Open File", style),
+            toolbar::Button::Save => match self.url {
+                _ if self.image.is_some() => action(save_icon(), None, "Save", style),
+                Some(_) => action(save_icon(), Some(Message::Save), "Save a local copy", style),
+                None => action(
+                    save_icon(),
+                    self.is_dirty.then_some(Message::Save),
+                    "Save",
+                    style,
+                ),
+            },
+            toolbar::Button::Spacer => horizontal_space(Length::Fill).into(),
+            toolbar::Button::Table => {
+                self.path.as_deref().and_then(table::delimiter)?;
+
+                toggle("Table", self.table.is_some(), Message::ToggleTable)
+            }
+            toolbar::Button::Dictate => {
+                self.config.dictation_command.as_ref()?;
+
+                let label = if self.dictation.is_some() {
+                    "● Recording"
+                } else if self.is_transcribing {
+                    "Transcribing..."
+                } else {
+                    "Dictate"
+                };
+
+                toggle(label, self.dictation.is_some(), Message::ToggleDictation)
+            }
+            toolbar::Button::Tail => {
+                if self.tail.is_none() && !self.path.as_deref().is_some_and(tail::is_log) {
+                    return None;
+                }
+
+                toggle("Tail", self.tail.is_some(), Message::ToggleTail)
+            }
+            toolbar::Button::Files => toggle(
+                "Files",
+                self.show_tree && self.workspace.is_some(),
+                Message::ToggleTree,
+            ),
+            toolbar::Button::Remote => toggle(
+                "Remote",
+                panel(|panel| matches!(panel, Panel::Remote(_))),
+                Message::OpenRemote,
+            ),
+            toolbar::Button::Collab => toggle(
+                "Collab",
+                self.collab.is_some() || panel(|panel| matches!(panel, Panel::Collab(_))),
+                Message::ToggleCollab,
+            ),
+            toolbar::Button::Scratch => toggle(
+                "Scratch",
+                panel(|panel| matches!(panel, Panel::Scratch(_))),
+                Message::ToggleScratch,
+            ),
+            toolbar::Button::Sync => toggle(
+                "Sync",
+                panel(|panel| matches!(panel, Panel::Sync(_))),
+                Message::ToggleSync,
+            ),
+            toolbar::Button::Todo => toggle(
+                "TODO",
+                panel(|panel| matches!(panel, Panel::Todos(_))),
+                Message::ToggleTodos,
+            ),
+            toolbar::Button::Search => toggle(
+                "Search",
+                panel(|panel| matches!(panel, Panel::Search(_))),
+                Message::ToggleSearch,
+            ),
+            toolbar::Button::Palette => {
+                toggle("Commands", self.palette.is_some(), Message::TogglePalette)
+            }
+            toolbar::Button::Preview => {
+                markup?;
+
+                toggle(
+                    "Preview",
+                    panel(|panel| matches!(panel, Panel::Preview(_))),
+                    Message::TogglePreview,
+                )
+            }
+            toolbar::Button::Outline => {
+                if markup != Some(preview::Format::Markdown) {
+                    return None;
+                }
+
+                toggle(
+                    "Outline",
+                    panel(|panel| matches!(panel, Panel::Outline(_))),
+                    Message::ToggleOutline,
+                )
+            }
+            toolbar::Button::Assistant => toggle(
+                "Assistant",
+                panel(|panel| matches!(panel, Panel::Assistant(_))),
+                Message::ToggleAssistant,
+            ),
+            toolbar::Button::FocusMode => {
+                toggle("Focus", self.focus_mode, Message::ToggleFocusMode)
+            }
+            toolbar::Button::Theme => pick_list(
+                highlighter::Theme::ALL,
+                Some(self.theme),
+                Message::ThemeSelected,
+            )
+            .into(),
+        };
+
+        Some(tool)
+    }
+
+    fn menu_items(&self, menu: menu::Menu) -> Vec<menu::Item> {
+        let item = |label: String, message: Option<Message>| menu::Item {
+            shortcut: message.as_ref().and_then(menu::shortcut),
+            label,
+            message,
+        };
+
+        let mut items = Vec::new();
+
+        match menu {
+            menu::Menu::Edit => {
+                let has_selection = self.content.selection().is_some();
+                let is_editable = self.url.is_none() && !self.is_read_only();
+
+                items.extend([
+                    item(
+                        String::from("Cut"),
+                        (has_selection && is_editable).then_some(Message::Cut),
+                    ),
+                    item(String::from("Copy"), has_selection.then_some(Message::Copy)),
+                    item(String::from("Paste"), is_editable.then_some(Message::Paste)),
+                    item(String::from("Select all"), Some(Message::SelectAll)),
+                ]);
+            }
+            menu::Menu::Help => {
+                items.extend([
+                    item(
+                        String::from("All commands..."),
+                        Some(Message::TogglePalette),
+                    ),
+                    item(String::from("Next tab"), Some(Message::CycleTabs(1))),
+                    item(
+                        String::from("Next focus region"),
+                        Some(Message::CycleFocus(1)),
+                    ),
+                ]);
+            }
+            menu::Menu::File | menu::Menu::View => {}
+        }
+
+        items.extend(
+            self.commands()
+                .into_iter()
+                .filter(|(_, message)| menu::place(message) == Some(menu))
+                .map(|(label, message)| item(label, Some(message))),
+        );
+
+        items
+    }
+
+    fn render_preview(&self) -> Command<Message> {
+        let Some((path, format)) = self
+            .path
+            .as_ref()
+            .and_then(|path| Some((path.clone(), preview::Format::detect(path)?)))
+        else {
+            return Command::none();
+        };
+
+        Command::perform(
+            preview::render(
+                path,
+                format,
+                self.content.text(),
+                self.config.preview_converter(format),
+                self.config.math_renderer().map(str::to_string),
+                self.config.diagram_renderer().map(str::to_string),
+            ),
+            Message::PreviewRendered,
+        )
+    }
+
+    fn tab_labels(&self) -> Vec<tabs::Label> {
+        self.tabs
+            .iter()
+            .map(|tab| match &tab.buffer {
+                Some(buffer) => tabs::Label {
+                    title: tabs::title(
+                        buffer.path.as_deref(),
+                        buffer.remote.as_ref(),
+                        buffer.url.as_deref(),
+                    ),
+                    path: buffer.path.clone(),
+                    is_dirty: buffer.is_dirty && buffer.is_loaded,
+                    is_pinned: tab.is_pinned,
+                    name: tab.name.clone(),
+                    tag: tab.tag,
+                },
+                None => tabs::Label {
+                    title: tabs::title(
+                        self.path.as_deref(),
+                        self.remote.as_ref(),
+                        self.url.as_deref(),
+                    ),
+                    path: self.path.clone(),
+                    is_dirty: self.is_dirty,
+                    is_pinned: tab.is_pinned,
+                    name: tab.name.clone(),
+                    tag: tab.tag,
+                },
+            })
+            .collect()
+    }
+
+    /// The theme being edited while the editor is open, else the user theme chosen.
+    fn active_theme(&self) -> Option<&themes::Theme> {
+        match &self.panel {
+            Some(Panel::ThemeEditor(draft)) => Some(&draft.theme),
+            _ => self.custom_theme.as_ref(),
+        }
+    }
+
+    fn recent_tabs(&self) -> Vec<usize> {
+        let mut recent: Vec<usize> = self
+            .history
+            .iter()
+            .filter_map(|id| self.tabs.iter().position(|tab| tab.id == *id))
+            .collect();
+
+        recent.extend((0..self.tabs.len()).filter(|index| !recent.contains(index)));
+        recent
+    }
+
+    fn touch_tab(&mut self) {
+        let id = self.tabs[self.active].id;
+
+        self.history
+            .retain(|recent| *recent != id && self.tabs.iter().any(|tab| tab.id == *recent));
+        self.history.insert(0, id);
+    }
+
+    /// The reduced-motion setting, or the desktop's when it is unset.
+    fn reduces_motion(&self) -> bool {
+        self.config
+            .reduce_motion
+            .unwrap_or(self.system_reduces_motion)
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.url.is_some() || self.is_read_only
+    }
+
+    fn remember_position(&mut self) {
+        if let Some(path) = &self.path {
+            self.positions
+                .set(path.clone(), self.content.cursor_position());
+        }
+    }
+
+    fn stash(&mut self) -> tabs::Buffer {
+        self.remember_position();
+
+        let memory = memory::estimate(&self.content, self.image.as_ref());
+
+        tabs::Buffer {
+            path: self.path.take(),
+            content: std::mem::replace(&mut self.content, text_editor::Content::new()),
+            is_dirty: std::mem::replace(&mut self.is_dirty, true),
+            image: self.image.take(),
+            table: self.table.take(),
+            tail: self.tail.take(),
+            remote: self.remote.take(),
+            url: self.url.take(),
+            link: self.link.take(),
+            replace_link: std::mem::take(&mut self.replace_link),
+            is_read_only: std::mem::take(&mut self.is_read_only),
+            bom: self.bom.take(),
+            line_ending: std::mem::take(&mut self.line_ending),
+            lossy: self.lossy.take(),
+            vault: self.vault.take(),
+            database: self.database.take(),
+            changed_outside: std::mem::take(&mut self.changed_outside),
+            is_loaded: true,
+            modified: self.modified.take(),
+            memory,
+        }
+    }
+
+    fn restore(&mut self, buffer: tabs::Buffer) {
+        self.path = buffer.path;
+        self.content = buffer.content;
+        self.is_dirty = buffer.is_dirty;
+        self.image = buffer.image;
+        self.table = buffer.table;
+        self.tail = buffer.tail;
+        self.remote = buffer.remote;
+        self.url = buffer.url;
+        self.link = buffer.link;
+        self.replace_link = buffer.replace_link;
+        self.is_read_only = buffer.is_read_only;
+        self.bom = buffer.bom;
+        self.line_ending = buffer.line_ending;
+        self.lossy = buffer.lossy;
+        self.vault = buffer.vault;
+        self.database = buffer.database;
+        self.changed_outside = buffer.changed_outside;
+        self.modified = buffer.modified;
+        self.offer_lossy = None;
+
+        self.count_words();
+    }
+
+    /// Brings derived state up to date with the buffer after typing pauses.
+    fn recompute(&mut self, job: debounce::Job) -> Command<Message> {
+        match job {
+            debounce::Job::Words => {
+                self.count_words();
+                Command::none()
+            }
+            debounce::Job::Outline => {
+                if let Some(Panel::Outline(headings)) = &mut self.panel {
+                    *headings = toc::headings(&self.content.text());
+                }
+
+                Command::none()
+            }
+            debounce::Job::Colors => {
+                if let Some(Panel::Colors(swatches)) = &mut self.panel {
+                    *swatches = colors::list(&self.content.text());
+                }
+
+                Command::none()
+            }
+            debounce::Job::Calculation => {
+                if let Some(Panel::Calculation(sheet)) = &mut self.panel {
+                    sheet.refresh(&self.content.text());
+                }
+
+                Command::none()
+            }
+            debounce::Job::Preview => match &self.panel {
+                Some(Panel::Preview(Some(preview)))
+                    if self.path.as_ref() == Some(&preview.path) =>
+                {
+                    self.render_preview()
+                }
+                _ => Command::none(),
+            },
+            debounce::Job::Statistics => match &self.panel {
+                Some(Panel::Statistics(_)) => Command::perform(
+                    stats::count(Arc::new(self.content.text())),
+                    Message::StatisticsComputed,
+                ),
+                _ => Command::none(),
+            },
+            debounce::Job::Changes => self.read_changes(),
+        }
+    }
+
+    fn count_words(&mut self) {
+        self.words = match &self.path {
+            Some(path) if goals::is_prose(path) => goals::count(&self.content.text()),
+            _ => 0,
+        };
+    }
+
+    fn tab_path(&self, index: usize) -> Option<&Path> {
+        if index == self.active {
+            self.path.as_deref()
+        } else {
+            self.tabs
+                .get(index)?
+                .buffer
+                .as_ref()
+                .and_then(|buffer| buffer.path.as_deref())
+        }
+    }
+
+    fn tab_index(&self, path: &Path) -> Option<usize> {
+        (0..self.tabs.len()).find(|index| self.tab_path(*index) == Some(path))
+    }
+
+    fn switch_tab(&mut self, index: usize) -> bool {
+        if index == self.active || index >= self.tabs.len() {
+            return true;
+        }
+
+        let buffer = self.tabs[index].buffer.take();
+        self.tabs[self.active].buffer = Some(self.stash());
+        self.touch_tab();
+        self.active = index;
+        self.touch_tab();
+
+        let is_loaded = match buffer {
+            Some(buffer) => {
+                let is_loaded = buffer.is_loaded;
+                self.restore(buffer);
+                is_loaded
+            }
+            None => true,
+        };
+
+        if let Some(budget) = self.config.memory_budget() {
+            self.evict_background_tabs(budget);
+        }
+
+        is_loaded
+    }
+
+    fn select_tab(&mut self, index: usize) -> Command<Message> {
+        if self.switch_tab(index) {
+            if let Some(line) = self.pending_jump.take() {
+                jump_to(&mut self.content, line);
+            }
+
+            self.read_blame()
+        } else {
+            match self.path.clone() {
+                Some(path) => self.load(path),
+                None => Command::none(),
+            }
+        }
+    }
+
+    fn new_tab(&mut self) {
+        let buffer = self.stash();
+
+        self.tabs[self.active].buffer = Some(buffer);
+        self.touch_tab();
+        self.tabs.push(tabs::Tab::active());
+        self.active = self.tabs.len() - 1;
+        self.touch_tab();
+
+        if let Some(budget) = self.config.memory_budget() {
+            self.evict_background_tabs(budget);
+        }
+    }
+
+    /// Unloads the least recently used background tabs until the buffers fit in `budget`.
+    ///
+    /// Unloaded tabs are read from disk again once shown, like those restored from a session.
+    fn evict_background_tabs(&mut self, budget: u64) {
+        let mut total: u64 = self
+            .memory_usage()
+            .iter()
+            .map(|usage| usage.bytes as u64)
+            .sum();
+
+        for index in self.recent_tabs().into_iter().rev() {
+            if total <= budget {
+                break;
+            }
+
+            let Some(buffer) = self.tabs[index]
+                .buffer
+                .as_mut()
+                .filter(|buffer| memory::is_evictable(buffer))
+            else {
+                continue;
+            };
+
+            if let Some(path) = buffer.path.take() {
+                total = total.saturating_sub(buffer.memory as u64);
+                *buffer = tabs::Buffer {
+                    database: buffer.database.take(),
+                    ..tabs::Buffer::unloaded(path)
+                };
+            }
+        }
+    }
+
+    fn memory_usage(&self) -> Vec<memory::Usage> {
+        self.tab_labels()
+            .into_iter()
+            .zip(&self.tabs)
+            .enumerate()
+            .map(|(index, (label, tab))| {
+                let (bytes, state) = match &tab.buffer {
+                    _ if index == self.active => (
+                        memory::estimate(&self.content, self.image.as_ref()),
+                        memory::State::Active,
+                    ),
+                    Some(buffer) if buffer.is_loaded => (buffer.memory, memory::State::Loaded),
+                    _ => (0, memory::State::Evicted),
+                };
+
+                memory::Usage {
+                    title: label.title,
+                    bytes,
+                    state,
+                }
+            })
+            .collect()
+    }
+
+    fn open_in_tab(&mut self, path: PathBuf) -> Command<Message> {
+        let (path, link) = resolve(path);
+
+        if let Some(index) = self.tab_index(&path) {
+            return self.select_tab(index);
+        }
+
+        if !self.is_blank() {
+            self.new_tab();
+        }
+
+        self.path = Some(path.clone());
+        self.link = link;
+
+        self.load(path)
+    }
+
+    fn is_blank(&self) -> bool {
+        self.path.is_none()
+            && self.remote.is_none()
+            && self.url.is_none()
+            && self.content.text().trim().is_empty()
+    }
+
+    /// Detaches the active tab from a file it failed to read, so saving the empty buffer
+    /// cannot overwrite the file. Returns the path it was bound to.
+    fn unbind_unread(&mut self) -> Option<PathBuf> {
+        if !self.content.text().trim().is_empty() {
+            return None;
+        }
+
+        self.link = None;
+        self.path.take()
+    }
+
+    /// Reads a file into the active tab.
+    fn load(&mut self, path: PathBuf) -> Command<Message> {
+        open(&mut self.tasks, self.tabs[self.active].id, path)
+    }
+
+    /// Runs a task whose result only applies while its tab is the active one.
+    fn perform_for_tab<T: Send + 'static>(
+        &mut self,
+        kind: tasks::Kind,
+        future: impl std::future::Future<Output = T> + Send + 'static,
+        map: impl FnOnce(T) -> Message + Send + 'static,
+    ) -> Command<Message> {
+        let tab = self.tabs[self.active].id;
+
+        self.tasks.perform(kind, Some(tab), future, map)
+    }
+
+    fn move_tab(&mut self, from: usize, to: usize) {
+        if from == to || from >= self.tabs.len() || to >= self.tabs.len() {
+            return;
+        }
+
+        let tab = self.tabs.remove(from);
+        self.tabs.insert(to, tab);
+
+        self.active = if self.active == from {
+            to
+        } else if from < self.active && self.active <= to {
+            self.active - 1
+        } else if to <= self.active && self.active < from {
+            self.active + 1
+        } else {
+            self.active
+        };
+    }
+
+    fn close_tabs(&mut self, mut indices: Vec<usize>) -> Command<Message> {
+        indices.retain(|index| self.tabs.get(*index).is_some_and(|tab| !tab.is_pinned));
+        indices.sort_unstable();
+
+        let mut command = Command::none();
+
+        for index in indices.into_iter().rev() {
+            if self.tabs.len() == 1 {
+                let _ = self.stash();
+                command = Command::none();
+                break;
+            }
+
+            if index == self.active {
+                let next = if index + 1 < self.tabs.len() {
+                    index + 1
+                } else {
+                    index - 1
+                };
+
+                command = self.select_tab(next);
+            }
+
+            self.tabs.remove(index);
+
+            if self.active > index {
+                self.active -= 1;
+            }
+        }
+
+        let open: Vec<_> = (0..self.tabs.len())
+            .filter_map(|index| self.tab_path(index).map(Path::to_path_buf))
+            .collect();
+        self.locks.retain(&open);
+
+        Command::batch([command, self.save_workspace()])
+    }
+
+    fn set_workspace(&mut self, workspace: workspace::Workspace) -> Command<Message> {
+        self.config.add_recent_workspace(workspace.root.clone());
+
+        self.tree = Some(tree::Tree::new(workspace.root.clone()));
+        self.indexer = indexer::Indexer::default();
+        self.show_tree = true;
+        let branch = status::branch(workspace.root.clone());
+        let history = repl::load_history(workspace.root.clone());
+        self.workspace = Some(workspace);
+
+        // REPLs belong to the workspace they were started in
+        self.repls.clear();
+        self.repl_history.clear();
+
+        if let Some(Panel::Repl) = self.panel {
+            self.panel = None;
+        }
+
+        Command::batch([
+            Command::perform(self.config.clone().save(), Message::ConfigSaved),
+            Command::perform(branch, Message::BranchLoaded),
+            Command::perform(history, Message::ReplHistoryLoaded),
+            self.index_tree(),
+        ])
+    }
+
+    fn open_files(&mut self, files: Vec<PathBuf>) -> Command<Message> {
+        let mut files = files.into_iter();
+        let file = files.next().map(|path| self.open_in_tab(path));
+
+        for (path, _) in files.map(resolve) {
+            if self.tab_index(&path).is_none() {
+                self.tabs.push(tabs::Tab::unloaded(path));
+            }
+        }
+
+        file.unwrap_or_else(Command::none)
+    }
+
+    fn has_unsaved_work(&self, index: usize) -> bool {
+        let is_blank = |path: &Option<PathBuf>, content: &text_editor::Content| {
+            path.is_none() && content.text().trim().is_empty()
+        };
+
+        match self.tabs.get(index).and_then(|tab| tab.buffer.as_ref()) {
+            Some(buffer) => {
+                buffer.is_loaded && buffer.is_dirty && !is_blank(&buffer.path, &buffer.content)
+            }
+            None => index == self.active && self.is_dirty && !is_blank(&self.path, &self.content),
+        }
+    }
+
+    fn current_session(&self) -> session::Session {
+        let open_files: Vec<_> = (0..self.tabs.len())
+            .filter_map(|index| self.tab_path(index).map(Path::to_path_buf))
+            .collect();
+
+        let labels = self
+            .tabs
+            .iter()
+            .enumerate()
+            .filter(|(_, tab)| tab.name.is_some() || tab.tag.is_some())
+            .filter_map(|(index, tab)| {
+                Some(session::Label {
+                    path: self.tab_path(index)?.to_path_buf(),
+                    name: tab.name.clone(),
+                    tag: tab.tag,
+                })
+            })
+            .collect();
+
+        session::Session {
+            workspace: self
+                .workspace
+                .as_ref()
+                .map(|workspace| workspace.root.clone()),
+            active: self
+                .path
+                .as_ref()
+                .and_then(|path| open_files.iter().position(|file| file == path))
+                .unwrap_or(0),
+            open_files,
+            show_tree: self.show_tree,
+            focus_mode: self.focus_mode,
+            labels,
+        }
+    }
+
+    fn save_workspace(&mut self) -> Command<Message> {
+        let paths: Vec<_> = (0..self.tabs.len())
+            .filter_map(|index| self.tab_path(index).map(Path::to_path_buf))
+            .collect();
+
+        match &mut self.workspace {
+            Some(workspace) => {
+                workspace.set_open_files(paths);
+
+                Command::perform(workspace.clone().save(), Message::WorkspaceSaved)
+            }
+            None => Command::none(),
+        }
+    }
+
+    /// The workspace's panel sizes, or the window's own when no workspace is open.
+    fn sizes(&self) -> &layout::Sizes {
+        match &self.workspace {
+            Some(workspace) => &workspace.settings.layout,
+            None => &self.layout,
+        }
+    }
+
+    fn sizes_mut(&mut self) -> &mut layout::Sizes {
+        match &mut self.workspace {
+            Some(workspace) => &mut workspace.settings.layout,
+            None => &mut self.layout,
+        }
+    }
+
+    fn save_sizes(&self) -> Command<Message> {
+        match &self.workspace {
+            Some(workspace) => Command::perform(workspace.clone().save(), Message::WorkspaceSaved),
+            None => Command::none(),
+        }
+    }
+
+    fn index_tree(&self) -> Command<Message> {
+        let (Some(workspace), Some(tree)) = (&self.workspace, &self.tree) else {
+            return Command::none();
+        };
+
+        let root = tree.root.clone();
+
+        Command::perform(
+            tree::index(root.clone(), tree.filter(&workspace.settings.exclude)),
+            move |entries| Message::TreeIndexed(root, entries),
+        )
+    }
+
+    /// The workspace's REPLs, or the usual interpreters when it configures none.
+    fn repl_names(&self) -> Vec<String> {
+        match &self.workspace {
+            Some(workspace) if !workspace.settings.repls.is_empty() => {
+                workspace.settings.repls.keys().cloned().collect()
+            }
+            _ => repl::DEFAULTS
+                .iter()
+                .map(|(name, _)| name.to_string())
+                .collect(),
+        }
+    }
+
+    fn repl_command(&self, name: &str) -> Option<String> {
+        match &self.workspace {
+            Some(workspace) if !workspace.settings.repls.is_empty() => {
+                workspace.settings.repls.get(name).cloned()
+            }
+            _ => repl::DEFAULTS
+                .iter()
+                .find(|(default, _)| *default == name)
+                .map(|(_, command)| command.to_string()),
+        }
+    }
+
+    fn save_repl_history(&self) -> Command<Message> {
+        match &self.workspace {
+            Some(workspace) => Command::perform(
+                repl::save_history(workspace.root.clone(), self.repl_history.clone()),
+                Message::ReplHistorySaved,
+            ),
+            None => Command::none(),
+        }
+    }
+
+    fn has_cells(&self) -> bool {
+        self.path
+            .as_deref()
+            .and_then(compression::extension)
+            .is_some_and(cells::supports)
+    }
+
+    /// Reads the buffer's cells again, starting over when it is a different file.
+    fn refresh_notebook(&mut self) -> Option<&mut cells::Notebook> {
+        let path = self.path.clone()?;
+        let extension = compression::extension(&path)?;
+
+        if !cells::supports(extension) {
+            return None;
+        }
+
+        if self
+            .notebook
+            .as_ref()
+            .map_or(true, |notebook| notebook.path != path)
+        {
+            let has_kernel = cells::has_kernel(extension);
+
+            self.notebook = Some(cells::Notebook::new(path.clone(), has_kernel));
+        }
+
+        let notebook = self.notebook.as_mut()?;
+        notebook.cells = cells::split(&self.content.text());
+
+        Some(notebook)
+    }
+
+    fn is_rest(&self) -> bool {
+        self.path
+            .as_deref()
+            .and_then(compression::extension)
+            .is_some_and(rest::is_rest)
+    }
+
+    fn end_debugging(&mut self) {
+        self.debug = None;
+
+        if let Some(Panel::Debug) = self.panel {
+            self.panel = None;
+        }
+    }
+
+    /// Reads the finished run's output for problems, warning about any matcher that failed.
+    fn match_problems(&mut self) {
+        let (Some(run), Some(workspace)) = (&self.run, &self.workspace) else {
+            return;
+        };
+
+        let (matchers, errors) = problems::matchers(&workspace.settings.problem_matchers);
+        self.problems = problems::find(&run.output(), &matchers, run.root());
+
+        if !errors.is_empty() {
+            self.toast = Some(toast::Toast::new(format!(
+                "Some problem matchers are not valid regexes: {}",
+                errors.join("; ")
+            )));
+        }
+    }
+
+    fn project_folder(&self) -> Option<(PathBuf, tree::Filter)> {
+        match (&self.workspace, &self.tree) {
+            (Some(workspace), Some(tree)) => Some((
+                workspace.root.clone(),
+                tree.filter(&workspace.settings.exclude),
+            )),
+            _ => self.path.as_deref().and_then(Path::parent).map(|folder| {
+                (
+                    folder.to_path_buf(),
+                    tree::Filter {
+                        exclude: workspace::Settings::default().exclude,
+                        show_ignored: false,
+                    },
+                )
+            }),
+        }
+    }
+
+    fn scan_todos(&self) -> Command<Message> {
+        let folder = self.scan_folder.then(|| self.project_folder()).flatten();
+
+        Command::perform(
+            todos::scan(self.content.text(), self.path.clone(), folder),
+            Message::TodosScanned,
+        )
+    }
+}
+
+fn resolve(path: PathBuf) -> (PathBuf, Option<PathBuf>) {
+    let is_link = std::fs::symlink_metadata(&path).is_ok_and(|metadata| metadata.is_symlink());
+
+    match std::fs::canonicalize(&path) {
+        Ok(canonical) if is_link => (canonical, Some(path)),
+        Ok(canonical) => (canonical, None),
+        Err(_) => (path, None),
+    }
+}
+
+fn jump_to(content: &mut text_editor::Content, line: usize) {
+    content.edit(text_editor::Action::Move(
+        text_editor::Motion::DocumentStart,
+    ));
+
+    while content.cursor_position().0 < line {
+        let previous = content.cursor_position();
+        content.edit(text_editor::Action::Move(text_editor::Motion::Down));
+
+        if content.cursor_position() == previous {
+            break;
+        }
+    }
+
+    content.edit(text_editor::Action::Move(text_editor::Motion::Home));
+}
+
+fn append(content: &mut text_editor::Content, text: String) {
+    let (line, column) = content.cursor_position();
+    let is_following = line + 1 >= content.line_count();
+
+    content.edit(text_editor::Action::Move(text_editor::Motion::DocumentEnd));
+    content.edit(text_editor::Action::Edit(text_editor::Edit::Paste(
+        Arc::new(text),
+    )));
+
+    if !is_following {
+        move_to(content, line, column);
+    }
+}
+
+/// Moves the cursor to a character column, the way `cursor_position` counts them.
+fn move_to(content: &mut text_editor::Content, line: usize, column: usize) {
+    let Some(text) = content.line(line).map(|text| text.to_string()) else {
+        return;
+    };
+
+    jump_to(content, line);
+
+    for _ in text.chars().take(column) {
+        content.edit(text_editor::Action::Move(text_editor::Motion::Right));
+    }
+}
+
+/// Moves the cursor to a byte offset within a line.
+fn move_to_byte(content: &mut text_editor::Content, line: usize, byte: usize) {
+    let Some(text) = content.line(line).map(|text| text.to_string()) else {
+        return;
+    };
+
+    move_to(content, line, char_column(&text, byte));
+}
+
+/// How many characters of the line come before a byte offset into it.
+fn char_column(line: &str, byte: usize) -> usize {
+    line.char_indices()
+        .take_while(|(index, _)| *index < byte)
+        .count()
+}
+
+fn paragraph(content: &text_editor::Content, line: usize) -> std::ops::Range<usize> {
+    let is_blank = |index: usize| {
+        content
+            .line(index)
+            .map_or(true, |text| text.trim().is_empty())
+    };
+
+    if is_blank(line) {
+        return line..line + 1;
+    }
+
+    let start = (0..line)
+        .rev()
+        .find(|index| is_blank(*index))
+        .map_or(0, |index| index + 1);
+    let end = (line + 1..content.line_count())
+        .find(|index| is_blank(*index))
+        .unwrap_or(content.line_count());
+
+    start..end
+}
+
+fn line_offsets(text: &str, lines: std::ops::Range<usize>) -> std::ops::Range<usize> {
+    let mut starts = std::iter::once(0).chain(text.match_indices('\n').map(|(index, _)| index + 1));
+
+    let start = starts.nth(lines.start).unwrap_or(text.len());
+    let end = starts
+        .nth(lines.end - lines.start - 1)
+        .map_or(text.len(), |next| next - 1);
+
+    start..end
+}
+
+/// The bytes of the buffer's text the selection covers, if there is one.
+fn selection_offsets(content: &text_editor::Content) -> Option<std::ops::Range<usize>> {
+    let selection = content.selection()?;
+    let text = content.text();
+    let offset = cursor_offset(content, &text);
+
+    // The cursor sits at whichever end the selection was made towards
+    if text[..offset].ends_with(selection.as_str()) {
+        Some(offset - selection.len()..offset)
+    } else {
+        Some(offset..(offset + selection.len()).min(text.len()))
+    }
+}
+
+/// The lines the selection touches, if there is one.
+fn selected_lines(content: &text_editor::Content) -> Option<std::ops::Range<usize>> {
+    let std::ops::Range { start, end } = selection_offsets(content)?;
+    let text = content.text();
+
+    let first = text[..start].matches('\n').count();
+    let last = text[..end.saturating_sub(1).max(start)]
+        .matches('\n')
+        .count();
+
+    Some(first..last + 1)
+}
+
+fn cursor_offset(content: &text_editor::Content, text: &str) -> usize {
+    let (line, column) = content.cursor_position();
+
+    let start: usize = text.split('\n').take(line).map(|line| line.len() + 1).sum();
+
+    let column = text[start.min(text.len())..]
+        .char_indices()
+        .nth(column)
+        .map_or(text.len() - start.min(text.len()), |(index, _)| index);
+
+    (start + column).min(text.len())
+}
+
+fn select_offsets(content: &mut text_editor::Content, text: &str, range: std::ops::Range<usize>) {
+    let line = text[..range.start].matches('\n').count();
+    let column = range.start - text[..range.start].rfind('\n').map_or(0, |index| index + 1);
+
+    move_to_byte(content, line, column);
+
+    for _ in text[range].chars() {
+        content.edit(text_editor::Action::Select(text_editor::Motion::Right));
+    }
+}
+
+/// The byte offsets the selection was started from and the cursor is at; the same when
+/// nothing is selected.
+fn selection_ends(content: &text_editor::Content, text: &str) -> (usize, usize) {
+    let head = cursor_offset(content, text);
+    let anchor = selection_offsets(content).map_or(head, |selection| {
+        if selection.start == head {
+            selection.end
+        } else {
+            selection.start
+        }
+    });
+
+    (anchor, head)
+}
+
+/// Selects from one byte offset to another, leaving the cursor at `head`.
+fn select_between(content: &mut text_editor::Content, text: &str, anchor: usize, head: usize) {
+    let line = text[..anchor].matches('\n').count();
+    let column = anchor - text[..anchor].rfind('\n').map_or(0, |index| index + 1);
+
+    move_to_byte(content, line, column);
+
+    let motion = if head < anchor {
+        text_editor::Motion::Left
+    } else {
+        text_editor::Motion::Right
+    };
+
+    for _ in text[anchor.min(head)..anchor.max(head)].chars() {
+        content.edit(text_editor::Action::Select(motion));
+    }
+}
+
+/// Replaces a byte range of the buffer with text someone else typed, keeping the local
+/// cursor and selection on the same text around it.
+fn splice(content: &mut text_editor::Content, range: std::ops::Range<usize>, inserted: &str) {
+    let text = content.text();
+    let (anchor, head) = selection_ends(content, &text);
+
+    let shift = |offset: usize| {
+        if offset >= range.end {
+            offset - range.len() + inserted.len()
+        } else {
+            offset.min(range.start)
+        }
+    };
+    let (anchor, head) = (shift(anchor), shift(head));
+
+    select_offsets(content, &text, range.clone());
+
+    if !inserted.is_empty() {
+        content.edit(text_editor::Action::Edit(text_editor::Edit::Paste(
+            Arc::new(inserted.to_string()),
+        )));
+    } else if !range.is_empty() {
+        content.edit(text_editor::Action::Edit(text_editor::Edit::Delete));
+    }
+
+    let text = content.text();
+    select_between(content, &text, anchor, head);
+}
+
+/// The local cursor and selection, as collaborators are shown them.
+fn caret(content: &text_editor::Content) -> collab::Caret {
+    let text = content.text();
+    let (anchor, head) = selection_ends(content, &text);
+
+    let position = |offset: usize| {
+        let before = &text[..offset];
+        let start = before.rfind('\n').map_or(0, |index| index + 1);
+
+        (
+            before.matches('\n').count(),
+            before[start..].chars().count(),
+        )
+    };
+
+    collab::Caret {
+        head: position(head),
+        anchor: position(anchor),
+    }
+}
+
+/// Puts each row at `column` of the line it lands on, one line after another.
+///
+/// Lines too short to reach the column are padded with spaces, and rows past the last
+/// line get lines of their own.
+fn rectangle(lines: &str, column: usize, rows: &[&str]) -> String {
+    let mut lines = lines.split('\n');
+
+    rows.iter()
+        .map(|row| {
+            let line = lines.next().unwrap_or_default();
+            let split = line
+                .char_indices()
+                .nth(column)
+                .map_or(line.len(), |(index, _)| index);
+            let padding = column.saturating_sub(line.chars().count());
+
+            format!(
+                "{}{}{row}{}",
+                &line[..split],
+                " ".repeat(padding),
+                &line[split..]
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn select_range(content: &mut text_editor::Content, line: usize, range: std::ops::Range<usize>) {
+    let Some(text) = content.line(line).map(|text| text.to_string()) else {
+        return;
+    };
+
+    move_to_byte(content, line, range.start);
+
+    for _ in text[range].chars() {
+        content.edit(text_editor::Action::Select(text_editor::Motion::Right));
+    }
+}
+
+fn icon<'a>(codepoint: char) -> Element<'a, Message> {
+    const ICON_FONT: Font = Font::with_name("editor");
+
+    text(codepoint).font(ICON_FONT).into()
+}
+
+fn action<'a>(
+    content: Element<'a, Message>,
+    on_press: Option<Message>,
+    label: &'a str,
+    style: toolbar::Style,
+) -> Element<'a, Message> {
+    let is_disabled = on_press.is_none();
+    let content: Element<_> = match style {
+        toolbar::Style::Icons => container(content).width(30).center_x().into(),
+        toolbar::Style::IconsAndLabels => row![content, text(label).size(14)].spacing(5).into(),
+    };
+
+    tooltip(
+        button(content)
+            .on_press_maybe(on_press)
+            .padding([5, 10])
+            .style(if is_disabled {
+                theme::Button::Secondary
+            } else {
+                theme::Button::Primary
+            }),
+        label,
+        tooltip::Position::FollowCursor,
+    )
+    .style(theme::Container::Box)
+    .into()
+}
+
+fn toggle<'a>(label: &'a str, is_active: bool, on_press: Message) -> Element<'a, Message> {
+    button(text(label).size(14))
+        .on_press(on_press)
+        .padding([5, 10])
+        .style(if is_active {
+            theme::Button::Primary
+        } else {
+            theme::Button::Secondary
+        })
+        .into()
+}
+
+fn new_icon<'a>() -> Element<'a, Message> {
+    icon('\u{E801}')
+}
+
+fn save_icon<'a>() -> Element<'a, Message> {
+    icon('\u{E800}')
+}
+
+fn open_icon<'a>() -> Element<'a, Message> {
+    icon('\u{E802}')
+}
+
+fn default_file() -> PathBuf {
+    PathBuf::from(format!("{}/src/main.rs", env!("CARGO_MANIFEST_DIR")))
+}
+
+/// Reads a file for the tab with the given id, replacing any read still under way.
+fn open(tasks: &mut tasks::Tasks, tab: usize, path: PathBuf) -> Command<Message> {
+    if viewer::is_image(&path) {
+        tasks.perform(
+            tasks::Kind::Open,
+            Some(tab),
+            viewer::load(path),
+            Message::ImageOpened,
+        )
+    } else {
+        tasks.perform(
+            tasks::Kind::Open,
+            Some(tab),
+            load_file(path),
+            Message::FileOpened,
+        )
+    }
+}
+
+async fn pick_file() -> Result<PathBuf, Error> {
+    let handle = rfd::AsyncFileDialog::new()
+        .set_title("Choose a file to open...")
+        .pick_file()
+        .await
+        .ok_or(Error::DialogClosed)?;
+    Ok(handle.path().to_owned())
+}
+
+async fn save_file(
+    path: Option<PathBuf>,
+    text: String,
+    bom: Option<bom::Bom>,
+) -> Result<PathBuf, Error> {
+    let path = if let Some(path) = path {
+        path
+    } else {
+        rfd::AsyncFileDialog::new()
+            .set_title("Choose a file name...")
+            .save_file()
+            .await
+            .ok_or(Error::DialogClosed)
+            .map(|handle| handle.path().to_owned())?
+    };
+    let bytes = encode(&path, text, bom).await?;
+
+    match offline::retry(|| offline::write(&path, &bytes)).await {
+        Ok(()) => Ok(path),
+        Err(error) if offline::is_transient(&error) => {
+            Err(Error::Queued(offline::stash(&path, &bytes).await?))
+        }
+        Err(error) => Err(Error::IOFailed(error.kind())),
+    }
+}
+
+async fn replace_link(
+    link: PathBuf,
+    text: String,
+    bom: Option<bom::Bom>,
+) -> Result<PathBuf, Error> {
+    tokio::fs::remove_file(&link)
+        .await
+        .map_err(|error| Error::IOFailed(error.kind()))?;
+
+    save_file(Some(link), text, bom).await
+}
+
+/// Saves a copy of a vault buffer, sealed with the key it was opened with.
+async fn save_sealed_copy(
+    key: vault::Key,
+    text: String,
+    bom: Option<bom::Bom>,
+) -> Result<PathBuf, Error> {
+    let path = rfd::AsyncFileDialog::new()
+        .set_title("Choose a file name...")
+        .save_file()
+        .await
+        .ok_or(Error::DialogClosed)?
+        .path()
+        .to_owned();
+
+    let bytes = vault::seal(&key, &bom::encode(text, bom));
+
+    tokio::fs::write(&path, bytes)
+        .await
+        .map(|()| path)
+        .map_err(|error| Error::IOFailed(error.kind()))
+}
+
+async fn save_elevated(
+    path: PathBuf,
+    text: String,
+    bom: Option<bom::Bom>,
+    vault: Option<vault::Key>,
+) -> Result<PathBuf, Error> {
+    // A vault stays sealed, rather than being written back as the plaintext it was opened to
+    let bytes = match vault {
+        Some(key) => vault::seal(&key, &bom::encode(text, bom)),
+        None => encode(&path, text, bom).await?,
+    };
+
+    elevate::write(path, bytes).await
+}
+
+async fn encode(path: &Path, text: String, bom: Option<bom::Bom>) -> Result<Vec<u8>, Error> {
+    let bytes = bom::encode(text, bom);
+
+    if let Some(format) = encryption::Format::detect(path) {
+        return format.encrypt(path, bytes).await;
+    }
+
+    match compression::Format::detect(path) {
+        Some(format) => tokio::task::spawn_blocking(move || format.compress(&bytes))
+            .await
+            .map_err(|_| Error::IOFailed(io::ErrorKind::Other))?
+            .map_err(|error| Error::IOFailed(error.kind())),
+        None => Ok(bytes),
+    }
+}
+
+async fn load_lossy(path: PathBuf) -> Result<(PathBuf, Arc<String>, lossy::Lossy), Error> {
+    let bytes = tokio::fs::read(&path)
+        .await
+        .map_err(|error| Error::IOFailed(error.kind()))?;
+
+    let (text, lossy) = tokio::task::spawn_blocking(move || lossy::decode(&bytes))
+        .await
+        .map_err(|_| Error::IOFailed(io::ErrorKind::Other))?;
+
+    Ok((path, Arc::new(text), lossy))
+}
+
+async fn load_file(path: PathBuf) -> Result<(PathBuf, Arc<String>, Option<bom::Bom>), Error> {
+    let bytes = offline::retry(|| tokio::fs::read(&path))
+        .await
+        .map_err(|error| Error::IOFailed(error.kind()))?;
+
+    let bytes = match compression::Format::detect(&path) {
+        Some(format) => tokio::task::spawn_blocking(move || format.decompress(&bytes))
+            .await
+            .map_err(|_| Error::IOFailed(io::ErrorKind::Other))?
+            .map_err(|error| Error::IOFailed(error.kind()))?,
+        None => match encryption::Format::detect(&path) {
+            Some(format) => format.decrypt(bytes).await?,
+            None => bytes,
+        },
+    };
+
+    if vault::is_sealed(&bytes) {
+        return Err(Error::Sealed(path));
+    }
+
+    let (contents, bom) = bom::decode(bytes).map_err(|error| Error::IOFailed(error.kind()))?;
+    Ok((path, Arc::new(contents), bom))
+}
+#[derive(Debug, Clone)]
+enum Error {
+    DialogClosed,
+    IOFailed(io::ErrorKind),
+    RemoteFailed(String),
+    InvalidConfig(String),
+    /// The file was encrypted in the editor and needs its passphrase
+    Sealed(PathBuf),
+    /// The file changed on disk since it was opened or last saved
+    Conflict(PathBuf),
+    /// The file's mount stopped answering, so the save waits on local disk
+    Queued(offline::Entry),
+}
+
+impl Error {
+    fn message(&self) -> Option<String> {
+        match self {
+            Error::DialogClosed => None,
+            Error::IOFailed(error) => Some(error.to_string()),
+            Error::RemoteFailed(error) => Some(error.clone()),
+            Error::InvalidConfig(error) => Some(format!("invalid config: {error}")),
+            Error::Sealed(_) | Error::Conflict(_) | Error::Queued(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn char_columns_count_characters_before_a_byte_offset() {
+        let line = "naïve café = ok";
+
+        assert_eq!(char_column(line, 0), 0);
+        assert_eq!(char_column(line, "naï".len()), 3);
+        assert_eq!(char_column(line, "naïve café".len()), 10);
+        assert_eq!(char_column(line, line.len() + 10), line.chars().count());
+    }
+
+    #[test]
+    fn moving_past_multibyte_text_stays_on_the_line() {
+        let mut content = text_editor::Content::with("// 日本語 ünïcödé\nnext");
+
+        // Column 4 falls inside the first multibyte character's bytes
+        move_to(&mut content, 0, 4);
+        assert_eq!(content.cursor_position().0, 0);
+
+        move_to(&mut content, 0, 100);
+        assert_eq!(content.cursor_position().0, 0);
+
+        move_to_byte(&mut content, 0, "// 日本".len());
+        assert_eq!(content.cursor_position().0, 0);
+    }
 }
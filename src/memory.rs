@@ -0,0 +1,136 @@
+use crate::{properties, tabs, viewer, Message};
+
+use iced::theme;
+use iced::widget::{button, column, container, row, scrollable, text, text_editor, Column};
+use iced::{Element, Length};
+
+/// Used while `memory_budget` is unset.
+pub const DEFAULT_BUDGET: u64 = 512 * 1024 * 1024;
+
+/// Bytes the editor keeps per byte of text, for its lines and their layout.
+const TEXT_OVERHEAD: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    Active,
+    Loaded,
+    /// Its text was dropped and is read again when the tab is shown
+    Evicted,
+}
+
+#[derive(Debug, Clone)]
+pub struct Usage {
+    pub title: String,
+    pub bytes: usize,
+    pub state: State,
+}
+
+/// A rough figure for what a buffer holds, since the editor can't report its own use.
+pub fn estimate(content: &text_editor::Content, image: Option<&viewer::Image>) -> usize {
+    let text: usize = (0..content.line_count())
+        .filter_map(|line| content.line(line))
+        .map(|line| line.len() + 1)
+        .sum();
+
+    let decoded = image
+        .and_then(|image| image.dimensions)
+        .map_or(0, |(width, height)| width as usize * height as usize * 4);
+
+    text * TEXT_OVERHEAD + decoded
+}
+
+/// Whether a background buffer can be dropped and read back from disk unchanged.
+pub fn is_evictable(buffer: &tabs::Buffer) -> bool {
+    buffer.is_loaded
+        && !buffer.is_dirty
+        && buffer.path.is_some()
+        && buffer.remote.is_none()
+        && buffer.url.is_none()
+        && buffer.tail.is_none()
+        && buffer.vault.is_none()
+        && buffer.lossy.is_none()
+        && !buffer.table.as_ref().is_some_and(|table| table.is_modified)
+}
+
+/// The resident size of the whole process, where the platform reports it.
+#[cfg(target_os = "linux")]
+pub fn resident() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+
+    let kilobytes = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))?
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+
+    Some(kilobytes * 1024)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn resident() -> Option<u64> {
+    None
+}
+
+pub fn view<'a>(usage: Vec<Usage>, budget: Option<u64>) -> Element<'a, Message> {
+    let total: usize = usage.iter().map(|usage| usage.bytes).sum();
+
+    let summary = match budget {
+        Some(budget) => format!(
+            "Buffers use about {} of a {} budget",
+            properties::format_size(total as u64),
+            properties::format_size(budget)
+        ),
+        None => format!(
+            "Buffers use about {}, with no budget",
+            properties::format_size(total as u64)
+        ),
+    };
+
+    let process = match resident() {
+        Some(bytes) => format!("Bonkitor uses {} in all", properties::format_size(bytes)),
+        None => String::from("Total use isn't reported on this platform"),
+    };
+
+    let rows = usage.into_iter().map(|usage| {
+        let state = match usage.state {
+            State::Active => "shown",
+            State::Loaded => "loaded",
+            State::Evicted => "unloaded",
+        };
+
+        row![
+            text(usage.title).size(14).width(Length::Fill),
+            text(state).size(13),
+            text(properties::format_size(usage.bytes as u64))
+                .size(14)
+                .width(90),
+        ]
+        .spacing(10)
+        .into()
+    });
+
+    container(
+        column![
+            text("Memory").size(16),
+            text(summary).size(14),
+            text(process).size(14),
+            scrollable(Column::with_children(rows.collect()).spacing(4)).height(Length::Fill),
+            row![
+                button(text("Unload background tabs").size(14))
+                    .on_press(Message::EvictBackgroundTabs),
+                button(text("Close").size(14))
+                    .on_press(Message::ClosePanel)
+                    .style(theme::Button::Secondary),
+            ]
+            .spacing(10),
+        ]
+        .spacing(10),
+    )
+    .width(Length::Fill)
+    .padding(10)
+    .style(theme::Container::Box)
+    .into()
+}
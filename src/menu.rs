@@ -0,0 +1,183 @@
+use crate::Message;
+
+use iced::theme;
+use iced::widget::{button, container, row, scrollable, text, Column, Row, Space};
+use iced::{Color, Element, Length};
+
+const MENU_WIDTH: u16 = 360;
+const MAX_HEIGHT: u16 = 480;
+const SHORTCUT_COLOR: Color = Color::from_rgb(0.5, 0.5, 0.5);
+
+/// The modifier shown in shortcut hints, matching what `Modifiers::command` checks.
+pub const COMMAND: &str = if cfg!(target_os = "macos") {
+    "Cmd"
+} else {
+    "Ctrl"
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Menu {
+    File,
+    Edit,
+    View,
+    Help,
+}
+
+impl Menu {
+    pub const ALL: [Menu; 4] = [Menu::File, Menu::Edit, Menu::View, Menu::Help];
+
+    fn label(self) -> &'static str {
+        match self {
+            Menu::File => "File",
+            Menu::Edit => "Edit",
+            Menu::View => "View",
+            Menu::Help => "Help",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Item {
+    pub label: String,
+    pub shortcut: Option<String>,
+    /// `None` shows the item greyed out
+    pub message: Option<Message>,
+}
+
+pub fn bar<'a>(open: Option<Menu>) -> Element<'a, Message> {
+    let titles = Menu::ALL.into_iter().map(|menu| {
+        let is_open = open == Some(menu);
+
+        button(text(menu.label()).size(14))
+            .on_press(Message::MenuOpened((!is_open).then_some(menu)))
+            .padding([2, 8])
+            .style(if is_open {
+                theme::Button::Primary
+            } else {
+                theme::Button::Text
+            })
+            .into()
+    });
+
+    Row::with_children(titles.collect()).spacing(2).into()
+}
+
+pub fn dropdown<'a>(menu: Menu, items: Vec<Item>) -> Element<'a, Message> {
+    let items = items.into_iter().map(|item| {
+        let shortcut = text(item.shortcut.unwrap_or_default())
+            .size(13)
+            .style(SHORTCUT_COLOR);
+
+        button(row![text(item.label).size(14).width(Length::Fill), shortcut].spacing(20))
+            .on_press_maybe(
+                item.message
+                    .map(|message| Message::MenuRun(Box::new(message))),
+            )
+            .width(Length::Fill)
+            .padding([4, 8])
+            .style(theme::Button::Text)
+            .into()
+    });
+
+    // Line the menu up under its title, which are all about the same width
+    let offset = menu as u16 * 50;
+
+    row![
+        Space::with_width(offset),
+        container(scrollable(Column::with_children(items.collect())))
+            .width(MENU_WIDTH)
+            .max_height(MAX_HEIGHT)
+            .padding(5)
+            .style(theme::Container::Box),
+    ]
+    .into()
+}
+
+/// Which menu a palette command belongs in; `None` keeps it to the palette.
+pub fn place(message: &Message) -> Option<Menu> {
+    let menu = match message {
+        Message::TreeSelected(_) | Message::TodoSelected(..) => return None,
+        Message::ShowAbout => Menu::Help,
+        Message::New
+        | Message::Open
+        | Message::OpenFolder
+        | Message::SwitchWorkspace(_)
+        | Message::ToggleSessions
+        | Message::OpenRemote
+        | Message::Save
+        | Message::ToggleScratch
+        | Message::ToggleCollab
+        | Message::ToggleSync
+        | Message::ShowProperties
+        | Message::CopyChecksum
+        | Message::CopyPermalink
+        | Message::CheckPatch
+        | Message::RunWorkspaceTask(_)
+        | Message::StartDebugging(_)
+        | Message::DebugControl(_)
+        | Message::ToggleChecksums
+        | Message::SetBom(_)
+        | Message::ToggleFileLocking
+        | Message::EvictBackgroundTabs
+        | Message::MergeFiles
+        | Message::StartMerge
+        | Message::EncryptFile
+        | Message::DecryptFile => Menu::File,
+        Message::ToggleTodos
+        | Message::ShowStatistics
+        | Message::ToggleWritingGoals
+        | Message::ReadAloud
+        | Message::ToggleStatusSettings
+        | Message::ToggleToolbarSettings
+        | Message::ToggleFocusMode
+        | Message::ToggleTypewriter
+        | Message::ToggleDimParagraphs
+        | Message::ToggleInspector
+        | Message::WrapAt(_)
+        | Message::TogglePerformanceHud
+        | Message::ShowMemory
+        | Message::ToggleInlineBlame
+        | Message::ToggleBlameGutter
+        | Message::ToggleHistory
+        | Message::ToggleChanges
+        | Message::ShowOutput
+        | Message::ShowProblems
+        | Message::ToggleRepls
+        | Message::ToggleRegexTester
+        | Message::ToggleThemeEditor
+        | Message::ImportTheme
+        | Message::CustomThemeSelected(_)
+        | Message::ToggleCalculation
+        | Message::ToggleDebugPanel
+        | Message::SetReducedMotion(_)
+        | Message::TogglePreview
+        | Message::ToggleOutline
+        | Message::ToggleColors
+        | Message::ToggleTrayIcon
+        | Message::ToggleMinimizeToTray
+        | Message::ThemeSelected(_) => Menu::View,
+        _ => Menu::Edit,
+    };
+
+    Some(menu)
+}
+
+/// The keyboard shortcut bound to a message, for showing beside it.
+pub fn shortcut(message: &Message) -> Option<String> {
+    let shortcut = match message {
+        Message::Save => format!("{COMMAND}+S"),
+        Message::JoinLines => format!("{COMMAND}+J"),
+        Message::Cut => format!("{COMMAND}+X"),
+        Message::Copy => format!("{COMMAND}+C"),
+        Message::Paste => format!("{COMMAND}+V"),
+        Message::SelectAll => format!("{COMMAND}+A"),
+        Message::ToggleSearch => format!("{COMMAND}+Shift+F"),
+        Message::TogglePalette => format!("{COMMAND}+Shift+P"),
+        Message::CycleTabs(_) => String::from("Ctrl+Tab"),
+        Message::CycleFocus(_) => String::from("F6"),
+        Message::ToggleFocusMode => String::from("F11"),
+        _ => return None,
+    };
+
+    Some(shortcut)
+}
@@ -0,0 +1,519 @@
+use crate::{Error, Message};
+
+use iced::theme;
+use iced::widget::scrollable::{self, RelativeOffset};
+use iced::widget::{button, column, container, row, text, Column};
+use iced::{Color, Command, Element, Length};
+use similar::{DiffOp, TextDiff};
+use std::path::{Path, PathBuf};
+
+const OURS_MARKER: &str = "<<<<<<<";
+const BASE_MARKER: &str = "|||||||";
+const SEPARATOR: &str = "=======";
+const THEIRS_MARKER: &str = ">>>>>>>";
+const CURRENT_COLOR: Color = Color::from_rgb(0.95, 0.7, 0.2);
+const RESOLVED_COLOR: Color = Color::from_rgb(0.4, 0.75, 0.45);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Choice {
+    Ours,
+    Base,
+    Theirs,
+    /// Ours followed by theirs
+    Both,
+}
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Settled(Vec<String>),
+    Conflict {
+        ours: Vec<String>,
+        /// Missing when conflict markers came without the common ancestor
+        base: Option<Vec<String>>,
+        theirs: Vec<String>,
+        choice: Option<Choice>,
+    },
+}
+
+/// A merge in progress, shown in place of the editor until it is written or cancelled.
+#[derive(Debug, Clone)]
+pub struct Merge {
+    pub target: PathBuf,
+    segments: Vec<Segment>,
+    labels: (String, String),
+    /// Index among the conflicts of the one being looked at
+    current: usize,
+}
+
+pub fn scrollable_id() -> scrollable::Id {
+    scrollable::Id::new("merge")
+}
+
+/// Whether the text has git's conflict markers in it.
+pub fn has_markers(text: &str) -> bool {
+    text.lines().any(|line| line.starts_with(OURS_MARKER))
+        && text.lines().any(|line| line.starts_with(THEIRS_MARKER))
+}
+
+impl Merge {
+    /// Splits text written by git, with or without the `diff3` base section.
+    pub fn from_markers(target: PathBuf, text: &str) -> Self {
+        let mut segments = Vec::new();
+        let mut settled = Vec::new();
+        let mut lines = text.lines();
+        let mut labels = (String::from("ours"), String::from("theirs"));
+
+        while let Some(line) = lines.next() {
+            let Some(label) = line.strip_prefix(OURS_MARKER) else {
+                settled.push(line.to_string());
+                continue;
+            };
+
+            if !label.trim().is_empty() {
+                labels.0 = label.trim().to_string();
+            }
+
+            let mut ours = Vec::new();
+            let mut base: Option<Vec<String>> = None;
+            let mut theirs = Vec::new();
+            let mut section = 0;
+
+            for line in lines.by_ref() {
+                if let Some(label) = line.strip_prefix(THEIRS_MARKER) {
+                    if !label.trim().is_empty() {
+                        labels.1 = label.trim().to_string();
+                    }
+
+                    break;
+                }
+
+                if line.starts_with(BASE_MARKER) && section == 0 {
+                    base = Some(Vec::new());
+                    section = 1;
+                } else if line == SEPARATOR && section < 2 {
+                    section = 2;
+                } else {
+                    match (section, &mut base) {
+                        (0, _) => ours.push(line.to_string()),
+                        (1, Some(base)) => base.push(line.to_string()),
+                        _ => theirs.push(line.to_string()),
+                    }
+                }
+            }
+
+            segments.push(Segment::Settled(std::mem::take(&mut settled)));
+            segments.push(Segment::Conflict {
+                ours,
+                base,
+                theirs,
+                choice: None,
+            });
+        }
+
+        segments.push(Segment::Settled(settled));
+
+        Self::new(target, segments, labels)
+    }
+
+    /// Merges two versions of a common ancestor, keeping changes made on one side only.
+    pub fn from_versions(target: PathBuf, base: &str, ours: &str, theirs: &str) -> Self {
+        let base_lines: Vec<String> = base.lines().map(str::to_string).collect();
+        let ours_lines: Vec<String> = ours.lines().map(str::to_string).collect();
+        let theirs_lines: Vec<String> = theirs.lines().map(str::to_string).collect();
+
+        let ours_hunks = hunks(base, ours, &ours_lines);
+        let theirs_hunks = hunks(base, theirs, &theirs_lines);
+
+        let mut all: Vec<(usize, &Hunk)> = ours_hunks
+            .iter()
+            .map(|hunk| (0, hunk))
+            .chain(theirs_hunks.iter().map(|hunk| (1, hunk)))
+            .collect();
+        all.sort_by_key(|(_, hunk)| (hunk.start, hunk.end));
+
+        let mut segments = Vec::new();
+        let mut position = 0;
+        let mut pending = all.into_iter().peekable();
+
+        while let Some((side, first)) = pending.next() {
+            // Changes that overlap or touch on the base are looked at together
+            let mut group = vec![(side, first)];
+            let mut end = first.end;
+
+            while let Some((side, hunk)) = pending.next_if(|(_, hunk)| hunk.start <= end) {
+                end = end.max(hunk.end);
+                group.push((side, hunk));
+            }
+
+            let start = group[0].1.start;
+
+            segments.push(Segment::Settled(base_lines[position..start].to_vec()));
+            position = end;
+
+            let side = |which| {
+                let hunks: Vec<&Hunk> = group
+                    .iter()
+                    .filter(|(side, _)| *side == which)
+                    .map(|(_, hunk)| *hunk)
+                    .collect();
+
+                (!hunks.is_empty()).then(|| apply(&base_lines, start, end, &hunks))
+            };
+
+            let base = base_lines[start..end].to_vec();
+
+            match (side(0), side(1)) {
+                (Some(ours), None) => segments.push(Segment::Settled(ours)),
+                (None, Some(theirs)) => segments.push(Segment::Settled(theirs)),
+                (Some(ours), Some(theirs)) if ours == theirs => {
+                    segments.push(Segment::Settled(ours));
+                }
+                (Some(ours), Some(theirs)) => segments.push(Segment::Conflict {
+                    ours,
+                    base: Some(base),
+                    theirs,
+                    choice: None,
+                }),
+                (None, None) => {}
+            }
+        }
+
+        segments.push(Segment::Settled(base_lines[position..].to_vec()));
+
+        Self::new(
+            target,
+            segments,
+            (String::from("ours"), String::from("theirs")),
+        )
+    }
+
+    fn new(target: PathBuf, segments: Vec<Segment>, labels: (String, String)) -> Self {
+        Self {
+            target,
+            segments: segments
+                .into_iter()
+                .filter(|segment| !matches!(segment, Segment::Settled(lines) if lines.is_empty()))
+                .collect(),
+            labels,
+            current: 0,
+        }
+    }
+
+    pub fn choose(&mut self, index: usize, choice: Choice) {
+        if let Some(Segment::Conflict { choice: chosen, .. }) = self.segments.get_mut(index) {
+            *chosen = Some(choice);
+        }
+
+        // Carry on to the next conflict still open
+        if let Some(next) = self
+            .conflicts()
+            .iter()
+            .position(|index| self.is_open(*index))
+        {
+            self.current = next;
+        }
+    }
+
+    /// Moves to another conflict and scrolls it into view.
+    pub fn navigate(&mut self, step: i32) -> Command<Message> {
+        let conflicts = self.conflicts();
+
+        if conflicts.is_empty() {
+            return Command::none();
+        }
+
+        self.current = (self.current as i32 + step).rem_euclid(conflicts.len() as i32) as usize;
+
+        let y = conflicts[self.current] as f32 / self.segments.len().max(1) as f32;
+
+        scrollable::snap_to(scrollable_id(), RelativeOffset { x: 0.0, y })
+    }
+
+    pub fn unresolved(&self) -> usize {
+        self.conflicts()
+            .into_iter()
+            .filter(|index| self.is_open(*index))
+            .count()
+    }
+
+    /// The merged text, with markers left around any conflict still unresolved.
+    pub fn result(&self) -> String {
+        let mut lines: Vec<String> = Vec::new();
+
+        for segment in &self.segments {
+            match segment {
+                Segment::Settled(settled) => lines.extend(settled.iter().cloned()),
+                Segment::Conflict {
+                    ours,
+                    base,
+                    theirs,
+                    choice,
+                } => match choice {
+                    Some(Choice::Ours) => lines.extend(ours.iter().cloned()),
+                    Some(Choice::Theirs) => lines.extend(theirs.iter().cloned()),
+                    Some(Choice::Both) => {
+                        lines.extend(ours.iter().cloned());
+                        lines.extend(theirs.iter().cloned());
+                    }
+                    Some(Choice::Base) => lines.extend(base.iter().flatten().cloned()),
+                    None => {
+                        lines.push(format!("{OURS_MARKER} {}", self.labels.0));
+                        lines.extend(ours.iter().cloned());
+
+                        if let Some(base) = base {
+                            lines.push(BASE_MARKER.to_string());
+                            lines.extend(base.iter().cloned());
+                        }
+
+                        lines.push(SEPARATOR.to_string());
+                        lines.extend(theirs.iter().cloned());
+                        lines.push(format!("{THEIRS_MARKER} {}", self.labels.1));
+                    }
+                },
+            }
+        }
+
+        let mut text = lines.join("\n");
+        text.push('\n');
+        text
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let conflicts = self.conflicts();
+        let current = conflicts.get(self.current).copied();
+
+        let status = match (conflicts.len(), self.unresolved()) {
+            (0, _) => String::from("No conflicts, the versions merge cleanly"),
+            (total, 0) => format!("All {total} conflicts resolved"),
+            (total, open) => format!(
+                "Conflict {} of {total}, {open} unresolved",
+                self.current + 1
+            ),
+        };
+
+        let controls = row![
+            text(format!("Merging into {}", file_name(&self.target))).size(14),
+            text(status).size(14).width(Length::Fill),
+            button(text("Previous").size(14))
+                .on_press(Message::MergeNavigated(-1))
+                .style(theme::Button::Secondary),
+            button(text("Next").size(14))
+                .on_press(Message::MergeNavigated(1))
+                .style(theme::Button::Secondary),
+            button(text("Write result").size(14)).on_press(Message::WriteMerge),
+            button(text("Cancel").size(14))
+                .on_press(Message::CancelMerge)
+                .style(theme::Button::Secondary),
+        ]
+        .spacing(10);
+
+        let segments = self
+            .segments
+            .iter()
+            .enumerate()
+            .map(|(index, segment)| match segment {
+                Segment::Settled(lines) => pane(lines, None),
+                Segment::Conflict {
+                    ours,
+                    base,
+                    theirs,
+                    choice,
+                } => {
+                    let color = match choice {
+                        Some(_) => RESOLVED_COLOR,
+                        None if Some(index) == current => CURRENT_COLOR,
+                        None => Color::from_rgb(0.9, 0.3, 0.3),
+                    };
+
+                    let take = |label, option: Choice| {
+                        button(text(label).size(13))
+                            .on_press(Message::MergeChose(index, option))
+                            .padding([2, 6])
+                            .style(if *choice == Some(option) {
+                                theme::Button::Primary
+                            } else {
+                                theme::Button::Secondary
+                            })
+                    };
+
+                    let mut buttons = row![
+                        take(format!("Take {}", self.labels.0), Choice::Ours),
+                        take(format!("Take {}", self.labels.1), Choice::Theirs),
+                        take(String::from("Take both"), Choice::Both),
+                    ]
+                    .spacing(5);
+
+                    if base.is_some() {
+                        buttons = buttons.push(take(String::from("Take base"), Choice::Base));
+                    }
+
+                    let empty = Vec::new();
+
+                    column![
+                        row![
+                            pane(ours, Some(&self.labels.0)),
+                            pane(base.as_ref().unwrap_or(&empty), Some("base")),
+                            pane(theirs, Some(&self.labels.1)),
+                        ]
+                        .spacing(5),
+                        buttons,
+                    ]
+                    .spacing(5)
+                    .into()
+                }
+            });
+
+        column![
+            controls,
+            scrollable::Scrollable::new(Column::with_children(segments.collect()).spacing(8))
+                .id(scrollable_id())
+                .height(Length::Fill),
+        ]
+        .spacing(10)
+        .padding(5)
+        .into()
+    }
+
+    fn conflicts(&self) -> Vec<usize> {
+        self.segments
+            .iter()
+            .enumerate()
+            .filter(|(_, segment)| matches!(segment, Segment::Conflict { .. }))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    fn is_open(&self, index: usize) -> bool {
+        matches!(
+            self.segments.get(index),
+            Some(Segment::Conflict { choice: None, .. })
+        )
+    }
+}
+
+fn pane<'a>(lines: &[String], title: Option<&str>) -> Element<'a, Message> {
+    let body = text(lines.join("\n")).size(13);
+
+    let content: Element<_> = match title {
+        Some(title) => column![text(title.to_string()).size(12), body]
+            .spacing(2)
+            .into(),
+        None => body.into(),
+    };
+
+    container(content)
+        .width(Length::Fill)
+        .padding(5)
+        .style(theme::Container::Box)
+        .into()
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+/// A run of base lines replaced on one side.
+#[derive(Debug)]
+struct Hunk {
+    start: usize,
+    end: usize,
+    lines: Vec<String>,
+}
+
+fn hunks(base: &str, side: &str, side_lines: &[String]) -> Vec<Hunk> {
+    TextDiff::from_lines(base, side)
+        .ops()
+        .iter()
+        .filter_map(|op| {
+            let (old, new) = match *op {
+                DiffOp::Equal { .. } => return None,
+                DiffOp::Delete {
+                    old_index,
+                    old_len,
+                    new_index,
+                } => (old_index..old_index + old_len, new_index..new_index),
+                DiffOp::Insert {
+                    old_index,
+                    new_index,
+                    new_len,
+                } => (old_index..old_index, new_index..new_index + new_len),
+                DiffOp::Replace {
+                    old_index,
+                    old_len,
+                    new_index,
+                    new_len,
+                } => (
+                    old_index..old_index + old_len,
+                    new_index..new_index + new_len,
+                ),
+            };
+
+            Some(Hunk {
+                start: old.start,
+                end: old.end,
+                lines: side_lines[new].to_vec(),
+            })
+        })
+        .collect()
+}
+
+/// One side's lines for the base range, its hunks applied over the base.
+fn apply(base: &[String], start: usize, end: usize, hunks: &[&Hunk]) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut position = start;
+
+    for hunk in hunks {
+        lines.extend(base[position..hunk.start].iter().cloned());
+        lines.extend(hunk.lines.iter().cloned());
+        position = hunk.end;
+    }
+
+    lines.extend(base[position..end].iter().cloned());
+    lines
+}
+
+pub async fn pick() -> Result<Merge, Error> {
+    let mut paths = Vec::new();
+
+    for title in [
+        "Choose the common ancestor...",
+        "Choose your version...",
+        "Choose their version...",
+    ] {
+        let handle = rfd::AsyncFileDialog::new()
+            .set_title(title)
+            .pick_file()
+            .await
+            .ok_or(Error::DialogClosed)?;
+
+        paths.push(handle.path().to_owned());
+    }
+
+    let mut texts = Vec::new();
+
+    for path in &paths {
+        texts.push(
+            tokio::fs::read_to_string(path)
+                .await
+                .map_err(|error| Error::IOFailed(error.kind()))?,
+        );
+    }
+
+    // Like git's mergetool, the result replaces your version
+    Ok(Merge::from_versions(
+        paths[1].clone(),
+        &texts[0],
+        &texts[1],
+        &texts[2],
+    ))
+}
+
+pub async fn write(path: PathBuf, text: String) -> Result<PathBuf, Error> {
+    tokio::fs::write(&path, text)
+        .await
+        .map_err(|error| Error::IOFailed(error.kind()))?;
+
+    Ok(path)
+}
@@ -0,0 +1,70 @@
+use std::process::Stdio;
+use tokio::process::Command;
+
+/// Whether the desktop asks applications to keep movement to a minimum.
+///
+/// Any failure to read the setting counts as no preference.
+pub async fn system_prefers_reduced() -> bool {
+    preference().await.unwrap_or(false)
+}
+
+#[cfg(target_os = "linux")]
+async fn preference() -> Option<bool> {
+    let output = read(Command::new("gsettings").args([
+        "get",
+        "org.gnome.desktop.interface",
+        "enable-animations",
+    ]))
+    .await?;
+
+    Some(output.trim() == "false")
+}
+
+#[cfg(target_os = "macos")]
+async fn preference() -> Option<bool> {
+    let output =
+        read(Command::new("defaults").args(["read", "com.apple.universalaccess", "reduceMotion"]))
+            .await?;
+
+    Some(output.trim() == "1")
+}
+
+#[cfg(windows)]
+async fn preference() -> Option<bool> {
+    // "Animate controls and elements inside windows" in the accessibility settings
+    let output = read(Command::new("reg").args([
+        "query",
+        r"HKCU\Control Panel\Desktop\WindowMetrics",
+        "/v",
+        "MinAnimate",
+    ]))
+    .await?;
+
+    let value = output
+        .lines()
+        .find(|line| line.contains("MinAnimate"))?
+        .split_whitespace()
+        .last()?;
+
+    Some(value == "0")
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+async fn preference() -> Option<bool> {
+    None
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos", windows))]
+async fn read(command: &mut Command) -> Option<String> {
+    let output = command
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .await
+        .ok()?;
+
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
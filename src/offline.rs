@@ -0,0 +1,242 @@
+use crate::Error;
+
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const QUEUE_FILE: &str = "offline.toml";
+/// How long one read or write may take before it counts as timed out.
+const ATTEMPT_TIMEOUT: Duration = Duration::from_secs(10);
+const RETRIES: usize = 2;
+const BACKOFF: Duration = Duration::from_millis(500);
+
+/// How long opening or saving runs before the status bar says it is still working.
+pub const STALL: Duration = Duration::from_secs(1);
+/// How often queued saves are tried again.
+pub const FLUSH_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Failures a network mount gives while it is slow or briefly gone, which may clear up by themselves.
+pub fn is_transient(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::TimedOut
+            | io::ErrorKind::Interrupted
+            | io::ErrorKind::WouldBlock
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::NotConnected
+            | io::ErrorKind::BrokenPipe
+            | io::ErrorKind::HostUnreachable
+            | io::ErrorKind::NetworkUnreachable
+            | io::ErrorKind::NetworkDown
+            | io::ErrorKind::StaleNetworkFileHandle
+            | io::ErrorKind::ResourceBusy
+    )
+}
+
+/// Runs `attempt` with a timeout, trying transient failures again after a growing pause.
+///
+/// A timed-out attempt keeps its blocking thread until the mount answers, so it can still
+/// finish after a later one. Saves go through [`write`] so such an attempt can't cut the file short.
+pub async fn retry<T, F, Fut>(mut attempt: F) -> io::Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = io::Result<T>>,
+{
+    let mut delay = BACKOFF;
+    let mut retries = 0;
+
+    loop {
+        let result = tokio::time::timeout(ATTEMPT_TIMEOUT, attempt())
+            .await
+            .unwrap_or_else(|_| Err(io::ErrorKind::TimedOut.into()));
+
+        match result {
+            Err(error) if retries < RETRIES && is_transient(&error) => {
+                tokio::time::sleep(delay).await;
+
+                delay *= 2;
+                retries += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Writes `bytes` to a fresh file next to `path` and renames it into place.
+///
+/// A link is followed so the file it points to changes, and the copy gets the original's
+/// permissions. An attempt abandoned partway only leaves its own temporary file behind.
+pub async fn write(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let target = match tokio::fs::canonicalize(path).await {
+        Ok(target) => target,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => path.to_path_buf(),
+        Err(error) => return Err(error),
+    };
+
+    let permissions = tokio::fs::metadata(&target)
+        .await
+        .ok()
+        .map(|metadata| metadata.permissions());
+
+    let mut name = target.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".bonkitor-save-{}", uuid::Uuid::new_v4()));
+    let temporary = target.with_file_name(name);
+
+    let result = async {
+        tokio::fs::write(&temporary, bytes).await?;
+
+        if let Some(permissions) = permissions {
+            tokio::fs::set_permissions(&temporary, permissions).await?;
+        }
+
+        tokio::fs::rename(&temporary, &target).await
+    }
+    .await;
+
+    if result.is_err() {
+        let _ = tokio::fs::remove_file(&temporary).await;
+    }
+
+    result
+}
+
+/// A save that could not reach its file, kept on local disk until it can.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    pub path: PathBuf,
+    copy: PathBuf,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Queue {
+    entries: Vec<Entry>,
+    #[serde(skip)]
+    pub is_flushing: bool,
+}
+
+impl Queue {
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn entries(&self) -> Vec<Entry> {
+        self.entries.clone()
+    }
+
+    /// Queues a save, returning the older one for the same file that it replaces.
+    pub fn push(&mut self, entry: Entry) -> Option<Entry> {
+        let replaced = self.remove(&entry.path);
+
+        self.entries.push(entry);
+        replaced
+    }
+
+    /// Drops the queued save for `path`, once a newer one has reached the file.
+    pub fn remove(&mut self, path: &Path) -> Option<Entry> {
+        let index = self.entries.iter().position(|entry| entry.path == path)?;
+
+        Some(self.entries.remove(index))
+    }
+
+    /// Adds the saves an earlier run left queued, without overriding any queued since.
+    pub fn restore(&mut self, earlier: Queue) {
+        for entry in earlier.entries {
+            if !self.entries.iter().any(|queued| queued.path == entry.path) {
+                self.entries.push(entry);
+            }
+        }
+    }
+
+    /// Drops saves that were written, keeping any queued again in the meantime.
+    pub fn written(&mut self, written: &[Entry]) {
+        self.entries.retain(|entry| !written.contains(entry));
+    }
+
+    pub async fn load() -> Result<Self, Error> {
+        let Some(path) = directory().map(|directory| directory.join(QUEUE_FILE)) else {
+            return Ok(Self::default());
+        };
+
+        match tokio::fs::read_to_string(&path).await {
+            Ok(text) => {
+                toml::from_str(&text).map_err(|error| Error::InvalidConfig(error.to_string()))
+            }
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(Error::IOFailed(error.kind())),
+        }
+    }
+
+    /// Writes the queue out, deleting the local copies of saves it no longer holds.
+    pub async fn save(self, discarded: Vec<Entry>) -> Result<(), Error> {
+        for entry in discarded {
+            let _ = tokio::fs::remove_file(&entry.copy).await;
+        }
+
+        let directory = directory().ok_or(Error::IOFailed(io::ErrorKind::NotFound))?;
+        let text = toml::to_string_pretty(&self)
+            .map_err(|error| Error::InvalidConfig(error.to_string()))?;
+
+        tokio::fs::create_dir_all(&directory)
+            .await
+            .map_err(|error| Error::IOFailed(error.kind()))?;
+
+        tokio::fs::write(directory.join(QUEUE_FILE), text)
+            .await
+            .map_err(|error| Error::IOFailed(error.kind()))
+    }
+}
+
+/// Keeps what was meant for `path` on local disk, to be written once the mount answers again.
+pub async fn stash(path: &Path, bytes: &[u8]) -> Result<Entry, Error> {
+    let directory = directory().ok_or(Error::IOFailed(io::ErrorKind::NotFound))?;
+    let copy = directory.join(format!("{}.bin", uuid::Uuid::new_v4()));
+
+    tokio::fs::create_dir_all(&directory)
+        .await
+        .map_err(|error| Error::IOFailed(error.kind()))?;
+
+    tokio::fs::write(&copy, bytes)
+        .await
+        .map_err(|error| Error::IOFailed(error.kind()))?;
+
+    Ok(Entry {
+        path: path.to_path_buf(),
+        copy,
+    })
+}
+
+/// Writes each queued save to its file, returning the ones that are done with.
+pub async fn flush(entries: Vec<Entry>) -> Vec<Entry> {
+    let mut written = Vec::new();
+
+    for entry in entries {
+        let bytes = match tokio::fs::read(&entry.copy).await {
+            Ok(bytes) => bytes,
+            // With the copy gone there is nothing left to write
+            Err(_) => {
+                written.push(entry);
+                continue;
+            }
+        };
+
+        if retry(|| write(&entry.path, &bytes)).await.is_ok() {
+            let _ = tokio::fs::remove_file(&entry.copy).await;
+
+            written.push(entry);
+        }
+    }
+
+    written
+}
+
+fn directory() -> Option<PathBuf> {
+    dirs::data_dir().map(|directory| directory.join("bonkitor").join("offline"))
+}
@@ -0,0 +1,114 @@
+use crate::{icons, Message};
+
+use iced::theme;
+use iced::widget::{button, column, container, row, scrollable, text, text_input, Column};
+use iced::{Command, Element, Length};
+
+const INPUT: &str = "palette";
+const MAX_RESULTS: usize = 12;
+
+#[derive(Debug, Clone, Default)]
+pub struct Palette {
+    pub query: String,
+    pub selected: usize,
+}
+
+impl Palette {
+    pub fn focus() -> Command<Message> {
+        text_input::focus(text_input::Id::new(INPUT))
+    }
+
+    pub fn select(&mut self, offset: isize, count: usize) {
+        if count == 0 {
+            self.selected = 0;
+        } else {
+            self.selected = (self.selected as isize + offset).rem_euclid(count as isize) as usize;
+        }
+    }
+
+    pub fn matches(&self, entries: Vec<(String, Message)>) -> Vec<(String, Message)> {
+        let query = self.query.to_lowercase();
+
+        let mut matches: Vec<_> = entries
+            .into_iter()
+            .filter_map(|(label, message)| {
+                let score = score(&label.to_lowercase(), &query)?;
+
+                Some((score, label, message))
+            })
+            .collect();
+
+        matches.sort_by_key(|(score, _, _)| *score);
+        matches.truncate(MAX_RESULTS);
+
+        matches
+            .into_iter()
+            .map(|(_, label, message)| (label, message))
+            .collect()
+    }
+
+    pub fn view(&self, matches: Vec<(String, Message)>) -> Element<'_, Message> {
+        let entries = matches
+            .into_iter()
+            .enumerate()
+            .map(|(index, (label, message))| {
+                // Files from quick open show what kind they are
+                let content: Element<_> = match &message {
+                    Message::TreeSelected(path) => row![icons::file(path), text(label).size(14)]
+                        .spacing(5)
+                        .into(),
+                    _ => text(label).size(14).into(),
+                };
+
+                button(content)
+                    .on_press(message)
+                    .width(Length::Fill)
+                    .padding([4, 8])
+                    .style(if index == self.selected {
+                        theme::Button::Primary
+                    } else {
+                        theme::Button::Text
+                    })
+                    .into()
+            });
+
+        container(
+            column![
+                text_input("Type a command...", &self.query)
+                    .id(text_input::Id::new(INPUT))
+                    .on_input(Message::PaletteChanged)
+                    .on_submit(Message::PaletteSubmitted)
+                    .size(14)
+                    .padding(5),
+                scrollable(Column::with_children(entries.collect())).height(Length::Shrink),
+            ]
+            .spacing(5),
+        )
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
+
+fn score(label: &str, query: &str) -> Option<usize> {
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    if let Some(index) = label.find(query) {
+        return Some(index);
+    }
+
+    let mut characters = label.char_indices();
+    let mut first = None;
+    let mut last = 0;
+
+    for wanted in query.chars() {
+        let (index, _) = characters.find(|(_, c)| *c == wanted)?;
+
+        first.get_or_insert(index);
+        last = index;
+    }
+
+    Some(label.len() + last - first.unwrap_or_default())
+}
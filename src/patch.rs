@@ -0,0 +1,504 @@
+use crate::{Error, Message};
+
+use iced::theme;
+use iced::widget::{button, column, container, row, scrollable, text, Column};
+use iced::{Color, Element, Length};
+use std::path::{Component, Path, PathBuf};
+
+const FAILED_COLOR: Color = Color::from_rgb(0.9, 0.3, 0.3);
+const APPLIED_COLOR: Color = Color::from_rgb(0.4, 0.75, 0.45);
+const HEADER_COLOR: Color = Color::from_rgb(0.55, 0.55, 0.55);
+const HUNK_COLOR: Color = Color::from_rgb(0.35, 0.6, 1.0);
+
+pub fn is_patch(extension: &str) -> bool {
+    extension == "patch" || extension == "diff"
+}
+
+/// Colors a unified diff line by what it does, as the syntax sets only tell headers apart.
+pub fn line_color(line: &str) -> Option<Color> {
+    if line.starts_with("+++") || line.starts_with("---") || line.starts_with("diff ") {
+        Some(HEADER_COLOR)
+    } else if line.starts_with("@@") {
+        Some(HUNK_COLOR)
+    } else if line.starts_with('+') {
+        Some(APPLIED_COLOR)
+    } else if line.starts_with('-') {
+        Some(FAILED_COLOR)
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Line {
+    Context(String),
+    Removed(String),
+    Added(String),
+}
+
+#[derive(Debug, Clone)]
+struct Hunk {
+    /// Where the hunk says it starts in the old file, counted from one
+    start: usize,
+    lines: Vec<Line>,
+    /// The new side ends the file without a line break
+    ends_without_newline: bool,
+}
+
+impl Hunk {
+    fn old(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                Line::Context(text) | Line::Removed(text) => Some(text.as_str()),
+                Line::Added(_) => None,
+            })
+            .collect()
+    }
+
+    fn new(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                Line::Context(text) | Line::Added(text) => Some(text.as_str()),
+                Line::Removed(_) => None,
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct File {
+    /// Missing for a file the patch creates
+    old: Option<String>,
+    /// Missing for a file the patch deletes
+    new: Option<String>,
+    hunks: Vec<Hunk>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Outcome {
+    Applies,
+    /// Applies this many lines away from where the hunk says
+    Moved(isize),
+    Fails(String),
+}
+
+/// What applying one file's part of the patch would do.
+#[derive(Debug, Clone)]
+pub struct Target {
+    pub path: PathBuf,
+    /// The old path of a file the patch renames, removed once the new one is written
+    renamed_from: Option<PathBuf>,
+    hunks: Vec<Outcome>,
+    /// The file's new text, or `None` when the patch deletes it
+    result: Option<String>,
+    is_new: bool,
+    error: Option<String>,
+}
+
+impl Target {
+    fn applies(&self) -> bool {
+        self.error.is_none()
+            && self
+                .hunks
+                .iter()
+                .all(|outcome| !matches!(outcome, Outcome::Fails(_)))
+    }
+}
+
+/// The outcome of a dry run, kept so the files can be written without reading them again.
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub root: PathBuf,
+    targets: Vec<Target>,
+    applied: Option<usize>,
+}
+
+impl Report {
+    pub fn applies(&self) -> bool {
+        !self.targets.is_empty() && self.targets.iter().all(Target::applies)
+    }
+
+    pub fn applied(&mut self, files: usize) {
+        self.applied = Some(files);
+    }
+
+    /// Every path the patch touches, including the old names of renamed files.
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.targets.iter().flat_map(|target| {
+            std::iter::once(target.path.as_path()).chain(target.renamed_from.as_deref())
+        })
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let failed = self
+            .targets
+            .iter()
+            .flat_map(|target| &target.hunks)
+            .filter(|outcome| matches!(outcome, Outcome::Fails(_)))
+            .count();
+
+        let summary = match (self.applied, failed) {
+            (Some(files), _) => format!("Applied to {files} files"),
+            (None, _) if self.targets.is_empty() => {
+                String::from("This buffer has no changes in it to apply")
+            }
+            (None, 0) if self.applies() => String::from("Every hunk applies"),
+            (None, 0) => String::from("Some files can't be changed"),
+            (None, 1) => String::from("1 hunk doesn't apply, so nothing was changed"),
+            (None, failed) => format!("{failed} hunks don't apply, so nothing was changed"),
+        };
+
+        let targets = self.targets.iter().map(|target| {
+            let path = target
+                .path
+                .strip_prefix(&self.root)
+                .unwrap_or(&target.path)
+                .display()
+                .to_string();
+
+            let action = match (&target.result, target.is_new, &target.renamed_from) {
+                (None, _, _) => String::from("delete"),
+                (Some(_), true, _) => String::from("create"),
+                (Some(_), false, Some(old)) => format!(
+                    "rename from {}",
+                    old.strip_prefix(&self.root).unwrap_or(old).display()
+                ),
+                (Some(_), false, None) => String::from("change"),
+            };
+
+            let mut lines = vec![text(format!("{path} ({action})")).size(14).into()];
+
+            if let Some(error) = &target.error {
+                lines.push(text(error).size(13).style(FAILED_COLOR).into());
+            }
+
+            lines.extend(target.hunks.iter().enumerate().map(|(index, outcome)| {
+                let (message, color) = match outcome {
+                    Outcome::Applies => (String::from("applies"), APPLIED_COLOR),
+                    Outcome::Moved(offset) => (
+                        format!("applies {} lines away", offset.unsigned_abs()),
+                        APPLIED_COLOR,
+                    ),
+                    Outcome::Fails(reason) => (reason.clone(), FAILED_COLOR),
+                };
+
+                text(format!("  Hunk {}: {message}", index + 1))
+                    .size(13)
+                    .style(color)
+                    .into()
+            }));
+
+            Column::with_children(lines).spacing(2).into()
+        });
+
+        container(
+            column![
+                text("Apply patch").size(16),
+                text(format!("{summary}, in {}", self.root.display())).size(14),
+                scrollable(Column::with_children(targets.collect()).spacing(8))
+                    .height(Length::Fill),
+                row![
+                    button(text("Apply").size(14)).on_press_maybe(
+                        (self.applied.is_none() && self.applies()).then_some(Message::ApplyPatch)
+                    ),
+                    button(text("Close").size(14))
+                        .on_press(Message::ClosePanel)
+                        .style(theme::Button::Secondary),
+                ]
+                .spacing(10),
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fill)
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
+
+/// Applies the patch to copies of the files under `root`, without writing anything.
+pub async fn check(root: PathBuf, patch: String) -> Result<Report, Error> {
+    let mut targets = Vec::new();
+
+    for file in parse(&patch) {
+        let Some(name) = file.new.as_ref().or(file.old.as_ref()) else {
+            continue;
+        };
+
+        // A patch can name any path, and only the files under the root are its to change
+        if [&file.old, &file.new]
+            .into_iter()
+            .flatten()
+            .any(|name| !is_inside(name))
+        {
+            targets.push(Target {
+                path: PathBuf::from(name),
+                renamed_from: None,
+                hunks: Vec::new(),
+                result: None,
+                is_new: false,
+                error: Some(String::from(
+                    "is outside the folder, so it won't be touched",
+                )),
+            });
+            continue;
+        }
+
+        let path = root.join(name);
+
+        let renamed_from = match (&file.old, &file.new) {
+            (Some(old), Some(new)) if old != new => Some(root.join(old)),
+            _ => None,
+        };
+
+        let original = match &file.old {
+            Some(old) => match tokio::fs::read_to_string(root.join(old)).await {
+                Ok(text) => Some(text),
+                Err(error) => {
+                    targets.push(Target {
+                        path,
+                        renamed_from,
+                        hunks: Vec::new(),
+                        result: None,
+                        is_new: false,
+                        error: Some(format!("can't be read: {error}")),
+                    });
+                    continue;
+                }
+            },
+            None => None,
+        };
+
+        let (hunks, result) = apply(original.as_deref().unwrap_or_default(), &file.hunks);
+
+        let error = match (&file.old, &file.new) {
+            (None, Some(_)) if tokio::fs::try_exists(&path).await.unwrap_or(false) => {
+                Some(String::from("already exists, so it can't be created"))
+            }
+            (Some(_), Some(_))
+                if renamed_from.is_some()
+                    && tokio::fs::try_exists(&path).await.unwrap_or(false) =>
+            {
+                Some(String::from("already exists, so it can't be renamed over"))
+            }
+            _ => None,
+        };
+
+        targets.push(Target {
+            path,
+            renamed_from,
+            hunks,
+            result: file.new.is_some().then_some(result),
+            is_new: file.old.is_none(),
+            error,
+        });
+    }
+
+    Ok(Report {
+        root,
+        targets,
+        applied: None,
+    })
+}
+
+/// Writes every file in a report whose hunks all applied, returning how many changed.
+pub async fn write(report: Report) -> Result<usize, Error> {
+    let mut written = 0;
+
+    for target in report.targets.iter().filter(|target| target.applies()) {
+        match &target.result {
+            Some(text) => {
+                if let Some(parent) = target.path.parent() {
+                    tokio::fs::create_dir_all(parent)
+                        .await
+                        .map_err(|error| Error::IOFailed(error.kind()))?;
+                }
+
+                tokio::fs::write(&target.path, text)
+                    .await
+                    .map_err(|error| Error::IOFailed(error.kind()))?;
+
+                if let Some(old) = &target.renamed_from {
+                    tokio::fs::remove_file(old)
+                        .await
+                        .map_err(|error| Error::IOFailed(error.kind()))?;
+                }
+            }
+            None => tokio::fs::remove_file(&target.path)
+                .await
+                .map_err(|error| Error::IOFailed(error.kind()))?,
+        }
+
+        written += 1;
+    }
+
+    Ok(written)
+}
+
+fn apply(original: &str, hunks: &[Hunk]) -> (Vec<Outcome>, String) {
+    let mut lines: Vec<&str> = original.lines().collect();
+    let mut ends_with_newline = original.is_empty() || original.ends_with('\n');
+    let mut outcomes = Vec::with_capacity(hunks.len());
+    // How far earlier hunks moved the lines after them
+    let mut shift: isize = 0;
+
+    for hunk in hunks {
+        let old = hunk.old();
+        let new = hunk.new();
+
+        let expected = (hunk.start.max(1) as isize - 1 + shift).max(0) as usize;
+
+        let Some(at) = find(&lines, &old, expected) else {
+            outcomes.push(Outcome::Fails(format!(
+                "its lines aren't near line {}",
+                expected + 1
+            )));
+            continue;
+        };
+
+        let is_last = at + old.len() == lines.len();
+
+        lines.splice(at..at + old.len(), new.iter().copied());
+        shift += new.len() as isize - old.len() as isize;
+
+        if is_last {
+            ends_with_newline = !hunk.ends_without_newline;
+        }
+
+        outcomes.push(match at as isize - expected as isize {
+            0 => Outcome::Applies,
+            offset => Outcome::Moved(offset),
+        });
+    }
+
+    let mut result = lines.join("\n");
+
+    if ends_with_newline && !lines.is_empty() {
+        result.push('\n');
+    }
+
+    (outcomes, result)
+}
+
+/// Where `old` appears in `lines`, looking outwards from `expected`.
+fn find(lines: &[&str], old: &[&str], expected: usize) -> Option<usize> {
+    let fits = |at: usize| {
+        at + old.len() <= lines.len()
+            && lines[at..at + old.len()]
+                .iter()
+                .zip(old)
+                .all(|(line, old)| line.trim_end_matches('\r') == old.trim_end_matches('\r'))
+    };
+
+    let last = lines.len().saturating_sub(old.len());
+
+    (0..=last.max(expected)).find_map(|distance| {
+        [
+            expected.checked_sub(distance),
+            expected.checked_add(distance),
+        ]
+        .into_iter()
+        .flatten()
+        .find(|at| *at <= last && fits(*at))
+    })
+}
+
+fn parse(patch: &str) -> Vec<File> {
+    let mut files: Vec<File> = Vec::new();
+    let mut lines = patch.lines().peekable();
+
+    while let Some(line) = lines.next() {
+        if let Some(old) = line.strip_prefix("--- ") {
+            let Some(new) = lines.next_if(|line| line.starts_with("+++ ")) else {
+                continue;
+            };
+
+            files.push(File {
+                old: name(old),
+                new: name(&new[4..]),
+                hunks: Vec::new(),
+            });
+        } else if let (Some(header), Some(file)) = (line.strip_prefix("@@ -"), files.last_mut()) {
+            let (start, mut old) = range(header);
+            let (_, mut new) = range(header.split_once(" +").map_or("", |(_, new)| new));
+
+            let mut hunk = Hunk {
+                start,
+                lines: Vec::new(),
+                ends_without_newline: false,
+            };
+
+            // The header's counts say where the hunk ends, as blank context may have lost its space
+            while old > 0 || new > 0 {
+                let Some(line) = lines.next() else {
+                    break;
+                };
+
+                match line.split_at(line.len().min(1)) {
+                    ("+", text) => {
+                        hunk.lines.push(Line::Added(text.to_string()));
+                        new = new.saturating_sub(1);
+                    }
+                    ("-", text) => {
+                        hunk.lines.push(Line::Removed(text.to_string()));
+                        old = old.saturating_sub(1);
+                    }
+                    ("\\", _) => {}
+                    (_, text) => {
+                        hunk.lines.push(Line::Context(text.to_string()));
+                        old = old.saturating_sub(1);
+                        new = new.saturating_sub(1);
+                    }
+                }
+            }
+
+            // Says the last line before it has no line break
+            if lines.next_if(|line| line.starts_with('\\')).is_some()
+                && matches!(hunk.lines.last(), Some(Line::Added(_) | Line::Context(_)))
+            {
+                hunk.ends_without_newline = true;
+            }
+
+            file.hunks.push(hunk);
+        }
+    }
+
+    files
+}
+
+/// The start and length of one side of a hunk header, e.g. `12,5` or just `12`.
+fn range(side: &str) -> (usize, usize) {
+    let side = side.split(' ').next().unwrap_or_default();
+
+    let (start, length) = match side.split_once(',') {
+        Some((start, length)) => (start, length.parse().unwrap_or(0)),
+        None => (side, 1),
+    };
+
+    (start.parse().unwrap_or(1), length)
+}
+
+/// The path a `---`/`+++` header names, without git's `a/` and `b/` or a timestamp.
+fn name(header: &str) -> Option<String> {
+    let path = header.split('\t').next()?.trim();
+
+    if path == "/dev/null" {
+        return None;
+    }
+
+    let path = path
+        .strip_prefix("a/")
+        .or_else(|| path.strip_prefix("b/"))
+        .unwrap_or(path);
+
+    Some(path.to_string())
+}
+
+/// Whether a path from a patch stays under the folder it's applied in.
+fn is_inside(name: &str) -> bool {
+    Path::new(name)
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
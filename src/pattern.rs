@@ -0,0 +1,333 @@
+use crate::Message;
+
+use iced::advanced::text::highlighter::{self, Format};
+use iced::theme;
+use iced::widget::{button, column, container, row, scrollable, text, text_editor, text_input};
+use iced::widget::{Column, Row};
+use iced::{Color, Element, Font, Length};
+use regex::RegexBuilder;
+use std::ops::Range;
+
+const MAX_LISTED: usize = 200;
+const MATCH_COLOR: Color = Color::from_rgb(0.35, 0.6, 1.0);
+const GROUP_COLORS: [Color; 4] = [
+    Color::from_rgb(0.9, 0.6, 0.2),
+    Color::from_rgb(0.4, 0.8, 0.4),
+    Color::from_rgb(0.8, 0.5, 0.9),
+    Color::from_rgb(0.9, 0.4, 0.4),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flag {
+    CaseInsensitive,
+    Multiline,
+    DotMatchesNewline,
+    IgnoreWhitespace,
+}
+
+impl Flag {
+    const ALL: [Flag; 4] = [
+        Flag::CaseInsensitive,
+        Flag::Multiline,
+        Flag::DotMatchesNewline,
+        Flag::IgnoreWhitespace,
+    ];
+
+    fn letter(self) -> char {
+        match self {
+            Flag::CaseInsensitive => 'i',
+            Flag::Multiline => 'm',
+            Flag::DotMatchesNewline => 's',
+            Flag::IgnoreWhitespace => 'x',
+        }
+    }
+}
+
+struct Group {
+    name: String,
+    range: Range<usize>,
+}
+
+struct Found {
+    range: Range<usize>,
+    groups: Vec<Option<Group>>,
+}
+
+pub struct Tester {
+    pub pattern: String,
+    flags: Vec<Flag>,
+    pub sample: text_editor::Content,
+    found: Result<Vec<Found>, String>,
+    /// Pieces of the sample to color, each on one line
+    spans: Vec<(usize, Range<usize>, Color)>,
+}
+
+impl Tester {
+    pub fn new(pattern: String, sample: &str) -> Self {
+        let mut tester = Self {
+            pattern,
+            flags: Vec::new(),
+            sample: text_editor::Content::with(sample),
+            found: Ok(Vec::new()),
+            spans: Vec::new(),
+        };
+
+        tester.run();
+        tester
+    }
+
+    pub fn toggle(&mut self, flag: Flag) {
+        if !self.flags.contains(&flag) {
+            self.flags.push(flag);
+        } else {
+            self.flags.retain(|current| *current != flag);
+        }
+
+        self.run();
+    }
+
+    /// The pattern with its flags written inline, as the search panel takes it.
+    pub fn inline(&self) -> String {
+        let flags: String = Flag::ALL
+            .iter()
+            .filter(|flag| self.flags.contains(flag))
+            .map(|flag| flag.letter())
+            .collect();
+
+        if flags.is_empty() {
+            self.pattern.clone()
+        } else {
+            format!("(?{flags}){}", self.pattern)
+        }
+    }
+
+    /// Matches the pattern against the sample again.
+    pub fn run(&mut self) {
+        let sample = self.sample.text();
+
+        self.spans.clear();
+
+        if self.pattern.is_empty() {
+            self.found = Ok(Vec::new());
+            return;
+        }
+
+        let regex = RegexBuilder::new(&self.pattern)
+            .case_insensitive(self.flags.contains(&Flag::CaseInsensitive))
+            .multi_line(self.flags.contains(&Flag::Multiline))
+            .dot_matches_new_line(self.flags.contains(&Flag::DotMatchesNewline))
+            .ignore_whitespace(self.flags.contains(&Flag::IgnoreWhitespace))
+            .build();
+
+        let regex = match regex {
+            Ok(regex) => regex,
+            Err(error) => {
+                self.found = Err(error.to_string());
+                return;
+            }
+        };
+
+        let names: Vec<Option<&str>> = regex.capture_names().collect();
+
+        let found: Vec<Found> = regex
+            .captures_iter(&sample)
+            .map(|captures| Found {
+                range: captures.get(0).map_or(0..0, |all| all.range()),
+                groups: (1..captures.len())
+                    .map(|index| {
+                        captures.get(index).map(|group| Group {
+                            name: names[index].map_or_else(|| index.to_string(), str::to_string),
+                            range: group.range(),
+                        })
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        let starts: Vec<usize> = std::iter::once(0)
+            .chain(sample.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+
+        for found in &found {
+            split_lines(&starts, found.range.clone(), MATCH_COLOR, &mut self.spans);
+
+            for (index, group) in found.groups.iter().enumerate() {
+                if let Some(group) = group {
+                    let color = GROUP_COLORS[index % GROUP_COLORS.len()];
+
+                    split_lines(&starts, group.range.clone(), color, &mut self.spans);
+                }
+            }
+        }
+
+        self.found = Ok(found);
+    }
+
+    pub fn settings(&self) -> Settings {
+        Settings {
+            spans: self.spans.clone(),
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let flags = Flag::ALL.iter().fold(Row::new().spacing(5), |flags, flag| {
+            flags.push(
+                button(text(flag.letter()).size(14))
+                    .on_press(Message::PatternFlagToggled(*flag))
+                    .style(if self.flags.contains(flag) {
+                        theme::Button::Primary
+                    } else {
+                        theme::Button::Secondary
+                    }),
+            )
+        });
+
+        let sample = text_editor(&self.sample)
+            .on_edit(Message::SampleEdited)
+            .height(160)
+            .highlight::<Highlighter>(self.settings(), |color, _theme| Format {
+                color: Some(*color),
+                font: None::<Font>,
+            });
+
+        let sample_text = self.sample.text();
+
+        let details: Element<'_, Message> = match &self.found {
+            Err(error) => text(error).size(13).style(GROUP_COLORS[3]).into(),
+            Ok(found) => {
+                let mut details = Column::new()
+                    .spacing(4)
+                    .push(text(format!("{} matches", found.len())).size(14));
+
+                for (index, found) in found.iter().take(MAX_LISTED).enumerate() {
+                    details = details.push(
+                        text(format!(
+                            "{}: {:?} at {}..{}",
+                            index + 1,
+                            &sample_text[found.range.clone()],
+                            found.range.start,
+                            found.range.end
+                        ))
+                        .size(13),
+                    );
+
+                    for group in found.groups.iter().flatten() {
+                        details = details.push(
+                            text(format!(
+                                "    {} = {:?}",
+                                group.name,
+                                &sample_text[group.range.clone()]
+                            ))
+                            .size(13),
+                        );
+                    }
+                }
+
+                scrollable(details).height(Length::Fill).into()
+            }
+        };
+
+        container(
+            column![
+                text("Regex tester").size(16),
+                row![
+                    text_input("Pattern", &self.pattern)
+                        .on_input(Message::PatternChanged)
+                        .font(Font::MONOSPACE),
+                    flags,
+                ]
+                .spacing(10),
+                sample,
+                details,
+                row![
+                    button(text("Use in search").size(14)).on_press_maybe(
+                        (self.found.is_ok() && !self.pattern.is_empty())
+                            .then_some(Message::SearchWithPattern)
+                    ),
+                    button(text("Close").size(14))
+                        .on_press(Message::ClosePanel)
+                        .style(theme::Button::Secondary),
+                ]
+                .spacing(10),
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fill)
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
+
+/// Cuts a byte range of the sample into one piece per line it covers.
+fn split_lines(
+    starts: &[usize],
+    range: Range<usize>,
+    color: Color,
+    spans: &mut Vec<(usize, Range<usize>, Color)>,
+) {
+    let first = starts.partition_point(|start| *start <= range.start) - 1;
+
+    for (line, start) in starts.iter().enumerate().skip(first) {
+        if *start > range.end || (*start == range.end && line > first) {
+            break;
+        }
+
+        let end = starts.get(line + 1).map_or(usize::MAX, |next| next - 1);
+        let piece = range.start.max(*start) - start..range.end.min(end) - start;
+
+        if !piece.is_empty() {
+            spans.push((line, piece, color));
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    spans: Vec<(usize, Range<usize>, Color)>,
+}
+
+/// Colors the sample's matches, and each capture group over its match.
+pub struct Highlighter {
+    spans: Vec<(usize, Range<usize>, Color)>,
+    current: usize,
+}
+
+impl highlighter::Highlighter for Highlighter {
+    type Settings = Settings;
+    type Highlight = Color;
+
+    type Iterator<'a> = std::vec::IntoIter<(Range<usize>, Color)>;
+
+    fn new(settings: &Self::Settings) -> Self {
+        Self {
+            spans: settings.spans.clone(),
+            current: 0,
+        }
+    }
+
+    fn update(&mut self, new_settings: &Self::Settings) {
+        self.spans = new_settings.spans.clone();
+        self.current = 0;
+    }
+
+    fn change_line(&mut self, line: usize) {
+        self.current = line;
+    }
+
+    fn highlight_line(&mut self, _line: &str) -> Self::Iterator<'_> {
+        let number = self.current;
+        self.current += 1;
+
+        self.spans
+            .iter()
+            .filter(|(line, ..)| *line == number)
+            .map(|(_, range, color)| (range.clone(), *color))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    fn current_line(&self) -> usize {
+        self.current
+    }
+}
@@ -0,0 +1,138 @@
+use crate::Message;
+
+use iced::widget::text;
+use iced::Element;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// How far back the figures reach.
+const WINDOW: Duration = Duration::from_secs(1);
+
+/// Live timings of frames, updates and views, shown while diagnosing slow typing.
+///
+/// Frame and update times read as average/worst over the last second. It is drawn as a status
+/// bar segment rather than an overlay floating over the editor.
+#[derive(Debug, Default)]
+pub struct Hud {
+    // Shared with timers, since `view` only gets `&self`
+    samples: Rc<RefCell<Samples>>,
+}
+
+#[derive(Debug, Default)]
+struct Samples {
+    frames: VecDeque<Instant>,
+    updates: VecDeque<(Instant, Duration)>,
+    view: Duration,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Stage {
+    Update,
+    View,
+}
+
+/// Records how long it lived once dropped, so early returns are measured too.
+pub struct Timer {
+    started: Instant,
+    stage: Stage,
+    samples: Rc<RefCell<Samples>>,
+}
+
+impl Hud {
+    pub fn time_update(&self) -> Timer {
+        self.timer(Stage::Update)
+    }
+
+    pub fn time_view(&self) -> Timer {
+        self.timer(Stage::View)
+    }
+
+    fn timer(&self, stage: Stage) -> Timer {
+        Timer {
+            started: Instant::now(),
+            stage,
+            samples: Rc::clone(&self.samples),
+        }
+    }
+
+    pub fn frame(&mut self, at: Instant) {
+        let mut samples = self.samples.borrow_mut();
+
+        samples.frames.push_back(at);
+        prune(&mut samples.frames, at, |frame| *frame);
+    }
+
+    pub fn view(&self, lines: usize) -> Element<'_, Message> {
+        let samples = self.samples.borrow();
+
+        let intervals: Vec<Duration> = samples
+            .frames
+            .iter()
+            .zip(samples.frames.iter().skip(1))
+            .map(|(previous, next)| next.duration_since(*previous))
+            .collect();
+
+        let frame = summarize(&intervals);
+        let updates: Vec<Duration> = samples.updates.iter().map(|(_, took)| *took).collect();
+        let update = summarize(&updates);
+
+        text(format!(
+            "frame {:.1}/{:.1} ms · {} updates/s {:.2}/{:.2} ms · view {:.2} ms · {lines} lines",
+            frame.0,
+            frame.1,
+            updates.len(),
+            update.0,
+            update.1,
+            milliseconds(samples.view),
+        ))
+        .size(12)
+        .into()
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        let took = self.started.elapsed();
+        let mut samples = self.samples.borrow_mut();
+
+        match self.stage {
+            Stage::Update => {
+                let now = Instant::now();
+
+                samples.updates.push_back((now, took));
+                prune(&mut samples.updates, now, |(at, _)| *at);
+            }
+            Stage::View => samples.view = took,
+        }
+    }
+}
+
+fn prune<T>(samples: &mut VecDeque<T>, now: Instant, at: impl Fn(&T) -> Instant) {
+    while samples
+        .front()
+        .is_some_and(|sample| now.duration_since(at(sample)) > WINDOW)
+    {
+        samples.pop_front();
+    }
+}
+
+/// The average and worst of the durations, in milliseconds.
+fn summarize(durations: &[Duration]) -> (f64, f64) {
+    if durations.is_empty() {
+        return (0.0, 0.0);
+    }
+
+    let total: Duration = durations.iter().sum();
+    let worst = durations.iter().max().copied().unwrap_or_default();
+
+    (
+        milliseconds(total) / durations.len() as f64,
+        milliseconds(worst),
+    )
+}
+
+fn milliseconds(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
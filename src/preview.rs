@@ -0,0 +1,655 @@
+use crate::{workspace, Error, Message};
+
+use iced::advanced::layout::{self, Layout};
+use iced::advanced::renderer;
+use iced::advanced::widget::{Tree, Widget};
+use iced::mouse;
+use iced::theme;
+use iced::widget::{button, column, container, horizontal_rule, scrollable, svg, text, Column};
+use iced::{Color, Element, Font, Length, Point, Rectangle, Size, Theme};
+use pulldown_cmark::Options;
+use std::path::{Path, PathBuf};
+
+const QUOTE_COLOR: Color = Color::from_rgb(0.55, 0.55, 0.6);
+const INDENT: u16 = 16;
+/// How tall math set within a line of text is drawn
+const INLINE_MATH_HEIGHT: f32 = 18.0;
+// Stands in for an inline formula in a paragraph's text until the paragraph is split up
+const FORMULA: char = '\u{FFFC}';
+
+// Elements whose contents are never shown
+const HIDDEN: &[&str] = &["head", "script", "style", "template", "noscript"];
+const BLOCKS: &[&str] = &[
+    "p",
+    "div",
+    "section",
+    "article",
+    "header",
+    "footer",
+    "main",
+    "nav",
+    "aside",
+    "table",
+    "tr",
+    "dl",
+    "dt",
+    "dd",
+    "figure",
+    "figcaption",
+    "form",
+    "br",
+];
+
+#[derive(Debug, Clone)]
+enum Block {
+    Heading(u8, String),
+    Paragraph(String),
+    /// A paragraph with inline math, split into words so formulas can sit among them
+    Mixed(Vec<Piece>),
+    Item(usize, String),
+    Quote(String),
+    Code(String),
+    Math(String),
+    Diagram(String),
+    Image(svg::Handle),
+    Rule,
+}
+
+#[derive(Debug, Clone)]
+enum Piece {
+    Word(String),
+    Math(String),
+    Image(svg::Handle),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Html,
+    Markdown,
+    ReStructuredText,
+    AsciiDoc,
+}
+
+impl Format {
+    pub fn detect(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "html" | "htm" | "xhtml" => Some(Format::Html),
+            "md" | "markdown" => Some(Format::Markdown),
+            "rst" | "rest" => Some(Format::ReStructuredText),
+            "adoc" | "asciidoc" | "asc" => Some(Format::AsciiDoc),
+            _ => None,
+        }
+    }
+
+    /// The key used for this format in the `preview_converters` setting.
+    pub fn key(self) -> &'static str {
+        match self {
+            Format::Html => "html",
+            Format::Markdown => "markdown",
+            Format::ReStructuredText => "rst",
+            Format::AsciiDoc => "asciidoc",
+        }
+    }
+
+    /// A command that reads this format on stdin and writes HTML to stdout.
+    pub fn default_converter(self) -> Option<&'static str> {
+        match self {
+            Format::Html | Format::Markdown => None,
+            Format::ReStructuredText => Some("pandoc --from rst --to html"),
+            Format::AsciiDoc => Some("asciidoctor --embedded --out-file - -"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Preview {
+    pub path: PathBuf,
+    blocks: Vec<Block>,
+}
+
+pub async fn render(
+    path: PathBuf,
+    format: Format,
+    text: String,
+    converter: Option<String>,
+    math_renderer: Option<String>,
+    diagram_renderer: Option<String>,
+) -> Result<Preview, Error> {
+    let directory = path.parent().map(Path::to_path_buf).unwrap_or_default();
+
+    let html = match (converter, format) {
+        (Some(command), _) => workspace::format(command, directory.clone(), text).await?,
+        (None, Format::Markdown) => markdown(&text),
+        (None, _) => text,
+    };
+
+    let mut blocks = Parser::default().parse(&html);
+
+    // Anything that fails to render keeps its source and is shown as code
+    for block in &mut blocks {
+        if let Block::Mixed(pieces) = block {
+            for piece in pieces.iter_mut() {
+                let Piece::Math(formula) = piece else {
+                    continue;
+                };
+
+                let formula = formula.clone();
+                let image = match &math_renderer {
+                    Some(command) => typeset(command, &formula).await,
+                    None => None,
+                };
+
+                *piece = match image {
+                    Some(image) => Piece::Image(svg::Handle::from_memory(image)),
+                    None => Piece::Word(format!("${formula}$")),
+                };
+            }
+
+            continue;
+        }
+
+        let image = match (&*block, &math_renderer, &diagram_renderer) {
+            (Block::Math(formula), Some(command), _) => typeset(command, formula).await,
+            (Block::Diagram(source), _, Some(command)) => {
+                workspace::format(command.clone(), directory.clone(), source.clone())
+                    .await
+                    .ok()
+                    .map(String::into_bytes)
+            }
+            _ => None,
+        };
+
+        if let Some(image) = image {
+            *block = Block::Image(svg::Handle::from_memory(image));
+        }
+    }
+
+    Ok(Preview { path, blocks })
+}
+
+fn markdown(text: &str) -> String {
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_MATH;
+    let mut html = String::new();
+
+    pulldown_cmark::html::push_html(&mut html, pulldown_cmark::Parser::new_ext(text, options));
+
+    html
+}
+
+// Runs the renderer with the formula as its last argument and expects SVG on stdout
+async fn typeset(command: &str, formula: &str) -> Option<Vec<u8>> {
+    let mut parts = command.split_whitespace();
+
+    let output = tokio::process::Command::new(parts.next()?)
+        .args(parts)
+        .arg(formula)
+        .output()
+        .await
+        .ok()?;
+
+    output.status.success().then_some(output.stdout)
+}
+
+pub fn rendering<'a>() -> Element<'a, Message> {
+    container(text("Rendering preview...").size(14))
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+}
+
+impl Preview {
+    pub fn view(&self) -> Element<'_, Message> {
+        let blocks = self.blocks.iter().map(|block| match block {
+            Block::Heading(level, heading) => text(heading).size(28 - 2 * u16::from(*level)).into(),
+            Block::Paragraph(paragraph) => text(paragraph).size(14).into(),
+            Block::Mixed(pieces) => flow(
+                pieces
+                    .iter()
+                    .map(|piece| match piece {
+                        Piece::Word(word) => text(word).size(14).into(),
+                        Piece::Math(formula) => text(format!("${formula}$")).size(14).into(),
+                        Piece::Image(image) => svg(image.clone())
+                            .width(Length::Shrink)
+                            .height(INLINE_MATH_HEIGHT)
+                            .into(),
+                    })
+                    .collect(),
+            ),
+            Block::Item(depth, item) => container(text(format!("• {item}")).size(14))
+                .padding([0, 0, 0, INDENT * *depth as u16])
+                .into(),
+            Block::Quote(quote) => container(text(quote).size(14).style(QUOTE_COLOR))
+                .padding([0, 0, 0, INDENT])
+                .into(),
+            Block::Code(code) | Block::Math(code) | Block::Diagram(code) => {
+                container(text(code).size(13).font(Font::MONOSPACE))
+                    .width(Length::Fill)
+                    .padding(5)
+                    .style(theme::Container::Box)
+                    .into()
+            }
+            Block::Image(image) => svg(image.clone()).width(Length::Shrink).into(),
+            Block::Rule => horizontal_rule(1).into(),
+        });
+
+        let title = self
+            .path
+            .file_name()
+            .map(|name| format!("Preview of {}", name.to_string_lossy()))
+            .unwrap_or_else(|| String::from("Preview"));
+
+        container(
+            column![
+                text(title).size(16),
+                scrollable(Column::with_children(blocks.collect()).spacing(8)).height(Length::Fill),
+                button(text("Close").size(14))
+                    .on_press(Message::ClosePanel)
+                    .style(theme::Button::Secondary),
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
+
+#[derive(Default)]
+struct Parser {
+    blocks: Vec<Block>,
+    buffer: String,
+    heading: Option<u8>,
+    lists: usize,
+    in_item: bool,
+    quotes: usize,
+    pre: usize,
+    hidden: usize,
+    math: Option<String>,
+    is_inline_math: bool,
+    /// Inline formulas in the paragraph being read, in order
+    formulas: Vec<String>,
+    diagram: bool,
+}
+
+impl Parser {
+    fn parse(mut self, html: &str) -> Vec<Block> {
+        let mut rest = html;
+
+        while let Some(start) = rest.find('<') {
+            self.text(&rest[..start]);
+            rest = &rest[start..];
+
+            if let Some(comment) = rest.strip_prefix("<!--") {
+                rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+                continue;
+            }
+
+            let Some(end) = rest.find('>') else {
+                break;
+            };
+
+            self.tag(&rest[1..end]);
+            rest = &rest[end + 1..];
+        }
+
+        self.text(rest);
+        self.flush();
+
+        self.blocks
+    }
+
+    fn text(&mut self, raw: &str) {
+        if self.hidden > 0 {
+            return;
+        }
+
+        match &mut self.math {
+            Some(math) => math.push_str(&decode(raw)),
+            None => self.buffer.push_str(&decode(raw)),
+        }
+    }
+
+    fn tag(&mut self, tag: &str) {
+        let (is_closing, tag) = match tag.strip_prefix('/') {
+            Some(tag) => (true, tag),
+            None => (false, tag),
+        };
+
+        let name = tag
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+
+        if HIDDEN.contains(&name.as_str()) {
+            self.hidden = if is_closing {
+                self.hidden.saturating_sub(1)
+            } else {
+                self.hidden + 1
+            };
+            return;
+        }
+
+        if self.hidden > 0 {
+            return;
+        }
+
+        match name.as_str() {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.flush();
+                self.heading = (!is_closing).then(|| name.as_bytes()[1] - b'0');
+            }
+            "ul" | "ol" => {
+                self.flush();
+                self.lists = if is_closing {
+                    self.lists.saturating_sub(1)
+                } else {
+                    self.lists + 1
+                };
+            }
+            "li" => {
+                self.flush();
+                self.in_item = !is_closing;
+            }
+            "blockquote" => {
+                self.flush();
+                self.quotes = if is_closing {
+                    self.quotes.saturating_sub(1)
+                } else {
+                    self.quotes + 1
+                };
+            }
+            "pre" => {
+                self.flush();
+                self.pre = if is_closing {
+                    self.pre.saturating_sub(1)
+                } else {
+                    self.pre + 1
+                };
+            }
+            "hr" => {
+                self.flush();
+                self.blocks.push(Block::Rule);
+            }
+            "td" | "th" if is_closing => self.buffer.push_str("  "),
+            "span" if is_closing && self.math.is_some() => {
+                if let Some(formula) = self.math.take() {
+                    let formula = formula.trim().to_string();
+
+                    if std::mem::take(&mut self.is_inline_math) {
+                        self.buffer.push(FORMULA);
+                        self.formulas.push(formula);
+                    } else {
+                        self.blocks.push(Block::Math(formula));
+                    }
+                }
+            }
+            "span"
+                if attribute(tag, "class")
+                    .is_some_and(|class| class.split_whitespace().any(|c| c == "math-display")) =>
+            {
+                self.flush();
+                self.math = Some(String::new());
+            }
+            "span"
+                if attribute(tag, "class")
+                    .is_some_and(|class| class.split_whitespace().any(|c| c == "math-inline")) =>
+            {
+                self.math = Some(String::new());
+                self.is_inline_math = true;
+            }
+            "code" if self.pre > 0 && !is_closing => {
+                self.diagram = attribute(tag, "class")
+                    .is_some_and(|class| class.split_whitespace().any(|c| c == "language-mermaid"));
+            }
+            "img" => {
+                if let Some(alt) = attribute(tag, "alt") {
+                    self.buffer.push_str(&format!("[{alt}]"));
+                }
+            }
+            name if BLOCKS.contains(&name) => self.flush(),
+            _ => {}
+        }
+    }
+
+    fn flush(&mut self) {
+        let raw = std::mem::take(&mut self.buffer);
+
+        if self.pre > 0 {
+            let code = raw.trim_matches('\n');
+
+            if !code.is_empty() {
+                self.blocks.push(if std::mem::take(&mut self.diagram) {
+                    Block::Diagram(code.to_string())
+                } else {
+                    Block::Code(code.to_string())
+                });
+            }
+
+            return;
+        }
+
+        let line = raw.split_whitespace().collect::<Vec<_>>().join(" ");
+        let mut formulas = std::mem::take(&mut self.formulas).into_iter();
+
+        if line.is_empty() {
+            return;
+        }
+
+        let is_paragraph = self.heading.is_none() && !self.in_item && self.quotes == 0;
+
+        if is_paragraph && line.contains(FORMULA) {
+            let mut pieces = Vec::new();
+
+            for word in line.split(' ') {
+                let mut parts = word.split(FORMULA);
+
+                if let Some(first) = parts.next().filter(|part| !part.is_empty()) {
+                    pieces.push(Piece::Word(first.to_string()));
+                }
+
+                for part in parts {
+                    pieces.push(Piece::Math(formulas.next().unwrap_or_default()));
+
+                    if !part.is_empty() {
+                        pieces.push(Piece::Word(part.to_string()));
+                    }
+                }
+            }
+
+            self.blocks.push(Block::Mixed(pieces));
+            return;
+        }
+
+        // Headings, list items, and quotes are single texts, so their formulas stay as source
+        let line = line
+            .split(FORMULA)
+            .enumerate()
+            .map(|(index, part)| match index {
+                0 => part.to_string(),
+                _ => format!("${}${part}", formulas.next().unwrap_or_default()),
+            })
+            .collect::<String>();
+
+        self.blocks.push(if let Some(level) = self.heading {
+            Block::Heading(level, line)
+        } else if self.in_item {
+            Block::Item(self.lists.max(1) - 1, line)
+        } else if self.quotes > 0 {
+            Block::Quote(line)
+        } else {
+            Block::Paragraph(line)
+        });
+    }
+}
+
+fn flow(children: Vec<Element<'_, Message>>) -> Element<'_, Message> {
+    Element::new(Flow { children })
+}
+
+/// Lays its children out like words, moving to a new line when the next one won't fit.
+struct Flow<'a> {
+    children: Vec<Element<'a, Message>>,
+}
+
+impl<'a> Flow<'a> {
+    const SPACING: f32 = 4.0;
+    const LINE_SPACING: f32 = 2.0;
+}
+
+impl<'a> Widget<Message, iced::Renderer> for Flow<'a> {
+    fn width(&self) -> Length {
+        Length::Fill
+    }
+
+    fn height(&self) -> Length {
+        Length::Shrink
+    }
+
+    fn children(&self) -> Vec<Tree> {
+        self.children.iter().map(Tree::new).collect()
+    }
+
+    fn diff(&self, tree: &mut Tree) {
+        tree.diff_children(&self.children);
+    }
+
+    fn layout(
+        &self,
+        tree: &mut Tree,
+        renderer: &iced::Renderer,
+        limits: &layout::Limits,
+    ) -> layout::Node {
+        let width = limits.width(Length::Fill).max().width;
+        let loose = layout::Limits::new(Size::ZERO, Size::new(width, f32::INFINITY));
+
+        let mut nodes = Vec::with_capacity(self.children.len());
+        let mut line: Vec<(layout::Node, f32)> = Vec::new();
+        let (mut x, mut y, mut height) = (0.0, 0.0, 0.0_f32);
+
+        // Each line's children are centered on it once its height is known
+        let mut place = |line: &mut Vec<(layout::Node, f32)>, y: f32, height: f32| {
+            for (mut node, x) in line.drain(..) {
+                node.move_to(Point::new(x, y + (height - node.size().height) / 2.0));
+                nodes.push(node);
+            }
+        };
+
+        for (child, state) in self.children.iter().zip(&mut tree.children) {
+            let node = child.as_widget().layout(state, renderer, &loose);
+            let size = node.size();
+
+            if x > 0.0 && x + size.width > width {
+                place(&mut line, y, height);
+
+                y += height + Self::LINE_SPACING;
+                x = 0.0;
+                height = 0.0;
+            }
+
+            line.push((node, x));
+            x += size.width + Self::SPACING;
+            height = height.max(size.height);
+        }
+
+        place(&mut line, y, height);
+
+        layout::Node::with_children(Size::new(width, y + height), nodes)
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut iced::Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+    ) {
+        for ((child, state), layout) in self
+            .children
+            .iter()
+            .zip(&tree.children)
+            .zip(layout.children())
+        {
+            child
+                .as_widget()
+                .draw(state, renderer, theme, style, layout, cursor, viewport);
+        }
+    }
+}
+
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let lowercase = tag.to_ascii_lowercase();
+    let start = lowercase.find(&format!("{name}="))? + name.len() + 1;
+    let value = &tag[start..];
+
+    let value = match value.chars().next()? {
+        quote @ ('"' | '\'') => value[1..].split(quote).next()?,
+        _ => value.split_whitespace().next()?,
+    };
+
+    Some(decode(value))
+}
+
+fn decode(raw: &str) -> String {
+    let mut decoded = String::with_capacity(raw.len());
+    let mut rest = raw;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest[1..]
+            .find(';')
+            .filter(|end| *end <= 10)
+            .and_then(|end| Some((entity(&rest[1..1 + end])?, end + 2)));
+
+        match entity {
+            Some((c, length)) => {
+                decoded.push(c);
+                rest = &rest[length..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    decoded.push_str(rest);
+    decoded
+}
+
+fn entity(name: &str) -> Option<char> {
+    if let Some(number) = name.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+
+        return char::from_u32(code);
+    }
+
+    Some(match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => '\u{A0}',
+        "copy" => '©',
+        "reg" => '®',
+        "hellip" => '…',
+        "mdash" => '—',
+        "ndash" => '–',
+        "laquo" => '«',
+        "raquo" => '»',
+        _ => return None,
+    })
+}
@@ -0,0 +1,196 @@
+use crate::Message;
+
+use iced::advanced::layout::{self, Layout};
+use iced::advanced::renderer;
+use iced::advanced::widget::{Operation, Tree, Widget};
+use iced::advanced::{overlay, Clipboard, Shell};
+use iced::event::{self, Event};
+use iced::mouse;
+use iced::{Element, Length, Point, Rectangle, Theme};
+
+pub struct Selection {
+    #[cfg(target_os = "linux")]
+    clipboard: Option<arboard::Clipboard>,
+}
+
+#[cfg(target_os = "linux")]
+impl Selection {
+    pub fn new() -> Self {
+        Self {
+            clipboard: arboard::Clipboard::new().ok(),
+        }
+    }
+
+    pub fn set(&mut self, text: String) {
+        use arboard::{LinuxClipboardKind, SetExtLinux};
+
+        if let Some(clipboard) = &mut self.clipboard {
+            let _ = clipboard
+                .set()
+                .clipboard(LinuxClipboardKind::Primary)
+                .text(text);
+        }
+    }
+
+    pub fn get(&mut self) -> Option<String> {
+        use arboard::{GetExtLinux, LinuxClipboardKind};
+
+        self.clipboard
+            .as_mut()?
+            .get()
+            .clipboard(LinuxClipboardKind::Primary)
+            .text()
+            .ok()
+    }
+}
+
+// Other platforms have no primary selection to speak of
+#[cfg(not(target_os = "linux"))]
+impl Selection {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    pub fn set(&mut self, _text: String) {}
+
+    pub fn get(&mut self) -> Option<String> {
+        None
+    }
+}
+
+/// Reports middle clicks on `content` with where they landed, relative to its top left.
+pub fn paste_area<'a>(
+    content: impl Into<Element<'a, Message>>,
+    on_press: fn(Point) -> Message,
+) -> Element<'a, Message> {
+    Element::new(PasteArea {
+        content: content.into(),
+        on_press,
+    })
+}
+
+struct PasteArea<'a> {
+    content: Element<'a, Message>,
+    on_press: fn(Point) -> Message,
+}
+
+impl<'a> Widget<Message, iced::Renderer> for PasteArea<'a> {
+    fn width(&self) -> Length {
+        self.content.as_widget().width()
+    }
+
+    fn height(&self) -> Length {
+        self.content.as_widget().height()
+    }
+
+    fn children(&self) -> Vec<Tree> {
+        vec![Tree::new(&self.content)]
+    }
+
+    fn diff(&self, tree: &mut Tree) {
+        tree.diff_children(std::slice::from_ref(&self.content));
+    }
+
+    fn layout(
+        &self,
+        tree: &mut Tree,
+        renderer: &iced::Renderer,
+        limits: &layout::Limits,
+    ) -> layout::Node {
+        self.content
+            .as_widget()
+            .layout(&mut tree.children[0], renderer, limits)
+    }
+
+    fn operate(
+        &self,
+        tree: &mut Tree,
+        layout: Layout<'_>,
+        renderer: &iced::Renderer,
+        operation: &mut dyn Operation<Message>,
+    ) {
+        self.content
+            .as_widget()
+            .operate(&mut tree.children[0], layout, renderer, operation);
+    }
+
+    fn on_event(
+        &mut self,
+        tree: &mut Tree,
+        event: Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        renderer: &iced::Renderer,
+        clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+        viewport: &Rectangle,
+    ) -> event::Status {
+        if let Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Middle)) = event {
+            if let Some(position) = cursor.position_in(layout.bounds()) {
+                shell.publish((self.on_press)(position));
+
+                return event::Status::Captured;
+            }
+        }
+
+        self.content.as_widget_mut().on_event(
+            &mut tree.children[0],
+            event,
+            layout,
+            cursor,
+            renderer,
+            clipboard,
+            shell,
+            viewport,
+        )
+    }
+
+    fn mouse_interaction(
+        &self,
+        tree: &Tree,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+        renderer: &iced::Renderer,
+    ) -> mouse::Interaction {
+        self.content.as_widget().mouse_interaction(
+            &tree.children[0],
+            layout,
+            cursor,
+            viewport,
+            renderer,
+        )
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut iced::Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+    ) {
+        self.content.as_widget().draw(
+            &tree.children[0],
+            renderer,
+            theme,
+            style,
+            layout,
+            cursor,
+            viewport,
+        );
+    }
+
+    fn overlay<'b>(
+        &'b mut self,
+        tree: &'b mut Tree,
+        layout: Layout<'_>,
+        renderer: &iced::Renderer,
+    ) -> Option<overlay::Element<'b, Message, iced::Renderer>> {
+        self.content
+            .as_widget_mut()
+            .overlay(&mut tree.children[0], layout, renderer)
+    }
+}
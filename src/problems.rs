@@ -0,0 +1,271 @@
+use crate::{widgets, Message};
+
+use iced::theme;
+use iced::widget::{button, column, container, row, scrollable, text};
+use iced::{Color, Element, Length};
+use regex::Regex;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+const ERROR_COLOR: Color = Color::from_rgb(0.9, 0.3, 0.3);
+const WARNING_COLOR: Color = Color::from_rgb(0.95, 0.7, 0.2);
+const NOTE_COLOR: Color = Color::from_rgb(0.35, 0.6, 1.0);
+const ROW_HEIGHT: f32 = 28.0;
+
+/// Built-in matchers, used by name in a workspace's `problem_matchers`.
+const PRESETS: &[(&str, &str)] = &[
+    (
+        "rustc",
+        r"(?m)^(?P<severity>error|warning)(?:\[\w+\])?: (?P<message>.+)\n\s*--> (?P<file>[^:\n]+):(?P<line>\d+):(?P<column>\d+)",
+    ),
+    (
+        "gcc",
+        r"(?m)^(?P<file>[^:\s][^:\n]*):(?P<line>\d+):(?:(?P<column>\d+):)? (?:fatal )?(?P<severity>error|warning|note): (?P<message>.+)$",
+    ),
+    (
+        "tsc",
+        r"(?m)^(?P<file>[^(\n]+)\((?P<line>\d+),(?P<column>\d+)\): (?P<severity>error|warning) (?P<message>.+)$",
+    ),
+    (
+        "tsc",
+        r"(?m)^(?P<file>[^:\s][^:\n]*):(?P<line>\d+):(?P<column>\d+) - (?P<severity>error|warning) (?P<message>.+)$",
+    ),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl Severity {
+    fn parse(text: &str) -> Self {
+        match text.to_ascii_lowercase().as_str() {
+            "error" | "fatal" => Severity::Error,
+            "warning" | "warn" => Severity::Warning,
+            _ => Severity::Note,
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            Severity::Error => ERROR_COLOR,
+            Severity::Warning => WARNING_COLOR,
+            Severity::Note => NOTE_COLOR,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    pub path: PathBuf,
+    /// Counted from zero, like the editor's lines
+    pub line: usize,
+    pub column: usize,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Turns `problem_matchers` entries into patterns, each either a preset's name or a regex.
+///
+/// A regex names its parts `file`, `line`, `column`, `severity` and `message`; only the
+/// first three are needed. An empty list uses every preset.
+pub fn matchers(entries: &[String]) -> (Vec<Regex>, Vec<String>) {
+    let mut matchers = Vec::new();
+    let mut errors = Vec::new();
+
+    let patterns: Vec<&str> = if entries.is_empty() {
+        PRESETS.iter().map(|(_, pattern)| *pattern).collect()
+    } else {
+        entries
+            .iter()
+            .flat_map(|entry| {
+                let named: Vec<&str> = PRESETS
+                    .iter()
+                    .filter(|(name, _)| name == entry)
+                    .map(|(_, pattern)| *pattern)
+                    .collect();
+
+                if named.is_empty() {
+                    vec![entry.as_str()]
+                } else {
+                    named
+                }
+            })
+            .collect()
+    };
+
+    for pattern in patterns {
+        match Regex::new(pattern) {
+            Ok(regex) => matchers.push(regex),
+            Err(error) => errors.push(format!("{pattern}: {error}")),
+        }
+    }
+
+    (matchers, errors)
+}
+
+/// Finds the problems a task printed, with paths taken from `root` when relative.
+pub fn find(output: &str, matchers: &[Regex], root: &Path) -> Vec<Problem> {
+    let mut problems: Vec<Problem> = matchers
+        .iter()
+        .flat_map(|matcher| matcher.captures_iter(output))
+        .filter_map(|captures| {
+            let file = captures.name("file")?.as_str().trim();
+            let line = captures.name("line")?.as_str().parse::<usize>().ok()?;
+
+            let column = captures
+                .name("column")
+                .and_then(|column| column.as_str().parse::<usize>().ok())
+                .unwrap_or(1);
+
+            Some(Problem {
+                path: root.join(file.trim_start_matches("./")),
+                line: line.saturating_sub(1),
+                column: column.saturating_sub(1),
+                severity: captures
+                    .name("severity")
+                    .map_or(Severity::Error, |severity| {
+                        Severity::parse(severity.as_str())
+                    }),
+                message: captures
+                    .name("message")
+                    .map_or_else(String::new, |message| message.as_str().trim().to_string()),
+            })
+        })
+        .collect();
+
+    problems.sort_by(|a, b| {
+        (a.severity, &a.path, a.line, a.column).cmp(&(b.severity, &b.path, b.line, b.column))
+    });
+    problems.dedup();
+
+    problems
+}
+
+/// The word a problem points at, or the whole line when its column is past the text.
+pub fn marker(line: &str, column: usize) -> Range<usize> {
+    let start = line
+        .char_indices()
+        .nth(column)
+        .map_or(line.len(), |(index, _)| index);
+
+    let end = line[start..]
+        .find(char::is_whitespace)
+        .map_or(line.len(), |length| start + length);
+
+    if start == end {
+        0..line.len()
+    } else {
+        start..end
+    }
+}
+
+/// The problems panel, which only keeps where its list is scrolled.
+#[derive(Debug, Clone, Default)]
+pub struct List {
+    offset: f32,
+}
+
+impl List {
+    pub fn scrolled(&mut self, viewport: scrollable::Viewport) {
+        self.offset = viewport.absolute_offset().y;
+    }
+
+    pub fn view<'a>(
+        &self,
+        problems: &'a [Problem],
+        root: Option<&'a Path>,
+        height: f32,
+    ) -> Element<'a, Message> {
+        view(problems, root, self.offset, height)
+    }
+}
+
+fn view<'a>(
+    problems: &'a [Problem],
+    root: Option<&'a Path>,
+    offset: f32,
+    height: f32,
+) -> Element<'a, Message> {
+    let count = |severity| {
+        problems
+            .iter()
+            .filter(|problem| problem.severity == severity)
+            .count()
+    };
+
+    let summary = if problems.is_empty() {
+        String::from("The last task run reported no problems")
+    } else {
+        format!(
+            "{} errors, {} warnings",
+            count(Severity::Error),
+            count(Severity::Warning)
+        )
+    };
+
+    let row = |index: usize| -> Element<'a, Message> {
+        let problem = &problems[index];
+        let path = root
+            .and_then(|root| problem.path.strip_prefix(root).ok())
+            .unwrap_or(&problem.path);
+
+        button(
+            row![
+                text(problem.severity.label())
+                    .size(13)
+                    .style(problem.severity.color())
+                    .width(60),
+                text(format!(
+                    "{}:{}:{}",
+                    path.display(),
+                    problem.line + 1,
+                    problem.column + 1
+                ))
+                .size(13)
+                .width(Length::FillPortion(2)),
+                text(&problem.message)
+                    .size(13)
+                    .width(Length::FillPortion(3)),
+            ]
+            .spacing(10),
+        )
+        .on_press(Message::ProblemSelected(index))
+        .width(Length::Fill)
+        .style(theme::Button::Text)
+        .into()
+    };
+
+    container(
+        column![
+            text("Problems").size(16),
+            text(summary).size(14),
+            widgets::virtual_list(
+                problems.len(),
+                ROW_HEIGHT,
+                offset,
+                height,
+                row,
+                Message::ProblemsScrolled,
+            ),
+            button(text("Close").size(14))
+                .on_press(Message::ClosePanel)
+                .style(theme::Button::Secondary),
+        ]
+        .spacing(10),
+    )
+    .width(Length::Fill)
+    .padding(10)
+    .style(theme::Container::Box)
+    .into()
+}
@@ -0,0 +1,197 @@
+use crate::{Error, Message};
+
+use iced::theme;
+use iced::widget::{button, column, container, row, text, Column};
+use iced::{Element, Length};
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+const SAMPLE_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct Properties {
+    pub canonical: PathBuf,
+    size: u64,
+    permissions: String,
+    created: Option<String>,
+    modified: Option<String>,
+    encoding: &'static str,
+    line_endings: &'static str,
+    mime: String,
+}
+
+impl Properties {
+    pub fn view(&self) -> Element<'_, Message> {
+        let entry = |label: &str, value: String| {
+            row![
+                text(label).size(14).width(Length::Fill),
+                text(value).size(14),
+            ]
+            .into()
+        };
+
+        let unknown = || String::from("unknown");
+
+        container(
+            column![
+                text("File info").size(16),
+                Column::with_children(vec![
+                    entry("Size", format_size(self.size)),
+                    entry("Permissions", self.permissions.clone()),
+                    entry("Created", self.created.clone().unwrap_or_else(unknown)),
+                    entry("Modified", self.modified.clone().unwrap_or_else(unknown)),
+                    entry("Encoding", self.encoding.to_string()),
+                    entry("Line endings", self.line_endings.to_string()),
+                    entry("Type", self.mime.clone()),
+                ])
+                .spacing(4),
+                text(self.canonical.display()).size(13),
+                row![
+                    button(text("Copy path").size(14))
+                        .on_press(Message::CopyPath(self.canonical.display().to_string())),
+                    button(text("Close").size(14))
+                        .on_press(Message::ClosePanel)
+                        .style(theme::Button::Secondary),
+                ]
+                .spacing(10),
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fill)
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
+
+pub fn loading<'a>() -> Element<'a, Message> {
+    container(
+        column![
+            text("File info").size(16),
+            text("Reading...").size(14),
+            button(text("Close").size(14))
+                .on_press(Message::ClosePanel)
+                .style(theme::Button::Secondary),
+        ]
+        .spacing(10),
+    )
+    .width(Length::Fill)
+    .padding(10)
+    .style(theme::Container::Box)
+    .into()
+}
+
+pub async fn inspect(path: PathBuf) -> Result<Properties, Error> {
+    let io_error = |error: std::io::Error| Error::IOFailed(error.kind());
+
+    let metadata = tokio::fs::metadata(&path).await.map_err(io_error)?;
+    let canonical = tokio::fs::canonicalize(&path).await.map_err(io_error)?;
+
+    let mut sample = vec![0; SAMPLE_SIZE.min(metadata.len() as usize)];
+
+    {
+        use tokio::io::AsyncReadExt;
+
+        let mut file = tokio::fs::File::open(&path).await.map_err(io_error)?;
+        let read = file.read(&mut sample).await.map_err(io_error)?;
+        sample.truncate(read);
+    }
+
+    Ok(Properties {
+        size: metadata.len(),
+        permissions: permissions(&metadata),
+        created: metadata.created().ok().map(timestamp),
+        modified: metadata.modified().ok().map(timestamp),
+        encoding: encoding(&sample),
+        line_endings: line_endings(&sample),
+        mime: mime_guess::from_path(&path)
+            .first_or_octet_stream()
+            .to_string(),
+        canonical,
+    })
+}
+
+fn timestamp(time: SystemTime) -> String {
+    chrono::DateTime::<chrono::Local>::from(time)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
+
+#[cfg(unix)]
+fn permissions(metadata: &std::fs::Metadata) -> String {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = metadata.permissions().mode();
+
+    (0..9)
+        .rev()
+        .map(|bit| {
+            if mode & (1 << bit) == 0 {
+                '-'
+            } else {
+                ['x', 'w', 'r'][bit % 3]
+            }
+        })
+        .collect()
+}
+
+#[cfg(not(unix))]
+fn permissions(metadata: &std::fs::Metadata) -> String {
+    String::from(if metadata.permissions().readonly() {
+        "read-only"
+    } else {
+        "read-write"
+    })
+}
+
+fn encoding(sample: &[u8]) -> &'static str {
+    match sample {
+        [0xef, 0xbb, 0xbf, ..] => "UTF-8 with BOM",
+        [0xff, 0xfe, ..] => "UTF-16 LE",
+        [0xfe, 0xff, ..] => "UTF-16 BE",
+        _ if sample.contains(&0) => "binary",
+        _ => match std::str::from_utf8(sample) {
+            Ok(text) if text.is_ascii() => "ASCII",
+            Ok(_) => "UTF-8",
+            // The sample may have cut a character in half at the end
+            Err(error) if error.error_len().is_none() => "UTF-8",
+            Err(_) => "unknown 8-bit",
+        },
+    }
+}
+
+fn line_endings(sample: &[u8]) -> &'static str {
+    let crlf = sample.windows(2).filter(|pair| *pair == b"\r\n").count();
+    let lf = sample.iter().filter(|byte| **byte == b'\n').count() - crlf;
+    let cr = sample.iter().filter(|byte| **byte == b'\r').count() - crlf;
+
+    match (lf > 0, crlf > 0, cr > 0) {
+        (false, false, false) => "none",
+        (true, false, false) => "LF",
+        (false, true, false) => "CRLF",
+        (false, false, true) => "CR",
+        _ => "mixed",
+    }
+}
+
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} bytes");
+    }
+
+    let mut size = bytes as f64;
+    let mut unit = "";
+
+    for next in UNITS {
+        if size < 1024.0 {
+            break;
+        }
+
+        size /= 1024.0;
+        unit = next;
+    }
+
+    format!("{size:.1} {unit} ({bytes} bytes)")
+}
@@ -0,0 +1,133 @@
+use std::ops::Range;
+
+const BULLETS: &[char] = &['-', '*', '+'];
+const OPENERS: &[char] = &['(', '[', '{', '-', '—', '/'];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Marker {
+    Bullet(char),
+    Number(usize, char),
+}
+
+#[derive(Debug, Clone)]
+pub struct Item {
+    indent: usize,
+    marker: Marker,
+    is_task: bool,
+    /// Byte length of the indent, marker and the space after it
+    pub prefix: usize,
+    pub is_empty: bool,
+}
+
+impl Item {
+    pub fn is_ordered(&self) -> bool {
+        matches!(self.marker, Marker::Number(..))
+    }
+
+    /// The prefix of the item that follows this one.
+    pub fn next(&self) -> String {
+        let marker = match self.marker {
+            Marker::Bullet(bullet) => bullet.to_string(),
+            Marker::Number(number, delimiter) => format!("{}{delimiter}", number + 1),
+        };
+
+        let task = if self.is_task { " [ ]" } else { "" };
+
+        format!("{}{marker}{task} ", " ".repeat(self.indent))
+    }
+}
+
+pub fn item(line: &str) -> Option<Item> {
+    let body = line.trim_start();
+    let indent = line.len() - body.len();
+
+    let (marker, rest) = match body.chars().next()? {
+        bullet if BULLETS.contains(&bullet) => (Marker::Bullet(bullet), &body[1..]),
+        c if c.is_ascii_digit() => {
+            let digits = body.chars().take_while(char::is_ascii_digit).count();
+            let delimiter = body[digits..]
+                .chars()
+                .next()
+                .filter(|c| matches!(c, '.' | ')'))?;
+
+            (
+                Marker::Number(body[..digits].parse().ok()?, delimiter),
+                &body[digits + 1..],
+            )
+        }
+        _ => return None,
+    };
+
+    if !(rest.is_empty() || rest.starts_with(' ')) {
+        return None;
+    }
+
+    let mut content = rest.strip_prefix(' ').unwrap_or(rest);
+
+    let task = ["[ ] ", "[x] ", "[X] "]
+        .into_iter()
+        .find_map(|task| content.strip_prefix(task));
+
+    if let Some(rest) = task {
+        content = rest;
+    }
+
+    Some(Item {
+        indent,
+        marker,
+        is_task: task.is_some(),
+        prefix: line.len() - content.len(),
+        is_empty: content.trim().is_empty(),
+    })
+}
+
+/// Number edits that keep an ordered list counting up from its first item.
+pub fn renumber(lines: &[&str]) -> Vec<(usize, Range<usize>, String)> {
+    let Some(first) = lines.first().and_then(|line| item(line)) else {
+        return Vec::new();
+    };
+
+    let Marker::Number(mut expected, _) = first.marker else {
+        return Vec::new();
+    };
+
+    let mut edits = Vec::new();
+
+    for (offset, line) in lines.iter().enumerate().skip(1) {
+        let indent = line.len() - line.trim_start().len();
+
+        if indent > first.indent {
+            continue;
+        }
+
+        match item(line) {
+            Some(Item {
+                indent,
+                marker: Marker::Number(number, _),
+                ..
+            }) if indent == first.indent => {
+                expected += 1;
+
+                if number != expected {
+                    let digits = number.to_string().len();
+
+                    edits.push((offset, indent..indent + digits, expected.to_string()));
+                }
+            }
+            _ => break,
+        }
+    }
+
+    edits
+}
+
+pub fn curly(quote: char, before: Option<char>) -> char {
+    let is_opening = before.map_or(true, |c| c.is_whitespace() || OPENERS.contains(&c));
+
+    match (quote, is_opening) {
+        ('"', true) => '“',
+        ('"', false) => '”',
+        (_, true) => '‘',
+        (_, false) => '’',
+    }
+}
@@ -0,0 +1,286 @@
+use crate::{Error, Message};
+
+use iced::theme;
+use iced::widget::{button, checkbox, column, container, row, text, text_input};
+use iced::{Element, Length};
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+
+const DEFAULT_PORT: u16 = 22;
+const DEFAULT_KEYS: &[&str] = &[".ssh/id_ed25519", ".ssh/id_ecdsa", ".ssh/id_rsa"];
+const KNOWN_HOSTS: &str = ".ssh/known_hosts";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub user: String,
+    pub host: String,
+    pub port: u16,
+    pub path: String,
+}
+
+impl Location {
+    pub fn parse(url: &str) -> Option<Self> {
+        let rest = url.trim().strip_prefix("sftp://")?;
+        let (authority, path) = rest.split_at(rest.find('/')?);
+        let (user, address) = authority.split_once('@')?;
+
+        let (host, port) = match address.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().ok()?),
+            None => (address, DEFAULT_PORT),
+        };
+
+        if user.is_empty() || host.is_empty() || path.len() < 2 {
+            return None;
+        }
+
+        Some(Self {
+            user: user.to_string(),
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        Path::new(&self.path)
+    }
+}
+
+impl std::fmt::Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.port == DEFAULT_PORT {
+            write!(f, "sftp://{}@{}{}", self.user, self.host, self.path)
+        } else {
+            write!(
+                f,
+                "sftp://{}@{}:{}{}",
+                self.user, self.host, self.port, self.path
+            )
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub location: Location,
+    password: String,
+    use_agent: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Dialog {
+    pub url: String,
+    pub password: String,
+    pub use_agent: bool,
+}
+
+impl Dialog {
+    pub fn new() -> Self {
+        Self {
+            use_agent: true,
+            ..Self::default()
+        }
+    }
+
+    pub fn session(&self) -> Option<Session> {
+        Some(Session {
+            location: Location::parse(&self.url)?,
+            password: self.password.clone(),
+            use_agent: self.use_agent,
+        })
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let connect = (self.session().is_some() || crate::web::is_url(&self.url))
+            .then_some(Message::ConnectRemote);
+
+        container(
+            column![
+                text("Open remote file").size(16),
+                text_input("sftp://user@host/path or https://...", &self.url)
+                    .on_input(Message::RemoteUrlChanged)
+                    .on_submit(Message::ConnectRemote)
+                    .size(14)
+                    .padding(5),
+                text_input("Password or key passphrase", &self.password)
+                    .on_input(Message::RemotePasswordChanged)
+                    .on_submit(Message::ConnectRemote)
+                    .password()
+                    .size(14)
+                    .padding(5),
+                checkbox("Use SSH agent", self.use_agent, Message::RemoteAgentToggled).size(14),
+                row![
+                    button(text("Open").size(14)).on_press_maybe(connect),
+                    button(text("Cancel").size(14))
+                        .on_press(Message::ClosePanel)
+                        .style(theme::Button::Secondary),
+                ]
+                .spacing(10),
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fill)
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
+
+pub async fn download(session: Session) -> Result<(Session, String), Error> {
+    tokio::task::spawn_blocking(move || {
+        let sftp = connect(&session)?.sftp().map_err(failed)?;
+
+        let mut text = String::new();
+
+        sftp.open(session.location.path())
+            .map_err(failed)?
+            .read_to_string(&mut text)
+            .map_err(|error| Error::IOFailed(error.kind()))?;
+
+        Ok((session, text))
+    })
+    .await
+    .map_err(failed)?
+}
+
+/// Writes next to the file and renames over it, so a dropped connection can't leave it cut short.
+pub async fn upload(session: Session, text: String) -> Result<(), Error> {
+    tokio::task::spawn_blocking(move || {
+        let sftp = connect(&session)?.sftp().map_err(failed)?;
+
+        let path = session.location.path();
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let temporary = path.with_file_name(format!(".{name}.{}.tmp", std::process::id()));
+
+        let mode = sftp
+            .stat(path)
+            .ok()
+            .and_then(|stat| stat.perm)
+            .map_or(0o644, |perm| perm & 0o7777);
+
+        let written = sftp
+            .open_mode(
+                &temporary,
+                ssh2::OpenFlags::WRITE | ssh2::OpenFlags::CREATE | ssh2::OpenFlags::TRUNCATE,
+                mode as i32,
+                ssh2::OpenType::File,
+            )
+            .map_err(failed)
+            .and_then(|mut file| {
+                file.write_all(text.as_bytes())
+                    .map_err(|error| Error::IOFailed(error.kind()))
+            });
+
+        if let Err(error) = written {
+            let _ = sftp.unlink(&temporary);
+            return Err(error);
+        }
+
+        // SFTP v3 servers won't rename onto an existing file, so those lose the old one first
+        if sftp
+            .rename(&temporary, path, Some(ssh2::RenameFlags::OVERWRITE))
+            .is_err()
+        {
+            if let Err(error) = sftp.unlink(path) {
+                let _ = sftp.unlink(&temporary);
+                return Err(failed(error));
+            }
+
+            // The text is only in the temporary file now, so it's kept if this fails
+            sftp.rename(&temporary, path, None).map_err(failed)?;
+        }
+
+        Ok(())
+    })
+    .await
+    .map_err(failed)?
+}
+
+fn connect(session: &Session) -> Result<ssh2::Session, Error> {
+    let Location {
+        user, host, port, ..
+    } = &session.location;
+
+    let stream = TcpStream::connect((host.as_str(), *port))
+        .map_err(|error| Error::IOFailed(error.kind()))?;
+
+    let mut ssh = ssh2::Session::new().map_err(failed)?;
+    ssh.set_tcp_stream(stream);
+    ssh.handshake().map_err(failed)?;
+
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+
+    check_host_key(&ssh, host, *port, home.as_deref())?;
+
+    if session.use_agent {
+        let _ = ssh.userauth_agent(user);
+    }
+
+    let passphrase = (!session.password.is_empty()).then_some(session.password.as_str());
+
+    if let Some(home) = &home {
+        for key in DEFAULT_KEYS.iter().map(|key| home.join(key)) {
+            if ssh.authenticated() {
+                break;
+            }
+
+            if key.exists() {
+                let _ = ssh.userauth_pubkey_file(user, None, &key, passphrase);
+            }
+        }
+    }
+
+    if !ssh.authenticated() {
+        if let Some(password) = passphrase {
+            ssh.userauth_password(user, password).map_err(failed)?;
+        }
+    }
+
+    if ssh.authenticated() {
+        Ok(ssh)
+    } else {
+        Err(Error::RemoteFailed(format!(
+            "could not authenticate as {user}"
+        )))
+    }
+}
+
+/// Refuses hosts that `~/.ssh/known_hosts` doesn't list, or lists with a different key.
+fn check_host_key(
+    ssh: &ssh2::Session,
+    host: &str,
+    port: u16,
+    home: Option<&Path>,
+) -> Result<(), Error> {
+    let mut known = ssh.known_hosts().map_err(failed)?;
+
+    if let Some(file) = home.map(|home| home.join(KNOWN_HOSTS)) {
+        if file.exists() {
+            known
+                .read_file(&file, ssh2::KnownHostFileKind::OpenSSH)
+                .map_err(failed)?;
+        }
+    }
+
+    let (key, _) = ssh
+        .host_key()
+        .ok_or_else(|| Error::RemoteFailed(format!("{host} sent no host key")))?;
+
+    match known.check_port(host, port, key) {
+        ssh2::CheckResult::Match => Ok(()),
+        ssh2::CheckResult::NotFound => Err(Error::RemoteFailed(format!(
+            "{host} isn't in ~/{KNOWN_HOSTS}, connect with ssh once to trust its key"
+        ))),
+        ssh2::CheckResult::Mismatch => Err(Error::RemoteFailed(format!(
+            "the host key for {host} doesn't match ~/{KNOWN_HOSTS}, it may have been replaced"
+        ))),
+        ssh2::CheckResult::Failure => Err(Error::RemoteFailed(format!(
+            "could not check the host key for {host}"
+        ))),
+    }
+}
+
+fn failed(error: impl std::fmt::Display) -> Error {
+    Error::RemoteFailed(error.to_string())
+}
@@ -0,0 +1,73 @@
+use std::env;
+use std::process::{self, Command};
+
+/// Read by iced when it picks a graphics backend.
+const BACKEND_VARIABLE: &str = "ICED_BACKEND";
+/// Set on the copy of the process started after the GPU failed.
+const FALLBACK_VARIABLE: &str = "BONKITOR_RENDER_FALLBACK";
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Renderer {
+    #[default]
+    Gpu,
+    /// Asked for with `--software-render`
+    Software,
+    /// Picked because the GPU backend failed to start
+    Fallback,
+    /// Whatever `ICED_BACKEND` was already set to
+    Environment(String),
+}
+
+impl Renderer {
+    pub fn choose(software_render: bool) -> Self {
+        if env::var_os(FALLBACK_VARIABLE).is_some() {
+            Renderer::Fallback
+        } else if software_render {
+            Renderer::Software
+        } else if let Ok(backend) = env::var(BACKEND_VARIABLE) {
+            Renderer::Environment(backend)
+        } else {
+            Renderer::Gpu
+        }
+    }
+
+    /// Points iced at the backend, pinning the GPU one so a failure is ours to handle.
+    pub fn select(&self) {
+        match self {
+            Renderer::Gpu => env::set_var(BACKEND_VARIABLE, "wgpu"),
+            Renderer::Software | Renderer::Fallback => env::set_var(BACKEND_VARIABLE, "tiny-skia"),
+            Renderer::Environment(_) => {}
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Renderer::Gpu => String::from("GPU (wgpu)"),
+            Renderer::Software => String::from("Software (tiny-skia), as requested"),
+            Renderer::Fallback => {
+                String::from("Software (tiny-skia), because the GPU renderer failed to start")
+            }
+            Renderer::Environment(backend) => format!("{backend}, from {BACKEND_VARIABLE}"),
+        }
+    }
+}
+
+/// Starts this program again with the software renderer and exits with its status.
+///
+/// A window's event loop can only be created once per process, so the retry needs a new one.
+pub fn restart_in_software() -> ! {
+    let status = env::current_exe().and_then(|program| {
+        Command::new(program)
+            .args(env::args_os().skip(1))
+            .env(FALLBACK_VARIABLE, "1")
+            .status()
+    });
+
+    match status {
+        Ok(status) => process::exit(status.code().unwrap_or(1)),
+        Err(error) => {
+            eprintln!("Could not restart with the software renderer: {error}");
+            process::exit(1)
+        }
+    }
+}
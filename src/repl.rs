@@ -0,0 +1,338 @@
+use crate::{widgets, workspace, Error, Message};
+
+use iced::futures::channel::mpsc;
+use iced::futures::{SinkExt, StreamExt};
+use iced::theme;
+use iced::widget::{button, column, container, row, scrollable, text, text_input, Row};
+use iced::{subscription, Element, Length, Subscription};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWriteExt, BufReader};
+
+const FILE: &str = "repl_history.toml";
+const LINE_HEIGHT: f32 = 18.0;
+const MAX_LINES: usize = 10_000;
+const MAX_HISTORY: usize = 500;
+
+/// Offered when a workspace configures no `repls` of its own.
+pub const DEFAULTS: [(&str, &str); 3] = [
+    ("python", "python3 -i -q -u"),
+    ("node", "node -i"),
+    ("rust", "evcxr"),
+];
+
+/// What was typed into each REPL, oldest first, by the REPL's name.
+pub type History = BTreeMap<String, Vec<String>>;
+
+/// The REPL a file's code is meant for, when it is one of the defaults.
+pub fn for_extension(extension: &str) -> Option<&'static str> {
+    match extension {
+        "py" => Some("python"),
+        "js" | "mjs" | "cjs" => Some("node"),
+        "rs" => Some("rust"),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    Ready(u64, mpsc::Sender<String>),
+    Line(u64, String),
+    Exited(u64, String),
+}
+
+pub struct Repl {
+    pub name: String,
+    command: String,
+    root: PathBuf,
+    pub id: u64,
+    sender: Option<mpsc::Sender<String>>,
+    queued: Vec<String>,
+    lines: Vec<String>,
+    pub input: String,
+    /// How far back in the history the input was recalled from
+    recalled: Option<usize>,
+    exit: Option<String>,
+    offset: f32,
+}
+
+impl Repl {
+    pub fn new(id: u64, name: String, command: String, root: PathBuf) -> Self {
+        Self {
+            name,
+            command,
+            root,
+            id,
+            sender: None,
+            queued: Vec::new(),
+            lines: Vec::new(),
+            input: String::new(),
+            recalled: None,
+            exit: None,
+            offset: 0.0,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.exit.is_none()
+    }
+
+    pub fn handle(&mut self, event: Event) {
+        match event {
+            Event::Ready(_, sender) => {
+                self.sender = Some(sender);
+
+                for input in std::mem::take(&mut self.queued) {
+                    self.write(input);
+                }
+            }
+            Event::Line(_, line) => self.print(line),
+            Event::Exited(_, reason) => {
+                self.print(reason.clone());
+                self.exit = Some(reason);
+                self.sender = None;
+            }
+        }
+    }
+
+    /// Sends code as if typed, ending a block of several lines with an empty one.
+    pub fn send(&mut self, code: &str) {
+        let code = code.trim_end();
+
+        for line in code.lines() {
+            self.print(format!("> {line}"));
+        }
+
+        let input = if code.contains('\n') {
+            format!("{code}\n\n")
+        } else {
+            format!("{code}\n")
+        };
+
+        self.recalled = None;
+        self.write(input);
+    }
+
+    /// Steps through the history, `-1` going further back.
+    pub fn recall(&mut self, step: isize, history: &[String]) {
+        if history.is_empty() {
+            return;
+        }
+
+        let back = match (self.recalled, step < 0) {
+            (None, true) => Some(0),
+            (None, false) => None,
+            (Some(back), true) => Some((back + 1).min(history.len() - 1)),
+            (Some(0), false) => None,
+            (Some(back), false) => Some(back - 1),
+        };
+
+        self.recalled = back;
+        self.input = back
+            .map(|back| history[history.len() - 1 - back].clone())
+            .unwrap_or_default();
+    }
+
+    pub fn scrolled(&mut self, viewport: scrollable::Viewport) {
+        self.offset = viewport.absolute_offset().y;
+    }
+
+    fn write(&mut self, input: String) {
+        match &mut self.sender {
+            Some(sender) => {
+                let _ = sender.try_send(input);
+            }
+            None if self.is_running() => self.queued.push(input),
+            None => {}
+        }
+    }
+
+    fn print(&mut self, line: String) {
+        self.lines.push(line);
+
+        if self.lines.len() > MAX_LINES {
+            self.lines.drain(..self.lines.len() - MAX_LINES);
+        }
+    }
+
+    /// Keeps the interpreter running while the subscription is kept, killing it once dropped.
+    pub fn subscription(&self) -> Subscription<Event> {
+        let (id, command, root) = (self.id, self.command.clone(), self.root.clone());
+
+        subscription::channel(("repl", id), 100, move |mut output| async move {
+            let spawned = workspace::shell(&command)
+                .current_dir(&root)
+                .env("PYTHONUNBUFFERED", "1")
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .kill_on_drop(true)
+                .spawn();
+
+            let reason = match spawned {
+                Ok(mut child) => {
+                    let (lines, mut received) = mpsc::channel(100);
+
+                    if let Some(stdout) = child.stdout.take() {
+                        tokio::spawn(forward(stdout, lines.clone()));
+                    }
+
+                    if let Some(stderr) = child.stderr.take() {
+                        tokio::spawn(forward(stderr, lines));
+                    }
+
+                    if let Some(mut stdin) = child.stdin.take() {
+                        let (sender, mut inputs) = mpsc::channel::<String>(100);
+
+                        tokio::spawn(async move {
+                            while let Some(input) = inputs.next().await {
+                                if stdin.write_all(input.as_bytes()).await.is_err() {
+                                    break;
+                                }
+
+                                let _ = stdin.flush().await;
+                            }
+                        });
+
+                        let _ = output.send(Event::Ready(id, sender)).await;
+                    }
+
+                    while let Some(line) = received.next().await {
+                        let _ = output.send(Event::Line(id, line)).await;
+                    }
+
+                    match child.wait().await {
+                        Ok(status) => format!("{command} exited with {status}"),
+                        Err(error) => error.to_string(),
+                    }
+                }
+                Err(error) => format!("{command} could not start: {error}"),
+            };
+
+            let _ = output.send(Event::Exited(id, reason)).await;
+
+            loop {
+                std::future::pending::<()>().await;
+            }
+        })
+    }
+}
+
+async fn forward(stream: impl AsyncRead + Unpin, mut lines: mpsc::Sender<String>) {
+    let mut reader = BufReader::new(stream).lines();
+
+    while let Ok(Some(line)) = reader.next_line().await {
+        if lines.send(line).await.is_err() {
+            break;
+        }
+    }
+}
+
+pub fn view<'a>(repls: &'a [Repl], active: usize, height: f32) -> Element<'a, Message> {
+    let tabs = repls
+        .iter()
+        .enumerate()
+        .fold(Row::new().spacing(5), |tabs, (index, repl)| {
+            let label = if repl.is_running() {
+                repl.name.clone()
+            } else {
+                format!("{} (exited)", repl.name)
+            };
+
+            tabs.push(
+                row![
+                    button(text(label).size(13))
+                        .on_press(Message::SelectRepl(index))
+                        .style(if index == active {
+                            theme::Button::Primary
+                        } else {
+                            theme::Button::Secondary
+                        }),
+                    button(text("×").size(13))
+                        .on_press(Message::CloseRepl(index))
+                        .style(theme::Button::Text),
+                ]
+                .spacing(2),
+            )
+        });
+
+    let body: Element<'a, Message> = match repls.get(active) {
+        Some(repl) => column![
+            widgets::virtual_list(
+                repl.lines.len(),
+                LINE_HEIGHT,
+                repl.offset,
+                height,
+                move |index| text(&repl.lines[index]).size(13).into(),
+                Message::ReplScrolled,
+            ),
+            row![
+                text_input("Type code and press Enter", &repl.input)
+                    .on_input(Message::ReplInputChanged)
+                    .on_submit(Message::SubmitRepl),
+                button(text("▲").size(13)).on_press(Message::RecallRepl(-1)),
+                button(text("▼").size(13)).on_press(Message::RecallRepl(1)),
+            ]
+            .spacing(5),
+        ]
+        .spacing(10)
+        .into(),
+        None => text("Start a REPL from the palette").size(14).into(),
+    };
+
+    container(
+        column![
+            text("REPLs").size(16),
+            tabs,
+            body,
+            button(text("Close").size(14))
+                .on_press(Message::ClosePanel)
+                .style(theme::Button::Secondary),
+        ]
+        .spacing(10),
+    )
+    .width(Length::Fill)
+    .padding(10)
+    .style(theme::Container::Box)
+    .into()
+}
+
+/// Adds an input to a REPL's history, dropping the oldest past the limit.
+pub fn remember(history: &mut History, name: &str, input: &str) {
+    let inputs = history.entry(name.to_string()).or_default();
+
+    if inputs.last().map(String::as_str) != Some(input) {
+        inputs.push(input.to_string());
+    }
+
+    if inputs.len() > MAX_HISTORY {
+        inputs.drain(..inputs.len() - MAX_HISTORY);
+    }
+}
+
+pub async fn load_history(root: PathBuf) -> Result<History, Error> {
+    match tokio::fs::read_to_string(workspace::file(&root, FILE)).await {
+        Ok(text) => toml::from_str(&text).map_err(|error| Error::InvalidConfig(error.to_string())),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(History::new()),
+        Err(error) => Err(Error::IOFailed(error.kind())),
+    }
+}
+
+pub async fn save_history(root: PathBuf, history: History) -> Result<(), Error> {
+    let text = toml::to_string_pretty(&history)
+        .map_err(|error| Error::InvalidConfig(error.to_string()))?;
+
+    write(&workspace::file(&root, FILE), text)
+        .await
+        .map_err(|error| Error::IOFailed(error.kind()))
+}
+
+async fn write(path: &Path, text: String) -> io::Result<()> {
+    if let Some(directory) = path.parent() {
+        tokio::fs::create_dir_all(directory).await?;
+    }
+
+    tokio::fs::write(path, text).await
+}
@@ -0,0 +1,241 @@
+use crate::{Error, Message};
+
+use iced::theme;
+use iced::widget::{button, column, container, row, scrollable, text, Column};
+use iced::{Color, Element, Length};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+const METHODS: [&str; 9] = [
+    "GET", "POST", "PUT", "PATCH", "DELETE", "HEAD", "OPTIONS", "TRACE", "CONNECT",
+];
+const SUCCESS_COLOR: Color = Color::from_rgb(0.4, 0.8, 0.4);
+const FAILURE_COLOR: Color = Color::from_rgb(0.9, 0.3, 0.3);
+
+pub fn is_rest(extension: &str) -> bool {
+    matches!(extension, "http" | "rest")
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Response {
+    request: String,
+    status: reqwest::StatusCode,
+    headers: Vec<(String, String)>,
+    pub body: String,
+    elapsed: Duration,
+}
+
+/// Parses the `###`-separated block holding `line`, filling in `@name = value` variables.
+pub fn request_at(text: &str, line: usize) -> Option<Request> {
+    let lines: Vec<&str> = text.lines().collect();
+    let line = line.min(lines.len().checked_sub(1)?);
+
+    let start = lines[..=line]
+        .iter()
+        .rposition(is_separator)
+        .map_or(0, |index| index + 1);
+
+    let end = lines[start..]
+        .iter()
+        .position(is_separator)
+        .map_or(lines.len(), |index| start + index);
+
+    parse(&lines[start..end], &variables(&lines))
+}
+
+fn parse(block: &[&str], variables: &BTreeMap<String, String>) -> Option<Request> {
+    let mut lines = block
+        .iter()
+        .map(|line| substitute(line.trim_end(), variables))
+        .skip_while(|line| {
+            let line = line.trim();
+
+            line.is_empty() || is_comment(line) || line.starts_with('@')
+        });
+
+    let request_line = lines.next()?;
+    let mut parts = request_line.split_whitespace();
+    let first = parts.next()?;
+
+    // A bare URL is a GET, and a trailing `HTTP/1.1` says nothing reqwest needs
+    let (method, url) = if METHODS.contains(&first.to_ascii_uppercase().as_str()) {
+        (first.to_ascii_uppercase(), parts.next()?.to_string())
+    } else {
+        (String::from("GET"), first.to_string())
+    };
+
+    let mut headers = Vec::new();
+
+    for line in lines.by_ref() {
+        if line.trim().is_empty() {
+            break;
+        }
+
+        if is_comment(line.trim()) {
+            continue;
+        }
+
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+
+    let body = lines.collect::<Vec<_>>().join("\n").trim().to_string();
+
+    Some(Request {
+        method,
+        url,
+        headers,
+        body,
+    })
+}
+
+fn is_separator(line: &&str) -> bool {
+    line.trim_start().starts_with("###")
+}
+
+fn is_comment(line: &str) -> bool {
+    line.starts_with('#') || line.starts_with("//")
+}
+
+fn variables(lines: &[&str]) -> BTreeMap<String, String> {
+    lines
+        .iter()
+        .filter_map(|line| line.trim().strip_prefix('@')?.split_once('='))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect()
+}
+
+fn substitute(line: &str, variables: &BTreeMap<String, String>) -> String {
+    variables
+        .iter()
+        .fold(line.to_string(), |line, (name, value)| {
+            line.replace(&format!("{{{{{name}}}}}"), value)
+                .replace(&format!("{{{{ {name} }}}}"), value)
+        })
+}
+
+pub async fn send(request: Request) -> Result<Response, Error> {
+    let failed = |error: reqwest::Error| Error::RemoteFailed(error.to_string());
+
+    let method = reqwest::Method::from_bytes(request.method.as_bytes())
+        .map_err(|_| Error::RemoteFailed(format!("{} is not an HTTP method", request.method)))?;
+
+    let mut builder = reqwest::Client::new()
+        .request(method, request.url.as_str())
+        .timeout(TIMEOUT);
+
+    for (name, value) in &request.headers {
+        builder = builder.header(name.as_str(), value.as_str());
+    }
+
+    if !request.body.is_empty() {
+        builder = builder.body(request.body.clone());
+    }
+
+    let started = Instant::now();
+    let response = builder.send().await.map_err(failed)?;
+
+    let status = response.status();
+    let headers = response
+        .headers()
+        .iter()
+        .map(|(name, value)| {
+            (
+                name.to_string(),
+                String::from_utf8_lossy(value.as_bytes()).into_owned(),
+            )
+        })
+        .collect();
+
+    let body = response.text().await.map_err(failed)?;
+
+    Ok(Response {
+        request: format!("{} {}", request.method, request.url),
+        status,
+        headers,
+        body: pretty(body),
+        elapsed: started.elapsed(),
+    })
+}
+
+/// Indents a JSON body, leaving anything else as it came.
+fn pretty(body: String) -> String {
+    serde_json::from_str::<serde_json::Value>(&body)
+        .ok()
+        .and_then(|value| serde_json::to_string_pretty(&value).ok())
+        .unwrap_or(body)
+}
+
+impl Response {
+    pub fn label(&self) -> String {
+        format!("Response: {}", self.request)
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let color = if self.status.is_success() {
+            SUCCESS_COLOR
+        } else {
+            FAILURE_COLOR
+        };
+
+        let headers = self
+            .headers
+            .iter()
+            .fold(Column::new().spacing(2), |headers, (name, value)| {
+                headers.push(text(format!("{name}: {value}")).size(13))
+            });
+
+        container(
+            column![
+                text(&self.request).size(16),
+                row![
+                    text(self.status.to_string()).size(14).style(color),
+                    text(format!("{} ms", self.elapsed.as_millis())).size(14),
+                ]
+                .spacing(10),
+                scrollable(column![headers, text(&self.body).size(13)].spacing(10))
+                    .height(Length::Fill),
+                row![
+                    button(text("Send again").size(14)).on_press(Message::SendRequest),
+                    button(text("Open body").size(14)).on_press(Message::OpenResponseBody),
+                    button(text("Close").size(14))
+                        .on_press(Message::ClosePanel)
+                        .style(theme::Button::Secondary),
+                ]
+                .spacing(10),
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fill)
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
+
+pub fn sending<'a>() -> Element<'a, Message> {
+    container(
+        column![
+            text("HTTP request").size(16),
+            text("Waiting for a response...").size(14),
+            button(text("Close").size(14))
+                .on_press(Message::ClosePanel)
+                .style(theme::Button::Secondary),
+        ]
+        .spacing(10),
+    )
+    .width(Length::Fill)
+    .padding(10)
+    .style(theme::Container::Box)
+    .into()
+}
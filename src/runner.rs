@@ -0,0 +1,201 @@
+use crate::{widgets, workspace, Message};
+
+use iced::futures::SinkExt;
+use iced::theme;
+use iced::widget::{button, column, container, row, scrollable, text};
+use iced::{subscription, Element, Length, Subscription};
+use std::path::PathBuf;
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::sync::mpsc;
+
+const LINE_HEIGHT: f32 = 18.0;
+/// Older lines are dropped past this, so a chatty task can't fill memory.
+const MAX_LINES: usize = 20_000;
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    Line(u64, String),
+    Exited(u64, Option<i32>),
+    Failed(u64, String),
+}
+
+/// One run of a workspace task and everything it has printed so far.
+#[derive(Debug, Clone)]
+pub struct Run {
+    pub task: String,
+    command: String,
+    root: PathBuf,
+    id: u64,
+    lines: Vec<String>,
+    /// Set once the process is gone, with its exit code when it had one
+    exit: Option<Result<Option<i32>, String>>,
+    offset: f32,
+}
+
+impl Run {
+    pub fn new(id: u64, task: String, command: String, root: PathBuf) -> Self {
+        Self {
+            task,
+            command,
+            root,
+            id,
+            lines: Vec::new(),
+            exit: None,
+            offset: 0.0,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.exit.is_none()
+    }
+
+    /// Takes in an event, returning whether it ended the run.
+    pub fn handle(&mut self, event: Event) -> bool {
+        match event {
+            Event::Line(id, line) if id == self.id => {
+                self.lines.push(line);
+
+                if self.lines.len() > MAX_LINES {
+                    self.lines.drain(..self.lines.len() - MAX_LINES);
+                }
+
+                false
+            }
+            Event::Exited(id, code) if id == self.id => {
+                self.exit = Some(Ok(code));
+                true
+            }
+            Event::Failed(id, error) if id == self.id => {
+                self.exit = Some(Err(error));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn stop(&mut self) {
+        if self.is_running() {
+            self.exit = Some(Err(String::from("stopped")));
+        }
+    }
+
+    pub fn output(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    pub fn root(&self) -> &PathBuf {
+        &self.root
+    }
+
+    pub fn scrolled(&mut self, viewport: scrollable::Viewport) {
+        self.offset = viewport.absolute_offset().y;
+    }
+
+    /// Runs the task while the subscription is kept, killing it once dropped.
+    pub fn subscription(&self) -> Subscription<Event> {
+        let (id, command, root) = (self.id, self.command.clone(), self.root.clone());
+
+        subscription::channel(("runner", id), 100, move |mut output| async move {
+            let spawned = workspace::shell(&command)
+                .current_dir(&root)
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .kill_on_drop(true)
+                .spawn();
+
+            match spawned {
+                Ok(mut child) => {
+                    let (sender, mut lines) = mpsc::unbounded_channel();
+
+                    if let Some(stdout) = child.stdout.take() {
+                        tokio::spawn(forward(stdout, sender.clone()));
+                    }
+
+                    if let Some(stderr) = child.stderr.take() {
+                        tokio::spawn(forward(stderr, sender));
+                    }
+
+                    while let Some(line) = lines.recv().await {
+                        let _ = output.send(Event::Line(id, line)).await;
+                    }
+
+                    let event = match child.wait().await {
+                        Ok(status) => Event::Exited(id, status.code()),
+                        Err(error) => Event::Failed(id, error.to_string()),
+                    };
+
+                    let _ = output.send(event).await;
+                }
+                Err(error) => {
+                    let _ = output.send(Event::Failed(id, error.to_string())).await;
+                }
+            }
+
+            loop {
+                std::future::pending::<()>().await;
+            }
+        })
+    }
+
+    pub fn view(&self, height: f32) -> Element<'_, Message> {
+        let status = match &self.exit {
+            None => String::from("running"),
+            Some(Ok(Some(0))) => String::from("finished"),
+            Some(Ok(Some(code))) => format!("failed with exit code {code}"),
+            Some(Ok(None)) => String::from("ended by a signal"),
+            Some(Err(error)) => error.clone(),
+        };
+
+        let line =
+            |index: usize| -> Element<'_, Message> { text(&self.lines[index]).size(13).into() };
+
+        let rerun = if self.is_running() {
+            button(text("Stop").size(14))
+                .on_press(Message::StopTaskRun)
+                .style(theme::Button::Destructive)
+        } else {
+            button(text("Run again").size(14))
+                .on_press(Message::RunWorkspaceTask(self.task.clone()))
+        };
+
+        container(
+            column![
+                text(format!("{}: {status}", self.task)).size(16),
+                text(&self.command).size(13),
+                widgets::virtual_list(
+                    self.lines.len(),
+                    LINE_HEIGHT,
+                    self.offset,
+                    height,
+                    line,
+                    Message::OutputScrolled,
+                ),
+                row![
+                    rerun,
+                    button(text("Problems").size(14)).on_press(Message::ShowProblems),
+                    button(text("Close").size(14))
+                        .on_press(Message::ClosePanel)
+                        .style(theme::Button::Secondary),
+                ]
+                .spacing(10),
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fill)
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
+
+async fn forward(stream: impl AsyncRead + Unpin, sender: mpsc::UnboundedSender<String>) {
+    let mut lines = BufReader::new(stream).lines();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        if sender.send(line).is_err() {
+            break;
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use std::ops::Range;
+use tree_sitter::{Language, Node, Parser};
+
+const OPENERS: &[char] = &['(', '[', '{'];
+const CLOSERS: &[char] = &[')', ']', '}'];
+const QUOTES: &[char] = &['"', '\'', '`'];
+
+/// Grows the selection to the next scope around it.
+///
+/// Files tree-sitter has a grammar for grow along their syntax nodes, and anything else by
+/// matching brackets and quotes.
+pub fn expand(
+    text: &str,
+    selection: Range<usize>,
+    extension: Option<&str>,
+) -> Option<Range<usize>> {
+    let mut candidates = Vec::new();
+
+    if let Some(word) = word(text, selection.start) {
+        candidates.push(word);
+    }
+
+    match extension
+        .and_then(language)
+        .and_then(|language| nodes(language, text, selection.clone()))
+    {
+        Some(nodes) => candidates.extend(nodes),
+        None => candidates.extend(brackets(text, selection.clone())),
+    }
+
+    candidates.push(0..text.len());
+
+    candidates
+        .into_iter()
+        .filter(|candidate| {
+            candidate.start <= selection.start
+                && candidate.end >= selection.end
+                && candidate.len() > selection.len()
+        })
+        .min_by_key(|candidate| candidate.len())
+}
+
+fn language(extension: &str) -> Option<Language> {
+    match extension {
+        "rs" => Some(tree_sitter_rust::language()),
+        "py" | "pyw" => Some(tree_sitter_python::language()),
+        "js" | "mjs" | "cjs" | "jsx" => Some(tree_sitter_javascript::language()),
+        "ts" | "mts" | "cts" => Some(tree_sitter_typescript::language_typescript()),
+        "tsx" => Some(tree_sitter_typescript::language_tsx()),
+        "json" => Some(tree_sitter_json::language()),
+        "c" | "h" => Some(tree_sitter_c::language()),
+        "go" => Some(tree_sitter_go::language()),
+        _ => None,
+    }
+}
+
+/// The ranges of the syntax nodes around the selection, from the innermost out.
+fn nodes(language: Language, text: &str, selection: Range<usize>) -> Option<Vec<Range<usize>>> {
+    let mut parser = Parser::new();
+    parser.set_language(language).ok()?;
+
+    let tree = parser.parse(text, None)?;
+    let mut node = tree
+        .root_node()
+        .descendant_for_byte_range(selection.start, selection.end)?;
+
+    let mut ranges = Vec::new();
+
+    loop {
+        ranges.extend(inside(node));
+        ranges.push(node.byte_range());
+
+        match node.parent() {
+            Some(parent) => node = parent,
+            None => break,
+        }
+    }
+
+    Some(ranges)
+}
+
+// What sits between a node's delimiters, like a block's braces or a string's quotes
+fn inside(node: Node<'_>) -> Option<Range<usize>> {
+    let count = node.child_count();
+
+    let first = node.child(0).filter(|first| !first.is_named())?;
+    let last = node
+        .child(count.checked_sub(1)?)
+        .filter(|last| !last.is_named() && count > 1)?;
+
+    Some(first.end_byte()..last.start_byte())
+}
+
+fn brackets(text: &str, selection: Range<usize>) -> Vec<Range<usize>> {
+    let mut candidates = quotes(text, selection.clone());
+    let mut current = selection;
+
+    while let Some((open, close)) = enclosing(text, current.clone()) {
+        candidates.push(open + 1..close);
+        candidates.push(open..close + 1);
+
+        if text[open..].starts_with('{') {
+            let line_start = text[..open].rfind('\n').map_or(0, |index| index + 1);
+            let line_end = text[close..]
+                .find('\n')
+                .map_or(text.len(), |index| close + index);
+
+            candidates.push(line_start..close + 1);
+            candidates.push(line_start..line_end);
+        }
+
+        current = open..close + 1;
+    }
+
+    candidates
+}
+
+fn word(text: &str, offset: usize) -> Option<Range<usize>> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+
+    let start = text[..offset]
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| is_word(*c))
+        .last()
+        .map_or(offset, |(index, _)| index);
+
+    let end = text[offset..]
+        .char_indices()
+        .find(|(_, c)| !is_word(*c))
+        .map_or(text.len(), |(index, _)| offset + index);
+
+    (start < end).then_some(start..end)
+}
+
+fn quotes(text: &str, selection: Range<usize>) -> Vec<Range<usize>> {
+    let line_start = text[..selection.start]
+        .rfind('\n')
+        .map_or(0, |index| index + 1);
+    let line_end = text[selection.end..]
+        .find('\n')
+        .map_or(text.len(), |index| selection.end + index);
+
+    let mut ranges = Vec::new();
+    let mut open: Option<(usize, char)> = None;
+
+    for (index, c) in text[line_start..line_end].char_indices() {
+        let index = line_start + index;
+
+        match open {
+            Some((start, quote)) if c == quote && !is_escaped(text, index) => {
+                if start < selection.start && index >= selection.end {
+                    ranges.push(start + 1..index);
+                    ranges.push(start..index + 1);
+                }
+
+                open = None;
+            }
+            None if QUOTES.contains(&c) => open = Some((index, c)),
+            _ => {}
+        }
+    }
+
+    ranges
+}
+
+fn enclosing(text: &str, selection: Range<usize>) -> Option<(usize, usize)> {
+    let mut depth = [0usize; 3];
+
+    let (open, kind) = text[..selection.start]
+        .char_indices()
+        .rev()
+        .find_map(|(index, c)| {
+            if let Some(kind) = CLOSERS.iter().position(|closer| *closer == c) {
+                depth[kind] += 1;
+            } else if let Some(kind) = OPENERS.iter().position(|opener| *opener == c) {
+                if depth[kind] == 0 {
+                    return Some((index, kind));
+                }
+
+                depth[kind] -= 1;
+            }
+
+            None
+        })?;
+
+    let mut depth = 0;
+
+    let close = text[selection.end..]
+        .char_indices()
+        .find_map(|(index, c)| {
+            if c == OPENERS[kind] {
+                depth += 1;
+            } else if c == CLOSERS[kind] {
+                if depth == 0 {
+                    return Some(selection.end + index);
+                }
+
+                depth -= 1;
+            }
+
+            None
+        })?;
+
+    Some((open, close))
+}
+
+fn is_escaped(text: &str, index: usize) -> bool {
+    text[..index]
+        .chars()
+        .rev()
+        .take_while(|c| *c == '\\')
+        .count()
+        % 2
+        == 1
+}
@@ -0,0 +1,140 @@
+use crate::{Error, Message};
+
+use iced::theme;
+use iced::widget::{button, column, container, row, scrollable, text, text_input, Column};
+use iced::{Element, Length};
+use std::io;
+use std::path::{Path, PathBuf};
+
+const EXTENSION: &str = "txt";
+
+pub const INBOX: &str = "inbox";
+
+#[derive(Debug, Clone, Default)]
+pub struct Menu {
+    pub name: String,
+    pub scratches: Vec<String>,
+}
+
+impl Menu {
+    pub fn view(&self) -> Element<'_, Message> {
+        let create = (!sanitize(&self.name).is_empty()).then_some(Message::CreateScratch);
+
+        let scratches = self.scratches.iter().map(|name| {
+            button(text(name).size(14))
+                .on_press(Message::OpenScratch(name.clone()))
+                .width(Length::Fill)
+                .padding(4)
+                .style(theme::Button::Text)
+                .into()
+        });
+
+        container(
+            column![
+                text("Scratch buffers").size(16),
+                row![
+                    text_input("New scratch name", &self.name)
+                        .on_input(Message::ScratchNameChanged)
+                        .on_submit(Message::CreateScratch)
+                        .size(14)
+                        .padding(5),
+                    button(text("Create").size(14)).on_press_maybe(create),
+                ]
+                .spacing(10),
+                scrollable(Column::with_children(scratches.collect())).height(Length::Fill),
+                button(text("Close").size(14))
+                    .on_press(Message::ClosePanel)
+                    .style(theme::Button::Secondary),
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fill)
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
+
+pub fn directory() -> Option<PathBuf> {
+    dirs::data_dir().map(|directory| directory.join("bonkitor").join("scratch"))
+}
+
+pub fn is_scratch(path: &Path) -> bool {
+    directory().is_some_and(|directory| path.starts_with(directory))
+}
+
+pub fn path(name: &str) -> Option<PathBuf> {
+    let name = sanitize(name);
+
+    (!name.is_empty())
+        .then(|| directory().map(|directory| directory.join(format!("{name}.{EXTENSION}"))))
+        .flatten()
+}
+
+pub async fn list() -> Result<Vec<String>, Error> {
+    let Some(directory) = directory() else {
+        return Ok(Vec::new());
+    };
+
+    let mut entries = match tokio::fs::read_dir(&directory).await {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(Error::IOFailed(error.kind())),
+    };
+
+    let mut names = Vec::new();
+
+    while let Some(entry) = entries
+        .next_entry()
+        .await
+        .map_err(|error| Error::IOFailed(error.kind()))?
+    {
+        let path = entry.path();
+
+        if path
+            .extension()
+            .is_some_and(|extension| extension == EXTENSION)
+        {
+            if let Some(stem) = path.file_stem() {
+                names.push(stem.to_string_lossy().into_owned());
+            }
+        }
+    }
+
+    names.sort();
+
+    Ok(names)
+}
+
+pub async fn create(name: String) -> Result<PathBuf, Error> {
+    let path = path(&name).ok_or(Error::IOFailed(io::ErrorKind::InvalidInput))?;
+
+    if let Some(directory) = path.parent() {
+        tokio::fs::create_dir_all(directory)
+            .await
+            .map_err(|error| Error::IOFailed(error.kind()))?;
+    }
+
+    if !path.exists() {
+        tokio::fs::write(&path, "")
+            .await
+            .map_err(|error| Error::IOFailed(error.kind()))?;
+    }
+
+    Ok(path)
+}
+
+pub async fn save(path: PathBuf, text: String) -> Result<PathBuf, Error> {
+    tokio::fs::write(&path, text)
+        .await
+        .map_err(|error| Error::IOFailed(error.kind()))?;
+
+    Ok(path)
+}
+
+pub fn sanitize(name: &str) -> String {
+    name.trim()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | ' '))
+        .collect()
+}
@@ -0,0 +1,460 @@
+use crate::{tree, widgets, Error, Message};
+
+use iced::theme;
+use iced::widget::{button, checkbox, column, container, row, scrollable, text, text_input};
+use iced::{Color, Element, Length};
+use regex::Regex;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+const MAX_MATCHES: usize = 5_000;
+const BACKUP_DIRECTORY: &str = ".bonkitor/backups";
+const REMOVED_COLOR: Color = Color::from_rgb(0.9, 0.3, 0.3);
+const ADDED_COLOR: Color = Color::from_rgb(0.4, 0.75, 0.45);
+const ROW_HEIGHT: f32 = 22.0;
+/// Rows tall enough for a match with its replacement preview below it
+const PREVIEW_HEIGHT: f32 = 56.0;
+
+#[derive(Debug, Clone, Default)]
+pub struct Search {
+    pub query: String,
+    pub replacement: String,
+    pub is_regex: bool,
+    pub is_searching: bool,
+    pub groups: Vec<Group>,
+    pub summary: Option<String>,
+    /// How far the results are scrolled, in pixels
+    pub offset: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Group {
+    pub path: PathBuf,
+    pub matches: Vec<Match>,
+    pub is_selected: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Match {
+    pub line: usize,
+    pub text: String,
+    pub is_selected: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Export {
+    Buffer,
+    File,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Summary {
+    pub paths: Vec<PathBuf>,
+    pub replacements: usize,
+    pub backups: Option<PathBuf>,
+}
+
+impl Search {
+    pub fn pattern(&self) -> Option<Regex> {
+        if self.query.is_empty() {
+            return None;
+        }
+
+        if self.is_regex {
+            Regex::new(&self.query).ok()
+        } else {
+            Regex::new(&regex::escape(&self.query)).ok()
+        }
+    }
+
+    pub fn toggle_file(&mut self, index: usize) {
+        if let Some(group) = self.groups.get_mut(index) {
+            group.is_selected = !group.is_selected;
+        }
+    }
+
+    pub fn toggle_match(&mut self, group: usize, index: usize) {
+        if let Some(found) = self
+            .groups
+            .get_mut(group)
+            .and_then(|group| group.matches.get_mut(index))
+        {
+            found.is_selected = !found.is_selected;
+        }
+    }
+
+    pub fn selected(&self) -> Vec<(PathBuf, Vec<usize>)> {
+        self.groups
+            .iter()
+            .filter(|group| group.is_selected)
+            .map(|group| {
+                let lines = group
+                    .matches
+                    .iter()
+                    .filter(|found| found.is_selected)
+                    .map(|found| found.line)
+                    .collect::<Vec<_>>();
+
+                (group.path.clone(), lines)
+            })
+            .filter(|(_, lines)| !lines.is_empty())
+            .collect()
+    }
+
+    pub fn report(&self, root: Option<&Path>) -> String {
+        self.groups
+            .iter()
+            .flat_map(|group| {
+                let path = root
+                    .and_then(|root| group.path.strip_prefix(root).ok())
+                    .unwrap_or(&group.path);
+
+                group.matches.iter().map(move |found| {
+                    format!("{}:{}: {}\n", path.display(), found.line + 1, found.text)
+                })
+            })
+            .collect()
+    }
+
+    pub fn scrolled(&mut self, viewport: scrollable::Viewport) {
+        self.offset = viewport.absolute_offset().y;
+    }
+
+    pub fn view(&self, root: Option<&Path>, height: f32) -> Element<'_, Message> {
+        let pattern = self.pattern();
+        let is_invalid = pattern.is_none() && !self.query.is_empty();
+
+        let status = if self.is_searching {
+            String::from("Searching...")
+        } else if is_invalid {
+            String::from("Invalid regular expression")
+        } else if let Some(summary) = &self.summary {
+            summary.clone()
+        } else {
+            let count: usize = self.groups.iter().map(|group| group.matches.len()).sum();
+
+            format!("{count} matches in {} files", self.groups.len())
+        };
+
+        // Replacing is offered for any pattern, so an empty replacement previews its deletions too
+        let replacing = pattern.as_ref();
+
+        // Each file's checkbox is a row of its own, followed by a row per match
+        let rows: Vec<(usize, Option<usize>)> = self
+            .groups
+            .iter()
+            .enumerate()
+            .flat_map(|(index, group)| {
+                std::iter::once((index, None))
+                    .chain((0..group.matches.len()).map(move |offset| (index, Some(offset))))
+            })
+            .collect();
+
+        let result = |row: usize| -> Element<'_, Message> {
+            let (index, offset) = rows[row];
+            let group = &self.groups[index];
+
+            let Some(offset) = offset else {
+                let title = root
+                    .and_then(|root| group.path.strip_prefix(root).ok())
+                    .unwrap_or(&group.path)
+                    .display()
+                    .to_string();
+
+                return checkbox(title, group.is_selected, move |_| {
+                    Message::SearchFileToggled(index)
+                })
+                .size(14)
+                .into();
+            };
+
+            let found = &group.matches[offset];
+
+            let mut preview = column![button(
+                text(format!("{}: {}", found.line + 1, found.text.trim())).size(13)
+            )
+            .on_press(Message::SearchResultSelected(
+                group.path.clone(),
+                found.line
+            ))
+            .style(theme::Button::Text)
+            .padding(2)];
+
+            if let Some(pattern) = replacing {
+                let replaced = pattern.replace_all(&found.text, self.replacement.as_str());
+
+                preview = preview
+                    .push(
+                        text(format!("- {}", found.text.trim()))
+                            .size(12)
+                            .style(REMOVED_COLOR),
+                    )
+                    .push(
+                        text(format!("+ {}", replaced.trim()))
+                            .size(12)
+                            .style(ADDED_COLOR),
+                    );
+            }
+
+            row![
+                checkbox("", found.is_selected && group.is_selected, move |_| {
+                    Message::SearchMatchToggled(index, offset)
+                })
+                .size(14),
+                preview,
+            ]
+            .spacing(5)
+            .into()
+        };
+
+        let can_search = pattern.is_some() && !self.is_searching;
+        let can_replace = can_search && !self.selected().is_empty();
+        let can_export = !self.groups.is_empty() && !self.is_searching;
+
+        container(
+            column![
+                text("Find in files").size(16),
+                text_input("Find", &self.query)
+                    .on_input(Message::SearchQueryChanged)
+                    .on_submit(Message::RunSearch)
+                    .size(14)
+                    .padding(5),
+                text_input("Replace with", &self.replacement)
+                    .on_input(Message::ReplacementChanged)
+                    .size(14)
+                    .padding(5),
+                checkbox(
+                    "Regular expression",
+                    self.is_regex,
+                    Message::SearchRegexToggled
+                )
+                .size(14),
+                row![
+                    button(text("Search").size(14))
+                        .on_press_maybe(can_search.then_some(Message::RunSearch)),
+                    button(text("Replace selected").size(14))
+                        .on_press_maybe(can_replace.then_some(Message::ApplyReplacements))
+                        .style(theme::Button::Destructive),
+                ]
+                .spacing(10),
+                row![
+                    button(text("Export to buffer").size(14)).on_press_maybe(
+                        can_export.then_some(Message::ExportResults(Export::Buffer))
+                    ),
+                    button(text("Export to file").size(14))
+                        .on_press_maybe(can_export.then_some(Message::ExportResults(Export::File))),
+                ]
+                .spacing(10),
+                text(status).size(14),
+                widgets::virtual_list(
+                    rows.len(),
+                    if replacing.is_some() {
+                        PREVIEW_HEIGHT
+                    } else {
+                        ROW_HEIGHT
+                    },
+                    self.offset,
+                    height,
+                    result,
+                    Message::SearchScrolled,
+                ),
+                button(text("Close").size(14))
+                    .on_press(Message::ClosePanel)
+                    .style(theme::Button::Secondary),
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fill)
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
+
+pub async fn export(report: String) -> Result<PathBuf, Error> {
+    let path = rfd::AsyncFileDialog::new()
+        .set_title("Export search results...")
+        .set_file_name("search-results.txt")
+        .save_file()
+        .await
+        .ok_or(Error::DialogClosed)?
+        .path()
+        .to_owned();
+
+    tokio::fs::write(&path, report)
+        .await
+        .map_err(|error| Error::IOFailed(error.kind()))?;
+
+    Ok(path)
+}
+
+/// Searches the folder, or only `candidates` when the index has narrowed them down.
+pub async fn find(
+    root: PathBuf,
+    filter: tree::Filter,
+    pattern: Regex,
+    candidates: Option<Vec<PathBuf>>,
+) -> Vec<Group> {
+    tokio::task::spawn_blocking(move || {
+        let mut total = 0;
+
+        let paths = candidates.unwrap_or_else(|| {
+            tree::walk(&root, &filter)
+                .into_iter()
+                .filter(|entry| !entry.is_dir)
+                .map(|entry| entry.path)
+                .collect()
+        });
+
+        paths
+            .into_iter()
+            .filter(|path| {
+                std::fs::metadata(path).is_ok_and(|metadata| metadata.len() <= MAX_FILE_SIZE)
+            })
+            .filter_map(|path| {
+                if total >= MAX_MATCHES {
+                    return None;
+                }
+
+                let text = std::fs::read_to_string(&path).ok()?;
+
+                let matches: Vec<_> = lines(&text)
+                    .map(|(line, _)| line)
+                    .enumerate()
+                    .filter(|(_, line)| pattern.is_match(line))
+                    .take(MAX_MATCHES - total)
+                    .map(|(line, text)| Match {
+                        line,
+                        text: text.to_string(),
+                        is_selected: true,
+                    })
+                    .collect();
+
+                total += matches.len();
+
+                (!matches.is_empty()).then_some(Group {
+                    path,
+                    matches,
+                    is_selected: true,
+                })
+            })
+            .collect()
+    })
+    .await
+    .unwrap_or_default()
+}
+
+/// The file's lines with the `\r` of a CRLF split off, numbered the same for finding and replacing.
+fn lines(text: &str) -> impl Iterator<Item = (&str, &str)> {
+    text.split('\n').map(|line| match line.strip_suffix('\r') {
+        Some(line) => (line, "\r"),
+        None => (line, ""),
+    })
+}
+
+pub async fn replace(
+    root: PathBuf,
+    files: Vec<(PathBuf, Vec<usize>)>,
+    pattern: Regex,
+    replacement: String,
+) -> Result<Summary, Error> {
+    tokio::task::spawn_blocking(move || apply(&root, files, &pattern, &replacement))
+        .await
+        .map_err(|_| Error::IOFailed(io::ErrorKind::Other))?
+}
+
+fn apply(
+    root: &Path,
+    files: Vec<(PathBuf, Vec<usize>)>,
+    pattern: &Regex,
+    replacement: &str,
+) -> Result<Summary, Error> {
+    let io_error = |error: io::Error| Error::IOFailed(error.kind());
+
+    let mut summary = Summary::default();
+    let mut changes = Vec::new();
+
+    // Work everything out before touching the disk so a bad file aborts cleanly
+    for (path, lines) in files {
+        let original = std::fs::read_to_string(&path).map_err(io_error)?;
+        let mut replacements = 0;
+
+        let updated = self::lines(&original)
+            .enumerate()
+            .map(|(line, (text, ending))| {
+                if lines.contains(&line) {
+                    replacements += pattern.find_iter(text).count();
+                    pattern.replace_all(text, replacement).into_owned() + ending
+                } else {
+                    text.to_string() + ending
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        if updated != original {
+            summary.replacements += replacements;
+            changes.push((path, original, updated));
+        }
+    }
+
+    let backups = root
+        .join(BACKUP_DIRECTORY)
+        .join(chrono::Local::now().format("%Y%m%d-%H%M%S").to_string());
+
+    for (path, original, _) in &changes {
+        let backup = backups.join(path.strip_prefix(root).unwrap_or(path));
+
+        if let Some(parent) = backup.parent() {
+            std::fs::create_dir_all(parent).map_err(io_error)?;
+        }
+
+        std::fs::write(backup, original).map_err(io_error)?;
+    }
+
+    let mut written: Vec<&Path> = Vec::new();
+
+    for (path, _, updated) in &changes {
+        if let Err(error) = write(path, updated) {
+            for (path, original, _) in changes
+                .iter()
+                .filter(|(path, ..)| written.contains(&path.as_path()))
+            {
+                let _ = std::fs::write(path, original);
+            }
+
+            return Err(io_error(error));
+        }
+
+        written.push(path);
+    }
+
+    summary.paths = changes.into_iter().map(|(path, ..)| path).collect();
+    summary.backups = (!summary.paths.is_empty()).then_some(backups);
+
+    Ok(summary)
+}
+
+/// Replaces a file's contents all at once, through a copy next to it that takes its place.
+///
+/// A link is followed so the file it points to changes rather than the link, and the copy
+/// gets the original's permissions before taking its place.
+fn write(path: &Path, contents: &str) -> io::Result<()> {
+    let target = std::fs::canonicalize(path)?;
+    let permissions = std::fs::metadata(&target)?.permissions();
+
+    let mut name = target.file_name().unwrap_or_default().to_os_string();
+    name.push(".bonkitor-replace");
+    let temporary = target.with_file_name(name);
+
+    let result = std::fs::write(&temporary, contents)
+        .and_then(|()| std::fs::set_permissions(&temporary, permissions))
+        .and_then(|()| std::fs::rename(&temporary, &target));
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temporary);
+    }
+
+    result
+}
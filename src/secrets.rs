@@ -0,0 +1,176 @@
+use crate::Message;
+
+use iced::theme;
+use iced::widget::{button, column, container, row, scrollable, text, Column};
+use iced::{Element, Length};
+
+const TOKEN_PREFIXES: &[&str] = &[
+    "ghp_",
+    "gho_",
+    "ghs_",
+    "github_pat_",
+    "glpat-",
+    "xoxb-",
+    "xoxp-",
+    "sk-",
+];
+const MIN_TOKEN_LENGTH: usize = 20;
+const MIN_RANDOM_LENGTH: usize = 24;
+// Bits per character; base64 secrets sit well above prose and identifiers
+const MIN_ENTROPY: f64 = 4.2;
+const MAX_SHOWN: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    AwsKey,
+    PrivateKey,
+    Token,
+    Random,
+}
+
+impl Kind {
+    fn label(self) -> &'static str {
+        match self {
+            Kind::AwsKey => "AWS access key",
+            Kind::PrivateKey => "Private key",
+            Kind::Token => "API token",
+            Kind::Random => "High-entropy string",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub line: usize,
+    pub kind: Kind,
+}
+
+pub fn scan(text: &str) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    for (line, content) in text.lines().enumerate() {
+        if content.contains("-----BEGIN") && content.contains("PRIVATE KEY-----") {
+            findings.push(Finding {
+                line,
+                kind: Kind::PrivateKey,
+            });
+
+            continue;
+        }
+
+        let kind = tokens(content).find_map(classify);
+
+        if let Some(kind) = kind {
+            findings.push(Finding { line, kind });
+        }
+    }
+
+    findings
+}
+
+fn tokens(line: &str) -> impl Iterator<Item = &str> {
+    line.split(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '+' | '/' | '=')))
+        .map(|token| token.trim_end_matches('='))
+        .filter(|token| token.len() >= MIN_TOKEN_LENGTH)
+}
+
+fn classify(token: &str) -> Option<Kind> {
+    let is_aws = token.len() == 20
+        && (token.starts_with("AKIA") || token.starts_with("ASIA"))
+        && token
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit());
+
+    if is_aws {
+        return Some(Kind::AwsKey);
+    }
+
+    if TOKEN_PREFIXES
+        .iter()
+        .any(|prefix| token.starts_with(prefix))
+    {
+        return Some(Kind::Token);
+    }
+
+    let has_digit = token.chars().any(|c| c.is_ascii_digit());
+    let has_letter = token.chars().any(|c| c.is_ascii_alphabetic());
+
+    (token.len() >= MIN_RANDOM_LENGTH && has_digit && has_letter && entropy(token) >= MIN_ENTROPY)
+        .then_some(Kind::Random)
+}
+
+fn entropy(token: &str) -> f64 {
+    let mut counts = [0usize; 128];
+
+    for byte in token.bytes() {
+        counts[usize::from(byte & 0x7F)] += 1;
+    }
+
+    let length = token.len() as f64;
+
+    counts
+        .iter()
+        .filter(|count| **count > 0)
+        .map(|count| {
+            let probability = *count as f64 / length;
+
+            -probability * probability.log2()
+        })
+        .sum()
+}
+
+/// A warning shown before content that looks like it holds credentials leaves the buffer.
+#[derive(Debug, Clone)]
+pub struct Warning {
+    pub findings: Vec<Finding>,
+    /// What to carry on with if the warning is dismissed
+    pub then: Message,
+    pub action: &'static str,
+}
+
+impl Warning {
+    pub fn view(&self, can_suppress: bool) -> Element<'_, Message> {
+        let findings = self.findings.iter().take(MAX_SHOWN).map(|finding| {
+            button(
+                text(format!(
+                    "Line {}: {}",
+                    finding.line + 1,
+                    finding.kind.label()
+                ))
+                .size(14),
+            )
+            .on_press(Message::OutlineSelected(finding.line))
+            .style(theme::Button::Text)
+            .into()
+        });
+
+        let mut actions = row![button(text(format!("{} anyway", self.action)).size(14))
+            .on_press(Message::ConfirmSecrets)]
+        .spacing(10);
+
+        if can_suppress {
+            actions = actions.push(
+                button(text("Don't warn for this file").size(14))
+                    .on_press(Message::SuppressSecrets)
+                    .style(theme::Button::Secondary),
+            );
+        }
+
+        container(
+            column![
+                text("Possible secrets").size(16),
+                text("This buffer looks like it contains credentials.").size(14),
+                scrollable(Column::with_children(findings.collect())).height(Length::Fill),
+                actions,
+                button(text("Cancel").size(14))
+                    .on_press(Message::ClosePanel)
+                    .style(theme::Button::Secondary),
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fill)
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
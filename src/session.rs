@@ -0,0 +1,253 @@
+use crate::{scratch, tabs, Error, Message};
+
+use iced::theme;
+use iced::widget::{button, column, container, row, scrollable, text, text_input, Column};
+use iced::{Element, Length};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::{Path, PathBuf};
+
+const EXTENSION: &str = "toml";
+const POSITIONS_FILE: &str = "positions.toml";
+const MAX_POSITIONS: usize = 1_000;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    pub workspace: Option<PathBuf>,
+    pub open_files: Vec<PathBuf>,
+    pub active: usize,
+    pub show_tree: bool,
+    pub focus_mode: bool,
+    pub labels: Vec<Label>,
+}
+
+/// The custom name and color of an open file's tab.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Label {
+    pub path: PathBuf,
+    pub name: Option<String>,
+    pub tag: Option<tabs::Tag>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Positions {
+    files: Vec<Position>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Position {
+    path: PathBuf,
+    line: usize,
+    column: usize,
+}
+
+impl Positions {
+    pub fn get(&self, path: &Path) -> Option<(usize, usize)> {
+        self.files
+            .iter()
+            .find(|position| position.path == path)
+            .map(|position| (position.line, position.column))
+    }
+
+    pub fn set(&mut self, path: PathBuf, (line, column): (usize, usize)) {
+        self.files.retain(|position| position.path != path);
+        self.files.insert(0, Position { path, line, column });
+        self.files.truncate(MAX_POSITIONS);
+    }
+
+    pub async fn load() -> Result<Self, Error> {
+        let Some(path) = positions_file() else {
+            return Ok(Self::default());
+        };
+
+        match tokio::fs::read_to_string(&path).await {
+            Ok(text) => {
+                toml::from_str(&text).map_err(|error| Error::InvalidConfig(error.to_string()))
+            }
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(Error::IOFailed(error.kind())),
+        }
+    }
+
+    pub async fn save(self) -> Result<(), Error> {
+        let path = positions_file().ok_or(Error::IOFailed(io::ErrorKind::NotFound))?;
+        let text = toml::to_string_pretty(&self)
+            .map_err(|error| Error::InvalidConfig(error.to_string()))?;
+
+        if let Some(directory) = path.parent() {
+            tokio::fs::create_dir_all(directory)
+                .await
+                .map_err(|error| Error::IOFailed(error.kind()))?;
+        }
+
+        tokio::fs::write(&path, text)
+            .await
+            .map_err(|error| Error::IOFailed(error.kind()))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Manager {
+    pub name: String,
+    pub sessions: Vec<String>,
+}
+
+impl Manager {
+    pub fn view(&self, current: Option<&str>) -> Element<'_, Message> {
+        let name = scratch::sanitize(&self.name);
+        let has_name = !name.is_empty();
+
+        let sessions = self.sessions.iter().map(|session| {
+            let title = if current == Some(session.as_str()) {
+                format!("{session} (current)")
+            } else {
+                session.clone()
+            };
+
+            row![
+                button(text(title).size(14))
+                    .on_press(Message::SwitchSession(session.clone()))
+                    .width(Length::Fill)
+                    .padding(4)
+                    .style(theme::Button::Text),
+                button(text("Rename").size(13))
+                    .on_press_maybe(
+                        (has_name && name != *session)
+                            .then(|| Message::RenameSession(session.clone())),
+                    )
+                    .padding(4)
+                    .style(theme::Button::Secondary),
+                button(text("Delete").size(13))
+                    .on_press(Message::DeleteSession(session.clone()))
+                    .padding(4)
+                    .style(theme::Button::Destructive),
+            ]
+            .spacing(5)
+            .into()
+        });
+
+        container(
+            column![
+                text("Sessions").size(16),
+                row![
+                    text_input("Session name", &self.name)
+                        .on_input(Message::SessionNameChanged)
+                        .on_submit(Message::SaveSession)
+                        .size(14)
+                        .padding(5),
+                    button(text("Save").size(14))
+                        .on_press_maybe(has_name.then_some(Message::SaveSession)),
+                ]
+                .spacing(10),
+                scrollable(Column::with_children(sessions.collect()).spacing(4))
+                    .height(Length::Fill),
+                button(text("Close").size(14))
+                    .on_press(Message::ClosePanel)
+                    .style(theme::Button::Secondary),
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fill)
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
+
+pub async fn list() -> Result<Vec<String>, Error> {
+    let Some(directory) = directory() else {
+        return Ok(Vec::new());
+    };
+
+    let mut entries = match tokio::fs::read_dir(&directory).await {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(Error::IOFailed(error.kind())),
+    };
+
+    let mut names = Vec::new();
+
+    while let Some(entry) = entries
+        .next_entry()
+        .await
+        .map_err(|error| Error::IOFailed(error.kind()))?
+    {
+        let path = entry.path();
+
+        if path
+            .extension()
+            .is_some_and(|extension| extension == EXTENSION)
+        {
+            if let Some(stem) = path.file_stem() {
+                names.push(stem.to_string_lossy().into_owned());
+            }
+        }
+    }
+
+    names.sort();
+
+    Ok(names)
+}
+
+pub async fn load(name: String) -> Result<(String, Session), Error> {
+    let path = file(&name)?;
+
+    let text = tokio::fs::read_to_string(&path)
+        .await
+        .map_err(|error| Error::IOFailed(error.kind()))?;
+
+    let session = toml::from_str(&text).map_err(|error| Error::InvalidConfig(error.to_string()))?;
+
+    Ok((name, session))
+}
+
+pub async fn save(name: String, session: Session) -> Result<String, Error> {
+    let path = file(&name)?;
+    let text = toml::to_string_pretty(&session)
+        .map_err(|error| Error::InvalidConfig(error.to_string()))?;
+
+    if let Some(directory) = path.parent() {
+        tokio::fs::create_dir_all(directory)
+            .await
+            .map_err(|error| Error::IOFailed(error.kind()))?;
+    }
+
+    tokio::fs::write(&path, text)
+        .await
+        .map_err(|error| Error::IOFailed(error.kind()))?;
+
+    Ok(scratch::sanitize(&name))
+}
+
+pub async fn rename(from: String, to: String) -> Result<String, Error> {
+    tokio::fs::rename(file(&from)?, file(&to)?)
+        .await
+        .map_err(|error| Error::IOFailed(error.kind()))?;
+
+    Ok(scratch::sanitize(&to))
+}
+
+pub async fn delete(name: String) -> Result<(), Error> {
+    tokio::fs::remove_file(file(&name)?)
+        .await
+        .map_err(|error| Error::IOFailed(error.kind()))
+}
+
+fn directory() -> Option<PathBuf> {
+    dirs::data_dir().map(|directory| directory.join("bonkitor").join("sessions"))
+}
+
+fn positions_file() -> Option<PathBuf> {
+    dirs::data_dir().map(|directory| directory.join("bonkitor").join(POSITIONS_FILE))
+}
+
+fn file(name: &str) -> Result<PathBuf, Error> {
+    let name = scratch::sanitize(name);
+
+    directory()
+        .filter(|_| !name.is_empty())
+        .map(|directory| directory.join(format!("{name}.{EXTENSION}")))
+        .ok_or(Error::IOFailed(io::ErrorKind::InvalidInput))
+}
@@ -0,0 +1,191 @@
+use crate::Message;
+
+use iced::theme;
+use iced::widget::{button, pick_list, row, text};
+use iced::Element;
+use tts::Tts;
+
+const RATE_STEP: f32 = 0.25;
+const MIN_RATE: f32 = 0.5;
+const MAX_RATE: f32 = 3.0;
+
+pub struct Reader {
+    tts: Tts,
+    voices: Vec<String>,
+    sentences: Vec<String>,
+    next: usize,
+    is_paused: bool,
+}
+
+impl Reader {
+    pub fn new(rate: f32, voice: Option<&str>) -> Result<Self, String> {
+        let tts = Tts::default().map_err(|error| error.to_string())?;
+
+        let voices = tts
+            .voices()
+            .map(|voices| voices.iter().map(|voice| voice.name()).collect())
+            .unwrap_or_default();
+
+        let mut reader = Self {
+            tts,
+            voices,
+            sentences: Vec::new(),
+            next: 0,
+            is_paused: false,
+        };
+
+        reader.set_rate(rate);
+
+        if let Some(voice) = voice {
+            reader.set_voice(voice);
+        }
+
+        Ok(reader)
+    }
+
+    pub fn read(&mut self, text: &str) {
+        let _ = self.tts.stop();
+
+        self.sentences = sentences(text);
+        self.next = 0;
+        self.is_paused = false;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.next < self.sentences.len() || self.is_speaking()
+    }
+
+    /// Queues the next sentence once the current one is done.
+    pub fn poll(&mut self) {
+        if self.is_paused || self.is_speaking() {
+            return;
+        }
+
+        if let Some(sentence) = self.sentences.get(self.next) {
+            let _ = self.tts.speak(sentence.as_str(), false);
+            self.next += 1;
+        }
+    }
+
+    // Most speech backends cannot pause, so the interrupted sentence is read again
+    pub fn pause(&mut self) {
+        let _ = self.tts.stop();
+
+        self.is_paused = true;
+        self.next = self.next.saturating_sub(1);
+    }
+
+    pub fn resume(&mut self) {
+        self.is_paused = false;
+    }
+
+    pub fn stop(&mut self) {
+        let _ = self.tts.stop();
+
+        self.sentences.clear();
+        self.next = 0;
+        self.is_paused = false;
+    }
+
+    pub fn set_rate(&mut self, multiplier: f32) {
+        let rate =
+            (self.tts.normal_rate() * multiplier).clamp(self.tts.min_rate(), self.tts.max_rate());
+
+        let _ = self.tts.set_rate(rate);
+    }
+
+    pub fn set_voice(&mut self, name: &str) {
+        if let Some(voice) = self
+            .tts
+            .voices()
+            .ok()
+            .and_then(|voices| voices.into_iter().find(|voice| voice.name() == name))
+        {
+            let _ = self.tts.set_voice(&voice);
+        }
+    }
+
+    pub fn view(&self, rate: f32, voice: Option<&str>) -> Element<'_, Message> {
+        let toggle = if self.is_paused {
+            button(text("Resume").size(14)).on_press(Message::ResumeSpeech)
+        } else {
+            button(text("Pause").size(14)).on_press(Message::PauseSpeech)
+        };
+
+        let mut controls = row![
+            text(format!(
+                "Reading {}/{}",
+                self.next.max(1),
+                self.sentences.len()
+            ))
+            .size(14),
+            toggle.padding([0, 5]),
+            button(text("Stop").size(14))
+                .on_press(Message::StopSpeech)
+                .padding([0, 5])
+                .style(theme::Button::Secondary),
+            button(text("−").size(14))
+                .on_press_maybe((rate > MIN_RATE).then_some(Message::SpeechRate(rate - RATE_STEP)))
+                .padding([0, 5])
+                .style(theme::Button::Text),
+            text(format!("{rate:.2}×")).size(14),
+            button(text("+").size(14))
+                .on_press_maybe((rate < MAX_RATE).then_some(Message::SpeechRate(rate + RATE_STEP)))
+                .padding([0, 5])
+                .style(theme::Button::Text),
+        ]
+        .spacing(5);
+
+        if !self.voices.is_empty() {
+            controls = controls.push(
+                pick_list(
+                    self.voices.clone(),
+                    voice.map(str::to_string),
+                    Message::SpeechVoice,
+                )
+                .placeholder("Default voice")
+                .text_size(14)
+                .padding([0, 5]),
+            );
+        }
+
+        controls.into()
+    }
+
+    fn is_speaking(&self) -> bool {
+        self.tts.is_speaking().unwrap_or(false)
+    }
+}
+
+// Speaking a sentence at a time lets reading pause and resume
+fn sentences(text: &str) -> Vec<String> {
+    let mut sentences = Vec::new();
+    let mut current = String::new();
+    let mut characters = text.chars().peekable();
+
+    while let Some(c) = characters.next() {
+        current.push(c);
+
+        let is_end = match c {
+            '.' | '!' | '?' => characters.peek().map_or(true, |next| next.is_whitespace()),
+            '\n' => characters.peek() == Some(&'\n'),
+            _ => false,
+        };
+
+        if is_end {
+            push(&mut sentences, std::mem::take(&mut current));
+        }
+    }
+
+    push(&mut sentences, current);
+
+    sentences
+}
+
+fn push(sentences: &mut Vec<String>, raw: String) {
+    let sentence = raw.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    if !sentence.is_empty() {
+        sentences.push(sentence);
+    }
+}
@@ -0,0 +1,142 @@
+use crate::Message;
+
+use iced::theme;
+use iced::widget::{button, column, container, row, scrollable, text, Column};
+use iced::{Element, Length};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+const WORDS_PER_MINUTE: usize = 230;
+const TAB_WIDTH: usize = 4;
+const BAR_WIDTH: usize = 20;
+
+#[derive(Debug, Clone, Default)]
+pub struct Statistics {
+    lines: usize,
+    words: usize,
+    characters: usize,
+    bytes: usize,
+    longest_line: (usize, usize),
+    indentation: BTreeMap<usize, usize>,
+}
+
+impl Statistics {
+    pub fn compute(text: &str) -> Self {
+        let mut statistics = Self {
+            bytes: text.len(),
+            ..Self::default()
+        };
+
+        for (number, line) in text.lines().enumerate() {
+            let length = line.chars().count();
+
+            statistics.lines += 1;
+            statistics.characters += length;
+            statistics.words += line.split_whitespace().count();
+
+            if length > statistics.longest_line.1 {
+                statistics.longest_line = (number + 1, length);
+            }
+
+            if !line.trim().is_empty() {
+                *statistics.indentation.entry(indent(line)).or_default() += 1;
+            }
+        }
+
+        // Newlines count as characters, like most word processors report them
+        statistics.characters += text.matches('\n').count();
+
+        statistics
+    }
+
+    pub fn reading_time(&self) -> String {
+        match self.words.div_ceil(WORDS_PER_MINUTE) {
+            0 | 1 => String::from("under a minute"),
+            minutes => format!("about {minutes} minutes"),
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let entry = |label: &str, value: String| {
+            row![
+                text(label).size(14).width(Length::Fill),
+                text(value).size(14),
+            ]
+            .into()
+        };
+
+        let (longest, length) = self.longest_line;
+        let most = self.indentation.values().copied().max().unwrap_or(1);
+
+        let histogram = self.indentation.iter().map(|(width, count)| {
+            let bar = "█".repeat((count * BAR_WIDTH).div_ceil(most));
+
+            row![
+                text(format!("{width:>3}")).size(13).width(32),
+                text(bar).size(13).width(Length::Fill),
+                text(count).size(13),
+            ]
+            .spacing(5)
+            .into()
+        });
+
+        container(
+            column![
+                text("Statistics").size(16),
+                Column::with_children(vec![
+                    entry("Lines", self.lines.to_string()),
+                    entry("Words", self.words.to_string()),
+                    entry("Characters", self.characters.to_string()),
+                    entry("Bytes", self.bytes.to_string()),
+                    entry("Longest line", format!("{length} (line {longest})")),
+                    entry("Reading time", self.reading_time()),
+                ])
+                .spacing(4),
+                text("Indentation").size(14),
+                scrollable(Column::with_children(histogram.collect())).height(Length::Fill),
+                row![
+                    button(text("Refresh").size(14)).on_press(Message::ShowStatistics),
+                    button(text("Close").size(14))
+                        .on_press(Message::ClosePanel)
+                        .style(theme::Button::Secondary),
+                ]
+                .spacing(10),
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fill)
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
+
+pub fn computing<'a>() -> Element<'a, Message> {
+    container(
+        column![
+            text("Statistics").size(16),
+            text("Counting...").size(14),
+            button(text("Close").size(14))
+                .on_press(Message::ClosePanel)
+                .style(theme::Button::Secondary),
+        ]
+        .spacing(10),
+    )
+    .width(Length::Fill)
+    .padding(10)
+    .style(theme::Container::Box)
+    .into()
+}
+
+pub async fn count(text: Arc<String>) -> Statistics {
+    tokio::task::spawn_blocking(move || Statistics::compute(&text))
+        .await
+        .unwrap_or_default()
+}
+
+fn indent(line: &str) -> usize {
+    line.chars()
+        .take_while(|c| c.is_whitespace())
+        .map(|c| if c == '\t' { TAB_WIDTH } else { 1 })
+        .sum()
+}
@@ -0,0 +1,296 @@
+use crate::config::Config;
+use crate::{compression, Message};
+
+use iced::theme;
+use iced::widget::{button, checkbox, column, container, horizontal_space, row, text, Column, Row};
+use iced::{Element, Length};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// A place in the status bar that a feature can fill.
+///
+/// The variants are in their default order; the ones up to `SaveDenied` sit on the left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Slot {
+    File,
+    Branch,
+    Encoding,
+    LineEnding,
+    Encryption,
+    Link,
+    Lossy,
+    ChangedOutside,
+    ReadOnly,
+    Storage,
+    SaveDenied,
+    Suspicious,
+    Color,
+    Collab,
+    Character,
+    Speech,
+    Goal,
+    Language,
+    Request,
+    Completion,
+    Problems,
+    Blame,
+    Index,
+    Performance,
+    Wrap,
+    Position,
+}
+
+impl Slot {
+    pub const ALL: [Slot; 26] = [
+        Slot::File,
+        Slot::Branch,
+        Slot::Encoding,
+        Slot::LineEnding,
+        Slot::Encryption,
+        Slot::Link,
+        Slot::Lossy,
+        Slot::ChangedOutside,
+        Slot::ReadOnly,
+        Slot::Storage,
+        Slot::SaveDenied,
+        Slot::Suspicious,
+        Slot::Color,
+        Slot::Collab,
+        Slot::Character,
+        Slot::Speech,
+        Slot::Goal,
+        Slot::Language,
+        Slot::Request,
+        Slot::Completion,
+        Slot::Problems,
+        Slot::Blame,
+        Slot::Index,
+        Slot::Performance,
+        Slot::Wrap,
+        Slot::Position,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Slot::File => "File",
+            Slot::Branch => "Git branch",
+            Slot::Encoding => "Encoding",
+            Slot::LineEnding => "Line endings",
+            Slot::Encryption => "Encryption",
+            Slot::Link => "Symlink",
+            Slot::Lossy => "Invalid UTF-8",
+            Slot::ChangedOutside => "Changed outside",
+            Slot::ReadOnly => "Read-only",
+            Slot::Storage => "Slow storage",
+            Slot::SaveDenied => "Save denied",
+            Slot::Suspicious => "Suspicious characters",
+            Slot::Color => "Color swatch",
+            Slot::Collab => "Collaboration",
+            Slot::Character => "Character info",
+            Slot::Speech => "Read aloud",
+            Slot::Goal => "Writing goal",
+            Slot::Language => "Language",
+            Slot::Request => "HTTP request",
+            Slot::Completion => "Inline completion",
+            Slot::Problems => "Problems",
+            Slot::Blame => "Git blame",
+            Slot::Index => "Project index",
+            Slot::Performance => "Performance HUD",
+            Slot::Wrap => "Wrapped line",
+            Slot::Position => "Cursor position",
+        }
+    }
+
+    fn is_left(self) -> bool {
+        self as usize <= Slot::SaveDenied as usize
+    }
+}
+
+/// The slots in the configured order, with any the config leaves out in their default place.
+pub fn order(config: &Config) -> Vec<Slot> {
+    let mut order: Vec<Slot> = Vec::with_capacity(Slot::ALL.len());
+
+    for slot in config.status_order.iter().chain(Slot::ALL.iter()) {
+        if !order.contains(slot) {
+            order.push(*slot);
+        }
+    }
+
+    order
+}
+
+/// Moves `slot` `step` places within its side of the bar.
+pub fn move_slot(config: &mut Config, slot: Slot, step: i32) {
+    let mut order = order(config);
+    let side: Vec<usize> = (0..order.len())
+        .filter(|index| order[*index].is_left() == slot.is_left())
+        .collect();
+
+    let Some(position) = side.iter().position(|index| order[*index] == slot) else {
+        return;
+    };
+
+    let target = position as i32 + step;
+
+    if (0..side.len() as i32).contains(&target) {
+        order.swap(side[position], side[target as usize]);
+        config.status_order = order;
+    }
+}
+
+/// Collects the segments features register for one frame, then lays them out.
+#[derive(Default)]
+pub struct Bar<'a> {
+    segments: Vec<(Slot, Element<'a, Message>)>,
+}
+
+impl<'a> Bar<'a> {
+    /// Fills `slot` with `content`, made clickable when there is an `on_press` message.
+    pub fn register(
+        &mut self,
+        slot: Slot,
+        content: impl Into<Element<'a, Message>>,
+        on_press: Option<Message>,
+    ) {
+        let content = match on_press {
+            Some(message) => button(content)
+                .on_press(message)
+                .padding(0)
+                .style(theme::Button::Text)
+                .into(),
+            None => content.into(),
+        };
+
+        self.segments.push((slot, content));
+    }
+
+    pub fn view(self, config: &Config) -> Element<'a, Message> {
+        let order = order(config);
+        let mut segments: Vec<_> = self
+            .segments
+            .into_iter()
+            .filter(|(slot, _)| !config.status_hidden.contains(slot))
+            .collect();
+
+        // Stable, so a slot registered twice keeps its segments in order
+        segments.sort_by_key(|(slot, _)| order.iter().position(|other| other == slot));
+
+        let (left, right): (Vec<_>, Vec<_>) =
+            segments.into_iter().partition(|(slot, _)| slot.is_left());
+
+        let side = |segments: Vec<(Slot, Element<'a, Message>)>| {
+            Row::with_children(segments.into_iter().map(|(_, content)| content).collect())
+                .spacing(10)
+        };
+
+        row![side(left), horizontal_space(Length::Fill), side(right)]
+            .spacing(10)
+            .into()
+    }
+}
+
+/// The settings panel for choosing which segments show and in what order.
+pub fn settings(config: &Config) -> Element<'_, Message> {
+    let order = order(config);
+
+    let entries = |is_left: bool| {
+        Column::with_children(
+            order
+                .iter()
+                .filter(|slot| slot.is_left() == is_left)
+                .map(|slot| {
+                    let slot = *slot;
+
+                    row![
+                        checkbox(
+                            slot.label(),
+                            !config.status_hidden.contains(&slot),
+                            move |is_shown| Message::StatusSegmentToggled(slot, is_shown)
+                        )
+                        .size(14)
+                        .text_size(14)
+                        .width(Length::Fill),
+                        button(text("↑").size(14))
+                            .on_press(Message::StatusSegmentMoved(slot, -1))
+                            .padding([0, 5])
+                            .style(theme::Button::Text),
+                        button(text("↓").size(14))
+                            .on_press(Message::StatusSegmentMoved(slot, 1))
+                            .padding([0, 5])
+                            .style(theme::Button::Text),
+                    ]
+                    .into()
+                })
+                .collect(),
+        )
+        .spacing(5)
+    };
+
+    container(
+        column![
+            text("Status bar").size(16),
+            text("Left").size(14),
+            entries(true),
+            text("Right").size(14),
+            entries(false),
+            button(text("Close").size(14))
+                .on_press(Message::ClosePanel)
+                .style(theme::Button::Secondary),
+        ]
+        .spacing(10),
+    )
+    .width(Length::Fill)
+    .padding(10)
+    .style(theme::Container::Box)
+    .into()
+}
+
+/// The branch checked out in the repository at `root`, or a short commit when detached.
+pub async fn branch(root: PathBuf) -> Option<String> {
+    let head = tokio::fs::read_to_string(root.join(".git").join("HEAD"))
+        .await
+        .ok()?;
+    let head = head.trim();
+
+    match head.strip_prefix("ref: ") {
+        Some(reference) => Some(
+            reference
+                .strip_prefix("refs/heads/")
+                .unwrap_or(reference)
+                .to_string(),
+        ),
+        None => Some(head.chars().take(7).collect()),
+    }
+}
+
+pub fn language(path: &Path) -> Option<&'static str> {
+    // The extension inside any compression or encryption one
+    let language = match compression::extension(path)?.to_ascii_lowercase().as_str() {
+        "rs" => "Rust",
+        "py" => "Python",
+        "js" | "mjs" | "cjs" => "JavaScript",
+        "ts" => "TypeScript",
+        "tsx" | "jsx" => "React",
+        "go" => "Go",
+        "c" | "h" => "C",
+        "cpp" | "cc" | "hpp" => "C++",
+        "java" => "Java",
+        "rb" => "Ruby",
+        "sh" | "bash" => "Shell",
+        "html" | "htm" => "HTML",
+        "css" => "CSS",
+        "json" => "JSON",
+        "toml" => "TOML",
+        "yaml" | "yml" => "YAML",
+        "md" | "markdown" => "Markdown",
+        "rst" => "reStructuredText",
+        "adoc" | "asciidoc" => "AsciiDoc",
+        "tex" => "LaTeX",
+        "csv" | "tsv" => "Table",
+        "txt" => "Plain text",
+        _ => return None,
+    };
+
+    Some(language)
+}
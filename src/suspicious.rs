@@ -0,0 +1,148 @@
+use iced::Color;
+use std::ops::Range;
+
+pub const WARNING_COLOR: Color = Color::from_rgb(1.0, 0.35, 0.7);
+
+const CONFUSABLES: &[(char, char)] = &[
+    ('а', 'a'),
+    ('е', 'e'),
+    ('о', 'o'),
+    ('р', 'p'),
+    ('с', 'c'),
+    ('у', 'y'),
+    ('х', 'x'),
+    ('і', 'i'),
+    ('ј', 'j'),
+    ('ѕ', 's'),
+    ('А', 'A'),
+    ('В', 'B'),
+    ('Е', 'E'),
+    ('К', 'K'),
+    ('М', 'M'),
+    ('Н', 'H'),
+    ('О', 'O'),
+    ('Р', 'P'),
+    ('С', 'C'),
+    ('Т', 'T'),
+    ('Х', 'X'),
+    ('ο', 'o'),
+    ('ν', 'v'),
+    ('Α', 'A'),
+    ('Β', 'B'),
+    ('Ε', 'E'),
+    ('Η', 'H'),
+    ('Ι', 'I'),
+    ('Κ', 'K'),
+    ('Μ', 'M'),
+    ('Ν', 'N'),
+    ('Ο', 'O'),
+    ('Ρ', 'P'),
+    ('Τ', 'T'),
+    ('Χ', 'X'),
+];
+
+pub fn replacement(c: char) -> Option<String> {
+    match c {
+        '\u{200B}'..='\u{200F}' | '\u{2060}' | '\u{FEFF}' | '\u{00AD}' => Some(String::new()),
+        '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}' => Some(String::new()),
+        '\u{00A0}' | '\u{2000}'..='\u{200A}' | '\u{202F}' | '\u{205F}' | '\u{3000}' => {
+            Some(String::from(" "))
+        }
+        _ => CONFUSABLES
+            .iter()
+            .find(|(confusable, _)| *confusable == c)
+            .map(|(_, ascii)| ascii.to_string()),
+    }
+}
+
+pub fn find(line: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let characters: Vec<(usize, char)> = line.char_indices().collect();
+
+    for (position, (index, c)) in characters.iter().enumerate() {
+        let Some(replacement) = replacement(*c) else {
+            continue;
+        };
+
+        if !replacement.trim().is_empty() {
+            if is_mixed_word(&characters, position) {
+                ranges.push(*index..index + c.len_utf8());
+            }
+
+            continue;
+        }
+
+        let start = position
+            .checked_sub(1)
+            .map_or(*index, |previous| characters[previous].0);
+        let end = characters
+            .get(position + 1)
+            .map_or(line.len(), |(next, c)| next + c.len_utf8());
+
+        ranges.push(start..end);
+    }
+
+    ranges
+}
+
+pub fn offsets(text: &str) -> Vec<usize> {
+    let mut offsets = Vec::new();
+    let mut line_start = 0;
+
+    for line in text.split('\n') {
+        let characters: Vec<(usize, char)> = line.char_indices().collect();
+
+        for (position, (index, c)) in characters.iter().enumerate() {
+            let is_suspicious = match replacement(*c) {
+                Some(replacement) if !replacement.trim().is_empty() => {
+                    is_mixed_word(&characters, position)
+                }
+                Some(_) => true,
+                None => false,
+            };
+
+            if is_suspicious {
+                offsets.push(line_start + index);
+            }
+        }
+
+        line_start += line.len() + 1;
+    }
+
+    offsets
+}
+
+pub fn strip(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+
+    for line in text.split_inclusive('\n') {
+        let characters: Vec<(usize, char)> = line.char_indices().collect();
+
+        for (position, (_, c)) in characters.iter().enumerate() {
+            match replacement(*c) {
+                Some(replacement)
+                    if replacement.trim().is_empty() || is_mixed_word(&characters, position) =>
+                {
+                    stripped.push_str(&replacement);
+                }
+                _ => stripped.push(*c),
+            }
+        }
+    }
+
+    stripped
+}
+
+fn is_mixed_word(characters: &[(usize, char)], position: usize) -> bool {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+
+    let before = characters[..position]
+        .iter()
+        .rev()
+        .take_while(|(_, c)| is_word(*c));
+    let after = characters[position + 1..]
+        .iter()
+        .take_while(|(_, c)| is_word(*c));
+
+    before.chain(after).any(|(_, c)| c.is_ascii_alphabetic())
+}
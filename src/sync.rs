@@ -0,0 +1,382 @@
+use crate::{config, encryption, git, Error, Message};
+
+use iced::theme;
+use iced::widget::{button, column, container, row, text, text_input};
+use iced::{Element, Length};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::path::Path;
+
+const STATE_FILE: &str = ".sync-state";
+/// Files in the config directory that must never leave this machine
+const PRIVATE: &[&str] = &[encryption::AGE_IDENTITY];
+
+#[derive(Debug, Clone, Default)]
+pub struct Panel {
+    pub endpoint: String,
+    pub is_syncing: bool,
+    pub report: Option<Report>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    pub pushed: usize,
+    pub pulled: usize,
+    pub conflicts: Vec<String>,
+}
+
+impl Panel {
+    pub fn view(&self) -> Element<'_, Message> {
+        let sync =
+            (!self.is_syncing && !self.endpoint.trim().is_empty()).then_some(Message::SyncNow);
+
+        let status = match (&self.report, self.is_syncing) {
+            (_, true) => String::from("Syncing..."),
+            (Some(report), false) if report.conflicts.is_empty() => {
+                format!("{} pushed, {} pulled", report.pushed, report.pulled)
+            }
+            (Some(report), false) => format!(
+                "{} pushed, {} pulled, conflicts in: {}",
+                report.pushed,
+                report.pulled,
+                report.conflicts.join(", ")
+            ),
+            (None, false) => String::from("Settings are stored in the config directory"),
+        };
+
+        container(
+            column![
+                text("Sync settings").size(16),
+                text_input("Git remote or https:// WebDAV folder", &self.endpoint)
+                    .on_input(Message::SyncEndpointChanged)
+                    .on_submit(Message::SyncNow)
+                    .size(14)
+                    .padding(5),
+                text(status).size(14),
+                row![
+                    button(text("Sync now").size(14)).on_press_maybe(sync),
+                    button(text("Close").size(14))
+                        .on_press(Message::ClosePanel)
+                        .style(theme::Button::Secondary),
+                ]
+                .spacing(10),
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fill)
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
+
+pub async fn run(config: config::Config) -> Result<Report, Error> {
+    let endpoint = config.sync_endpoint.clone().unwrap_or_default();
+    config.save().await?;
+
+    let directory = config::directory().ok_or(Error::InvalidConfig(String::from(
+        "no configuration directory available",
+    )))?;
+
+    tokio::fs::create_dir_all(&directory)
+        .await
+        .map_err(|error| Error::IOFailed(error.kind()))?;
+
+    if endpoint.starts_with("https://") || endpoint.starts_with("http://") {
+        webdav(&directory, endpoint.trim_end_matches('/')).await
+    } else {
+        git_sync(&directory, &endpoint).await
+    }
+}
+
+async fn git_sync(directory: &Path, remote: &str) -> Result<Report, Error> {
+    if !directory.join(".git").exists() {
+        git::run(directory, &["init"]).await?;
+        git::run(directory, &["remote", "add", "origin", remote]).await?;
+    } else {
+        git::run(directory, &["remote", "set-url", "origin", remote]).await?;
+    }
+
+    exclude_private(directory).map_err(|error| Error::IOFailed(error.kind()))?;
+    git::run(directory, &["add", "-A"]).await?;
+
+    // Untrack anything private that was committed before it was excluded
+    for name in PRIVATE {
+        git::run(
+            directory,
+            &["rm", "--cached", "--quiet", "--ignore-unmatch", name],
+        )
+        .await?;
+    }
+
+    let pushed = git::run(directory, &["diff", "--cached", "--name-only"])
+        .await?
+        .lines()
+        .count();
+
+    if pushed > 0 {
+        git::run(directory, &["commit", "-m", "Sync settings"]).await?;
+    }
+
+    let before = git::run(directory, &["rev-parse", "HEAD"])
+        .await
+        .unwrap_or_default();
+    let has_remote = git::run(directory, &["ls-remote", "--heads", "origin"])
+        .await?
+        .lines()
+        .any(|line| line.ends_with("refs/heads/main"));
+
+    let mut report = Report {
+        pushed,
+        ..Report::default()
+    };
+
+    if has_remote {
+        if git::run(directory, &["pull", "--rebase", "origin", "main"])
+            .await
+            .is_err()
+        {
+            report.conflicts = git::run(directory, &["diff", "--name-only", "--diff-filter=U"])
+                .await
+                .unwrap_or_default()
+                .lines()
+                .map(str::to_owned)
+                .collect();
+
+            let _ = git::run(directory, &["rebase", "--abort"]).await;
+
+            return Ok(report);
+        }
+
+        if !before.is_empty() {
+            report.pulled = git::run(directory, &["diff", "--name-only", before.trim(), "HEAD"])
+                .await
+                .unwrap_or_default()
+                .lines()
+                .count();
+        }
+    }
+
+    git::run(directory, &["push", "origin", "HEAD:main"]).await?;
+
+    Ok(report)
+}
+
+async fn webdav(directory: &Path, endpoint: &str) -> Result<Report, Error> {
+    let client = reqwest::Client::new();
+    let failed = |error: reqwest::Error| Error::RemoteFailed(error.to_string());
+
+    let state_path = directory.join(STATE_FILE);
+    let mut state: BTreeMap<String, u64> = tokio::fs::read_to_string(&state_path)
+        .await
+        .ok()
+        .and_then(|text| toml::from_str(&text).ok())
+        .unwrap_or_default();
+
+    let mut names = local_files(directory);
+    names.extend(remote_files(&client, endpoint).await?);
+
+    let mut report = Report::default();
+
+    for name in names {
+        let path = directory.join(&name);
+        let url = format!("{endpoint}/{name}");
+
+        let local = tokio::fs::read(&path).await.ok();
+        let response = client.get(&url).send().await.map_err(failed)?;
+
+        let remote = if response.status().is_success() {
+            Some(response.bytes().await.map_err(failed)?.to_vec())
+        } else {
+            None
+        };
+
+        let last = state.get(&name).copied();
+        let is_changed = |bytes: Option<&Vec<u8>>| bytes.map(|bytes| checksum(bytes)) != last;
+
+        let synced = match (local.as_ref(), remote.as_ref()) {
+            (Some(local), Some(remote)) if local == remote => local.clone(),
+            (Some(local), remote) if remote.is_none() || !is_changed(remote) => {
+                client
+                    .put(&url)
+                    .body(local.clone())
+                    .send()
+                    .await
+                    .and_then(reqwest::Response::error_for_status)
+                    .map_err(failed)?;
+
+                report.pushed += 1;
+                local.clone()
+            }
+            (local, Some(remote)) if local.is_none() || !is_changed(local) => {
+                tokio::fs::write(&path, remote)
+                    .await
+                    .map_err(|error| Error::IOFailed(error.kind()))?;
+
+                report.pulled += 1;
+                remote.clone()
+            }
+            (Some(_), Some(_)) => {
+                report.conflicts.push(name);
+                continue;
+            }
+            (None, None) => continue,
+        };
+
+        state.insert(name, checksum(&synced));
+    }
+
+    let text = toml::to_string(&state).map_err(|error| Error::InvalidConfig(error.to_string()))?;
+
+    tokio::fs::write(&state_path, text)
+        .await
+        .map_err(|error| Error::IOFailed(error.kind()))?;
+
+    Ok(report)
+}
+
+async fn remote_files(client: &reqwest::Client, endpoint: &str) -> Result<BTreeSet<String>, Error> {
+    let failed = |error: reqwest::Error| Error::RemoteFailed(error.to_string());
+    let propfind = reqwest::Method::from_bytes(b"PROPFIND").expect("valid method");
+
+    let response = client
+        .request(propfind, format!("{endpoint}/"))
+        .header("Depth", "1")
+        .send()
+        .await
+        .map_err(failed)?;
+
+    if !response.status().is_success() {
+        return Ok(BTreeSet::new());
+    }
+
+    let body = response.text().await.map_err(failed)?;
+
+    Ok(body
+        .split("href>")
+        .skip(1)
+        .step_by(2)
+        .filter_map(|href| {
+            let href = href.split('<').next()?;
+            let name = href.trim_end_matches('/').rsplit('/').next()?;
+
+            (!href.ends_with('/') && is_synced(name)).then(|| name.to_string())
+        })
+        .collect())
+}
+
+fn local_files(directory: &Path) -> BTreeSet<String> {
+    std::fs::read_dir(directory)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_file()))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| is_synced(name))
+        .collect()
+}
+
+fn is_synced(name: &str) -> bool {
+    !name.is_empty() && !name.starts_with('.') && !PRIVATE.contains(&name)
+}
+
+/// Lists the private files in the repository's own exclude file, so `git add -A` skips them.
+fn exclude_private(directory: &Path) -> io::Result<()> {
+    let path = directory.join(".git").join("info").join("exclude");
+    let mut exclude = std::fs::read_to_string(&path).unwrap_or_default();
+
+    let missing: Vec<_> = PRIVATE
+        .iter()
+        .filter(|name| {
+            !exclude
+                .lines()
+                .any(|line| line.trim() == format!("/{name}"))
+        })
+        .collect();
+
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    if !exclude.is_empty() && !exclude.ends_with('\n') {
+        exclude.push('\n');
+    }
+
+    for name in missing {
+        exclude.push_str(&format!("/{name}\n"));
+    }
+
+    std::fs::create_dir_all(directory.join(".git").join("info"))?;
+    std::fs::write(path, exclude)
+}
+
+fn checksum(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::process::Command;
+
+    fn setup() -> tempfile::TempDir {
+        let directory = tempfile::tempdir().unwrap();
+
+        std::fs::write(directory.path().join("config.toml"), "theme = \"Dark\"\n").unwrap();
+        std::fs::write(
+            directory.path().join(encryption::AGE_IDENTITY),
+            "AGE-SECRET-KEY-1EXAMPLE\n",
+        )
+        .unwrap();
+
+        directory
+    }
+
+    #[test]
+    fn local_files_skip_the_age_identity() {
+        let directory = setup();
+
+        let files = local_files(directory.path());
+
+        assert!(files.contains("config.toml"));
+        assert!(!files.contains(encryption::AGE_IDENTITY));
+    }
+
+    #[test]
+    fn git_sync_skips_the_age_identity() {
+        let directory = setup();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args(args)
+                .current_dir(directory.path())
+                .output()
+                .unwrap()
+        };
+
+        assert!(git(&["init", "--quiet"]).status.success());
+
+        exclude_private(directory.path()).unwrap();
+        // Running it again must not list the file twice
+        exclude_private(directory.path()).unwrap();
+
+        assert!(git(&["add", "-A"]).status.success());
+
+        let staged = String::from_utf8(git(&["diff", "--cached", "--name-only"]).stdout).unwrap();
+
+        assert!(staged.lines().any(|name| name == "config.toml"));
+        assert!(!staged.lines().any(|name| name == encryption::AGE_IDENTITY));
+
+        let exclude = std::fs::read_to_string(directory.path().join(".git/info/exclude")).unwrap();
+
+        assert_eq!(
+            exclude
+                .lines()
+                .filter(|line| *line == format!("/{}", encryption::AGE_IDENTITY))
+                .count(),
+            1
+        );
+    }
+}
@@ -0,0 +1,442 @@
+use crate::{widgets, Message};
+
+use iced::theme;
+use iced::widget::scrollable::{Direction, Properties};
+use iced::widget::{button, column, container, row, scrollable, text, text_input, Row};
+use iced::{Element, Length};
+use std::cmp::Ordering;
+use std::path::Path;
+
+const DEFAULT_WIDTH: f32 = 120.0;
+const MIN_WIDTH: f32 = 40.0;
+/// Each row of inputs, with the spacing between rows
+const ROW_HEIGHT: f32 = 30.0;
+
+#[derive(Debug, Clone)]
+pub struct Table {
+    delimiter: u8,
+    crlf: bool,
+    rows: Vec<Vec<String>>,
+    order: Vec<usize>,
+    widths: Vec<f32>,
+    sort: Option<(usize, bool)>,
+    pub is_modified: bool,
+    /// How far the grid is scrolled down, in pixels
+    offset: f32,
+}
+
+#[derive(Debug, Clone)]
+pub enum Action {
+    Edit {
+        row: usize,
+        column: usize,
+        value: String,
+    },
+    Sort(usize),
+    Resize(usize, f32),
+}
+
+pub fn delimiter(path: &Path) -> Option<u8> {
+    match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+        "csv" => Some(b','),
+        "tsv" => Some(b'\t'),
+        _ => None,
+    }
+}
+
+impl Table {
+    pub fn parse(text: &str, delimiter: u8) -> Result<Self, csv::Error> {
+        let rows: Vec<Vec<String>> = csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(text.as_bytes())
+            .records()
+            .map(|record| Ok(record?.iter().map(str::to_owned).collect()))
+            .collect::<Result<_, csv::Error>>()?;
+
+        let columns = rows.iter().map(Vec::len).max().unwrap_or_default();
+
+        Ok(Self {
+            delimiter,
+            crlf: text.contains("\r\n"),
+            order: (0..rows.len()).collect(),
+            rows,
+            widths: vec![DEFAULT_WIDTH; columns],
+            sort: None,
+            is_modified: false,
+            offset: 0.0,
+        })
+    }
+
+    pub fn to_text(&self) -> String {
+        let terminator = if self.crlf {
+            csv::Terminator::CRLF
+        } else {
+            csv::Terminator::Any(b'\n')
+        };
+
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(self.delimiter)
+            .terminator(terminator)
+            .flexible(true)
+            .from_writer(Vec::new());
+
+        for row in &self.rows {
+            let _ = writer.write_record(row);
+        }
+
+        writer
+            .into_inner()
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn perform(&mut self, action: Action) {
+        match action {
+            Action::Edit { row, column, value } => {
+                if let Some(cells) = self.rows.get_mut(row) {
+                    if cells.len() <= column {
+                        cells.resize(column + 1, String::new());
+                    }
+
+                    cells[column] = value;
+                    self.is_modified = true;
+                }
+            }
+            Action::Sort(column) => {
+                let ascending = match self.sort {
+                    Some((current, ascending)) if current == column => !ascending,
+                    _ => true,
+                };
+
+                let rows = &self.rows;
+                let cell = |row: usize| {
+                    rows[row]
+                        .get(column)
+                        .map(String::as_str)
+                        .unwrap_or_default()
+                };
+                let start = self.order.len().min(1);
+
+                self.order[start..].sort_by(|a, b| {
+                    let ordering = compare(cell(*a), cell(*b));
+
+                    if ascending {
+                        ordering
+                    } else {
+                        ordering.reverse()
+                    }
+                });
+
+                self.sort = Some((column, ascending));
+            }
+            Action::Resize(column, delta) => {
+                if let Some(width) = self.widths.get_mut(column) {
+                    *width = (*width + delta).max(MIN_WIDTH);
+                }
+            }
+        }
+    }
+
+    pub fn scrolled(&mut self, viewport: scrollable::Viewport) {
+        self.offset = viewport.absolute_offset().y;
+    }
+
+    pub fn view(&self, height: f32) -> Element<'_, Message> {
+        let header = Row::with_children(
+            self.widths
+                .iter()
+                .enumerate()
+                .map(|(column, width)| {
+                    let title = self
+                        .order
+                        .first()
+                        .and_then(|row| self.rows[*row].get(column))
+                        .cloned()
+                        .unwrap_or_default();
+
+                    let indicator = match self.sort {
+                        Some((current, true)) if current == column => " ▲",
+                        Some((current, false)) if current == column => " ▼",
+                        _ => "",
+                    };
+
+                    row![
+                        button(text(format!("{title}{indicator}")).size(14))
+                            .on_press(Message::TableChanged(Action::Sort(column)))
+                            .style(theme::Button::Secondary)
+                            .width(Length::Fill),
+                        resize_button("‹", column, -20.0),
+                        resize_button("›", column, 20.0),
+                    ]
+                    .width(*width)
+                    .into()
+                })
+                .collect(),
+        )
+        .spacing(2);
+
+        // The first row is the header, so the grid's rows start after it
+        let line = |index: usize| -> Element<'_, Message> {
+            let row = self.order[index + 1];
+
+            Row::with_children(
+                self.widths
+                    .iter()
+                    .enumerate()
+                    .map(|(column, width)| {
+                        let value = self.rows[row]
+                            .get(column)
+                            .map(String::as_str)
+                            .unwrap_or_default();
+
+                        text_input("", value)
+                            .on_input(move |value| {
+                                Message::TableChanged(Action::Edit { row, column, value })
+                            })
+                            .size(14)
+                            .padding(4)
+                            .width(*width)
+                            .into()
+                    })
+                    .collect(),
+            )
+            .spacing(2)
+            .into()
+        };
+
+        let grid = column![
+            header,
+            widgets::virtual_column(
+                self.order.len().saturating_sub(1),
+                ROW_HEIGHT,
+                self.offset,
+                height,
+                line,
+            ),
+        ]
+        .spacing(2);
+
+        scrollable(grid)
+            .direction(Direction::Both {
+                vertical: Properties::default(),
+                horizontal: Properties::default(),
+            })
+            .on_scroll(Message::TableScrolled)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+}
+
+fn compare(a: &str, b: &str) -> Ordering {
+    match (a.trim().parse::<f64>(), b.trim().parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+        _ => a.cmp(b),
+    }
+}
+
+fn resize_button<'a>(label: &'a str, column: usize, delta: f32) -> Element<'a, Message> {
+    button(text(label).size(14))
+        .on_press(Message::TableChanged(Action::Resize(column, delta)))
+        .style(theme::Button::Text)
+        .padding([4, 2])
+        .into()
+}
+
+/// Column edits on delimited text, run from the palette rather than the table view.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Swap,
+    Delete,
+    Extract,
+    Sum,
+}
+
+impl Operation {
+    pub const ALL: [Operation; 4] = [
+        Operation::Swap,
+        Operation::Delete,
+        Operation::Extract,
+        Operation::Sum,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Operation::Swap => "Swap two columns",
+            Operation::Delete => "Delete column",
+            Operation::Extract => "Extract column to new buffer",
+            Operation::Sum => "Sum column",
+        }
+    }
+}
+
+pub enum Outcome {
+    Replace(String),
+    Open(String),
+    Total {
+        sum: f64,
+        values: usize,
+        skipped: usize,
+    },
+}
+
+/// Asks for the delimiter and the 1-based columns an operation works on.
+#[derive(Debug, Clone)]
+pub struct Prompt {
+    pub operation: Operation,
+    pub delimiter: String,
+    pub column: String,
+    pub other: String,
+}
+
+impl Prompt {
+    pub fn new(operation: Operation, delimiter: u8) -> Self {
+        Self {
+            operation,
+            delimiter: match delimiter {
+                b'\t' => String::from("\\t"),
+                delimiter => char::from(delimiter).to_string(),
+            },
+            column: String::from("1"),
+            other: String::from("2"),
+        }
+    }
+
+    /// The delimiter byte and the 0-based columns, when all of them make sense.
+    fn parsed(&self) -> Option<(u8, usize, usize)> {
+        let delimiter = match self.delimiter.as_str() {
+            "\\t" | "\t" | "tab" => b'\t',
+            delimiter if delimiter.len() == 1 => delimiter.as_bytes()[0],
+            _ => return None,
+        };
+
+        let index = |column: &str| column.trim().parse::<usize>().ok()?.checked_sub(1);
+        let column = index(&self.column)?;
+
+        let other = match self.operation {
+            Operation::Swap => index(&self.other)?,
+            _ => column,
+        };
+
+        Some((delimiter, column, other))
+    }
+
+    pub fn apply(&self, text: &str) -> Option<Result<Outcome, csv::Error>> {
+        let (delimiter, column, other) = self.parsed()?;
+
+        Some(Table::parse(text, delimiter).map(|mut table| {
+            let rows = &mut table.rows;
+
+            match self.operation {
+                Operation::Swap => {
+                    for row in rows.iter_mut() {
+                        if row.len() > column.max(other) {
+                            row.swap(column, other);
+                        }
+                    }
+                }
+                Operation::Delete => {
+                    for row in rows.iter_mut() {
+                        if column < row.len() {
+                            row.remove(column);
+                        }
+                    }
+                }
+                Operation::Extract => {
+                    let values: Vec<&str> = rows
+                        .iter()
+                        .map(|row| row.get(column).map(String::as_str).unwrap_or_default())
+                        .collect();
+
+                    return Outcome::Open(values.join("\n"));
+                }
+                Operation::Sum => {
+                    let cells = rows
+                        .iter()
+                        .filter_map(|row| row.get(column))
+                        .map(|cell| cell.trim())
+                        .filter(|cell| !cell.is_empty());
+
+                    let (mut sum, mut values, mut skipped) = (0.0, 0, 0);
+
+                    // A header or a stray note is counted as skipped rather than failing the sum
+                    for cell in cells {
+                        match cell.parse::<f64>() {
+                            Ok(value) => {
+                                sum += value;
+                                values += 1;
+                            }
+                            Err(_) => skipped += 1,
+                        }
+                    }
+
+                    return Outcome::Total {
+                        sum,
+                        values,
+                        skipped,
+                    };
+                }
+            }
+
+            let mut written = table.to_text();
+
+            // The lines were taken without their final line break
+            if !text.ends_with('\n') {
+                written.truncate(written.trim_end_matches(['\r', '\n']).len());
+            }
+
+            Outcome::Replace(written)
+        }))
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let mut inputs = row![
+            text_input("Delimiter", &self.delimiter)
+                .on_input(Message::ColumnDelimiterChanged)
+                .size(14)
+                .width(100),
+            text_input("Column", &self.column)
+                .on_input(Message::ColumnChanged)
+                .on_submit(Message::ApplyColumnTool)
+                .size(14)
+                .width(100),
+        ]
+        .spacing(10);
+
+        if self.operation == Operation::Swap {
+            inputs = inputs.push(
+                text_input("Other column", &self.other)
+                    .on_input(Message::OtherColumnChanged)
+                    .on_submit(Message::ApplyColumnTool)
+                    .size(14)
+                    .width(100),
+            );
+        }
+
+        let apply = self.parsed().map(|_| Message::ApplyColumnTool);
+
+        container(
+            column![
+                text(self.operation.label()).size(16),
+                text("Works on the lines the selection touches, or the whole buffer. Columns count from 1, and \\t is a tab.")
+                    .size(14),
+                inputs,
+                row![
+                    button(text("Apply").size(14)).on_press_maybe(apply),
+                    button(text("Cancel").size(14))
+                        .on_press(Message::ClosePanel)
+                        .style(theme::Button::Secondary),
+                ]
+                .spacing(10),
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fill)
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
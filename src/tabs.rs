@@ -0,0 +1,433 @@
+use crate::{bom, icons, lines, lossy, remote, table, tail, vault, viewer, widgets, Message};
+
+use iced::theme;
+use iced::widget::scrollable::{Direction, Properties};
+use iced::widget::{
+    button, column, container, mouse_area, pick_list, row, scrollable, text, text_editor,
+    text_input, Column, Row,
+};
+use iced::{Color, Element, Length};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::SystemTime;
+
+const PINNED_WIDTH: f32 = 72.0;
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+pub struct Buffer {
+    pub path: Option<PathBuf>,
+    pub content: text_editor::Content,
+    pub is_dirty: bool,
+    pub image: Option<viewer::Image>,
+    pub table: Option<table::Table>,
+    pub tail: Option<tail::Tail>,
+    pub remote: Option<remote::Session>,
+    pub url: Option<String>,
+    pub link: Option<PathBuf>,
+    pub replace_link: bool,
+    pub is_read_only: bool,
+    pub bom: Option<bom::Bom>,
+    pub line_ending: lines::Ending,
+    pub lossy: Option<lossy::Lossy>,
+    pub vault: Option<vault::Key>,
+    /// The connection picked with "Use database" while this buffer was open
+    pub database: Option<String>,
+    pub changed_outside: bool,
+    pub is_loaded: bool,
+    /// When the file was last read or written by the editor
+    pub modified: Option<SystemTime>,
+    /// Estimated when the buffer was moved to the background
+    pub memory: usize,
+}
+
+impl Buffer {
+    pub fn unloaded(path: PathBuf) -> Self {
+        Self {
+            path: Some(path),
+            content: text_editor::Content::new(),
+            is_dirty: false,
+            image: None,
+            table: None,
+            tail: None,
+            remote: None,
+            url: None,
+            link: None,
+            replace_link: false,
+            is_read_only: false,
+            bom: None,
+            line_ending: lines::Ending::Lf,
+            lossy: None,
+            vault: None,
+            database: None,
+            changed_outside: false,
+            is_loaded: false,
+            modified: None,
+            memory: 0,
+        }
+    }
+}
+
+pub struct Tab {
+    pub id: usize,
+    pub is_pinned: bool,
+    /// Shown in place of the file name
+    pub name: Option<String>,
+    pub tag: Option<Tag>,
+    pub buffer: Option<Buffer>,
+}
+
+impl Tab {
+    pub fn active() -> Self {
+        Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            is_pinned: false,
+            name: None,
+            tag: None,
+            buffer: None,
+        }
+    }
+
+    pub fn unloaded(path: PathBuf) -> Self {
+        Self {
+            buffer: Some(Buffer::unloaded(path)),
+            ..Self::active()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Pin,
+    Close,
+    CloseOthers,
+    CloseToRight,
+    CopyPath,
+    Reveal,
+    Label,
+}
+
+/// A color given to a tab, to tell apart files with similar names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Tag {
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Blue,
+    Purple,
+}
+
+impl Tag {
+    pub const ALL: [Tag; 6] = [
+        Tag::Red,
+        Tag::Orange,
+        Tag::Yellow,
+        Tag::Green,
+        Tag::Blue,
+        Tag::Purple,
+    ];
+
+    pub fn color(self) -> Color {
+        match self {
+            Tag::Red => Color::from_rgb(0.9, 0.3, 0.3),
+            Tag::Orange => Color::from_rgb(0.95, 0.6, 0.2),
+            Tag::Yellow => Color::from_rgb(0.9, 0.8, 0.2),
+            Tag::Green => Color::from_rgb(0.35, 0.75, 0.35),
+            Tag::Blue => Color::from_rgb(0.35, 0.6, 1.0),
+            Tag::Purple => Color::from_rgb(0.7, 0.45, 0.9),
+        }
+    }
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Tag::Red => "Red",
+            Tag::Orange => "Orange",
+            Tag::Yellow => "Yellow",
+            Tag::Green => "Green",
+            Tag::Blue => "Blue",
+            Tag::Purple => "Purple",
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub title: String,
+    pub path: Option<PathBuf>,
+    pub is_dirty: bool,
+    pub is_pinned: bool,
+    pub name: Option<String>,
+    pub tag: Option<Tag>,
+}
+
+impl Label {
+    /// The custom name when the tab has one, else the file's.
+    pub fn shown(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.title)
+    }
+
+    fn text(&self, content: String) -> iced::widget::Text<'static> {
+        let content = text(content).size(14);
+
+        match self.tag {
+            Some(tag) => content.style(tag.color()),
+            None => content,
+        }
+    }
+}
+
+/// Naming and coloring one tab.
+#[derive(Debug, Clone)]
+pub struct Prompt {
+    pub id: usize,
+    pub title: String,
+    pub name: String,
+    pub tag: Option<Tag>,
+}
+
+impl Prompt {
+    pub fn new(tab: &Tab, title: String) -> Self {
+        Self {
+            id: tab.id,
+            title,
+            name: tab.name.clone().unwrap_or_default(),
+            tag: tab.tag,
+        }
+    }
+
+    /// The name to keep, none when left blank.
+    pub fn name(&self) -> Option<String> {
+        let name = self.name.trim();
+
+        (!name.is_empty()).then(|| name.to_string())
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let tags = Tag::ALL.iter().fold(
+            Row::new().spacing(5).push(
+                button(text("None").size(14))
+                    .on_press(Message::TabTagSelected(None))
+                    .style(if self.tag.is_none() {
+                        theme::Button::Primary
+                    } else {
+                        theme::Button::Secondary
+                    }),
+            ),
+            |tags, tag| {
+                tags.push(
+                    button(text(tag.to_string()).size(14).style(tag.color()))
+                        .on_press(Message::TabTagSelected(Some(*tag)))
+                        .style(if self.tag == Some(*tag) {
+                            theme::Button::Primary
+                        } else {
+                            theme::Button::Secondary
+                        }),
+                )
+            },
+        );
+
+        container(
+            column![
+                text(format!("Label {}", self.title)).size(16),
+                text_input(&self.title, &self.name)
+                    .on_input(Message::TabNameChanged)
+                    .on_submit(Message::ApplyTabLabel),
+                tags,
+                row![
+                    button(text("Apply").size(14)).on_press(Message::ApplyTabLabel),
+                    button(text("Cancel").size(14))
+                        .on_press(Message::ClosePanel)
+                        .style(theme::Button::Secondary),
+                ]
+                .spacing(10),
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fill)
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Choice {
+    index: usize,
+    title: String,
+}
+
+impl fmt::Display for Choice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.title)
+    }
+}
+
+pub fn title(path: Option<&Path>, remote: Option<&remote::Session>, url: Option<&str>) -> String {
+    if let Some(session) = remote {
+        return name(session.location.path());
+    }
+
+    if let Some(url) = url {
+        return url
+            .rsplit('/')
+            .find(|part| !part.is_empty())
+            .unwrap_or(url)
+            .to_string();
+    }
+
+    path.map(name).unwrap_or_else(|| String::from("New file"))
+}
+
+pub fn view(
+    labels: Vec<Label>,
+    active: usize,
+    menu: Option<usize>,
+    dragging: Option<usize>,
+) -> Element<'static, Message> {
+    let mut bar = Row::new().spacing(2);
+    let mut actions = None;
+
+    let choices: Vec<_> = labels
+        .iter()
+        .enumerate()
+        .map(|(index, label)| Choice {
+            index,
+            title: label.shown().to_string(),
+        })
+        .collect();
+
+    for (index, label) in labels.into_iter().enumerate() {
+        let dirty = if label.is_dirty { " •" } else { "" };
+
+        let icon = match &label.path {
+            Some(path) => icons::file(path),
+            None => icons::view(icons::Kind::Text),
+        };
+
+        let title = if label.is_pinned {
+            container(
+                row![
+                    icon,
+                    label.text(format!("{}{dirty}", abbreviate(label.shown())))
+                ]
+                .spacing(5),
+            )
+            .width(PINNED_WIDTH)
+        } else {
+            container(row![icon, label.text(format!("{}{dirty}", label.shown()))].spacing(5))
+        }
+        .padding([5, 10])
+        .style(if index == active || dragging == Some(index) {
+            theme::Container::Box
+        } else {
+            theme::Container::Transparent
+        });
+
+        let mut tab = row![mouse_area(title)
+            .on_press(Message::TabPressed(index))
+            .on_release(Message::TabDropped(index))
+            .on_right_press(Message::TabMenu(Some(index)))];
+
+        if !label.is_pinned {
+            tab = tab.push(
+                button(text("×").size(14))
+                    .on_press(Message::TabAction(index, Action::Close))
+                    .padding([5, 6])
+                    .style(theme::Button::Text),
+            );
+        }
+
+        bar = bar.push(tab);
+
+        if menu == Some(index) {
+            actions = Some(context_menu(index, &label));
+        }
+    }
+
+    let selected = choices.get(active).cloned();
+
+    let bar = row![
+        scrollable(bar)
+            .direction(Direction::Horizontal(
+                Properties::new().width(4).scroller_width(4)
+            ))
+            .width(Length::Fill),
+        pick_list(choices, selected, |choice| Message::TabSelected(
+            choice.index
+        ))
+        .text_size(14)
+        .width(180),
+    ]
+    .spacing(10);
+
+    match actions {
+        Some(actions) => column![bar, actions].spacing(5).into(),
+        None => bar.into(),
+    }
+}
+
+pub fn switcher(labels: Vec<Label>, selected: usize) -> Element<'static, Message> {
+    let entries = labels.into_iter().enumerate().map(|(index, label)| {
+        container(label.text(label.shown().to_string()))
+            .width(Length::Fill)
+            .padding([4, 8])
+            .style(if index == selected {
+                theme::Container::Box
+            } else {
+                theme::Container::Transparent
+            })
+            .into()
+    });
+
+    container(Column::with_children(entries.collect()).spacing(2))
+        .width(300)
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+}
+
+fn context_menu(index: usize, label: &Label) -> Element<'static, Message> {
+    let has_path = label.path.is_some();
+
+    let items = [
+        (
+            if label.is_pinned { "Unpin" } else { "Pin" },
+            Some(Action::Pin),
+        ),
+        ("Close", (!label.is_pinned).then_some(Action::Close)),
+        ("Name and Color...", Some(Action::Label)),
+        ("Close Others", Some(Action::CloseOthers)),
+        ("Close to the Right", Some(Action::CloseToRight)),
+        ("Copy Path", has_path.then_some(Action::CopyPath)),
+        ("Reveal in File Manager", has_path.then_some(Action::Reveal)),
+    ];
+
+    widgets::context_menu(
+        items
+            .into_iter()
+            .map(|(title, action)| {
+                (
+                    title,
+                    action.map(|action| Message::TabAction(index, action)),
+                )
+            })
+            .collect(),
+        Message::TabMenu(None),
+    )
+}
+
+fn name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+fn abbreviate(title: &str) -> String {
+    title.chars().take(3).collect()
+}
@@ -0,0 +1,134 @@
+use crate::{Error, Message};
+
+use iced::widget::{checkbox, row, text_editor, text_input};
+use iced::{Color, Element};
+use regex::Regex;
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+const LOG_EXTENSIONS: &[&str] = &["log", "out"];
+
+pub struct Tail {
+    pub offset: u64,
+    pub is_reading: bool,
+    pub filter: String,
+    pub is_regex: bool,
+    pub filtered: Option<text_editor::Content>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Chunk {
+    pub offset: u64,
+    pub text: String,
+    pub is_truncated: bool,
+}
+
+impl Tail {
+    pub fn new(offset: u64) -> Self {
+        Self {
+            offset,
+            is_reading: false,
+            filter: String::new(),
+            is_regex: false,
+            filtered: None,
+        }
+    }
+
+    pub fn refilter(&mut self, content: &text_editor::Content) {
+        if self.filter.is_empty() {
+            self.filtered = None;
+            return;
+        }
+
+        let regex = if self.is_regex {
+            match Regex::new(&self.filter) {
+                Ok(regex) => Some(regex),
+                Err(_) => return,
+            }
+        } else {
+            None
+        };
+
+        let lines: Vec<String> = content
+            .lines()
+            .filter(|line| match &regex {
+                Some(regex) => regex.is_match(line),
+                None => line.contains(self.filter.as_str()),
+            })
+            .map(|line| line.to_string())
+            .collect();
+
+        self.filtered = Some(text_editor::Content::with(&lines.join("\n")));
+    }
+
+    pub fn controls(&self) -> Element<'_, Message> {
+        row![
+            text_input("Filter lines...", &self.filter)
+                .on_input(Message::TailFilterChanged)
+                .size(14)
+                .padding(5),
+            checkbox("Regex", self.is_regex, Message::TailRegexToggled).size(14),
+        ]
+        .spacing(10)
+        .into()
+    }
+}
+
+pub fn is_log(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| LOG_EXTENSIONS.contains(&extension))
+}
+
+pub fn level_color(line: &str) -> Option<Color> {
+    if line.contains("ERROR") || line.contains("FATAL") {
+        Some(Color::from_rgb(0.9, 0.3, 0.3))
+    } else if line.contains("WARN") {
+        Some(Color::from_rgb(0.95, 0.7, 0.2))
+    } else if line.contains("INFO") {
+        Some(Color::from_rgb(0.4, 0.75, 0.45))
+    } else if line.contains("DEBUG") || line.contains("TRACE") {
+        Some(Color::from_rgb(0.55, 0.55, 0.55))
+    } else {
+        None
+    }
+}
+
+pub async fn start(path: PathBuf) -> Result<u64, Error> {
+    tokio::fs::metadata(&path)
+        .await
+        .map(|metadata| metadata.len())
+        .map_err(|error| Error::IOFailed(error.kind()))
+}
+
+pub async fn read(path: PathBuf, offset: u64) -> Result<Chunk, Error> {
+    let io = |error: std::io::Error| Error::IOFailed(error.kind());
+
+    let mut file = tokio::fs::File::open(&path).await.map_err(io)?;
+    let length = file.metadata().await.map_err(io)?.len();
+
+    let (start, is_truncated) = if length < offset {
+        (0, true)
+    } else {
+        (offset, false)
+    };
+
+    file.seek(SeekFrom::Start(start)).await.map_err(io)?;
+
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).await.map_err(io)?;
+
+    let complete = bytes
+        .iter()
+        .rposition(|byte| *byte == b'\n')
+        .map_or(0, |index| index + 1);
+
+    bytes.truncate(complete);
+
+    Ok(Chunk {
+        offset: start + complete as u64,
+        text: String::from_utf8_lossy(&bytes).into_owned(),
+        is_truncated,
+    })
+}
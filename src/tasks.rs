@@ -0,0 +1,108 @@
+use crate::Message;
+
+use iced::futures::future::{abortable, AbortHandle};
+use iced::Command;
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+/// Work that only matters while it is the latest of its kind for its tab.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Kind {
+    Open,
+    Save,
+    Search,
+    Format,
+    Blame,
+    Filter,
+}
+
+impl Kind {
+    /// Saves run to the end even when overtaken, as stopping one could leave a file half written.
+    fn is_cancellable(self) -> bool {
+        !matches!(self, Kind::Save)
+    }
+}
+
+/// Names one run of a task, so its result can be told apart from a newer one's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ticket {
+    pub kind: Kind,
+    /// The tab the result belongs to, if it belongs to one
+    pub tab: Option<usize>,
+    id: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct Tasks {
+    next: u64,
+    /// Keyed by tab too, so work for one tab never overtakes another's
+    running: HashMap<(Kind, Option<usize>), (Ticket, AbortHandle, Instant)>,
+}
+
+impl Tasks {
+    /// Runs `future` as the latest task of its kind for `tab`, cancelling the one it replaces.
+    ///
+    /// The result arrives as `Message::TaskFinished`, or without its message when cancelled.
+    pub fn perform<T: Send + 'static>(
+        &mut self,
+        kind: Kind,
+        tab: Option<usize>,
+        future: impl Future<Output = T> + Send + 'static,
+        map: impl FnOnce(T) -> Message + Send + 'static,
+    ) -> Command<Message> {
+        self.next += 1;
+
+        let ticket = Ticket {
+            kind,
+            tab,
+            id: self.next,
+        };
+
+        let (future, handle) = abortable(future);
+
+        if let Some((_, replaced, _)) = self
+            .running
+            .insert((kind, tab), (ticket, handle, Instant::now()))
+        {
+            if kind.is_cancellable() {
+                replaced.abort();
+            }
+        }
+
+        Command::perform(future, move |result| {
+            Message::TaskFinished(ticket, result.ok().map(|output| Box::new(map(output))))
+        })
+    }
+
+    /// Marks the run as done, returning whether it was still the latest of its kind for its tab.
+    pub fn finish(&mut self, ticket: Ticket) -> bool {
+        let key = (ticket.kind, ticket.tab);
+
+        let is_latest = self
+            .running
+            .get(&key)
+            .is_some_and(|(latest, ..)| *latest == ticket);
+
+        if is_latest {
+            self.running.remove(&key);
+        }
+
+        is_latest
+    }
+
+    pub fn is_running(&self, kinds: &[Kind]) -> bool {
+        self.running.keys().any(|(kind, _)| kinds.contains(kind))
+    }
+
+    /// The longest-running of `kinds`, once it has been going for more than `after`.
+    pub fn stalled(&self, kinds: &[Kind], after: Duration) -> Option<Kind> {
+        self.running
+            .iter()
+            .filter(|((kind, _), _)| kinds.contains(kind))
+            .map(|((kind, _), (.., started))| (*kind, started.elapsed()))
+            .filter(|(_, elapsed)| *elapsed > after)
+            .max_by_key(|(_, elapsed)| *elapsed)
+            .map(|(kind, _)| kind)
+    }
+}
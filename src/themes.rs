@@ -0,0 +1,769 @@
+use crate::{colors, config, Error, Message};
+
+use iced::futures::SinkExt;
+use iced::theme::{self, Palette};
+use iced::widget::{
+    button, column, container, row, scrollable, slider, text, text_editor, text_input, Column,
+};
+use iced::{subscription, Background, Color, Element, Length, Subscription};
+use notify::{RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+const EXTENSION: &str = "toml";
+/// How long file events are gathered before the themes are read again.
+const SETTLE: Duration = Duration::from_millis(200);
+
+/// Scopes the editor offers a color for, with the color a new theme starts from.
+const SCOPES: [(&str, Color); 10] = [
+    ("comment", Color::from_rgb(0.45, 0.5, 0.45)),
+    ("string", Color::from_rgb(0.6, 0.75, 0.45)),
+    ("constant.numeric", Color::from_rgb(0.85, 0.6, 0.4)),
+    ("constant", Color::from_rgb(0.8, 0.5, 0.6)),
+    ("keyword", Color::from_rgb(0.55, 0.65, 0.95)),
+    ("storage", Color::from_rgb(0.7, 0.55, 0.9)),
+    ("entity.name.function", Color::from_rgb(0.4, 0.75, 0.85)),
+    ("entity.name.type", Color::from_rgb(0.9, 0.8, 0.45)),
+    ("variable", Color::from_rgb(0.85, 0.45, 0.45)),
+    ("punctuation", Color::from_rgb(0.6, 0.6, 0.6)),
+];
+
+/// A theme the user wrote or exported, read from the config folder's `themes`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "File", into = "File")]
+pub struct Theme {
+    pub name: String,
+    pub background: Color,
+    pub foreground: Color,
+    pub selection: Color,
+    /// Drawn as the accent; the editor's caret takes the foreground
+    pub cursor: Color,
+    pub scopes: Vec<(String, Color)>,
+}
+
+/// A theme as written: colors in any notation the color picker reads, scopes by selector.
+#[derive(Serialize, Deserialize)]
+struct File {
+    #[serde(skip)]
+    name: String,
+    background: String,
+    foreground: String,
+    selection: String,
+    cursor: String,
+    #[serde(default)]
+    scopes: BTreeMap<String, String>,
+}
+
+impl TryFrom<File> for Theme {
+    type Error = String;
+
+    fn try_from(file: File) -> Result<Self, Self::Error> {
+        Ok(Self {
+            name: file.name,
+            background: parse(&file.background)?,
+            foreground: parse(&file.foreground)?,
+            selection: parse(&file.selection)?,
+            cursor: parse(&file.cursor)?,
+            scopes: file
+                .scopes
+                .iter()
+                .map(|(scope, color)| Ok((scope.clone(), parse(color)?)))
+                .collect::<Result<_, String>>()?,
+        })
+    }
+}
+
+impl From<Theme> for File {
+    fn from(theme: Theme) -> Self {
+        let hex = |color| colors::format(color, colors::Notation::Hex);
+
+        Self {
+            name: theme.name,
+            background: hex(theme.background),
+            foreground: hex(theme.foreground),
+            selection: hex(theme.selection),
+            cursor: hex(theme.cursor),
+            scopes: theme
+                .scopes
+                .into_iter()
+                .map(|(scope, color)| (scope, hex(color)))
+                .collect(),
+        }
+    }
+}
+
+fn parse(value: &str) -> Result<Color, String> {
+    colors::find(value.trim())
+        .first()
+        .filter(|swatch| swatch.range == (0..value.trim().len()))
+        .map(|swatch| swatch.color)
+        .ok_or_else(|| format!("{value:?} is not a color"))
+}
+
+impl Theme {
+    /// A theme to start editing from, taking the colors on screen.
+    pub fn from_palette(name: String, palette: Palette) -> Self {
+        let is_light = luminance(palette.background) > 0.5;
+
+        Self {
+            name,
+            background: palette.background,
+            foreground: palette.text,
+            selection: Color {
+                a: 0.4,
+                ..palette.primary
+            },
+            cursor: palette.primary,
+            scopes: SCOPES
+                .iter()
+                .map(|(scope, color)| {
+                    let color = if is_light { darken(*color) } else { *color };
+
+                    (scope.to_string(), color)
+                })
+                .collect(),
+        }
+    }
+
+    pub fn to_iced(&self) -> iced::Theme {
+        iced::Theme::custom(Palette {
+            background: self.background,
+            text: self.foreground,
+            primary: self.cursor,
+            ..if luminance(self.background) > 0.5 {
+                Palette::LIGHT
+            } else {
+                Palette::DARK
+            }
+        })
+    }
+}
+
+fn luminance(color: Color) -> f32 {
+    0.2126 * color.r + 0.7152 * color.g + 0.0722 * color.b
+}
+
+fn darken(color: Color) -> Color {
+    Color::from_rgb(color.r * 0.6, color.g * 0.6, color.b * 0.6)
+}
+
+/// The editor's usual look with a theme's selection color, and no background when see-through.
+pub struct Style {
+    pub selection: Option<Color>,
+    pub is_clear: bool,
+}
+
+impl Style {
+    fn appearance(&self, appearance: text_editor::Appearance) -> text_editor::Appearance {
+        if self.is_clear {
+            text_editor::Appearance {
+                background: Background::Color(Color::TRANSPARENT),
+                ..appearance
+            }
+        } else {
+            appearance
+        }
+    }
+}
+
+impl text_editor::StyleSheet for Style {
+    type Style = iced::Theme;
+
+    fn active(&self, style: &Self::Style) -> text_editor::Appearance {
+        self.appearance(style.active(&Default::default()))
+    }
+
+    fn focused(&self, style: &Self::Style) -> text_editor::Appearance {
+        self.appearance(style.focused(&Default::default()))
+    }
+
+    fn hovered(&self, style: &Self::Style) -> text_editor::Appearance {
+        self.appearance(style.hovered(&Default::default()))
+    }
+
+    fn disabled(&self, style: &Self::Style) -> text_editor::Appearance {
+        self.appearance(style.disabled(&Default::default()))
+    }
+
+    fn placeholder_color(&self, style: &Self::Style) -> Color {
+        style.placeholder_color(&Default::default())
+    }
+
+    fn value_color(&self, style: &Self::Style) -> Color {
+        style.value_color(&Default::default())
+    }
+
+    fn disabled_color(&self, style: &Self::Style) -> Color {
+        style.disabled_color(&Default::default())
+    }
+
+    fn selection_color(&self, style: &Self::Style) -> Color {
+        self.selection
+            .unwrap_or_else(|| style.selection_color(&Default::default()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Background,
+    Foreground,
+    Selection,
+    Cursor,
+    /// An index into the theme's scopes
+    Scope(usize),
+}
+
+/// A theme being edited, previewed on screen until the panel closes.
+#[derive(Debug, Clone)]
+pub struct Draft {
+    pub theme: Theme,
+    target: Target,
+    hex: String,
+}
+
+impl Draft {
+    pub fn new(mut theme: Theme) -> Self {
+        for (scope, color) in SCOPES {
+            if !theme.scopes.iter().any(|(other, _)| other == scope) {
+                theme.scopes.push((scope.to_string(), color));
+            }
+        }
+
+        let mut draft = Self {
+            theme,
+            target: Target::Background,
+            hex: String::new(),
+        };
+
+        draft.select(Target::Background);
+        draft
+    }
+
+    fn color(&self, target: Target) -> Color {
+        match target {
+            Target::Background => self.theme.background,
+            Target::Foreground => self.theme.foreground,
+            Target::Selection => self.theme.selection,
+            Target::Cursor => self.theme.cursor,
+            Target::Scope(index) => self
+                .theme
+                .scopes
+                .get(index)
+                .map_or(self.theme.foreground, |(_, color)| *color),
+        }
+    }
+
+    pub fn select(&mut self, target: Target) {
+        self.target = target;
+        self.hex = colors::format(self.color(target), colors::Notation::Hex);
+    }
+
+    pub fn set(&mut self, color: Color) {
+        let slot = match self.target {
+            Target::Background => &mut self.theme.background,
+            Target::Foreground => &mut self.theme.foreground,
+            Target::Selection => &mut self.theme.selection,
+            Target::Cursor => &mut self.theme.cursor,
+            Target::Scope(index) => match self.theme.scopes.get_mut(index) {
+                Some((_, color)) => color,
+                None => return,
+            },
+        };
+
+        *slot = color;
+        self.hex = colors::format(color, colors::Notation::Hex);
+    }
+
+    /// Takes a typed color once it reads as one, keeping the text either way.
+    pub fn set_hex(&mut self, hex: String) {
+        if let Ok(color) = parse(&hex) {
+            self.set(color);
+        }
+
+        self.hex = hex;
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let targets = [
+            ("Background", Target::Background),
+            ("Foreground", Target::Foreground),
+            ("Selection", Target::Selection),
+            ("Cursor", Target::Cursor),
+        ]
+        .into_iter()
+        .map(|(label, target)| (label.to_string(), target))
+        .chain(
+            self.theme
+                .scopes
+                .iter()
+                .enumerate()
+                .map(|(index, (scope, _))| (scope.clone(), Target::Scope(index))),
+        )
+        .fold(Column::new().spacing(4), |targets, (label, target)| {
+            targets.push(
+                row![
+                    colors::swatch(self.color(target), Message::ThemeTargetSelected(target)),
+                    button(text(label).size(14))
+                        .on_press(Message::ThemeTargetSelected(target))
+                        .padding([2, 6])
+                        .style(if target == self.target {
+                            theme::Button::Primary
+                        } else {
+                            theme::Button::Text
+                        }),
+                ]
+                .spacing(8),
+            )
+        });
+
+        let color = self.color(self.target);
+
+        let channel = |label: &'static str, value: f32, update: fn(Color, f32) -> Color| {
+            row![
+                text(label).size(14).width(20),
+                slider(0.0..=1.0, value, move |value| Message::ThemeColorChanged(
+                    update(color, value)
+                ))
+                .step(1.0 / 255.0),
+            ]
+            .spacing(10)
+        };
+
+        let picker = column![
+            text_input("#rrggbb", &self.hex).on_input(Message::ThemeHexChanged),
+            channel("R", color.r, |color, r| Color { r, ..color }),
+            channel("G", color.g, |color, g| Color { g, ..color }),
+            channel("B", color.b, |color, b| Color { b, ..color }),
+            channel("A", color.a, |color, a| Color { a, ..color }),
+        ]
+        .spacing(10)
+        .width(Length::Fill);
+
+        container(
+            column![
+                text("Theme editor").size(16),
+                text_input("Theme name", &self.theme.name).on_input(Message::ThemeNameChanged),
+                row![scrollable(targets).height(260).width(240), picker,].spacing(20),
+                row![
+                    button(text("Export").size(14)).on_press_maybe(
+                        (!self.theme.name.trim().is_empty()).then_some(Message::ExportTheme)
+                    ),
+                    button(text("Close").size(14))
+                        .on_press(Message::ClosePanel)
+                        .style(theme::Button::Secondary),
+                ]
+                .spacing(10),
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fill)
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
+
+fn directory() -> Option<PathBuf> {
+    config::directory().map(|directory| directory.join("themes"))
+}
+
+/// Reads every theme in the folder, by name.
+pub async fn load() -> Result<Vec<Theme>, Error> {
+    let Some(directory) = directory() else {
+        return Ok(Vec::new());
+    };
+
+    let mut entries = match tokio::fs::read_dir(&directory).await {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(Error::IOFailed(error.kind())),
+    };
+
+    let mut themes = Vec::new();
+
+    while let Some(entry) = entries
+        .next_entry()
+        .await
+        .map_err(|error| Error::IOFailed(error.kind()))?
+    {
+        let path = entry.path();
+
+        if path.extension().and_then(|extension| extension.to_str()) != Some(EXTENSION) {
+            continue;
+        }
+
+        let Some(name) = path
+            .file_stem()
+            .map(|name| name.to_string_lossy().into_owned())
+        else {
+            continue;
+        };
+
+        let text = tokio::fs::read_to_string(&path)
+            .await
+            .map_err(|error| Error::IOFailed(error.kind()))?;
+
+        let theme: Theme = toml::from_str(&text)
+            .map_err(|error| Error::InvalidConfig(format!("{}: {error}", path.display())))?;
+
+        themes.push(Theme { name, ..theme });
+    }
+
+    themes.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(themes)
+}
+
+/// Writes the theme to the folder, named after it.
+pub async fn save(theme: Theme) -> Result<PathBuf, Error> {
+    let directory = directory().ok_or(Error::IOFailed(io::ErrorKind::NotFound))?;
+    let path = directory.join(format!("{}.{EXTENSION}", theme.name.trim()));
+    let text =
+        toml::to_string_pretty(&theme).map_err(|error| Error::InvalidConfig(error.to_string()))?;
+
+    tokio::fs::create_dir_all(&directory)
+        .await
+        .map_err(|error| Error::IOFailed(error.kind()))?;
+
+    tokio::fs::write(&path, text)
+        .await
+        .map_err(|error| Error::IOFailed(error.kind()))?;
+
+    Ok(path)
+}
+
+/// Asks for the themes to be read again whenever a file in the folder changes.
+pub fn subscription() -> Subscription<Message> {
+    subscription::channel("themes", 10, |mut output| async move {
+        let (sender, mut changes) = mpsc::unbounded_channel();
+
+        let watcher = directory().map(|directory| {
+            std::fs::create_dir_all(&directory)
+                .map_err(notify::Error::io)
+                .and_then(|()| {
+                    notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                        if event.is_ok() {
+                            let _ = sender.send(());
+                        }
+                    })
+                })
+                .and_then(|mut watcher| {
+                    watcher.watch(&directory, RecursiveMode::NonRecursive)?;
+                    Ok(watcher)
+                })
+        });
+
+        // Held so the folder stays watched
+        let _watcher = match watcher {
+            Some(Ok(watcher)) => Some(watcher),
+            _ => None,
+        };
+
+        while changes.recv().await.is_some() {
+            tokio::time::sleep(SETTLE).await;
+            while changes.try_recv().is_ok() {}
+
+            let _ = output.send(Message::ThemesChanged).await;
+        }
+
+        loop {
+            std::future::pending::<()>().await;
+        }
+    })
+}
+
+/// Converts a TextMate `.tmTheme` or VS Code JSON theme the user picks, saving it as a theme.
+pub async fn import() -> Result<Theme, Error> {
+    let handle = rfd::AsyncFileDialog::new()
+        .set_title("Choose a theme to import...")
+        .add_filter("TextMate or VS Code theme", &["tmTheme", "json"])
+        .pick_file()
+        .await
+        .ok_or(Error::DialogClosed)?;
+
+    let path = handle.path().to_owned();
+    let text = tokio::fs::read_to_string(&path)
+        .await
+        .map_err(|error| Error::IOFailed(error.kind()))?;
+
+    let invalid = |error: String| Error::InvalidConfig(format!("{}: {error}", path.display()));
+
+    let theme = if path.extension().and_then(|extension| extension.to_str()) == Some("json") {
+        from_vscode(&text).map_err(invalid)?
+    } else {
+        from_tmtheme(&text).map_err(invalid)?
+    };
+
+    let name = if theme.name.trim().is_empty() {
+        stem(&path)
+    } else {
+        theme.name.trim().to_string()
+    };
+
+    let theme = Theme {
+        name: name.replace(['/', '\\', ':'], "-"),
+        ..theme
+    };
+
+    save(theme.clone()).await?;
+
+    Ok(theme)
+}
+
+fn stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// The colors an imported theme gives, before the missing ones are filled in.
+#[derive(Default)]
+struct Imported {
+    name: String,
+    is_light: Option<bool>,
+    background: Option<Color>,
+    foreground: Option<Color>,
+    selection: Option<Color>,
+    cursor: Option<Color>,
+    /// Foreground colors by scope selector, in the order the theme lists them
+    scopes: Vec<(String, Color)>,
+}
+
+impl Imported {
+    /// Adds a rule, which may name several selectors split by commas.
+    fn rule(&mut self, selectors: &str, foreground: Option<&str>) {
+        let Some(color) = foreground.and_then(|color| parse(color).ok()) else {
+            return;
+        };
+
+        for selector in selectors.split(',').map(str::trim) {
+            if !selector.is_empty() {
+                self.scopes.retain(|(other, _)| other != selector);
+                self.scopes.push((selector.to_string(), color));
+            }
+        }
+    }
+
+    fn finish(self) -> Theme {
+        let is_light = self
+            .is_light
+            .or(self.background.map(|color| luminance(color) > 0.5))
+            .unwrap_or(false);
+        let palette = if is_light {
+            Palette::LIGHT
+        } else {
+            Palette::DARK
+        };
+
+        let foreground = self.foreground.unwrap_or(palette.text);
+        let mut scopes = self.scopes;
+
+        // The editor's own scopes take the color of the closest rule that covers them
+        for (scope, _) in SCOPES {
+            if scopes.iter().any(|(selector, _)| selector == scope) {
+                continue;
+            }
+
+            let closest = scopes
+                .iter()
+                .filter(|(selector, _)| {
+                    scope.starts_with(selector.as_str()) && scope[selector.len()..].starts_with('.')
+                })
+                .max_by_key(|(selector, _)| selector.len())
+                .map(|(_, color)| *color);
+
+            if let Some(color) = closest {
+                scopes.push((scope.to_string(), color));
+            }
+        }
+
+        Theme {
+            name: self.name,
+            background: self.background.unwrap_or(palette.background),
+            foreground,
+            selection: self.selection.unwrap_or(Color {
+                a: 0.4,
+                ..palette.primary
+            }),
+            cursor: self.cursor.unwrap_or(foreground),
+            scopes,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct TmTheme {
+    #[serde(default)]
+    name: String,
+    settings: Vec<TmRule>,
+}
+
+#[derive(Deserialize)]
+struct TmRule {
+    scope: Option<String>,
+    #[serde(default)]
+    settings: BTreeMap<String, plist::Value>,
+}
+
+fn from_tmtheme(text: &str) -> Result<Theme, String> {
+    let theme: TmTheme = plist::from_bytes(text.as_bytes()).map_err(|error| error.to_string())?;
+    let mut imported = Imported {
+        name: theme.name,
+        ..Imported::default()
+    };
+
+    for rule in theme.settings {
+        let setting = |key: &str| rule.settings.get(key).and_then(plist::Value::as_string);
+
+        match &rule.scope {
+            Some(selectors) => imported.rule(selectors, setting("foreground")),
+            // The rule without a scope holds the editor's own colors
+            None => {
+                let color = |key| setting(key).and_then(|color| parse(color).ok());
+
+                imported.background = color("background");
+                imported.foreground = color("foreground");
+                imported.selection = color("selection");
+                imported.cursor = color("caret");
+            }
+        }
+    }
+
+    Ok(imported.finish())
+}
+
+fn from_vscode(text: &str) -> Result<Theme, String> {
+    let theme: serde_json::Value =
+        serde_json::from_str(&strip_jsonc(text)).map_err(|error| error.to_string())?;
+
+    let string = |value: &serde_json::Value, key: &str| {
+        value
+            .get(key)
+            .and_then(serde_json::Value::as_str)
+            .map(str::to_string)
+    };
+
+    let colors = theme.get("colors").cloned().unwrap_or_default();
+    let color = |key| string(&colors, key).and_then(|color| parse(&color).ok());
+
+    let mut imported = Imported {
+        name: string(&theme, "name").unwrap_or_default(),
+        is_light: string(&theme, "type").map(|kind| kind.starts_with("light")),
+        background: color("editor.background"),
+        foreground: color("editor.foreground").or(color("foreground")),
+        selection: color("editor.selectionBackground"),
+        cursor: color("editorCursor.foreground"),
+        ..Imported::default()
+    };
+
+    let rules = theme
+        .get("tokenColors")
+        .and_then(serde_json::Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+
+    for rule in rules {
+        let foreground = rule
+            .get("settings")
+            .and_then(|settings| string(settings, "foreground"));
+
+        let selectors = match rule.get("scope") {
+            Some(serde_json::Value::String(selectors)) => selectors.clone(),
+            Some(serde_json::Value::Array(selectors)) => selectors
+                .iter()
+                .filter_map(serde_json::Value::as_str)
+                .collect::<Vec<_>>()
+                .join(","),
+            _ => continue,
+        };
+
+        imported.rule(&selectors, foreground.as_deref());
+    }
+
+    Ok(imported.finish())
+}
+
+/// Drops the comments and trailing commas VS Code allows in its JSON.
+fn strip_jsonc(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(character) = chars.next() {
+        if in_string {
+            stripped.push(character);
+
+            match character {
+                '\\' => stripped.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+
+            continue;
+        }
+
+        match (character, chars.peek().copied()) {
+            ('"', _) => {
+                in_string = true;
+                stripped.push(character);
+            }
+            ('/', Some('/')) => {
+                for character in chars.by_ref() {
+                    if character == '\n' {
+                        stripped.push('\n');
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+
+                let mut previous = ' ';
+
+                for character in chars.by_ref() {
+                    if previous == '*' && character == '/' {
+                        break;
+                    }
+
+                    previous = character;
+                }
+            }
+            _ => stripped.push(character),
+        }
+    }
+
+    // A comma is trailing when only whitespace stands between it and a closing bracket
+    let mut cleaned = String::with_capacity(stripped.len());
+    let mut in_string = false;
+    let mut characters = stripped.char_indices().peekable();
+
+    while let Some((index, character)) = characters.next() {
+        if in_string {
+            cleaned.push(character);
+
+            match character {
+                '\\' => cleaned.extend(characters.next().map(|(_, escaped)| escaped)),
+                '"' => in_string = false,
+                _ => {}
+            }
+
+            continue;
+        }
+
+        match character {
+            '"' => in_string = true,
+            ',' if stripped[index + 1..].trim_start().starts_with(['}', ']']) => {
+                continue;
+            }
+            _ => {}
+        }
+
+        cleaned.push(character);
+    }
+
+    cleaned
+}
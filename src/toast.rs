@@ -0,0 +1,51 @@
+use crate::Message;
+
+use iced::theme;
+use iced::time;
+use iced::widget::{button, container, row, text};
+use iced::{Element, Length, Subscription};
+use std::time::{Duration, Instant};
+
+/// How long a toast stays before it goes by itself.
+const DURATION: Duration = Duration::from_secs(8);
+
+/// A short message shown above the status bar for a moment.
+#[derive(Debug, Clone)]
+pub struct Toast {
+    pub message: String,
+    shown_at: Instant,
+}
+
+impl Toast {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            shown_at: Instant::now(),
+        }
+    }
+
+    pub fn is_expired(&self, now: Instant) -> bool {
+        now.duration_since(self.shown_at) >= DURATION
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        container(
+            row![
+                text(&self.message).size(14).width(Length::Fill),
+                button(text("Dismiss").size(13))
+                    .on_press(Message::DismissToast)
+                    .padding([2, 6])
+                    .style(theme::Button::Secondary),
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fill)
+        .padding(8)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
+
+pub fn subscription() -> Subscription<Message> {
+    time::every(Duration::from_secs(1)).map(|_| Message::ToastTick)
+}
@@ -0,0 +1,183 @@
+use crate::Message;
+
+use iced::theme;
+use iced::widget::{button, column, container, scrollable, text, Column};
+use iced::{Element, Length};
+use std::collections::BTreeMap;
+
+pub const START_MARKER: &str = "<!-- toc -->";
+pub const END_MARKER: &str = "<!-- /toc -->";
+
+const INDENT: u16 = 12;
+
+#[derive(Debug, Clone)]
+pub struct Heading {
+    pub line: usize,
+    pub level: usize,
+    pub title: String,
+    pub anchor: String,
+}
+
+pub fn headings(text: &str) -> Vec<Heading> {
+    let mut headings = Vec::new();
+    let mut anchors: BTreeMap<String, usize> = BTreeMap::new();
+    let mut fence: Option<&str> = None;
+
+    for (line, content) in text.lines().enumerate() {
+        let trimmed = content.trim_start();
+
+        if let Some(marker) = ["```", "~~~"]
+            .into_iter()
+            .find(|marker| trimmed.starts_with(marker))
+        {
+            match fence {
+                Some(open) if open == marker => fence = None,
+                None => fence = Some(marker),
+                Some(_) => {}
+            }
+
+            continue;
+        }
+
+        if fence.is_some() || content.len() - trimmed.len() > 3 {
+            continue;
+        }
+
+        let level = trimmed.chars().take_while(|c| *c == '#').count();
+        let rest = &trimmed[level..];
+
+        if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with([' ', '\t'])) {
+            continue;
+        }
+
+        let title = rest.trim().trim_end_matches('#').trim_end().to_string();
+
+        if title.is_empty() {
+            continue;
+        }
+
+        let slug = slug(&title);
+        let count = anchors.entry(slug.clone()).or_default();
+
+        let anchor = match *count {
+            0 => slug,
+            count => format!("{slug}-{count}"),
+        };
+
+        *count += 1;
+
+        headings.push(Heading {
+            line,
+            level,
+            title,
+            anchor,
+        });
+    }
+
+    headings
+}
+
+/// Rewrites the list between the markers, or inserts markers and a list at `line`.
+pub fn update(text: &str, line: usize) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+
+    let start = lines.iter().position(|line| line.trim() == START_MARKER);
+    let end = start.and_then(|start| {
+        lines[start..]
+            .iter()
+            .position(|line| line.trim() == END_MARKER)
+            .map(|end| start + end)
+    });
+
+    // Only headings below the table are listed in it
+    let from = start.unwrap_or(line);
+    let headings: Vec<_> = headings(text)
+        .into_iter()
+        .filter(|heading| heading.line >= from)
+        .collect();
+
+    let (before, after) = match (start, end) {
+        (Some(start), Some(end)) => (&lines[..start], &lines[end + 1..]),
+        (Some(start), None) => (&lines[..start], &lines[start + 1..]),
+        (None, _) => lines.split_at(line.min(lines.len())),
+    };
+
+    let mut updated: Vec<String> = before.iter().map(|line| line.to_string()).collect();
+
+    updated.push(START_MARKER.to_string());
+    updated.extend(list(&headings));
+    updated.push(END_MARKER.to_string());
+    updated.extend(after.iter().map(|line| line.to_string()));
+
+    let mut updated = updated.join("\n");
+
+    if text.ends_with('\n') {
+        updated.push('\n');
+    }
+
+    updated
+}
+
+pub fn view(headings: &[Heading]) -> Element<'_, Message> {
+    let top = headings
+        .iter()
+        .map(|heading| heading.level)
+        .min()
+        .unwrap_or(1);
+
+    let entries = headings.iter().map(|heading| {
+        button(text(&heading.title).size(13))
+            .on_press(Message::OutlineSelected(heading.line))
+            .width(Length::Fill)
+            .padding([2, 4, 2, 4 + INDENT * (heading.level - top) as u16])
+            .style(theme::Button::Text)
+            .into()
+    });
+
+    container(
+        column![
+            text("Outline").size(16),
+            scrollable(Column::with_children(entries.collect())).height(Length::Fill),
+            button(text("Update table of contents").size(14)).on_press(Message::UpdateToc),
+            button(text("Close").size(14))
+                .on_press(Message::ClosePanel)
+                .style(theme::Button::Secondary),
+        ]
+        .spacing(10),
+    )
+    .width(Length::Fill)
+    .padding(10)
+    .style(theme::Container::Box)
+    .into()
+}
+
+fn list(headings: &[Heading]) -> impl Iterator<Item = String> + '_ {
+    let top = headings
+        .iter()
+        .map(|heading| heading.level)
+        .min()
+        .unwrap_or(1);
+
+    headings.iter().map(move |heading| {
+        format!(
+            "{}- [{}](#{})",
+            "  ".repeat(heading.level - top),
+            heading.title,
+            heading.anchor
+        )
+    })
+}
+
+// The anchor GitHub generates for a heading
+fn slug(title: &str) -> String {
+    title
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            '-' | '_' => Some(c),
+            c if c.is_alphanumeric() => Some(c),
+            _ => None,
+        })
+        .collect()
+}
@@ -0,0 +1,195 @@
+use crate::{tree, widgets, Message};
+
+use iced::theme;
+use iced::widget::{button, checkbox, column, container, horizontal_space, row, scrollable, text};
+use iced::{Color, Element, Length};
+use std::path::{Path, PathBuf};
+
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+const COMMENT_MARKERS: &[&str] = &["//", "#", "/*", "*", "--", ";", "<!--"];
+const ROW_HEIGHT: f32 = 22.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Kind {
+    Fixme,
+    Hack,
+    Todo,
+}
+
+impl Kind {
+    const ALL: [Kind; 3] = [Kind::Fixme, Kind::Hack, Kind::Todo];
+
+    fn marker(self) -> &'static str {
+        match self {
+            Kind::Fixme => "FIXME",
+            Kind::Hack => "HACK",
+            Kind::Todo => "TODO",
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            Kind::Fixme => Color::from_rgb(0.9, 0.3, 0.3),
+            Kind::Hack => Color::from_rgb(0.95, 0.6, 0.2),
+            Kind::Todo => Color::from_rgb(0.4, 0.7, 1.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Item {
+    pub line: usize,
+    pub kind: Kind,
+    pub text: String,
+}
+
+/// The TODO panel: what the last scan found and where the list is scrolled.
+#[derive(Debug, Clone, Default)]
+pub struct List {
+    pub groups: Vec<Group>,
+    offset: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Group {
+    pub path: Option<PathBuf>,
+    pub items: Vec<Item>,
+}
+
+pub fn find(text: &str) -> Vec<Item> {
+    text.lines()
+        .enumerate()
+        .filter_map(|(line, content)| {
+            Kind::ALL.into_iter().find_map(|kind| {
+                let start = content.find(kind.marker())?;
+                let (before, after) = content.split_at(start);
+
+                let is_comment = COMMENT_MARKERS.iter().any(|marker| before.contains(marker));
+
+                let is_word = after[kind.marker().len()..]
+                    .chars()
+                    .next()
+                    .map_or(true, |c| !c.is_alphanumeric() && c != '_');
+
+                (is_comment && is_word).then(|| Item {
+                    line,
+                    kind,
+                    text: after.trim().to_string(),
+                })
+            })
+        })
+        .collect()
+}
+
+pub async fn scan(
+    buffer: String,
+    path: Option<PathBuf>,
+    folder: Option<(PathBuf, tree::Filter)>,
+) -> Vec<Group> {
+    let mut groups = vec![Group {
+        items: find(&buffer),
+        path: path.clone(),
+    }];
+
+    if let Some((folder, filter)) = folder {
+        let others = tokio::task::spawn_blocking(move || {
+            tree::walk(&folder, &filter)
+                .into_iter()
+                .filter(|entry| !entry.is_dir && Some(&entry.path) != path.as_ref())
+                .filter(|entry| {
+                    std::fs::metadata(&entry.path)
+                        .is_ok_and(|metadata| metadata.len() <= MAX_FILE_SIZE)
+                })
+                .filter_map(|entry| {
+                    let text = std::fs::read_to_string(&entry.path).ok()?;
+
+                    Some(Group {
+                        items: find(&text),
+                        path: Some(entry.path),
+                    })
+                })
+                .collect::<Vec<_>>()
+        })
+        .await
+        .unwrap_or_default();
+
+        groups.extend(others);
+    }
+
+    groups.retain(|group| !group.items.is_empty());
+    groups
+}
+
+impl List {
+    pub fn scrolled(&mut self, viewport: scrollable::Viewport) {
+        self.offset = viewport.absolute_offset().y;
+    }
+
+    pub fn view(&self, include_folder: bool, height: f32) -> Element<'_, Message> {
+        view(&self.groups, include_folder, self.offset, height)
+    }
+}
+
+fn view(groups: &[Group], include_folder: bool, offset: f32, height: f32) -> Element<'_, Message> {
+    let header = row![
+        text("TODO").size(16),
+        horizontal_space(Length::Fill),
+        checkbox("Folder", include_folder, Message::ScanFolderToggled).size(14),
+    ]
+    .spacing(10);
+
+    // Each file's name is a row of its own, followed by a row per item
+    let rows: Vec<(usize, Option<usize>)> = groups
+        .iter()
+        .enumerate()
+        .flat_map(|(index, group)| {
+            std::iter::once((index, None))
+                .chain((0..group.items.len()).map(move |item| (index, Some(item))))
+        })
+        .collect();
+
+    let entry = |row: usize| -> Element<'_, Message> {
+        let (index, item) = rows[row];
+        let group = &groups[index];
+
+        let Some(item) = item.map(|item| &group.items[item]) else {
+            let title = group
+                .path
+                .as_deref()
+                .and_then(Path::file_name)
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| String::from("New file"));
+
+            return text(title).size(14).into();
+        };
+
+        button(
+            text(format!("{}: {}", item.line + 1, item.text))
+                .size(13)
+                .style(item.kind.color()),
+        )
+        .on_press(Message::TodoSelected(group.path.clone(), item.line))
+        .style(theme::Button::Text)
+        .padding(2)
+        .into()
+    };
+
+    container(
+        column![
+            header,
+            widgets::virtual_list(
+                rows.len(),
+                ROW_HEIGHT,
+                offset,
+                height,
+                entry,
+                Message::TodosScrolled,
+            ),
+        ]
+        .spacing(10),
+    )
+    .width(Length::Fill)
+    .padding(10)
+    .style(theme::Container::Box)
+    .into()
+}
@@ -0,0 +1,209 @@
+use crate::config::Config;
+use crate::Message;
+
+use iced::theme;
+use iced::widget::{button, checkbox, column, container, row, scrollable, text, Column};
+use iced::{Element, Length};
+use serde::{Deserialize, Serialize};
+
+/// A button that can be placed in the toolbar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Button {
+    New,
+    Open,
+    Save,
+    /// Pushes the buttons after it to the right edge
+    Spacer,
+    Table,
+    Dictate,
+    Tail,
+    Files,
+    Remote,
+    Collab,
+    Scratch,
+    Sync,
+    Todo,
+    Search,
+    Palette,
+    Preview,
+    Outline,
+    Assistant,
+    FocusMode,
+    Theme,
+}
+
+impl Button {
+    pub const ALL: [Button; 20] = [
+        Button::New,
+        Button::Open,
+        Button::Save,
+        Button::Spacer,
+        Button::Table,
+        Button::Dictate,
+        Button::Tail,
+        Button::Files,
+        Button::Remote,
+        Button::Collab,
+        Button::Scratch,
+        Button::Sync,
+        Button::Todo,
+        Button::Search,
+        Button::Palette,
+        Button::Preview,
+        Button::Outline,
+        Button::Assistant,
+        Button::FocusMode,
+        Button::Theme,
+    ];
+
+    /// The toolbar as it was before it could be customized.
+    pub const DEFAULT: [Button; 14] = [
+        Button::New,
+        Button::Open,
+        Button::Save,
+        Button::Spacer,
+        Button::Table,
+        Button::Dictate,
+        Button::Tail,
+        Button::Files,
+        Button::Remote,
+        Button::Collab,
+        Button::Scratch,
+        Button::Sync,
+        Button::Todo,
+        Button::Theme,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Button::New => "New File",
+            Button::Open => "Open File",
+            Button::Save => "Save",
+            Button::Spacer => "Flexible space",
+            Button::Table => "Table",
+            Button::Dictate => "Dictate",
+            Button::Tail => "Tail",
+            Button::Files => "Files",
+            Button::Remote => "Remote",
+            Button::Collab => "Collab",
+            Button::Scratch => "Scratch",
+            Button::Sync => "Sync",
+            Button::Todo => "TODO",
+            Button::Search => "Search",
+            Button::Palette => "Commands",
+            Button::Preview => "Preview",
+            Button::Outline => "Outline",
+            Button::Assistant => "Assistant",
+            Button::FocusMode => "Focus",
+            Button::Theme => "Theme",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Style {
+    /// Icons alone where a button has one, with the label as a tooltip
+    #[default]
+    Icons,
+    IconsAndLabels,
+}
+
+/// Moves a shown button `step` places along the toolbar.
+pub fn move_button(config: &mut Config, button: Button, step: i32) {
+    let mut buttons = config.toolbar().to_vec();
+
+    let Some(position) = buttons.iter().position(|other| *other == button) else {
+        return;
+    };
+
+    let target = position as i32 + step;
+
+    if (0..buttons.len() as i32).contains(&target) {
+        buttons.swap(position, target as usize);
+        config.toolbar = Some(buttons);
+    }
+}
+
+pub fn set_shown(config: &mut Config, button: Button, is_shown: bool) {
+    let mut buttons = config.toolbar().to_vec();
+    buttons.retain(|other| *other != button);
+
+    if is_shown {
+        buttons.push(button);
+    }
+
+    config.toolbar = Some(buttons);
+}
+
+/// The settings panel for picking the toolbar's buttons, their order and style.
+pub fn settings(config: &Config) -> Element<'_, Message> {
+    let shown = config.toolbar();
+
+    let entry = |button: Button, is_shown: bool| -> Element<'_, Message> {
+        let toggle = checkbox(button.label(), is_shown, move |is_shown| {
+            Message::ToolbarButtonToggled(button, is_shown)
+        })
+        .size(14)
+        .text_size(14)
+        .width(Length::Fill);
+
+        let arrows = is_shown.then(|| {
+            row![
+                arrow("↑", Message::ToolbarButtonMoved(button, -1)),
+                arrow("↓", Message::ToolbarButtonMoved(button, 1)),
+            ]
+        });
+
+        match arrows {
+            Some(arrows) => row![toggle, arrows].into(),
+            None => toggle.into(),
+        }
+    };
+
+    let entries = shown.iter().map(|button| entry(*button, true)).chain(
+        Button::ALL
+            .into_iter()
+            .filter(|button| !shown.contains(button))
+            .map(|button| entry(button, false)),
+    );
+
+    let style = |label, style: Style| {
+        button(text(label).size(14))
+            .on_press(Message::ToolbarStyleSelected(style))
+            .padding([5, 10])
+            .style(if config.toolbar_style == style {
+                theme::Button::Primary
+            } else {
+                theme::Button::Secondary
+            })
+    };
+
+    container(
+        column![
+            text("Toolbar").size(16),
+            row![
+                style("Icons", Style::Icons),
+                style("Icons and labels", Style::IconsAndLabels),
+            ]
+            .spacing(5),
+            scrollable(Column::with_children(entries.collect()).spacing(5)).height(Length::Fill),
+            button(text("Close").size(14))
+                .on_press(Message::ClosePanel)
+                .style(theme::Button::Secondary),
+        ]
+        .spacing(10),
+    )
+    .width(Length::Fill)
+    .padding(10)
+    .style(theme::Container::Box)
+    .into()
+}
+
+fn arrow<'a>(label: &'a str, on_press: Message) -> iced::widget::Button<'a, Message> {
+    button(text(label).size(14))
+        .on_press(on_press)
+        .padding([0, 5])
+        .style(theme::Button::Text)
+}
@@ -0,0 +1,535 @@
+use crate::Message;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
+use iced::theme;
+use iced::widget::{button, checkbox, column, container, row, text, text_input};
+use iced::{Element, Length};
+use md5::Md5;
+use sha2::{Digest, Sha256};
+use std::cmp::Ordering;
+use unicode_width::UnicodeWidthChar;
+
+/// How far apart tab stops are, as the editor draws them.
+const TAB_WIDTH: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transform {
+    Base64Encode,
+    Base64Decode,
+    UrlEncode,
+    UrlDecode,
+    Md5,
+    Sha256,
+    TimestampToDate,
+    DateToTimestamp,
+    ToHex,
+    ToDecimal,
+    ToBinary,
+    ToOctal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Output {
+    Replace,
+    Copy,
+    /// Only shown in a toast, leaving the buffer and clipboard alone
+    Show,
+}
+
+impl Transform {
+    pub const ALL: [Transform; 12] = [
+        Transform::Base64Encode,
+        Transform::Base64Decode,
+        Transform::UrlEncode,
+        Transform::UrlDecode,
+        Transform::Md5,
+        Transform::Sha256,
+        Transform::TimestampToDate,
+        Transform::DateToTimestamp,
+        Transform::ToHex,
+        Transform::ToDecimal,
+        Transform::ToBinary,
+        Transform::ToOctal,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Transform::Base64Encode => "Base64 encode",
+            Transform::Base64Decode => "Base64 decode",
+            Transform::UrlEncode => "URL-encode",
+            Transform::UrlDecode => "URL-decode",
+            Transform::Md5 => "MD5",
+            Transform::Sha256 => "SHA-256",
+            Transform::TimestampToDate => "Unix timestamp to date",
+            Transform::DateToTimestamp => "Date to Unix timestamp",
+            Transform::ToHex => "Number to hexadecimal",
+            Transform::ToDecimal => "Number to decimal",
+            Transform::ToBinary => "Number to binary",
+            Transform::ToOctal => "Number to octal",
+        }
+    }
+
+    /// Conversions short enough to read at a glance in a toast.
+    pub fn can_show(self) -> bool {
+        !matches!(
+            self,
+            Transform::Base64Encode
+                | Transform::Base64Decode
+                | Transform::UrlEncode
+                | Transform::UrlDecode
+                | Transform::Md5
+                | Transform::Sha256
+        )
+    }
+
+    pub fn apply(self, input: &str) -> Option<String> {
+        match self {
+            Transform::Base64Encode => Some(STANDARD.encode(input)),
+            Transform::Base64Decode => {
+                let bytes = STANDARD.decode(input.trim()).ok()?;
+
+                String::from_utf8(bytes).ok()
+            }
+            Transform::UrlEncode => Some(urlencoding::encode(input).into_owned()),
+            Transform::UrlDecode => urlencoding::decode(input)
+                .ok()
+                .map(|text| text.into_owned()),
+            Transform::Md5 => Some(hex(&Md5::digest(input))),
+            Transform::Sha256 => Some(hex(&Sha256::digest(input))),
+            Transform::TimestampToDate => timestamp_to_date(input.trim()),
+            Transform::DateToTimestamp => date_to_timestamp(input.trim()),
+            Transform::ToHex => number(input).map(|(sign, value)| format!("{sign}0x{value:x}")),
+            Transform::ToDecimal => number(input).map(|(sign, value)| format!("{sign}{value}")),
+            Transform::ToBinary => number(input).map(|(sign, value)| format!("{sign}0b{value:b}")),
+            Transform::ToOctal => number(input).map(|(sign, value)| format!("{sign}0o{value:o}")),
+        }
+    }
+}
+
+/// Seconds since the epoch, or milliseconds when there are more than 11 digits.
+fn timestamp_to_date(input: &str) -> Option<String> {
+    let value: i64 = input.parse().ok()?;
+    let digits = input.trim_start_matches('-').len();
+
+    let date = if digits > 11 {
+        Local.timestamp_millis_opt(value).single()?
+    } else {
+        Local.timestamp_opt(value, 0).single()?
+    };
+
+    Some(date.format("%Y-%m-%d %H:%M:%S %:z").to_string())
+}
+
+/// Takes RFC 3339, or a local date with an optional time.
+fn date_to_timestamp(input: &str) -> Option<String> {
+    if let Ok(date) = DateTime::parse_from_rfc3339(input) {
+        return Some(date.timestamp().to_string());
+    }
+
+    if let Ok(date) = DateTime::parse_from_str(input, "%Y-%m-%d %H:%M:%S %:z") {
+        return Some(date.timestamp().to_string());
+    }
+
+    let naive = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(input, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(input, "%Y-%m-%d")
+                .ok()?
+                .and_hms_opt(0, 0, 0)
+        })?;
+
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|date| date.timestamp().to_string())
+}
+
+/// A whole number written in decimal or with a `0x`, `0b` or `0o` prefix, as its sign and size.
+fn number(input: &str) -> Option<(&'static str, u128)> {
+    let input = input.trim().replace('_', "");
+    let (sign, digits) = match input.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", input.as_str()),
+    };
+
+    let lower = digits.to_ascii_lowercase();
+    let (radix, digits) = [("0x", 16), ("0b", 2), ("0o", 8)]
+        .iter()
+        .find_map(|(prefix, radix)| Some((*radix, lower.strip_prefix(prefix)?)))
+        .unwrap_or((10, lower.as_str()));
+
+    let value = u128::from_str_radix(digits, radix).ok()?;
+
+    Some((if value == 0 { "" } else { sign }, value))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Tools that rework whole lines and need a little input first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    Align,
+    Sort,
+}
+
+/// Asks what to align on, or which column to sort by.
+#[derive(Debug, Clone)]
+pub struct Prompt {
+    pub tool: Tool,
+    pub input: String,
+    pub is_numeric: bool,
+    pub is_descending: bool,
+}
+
+impl Prompt {
+    pub fn new(tool: Tool) -> Self {
+        Self {
+            tool,
+            input: match tool {
+                Tool::Align => String::from("="),
+                Tool::Sort => String::new(),
+            },
+            is_numeric: false,
+            is_descending: false,
+        }
+    }
+
+    /// The reworked lines, or `None` when the input makes no sense.
+    pub fn apply(&self, lines: &str) -> Option<String> {
+        match self.tool {
+            Tool::Align => {
+                let separator = self.input.trim();
+
+                (!separator.is_empty()).then(|| align(lines, separator))
+            }
+            Tool::Sort => {
+                let column = match self.input.trim() {
+                    "" => None,
+                    column => Some(column.parse::<usize>().ok().filter(|column| *column > 0)?),
+                };
+
+                Some(sort(lines, column, self.is_numeric, self.is_descending))
+            }
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let (title, description, placeholder) = match self.tool {
+            Tool::Align => (
+                "Align selection",
+                "Pads the selected lines so the first occurrence of the text lines up.",
+                "=",
+            ),
+            Tool::Sort => (
+                "Sort lines",
+                "Sorts the selected lines by a whitespace-separated column, or by the whole line when left empty.",
+                "Column, e.g. 2",
+            ),
+        };
+
+        let mut options = row![].spacing(10);
+
+        if self.tool == Tool::Sort {
+            options = options
+                .push(checkbox("Numeric", self.is_numeric, Message::SortNumericToggled).size(16))
+                .push(
+                    checkbox(
+                        "Descending",
+                        self.is_descending,
+                        Message::SortDescendingToggled,
+                    )
+                    .size(16),
+                );
+        }
+
+        let apply = self.apply("").map(|_| Message::ApplyLineTool);
+
+        container(
+            column![
+                text(title).size(16),
+                text(description).size(14),
+                text_input(placeholder, &self.input)
+                    .on_input(Message::LineToolInputChanged)
+                    .on_submit(Message::ApplyLineTool)
+                    .size(14),
+                options,
+                row![
+                    button(text("Apply").size(14)).on_press_maybe(apply),
+                    button(text("Cancel").size(14))
+                        .on_press(Message::ClosePanel)
+                        .style(theme::Button::Secondary),
+                ]
+                .spacing(10),
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fill)
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
+
+/// How wide text is on screen, counting wide characters twice and tabs up to the next stop.
+fn width(text: &str) -> usize {
+    text.chars().fold(0, |width, character| match character {
+        '\t' => (width / TAB_WIDTH + 1) * TAB_WIDTH,
+        character => width + character.width().unwrap_or(0),
+    })
+}
+
+/// Pads each line before its first `separator` so they all start in the same column.
+pub fn align(lines: &str, separator: &str) -> String {
+    let split: Vec<Option<(&str, &str)>> = lines
+        .split('\n')
+        .map(|line| {
+            let index = line.find(separator)?;
+
+            Some((line[..index].trim_end(), &line[index..]))
+        })
+        .collect();
+
+    // Keep a space before the separator when any line had one
+    let gap = usize::from(lines.split('\n').any(|line| {
+        line.find(separator)
+            .is_some_and(|index| line[..index].ends_with(char::is_whitespace))
+    }));
+
+    let column = split
+        .iter()
+        .flatten()
+        .map(|(before, _)| width(before))
+        .max()
+        .unwrap_or(0)
+        + gap;
+
+    lines
+        .split('\n')
+        .zip(&split)
+        .map(|(line, split)| match split {
+            Some((before, rest)) => {
+                format!("{before}{}{rest}", " ".repeat(column - width(before)))
+            }
+            None => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Sorts lines by a 1-based whitespace-separated column, lines lacking it going last.
+pub fn sort(lines: &str, column: Option<usize>, is_numeric: bool, is_descending: bool) -> String {
+    let key = |line: &str| -> Option<String> {
+        match column {
+            Some(column) => line.split_whitespace().nth(column - 1).map(str::to_string),
+            None => Some(line.to_string()),
+        }
+    };
+
+    let number = |key: &str| -> Option<f64> {
+        let end = key
+            .char_indices()
+            .find(|(index, character)| {
+                !(character.is_ascii_digit()
+                    || matches!(character, '.' | '_' | ',')
+                    || (*index == 0 && matches!(character, '-' | '+')))
+            })
+            .map_or(key.len(), |(index, _)| index);
+
+        key[..end].replace(['_', ','], "").parse().ok()
+    };
+
+    let compare = |a: &Option<String>, b: &Option<String>| -> Ordering {
+        match (a, b) {
+            (Some(a), Some(b)) if is_numeric => match (number(a), number(b)) {
+                (Some(x), Some(y)) => x.total_cmp(&y),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => a.cmp(b),
+            },
+            (Some(a), Some(b)) => a.cmp(b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    };
+
+    let mut keyed: Vec<(Option<String>, &str)> =
+        lines.split('\n').map(|line| (key(line), line)).collect();
+
+    keyed.sort_by(|(a, _), (b, _)| {
+        let ordering = compare(a, b);
+
+        // Lines without the column stay at the end either way
+        if is_descending && a.is_some() && b.is_some() {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+
+    keyed
+        .into_iter()
+        .map(|(_, line)| line)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64_round_trips() {
+        let encoded = Transform::Base64Encode.apply("hello, wörld").unwrap();
+
+        assert_eq!(
+            Transform::Base64Decode.apply(&encoded).as_deref(),
+            Some("hello, wörld")
+        );
+        assert_eq!(Transform::Base64Encode.apply("hello").unwrap(), "aGVsbG8=");
+        assert_eq!(
+            Transform::Base64Decode.apply(" aGVsbG8=\n").as_deref(),
+            Some("hello")
+        );
+    }
+
+    #[test]
+    fn base64_rejects_invalid_input() {
+        assert_eq!(Transform::Base64Decode.apply("not base64!"), None);
+        // Decodes to 0xff, which is not UTF-8
+        assert_eq!(Transform::Base64Decode.apply("/w=="), None);
+    }
+
+    #[test]
+    fn url_encoding_round_trips() {
+        let encoded = Transform::UrlEncode.apply("a b&c=d/é").unwrap();
+
+        assert_eq!(encoded, "a%20b%26c%3Dd%2F%C3%A9");
+        assert_eq!(
+            Transform::UrlDecode.apply(&encoded).as_deref(),
+            Some("a b&c=d/é")
+        );
+    }
+
+    #[test]
+    fn url_decoding_rejects_invalid_utf8() {
+        assert_eq!(Transform::UrlDecode.apply("%ff"), None);
+    }
+
+    #[test]
+    fn digests_match_known_vectors() {
+        assert_eq!(
+            Transform::Md5.apply("").unwrap(),
+            "d41d8cd98f00b204e9800998ecf8427e"
+        );
+        assert_eq!(
+            Transform::Md5.apply("abc").unwrap(),
+            "900150983cd24fb0d6963f7d28e17f72"
+        );
+        assert_eq!(
+            Transform::Sha256.apply("").unwrap(),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            Transform::Sha256.apply("abc").unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn timestamps_round_trip_through_dates() {
+        let date = Transform::TimestampToDate.apply("1000000000").unwrap();
+
+        assert_eq!(
+            Transform::DateToTimestamp.apply(&date).as_deref(),
+            Some("1000000000")
+        );
+        assert_eq!(
+            Transform::TimestampToDate.apply("1000000000000").as_deref(),
+            Some(date.as_str())
+        );
+    }
+
+    #[test]
+    fn dates_parse_as_rfc_3339() {
+        assert_eq!(
+            Transform::DateToTimestamp
+                .apply("2001-09-09T01:46:40Z")
+                .as_deref(),
+            Some("1000000000")
+        );
+        assert_eq!(
+            Transform::DateToTimestamp
+                .apply("2001-09-09T03:46:40+02:00")
+                .as_deref(),
+            Some("1000000000")
+        );
+    }
+
+    #[test]
+    fn dates_reject_invalid_input() {
+        assert_eq!(Transform::TimestampToDate.apply("yesterday"), None);
+        assert_eq!(Transform::DateToTimestamp.apply("2001-13-40"), None);
+    }
+
+    #[test]
+    fn numbers_convert_between_bases() {
+        assert_eq!(Transform::ToHex.apply("255").as_deref(), Some("0xff"));
+        assert_eq!(Transform::ToDecimal.apply("0xFF").as_deref(), Some("255"));
+        assert_eq!(Transform::ToDecimal.apply("-0b101").as_deref(), Some("-5"));
+        assert_eq!(Transform::ToOctal.apply("8").as_deref(), Some("0o10"));
+        assert_eq!(Transform::ToBinary.apply("0o17").as_deref(), Some("0b1111"));
+        assert_eq!(Transform::ToHex.apply("1_000").as_deref(), Some("0x3e8"));
+        assert_eq!(Transform::ToDecimal.apply("-0").as_deref(), Some("0"));
+    }
+
+    #[test]
+    fn numbers_reject_invalid_input() {
+        assert_eq!(Transform::ToDecimal.apply("0xzz"), None);
+        assert_eq!(Transform::ToHex.apply("12.5"), None);
+        assert_eq!(Transform::ToHex.apply(""), None);
+    }
+
+    #[test]
+    fn align_lines_up_the_separator() {
+        assert_eq!(
+            align("a = 1\nlong = 2\nnone", "="),
+            "a    = 1\nlong = 2\nnone"
+        );
+        assert_eq!(align("a=1\nbb=2", "="), "a =1\nbb=2");
+    }
+
+    #[test]
+    fn align_counts_wide_characters_twice() {
+        assert_eq!(align("日本=1\nab=2", "="), "日本=1\nab  =2");
+    }
+
+    #[test]
+    fn sort_orders_whole_lines() {
+        assert_eq!(sort("b\na\nc", None, false, false), "a\nb\nc");
+        assert_eq!(sort("b\na\nc", None, false, true), "c\nb\na");
+    }
+
+    #[test]
+    fn sort_by_numeric_column_keeps_short_lines_last() {
+        assert_eq!(sort("x 10\ny 9\nz", Some(2), true, false), "y 9\nx 10\nz");
+        assert_eq!(sort("y 9\nz\nx 10", Some(2), true, true), "x 10\ny 9\nz");
+        assert_eq!(sort("x 10\ny 9", Some(2), false, false), "x 10\ny 9");
+    }
+
+    #[test]
+    fn sort_prompt_rejects_invalid_columns() {
+        let mut prompt = Prompt::new(Tool::Sort);
+
+        prompt.input = String::from("0");
+        assert_eq!(prompt.apply("a\nb"), None);
+
+        prompt.input = String::from("second");
+        assert_eq!(prompt.apply("a\nb"), None);
+    }
+}
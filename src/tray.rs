@@ -0,0 +1,99 @@
+use std::path::PathBuf;
+use tray_icon::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
+use tray_icon::{Icon, TrayIcon, TrayIconBuilder};
+
+const SHOW: &str = "show";
+const NEW_WINDOW: &str = "new-window";
+const RECENT: &str = "recent-";
+const QUIT: &str = "quit";
+const ICON_SIZE: u32 = 32;
+const ICON_COLOR: [u8; 4] = [0x4a, 0x7d, 0xc9, 0xff];
+
+#[derive(Debug, Clone)]
+pub enum Action {
+    Show,
+    NewWindow,
+    OpenRecent(PathBuf),
+    Quit,
+}
+
+pub struct Tray {
+    recent: Vec<PathBuf>,
+    #[cfg(not(target_os = "linux"))]
+    _icon: Option<TrayIcon>,
+}
+
+impl Tray {
+    // GTK wants its own main loop for the tray, which iced does not run
+    #[cfg(target_os = "linux")]
+    pub fn spawn(recent: Vec<PathBuf>) -> Self {
+        let paths = recent.clone();
+
+        std::thread::spawn(move || {
+            if gtk::init().is_ok() {
+                let _icon = build(&paths);
+
+                gtk::main();
+            }
+        });
+
+        Self { recent }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn spawn(recent: Vec<PathBuf>) -> Self {
+        Self {
+            _icon: build(&recent),
+            recent,
+        }
+    }
+
+    pub fn poll(&self) -> Vec<Action> {
+        MenuEvent::receiver()
+            .try_iter()
+            .filter_map(|event| match event.id.0.as_str() {
+                SHOW => Some(Action::Show),
+                NEW_WINDOW => Some(Action::NewWindow),
+                QUIT => Some(Action::Quit),
+                id => id
+                    .strip_prefix(RECENT)
+                    .and_then(|index| index.parse::<usize>().ok())
+                    .and_then(|index| self.recent.get(index).cloned())
+                    .map(Action::OpenRecent),
+            })
+            .collect()
+    }
+}
+
+fn build(recent: &[PathBuf]) -> Option<TrayIcon> {
+    let open_recent = Submenu::new("Open recent", !recent.is_empty());
+
+    for (index, path) in recent.iter().enumerate() {
+        let _ = open_recent.append(&MenuItem::with_id(
+            format!("{RECENT}{index}"),
+            path.display().to_string(),
+            true,
+            None,
+        ));
+    }
+
+    let menu = Menu::new();
+
+    let _ = menu.append_items(&[
+        &MenuItem::with_id(SHOW, "Show Bonkitor", true, None),
+        &MenuItem::with_id(NEW_WINDOW, "New window", true, None),
+        &open_recent,
+        &PredefinedMenuItem::separator(),
+        &MenuItem::with_id(QUIT, "Quit", true, None),
+    ]);
+
+    let pixels = ICON_COLOR.repeat((ICON_SIZE * ICON_SIZE) as usize);
+    let icon = Icon::from_rgba(pixels, ICON_SIZE, ICON_SIZE).ok()?;
+
+    TrayIconBuilder::new()
+        .with_menu(Box::new(menu))
+        .with_tooltip("Bonkitor")
+        .with_icon(icon)
+        .build()
+        .ok()
+}
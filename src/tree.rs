@@ -0,0 +1,298 @@
+use crate::{icons, Message};
+
+use iced::theme;
+use iced::widget::{
+    button, checkbox, column, container, row, scrollable, text, text_input, Column, Row,
+};
+use iced::{Color, Element, Length};
+use ignore::overrides::OverrideBuilder;
+use ignore::WalkBuilder;
+use std::collections::BTreeSet;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+const MAX_ENTRIES: usize = 20_000;
+const INDENT: u16 = 12;
+const MATCH_COLOR: Color = Color::from_rgb(0.95, 0.7, 0.2);
+
+#[derive(Debug, Clone)]
+pub struct Filter {
+    pub exclude: Vec<String>,
+    pub show_ignored: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub path: PathBuf,
+    pub depth: usize,
+    pub is_dir: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Tree {
+    pub root: PathBuf,
+    pub entries: Vec<Entry>,
+    pub expanded: BTreeSet<PathBuf>,
+    pub show_ignored: bool,
+    pub query: String,
+    /// The keyboard cursor among the visible entries
+    pub cursor: Option<usize>,
+}
+
+pub fn filter_id() -> text_input::Id {
+    text_input::Id::new("tree-filter")
+}
+
+impl Tree {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            entries: Vec::new(),
+            expanded: BTreeSet::new(),
+            show_ignored: false,
+            query: String::new(),
+            cursor: None,
+        }
+    }
+
+    pub fn filter(&self, exclude: &[String]) -> Filter {
+        Filter {
+            exclude: exclude.to_vec(),
+            show_ignored: self.show_ignored,
+        }
+    }
+
+    pub fn toggle(&mut self, path: PathBuf) {
+        if !self.expanded.remove(&path) {
+            self.expanded.insert(path);
+        }
+    }
+
+    pub fn move_cursor(&mut self, step: i32) {
+        let count = self.visible().len();
+
+        if count == 0 {
+            self.cursor = None;
+            return;
+        }
+
+        let next = match self.cursor {
+            Some(cursor) => (cursor as i32 + step).clamp(0, count as i32 - 1) as usize,
+            None => 0,
+        };
+
+        self.cursor = Some(next);
+    }
+
+    /// What pressing the entry under the keyboard cursor would do.
+    pub fn activate(&self) -> Option<Message> {
+        let (entry, _) = self.visible().into_iter().nth(self.cursor?)?;
+
+        Some(if entry.is_dir {
+            Message::TreeToggled(entry.path.clone())
+        } else {
+            Message::TreeSelected(entry.path.clone())
+        })
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = &Path> {
+        self.entries
+            .iter()
+            .filter(|entry| !entry.is_dir)
+            .map(|entry| entry.path.as_path())
+    }
+
+    pub fn relative<'a>(&self, path: &'a Path) -> &'a Path {
+        path.strip_prefix(&self.root).unwrap_or(path)
+    }
+
+    pub fn view(&self, current: Option<&Path>) -> Element<'_, Message> {
+        let entries = self
+            .visible()
+            .into_iter()
+            .enumerate()
+            .map(|(index, (entry, ranges))| {
+                let name = entry
+                    .path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+
+                let is_expanded = !self.query.is_empty() || self.expanded.contains(&entry.path);
+
+                let (marker, message) = match (entry.is_dir, is_expanded) {
+                    (true, true) => ("▾ ", Message::TreeToggled(entry.path.clone())),
+                    (true, false) => ("▸ ", Message::TreeToggled(entry.path.clone())),
+                    (false, _) => ("  ", Message::TreeSelected(entry.path.clone())),
+                };
+
+                let icon = if entry.is_dir {
+                    icons::folder(is_expanded)
+                } else {
+                    icons::file(&entry.path)
+                };
+
+                button(row![icon, label(marker, name, ranges)].spacing(4))
+                    .on_press(message)
+                    .width(Length::Fill)
+                    .padding([2, 4, 2, 4 + INDENT * (entry.depth as u16 - 1)])
+                    .style(if current == Some(entry.path.as_path()) {
+                        theme::Button::Primary
+                    } else if self.cursor == Some(index) {
+                        theme::Button::Secondary
+                    } else {
+                        theme::Button::Text
+                    })
+                    .into()
+            });
+
+        container(
+            column![
+                text(crate::workspace::name(&self.root)).size(16),
+                text_input("Filter files...", &self.query)
+                    .id(filter_id())
+                    .on_input(Message::TreeFilterChanged)
+                    .size(14)
+                    .padding(5),
+                checkbox(
+                    "Show ignored",
+                    self.show_ignored,
+                    Message::ShowIgnoredToggled
+                )
+                .size(14),
+                scrollable(Column::with_children(entries.collect())),
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+    }
+
+    fn visible(&self) -> Vec<(&Entry, Vec<Range<usize>>)> {
+        if self.query.is_empty() {
+            let mut collapsed: Option<usize> = None;
+
+            return self
+                .entries
+                .iter()
+                .filter(|entry| {
+                    if collapsed.is_some_and(|depth| entry.depth > depth) {
+                        return false;
+                    }
+
+                    collapsed = (entry.is_dir && !self.expanded.contains(&entry.path))
+                        .then_some(entry.depth);
+
+                    true
+                })
+                .map(|entry| (entry, Vec::new()))
+                .collect();
+        }
+
+        let matches: Vec<_> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let name = entry.path.file_name().unwrap_or_default().to_string_lossy();
+
+                (entry, matched(&name, &self.query))
+            })
+            .collect();
+
+        let shown: BTreeSet<&Path> = matches
+            .iter()
+            .filter(|(_, ranges)| ranges.is_some())
+            .flat_map(|(entry, _)| entry.path.ancestors())
+            .collect();
+
+        matches
+            .into_iter()
+            .filter(|(entry, _)| shown.contains(entry.path.as_path()))
+            .map(|(entry, ranges)| (entry, ranges.unwrap_or_default()))
+            .collect()
+    }
+}
+
+fn matched(name: &str, query: &str) -> Option<Vec<Range<usize>>> {
+    let lowercase = name.to_lowercase();
+    let query = query.to_lowercase();
+
+    if lowercase.len() == name.len() {
+        if let Some(start) = lowercase.find(&query) {
+            return Some(vec![start..start + query.len()]);
+        }
+    }
+
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    let mut characters = name.char_indices();
+
+    for wanted in query.chars() {
+        let (index, c) = characters.find(|(_, c)| c.to_lowercase().eq(wanted.to_lowercase()))?;
+
+        match ranges.last_mut() {
+            Some(range) if range.end == index => range.end += c.len_utf8(),
+            _ => ranges.push(index..index + c.len_utf8()),
+        }
+    }
+
+    Some(ranges)
+}
+
+fn label<'a>(marker: &'a str, name: String, ranges: Vec<Range<usize>>) -> Element<'a, Message> {
+    let mut parts = Row::new().push(text(marker).size(13));
+    let mut end = 0;
+
+    for range in ranges {
+        parts = parts
+            .push(text(&name[end..range.start]).size(13))
+            .push(text(&name[range.clone()]).size(13).style(MATCH_COLOR));
+
+        end = range.end;
+    }
+
+    parts.push(text(&name[end..]).size(13)).into()
+}
+
+pub async fn index(root: PathBuf, filter: Filter) -> Vec<Entry> {
+    tokio::task::spawn_blocking(move || walk(&root, &filter))
+        .await
+        .unwrap_or_default()
+}
+
+pub fn walk(root: &Path, filter: &Filter) -> Vec<Entry> {
+    let mut overrides = OverrideBuilder::new(root);
+
+    for pattern in &filter.exclude {
+        let _ = overrides.add(&format!("!{pattern}"));
+    }
+
+    let mut walker = WalkBuilder::new(root);
+
+    walker
+        .hidden(!filter.show_ignored)
+        .ignore(!filter.show_ignored)
+        .git_ignore(!filter.show_ignored)
+        .git_global(!filter.show_ignored)
+        .git_exclude(!filter.show_ignored)
+        .require_git(false)
+        .sort_by_file_path(|a, b| a.cmp(b));
+
+    if let Ok(overrides) = overrides.build() {
+        walker.overrides(overrides);
+    }
+
+    walker
+        .build()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.depth() > 0)
+        .take(MAX_ENTRIES)
+        .map(|entry| Entry {
+            is_dir: entry.file_type().is_some_and(|kind| kind.is_dir()),
+            depth: entry.depth(),
+            path: entry.into_path(),
+        })
+        .collect()
+}
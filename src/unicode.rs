@@ -0,0 +1,88 @@
+use crate::Message;
+
+use iced::theme;
+use iced::widget::{button, column, container, row, scrollable, text, text_input, Column};
+use iced::{Element, Length};
+
+const MAX_RESULTS: usize = 50;
+const SEARCH_LIMIT: u32 = 0x2FFFF;
+
+pub fn describe(c: char) -> String {
+    match unicode_names2::name(c) {
+        Some(name) => format!("U+{:04X} {name}", c as u32),
+        None => format!("U+{:04X}", c as u32),
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Picker {
+    pub query: String,
+    results: Vec<char>,
+}
+
+impl Picker {
+    pub fn search(&mut self, query: String) {
+        self.results = find(&query);
+        self.query = query;
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let results = self.results.iter().map(|c| {
+            let c = *c;
+
+            button(row![text(c).size(16).width(30), text(describe(c)).size(13)].spacing(10))
+                .on_press(Message::InsertCharacter(c))
+                .width(Length::Fill)
+                .padding(4)
+                .style(theme::Button::Text)
+                .into()
+        });
+
+        container(
+            column![
+                text("Insert Unicode character").size(16),
+                text_input("Name or U+2713", &self.query)
+                    .on_input(Message::UnicodeQueryChanged)
+                    .size(14)
+                    .padding(5),
+                scrollable(Column::with_children(results.collect())).height(Length::Fill),
+                button(text("Close").size(14))
+                    .on_press(Message::ClosePanel)
+                    .style(theme::Button::Secondary),
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fill)
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
+
+fn find(query: &str) -> Vec<char> {
+    let query = query.trim();
+
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    let hex = query
+        .strip_prefix("U+")
+        .or_else(|| query.strip_prefix("u+"))
+        .or_else(|| query.strip_prefix("0x"));
+
+    if let Some(c) = hex
+        .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+        .and_then(char::from_u32)
+    {
+        return vec![c];
+    }
+
+    let query = query.to_uppercase();
+
+    (0..=SEARCH_LIMIT)
+        .filter_map(char::from_u32)
+        .filter(|c| unicode_names2::name(*c).is_some_and(|name| name.to_string().contains(&query)))
+        .take(MAX_RESULTS)
+        .collect()
+}
@@ -0,0 +1,226 @@
+use crate::{Error, Message};
+
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use iced::theme;
+use iced::widget::{button, column, container, row, text, text_input};
+use iced::{Element, Length};
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const MAGIC: &[u8] = b"BONKITOR-VAULT1\n";
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 24;
+const HEADER_LENGTH: usize = MAGIC.len() + SALT_LENGTH;
+
+/// A key derived from a passphrase, kept so saving doesn't ask again.
+#[derive(Clone)]
+pub struct Key {
+    key: [u8; 32],
+    salt: [u8; SALT_LENGTH],
+}
+
+// Never print key material
+impl fmt::Debug for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Key(..)")
+    }
+}
+
+impl Key {
+    fn derive(passphrase: &str, salt: [u8; SALT_LENGTH]) -> Result<Self, Error> {
+        let mut key = [0; 32];
+
+        argon2::Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+            .map_err(|error| Error::RemoteFailed(error.to_string()))?;
+
+        Ok(Self { key, salt })
+    }
+}
+
+pub fn is_sealed(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+/// Encrypts the text as the magic line, salt, nonce and XChaCha20-Poly1305 ciphertext.
+pub fn seal(key: &Key, plaintext: &[u8]) -> Vec<u8> {
+    let cipher = XChaCha20Poly1305::new(&key.key.into());
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+    let mut bytes = Vec::with_capacity(HEADER_LENGTH + NONCE_LENGTH + plaintext.len() + 16);
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&key.salt);
+
+    // The header is authenticated too, so it cannot be swapped
+    let ciphertext = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext,
+                aad: &bytes,
+            },
+        )
+        .expect("encrypting into memory cannot fail");
+
+    bytes.extend_from_slice(&nonce);
+    bytes.extend_from_slice(&ciphertext);
+
+    bytes
+}
+
+fn unseal(bytes: &[u8], passphrase: &str) -> Result<(String, Key), Error> {
+    let invalid = || Error::IOFailed(io::ErrorKind::InvalidData);
+
+    if !is_sealed(bytes) || bytes.len() < HEADER_LENGTH + NONCE_LENGTH {
+        return Err(invalid());
+    }
+
+    let (header, rest) = bytes.split_at(HEADER_LENGTH);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LENGTH);
+
+    let salt = header[MAGIC.len()..].try_into().map_err(|_| invalid())?;
+    let key = Key::derive(passphrase, salt)?;
+
+    let plaintext = XChaCha20Poly1305::new(&key.key.into())
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: header,
+            },
+        )
+        .map_err(|_| Error::RemoteFailed(String::from("wrong passphrase or damaged file")))?;
+
+    let text = String::from_utf8(plaintext).map_err(|_| invalid())?;
+
+    Ok((text, key))
+}
+
+/// Derives a key with a fresh salt for a file being encrypted.
+pub async fn create(passphrase: String) -> Result<Key, Error> {
+    tokio::task::spawn_blocking(move || {
+        let mut salt = [0; SALT_LENGTH];
+        OsRng.fill_bytes(&mut salt);
+
+        Key::derive(&passphrase, salt)
+    })
+    .await
+    .map_err(|_| Error::IOFailed(io::ErrorKind::Other))?
+}
+
+pub async fn unlock(
+    path: PathBuf,
+    passphrase: String,
+) -> Result<(PathBuf, Arc<String>, Key), Error> {
+    let bytes = tokio::fs::read(&path)
+        .await
+        .map_err(|error| Error::IOFailed(error.kind()))?;
+
+    // Argon2 is slow on purpose, so keep it off the UI thread
+    let (text, key) = tokio::task::spawn_blocking(move || unseal(&bytes, &passphrase))
+        .await
+        .map_err(|_| Error::IOFailed(io::ErrorKind::Other))??;
+
+    Ok((path, Arc::new(text), key))
+}
+
+#[derive(Debug, Clone)]
+pub struct Prompt {
+    /// The sealed file to unlock, or `None` when encrypting the current buffer
+    pub path: Option<PathBuf>,
+    pub passphrase: String,
+    pub confirmation: String,
+    pub is_working: bool,
+}
+
+impl Prompt {
+    pub fn unlock(path: PathBuf) -> Self {
+        Self {
+            path: Some(path),
+            passphrase: String::new(),
+            confirmation: String::new(),
+            is_working: false,
+        }
+    }
+
+    pub fn encrypt() -> Self {
+        Self {
+            path: None,
+            passphrase: String::new(),
+            confirmation: String::new(),
+            is_working: false,
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let is_unlocking = self.path.is_some();
+
+        let is_ready = !self.passphrase.is_empty()
+            && (is_unlocking || self.passphrase == self.confirmation)
+            && !self.is_working;
+
+        let passphrase = text_input("Passphrase", &self.passphrase)
+            .on_input(Message::PassphraseChanged)
+            .on_submit(Message::SubmitPassphrase)
+            .password()
+            .size(14)
+            .padding(5);
+
+        let title = if is_unlocking {
+            "Unlock file"
+        } else {
+            "Encrypt this file"
+        };
+
+        let mut content = column![text(title).size(16)].spacing(10);
+
+        if let Some(path) = &self.path {
+            content = content.push(text(path.display()).size(13));
+        } else {
+            content = content.push(
+                text("Saves are encrypted from now on. A lost passphrase cannot be recovered.")
+                    .size(14),
+            );
+        }
+
+        content = content.push(passphrase);
+
+        if !is_unlocking {
+            content = content.push(
+                text_input("Repeat passphrase", &self.confirmation)
+                    .on_input(Message::PassphraseConfirmed)
+                    .on_submit(Message::SubmitPassphrase)
+                    .password()
+                    .size(14)
+                    .padding(5),
+            );
+        }
+
+        let label = match (self.is_working, is_unlocking) {
+            (true, _) => "Deriving key...",
+            (false, true) => "Unlock",
+            (false, false) => "Encrypt",
+        };
+
+        container(
+            content.push(
+                row![
+                    button(text(label).size(14))
+                        .on_press_maybe(is_ready.then_some(Message::SubmitPassphrase)),
+                    button(text("Cancel").size(14))
+                        .on_press(Message::ClosePanel)
+                        .style(theme::Button::Secondary),
+                ]
+                .spacing(10),
+            ),
+        )
+        .width(Length::Fill)
+        .padding(10)
+        .style(theme::Container::Box)
+        .into()
+    }
+}
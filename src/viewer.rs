@@ -0,0 +1,177 @@
+use crate::{Error, Message};
+
+use iced::theme;
+use iced::widget::scrollable::{Direction, Properties};
+use iced::widget::{
+    button, column, container, horizontal_space, image, row, scrollable, svg, text,
+};
+use iced::{ContentFit, Element, Length};
+use std::path::{Path, PathBuf};
+
+const RASTER: &[&str] = &["png", "jpg", "jpeg", "gif", "bmp", "webp", "ico"];
+const ZOOM_STEP: f32 = 1.25;
+
+#[derive(Debug, Clone)]
+pub enum Source {
+    Raster(image::Handle),
+    Vector(svg::Handle),
+}
+
+#[derive(Debug, Clone)]
+pub struct Image {
+    pub source: Source,
+    pub dimensions: Option<(u32, u32)>,
+    pub zoom: Option<f32>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Zoom {
+    In,
+    Out,
+    Actual,
+    Fit,
+}
+
+impl Image {
+    pub fn zoom(&mut self, zoom: Zoom) {
+        let current = self.zoom.unwrap_or(1.0);
+
+        self.zoom = match zoom {
+            Zoom::In => Some((current * ZOOM_STEP).min(32.0)),
+            Zoom::Out => Some((current / ZOOM_STEP).max(1.0 / 32.0)),
+            Zoom::Actual => Some(1.0),
+            Zoom::Fit => None,
+        };
+    }
+
+    pub fn status(&self) -> String {
+        let zoom = match self.zoom {
+            Some(zoom) => format!("{:.0}%", zoom * 100.0),
+            None => String::from("Fit"),
+        };
+
+        match self.dimensions {
+            Some((width, height)) => format!("{width} × {height} px  {zoom}"),
+            None => zoom,
+        }
+    }
+}
+
+pub fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            let extension = extension.to_ascii_lowercase();
+
+            extension == "svg" || RASTER.contains(&extension.as_str())
+        })
+}
+
+pub async fn load(path: PathBuf) -> Result<(PathBuf, Image), Error> {
+    let bytes = tokio::fs::read(&path)
+        .await
+        .map_err(|error| Error::IOFailed(error.kind()))?;
+
+    let is_vector = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("svg"));
+
+    let image = if is_vector {
+        Image {
+            dimensions: svg_dimensions(&bytes),
+            source: Source::Vector(svg::Handle::from_memory(bytes)),
+            zoom: None,
+        }
+    } else {
+        Image {
+            dimensions: imagesize::blob_size(&bytes)
+                .ok()
+                .map(|size| (size.width as u32, size.height as u32)),
+            source: Source::Raster(image::Handle::from_memory(bytes)),
+            zoom: None,
+        }
+    };
+
+    Ok((path, image))
+}
+
+fn svg_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    let source = std::str::from_utf8(bytes).ok()?;
+    let start = source.find("<svg")?;
+    let tag = &source[start..start + source[start..].find('>')?];
+
+    let attribute = |name: &str| -> Option<u32> {
+        let value = tag.split(&format!(" {name}=\"")).nth(1)?;
+        let value = &value[..value.find('"')?];
+        let number = value.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+
+        number
+            .parse::<f32>()
+            .ok()
+            .map(|number| number.round() as u32)
+    };
+
+    Some((attribute("width")?, attribute("height")?))
+}
+
+pub fn view(image: &Image) -> Element<'_, Message> {
+    let controls = row![
+        horizontal_space(Length::Fill),
+        zoom_button("-", Zoom::Out),
+        zoom_button("+", Zoom::In),
+        zoom_button("1:1", Zoom::Actual),
+        zoom_button("Fit", Zoom::Fit),
+    ]
+    .spacing(5);
+
+    let size = |length: u32| match image.zoom {
+        Some(zoom) => Length::Fixed(length as f32 * zoom),
+        None => Length::Fill,
+    };
+
+    let (width, height) = match image.dimensions {
+        Some((width, height)) => (size(width), size(height)),
+        None => (Length::Fill, Length::Fill),
+    };
+
+    let picture: Element<_> = match &image.source {
+        Source::Raster(handle) => iced::widget::image(handle.clone())
+            .width(width)
+            .height(height)
+            .content_fit(ContentFit::Contain)
+            .into(),
+        Source::Vector(handle) => iced::widget::svg(handle.clone())
+            .width(width)
+            .height(height)
+            .content_fit(ContentFit::Contain)
+            .into(),
+    };
+
+    let picture: Element<_> = if image.zoom.is_some() {
+        scrollable(picture)
+            .direction(Direction::Both {
+                vertical: Properties::default(),
+                horizontal: Properties::default(),
+            })
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    } else {
+        container(picture)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .into()
+    };
+
+    column![controls, picture].spacing(10).into()
+}
+
+fn zoom_button(label: &str, zoom: Zoom) -> Element<'_, Message> {
+    button(text(label).size(14))
+        .on_press(Message::ZoomImage(zoom))
+        .padding([5, 10])
+        .style(theme::Button::Secondary)
+        .into()
+}
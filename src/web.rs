@@ -0,0 +1,28 @@
+use crate::Error;
+
+pub fn is_url(text: &str) -> bool {
+    let text = text.trim();
+
+    text.starts_with("https://") || text.starts_with("http://")
+}
+
+pub fn extension(url: &str) -> Option<&str> {
+    let path = url.split(['?', '#']).next()?;
+    let file = path.rsplit('/').next()?;
+
+    file.rsplit_once('.').map(|(_, extension)| extension)
+}
+
+pub async fn fetch(url: String) -> Result<(String, String), Error> {
+    let failed = |error: reqwest::Error| Error::RemoteFailed(error.to_string());
+
+    let text = reqwest::get(url.trim())
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(failed)?
+        .text()
+        .await
+        .map_err(failed)?;
+
+    Ok((url, text))
+}
@@ -0,0 +1,69 @@
+use crate::Message;
+
+use iced::theme;
+use iced::widget::{button, container, scrollable, text, vertical_space, Column, Row};
+use iced::{Element, Length};
+
+/// Rows built beyond each edge of the view, so quick scrolling doesn't show gaps.
+const OVERSCAN: usize = 20;
+
+pub fn context_menu<'a>(
+    items: Vec<(&'a str, Option<Message>)>,
+    cancel: Message,
+) -> Element<'a, Message> {
+    let items = items.into_iter().map(|(title, message)| {
+        button(text(title).size(14))
+            .on_press_maybe(message)
+            .padding([4, 8])
+            .style(theme::Button::Text)
+            .into()
+    });
+
+    Row::with_children(items.collect())
+        .push(
+            button(text("Cancel").size(14))
+                .on_press(cancel)
+                .padding([4, 8])
+                .style(theme::Button::Secondary),
+        )
+        .spacing(5)
+        .width(Length::Shrink)
+        .into()
+}
+
+/// A scrollable list that only builds the rows in view.
+///
+/// Every row is held to `row_height`, so blank space can stand in for the rows left out.
+pub fn virtual_list<'a>(
+    count: usize,
+    row_height: f32,
+    offset: f32,
+    height: f32,
+    row: impl Fn(usize) -> Element<'a, Message>,
+    on_scroll: impl Fn(scrollable::Viewport) -> Message + 'a,
+) -> Element<'a, Message> {
+    scrollable(virtual_column(count, row_height, offset, height, row))
+        .on_scroll(on_scroll)
+        .height(Length::Fill)
+        .into()
+}
+
+/// The rows of a [`virtual_list`], for content that is scrolled along with something else.
+pub fn virtual_column<'a>(
+    count: usize,
+    row_height: f32,
+    offset: f32,
+    height: f32,
+    row: impl Fn(usize) -> Element<'a, Message>,
+) -> Column<'a, Message> {
+    let first = ((offset / row_height) as usize)
+        .saturating_sub(OVERSCAN)
+        .min(count);
+    let last = (((offset + height) / row_height).ceil() as usize + OVERSCAN).min(count);
+
+    let mut rows = vec![vertical_space(first as f32 * row_height).into()];
+    rows.extend((first..last).map(|index| container(row(index)).height(row_height).into()));
+    rows.push(vertical_space((count - last) as f32 * row_height).into());
+
+    Column::with_children(rows)
+}
@@ -0,0 +1,137 @@
+const PAIRS: &[(char, char)] = &[
+    ('(', ')'),
+    ('[', ']'),
+    ('{', '}'),
+    ('"', '"'),
+    ('\'', '\''),
+    ('`', '`'),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Class {
+    Whitespace,
+    Underscore,
+    Word,
+    Punctuation,
+}
+
+/// The character that closes `open`, when typing it at `column` should insert both.
+pub fn closing(line: &str, column: usize, open: char) -> Option<char> {
+    let &(_, close) = PAIRS.iter().find(|(pair, _)| *pair == open)?;
+
+    let characters: Vec<char> = line.chars().collect();
+    let before = column
+        .checked_sub(1)
+        .and_then(|index| characters.get(index));
+    let after = characters.get(column);
+
+    let is_free = after.map_or(true, |after| {
+        after.is_whitespace() || (after != &open && PAIRS.iter().any(|(_, close)| close == after))
+    });
+
+    // A quote after a letter is more likely an apostrophe or the end of a string
+    let is_apostrophe = open == close && before.is_some_and(|before| before.is_alphanumeric());
+
+    (is_free && !is_apostrophe).then_some(close)
+}
+
+pub fn is_in_pair(line: &str, column: usize) -> bool {
+    let mut characters = line.chars().skip(column.saturating_sub(1));
+
+    match (column > 0, characters.next(), characters.next()) {
+        (true, Some(open), Some(close)) => PAIRS.contains(&(open, close)),
+        _ => false,
+    }
+}
+
+pub fn previous(line: &str, column: usize, subwords: bool) -> usize {
+    let characters: Vec<char> = line.chars().collect();
+    let mut index = column.min(characters.len());
+
+    while index > 0 && characters[index - 1].is_whitespace() {
+        index -= 1;
+    }
+
+    if index > 0 {
+        index -= 1;
+
+        while index > 0 && !is_boundary(&characters, index, subwords) {
+            index -= 1;
+        }
+    }
+
+    index
+}
+
+pub fn next(line: &str, column: usize, subwords: bool) -> usize {
+    let characters: Vec<char> = line.chars().collect();
+    let mut index = column.min(characters.len());
+
+    while index < characters.len() && characters[index].is_whitespace() {
+        index += 1;
+    }
+
+    if index < characters.len() {
+        index += 1;
+
+        while index < characters.len() && !is_boundary(&characters, index, subwords) {
+            index += 1;
+        }
+    }
+
+    index
+}
+
+pub fn around(line: &str, column: usize) -> std::ops::Range<usize> {
+    let characters: Vec<char> = line.chars().collect();
+    let column = column.min(characters.len());
+
+    let mut start = column;
+    while start > 0 && !is_boundary(&characters, start, true) {
+        start -= 1;
+    }
+
+    let mut end = column;
+    while end < characters.len() && (end == start || !is_boundary(&characters, end, true)) {
+        end += 1;
+    }
+
+    let offset = |index: usize| characters[..index].iter().map(|c| c.len_utf8()).sum();
+
+    offset(start)..offset(end)
+}
+
+fn class(c: char, subwords: bool) -> Class {
+    if c.is_whitespace() {
+        Class::Whitespace
+    } else if c == '_' && subwords {
+        Class::Underscore
+    } else if c.is_alphanumeric() || c == '_' {
+        Class::Word
+    } else {
+        Class::Punctuation
+    }
+}
+
+fn is_boundary(characters: &[char], index: usize, subwords: bool) -> bool {
+    let before = characters[index - 1];
+    let current = characters[index];
+
+    if class(before, subwords) != class(current, subwords) {
+        return true;
+    }
+
+    if !subwords || class(current, subwords) != Class::Word {
+        return false;
+    }
+
+    let is_hump = (before.is_lowercase() || before.is_ascii_digit()) && current.is_uppercase();
+
+    let is_acronym_end = before.is_uppercase()
+        && current.is_uppercase()
+        && characters
+            .get(index + 1)
+            .is_some_and(|next| next.is_lowercase());
+
+    is_hump || is_acronym_end
+}
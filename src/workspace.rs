@@ -0,0 +1,171 @@
+use crate::{debug, layout, Error};
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+
+const DIRECTORY: &str = ".bonkitor";
+const FILE: &str = "workspace.toml";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub exclude: Vec<String>,
+    pub formatters: BTreeMap<String, String>,
+    pub tasks: BTreeMap<String, String>,
+    /// Preset names (`rustc`, `gcc`, `tsc`) or regexes used to find problems in task output
+    pub problem_matchers: Vec<String>,
+    /// Debug adapters to start, by the name the palette shows
+    pub debug: BTreeMap<String, debug::Configuration>,
+    /// Interpreter commands by name, like `python = "python3 -i -q -u"`
+    pub repls: BTreeMap<String, String>,
+    pub open_files: Vec<PathBuf>,
+    pub layout: layout::Sizes,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            exclude: vec![
+                String::from("target"),
+                String::from("node_modules"),
+                String::from(".git"),
+            ],
+            formatters: BTreeMap::new(),
+            tasks: BTreeMap::new(),
+            problem_matchers: Vec::new(),
+            debug: BTreeMap::new(),
+            repls: BTreeMap::new(),
+            open_files: Vec::new(),
+            layout: layout::Sizes::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    pub root: PathBuf,
+    pub settings: Settings,
+}
+
+impl Workspace {
+    pub fn name(&self) -> String {
+        name(&self.root)
+    }
+
+    pub fn formatter(&self, path: &Path) -> Option<&str> {
+        let extension = path.extension()?.to_str()?;
+
+        self.settings.formatters.get(extension).map(String::as_str)
+    }
+
+    pub fn set_open_files(&mut self, files: impl IntoIterator<Item = PathBuf>) {
+        self.settings.open_files = files
+            .into_iter()
+            .map(|path| match path.strip_prefix(&self.root) {
+                Ok(relative) => relative.to_path_buf(),
+                Err(_) => path,
+            })
+            .collect();
+    }
+
+    pub fn open_files(&self) -> impl Iterator<Item = PathBuf> + '_ {
+        self.settings
+            .open_files
+            .iter()
+            .map(|path| self.root.join(path))
+    }
+
+    pub async fn open(root: PathBuf) -> Result<Self, Error> {
+        let path = root.join(DIRECTORY).join(FILE);
+
+        let settings = match tokio::fs::read_to_string(&path).await {
+            Ok(text) => {
+                toml::from_str(&text).map_err(|error| Error::InvalidConfig(error.to_string()))?
+            }
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Settings::default(),
+            Err(error) => return Err(Error::IOFailed(error.kind())),
+        };
+
+        let workspace = Self { root, settings };
+        workspace.clone().save().await?;
+
+        Ok(workspace)
+    }
+
+    pub async fn save(self) -> Result<(), Error> {
+        let directory = self.root.join(DIRECTORY);
+        let text = toml::to_string_pretty(&self.settings)
+            .map_err(|error| Error::InvalidConfig(error.to_string()))?;
+
+        tokio::fs::create_dir_all(&directory)
+            .await
+            .map_err(|error| Error::IOFailed(error.kind()))?;
+
+        tokio::fs::write(directory.join(FILE), text)
+            .await
+            .map_err(|error| Error::IOFailed(error.kind()))
+    }
+}
+
+/// A file kept alongside the workspace's settings.
+pub fn file(root: &Path, name: &str) -> PathBuf {
+    root.join(DIRECTORY).join(name)
+}
+
+pub fn name(root: &Path) -> String {
+    root.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| root.display().to_string())
+}
+
+pub async fn pick() -> Result<PathBuf, Error> {
+    let handle = rfd::AsyncFileDialog::new()
+        .set_title("Choose a folder to open...")
+        .pick_folder()
+        .await
+        .ok_or(Error::DialogClosed)?;
+
+    Ok(handle.path().to_owned())
+}
+
+pub async fn format(command: String, root: PathBuf, text: String) -> Result<String, Error> {
+    let io = |error: io::Error| Error::IOFailed(error.kind());
+
+    let mut child = shell(&command)
+        .current_dir(root)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(io)?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes()).await.map_err(io)?;
+    }
+
+    let output = child.wait_with_output().await.map_err(io)?;
+
+    if output.status.success() {
+        String::from_utf8(output.stdout).map_err(|_| Error::IOFailed(io::ErrorKind::InvalidData))
+    } else {
+        Err(Error::RemoteFailed(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+pub fn shell(command: &str) -> tokio::process::Command {
+    if cfg!(windows) {
+        let mut shell = tokio::process::Command::new("cmd");
+        shell.args(["/C", command]);
+        shell
+    } else {
+        let mut shell = tokio::process::Command::new("sh");
+        shell.args(["-c", command]);
+        shell
+    }
+}